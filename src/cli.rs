@@ -1,3 +1,4 @@
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
 use clap::{Args, Parser, Subcommand, ValueEnum};
@@ -13,7 +14,11 @@ pub struct Cli {
 #[derive(Debug, Subcommand)]
 pub enum Command {
     #[command(about = "Index a repository into the local SQLite database")]
-    Index(RepoArgs),
+    Index(IndexArgs),
+    #[command(about = "Keep the index up to date as files change")]
+    Watch(WatchArgs),
+    #[command(about = "Answer JSON-RPC or MCP queries over stdio or a Unix socket")]
+    Serve(ServeArgs),
     #[command(about = "Show index status and health")]
     Status(StatusArgs),
     #[command(about = "List JSON schemas exposed by repo-scout commands")]
@@ -57,6 +62,8 @@ pub enum Command {
     Deps(DepsArgs),
     #[command(about = "Show most-connected symbols (hotspots)")]
     Hotspots(HotspotsArgs),
+    #[command(about = "List the top authors of a file or symbol from git history")]
+    Owners(OwnersArgs),
     #[command(name = "call-path", about = "Find call path between two symbols")]
     CallPath(CallPathArgs),
     #[command(about = "Show structurally related symbols")]
@@ -93,6 +100,8 @@ pub enum Command {
     MoveCheck(MoveCheckArgs),
     #[command(name = "rename-check", about = "Preview impacts of renaming a symbol")]
     RenameCheck(RenameCheckArgs),
+    #[command(about = "Rename a symbol and its bound references as a reviewable patch")]
+    Rename(RenameArgs),
     #[command(
         name = "split-check",
         about = "Pre-flight analysis for splitting a file"
@@ -131,10 +140,31 @@ pub struct HealthArgs {
     pub save_baseline: bool,
     #[arg(long, default_value_t = false)]
     pub diff: bool,
+    #[command(flatten)]
+    pub ranking: FunctionRankingArgs,
     #[arg(long)]
     pub json: bool,
 }
 
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ComplexitySort {
+    Complexity,
+    Nesting,
+    Params,
+}
+
+#[derive(Debug, Args, Default)]
+pub struct FunctionRankingArgs {
+    #[arg(long, value_enum)]
+    pub sort: Option<ComplexitySort>,
+    #[arg(long)]
+    pub min_complexity: Option<u32>,
+    #[arg(long)]
+    pub min_nesting: Option<u32>,
+    #[arg(long)]
+    pub min_params: Option<u32>,
+}
+
 #[derive(Debug, Args)]
 pub struct TreeArgs {
     #[arg(long)]
@@ -179,6 +209,34 @@ pub struct RepoArgs {
     pub repo: PathBuf,
 }
 
+#[derive(Debug, Args)]
+pub struct IndexArgs {
+    #[arg(long)]
+    pub repo: PathBuf,
+    #[arg(long)]
+    pub jobs: Option<NonZeroUsize>,
+}
+
+#[derive(Debug, Args)]
+pub struct WatchArgs {
+    #[arg(long)]
+    pub repo: PathBuf,
+    #[arg(long)]
+    pub jobs: Option<NonZeroUsize>,
+    #[arg(long = "debounce-ms", default_value_t = 200)]
+    pub debounce_ms: u64,
+}
+
+#[derive(Debug, Args)]
+pub struct ServeArgs {
+    #[arg(long)]
+    pub repo: PathBuf,
+    #[arg(long)]
+    pub socket: Option<PathBuf>,
+    #[arg(long, default_value_t = false, conflicts_with = "socket")]
+    pub mcp: bool,
+}
+
 #[derive(Debug, Args)]
 pub struct StatusArgs {
     #[arg(long)]
@@ -245,7 +303,10 @@ pub struct SymbolFilterArgs {
 
 #[derive(Debug, Args)]
 pub struct FindArgs {
-    pub symbol: String,
+    #[arg(required_unless_present = "doc")]
+    pub symbol: Option<String>,
+    #[arg(long, conflicts_with = "symbol")]
+    pub doc: Option<String>,
     #[arg(long)]
     pub repo: PathBuf,
     #[arg(long)]
@@ -345,6 +406,8 @@ pub struct ContextArgs {
     pub code_only: bool,
     #[arg(long, default_value_t = false)]
     pub exclude_tests: bool,
+    #[arg(long, default_value_t = false)]
+    pub pack: bool,
 }
 
 #[derive(Debug, Args)]
@@ -356,6 +419,10 @@ pub struct TestsForArgs {
     pub json: bool,
     #[arg(long, default_value_t = false)]
     pub include_support: bool,
+    #[arg(long, default_value_t = false, conflicts_with = "include_support")]
+    pub via_calls: bool,
+    #[arg(long, default_value_t = 3, requires = "via_calls")]
+    pub max_depth: u32,
 }
 
 #[derive(Debug, Args)]
@@ -462,6 +529,19 @@ pub struct HotspotsArgs {
     pub json: bool,
     #[arg(long, default_value_t = 10)]
     pub limit: u32,
+    #[arg(long, default_value_t = false)]
+    pub churn: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct OwnersArgs {
+    pub target: String,
+    #[arg(long)]
+    pub repo: PathBuf,
+    #[arg(long)]
+    pub json: bool,
+    #[arg(long, default_value_t = 5)]
+    pub limit: u32,
 }
 
 #[derive(Debug, Args)]
@@ -474,6 +554,10 @@ pub struct CallPathArgs {
     pub json: bool,
     #[arg(long, default_value_t = 10)]
     pub max_depth: u32,
+    #[arg(long, default_value_t = 3)]
+    pub max_paths: usize,
+    #[arg(long, default_value_t = false)]
+    pub include_structural: bool,
 }
 
 #[derive(Debug, Args)]
@@ -503,6 +587,12 @@ pub struct CouplingArgs {
     pub include_tests: bool,
     #[arg(long, default_value_t = false)]
     pub include_fixtures: bool,
+    #[arg(long, default_value_t = false)]
+    pub temporal: bool,
+    #[arg(long, default_value_t = 2, requires = "temporal")]
+    pub min_shared: u32,
+    #[arg(long, default_value_t = 30, requires = "temporal")]
+    pub max_changeset: u32,
 }
 
 #[derive(Debug, Args)]
@@ -548,6 +638,8 @@ pub struct SuggestArgs {
     pub safe_only: bool,
     #[arg(long = "min-score")]
     pub min_score: Option<f64>,
+    #[command(flatten)]
+    pub ranking: FunctionRankingArgs,
 }
 
 #[derive(Debug, Args)]
@@ -598,6 +690,23 @@ pub struct RenameCheckArgs {
     pub include_fixtures: bool,
 }
 
+#[derive(Debug, Args)]
+pub struct RenameArgs {
+    pub symbol: String,
+    #[arg(long)]
+    pub to: String,
+    #[arg(long)]
+    pub repo: PathBuf,
+    #[arg(long)]
+    pub json: bool,
+    #[arg(long, default_value_t = false, conflicts_with = "patch")]
+    pub apply: bool,
+    #[arg(long, default_value_t = false)]
+    pub patch: bool,
+    #[arg(long, default_value_t = false)]
+    pub include_text: bool,
+}
+
 #[derive(Debug, Args)]
 pub struct SplitCheckArgs {
     pub file: String,
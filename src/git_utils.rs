@@ -1,5 +1,6 @@
+use std::io::{BufRead, BufReader, Write};
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Stdio};
 
 pub fn changed_files_since(repo: &Path, since: &str) -> anyhow::Result<Vec<String>> {
     if since.starts_with('-') {
@@ -58,3 +59,231 @@ pub fn head_sha(repo: &Path) -> anyhow::Result<String> {
     }
     Ok(String::from_utf8(output.stdout)?.trim().to_string())
 }
+
+/// Streams `git log --raw --numstat -z` for up to `max_commits` non-merge commits in
+/// `revisions` (a revision or `<since>..<until>` range), newest first, calling `on_record` for
+/// each NUL-terminated record. Each commit starts with a
+/// `\x1e<sha>\x1f<author time>\x1f<author>\x1f<email>` header record. Paths are relative to
+/// `repo`, and renames are detected.
+pub fn for_each_commit_change(
+    repo: &Path,
+    revisions: &str,
+    max_commits: usize,
+    on_record: impl FnMut(&str) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    if revisions.starts_with('-') {
+        anyhow::bail!("invalid revision '{revisions}' (looks like a flag)");
+    }
+    let mut command = Command::new("git");
+    command
+        .args([
+            "log",
+            "--no-merges",
+            "--topo-order",
+            "-M",
+            "--relative",
+            "--raw",
+            "--numstat",
+            "-z",
+            "--no-color",
+            "--format=%x1e%H%x1f%at%x1f%aN%x1f%aE",
+        ])
+        .arg(format!("--max-count={max_commits}"))
+        .arg(revisions);
+    stream_git_log(repo, command, None, b'\0', on_record)
+}
+
+/// Streams the `git log -p --unified=0` patches of `commits`, limited to `paths`, calling
+/// `on_line` for each output line. Each commit starts with a `\x1e<sha>` header line. Paths
+/// are relative to `repo`, and renames among `paths` are detected.
+pub fn for_each_commit_patch_line<'a>(
+    repo: &Path,
+    commits: &[String],
+    paths: impl IntoIterator<Item = &'a String>,
+    on_line: impl FnMut(&str) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    // Revisions and pathspecs go through stdin, so neither is limited by the argument length.
+    let mut input = String::new();
+    for commit in commits {
+        input.push_str(commit);
+        input.push('\n');
+    }
+    input.push_str("--\n");
+    for path in paths.into_iter().filter(|path| !path.contains('\n')) {
+        input.push_str(path);
+        input.push('\n');
+    }
+    let mut command = Command::new("git");
+    command.args([
+        "--literal-pathspecs",
+        "-c",
+        "core.quotePath=false",
+        "log",
+        "--no-walk=unsorted",
+        "--stdin",
+        "-M",
+        "--relative",
+        "-p",
+        "--unified=0",
+        "--no-color",
+        "--no-ext-diff",
+        "--no-textconv",
+        "--src-prefix=a/",
+        "--dst-prefix=b/",
+        "--format=%x1e%H",
+    ]);
+    stream_git_log(repo, command, Some(input), b'\n', on_line)
+}
+
+/// Runs a `git log` command in `repo`, feeding it `input` on stdin, and calls `on_record` with
+/// each `delimiter`-terminated output record.
+fn stream_git_log(
+    repo: &Path,
+    mut command: Command,
+    input: Option<String>,
+    delimiter: u8,
+    mut on_record: impl FnMut(&str) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let mut child = command
+        .current_dir(repo)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let writer = match (input, child.stdin.take()) {
+        (Some(input), Some(mut stdin)) => Some(std::thread::spawn(move || {
+            // A write error means git exited early; its status is reported below.
+            let _ = stdin.write_all(input.as_bytes());
+        })),
+        _ => None,
+    };
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow::anyhow!("git log stdout was not captured"))?;
+    let mut reader = BufReader::new(stdout);
+    let mut buffer = Vec::new();
+    let mut result = Ok(());
+    loop {
+        buffer.clear();
+        match reader.read_until(delimiter, &mut buffer) {
+            Ok(0) => break,
+            Ok(_) => {
+                if buffer.last() == Some(&delimiter) {
+                    buffer.pop();
+                }
+                let record = String::from_utf8_lossy(&buffer);
+                if let Err(error) = on_record(record.trim_end_matches('\r')) {
+                    result = Err(error);
+                    break;
+                }
+            }
+            Err(error) => {
+                result = Err(error.into());
+                break;
+            }
+        }
+    }
+    if result.is_err() {
+        let _ = child.kill();
+    }
+    let output = child.wait_with_output()?;
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+    result?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git log failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(())
+}
+
+/// Whether `ancestor` is reachable from `descendant`.
+pub fn is_ancestor(repo: &Path, ancestor: &str, descendant: &str) -> anyhow::Result<bool> {
+    if ancestor.starts_with('-') || descendant.starts_with('-') {
+        anyhow::bail!("invalid revision (looks like a flag)");
+    }
+    let output = Command::new("git")
+        .args(["merge-base", "--is-ancestor", ancestor, descendant])
+        .current_dir(repo)
+        .output()?;
+    match output.status.code() {
+        Some(0) => Ok(true),
+        Some(1) => Ok(false),
+        _ => anyhow::bail!(
+            "git merge-base failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ),
+    }
+}
+
+/// Resolves `revision` to a full commit SHA.
+pub fn resolve_commit(repo: &Path, revision: &str) -> anyhow::Result<String> {
+    if revision.starts_with('-') {
+        anyhow::bail!("invalid revision '{revision}' (looks like a flag)");
+    }
+    let output = Command::new("git")
+        .args(["rev-parse", "--verify", "--quiet"])
+        .arg(format!("{revision}^{{commit}}"))
+        .current_dir(repo)
+        .output()?;
+    if !output.status.success() {
+        anyhow::bail!("unknown revision '{revision}'");
+    }
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+/// The path of `repo` relative to the top of its git work tree, with a trailing `/` unless empty.
+pub fn path_prefix(repo: &Path) -> anyhow::Result<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--show-prefix"])
+        .current_dir(repo)
+        .output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git rev-parse failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+/// Checks out `commit` into a new detached worktree at `path`.
+pub fn add_worktree(repo: &Path, path: &Path, commit: &str) -> anyhow::Result<()> {
+    let output = Command::new("git")
+        .args(["worktree", "add", "--detach", "--force", "--quiet"])
+        .arg(path)
+        .arg(commit)
+        .current_dir(repo)
+        .output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git worktree add failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(())
+}
+
+/// Removes a worktree created by [`add_worktree`], including its files.
+pub fn remove_worktree(repo: &Path, path: &Path) -> anyhow::Result<()> {
+    let output = Command::new("git")
+        .args(["worktree", "remove", "--force"])
+        .arg(path)
+        .current_dir(repo)
+        .output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git worktree remove failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(())
+}
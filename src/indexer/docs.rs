@@ -0,0 +1,212 @@
+use crate::indexer::languages::ExtractedSymbol;
+
+/// Returns the documentation attached to `symbol`, following its language's convention:
+/// `///` lines or a `/** */` block above Rust items, a `/** */` JSDoc block above TypeScript
+/// and JavaScript declarations, the `//` lines directly above Go declarations, and the
+/// docstring opening a Python function or class body. Comment markers are stripped.
+pub fn doc_comment(symbol: &ExtractedSymbol, lines: &[&str]) -> Option<String> {
+    let start = (symbol.start_line as usize)
+        .saturating_sub(1)
+        .min(lines.len());
+    let end = (symbol.end_line as usize).clamp(start, lines.len());
+    let above = &lines[..start];
+    let doc = match symbol.language.as_str() {
+        "python" => docstring(&lines[start..end]),
+        "go" => line_doc_above(above, "//"),
+        "rust" => {
+            let outer = line_doc_above(above, "///");
+            if outer.is_empty() {
+                block_doc_above(above)
+            } else {
+                outer
+            }
+        }
+        _ => block_doc_above(above),
+    };
+    let first = doc.iter().position(|line| !line.is_empty())?;
+    let last = doc.iter().rposition(|line| !line.is_empty())?;
+    Some(doc[first..=last].join("\n"))
+}
+
+/// Comment lines directly above a definition, skipping attributes and decorators between them.
+pub fn leading_comment<'a>(above: &[&'a str]) -> Vec<&'a str> {
+    let mut comment = Vec::new();
+    for line in above.iter().rev().map(|line| line.trim()) {
+        if is_attribute(line) {
+            continue;
+        }
+        let Some(text) = comment_text(line) else {
+            break;
+        };
+        comment.push(text);
+    }
+    comment.reverse();
+    comment
+}
+
+/// A Python docstring: the triple-quoted string opening the body after its header line.
+pub fn docstring<'a>(body: &[&'a str]) -> Vec<&'a str> {
+    let Some(header) = body.iter().position(|line| {
+        line.split('#')
+            .next()
+            .unwrap_or("")
+            .trim_end()
+            .ends_with(':')
+    }) else {
+        return Vec::new();
+    };
+    let mut lines = body[header + 1..].iter().map(|line| line.trim());
+    let Some(first) = lines.next() else {
+        return Vec::new();
+    };
+    let Some(quote) = ["\"\"\"", "'''"]
+        .into_iter()
+        .find(|quote| first.starts_with(quote))
+    else {
+        return Vec::new();
+    };
+    let opening = &first[quote.len()..];
+    if let Some(closed) = opening.strip_suffix(quote) {
+        return vec![closed];
+    }
+    let mut docstring = vec![opening];
+    for line in lines {
+        if let Some(index) = line.find(quote) {
+            docstring.push(&line[..index]);
+            break;
+        }
+        docstring.push(line);
+    }
+    docstring
+}
+
+fn is_attribute(line: &str) -> bool {
+    line.starts_with("#[") || line.starts_with('@')
+}
+
+fn comment_text(line: &str) -> Option<&str> {
+    ["///", "//!", "//", "/**", "/*", "*/", "*", "#"]
+        .into_iter()
+        .find_map(|marker| line.strip_prefix(marker))
+        .map(|text| text.trim_end_matches("*/").trim())
+}
+
+/// Consecutive `marker` comment lines directly above a definition. Go tool directives such as
+/// `//go:generate` and Rust `////` separator comments are not documentation.
+fn line_doc_above<'a>(above: &[&'a str], marker: &str) -> Vec<&'a str> {
+    let mut doc = Vec::new();
+    for line in above.iter().rev().map(|line| line.trim()) {
+        if is_attribute(line) {
+            continue;
+        }
+        let Some(text) = line.strip_prefix(marker) else {
+            break;
+        };
+        if text.starts_with('/') || text.starts_with("go:") {
+            break;
+        }
+        doc.push(text.trim());
+    }
+    doc.reverse();
+    doc
+}
+
+/// A `/** ... */` block ending directly above a definition, with `*` gutters stripped.
+fn block_doc_above<'a>(above: &[&'a str]) -> Vec<&'a str> {
+    let mut lines = above
+        .iter()
+        .rev()
+        .map(|line| line.trim())
+        .skip_while(|line| is_attribute(line));
+    let Some(last) = lines.next().filter(|line| line.ends_with("*/")) else {
+        return Vec::new();
+    };
+    let mut block = vec![last];
+    if !last.starts_with("/*") {
+        for line in lines {
+            block.push(line);
+            if line.starts_with("/*") {
+                break;
+            }
+        }
+    }
+    block.reverse();
+    if !block[0].starts_with("/**") || block[0].starts_with("/***") {
+        return Vec::new();
+    }
+    block
+        .into_iter()
+        .map(|line| {
+            let line = line.strip_prefix("/**").unwrap_or(line);
+            let line = line.strip_suffix("*/").unwrap_or(line).trim();
+            line.strip_prefix('*').unwrap_or(line).trim()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::doc_comment;
+    use crate::indexer::languages::ExtractedSymbol;
+
+    fn doc(language: &str, source: &str, lines: (u32, u32)) -> Option<String> {
+        let symbol = ExtractedSymbol {
+            symbol: "item".to_string(),
+            qualified_symbol: None,
+            kind: "function".to_string(),
+            language: language.to_string(),
+            container: None,
+            start_line: lines.0,
+            start_column: 1,
+            end_line: lines.1,
+            end_column: 1,
+            signature: None,
+            test_path: None,
+            metrics: None,
+            visibility: None,
+            doc_comment: None,
+        };
+        doc_comment(&symbol, &source.lines().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn doc_comment_reads_rust_line_and_block_docs() {
+        let source = "// not docs\n/// Parses input.\n///\n/// Returns the count.\n#[inline]\nfn item() {}\n";
+        assert_eq!(
+            doc("rust", source, (6, 6)).as_deref(),
+            Some("Parses input.\n\nReturns the count.")
+        );
+        assert_eq!(doc("rust", "// plain\nfn item() {}\n", (2, 2)), None);
+        assert_eq!(
+            doc("rust", "/** Block docs. */\nfn item() {}\n", (2, 2)).as_deref(),
+            Some("Block docs.")
+        );
+    }
+
+    #[test]
+    fn doc_comment_reads_jsdoc_go_and_python_docs() {
+        let jsdoc = "/**\n * Uploads a part.\n * @param part bytes\n */\n@traced\nexport function item() {}\n";
+        assert_eq!(
+            doc("typescript", jsdoc, (6, 6)).as_deref(),
+            Some("Uploads a part.\n@param part bytes")
+        );
+        assert_eq!(
+            doc(
+                "javascript",
+                "/* not jsdoc */\nfunction item() {}\n",
+                (2, 2)
+            ),
+            None
+        );
+        let go = "//go:generate stringer\n// Item returns the item.\n// It never fails.\nfunc Item() {}\n";
+        assert_eq!(
+            doc("go", go, (4, 4)).as_deref(),
+            Some("Item returns the item.\nIt never fails.")
+        );
+        let python = "@cached\ndef item(\n    value,\n):\n    \"\"\"Return the item.\n\n    Cached per value.\n    \"\"\"\n    return value\n";
+        assert_eq!(
+            doc("python", python, (1, 9)).as_deref(),
+            Some("Return the item.\n\nCached per value.")
+        );
+    }
+}
@@ -1,5 +1,6 @@
-use std::fs;
-use std::path::Path;
+use std::fs::{self, FileType};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::Context;
 use ignore::WalkBuilder;
@@ -9,14 +10,127 @@ pub struct SourceFile {
     pub relative_path: String,
     pub bytes: Vec<u8>,
     pub content_hash: String,
+    pub metadata: FileMetadata,
 }
 
-pub fn discover_source_files(repo: &Path) -> anyhow::Result<Vec<SourceFile>> {
-    let mut files = Vec::new();
-    let walker = WalkBuilder::new(repo).standard_filters(true).build();
+/// Size and modification time recorded for an indexed file. A file whose metadata matches the
+/// stored row is not read again.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileMetadata {
+    pub size: i64,
+    /// Nanoseconds since the Unix epoch, or `None` when the timestamp is unavailable or too
+    /// recent to trust (a write within the same clock tick would leave it unchanged).
+    pub modified_ns: Option<i64>,
+}
+
+/// A discovered file that has not been read yet.
+#[derive(Debug)]
+pub struct SourceEntry {
+    pub path: PathBuf,
+    pub relative_path: String,
+    pub metadata: FileMetadata,
+}
+
+/// Writes closer than this to the indexing run leave `modified_ns` unset so the next run
+/// re-hashes the file instead of trusting a timestamp that a later write could repeat.
+const RACY_MTIME_WINDOW: Duration = Duration::from_secs(2);
+
+/// Walks `repo` and stats every file, without reading contents. Entries are sorted by path.
+pub fn discover_source_entries(repo: &Path) -> anyhow::Result<Vec<SourceEntry>> {
+    let started = SystemTime::now();
+    walk_source_paths(repo)?
+        .into_iter()
+        .map(|(path, relative_path)| {
+            let metadata = read_file_metadata(&path, started)?;
+            Ok(SourceEntry {
+                path,
+                relative_path,
+                metadata,
+            })
+        })
+        .collect()
+}
 
+/// Loads and hashes a single file for indexing. Metadata is read before the contents, so a
+/// write that lands in between shows up as changed metadata on the next run.
+pub fn read_source_file(path: &Path, relative_path: String) -> anyhow::Result<SourceFile> {
+    let metadata = read_file_metadata(path, SystemTime::now())?;
+    let bytes = fs::read(path)
+        .with_context(|| format!("failed to read file for indexing: {}", path.display()))?;
+    let content_hash = blake3::hash(&bytes).to_hex().to_string();
+    Ok(SourceFile {
+        relative_path,
+        bytes,
+        content_hash,
+        metadata,
+    })
+}
+
+fn read_file_metadata(path: &Path, now: SystemTime) -> anyhow::Result<FileMetadata> {
+    let metadata = fs::metadata(path)
+        .with_context(|| format!("failed to read file metadata: {}", path.display()))?;
+    let modified_ns = metadata
+        .modified()
+        .ok()
+        .filter(|modified| {
+            now.duration_since(*modified)
+                .is_ok_and(|age| age >= RACY_MTIME_WINDOW)
+        })
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .and_then(|since_epoch| i64::try_from(since_epoch.as_nanos()).ok());
+    Ok(FileMetadata {
+        size: i64::try_from(metadata.len()).unwrap_or(i64::MAX),
+        modified_ns,
+    })
+}
+
+/// Lists the files under the repository-relative `directory` that a full walk would visit, as
+/// sorted repository-relative paths.
+pub fn discover_source_paths_under(repo: &Path, directory: &str) -> anyhow::Result<Vec<String>> {
+    Ok(walk_paths(&repo.join(directory), repo)?
+        .into_iter()
+        .map(|(_, relative)| relative)
+        .collect())
+}
+
+/// Lists the entries directly inside the repository-relative `directory` (`""` for the root)
+/// that a full walk would visit, with their file types. Ignore files in `directory`'s ancestors
+/// apply just as they do during a full walk. A missing directory lists as empty.
+pub fn list_source_directory(
+    repo: &Path,
+    directory: &str,
+) -> anyhow::Result<Vec<(String, FileType)>> {
+    let root = repo.join(directory);
+    if !root.is_dir() {
+        return Ok(Vec::new());
+    }
+    let walker = WalkBuilder::new(&root)
+        .standard_filters(true)
+        .max_depth(Some(1))
+        .build();
+    let mut entries = Vec::new();
     for entry in walker {
-        let entry = entry.with_context(|| format!("failed to walk {}", repo.display()))?;
+        let entry = entry.with_context(|| format!("failed to walk {}", root.display()))?;
+        if entry.depth() == 0 {
+            continue;
+        }
+        if let Some(file_type) = entry.file_type() {
+            entries.push((entry.file_name().to_string_lossy().into_owned(), file_type));
+        }
+    }
+    Ok(entries)
+}
+
+fn walk_source_paths(repo: &Path) -> anyhow::Result<Vec<(PathBuf, String)>> {
+    walk_paths(repo, repo)
+}
+
+fn walk_paths(root: &Path, repo: &Path) -> anyhow::Result<Vec<(PathBuf, String)>> {
+    let mut paths = Vec::new();
+    let walker = WalkBuilder::new(root).standard_filters(true).build();
+
+    for entry in walker {
+        let entry = entry.with_context(|| format!("failed to walk {}", root.display()))?;
         if !entry.file_type().is_some_and(|ft| ft.is_file()) {
             continue;
         }
@@ -27,24 +141,16 @@ pub fn discover_source_files(repo: &Path) -> anyhow::Result<Vec<SourceFile>> {
             .unwrap_or(path)
             .to_string_lossy()
             .replace('\\', "/");
-        let bytes = fs::read(path)
-            .with_context(|| format!("failed to read file for indexing: {}", path.display()))?;
-        let content_hash = blake3::hash(&bytes).to_hex().to_string();
-
-        files.push(SourceFile {
-            relative_path: relative,
-            bytes,
-            content_hash,
-        });
+        paths.push((path.to_path_buf(), relative));
     }
 
-    files.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
-    Ok(files)
+    paths.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(paths)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::discover_source_files;
+    use super::{discover_source_entries, read_source_file};
     use std::fs;
 
     #[cfg(unix)]
@@ -62,22 +168,23 @@ mod tests {
     }
 
     #[test]
-    fn discover_source_files_reports_walk_errors() {
+    fn discover_source_entries_reports_walk_errors() {
         let repo = tempfile::tempdir().expect("temp dir should be created");
         let missing = repo.path().join("missing");
-        let error = discover_source_files(&missing).expect_err("missing repo should fail walking");
+        let error =
+            discover_source_entries(&missing).expect_err("missing repo should fail walking");
         assert!(error.to_string().contains("failed to walk"));
     }
 
     #[test]
     #[cfg(unix)]
-    fn discover_source_files_reports_read_errors() {
+    fn read_source_file_reports_read_errors() {
         let repo = tempfile::tempdir().expect("temp dir should be created");
         let unreadable = repo.path().join("secret.rs");
         fs::write(&unreadable, "fn hidden() {}\n").expect("fixture file should be written");
 
         with_unreadable_file(&unreadable, || {
-            let error = discover_source_files(repo.path())
+            let error = read_source_file(&unreadable, "secret.rs".to_string())
                 .expect_err("unreadable file should fail content loading");
             assert!(
                 error
@@ -86,4 +193,16 @@ mod tests {
             );
         });
     }
+
+    #[test]
+    fn discover_source_entries_leaves_recent_modification_times_unset() {
+        let repo = tempfile::tempdir().expect("temp dir should be created");
+        fs::write(repo.path().join("lib.rs"), "fn fresh() {}\n").expect("fixture should write");
+
+        let entries = discover_source_entries(repo.path()).expect("discovery should succeed");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].relative_path, "lib.rs");
+        assert_eq!(entries[0].metadata.size, 14);
+        assert_eq!(entries[0].metadata.modified_ns, None);
+    }
 }
@@ -0,0 +1,838 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+use rusqlite::{Connection, OptionalExtension, params};
+
+use crate::git_utils;
+
+/// Most recent non-merge commits kept in the history tables.
+pub const HISTORY_COMMIT_LIMIT: usize = 5000;
+
+/// A commit that touched at least one indexed file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct HistoryCommit {
+    sha: String,
+    authored_at: i64,
+    author: String,
+    email: String,
+}
+
+/// One `@@ -old_start,old_len +new_start,new_len @@` hunk of a zero-context patch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Hunk {
+    old_start: u32,
+    old_len: u32,
+    new_start: u32,
+    new_len: u32,
+}
+
+/// One file a commit changed. Paths and line counts come from `git log --raw --numstat`; hunks
+/// are read only for files whose line history is recorded.
+#[derive(Debug, Default)]
+struct FileDiff {
+    old_path: Option<String>,
+    new_path: Option<String>,
+    added: u32,
+    deleted: u32,
+    hunks: Vec<Hunk>,
+}
+
+/// A commit and the files it changed, as listed by `git log --raw --numstat`.
+#[derive(Debug)]
+struct LoggedCommit {
+    commit: HistoryCommit,
+    diffs: Vec<FileDiff>,
+}
+
+/// Lines `start..=end` of a file at some commit and the HEAD lines they became. Unchanged lines
+/// map one to one from `head_start`; lines later rewritten map as a block onto
+/// `head_start..=head_end`, the HEAD lines that replaced them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Span {
+    start: u32,
+    end: u32,
+    head_start: u32,
+    head_end: Option<u32>,
+}
+
+impl Span {
+    fn identity() -> Self {
+        Self {
+            start: 1,
+            end: u32::MAX,
+            head_start: 1,
+            head_end: None,
+        }
+    }
+
+    /// The HEAD lines of `low..=high`, which must lie within the span.
+    fn head_range(&self, low: u32, high: u32) -> (u32, u32) {
+        match self.head_end {
+            Some(head_end) => (self.head_start, head_end),
+            None => (
+                self.head_start + (low - self.start),
+                self.head_start + (high - self.start),
+            ),
+        }
+    }
+}
+
+/// An indexed HEAD file followed back through history, possibly under an older name.
+struct TrackedFile {
+    head_path: String,
+    spans: Vec<Span>,
+}
+
+/// Commits read in this refresh, newest first, with their rows keyed by position in `commits`.
+#[derive(Default)]
+struct HistoryTables {
+    commits: Vec<HistoryCommit>,
+    file_changes: Vec<(usize, String, u32, u32)>,
+    line_changes: Vec<(usize, String, u32, u32)>,
+    carried: CarriedHistory,
+}
+
+/// Rows from an earlier refresh, moved onto the current HEAD's paths and lines and keyed by
+/// their stored commit ids.
+#[derive(Default)]
+struct CarriedHistory {
+    commits: Vec<(i64, HistoryCommit)>,
+    file_changes: Vec<(i64, String, u32, u32)>,
+    line_changes: Vec<(i64, String, u32, u32)>,
+}
+
+/// Brings the git history tables up to date when HEAD moved since the last refresh.
+///
+/// When the recorded HEAD is an ancestor of the current one, only the commits since it are read
+/// and the stored rows are carried over onto the new HEAD's paths and lines; otherwise (first
+/// run, rewritten history) the newest [`HISTORY_COMMIT_LIMIT`] non-merge commits are read. Each
+/// commit touching an indexed file records its added and deleted line counts from
+/// `git log --numstat`. For files with indexed symbols, the zero-context hunks of those commits
+/// are read as well, and each hunk is mapped through the later commits onto the HEAD lines it
+/// last shaped, so symbol line ranges can be matched against the commits that changed them.
+/// Renames are followed. Outside a git repository, or before the first commit, the tables are
+/// left untouched.
+pub fn refresh_history(connection: &mut Connection, repo: &Path) -> anyhow::Result<()> {
+    let Ok(head) = git_utils::head_sha(repo) else {
+        return Ok(());
+    };
+    let recorded_head = connection
+        .query_row(
+            "SELECT value FROM meta WHERE key = 'history_head'",
+            [],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()?;
+    if recorded_head.as_deref() == Some(head.as_str()) {
+        return Ok(());
+    }
+    let since = recorded_head
+        .filter(|recorded| git_utils::is_ancestor(repo, recorded, &head).unwrap_or(false));
+    let revisions = match &since {
+        Some(recorded) => format!("{recorded}..{head}"),
+        None => head.clone(),
+    };
+    let mut commits = read_commit_changes(repo, &revisions)?;
+    // A range at least as long as the window replaces every stored commit.
+    let incremental = since.is_some() && commits.len() < HISTORY_COMMIT_LIMIT;
+
+    let indexed = indexed_paths(connection)?;
+    let line_paths = symbol_file_paths(connection)?
+        .into_iter()
+        .filter(|path| indexed.contains(path))
+        .collect::<BTreeSet<_>>();
+    read_line_hunks(repo, &mut commits, &line_paths)?;
+
+    let mut tracked = indexed
+        .into_iter()
+        .map(|path| {
+            let file = TrackedFile {
+                head_path: path.clone(),
+                spans: vec![Span::identity()],
+            };
+            (path, file)
+        })
+        .collect::<HashMap<_, _>>();
+    let mut tables = HistoryTables::default();
+    for logged in commits {
+        apply_commit(&mut tables, &mut tracked, logged.commit, logged.diffs);
+    }
+
+    if incremental {
+        carry_over_history(connection, &mut tables, &tracked)?;
+    }
+    write_history(connection, &tables, &head)
+}
+
+/// Reads the commits in `revisions` with the files each one changed.
+fn read_commit_changes(repo: &Path, revisions: &str) -> anyhow::Result<Vec<LoggedCommit>> {
+    let mut commits = Vec::<LoggedCommit>::new();
+    // Raw records (`:<modes> <blobs> <status>`) list a commit's files first, then numstat
+    // records (`<added>\t<deleted>\t<path>`) give their counts in the same order. Renames put
+    // both paths in the following records, and numstat leaves its path field empty.
+    let mut pending_paths = 0_usize;
+    let mut pending_status = None;
+    let mut paths = Vec::new();
+    let mut numstat_index = 0_usize;
+    git_utils::for_each_commit_change(repo, revisions, HISTORY_COMMIT_LIMIT, |record| {
+        let record = record.trim_start_matches('\n');
+        if let Some(header) = record.strip_prefix('\u{1e}') {
+            commits.push(LoggedCommit {
+                commit: parse_commit_header(header)?,
+                diffs: Vec::new(),
+            });
+            numstat_index = 0;
+            return Ok(());
+        }
+        let Some(logged) = commits.last_mut() else {
+            return Ok(());
+        };
+        if pending_paths > 0 {
+            paths.push(record.to_string());
+            pending_paths -= 1;
+            if pending_paths == 0
+                && let Some(status) = pending_status.take()
+            {
+                logged
+                    .diffs
+                    .push(raw_file_diff(status, std::mem::take(&mut paths)));
+            }
+            return Ok(());
+        }
+        if let Some(raw) = record.strip_prefix(':') {
+            let status = raw
+                .rsplit(' ')
+                .next()
+                .and_then(|status| status.chars().next())
+                .ok_or_else(|| anyhow::anyhow!("malformed git log raw record: {record:?}"))?;
+            pending_paths = if matches!(status, 'R' | 'C') { 2 } else { 1 };
+            pending_status = Some(status);
+            paths.clear();
+            return Ok(());
+        }
+        if record.is_empty() {
+            return Ok(());
+        }
+        let mut fields = record.splitn(3, '\t');
+        let (Some(added), Some(deleted), Some(path)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            anyhow::bail!("malformed git log numstat record: {record:?}");
+        };
+        if path.is_empty() {
+            // The rename's paths repeat the raw record's.
+            pending_paths = 2;
+            pending_status = None;
+        }
+        if let Some(diff) = logged.diffs.get_mut(numstat_index) {
+            // Binary files report `-` for both counts.
+            diff.added = added.parse().unwrap_or(0);
+            diff.deleted = deleted.parse().unwrap_or(0);
+        }
+        numstat_index += 1;
+        Ok(())
+    })?;
+    Ok(commits)
+}
+
+fn raw_file_diff(status: char, mut paths: Vec<String>) -> FileDiff {
+    let (old_path, new_path) = match status {
+        'A' => (None, paths.pop()),
+        'D' => (paths.pop(), None),
+        'R' | 'C' => {
+            let new_path = paths.pop();
+            (paths.pop(), new_path)
+        }
+        _ => {
+            let path = paths.pop();
+            (path.clone(), path)
+        }
+    };
+    FileDiff {
+        old_path,
+        new_path,
+        ..FileDiff::default()
+    }
+}
+
+/// Reads the zero-context hunks of the commits that changed a file in `line_paths` (under its
+/// HEAD name or an earlier one) and attaches them to those files' diffs.
+fn read_line_hunks(
+    repo: &Path,
+    commits: &mut [LoggedCommit],
+    line_paths: &BTreeSet<String>,
+) -> anyhow::Result<()> {
+    // Follow renames back from HEAD, exactly as `apply_commit` will, to learn which commits
+    // touched these files and every name they had.
+    let mut current = line_paths.clone();
+    let mut names = line_paths.clone();
+    let mut shas = Vec::new();
+    for logged in commits.iter() {
+        let touched = logged
+            .diffs
+            .iter()
+            .filter(|diff| {
+                diff.new_path
+                    .as_ref()
+                    .is_some_and(|path| current.contains(path))
+            })
+            .collect::<Vec<_>>();
+        if touched.is_empty() {
+            continue;
+        }
+        shas.push(logged.commit.sha.clone());
+        for diff in &touched {
+            current.remove(diff.new_path.as_deref().unwrap_or_default());
+        }
+        for diff in touched {
+            if let Some(old_path) = &diff.old_path {
+                current.insert(old_path.clone());
+                names.insert(old_path.clone());
+            }
+        }
+    }
+    if shas.is_empty() {
+        return Ok(());
+    }
+
+    let mut patches = HashMap::<String, Vec<FileDiff>>::new();
+    let mut sha = None;
+    let mut diffs = Vec::<FileDiff>::new();
+    let mut in_hunks = false;
+    git_utils::for_each_commit_patch_line(repo, &shas, &names, |line| {
+        if let Some(header) = line.strip_prefix('\u{1e}') {
+            if let Some(finished) = sha.replace(header.trim().to_string()) {
+                patches.insert(finished, std::mem::take(&mut diffs));
+            }
+            return Ok(());
+        }
+        if let Some(paths) = line.strip_prefix("diff --git ") {
+            let (old_path, new_path) = split_diff_paths(paths);
+            diffs.push(FileDiff {
+                old_path,
+                new_path,
+                ..FileDiff::default()
+            });
+            in_hunks = false;
+            return Ok(());
+        }
+        let Some(diff) = diffs.last_mut() else {
+            return Ok(());
+        };
+        if let Some(hunk) = line.strip_prefix("@@ ") {
+            diff.hunks.push(parse_hunk_header(hunk)?);
+            in_hunks = true;
+        } else if in_hunks {
+            // Patch content; the hunk header already counts its lines.
+        } else if let Some(path) = line.strip_prefix("rename from ") {
+            diff.old_path = Some(path.to_string());
+        } else if let Some(path) = line.strip_prefix("rename to ") {
+            diff.new_path = Some(path.to_string());
+        } else if let Some(path) = line.strip_prefix("--- ") {
+            diff.old_path = patch_path(path, "a/");
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            diff.new_path = patch_path(path, "b/");
+        } else if line.starts_with("new file mode") {
+            diff.old_path = None;
+        } else if line.starts_with("deleted file mode") {
+            diff.new_path = None;
+        }
+        Ok(())
+    })?;
+    if let Some(finished) = sha {
+        patches.insert(finished, diffs);
+    }
+
+    for logged in commits {
+        let Some(patch) = patches.get_mut(&logged.commit.sha) else {
+            continue;
+        };
+        for diff in &mut logged.diffs {
+            let Some(new_path) = &diff.new_path else {
+                continue;
+            };
+            if let Some(patched) = patch
+                .iter_mut()
+                .find(|patched| patched.new_path.as_ref() == Some(new_path))
+            {
+                diff.hunks = std::mem::take(&mut patched.hunks);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn indexed_paths(connection: &Connection) -> anyhow::Result<BTreeSet<String>> {
+    let mut statement =
+        connection.prepare_cached("SELECT file_path FROM indexed_files ORDER BY file_path")?;
+    let rows = statement.query_map([], |row| row.get::<_, String>(0))?;
+    rows.collect::<Result<BTreeSet<_>, _>>().map_err(Into::into)
+}
+
+/// Files with at least one indexed symbol: the only files whose line history is queried.
+fn symbol_file_paths(connection: &Connection) -> anyhow::Result<BTreeSet<String>> {
+    let mut statement = connection.prepare_cached("SELECT DISTINCT file_path FROM symbols_v2")?;
+    let rows = statement.query_map([], |row| row.get::<_, String>(0))?;
+    rows.collect::<Result<BTreeSet<_>, _>>().map_err(Into::into)
+}
+
+fn parse_commit_header(header: &str) -> anyhow::Result<HistoryCommit> {
+    let mut fields = header.split('\u{1f}');
+    let mut field = || {
+        fields
+            .next()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("malformed git log header: {header:?}"))
+    };
+    Ok(HistoryCommit {
+        sha: field()?,
+        authored_at: field()?.parse()?,
+        author: field()?,
+        email: field()?,
+    })
+}
+
+/// Splits `a/<old> b/<new>`. Paths containing ` b/` are ambiguous unless both sides match;
+/// rename and `---`/`+++` lines correct them when present.
+fn split_diff_paths(paths: &str) -> (Option<String>, Option<String>) {
+    let Some(rest) = paths.strip_prefix("a/") else {
+        return (None, None);
+    };
+    let half = rest.len().saturating_sub(3) / 2;
+    if rest.len() > 3
+        && rest.is_char_boundary(half)
+        && rest[half..].starts_with(" b/")
+        && rest[..half] == rest[half + 3..]
+    {
+        let path = rest[..half].to_string();
+        return (Some(path.clone()), Some(path));
+    }
+    match rest.split_once(" b/") {
+        Some((old, new)) => (Some(old.to_string()), Some(new.to_string())),
+        None => (None, None),
+    }
+}
+
+fn patch_path(path: &str, prefix: &str) -> Option<String> {
+    let path = path.trim_end_matches('\t');
+    if path == "/dev/null" {
+        return None;
+    }
+    Some(path.strip_prefix(prefix).unwrap_or(path).to_string())
+}
+
+/// Parses `-a[,b] +c[,d] @@ ...`; an omitted length means one line.
+fn parse_hunk_header(header: &str) -> anyhow::Result<Hunk> {
+    let mut ranges = header.split_whitespace();
+    let mut range = |sign: char| -> anyhow::Result<(u32, u32)> {
+        let text = ranges
+            .next()
+            .and_then(|range| range.strip_prefix(sign))
+            .ok_or_else(|| anyhow::anyhow!("malformed hunk header: @@ {header}"))?;
+        Ok(match text.split_once(',') {
+            Some((start, len)) => (start.parse()?, len.parse()?),
+            None => (text.parse()?, 1),
+        })
+    };
+    let (old_start, old_len) = range('-')?;
+    let (new_start, new_len) = range('+')?;
+    Ok(Hunk {
+        old_start,
+        old_len,
+        new_start,
+        new_len,
+    })
+}
+
+/// Records `commit`'s changes to tracked files, then rewinds their line mappings to the state
+/// before the commit.
+fn apply_commit(
+    tables: &mut HistoryTables,
+    tracked: &mut HashMap<String, TrackedFile>,
+    commit: HistoryCommit,
+    diffs: Vec<FileDiff>,
+) {
+    // Detach every touched file before re-keying, so a rename onto a path that is also
+    // created in this commit cannot pick up the wrong file.
+    let touched = diffs
+        .into_iter()
+        .filter_map(|diff| {
+            let file = tracked.remove(diff.new_path.as_deref()?)?;
+            Some((diff, file))
+        })
+        .collect::<Vec<_>>();
+    if touched.is_empty() {
+        return;
+    }
+    tables.commits.push(commit);
+    let commit_index = tables.commits.len() - 1;
+    for (diff, mut file) in touched {
+        let mut hunks = diff.hunks;
+        hunks.sort_by_key(|hunk| hunk.new_start);
+        tables.file_changes.push((
+            commit_index,
+            file.head_path.clone(),
+            diff.added,
+            diff.deleted,
+        ));
+        for hunk in &hunks {
+            let (start, end) = written_lines(hunk);
+            for (head_start, head_end) in head_ranges(&file.spans, start, end) {
+                tables.line_changes.push((
+                    commit_index,
+                    file.head_path.clone(),
+                    head_start,
+                    head_end,
+                ));
+            }
+        }
+        let Some(old_path) = diff.old_path else {
+            continue;
+        };
+        file.spans = rewind_spans(&file.spans, &hunks);
+        tracked.entry(old_path).or_insert(file);
+    }
+}
+
+/// The post-image lines a hunk wrote. Pure deletions count against the line they were removed
+/// after.
+fn written_lines(hunk: &Hunk) -> (u32, u32) {
+    if hunk.new_len == 0 {
+        (hunk.new_start.max(1), hunk.new_start.max(1))
+    } else {
+        (hunk.new_start, hunk.new_start + hunk.new_len - 1)
+    }
+}
+
+/// HEAD line ranges of the lines `start..=end` in the state `spans` describe, merged and sorted.
+fn head_ranges(spans: &[Span], start: u32, end: u32) -> Vec<(u32, u32)> {
+    let mut ranges = spans
+        .iter()
+        .filter_map(|span| {
+            let low = start.max(span.start);
+            let high = end.min(span.end);
+            (low <= high).then(|| span.head_range(low, high))
+        })
+        .collect::<Vec<_>>();
+    ranges.sort_unstable();
+    let mut merged = Vec::<(u32, u32)>::new();
+    for (low, high) in ranges {
+        match merged.last_mut() {
+            Some(last) if low <= last.1.saturating_add(1) => last.1 = last.1.max(high),
+            _ => merged.push((low, high)),
+        }
+    }
+    merged
+}
+
+/// Maps spans over a commit's post-image onto its pre-image. Lines after each hunk shift by the
+/// lines it added or removed, and the lines a hunk replaced map to the HEAD lines its new lines
+/// became, so earlier commits to them still count against the code that replaced them. Lines a
+/// hunk only deleted map to the line they were removed after.
+fn rewind_spans(spans: &[Span], hunks: &[Hunk]) -> Vec<Span> {
+    let mut segments = Vec::new();
+    let mut next = 1_i64;
+    let mut shift = 0_i64;
+    let mut rewound = Vec::new();
+    for hunk in hunks {
+        let new_start = i64::from(hunk.new_start);
+        let new_len = i64::from(hunk.new_len);
+        if new_len == 0 {
+            segments.push((next, new_start, shift));
+            next = new_start + 1;
+        } else {
+            segments.push((next, new_start - 1, shift));
+            next = new_start + new_len;
+        }
+        shift += new_len - i64::from(hunk.old_len);
+        let (written_start, written_end) = written_lines(hunk);
+        let replaced = head_ranges(spans, written_start, written_end);
+        if let (Some(first), Some(last), true) =
+            (replaced.first(), replaced.last(), hunk.old_len > 0)
+        {
+            rewound.push(Span {
+                start: hunk.old_start,
+                end: hunk.old_start + hunk.old_len - 1,
+                head_start: first.0,
+                head_end: Some(last.1),
+            });
+        }
+    }
+    segments.push((next, i64::from(u32::MAX), shift));
+
+    for span in spans {
+        for &(low, high, shift) in &segments {
+            let low = low.max(i64::from(span.start));
+            let high = high.min(i64::from(span.end));
+            if low > high {
+                continue;
+            }
+            let (Ok(start), Ok(end)) = (
+                u32::try_from(low - shift),
+                u32::try_from((high - shift).min(i64::from(u32::MAX))),
+            ) else {
+                continue;
+            };
+            if start == 0 {
+                continue;
+            }
+            let (head_start, head_end) = span.head_range(low as u32, high as u32);
+            rewound.push(Span {
+                start,
+                end,
+                head_start,
+                head_end: span.head_end.map(|_| head_end),
+            });
+        }
+    }
+    rewound.sort_by_key(|span| span.start);
+    rewound
+}
+
+/// Loads the stored history into `tables.carried`, keeping only the newest commits that still
+/// fit in the window beside the ones just read. Each file's rows follow it to its HEAD path and
+/// line ranges map through the spans `tracked` rewound over the new commits; rows of files no
+/// longer tracked are dropped.
+fn carry_over_history(
+    connection: &Connection,
+    tables: &mut HistoryTables,
+    tracked: &HashMap<String, TrackedFile>,
+) -> anyhow::Result<()> {
+    let keep = HISTORY_COMMIT_LIMIT.saturating_sub(tables.commits.len());
+    let mut statement = connection.prepare_cached(
+        "SELECT commit_id, sha, author, email, authored_at FROM history_commits
+         ORDER BY commit_id DESC LIMIT ?1",
+    )?;
+    let commits = statement
+        .query_map(params![keep as i64], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                HistoryCommit {
+                    sha: row.get(1)?,
+                    author: row.get(2)?,
+                    email: row.get(3)?,
+                    authored_at: row.get(4)?,
+                },
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let Some(oldest_kept) = commits.last().map(|(commit_id, _)| *commit_id) else {
+        return Ok(());
+    };
+    let carried = &mut tables.carried;
+    carried.commits = commits;
+
+    let mut statement = connection.prepare_cached(
+        "SELECT commit_id, file_path, lines_added, lines_deleted FROM history_file_changes
+         WHERE commit_id >= ?1",
+    )?;
+    let rows = statement.query_map(params![oldest_kept], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get(2)?,
+            row.get(3)?,
+        ))
+    })?;
+    for row in rows {
+        let (commit_id, file_path, added, deleted) = row?;
+        if let Some(file) = tracked.get(&file_path) {
+            carried
+                .file_changes
+                .push((commit_id, file.head_path.clone(), added, deleted));
+        }
+    }
+
+    let mut statement = connection.prepare_cached(
+        "SELECT commit_id, file_path, start_line, end_line FROM history_line_changes
+         WHERE commit_id >= ?1",
+    )?;
+    let rows = statement.query_map(params![oldest_kept], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get(2)?,
+            row.get(3)?,
+        ))
+    })?;
+    for row in rows {
+        let (commit_id, file_path, start, end) = row?;
+        let Some(file) = tracked.get(&file_path) else {
+            continue;
+        };
+        for (head_start, head_end) in head_ranges(&file.spans, start, end) {
+            carried
+                .line_changes
+                .push((commit_id, file.head_path.clone(), head_start, head_end));
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the history tables with `tables`. Commit ids increase with recency: carried commits
+/// keep theirs, and the commits just read are numbered after them, oldest first.
+fn write_history(
+    connection: &mut Connection,
+    tables: &HistoryTables,
+    head: &str,
+) -> anyhow::Result<()> {
+    let carried = &tables.carried;
+    let base_id = carried
+        .commits
+        .first()
+        .map_or(0, |(commit_id, _)| *commit_id);
+    let new_id = |index: usize| base_id + (tables.commits.len() - index) as i64;
+    let tx = connection.transaction()?;
+    tx.execute_batch(
+        "DELETE FROM history_commits;
+         DELETE FROM history_file_changes;
+         DELETE FROM history_line_changes;",
+    )?;
+    {
+        let mut insert_commit = tx.prepare_cached(
+            "INSERT INTO history_commits(commit_id, sha, author, email, authored_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        let commits = carried
+            .commits
+            .iter()
+            .map(|(commit_id, commit)| (*commit_id, commit))
+            .chain(
+                tables
+                    .commits
+                    .iter()
+                    .enumerate()
+                    .map(|(index, commit)| (new_id(index), commit)),
+            );
+        for (commit_id, commit) in commits {
+            insert_commit.execute(params![
+                commit_id,
+                commit.sha,
+                commit.author,
+                commit.email,
+                commit.authored_at
+            ])?;
+        }
+        let mut insert_file = tx.prepare_cached(
+            "INSERT OR IGNORE INTO history_file_changes(
+                commit_id, file_path, lines_added, lines_deleted
+             ) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        let file_changes =
+            carried
+                .file_changes
+                .iter()
+                .cloned()
+                .chain(
+                    tables
+                        .file_changes
+                        .iter()
+                        .map(|(index, path, added, deleted)| {
+                            (new_id(*index), path.clone(), *added, *deleted)
+                        }),
+                );
+        for (commit_id, file_path, added, deleted) in file_changes {
+            insert_file.execute(params![commit_id, file_path, added, deleted])?;
+        }
+        let mut insert_lines = tx.prepare_cached(
+            "INSERT INTO history_line_changes(commit_id, file_path, start_line, end_line)
+             VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        let line_changes = carried.line_changes.iter().cloned().chain(
+            tables
+                .line_changes
+                .iter()
+                .map(|(index, path, start, end)| (new_id(*index), path.clone(), *start, *end)),
+        );
+        for (commit_id, file_path, start, end) in line_changes {
+            insert_lines.execute(params![commit_id, file_path, start, end])?;
+        }
+    }
+    tx.execute(
+        "INSERT OR REPLACE INTO meta(key, value) VALUES('history_head', ?1)",
+        [head],
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Hunk, Span, head_ranges, parse_hunk_header, rewind_spans, split_diff_paths};
+
+    fn hunk(old_start: u32, old_len: u32, new_start: u32, new_len: u32) -> Hunk {
+        Hunk {
+            old_start,
+            old_len,
+            new_start,
+            new_len,
+        }
+    }
+
+    #[test]
+    fn parse_hunk_header_defaults_lengths_to_one() {
+        assert_eq!(
+            parse_hunk_header("-3,0 +4,2 @@ fn run()").expect("header should parse"),
+            hunk(3, 0, 4, 2)
+        );
+        assert_eq!(
+            parse_hunk_header("-7 +7 @@").expect("header should parse"),
+            hunk(7, 1, 7, 1)
+        );
+        assert!(parse_hunk_header("garbage").is_err());
+    }
+
+    #[test]
+    fn split_diff_paths_handles_spaces_and_renames() {
+        assert_eq!(
+            split_diff_paths("a/src/my b/file.rs b/src/my b/file.rs"),
+            (
+                Some("src/my b/file.rs".to_string()),
+                Some("src/my b/file.rs".to_string())
+            )
+        );
+        assert_eq!(
+            split_diff_paths("a/old.rs b/new.rs"),
+            (Some("old.rs".to_string()), Some("new.rs".to_string()))
+        );
+    }
+
+    #[test]
+    fn rewind_spans_maps_replaced_lines_onto_their_replacements() {
+        let head = vec![Span::identity()];
+        // The commit replaced old line 3 with new lines 3-4 and deleted old lines 8-9.
+        let hunks = [hunk(3, 1, 3, 2), hunk(8, 2, 8, 0)];
+        assert_eq!(head_ranges(&head, 3, 4), vec![(3, 4)]);
+
+        let before = rewind_spans(&head, &hunks);
+        let exact = |start, end, head_start| Span {
+            start,
+            end,
+            head_start,
+            head_end: None,
+        };
+        assert_eq!(
+            before,
+            vec![
+                exact(1, 2, 1),
+                Span {
+                    start: 3,
+                    end: 3,
+                    head_start: 3,
+                    head_end: Some(4),
+                },
+                exact(4, 7, 5),
+                Span {
+                    start: 8,
+                    end: 9,
+                    head_start: 8,
+                    head_end: Some(8),
+                },
+                exact(10, u32::MAX, 9),
+            ]
+        );
+        assert_eq!(head_ranges(&before, 2, 5), vec![(2, 6)]);
+        assert_eq!(head_ranges(&before, 9, 10), vec![(8, 9)]);
+        assert_eq!(head_ranges(&before, 20, 21), vec![(19, 20)]);
+    }
+}
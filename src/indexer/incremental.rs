@@ -0,0 +1,454 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs::FileType;
+use std::path::{Path, PathBuf};
+
+use rusqlite::Connection;
+
+use super::workspace::WorkspaceLayout;
+use super::{
+    IndexOptions, IndexSummary, delete_file_rows, entry_paths, files, history, index_changed_files,
+    index_source_entries, indexed_file_hashes, invalidate_layout_dependents, replay_deferred_edges,
+    workspace_layout,
+};
+
+const WORKSPACE_MANIFESTS: &[&str] = &[
+    "go.mod",
+    "go.work",
+    "Cargo.toml",
+    "package.json",
+    "pyproject.toml",
+    "setup.cfg",
+    "__init__.py",
+];
+
+/// Keeps an existing index in sync with a working tree one batch of touched paths at a time,
+/// instead of re-walking and re-hashing every file like [`super::index_repository`].
+pub struct IncrementalIndexer {
+    repo: PathBuf,
+    connection: Connection,
+    options: IndexOptions,
+    workspace: WorkspaceLayout,
+    live_paths: BTreeSet<String>,
+}
+
+/// Paths touched by one [`IncrementalIndexer::reindex_paths`] call, sorted.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ReindexSummary {
+    /// Touched files whose content changed and were reindexed.
+    pub reindexed_files: Vec<String>,
+    /// Unchanged files reindexed so their edges into the touched files are resolved again.
+    pub dependent_files: Vec<String>,
+    /// Files that no longer exist and were pruned from the index.
+    pub removed_files: Vec<String>,
+}
+
+impl ReindexSummary {
+    pub fn is_empty(&self) -> bool {
+        self.reindexed_files.is_empty()
+            && self.dependent_files.is_empty()
+            && self.removed_files.is_empty()
+    }
+}
+
+impl IncrementalIndexer {
+    /// Opens the index at `db_path` and brings it up to date with a full index pass.
+    pub fn open(
+        repo: &Path,
+        db_path: &Path,
+        options: IndexOptions,
+    ) -> anyhow::Result<(Self, IndexSummary)> {
+        let mut connection = Connection::open(db_path)?;
+        let entries = files::discover_source_entries(repo)?;
+        let paths = entry_paths(&entries);
+        let workspace = workspace_layout(repo, &paths);
+        let live_paths = paths.into_iter().collect();
+        let summary = index_source_entries(&mut connection, entries, &workspace, &options)?;
+        history::refresh_history(&mut connection, repo)?;
+        let indexer = Self {
+            repo: repo.to_path_buf(),
+            connection,
+            options,
+            workspace,
+            live_paths,
+        };
+        Ok((indexer, summary))
+    }
+
+    /// Reindexes the repository-relative `touched_paths`. Deleted files are pruned, and files
+    /// whose edges pointed into a changed file (or that reference a symbol it newly defines) are
+    /// reindexed too, so cross-file edges dropped by the rewrite are replayed. The live path set
+    /// is updated from the batch itself, and the workspace layout is rebuilt from manifest
+    /// contents only when paths appear or disappear or a manifest changes; files that resolved
+    /// imports against a part of the layout that changed are reindexed as dependents.
+    pub fn reindex_paths(
+        &mut self,
+        touched_paths: &BTreeSet<String>,
+    ) -> anyhow::Result<ReindexSummary> {
+        let (added_paths, removed_paths) = self.apply_path_events(touched_paths)?;
+        let removed_files = removed_paths.into_iter().collect::<Vec<_>>();
+        let layout_changed = !removed_files.is_empty()
+            || !added_paths.is_empty()
+            || touched_paths.iter().any(|path| is_workspace_manifest(path));
+        delete_file_rows(&mut self.connection, &removed_files)?;
+        let mut layout_dependents = BTreeSet::new();
+        if layout_changed {
+            let paths = self.live_paths.iter().cloned().collect::<Vec<_>>();
+            self.workspace = workspace_layout(&self.repo, &paths);
+            layout_dependents =
+                invalidate_layout_dependents(&mut self.connection, &self.workspace, &paths)?;
+        }
+
+        let indexed_hashes = indexed_file_hashes(&self.connection)?;
+        let mut changed_files = Vec::new();
+        for path in touched_paths
+            .iter()
+            .chain(&added_paths)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter(|path| self.live_paths.contains(*path))
+        {
+            let file = files::read_source_file(&self.repo.join(path), path.clone())?;
+            if indexed_hashes.get(path) != Some(&file.content_hash) {
+                changed_files.push(file);
+            }
+        }
+        let reindexed_files = changed_files
+            .iter()
+            .map(|file| file.relative_path.clone())
+            .collect::<Vec<_>>();
+        let previous_symbols = defined_symbols(&self.connection, &reindexed_files)?;
+        let mut dependents = inbound_edge_files(&self.connection, &reindexed_files)?;
+
+        let mut deferred_edges = Vec::new();
+        index_changed_files(
+            &mut self.connection,
+            &changed_files,
+            &self.workspace,
+            self.options.jobs,
+            &mut deferred_edges,
+        )?;
+        let introduced_symbols = defined_symbols(&self.connection, &reindexed_files)?
+            .difference(&previous_symbols)
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        dependents.extend(referencing_files(&self.connection, &introduced_symbols)?);
+        dependents.extend(layout_dependents);
+        for path in &reindexed_files {
+            dependents.remove(path);
+        }
+        dependents.retain(|path| self.live_paths.contains(path));
+
+        let mut dependent_files = Vec::new();
+        for path in &dependents {
+            dependent_files.push(files::read_source_file(
+                &self.repo.join(path),
+                path.clone(),
+            )?);
+        }
+        index_changed_files(
+            &mut self.connection,
+            &dependent_files,
+            &self.workspace,
+            self.options.jobs,
+            &mut deferred_edges,
+        )?;
+        replay_deferred_edges(&mut self.connection, deferred_edges)?;
+
+        Ok(ReindexSummary {
+            reindexed_files,
+            dependent_files: dependents.into_iter().collect(),
+            removed_files,
+        })
+    }
+}
+
+impl IncrementalIndexer {
+    /// Updates the live path set from a batch of touched paths without walking the repository.
+    /// Touched files are checked against the walker's filters; a touched directory (created or
+    /// renamed into place) has only its own subtree walked, and a vanished or ignored path drops
+    /// every live path beneath it. Returns the paths that appeared and those that disappeared.
+    fn apply_path_events(
+        &mut self,
+        touched_paths: &BTreeSet<String>,
+    ) -> anyhow::Result<(BTreeSet<String>, BTreeSet<String>)> {
+        let mut visible_entries = VisibleEntries::new(&self.repo);
+        let mut added = BTreeSet::new();
+        let mut removed = BTreeSet::new();
+        for path in touched_paths {
+            let file_type = visible_entries.file_type(path)?;
+            if file_type.is_some_and(|file_type| file_type.is_dir()) {
+                for nested in files::discover_source_paths_under(&self.repo, path)? {
+                    if self.live_paths.insert(nested.clone()) {
+                        added.insert(nested);
+                    }
+                }
+                continue;
+            }
+            let prefix = format!("{path}/");
+            let nested = self
+                .live_paths
+                .range(prefix.clone()..)
+                .take_while(|live_path| live_path.starts_with(&prefix))
+                .cloned()
+                .collect::<Vec<_>>();
+            for nested in nested {
+                self.live_paths.remove(&nested);
+                removed.insert(nested);
+            }
+            if file_type.is_some_and(|file_type| file_type.is_file()) {
+                if self.live_paths.insert(path.clone()) {
+                    added.insert(path.clone());
+                }
+            } else if self.live_paths.remove(path) {
+                removed.insert(path.clone());
+            }
+        }
+        Ok((added, removed))
+    }
+}
+
+/// Answers whether repository paths would be visited by a full walk, listing each directory on
+/// the way at most once per batch.
+struct VisibleEntries<'a> {
+    repo: &'a Path,
+    listings: HashMap<String, HashMap<String, FileType>>,
+}
+
+impl<'a> VisibleEntries<'a> {
+    fn new(repo: &'a Path) -> Self {
+        Self {
+            repo,
+            listings: HashMap::new(),
+        }
+    }
+
+    /// The file type of `path` when it and every directory above it pass the walker's filters.
+    fn file_type(&mut self, path: &str) -> anyhow::Result<Option<FileType>> {
+        let mut directory = String::new();
+        let mut segments = path.split('/').peekable();
+        while let Some(segment) = segments.next() {
+            let Some(file_type) = self.listing(&directory)?.get(segment).copied() else {
+                return Ok(None);
+            };
+            if segments.peek().is_none() {
+                return Ok(Some(file_type));
+            }
+            if !file_type.is_dir() {
+                return Ok(None);
+            }
+            if !directory.is_empty() {
+                directory.push('/');
+            }
+            directory.push_str(segment);
+        }
+        Ok(None)
+    }
+
+    fn listing(&mut self, directory: &str) -> anyhow::Result<&HashMap<String, FileType>> {
+        if !self.listings.contains_key(directory) {
+            let entries = files::list_source_directory(self.repo, directory)?;
+            self.listings
+                .insert(directory.to_string(), entries.into_iter().collect());
+        }
+        Ok(&self.listings[directory])
+    }
+}
+
+fn is_workspace_manifest(path: &str) -> bool {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    WORKSPACE_MANIFESTS.contains(&file_name)
+        || (file_name.starts_with("tsconfig") && file_name.ends_with(".json"))
+}
+
+fn defined_symbols(
+    connection: &Connection,
+    file_paths: &[String],
+) -> anyhow::Result<BTreeSet<String>> {
+    let mut statement = connection.prepare("SELECT symbol FROM symbols_v2 WHERE file_path = ?1")?;
+    let mut symbols = BTreeSet::new();
+    for file_path in file_paths {
+        let rows = statement.query_map([file_path], |row| row.get::<_, String>(0))?;
+        for row in rows {
+            symbols.insert(row?);
+        }
+    }
+    Ok(symbols)
+}
+
+fn inbound_edge_files(
+    connection: &Connection,
+    file_paths: &[String],
+) -> anyhow::Result<BTreeSet<String>> {
+    let mut statement = connection.prepare(
+        "SELECT DISTINCT from_symbols.file_path
+         FROM symbol_edges_v2 edges
+         JOIN symbols_v2 from_symbols ON from_symbols.symbol_id = edges.from_symbol_id
+         JOIN symbols_v2 to_symbols ON to_symbols.symbol_id = edges.to_symbol_id
+         WHERE to_symbols.file_path = ?1",
+    )?;
+    let mut dependents = BTreeSet::new();
+    for file_path in file_paths {
+        let rows = statement.query_map([file_path], |row| row.get::<_, String>(0))?;
+        for row in rows {
+            dependents.insert(row?);
+        }
+    }
+    Ok(dependents)
+}
+
+fn referencing_files(
+    connection: &Connection,
+    symbols: &BTreeSet<String>,
+) -> anyhow::Result<BTreeSet<String>> {
+    let mut statement =
+        connection.prepare("SELECT DISTINCT file_path FROM ast_references WHERE symbol = ?1")?;
+    let mut dependents = BTreeSet::new();
+    for symbol in symbols {
+        let rows = statement.query_map([symbol], |row| row.get::<_, String>(0))?;
+        for row in rows {
+            dependents.insert(row?);
+        }
+    }
+    Ok(dependents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::schema;
+
+    fn open_indexer(repo: &Path) -> IncrementalIndexer {
+        let db_path = repo.join(".index.sqlite");
+        let connection = Connection::open(&db_path).expect("index database should open");
+        schema::bootstrap_schema(&connection).expect("schema should bootstrap");
+        IncrementalIndexer::open(repo, &db_path, IndexOptions::default())
+            .expect("initial index should succeed")
+            .0
+    }
+
+    #[test]
+    fn reindex_paths_revisits_files_referencing_newly_defined_symbols() {
+        let repo = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::write(
+            repo.path().join("app.py"),
+            "from tools import pretty\n\n\ndef main():\n    return pretty(1)\n",
+        )
+        .expect("fixture should be written");
+        let mut indexer = open_indexer(repo.path());
+
+        std::fs::write(
+            repo.path().join("tools.py"),
+            "def pretty(value):\n    return value\n",
+        )
+        .expect("fixture should be written");
+        let summary = indexer
+            .reindex_paths(&BTreeSet::from(["tools.py".to_string()]))
+            .expect("reindex should succeed");
+
+        assert_eq!(
+            summary,
+            ReindexSummary {
+                reindexed_files: vec!["tools.py".to_string()],
+                dependent_files: vec!["app.py".to_string()],
+                removed_files: Vec::new(),
+            }
+        );
+        let unchanged = indexer
+            .reindex_paths(&BTreeSet::from(["tools.py".to_string()]))
+            .expect("reindex should succeed");
+        assert!(unchanged.is_empty());
+    }
+
+    #[test]
+    fn reindex_paths_revisits_go_importers_when_a_package_gains_a_file() {
+        let repo = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::create_dir_all(repo.path().join("pkg/tool")).expect("fixture dir should exist");
+        std::fs::write(repo.path().join("go.mod"), "module example.com/app\n")
+            .expect("fixture should be written");
+        std::fs::write(
+            repo.path().join("pkg/tool/a.go"),
+            "package tool\n\nfunc A() {}\n",
+        )
+        .expect("fixture should be written");
+        std::fs::write(
+            repo.path().join("main.go"),
+            "package main\n\nimport \"example.com/app/pkg/tool\"\n\nfunc main() {\n\ttool.A()\n\ttool.B()\n}\n",
+        )
+        .expect("fixture should be written");
+        let mut indexer = open_indexer(repo.path());
+
+        std::fs::write(
+            repo.path().join("pkg/tool/b.go"),
+            "package tool\n\nfunc B() {}\n",
+        )
+        .expect("fixture should be written");
+        let summary = indexer
+            .reindex_paths(&BTreeSet::from(["pkg/tool/b.go".to_string()]))
+            .expect("reindex should succeed");
+
+        assert_eq!(summary.reindexed_files, vec!["pkg/tool/b.go".to_string()]);
+        assert!(
+            summary.dependent_files.contains(&"main.go".to_string()),
+            "{summary:?}"
+        );
+        let callers = indexer
+            .connection
+            .query_row(
+                "SELECT COUNT(*)
+                 FROM symbol_edges_v2 edges
+                 JOIN symbols_v2 callee ON callee.symbol_id = edges.to_symbol_id
+                 JOIN symbols_v2 caller ON caller.symbol_id = edges.from_symbol_id
+                 WHERE callee.symbol = 'B' AND caller.file_path = 'main.go'
+                   AND edges.edge_kind = 'calls'",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .expect("edge count should load");
+        assert_eq!(callers, 1);
+    }
+
+    #[test]
+    fn reindex_paths_tracks_directories_and_ignored_paths_from_events() {
+        let repo = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::create_dir(repo.path().join(".git")).expect("git marker should be created");
+        std::fs::write(repo.path().join(".gitignore"), "build/\n").expect("fixture should write");
+        std::fs::write(repo.path().join("lib.rs"), "fn root() {}\n").expect("fixture should write");
+        let mut indexer = open_indexer(repo.path());
+
+        std::fs::create_dir_all(repo.path().join("build")).expect("build dir should be created");
+        std::fs::write(repo.path().join("build/out.rs"), "fn generated() {}\n")
+            .expect("fixture should write");
+        std::fs::create_dir_all(repo.path().join("src/nested")).expect("dir should be created");
+        std::fs::write(repo.path().join("src/nested/a.rs"), "fn a() {}\n")
+            .expect("fixture should write");
+        std::fs::write(repo.path().join("src/b.rs"), "fn b() {}\n").expect("fixture should write");
+        let summary = indexer
+            .reindex_paths(&BTreeSet::from([
+                "build/out.rs".to_string(),
+                "src".to_string(),
+            ]))
+            .expect("reindex should succeed");
+        assert_eq!(
+            summary.reindexed_files,
+            vec!["src/b.rs".to_string(), "src/nested/a.rs".to_string()]
+        );
+
+        std::fs::remove_dir_all(repo.path().join("src")).expect("dir should be removed");
+        let summary = indexer
+            .reindex_paths(&BTreeSet::from(["src".to_string()]))
+            .expect("reindex should succeed");
+        assert_eq!(
+            summary.removed_files,
+            vec!["src/b.rs".to_string(), "src/nested/a.rs".to_string()]
+        );
+        assert_eq!(indexer.live_paths, BTreeSet::from(["lib.rs".to_string()]));
+    }
+
+    #[test]
+    fn workspace_manifest_detection_covers_nested_and_tsconfig_files() {
+        assert!(is_workspace_manifest("crates/app/Cargo.toml"));
+        assert!(is_workspace_manifest("apps/web/tsconfig.build.json"));
+        assert!(is_workspace_manifest("src/acme/__init__.py"));
+        assert!(!is_workspace_manifest("src/acme/models.py"));
+    }
+}
@@ -0,0 +1,134 @@
+use tree_sitter::Node;
+
+/// Per-function metrics stored in the `symbols_v2` complexity columns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ComplexityMetrics {
+    pub param_count: u32,
+    /// Deepest nesting of branch and loop constructs in the body; `0` for straight-line code.
+    pub nesting_depth: u32,
+    /// Branching and looping constructs: `if`, loops, `match`/`switch`, ternaries, `catch`.
+    pub branch_count: u32,
+    /// Cyclomatic complexity: one plus each decision point, where every non-default case arm
+    /// and every short-circuit operator is a decision point of its own.
+    pub complexity_score: u32,
+}
+
+/// Node kinds a grammar uses for the constructs the metrics count.
+pub struct ComplexityRules {
+    /// Children of the `parameters` field that each count as a parameter.
+    pub parameter_kinds: &'static [&'static str],
+    /// Leading parameter names that bind the receiver rather than an argument (`self`, `cls`).
+    pub receiver_names: &'static [&'static str],
+    /// Decision points that nest their bodies: `if`, loops, `catch`.
+    pub branch_kinds: &'static [&'static str],
+    /// Decision points that do not add nesting: `elif`, ternaries, comprehension filters.
+    pub flat_branch_kinds: &'static [&'static str],
+    /// Multi-way branches; their arms carry the decision points.
+    pub switch_kinds: &'static [&'static str],
+    pub arm_kinds: &'static [&'static str],
+    /// Expressions whose `operator` field may be a short-circuit operator.
+    pub logical_kinds: &'static [&'static str],
+    /// Nested definitions measured on their own rather than as part of the enclosing function.
+    pub nested_definition_kinds: &'static [&'static str],
+}
+
+const SHORT_CIRCUIT_OPERATORS: &[&str] = &["&&", "||", "??", "and", "or"];
+
+/// Measures a function-like node with `parameters` and `body` fields.
+pub fn function_metrics(
+    function: Node<'_>,
+    source: &str,
+    rules: &ComplexityRules,
+) -> ComplexityMetrics {
+    let mut metrics = ComplexityMetrics {
+        param_count: parameter_count(function, source, rules),
+        complexity_score: 1,
+        ..ComplexityMetrics::default()
+    };
+    let Some(body) = function.child_by_field_name("body") else {
+        return metrics;
+    };
+
+    let mut stack = vec![(body, 0_u32)];
+    while let Some((node, depth)) = stack.pop() {
+        let kind = node.kind();
+        if rules.nested_definition_kinds.contains(&kind) {
+            continue;
+        }
+        let mut child_depth = depth;
+        if rules.branch_kinds.contains(&kind) {
+            metrics.branch_count += 1;
+            metrics.complexity_score += 1;
+            if !is_else_if(node) {
+                child_depth += 1;
+            }
+        } else if rules.flat_branch_kinds.contains(&kind) {
+            metrics.branch_count += 1;
+            metrics.complexity_score += 1;
+        } else if rules.switch_kinds.contains(&kind) {
+            metrics.branch_count += 1;
+            child_depth += 1;
+        } else if (rules.arm_kinds.contains(&kind) && !is_wildcard_arm(node, source))
+            || (rules.logical_kinds.contains(&kind) && is_short_circuit(node))
+        {
+            metrics.complexity_score += 1;
+        }
+        metrics.nesting_depth = metrics.nesting_depth.max(child_depth);
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            stack.push((child, child_depth));
+        }
+    }
+    metrics
+}
+
+fn parameter_count(function: Node<'_>, source: &str, rules: &ComplexityRules) -> u32 {
+    let Some(parameters) = function.child_by_field_name("parameters") else {
+        // Single-parameter arrow functions (`x => x + 1`) use a `parameter` field.
+        return u32::from(function.child_by_field_name("parameter").is_some());
+    };
+    let mut cursor = parameters.walk();
+    let mut count = 0;
+    for (index, parameter) in parameters
+        .named_children(&mut cursor)
+        .filter(|parameter| rules.parameter_kinds.contains(&parameter.kind()))
+        .enumerate()
+    {
+        if index == 0
+            && parameter
+                .utf8_text(source.as_bytes())
+                .is_ok_and(|text| rules.receiver_names.contains(&text))
+        {
+            continue;
+        }
+        // Go declares several names with one type: `func f(a, b int)`.
+        let mut name_cursor = parameter.walk();
+        let names = parameter
+            .children_by_field_name("name", &mut name_cursor)
+            .count();
+        count += u32::try_from(names.max(1)).unwrap_or(u32::MAX);
+    }
+    count
+}
+
+fn is_else_if(node: Node<'_>) -> bool {
+    node.parent().is_some_and(|parent| {
+        parent.kind() == "else_clause"
+            || (parent.kind() == node.kind()
+                && parent.child_by_field_name("alternative") == Some(node))
+    })
+}
+
+fn is_wildcard_arm(arm: Node<'_>, source: &str) -> bool {
+    let mut cursor = arm.walk();
+    arm.named_children(&mut cursor)
+        .next()
+        .and_then(|pattern| pattern.utf8_text(source.as_bytes()).ok())
+        .is_some_and(|pattern| pattern.trim() == "_")
+}
+
+fn is_short_circuit(node: Node<'_>) -> bool {
+    node.child_by_field_name("operator")
+        .is_some_and(|operator| SHORT_CIRCUIT_OPERATORS.contains(&operator.kind()))
+}
@@ -1,19 +1,36 @@
 use anyhow::Context;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::Path;
 use tree_sitter::{Node, Parser};
 
+use crate::indexer::languages::complexity::{ComplexityRules, function_metrics};
 use crate::indexer::languages::{
     ExtractedEdge, ExtractedReference, ExtractedSymbol, ExtractionUnit, LanguageAdapter, SymbolKey,
 };
+use crate::indexer::workspace::WorkspaceLayout;
 
 pub struct GoLanguageAdapter;
 
+const COMPLEXITY_RULES: ComplexityRules = ComplexityRules {
+    parameter_kinds: &["parameter_declaration", "variadic_parameter_declaration"],
+    receiver_names: &[],
+    branch_kinds: &["if_statement", "for_statement"],
+    flat_branch_kinds: &[],
+    switch_kinds: &[
+        "expression_switch_statement",
+        "type_switch_statement",
+        "select_statement",
+    ],
+    arm_kinds: &["expression_case", "type_case", "communication_case"],
+    logical_kinds: &["binary_expression"],
+    nested_definition_kinds: &[],
+};
+
 #[derive(Debug, Clone)]
 struct GoImportBinding {
     local_symbol: String,
     imported_symbol: String,
-    import_path: String,
+    target_files: Vec<String>,
 }
 
 fn scoped_symbol_key(file_path: &str, language: &str, symbol: &str) -> SymbolKey {
@@ -44,6 +61,15 @@ impl LanguageAdapter for GoLanguageAdapter {
     }
 
     fn extract(&self, file_path: &str, source: &str) -> anyhow::Result<ExtractionUnit> {
+        self.extract_in_workspace(file_path, source, &WorkspaceLayout::default())
+    }
+
+    fn extract_in_workspace(
+        &self,
+        file_path: &str,
+        source: &str,
+        workspace: &WorkspaceLayout,
+    ) -> anyhow::Result<ExtractionUnit> {
         let mut parser = Parser::new();
         parser
             .set_language(&tree_sitter_go::LANGUAGE.into())
@@ -54,7 +80,9 @@ impl LanguageAdapter for GoLanguageAdapter {
             .context("failed to parse go source")?;
 
         let language = self.language_id().to_string();
-        let import_target_hints = import_target_hints(file_path, source, tree.root_node());
+        let import_target_hints =
+            import_target_hints(file_path, source, tree.root_node(), workspace);
+        let imported_members = imported_member_symbols(tree.root_node(), source);
         let mut symbols = Vec::new();
         let mut references = Vec::new();
         let mut edges = Vec::new();
@@ -141,7 +169,7 @@ impl LanguageAdapter for GoLanguageAdapter {
                     );
                 }
                 "import_spec" => {
-                    if let Some(binding) = import_binding(node, source, file_path) {
+                    if let Some(binding) = import_binding(node, source, file_path, workspace) {
                         let (start_line, start_column) = start_position(node);
                         let (end_line, end_column) = end_position(node);
                         symbols.push(ExtractedSymbol {
@@ -158,18 +186,29 @@ impl LanguageAdapter for GoLanguageAdapter {
                             end_line,
                             end_column,
                             signature: Some(format!("import {}", binding.local_symbol)),
+                            test_path: None,
+                            metrics: None,
+                            visibility: Some("private".to_string()),
+                            doc_comment: None,
                         });
-                        edges.push(ExtractedEdge {
-                            from_symbol_key: scoped_symbol_key(
-                                file_path,
-                                &language,
-                                &binding.local_symbol,
-                            ),
-                            to_symbol_key: language_symbol_key(&binding.imported_symbol, &language),
-                            edge_kind: "imports".to_string(),
-                            confidence: 0.9,
-                            provenance: "import_resolution".to_string(),
-                        });
+                        let to_symbol_keys = import_edge_target_keys(
+                            &binding,
+                            imported_members.get(&binding.local_symbol),
+                            &language,
+                        );
+                        for to_symbol_key in to_symbol_keys {
+                            edges.push(ExtractedEdge {
+                                from_symbol_key: scoped_symbol_key(
+                                    file_path,
+                                    &language,
+                                    &binding.local_symbol,
+                                ),
+                                to_symbol_key,
+                                edge_kind: "imports".to_string(),
+                                confidence: 0.9,
+                                provenance: "import_resolution".to_string(),
+                            });
+                        }
                     }
                 }
                 "call_expression" => {
@@ -281,11 +320,25 @@ fn push_named_definition(
         end_line,
         end_column,
         signature: None,
+        test_path: None,
+        metrics: matches!(kind, "function" | "method")
+            .then(|| function_metrics(node, source, &COMPLEXITY_RULES)),
+        visibility: Some(go_visibility(&symbol).to_string()),
+        doc_comment: None,
     });
 
     Some(symbol)
 }
 
+/// Go exports exactly the identifiers that start with an upper-case letter.
+fn go_visibility(symbol: &str) -> &'static str {
+    if symbol.chars().next().is_some_and(char::is_uppercase) {
+        "public"
+    } else {
+        "private"
+    }
+}
+
 fn push_identifier_list_definitions(
     node: Node<'_>,
     source: &str,
@@ -314,6 +367,10 @@ fn push_identifier_list_definitions(
             end_line,
             end_column,
             signature: None,
+            test_path: None,
+            metrics: None,
+            visibility: Some(go_visibility(&symbol).to_string()),
+            doc_comment: None,
         });
     }
 }
@@ -341,7 +398,7 @@ fn collect_call_symbols(
     caller: Option<&str>,
     file_path: &str,
     language: &str,
-    import_target_hints: &HashMap<String, String>,
+    import_target_hints: &HashMap<String, Vec<String>>,
     references: &mut Vec<ExtractedReference>,
     edges: &mut Vec<ExtractedEdge>,
 ) {
@@ -430,20 +487,15 @@ fn call_target_symbol_keys(
     language: &str,
     symbol: &str,
     qualifier: Option<&str>,
-    import_target_hints: &HashMap<String, String>,
+    import_target_hints: &HashMap<String, Vec<String>>,
 ) -> Vec<SymbolKey> {
     let mut keys = Vec::new();
 
     if let Some(qualifier) = qualifier
-        && let Some(import_path) = import_target_hints.get(qualifier)
+        && let Some(target_files) = import_target_hints.get(qualifier)
     {
-        for candidate_file in go_import_candidate_paths(import_path) {
-            keys.push(SymbolKey {
-                symbol: symbol.to_string(),
-                qualified_symbol: Some(format!("{language}:{candidate_file}::{symbol}")),
-                file_path: Some(candidate_file),
-                language: Some(language.to_string()),
-            });
+        for target_file in target_files {
+            keys.push(package_member_key(target_file, language, symbol));
         }
     }
 
@@ -458,6 +510,64 @@ fn call_target_symbol_keys(
     dedupe_symbol_keys(keys)
 }
 
+fn package_member_key(target_file: &str, language: &str, symbol: &str) -> SymbolKey {
+    SymbolKey {
+        symbol: symbol.to_string(),
+        qualified_symbol: Some(format!("{language}:{target_file}::{symbol}")),
+        file_path: Some(target_file.to_string()),
+        language: Some(language.to_string()),
+    }
+}
+
+/// Links an import to the package members this file actually uses (`pkg.Member`) in every file
+/// of the imported package. Without resolved members the edge falls back to the package name.
+fn import_edge_target_keys(
+    binding: &GoImportBinding,
+    used_members: Option<&BTreeSet<String>>,
+    language: &str,
+) -> Vec<SymbolKey> {
+    let mut keys = Vec::new();
+    if let Some(used_members) = used_members {
+        for target_file in &binding.target_files {
+            for member in used_members {
+                keys.push(package_member_key(target_file, language, member));
+            }
+        }
+    }
+    if keys.is_empty() {
+        keys.push(language_symbol_key(&binding.imported_symbol, language));
+    }
+    keys
+}
+
+fn imported_member_symbols(root: Node<'_>, source: &str) -> HashMap<String, BTreeSet<String>> {
+    let mut members: HashMap<String, BTreeSet<String>> = HashMap::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        let fields = match node.kind() {
+            "selector_expression" => Some(("operand", "field")),
+            "qualified_type" => Some(("package", "name")),
+            _ => None,
+        };
+        if let Some((qualifier_field, member_field)) = fields
+            && let Some(qualifier_node) = node.child_by_field_name(qualifier_field)
+            && matches!(qualifier_node.kind(), "identifier" | "package_identifier")
+            && let Some(qualifier) = node_text(qualifier_node, source)
+            && let Some(member) = node
+                .child_by_field_name(member_field)
+                .and_then(|member_node| node_text(member_node, source))
+        {
+            members.entry(qualifier).or_default().insert(member);
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            stack.push(child);
+        }
+    }
+    members
+}
+
 fn go_import_candidate_paths(import_path: &str) -> Vec<String> {
     if import_path.ends_with(".go") {
         return vec![import_path.to_string()];
@@ -503,14 +613,19 @@ fn dedupe_symbol_keys(keys: Vec<SymbolKey>) -> Vec<SymbolKey> {
     deduped
 }
 
-fn import_target_hints(file_path: &str, source: &str, root: Node<'_>) -> HashMap<String, String> {
+fn import_target_hints(
+    file_path: &str,
+    source: &str,
+    root: Node<'_>,
+    workspace: &WorkspaceLayout,
+) -> HashMap<String, Vec<String>> {
     let mut hints = HashMap::new();
     let mut stack = vec![root];
     while let Some(node) = stack.pop() {
         if node.kind() == "import_spec"
-            && let Some(binding) = import_binding(node, source, file_path)
+            && let Some(binding) = import_binding(node, source, file_path, workspace)
         {
-            hints.insert(binding.local_symbol, binding.import_path);
+            hints.insert(binding.local_symbol, binding.target_files);
         }
 
         let mut cursor = node.walk();
@@ -521,11 +636,16 @@ fn import_target_hints(file_path: &str, source: &str, root: Node<'_>) -> HashMap
     hints
 }
 
-fn import_binding(node: Node<'_>, source: &str, file_path: &str) -> Option<GoImportBinding> {
+fn import_binding(
+    node: Node<'_>,
+    source: &str,
+    file_path: &str,
+    workspace: &WorkspaceLayout,
+) -> Option<GoImportBinding> {
     let path_node = node.child_by_field_name("path")?;
     let import_literal = node_text(path_node, source)?;
     let import_specifier = unquote_go_string(&import_literal)?;
-    let import_path = resolve_go_import_path(file_path, &import_specifier)?;
+    let target_files = go_import_target_files(file_path, &import_specifier, workspace)?;
 
     let imported_symbol = package_name_from_import(&import_specifier)?;
     let local_symbol = match node.child_by_field_name("name") {
@@ -539,10 +659,24 @@ fn import_binding(node: Node<'_>, source: &str, file_path: &str) -> Option<GoImp
     Some(GoImportBinding {
         local_symbol,
         imported_symbol,
-        import_path,
+        target_files,
     })
 }
 
+/// Resolves an import to package files through the workspace's `go.mod` module paths, falling
+/// back to repo-relative path guessing when no module in the workspace owns the import.
+fn go_import_target_files(
+    from_file_path: &str,
+    import_specifier: &str,
+    workspace: &WorkspaceLayout,
+) -> Option<Vec<String>> {
+    if let Some(package_files) = workspace.go_package_files(import_specifier.trim()) {
+        return Some(package_files);
+    }
+    let import_path = resolve_go_import_path(from_file_path, import_specifier)?;
+    Some(go_import_candidate_paths(&import_path))
+}
+
 fn resolve_go_import_path(from_file_path: &str, import_specifier: &str) -> Option<String> {
     let trimmed = import_specifier.trim();
     if trimmed.is_empty() {
@@ -757,6 +891,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn extract_in_workspace_resolves_module_imports_to_package_files() {
+        let workspace = WorkspaceLayout::from_source_files(&[
+            crate::indexer::files::SourceFile {
+                relative_path: "go.mod".to_string(),
+                bytes: b"module example.com/app\n".to_vec(),
+                content_hash: String::new(),
+                metadata: Default::default(),
+            },
+            crate::indexer::files::SourceFile {
+                relative_path: "pkg/tool/a.go".to_string(),
+                bytes: b"package tool\n".to_vec(),
+                content_hash: String::new(),
+                metadata: Default::default(),
+            },
+            crate::indexer::files::SourceFile {
+                relative_path: "pkg/tool/b.go".to_string(),
+                bytes: b"package tool\n".to_vec(),
+                content_hash: String::new(),
+                metadata: Default::default(),
+            },
+        ]);
+        let source = r#"package main
+
+import t "example.com/app/pkg/tool"
+
+func run(cfg t.Config) {
+    t.Build()
+}
+"#;
+        let unit = GoLanguageAdapter
+            .extract_in_workspace("cmd/main.go", source, &workspace)
+            .expect("go extraction should succeed");
+
+        let targets = |kind: &str| {
+            unit.edges
+                .iter()
+                .filter(|edge| edge.edge_kind == kind)
+                .filter_map(|edge| edge.to_symbol_key.qualified_symbol.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            targets("imports"),
+            vec![
+                "go:pkg/tool/a.go::Build".to_string(),
+                "go:pkg/tool/b.go::Build".to_string(),
+                "go:pkg/tool/a.go::Config".to_string(),
+                "go:pkg/tool/b.go::Config".to_string(),
+            ]
+        );
+        let calls = targets("calls");
+        assert!(calls.contains(&"go:pkg/tool/a.go::Build".to_string()));
+        assert!(calls.contains(&"go:pkg/tool/b.go::Build".to_string()));
+        assert!(!calls.iter().any(|target| target.contains("cmd/pkg")));
+    }
+
     #[test]
     fn adapter_extract_covers_const_var_import_and_call_paths() {
         let source = r#"
@@ -1239,4 +1429,38 @@ func run() { alias.Do() }
             "mismatched source text should trigger selector field empty-symbol guard"
         );
     }
+
+    #[test]
+    fn adapter_extract_records_visibility_from_identifier_case() {
+        let source = r#"
+package main
+
+import "fmt"
+
+type Worker struct{}
+
+func (w Worker) Run() { fmt.Println(w.step()) }
+
+func (w Worker) step() int { return 1 }
+
+var Limit = 1
+
+var offset = 2
+"#;
+        let unit = GoLanguageAdapter
+            .extract("src/main.go", source)
+            .expect("extraction should succeed");
+        let visibility = |symbol: &str| {
+            unit.symbols
+                .iter()
+                .find(|item| item.symbol == symbol)
+                .and_then(|item| item.visibility.clone())
+        };
+        assert_eq!(visibility("Worker").as_deref(), Some("public"));
+        assert_eq!(visibility("Run").as_deref(), Some("public"));
+        assert_eq!(visibility("step").as_deref(), Some("private"));
+        assert_eq!(visibility("Limit").as_deref(), Some("public"));
+        assert_eq!(visibility("offset").as_deref(), Some("private"));
+        assert_eq!(visibility("fmt").as_deref(), Some("private"));
+    }
 }
@@ -0,0 +1,175 @@
+use anyhow::Context;
+use tree_sitter::Parser;
+
+use crate::indexer::languages::typescript::extract_ecmascript_tree;
+use crate::indexer::languages::{ExtractionUnit, LanguageAdapter};
+use crate::indexer::workspace::WorkspaceLayout;
+
+pub struct JavaScriptLanguageAdapter;
+
+impl LanguageAdapter for JavaScriptLanguageAdapter {
+    fn language_id(&self) -> &'static str {
+        "javascript"
+    }
+
+    fn file_extensions(&self) -> &'static [&'static str] {
+        &["js", "jsx", "mjs", "cjs"]
+    }
+
+    fn extract(&self, file_path: &str, source: &str) -> anyhow::Result<ExtractionUnit> {
+        self.extract_in_workspace(file_path, source, &WorkspaceLayout::default())
+    }
+
+    fn extract_in_workspace(
+        &self,
+        file_path: &str,
+        source: &str,
+        workspace: &WorkspaceLayout,
+    ) -> anyhow::Result<ExtractionUnit> {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_javascript::LANGUAGE.into())
+            .context("failed to load tree-sitter javascript language")?;
+
+        let tree = parser
+            .parse(source, None)
+            .context("failed to parse javascript source")?;
+
+        extract_ecmascript_tree(&tree, file_path, source, workspace, self.language_id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol_kinds(unit: &ExtractionUnit) -> Vec<(String, String)> {
+        unit.symbols
+            .iter()
+            .map(|symbol| (symbol.symbol.clone(), symbol.kind.clone()))
+            .collect()
+    }
+
+    fn edge_targets(unit: &ExtractionUnit, edge_kind: &str) -> Vec<(String, Option<String>)> {
+        unit.edges
+            .iter()
+            .filter(|edge| edge.edge_kind == edge_kind)
+            .map(|edge| {
+                (
+                    edge.from_symbol_key.symbol.clone(),
+                    edge.to_symbol_key.qualified_symbol.clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn extracts_esm_declarations_and_contains_edges() {
+        let source = r#"import { format } from "./format";
+
+export function render(value) {
+  return format(value);
+}
+
+export class Widget {
+  draw() {
+    return render(1);
+  }
+}
+
+const paint = () => <Widget />;
+"#;
+        let unit = JavaScriptLanguageAdapter
+            .extract("src/ui/view.jsx", source)
+            .expect("javascript extraction should succeed");
+
+        assert_eq!(
+            symbol_kinds(&unit),
+            vec![
+                ("format".to_string(), "import".to_string()),
+                ("render".to_string(), "function".to_string()),
+                ("Widget".to_string(), "class".to_string()),
+                ("draw".to_string(), "method".to_string()),
+                ("paint".to_string(), "variable".to_string()),
+            ]
+        );
+        assert!(
+            unit.symbols
+                .iter()
+                .all(|symbol| symbol.language == "javascript")
+        );
+        assert_eq!(
+            edge_targets(&unit, "contains"),
+            vec![(
+                "Widget".to_string(),
+                Some("javascript:src/ui/view.jsx::draw".to_string())
+            )]
+        );
+        assert!(edge_targets(&unit, "imports").contains(&(
+            "format".to_string(),
+            Some("javascript:src/ui/format.js::format".to_string())
+        )));
+        assert!(edge_targets(&unit, "calls").contains(&(
+            "render".to_string(),
+            Some("javascript:src/ui/format.js::format".to_string())
+        )));
+    }
+
+    #[test]
+    fn extracts_commonjs_exports_and_require_bindings() {
+        let source = r#"const { loadUser, save: persist } = require("./store");
+const log = require("./log");
+
+exports.handle = function (id) {
+  const user = loadUser(id);
+  log.info(user);
+  return persist(user);
+};
+
+module.exports.close = () => log.flush();
+
+module.exports = {
+  start: function () {
+    return 1;
+  },
+  stop() {
+    return 0;
+  },
+};
+"#;
+        let unit = JavaScriptLanguageAdapter
+            .extract("lib/service.cjs", source)
+            .expect("javascript extraction should succeed");
+
+        assert_eq!(
+            symbol_kinds(&unit),
+            vec![
+                ("loadUser".to_string(), "import".to_string()),
+                ("persist".to_string(), "import".to_string()),
+                ("handle".to_string(), "function".to_string()),
+                ("close".to_string(), "function".to_string()),
+                ("start".to_string(), "function".to_string()),
+                ("stop".to_string(), "method".to_string()),
+            ]
+        );
+
+        let imports = edge_targets(&unit, "imports");
+        assert!(imports.contains(&(
+            "persist".to_string(),
+            Some("javascript:lib/store.js::save".to_string())
+        )));
+
+        let calls = edge_targets(&unit, "calls");
+        for expected in [
+            ("handle", "javascript:lib/store.js::loadUser"),
+            ("handle", "javascript:lib/store.cjs::save"),
+            ("handle", "javascript:lib/log.js::info"),
+            ("close", "javascript:lib/log.mjs::flush"),
+        ] {
+            assert!(
+                calls.contains(&(expected.0.to_string(), Some(expected.1.to_string()))),
+                "missing call edge {expected:?} in {calls:?}"
+            );
+        }
+    }
+}
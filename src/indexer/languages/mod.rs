@@ -1,8 +1,14 @@
+pub mod complexity;
 pub mod go;
+pub mod javascript;
 pub mod python;
 pub mod rust;
 pub mod typescript;
 
+use crate::indexer::workspace::WorkspaceLayout;
+
+pub use complexity::ComplexityMetrics;
+
 #[derive(Debug, Clone)]
 pub struct SymbolKey {
     pub symbol: String,
@@ -23,6 +29,17 @@ pub struct ExtractedSymbol {
     pub end_line: u32,
     pub end_column: u32,
     pub signature: Option<String>,
+    /// Runner path selecting the test this symbol belongs to (Rust: `module::tests::name`);
+    /// `None` for non-test code.
+    pub test_path: Option<String>,
+    /// Complexity metrics for functions and methods; `None` for other kinds.
+    pub metrics: Option<ComplexityMetrics>,
+    /// Language-level visibility: `pub`/`pub(crate)`/`pub(super)`/`private` (Rust),
+    /// `export`/`export default`/`private` (TypeScript, JavaScript), `public`/`private`
+    /// (Go, Python).
+    pub visibility: Option<String>,
+    /// Documentation attached to the definition; filled by the indexer from the source text.
+    pub doc_comment: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -52,4 +69,15 @@ pub trait LanguageAdapter {
     fn language_id(&self) -> &'static str;
     fn file_extensions(&self) -> &'static [&'static str];
     fn extract(&self, file_path: &str, source: &str) -> anyhow::Result<ExtractionUnit>;
+
+    /// Extracts with access to repository-level layout (module manifests, package roots).
+    /// Adapters that resolve imports across packages override this; the default ignores it.
+    fn extract_in_workspace(
+        &self,
+        file_path: &str,
+        source: &str,
+        _workspace: &WorkspaceLayout,
+    ) -> anyhow::Result<ExtractionUnit> {
+        self.extract(file_path, source)
+    }
 }
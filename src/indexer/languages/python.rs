@@ -1,14 +1,39 @@
 use anyhow::Context;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use tree_sitter::{Node, Parser};
 
+use crate::indexer::languages::complexity::{ComplexityRules, function_metrics};
 use crate::indexer::languages::{
     ExtractedEdge, ExtractedReference, ExtractedSymbol, ExtractionUnit, LanguageAdapter, SymbolKey,
 };
+use crate::indexer::workspace::WorkspaceLayout;
 
 pub struct PythonLanguageAdapter;
 
+const COMPLEXITY_RULES: ComplexityRules = ComplexityRules {
+    parameter_kinds: &[
+        "identifier",
+        "typed_parameter",
+        "default_parameter",
+        "typed_default_parameter",
+        "list_splat_pattern",
+        "dictionary_splat_pattern",
+    ],
+    receiver_names: &["self", "cls"],
+    branch_kinds: &[
+        "if_statement",
+        "for_statement",
+        "while_statement",
+        "except_clause",
+    ],
+    flat_branch_kinds: &["elif_clause", "conditional_expression", "if_clause"],
+    switch_kinds: &["match_statement"],
+    arm_kinds: &["case_clause"],
+    logical_kinds: &["boolean_operator"],
+    nested_definition_kinds: &["function_definition", "class_definition"],
+};
+
 #[derive(Debug, Clone)]
 struct ImportCallHint {
     import_path: String,
@@ -43,6 +68,15 @@ impl LanguageAdapter for PythonLanguageAdapter {
     }
 
     fn extract(&self, file_path: &str, source: &str) -> anyhow::Result<ExtractionUnit> {
+        self.extract_in_workspace(file_path, source, &WorkspaceLayout::default())
+    }
+
+    fn extract_in_workspace(
+        &self,
+        file_path: &str,
+        source: &str,
+        workspace: &WorkspaceLayout,
+    ) -> anyhow::Result<ExtractionUnit> {
         let mut parser = Parser::new();
         parser
             .set_language(&tree_sitter_python::LANGUAGE.into())
@@ -53,8 +87,8 @@ impl LanguageAdapter for PythonLanguageAdapter {
             .context("failed to parse python source")?;
 
         let language = self.language_id().to_string();
-        let import_target_hints = import_target_hints(file_path, source);
-        let import_call_hints = import_call_hints(file_path, source);
+        let import_target_hints = import_target_hints(file_path, source, workspace);
+        let import_call_hints = import_call_hints(file_path, source, workspace);
         let mut symbols = Vec::new();
         let mut references = Vec::new();
         let mut edges = Vec::new();
@@ -140,6 +174,10 @@ impl LanguageAdapter for PythonLanguageAdapter {
                             end_line: binding.end_line,
                             end_column: binding.end_column,
                             signature: Some(format!("import {}", binding.local_symbol)),
+                            test_path: None,
+                            metrics: None,
+                            visibility: None,
+                            doc_comment: None,
                         });
                         edges.push(ExtractedEdge {
                             from_symbol_key: scoped_symbol_key(
@@ -164,6 +202,11 @@ impl LanguageAdapter for PythonLanguageAdapter {
         }
 
         symbols.extend(module_constants(file_path, &language, source));
+        let exported_names = dunder_all_names(tree.root_node(), source);
+        for symbol in &mut symbols {
+            symbol.visibility =
+                Some(python_visibility(symbol, exported_names.as_ref()).to_string());
+        }
 
         symbols.sort_by(|left, right| {
             left.start_line
@@ -257,6 +300,11 @@ fn push_named_definition(
         end_line,
         end_column,
         signature: signature_summary(node, source),
+        test_path: None,
+        metrics: matches!(kind, "function" | "method")
+            .then(|| function_metrics(node, source, &COMPLEXITY_RULES)),
+        visibility: None,
+        doc_comment: None,
     });
     Some(symbol)
 }
@@ -425,6 +473,61 @@ fn enclosing_callable_name(node: Node<'_>, source: &str) -> Option<String> {
     None
 }
 
+/// Names listed in a module-level `__all__` (assigned or extended with `+=`), if the module
+/// declares one.
+fn dunder_all_names(root: Node<'_>, source: &str) -> Option<HashSet<String>> {
+    let mut names: Option<HashSet<String>> = None;
+    let mut cursor = root.walk();
+    for statement in root.named_children(&mut cursor) {
+        let Some(assignment) = statement.named_child(0) else {
+            continue;
+        };
+        if !matches!(assignment.kind(), "assignment" | "augmented_assignment")
+            || assignment
+                .child_by_field_name("left")
+                .and_then(|left| node_text(left, source))
+                .as_deref()
+                != Some("__all__")
+        {
+            continue;
+        }
+        let Some(listed) = assignment.child_by_field_name("right") else {
+            continue;
+        };
+        let entries = names.get_or_insert_with(HashSet::new);
+        let mut list_cursor = listed.walk();
+        for entry in listed.named_children(&mut list_cursor) {
+            if entry.kind() == "string"
+                && let Some(text) = node_text(entry, source)
+            {
+                entries.insert(text.trim_matches(['"', '\'']).to_string());
+            }
+        }
+    }
+    names
+}
+
+/// Module-level names follow `__all__` when the module declares it. Otherwise, and for class
+/// members, a leading underscore marks a name private; dunder names stay public.
+fn python_visibility(symbol: &ExtractedSymbol, exported: Option<&HashSet<String>>) -> &'static str {
+    let name = symbol.symbol.as_str();
+    if symbol.container.is_none()
+        && let Some(exported) = exported
+    {
+        return if exported.contains(name) {
+            "public"
+        } else {
+            "private"
+        };
+    }
+    let is_dunder = name.len() > 4 && name.starts_with("__") && name.ends_with("__");
+    if symbol.kind == "import" || (name.starts_with('_') && !is_dunder) {
+        "private"
+    } else {
+        "public"
+    }
+}
+
 fn module_constants(file_path: &str, language: &str, source: &str) -> Vec<ExtractedSymbol> {
     let mut symbols = Vec::new();
 
@@ -454,6 +557,10 @@ fn module_constants(file_path: &str, language: &str, source: &str) -> Vec<Extrac
             end_line: line_no,
             end_column: start_column + candidate.len() as u32,
             signature: Some(line.trim().to_string()),
+            test_path: None,
+            metrics: None,
+            visibility: None,
+            doc_comment: None,
         });
     }
 
@@ -564,11 +671,15 @@ fn import_bindings(node: Node<'_>, source: &str) -> Vec<ImportBinding> {
     bindings
 }
 
-fn import_target_hints(file_path: &str, source: &str) -> HashMap<String, String> {
+fn import_target_hints(
+    file_path: &str,
+    source: &str,
+    workspace: &WorkspaceLayout,
+) -> HashMap<String, String> {
     let mut hints = HashMap::new();
 
-    for line in source.lines() {
-        let trimmed = line.trim();
+    for line in logical_import_lines(source) {
+        let trimmed = line.as_str();
         if let Some(rest) = trimmed.strip_prefix("import ") {
             for specifier in rest.split(',') {
                 let specifier = specifier.trim();
@@ -581,10 +692,19 @@ fn import_target_hints(file_path: &str, source: &str) -> HashMap<String, String>
                     } else {
                         (specifier, None)
                     };
-                let Some(import_path) = resolve_python_import_path(file_path, imported_module)
+                let Some(import_path) =
+                    resolve_python_import_path(file_path, imported_module, workspace)
                 else {
                     continue;
                 };
+                let dotted = imported_module.contains('.')
+                    && imported_module.split('.').all(|segment| {
+                        first_identifier(segment).is_some_and(|identifier| identifier == segment)
+                    });
+                if local_alias.is_none() && dotted {
+                    // `import acme.db.models` makes `acme.db.models.load()` callable by its full path.
+                    hints.insert(imported_module.to_string(), import_path.clone());
+                }
                 let local_symbol = local_alias
                     .map(str::to_string)
                     .or_else(|| first_identifier(imported_module))
@@ -615,11 +735,18 @@ fn import_target_hints(file_path: &str, source: &str) -> HashMap<String, String>
                 if local_symbol.is_empty() {
                     continue;
                 }
-                let import_path = resolve_python_import_path(
-                    file_path,
-                    &format!("{}.{}", module_name.trim(), imported_name.trim()),
-                )
-                .or_else(|| resolve_python_import_path(file_path, module_name.trim()));
+                let module_name = module_name.trim();
+                let submodule_name = if module_name.ends_with('.') {
+                    format!("{module_name}{}", imported_name.trim())
+                } else {
+                    format!("{module_name}.{}", imported_name.trim())
+                };
+                let import_path =
+                    existing_python_import_path(file_path, &submodule_name, workspace)
+                        .or_else(|| existing_python_import_path(file_path, module_name, workspace))
+                        .or_else(|| {
+                            resolve_python_import_path(file_path, &submodule_name, workspace)
+                        });
                 let Some(import_path) = import_path else {
                     continue;
                 };
@@ -631,18 +758,23 @@ fn import_target_hints(file_path: &str, source: &str) -> HashMap<String, String>
     hints
 }
 
-fn import_call_hints(file_path: &str, source: &str) -> HashMap<String, ImportCallHint> {
+fn import_call_hints(
+    file_path: &str,
+    source: &str,
+    workspace: &WorkspaceLayout,
+) -> HashMap<String, ImportCallHint> {
     let mut hints = HashMap::new();
 
-    for line in source.lines() {
-        let trimmed = line.trim();
-        let Some(rest) = trimmed.strip_prefix("from ") else {
+    for line in logical_import_lines(source) {
+        let Some(rest) = line.strip_prefix("from ") else {
             continue;
         };
         let Some((module_name, imports_part)) = rest.split_once(" import ") else {
             continue;
         };
-        let Some(import_path) = resolve_python_import_path(file_path, module_name.trim()) else {
+        let Some(import_path) =
+            resolve_python_import_path(file_path, module_name.trim(), workspace)
+        else {
             continue;
         };
 
@@ -676,14 +808,62 @@ fn import_call_hints(file_path: &str, source: &str) -> HashMap<String, ImportCal
     hints
 }
 
-fn resolve_python_import_path(from_file_path: &str, module_name: &str) -> Option<String> {
+/// Splits `source` into trimmed lines with comments removed, folding each parenthesized or
+/// backslash-continued import onto one line so `from pkg import (\n    a,\n    b,\n)` reads as
+/// `from pkg import a, b`.
+fn logical_import_lines(source: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut pending: Option<String> = None;
+    for line in source.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let mut statement = match pending.take() {
+            Some(mut statement) => {
+                statement.push(' ');
+                statement.push_str(line);
+                statement
+            }
+            None => line.to_string(),
+        };
+        if !(statement.starts_with("import ") || statement.starts_with("from ")) {
+            lines.push(statement);
+            continue;
+        }
+        let open_paren = statement.matches('(').count() > statement.matches(')').count();
+        if open_paren || statement.ends_with('\\') {
+            statement = statement.trim_end_matches('\\').trim_end().to_string();
+            pending = Some(statement);
+            continue;
+        }
+        lines.push(
+            statement
+                .split(['(', ')'])
+                .map(str::trim)
+                .collect::<Vec<_>>()
+                .join(" ")
+                .trim()
+                .to_string(),
+        );
+    }
+    lines.extend(pending);
+    lines
+}
+
+fn resolve_python_import_path(
+    from_file_path: &str,
+    module_name: &str,
+    workspace: &WorkspaceLayout,
+) -> Option<String> {
     let module_path = module_name.trim();
     if module_path.is_empty() {
         return None;
     }
+    if let Some(import_path) = existing_python_import_path(from_file_path, module_path, workspace) {
+        return Some(import_path);
+    }
 
     if module_path.starts_with('.') {
-        return resolve_relative_python_import_path(from_file_path, module_path);
+        return resolve_relative_python_module_path(from_file_path, module_path)
+            .map(|resolved| format!("{resolved}.py"));
     }
 
     let normalized_module_path = module_path.replace('.', "/");
@@ -700,7 +880,24 @@ fn resolve_python_import_path(from_file_path: &str, module_name: &str) -> Option
     }
 }
 
-fn resolve_relative_python_import_path(from_file_path: &str, module_path: &str) -> Option<String> {
+/// Resolves a module name only when it maps to an indexed `module.py` or `module/__init__.py`.
+fn existing_python_import_path(
+    from_file_path: &str,
+    module_name: &str,
+    workspace: &WorkspaceLayout,
+) -> Option<String> {
+    let module_path = module_name.trim();
+    if module_path.starts_with('.') {
+        let resolved = resolve_relative_python_module_path(from_file_path, module_path)?;
+        workspace.python_package_file(&resolved)
+    } else {
+        workspace.python_module_file(from_file_path, module_path)
+    }
+}
+
+/// Returns the extensionless path a relative module name points at, e.g. `src/pkg/util` for
+/// `.util` imported from `src/pkg/consumer.py`.
+fn resolve_relative_python_module_path(from_file_path: &str, module_path: &str) -> Option<String> {
     let levels = module_path.chars().take_while(|ch| *ch == '.').count();
     if levels == 0 {
         return None;
@@ -717,7 +914,6 @@ fn resolve_relative_python_import_path(from_file_path: &str, module_path: &str)
             resolved_path.push(segment);
         }
     }
-    resolved_path.set_extension("py");
     Some(resolved_path.to_string_lossy().replace('\\', "/"))
 }
 
@@ -790,14 +986,64 @@ mod tests {
 
     #[test]
     fn resolve_python_import_path_supports_relative_module_paths() {
-        let resolved = resolve_python_import_path("src/pkg/consumer.py", ".util");
+        let resolved =
+            resolve_python_import_path("src/pkg/consumer.py", ".util", &WorkspaceLayout::default());
         assert_eq!(resolved.as_deref(), Some("src/pkg/util.py"));
     }
 
+    #[test]
+    fn import_hints_resolve_through_workspace_package_roots() {
+        let source_file = |relative_path: &str, contents: &str| crate::indexer::files::SourceFile {
+            relative_path: relative_path.to_string(),
+            bytes: contents.as_bytes().to_vec(),
+            content_hash: String::new(),
+            metadata: Default::default(),
+        };
+        let workspace = WorkspaceLayout::from_source_files(&[
+            source_file("svc/setup.cfg", "[options]\npackage_dir =\n    =src\n"),
+            source_file("svc/src/acme/__init__.py", ""),
+            source_file("svc/src/acme/db/__init__.py", ""),
+            source_file("svc/src/acme/db/models.py", ""),
+            source_file("svc/src/acme/api/__init__.py", ""),
+            source_file("svc/src/acme/api/helpers.py", ""),
+        ]);
+        let source = "\
+import acme.db as db
+from acme.db.models import load_user
+from acme.db import models
+from . import helpers
+";
+        let target_hints = import_target_hints("svc/src/acme/api/views.py", source, &workspace);
+        assert_eq!(
+            target_hints.get("db").map(String::as_str),
+            Some("svc/src/acme/db/__init__.py")
+        );
+        assert_eq!(
+            target_hints.get("load_user").map(String::as_str),
+            Some("svc/src/acme/db/models.py")
+        );
+        assert_eq!(
+            target_hints.get("models").map(String::as_str),
+            Some("svc/src/acme/db/models.py")
+        );
+        assert_eq!(
+            target_hints.get("helpers").map(String::as_str),
+            Some("svc/src/acme/api/helpers.py")
+        );
+
+        let call_hints = import_call_hints("svc/tests/test_models.py", source, &workspace);
+        assert_eq!(
+            call_hints
+                .get("load_user")
+                .map(|hint| hint.import_path.as_str()),
+            Some("svc/src/acme/db/models.py")
+        );
+    }
+
     #[test]
     fn import_call_hints_capture_relative_from_import_paths() {
         let source = "from .util import helper\n\n\ndef run():\n    return helper()\n";
-        let hints = import_call_hints("src/pkg/consumer.py", source);
+        let hints = import_call_hints("src/pkg/consumer.py", source, &WorkspaceLayout::default());
         let hint = hints
             .get("helper")
             .expect("helper import should produce call hint");
@@ -813,7 +1059,7 @@ from .local import run as local_run, *, helper
 from pkg.mod import Build as MakeBuild
 "#;
 
-        let hints = import_target_hints("src/app/main.py", source);
+        let hints = import_target_hints("src/app/main.py", source, &WorkspaceLayout::default());
         assert_eq!(
             hints.get("tools"),
             Some(&"src/pkg/tools.py".to_string()),
@@ -834,7 +1080,7 @@ from pkg.mod import Build as MakeBuild
             "star imports should be skipped from import hints"
         );
 
-        let call_hints = import_call_hints("src/app/main.py", source);
+        let call_hints = import_call_hints("src/app/main.py", source, &WorkspaceLayout::default());
         let make_build = call_hints
             .get("MakeBuild")
             .expect("from-import alias should produce call hint");
@@ -842,6 +1088,39 @@ from pkg.mod import Build as MakeBuild
         assert_eq!(make_build.imported_symbol, "Build");
     }
 
+    #[test]
+    fn import_hints_cover_dotted_modules_and_parenthesized_imports() {
+        let source = r#"
+import acme.db.models
+from .db import (
+    models,  # the table layer
+    schema as tables,
+)
+from acme.db import \
+    helpers
+"#;
+
+        let hints = import_target_hints("acme/service.py", source, &WorkspaceLayout::default());
+        assert_eq!(
+            hints.get("acme.db.models"),
+            Some(&"acme/acme/db/models.py".to_string()),
+            "dotted imports should be callable through their full module path"
+        );
+        assert_eq!(hints.get("models"), Some(&"acme/db/models.py".to_string()));
+        assert_eq!(hints.get("tables"), Some(&"acme/db/schema.py".to_string()));
+        assert_eq!(
+            hints.get("helpers"),
+            Some(&"acme/acme/db/helpers.py".to_string())
+        );
+
+        let call_hints = import_call_hints("acme/service.py", source, &WorkspaceLayout::default());
+        let tables = call_hints
+            .get("tables")
+            .expect("parenthesized alias should produce call hint");
+        assert_eq!(tables.import_path, "acme/db.py");
+        assert_eq!(tables.imported_symbol, "schema");
+    }
+
     #[test]
     fn module_constant_and_name_validation_cover_rejections() {
         let constants = module_constants(
@@ -869,17 +1148,24 @@ from pkg.mod import Build as MakeBuild
 
     #[test]
     fn resolve_python_import_path_covers_empty_relative_and_root_cases() {
-        assert_eq!(resolve_python_import_path("src/app/main.py", ""), None);
         assert_eq!(
-            resolve_python_import_path("src/app/main.py", "pkg.mod"),
+            resolve_python_import_path("src/app/main.py", "", &WorkspaceLayout::default()),
+            None
+        );
+        assert_eq!(
+            resolve_python_import_path("src/app/main.py", "pkg.mod", &WorkspaceLayout::default()),
             Some("src/pkg/mod.py".to_string())
         );
         assert_eq!(
-            resolve_python_import_path("src/app/main.py", ".pkg.util"),
+            resolve_python_import_path("src/app/main.py", ".pkg.util", &WorkspaceLayout::default()),
             Some("src/app/pkg/util.py".to_string())
         );
         assert_eq!(
-            resolve_python_import_path("src/app/main.py", "..shared.util"),
+            resolve_python_import_path(
+                "src/app/main.py",
+                "..shared.util",
+                &WorkspaceLayout::default()
+            ),
             Some("src/shared/util.py".to_string())
         );
     }
@@ -900,8 +1186,9 @@ def run():
         let call_nodes = find_nodes_of_kind(root, "call");
         let mut references = Vec::new();
         let mut edges = Vec::new();
-        let target_hints = import_target_hints("src/app/main.py", source);
-        let call_hints = import_call_hints("src/app/main.py", source);
+        let target_hints =
+            import_target_hints("src/app/main.py", source, &WorkspaceLayout::default());
+        let call_hints = import_call_hints("src/app/main.py", source, &WorkspaceLayout::default());
         for call in call_nodes {
             let function_node = call
                 .child_by_field_name("function")
@@ -1064,7 +1351,7 @@ def run():
             Some("run"),
             "src/app/main.py",
             "python",
-            &import_target_hints("src/app/main.py", source),
+            &import_target_hints("src/app/main.py", source, &WorkspaceLayout::default()),
             &HashMap::new(),
             &mut references,
             &mut edges,
@@ -1082,8 +1369,8 @@ def run():
             Some("run"),
             "src/app/main.py",
             "python",
-            &import_target_hints("src/app/main.py", source),
-            &import_call_hints("src/app/main.py", source),
+            &import_target_hints("src/app/main.py", source, &WorkspaceLayout::default()),
+            &import_call_hints("src/app/main.py", source, &WorkspaceLayout::default()),
             &mut recursive_refs,
             &mut recursive_edges,
         );
@@ -1123,7 +1410,8 @@ def run():
 import pkg.tools as tools, , unknown as \n\
 from pkg.mod import helper as local, *, ... as bad, value as \n\
 from missingline\n";
-        let hints = import_target_hints("src/app/main.py", hint_source);
+        let hints =
+            import_target_hints("src/app/main.py", hint_source, &WorkspaceLayout::default());
         assert_eq!(
             hints.get("tools"),
             Some(&"src/pkg/tools.py".to_string()),
@@ -1134,18 +1422,22 @@ from missingline\n";
 from pkg.mod import helper as local, value as \n\
 from  import missing\n\
 from missingline\n";
-        let call_hints = import_call_hints("src/app/main.py", call_hint_source);
+        let call_hints = import_call_hints(
+            "src/app/main.py",
+            call_hint_source,
+            &WorkspaceLayout::default(),
+        );
         assert!(
             call_hints.contains_key("local"),
             "valid from-import call hints should remain after malformed lines are skipped"
         );
 
         assert_eq!(
-            resolve_python_import_path("", "pkg.mod"),
+            resolve_python_import_path("", "pkg.mod", &WorkspaceLayout::default()),
             Some("pkg/mod.py".to_string())
         );
         assert_eq!(
-            resolve_relative_python_import_path("src/app/main.py", "pkg.mod"),
+            resolve_relative_python_module_path("src/app/main.py", "pkg.mod"),
             None
         );
 
@@ -1221,6 +1513,7 @@ import .... as alias\n\
 import pkg.mod as \n\
 from pkg.mod import value as \n\
 from ....pkg import value\n",
+            &WorkspaceLayout::default(),
         );
         assert!(
             !hints.contains_key(""),
@@ -1230,28 +1523,35 @@ from ....pkg import value\n",
         let call_hints = import_call_hints(
             "src/app/main.py",
             "from pkg.mod import ... as alias, value as \n",
+            &WorkspaceLayout::default(),
         );
         assert!(
             !call_hints.contains_key(""),
             "call hints should ignore malformed aliases that normalize to empty symbols"
         );
 
-        let malformed_only_hints =
-            import_target_hints("src/app/main.py", "import ...\nfrom pkg.mod import ...\n");
+        let malformed_only_hints = import_target_hints(
+            "src/app/main.py",
+            "import ...\nfrom pkg.mod import ...\n",
+            &WorkspaceLayout::default(),
+        );
         assert!(
             malformed_only_hints.is_empty(),
             "malformed import symbols should be ignored when local names are empty"
         );
-        let malformed_only_call_hints =
-            import_call_hints("src/app/main.py", "from pkg.mod import ...\n");
+        let malformed_only_call_hints = import_call_hints(
+            "src/app/main.py",
+            "from pkg.mod import ...\n",
+            &WorkspaceLayout::default(),
+        );
         assert!(
             malformed_only_call_hints.is_empty(),
             "malformed from-import call hints should be skipped when local symbols are empty"
         );
 
         assert_eq!(
-            resolve_relative_python_import_path("src/app/main.py", ".pkg.util"),
-            Some("src/app/pkg/util.py".to_string())
+            resolve_relative_python_module_path("src/app/main.py", ".pkg.util"),
+            Some("src/app/pkg/util".to_string())
         );
 
         let blank_tree = parse_python_root("   \n");
@@ -1352,4 +1652,33 @@ def run():
             Some("def run():".to_string())
         );
     }
+
+    #[test]
+    fn adapter_extract_records_visibility_from_underscores_and_dunder_all() {
+        let visibilities = |source: &str| {
+            PythonLanguageAdapter
+                .extract("src/app/main.py", source)
+                .expect("python extraction should succeed")
+                .symbols
+                .into_iter()
+                .map(|item| (item.symbol, item.visibility.unwrap_or_default()))
+                .collect::<HashMap<_, _>>()
+        };
+
+        let without_all = visibilities(
+            "import os\n\nclass Worker:\n    def _step(self):\n        pass\n\n    def __init__(self):\n        pass\n\ndef _helper():\n    pass\n",
+        );
+        assert_eq!(without_all["os"], "private");
+        assert_eq!(without_all["Worker"], "public");
+        assert_eq!(without_all["_step"], "private");
+        assert_eq!(without_all["__init__"], "public");
+        assert_eq!(without_all["_helper"], "private");
+
+        let with_all = visibilities(
+            "__all__ = [\"run\"]\n__all__ += [\"_extra\"]\n\ndef run():\n    pass\n\ndef stop():\n    pass\n\ndef _extra():\n    pass\n",
+        );
+        assert_eq!(with_all["run"], "public");
+        assert_eq!(with_all["stop"], "private");
+        assert_eq!(with_all["_extra"], "public");
+    }
 }
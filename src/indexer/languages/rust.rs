@@ -2,7 +2,8 @@ use crate::indexer::languages::{
     ExtractedEdge, ExtractedReference, ExtractedSymbol, ExtractionUnit, LanguageAdapter, SymbolKey,
 };
 use crate::indexer::rust_ast;
-use std::collections::HashSet;
+use crate::indexer::workspace::WorkspaceLayout;
+use std::collections::{HashMap, HashSet};
 
 pub struct RustLanguageAdapter;
 
@@ -16,8 +17,18 @@ impl LanguageAdapter for RustLanguageAdapter {
     }
 
     fn extract(&self, file_path: &str, source: &str) -> anyhow::Result<ExtractionUnit> {
+        self.extract_in_workspace(file_path, source, &WorkspaceLayout::default())
+    }
+
+    fn extract_in_workspace(
+        &self,
+        file_path: &str,
+        source: &str,
+        workspace: &WorkspaceLayout,
+    ) -> anyhow::Result<ExtractionUnit> {
         let (definitions, references) = rust_ast::extract_rust_items(source)?;
         let language = self.language_id().to_string();
+        let module_context = ModuleContext::new(file_path, source, workspace);
 
         let mut symbols = Vec::new();
         let mut references_out = Vec::new();
@@ -38,6 +49,12 @@ impl LanguageAdapter for RustLanguageAdapter {
                 end_line: definition.end_line,
                 end_column: definition.end_column,
                 signature: definition.signature,
+                test_path: definition
+                    .test_path
+                    .map(|path| module_context.crate_test_path(&path)),
+                metrics: definition.metrics,
+                visibility: Some(definition.visibility),
+                doc_comment: None,
             };
             symbols.push(extracted_symbol);
 
@@ -62,12 +79,13 @@ impl LanguageAdapter for RustLanguageAdapter {
             if let Some(caller_symbol) = reference.caller {
                 let from_symbol_key = scoped_symbol_key(file_path, &language, &caller_symbol);
                 for to_symbol_key in qualified_target_symbol_keys(
-                    file_path,
+                    &module_context,
                     &language,
                     source,
                     reference.line,
                     reference.column,
                     &reference.symbol,
+                    &reference.modules,
                 ) {
                     edges.push(ExtractedEdge {
                         from_symbol_key: from_symbol_key.clone(),
@@ -99,31 +117,165 @@ fn scoped_symbol_key(file_path: &str, language: &str, symbol: &str) -> SymbolKey
     }
 }
 
+/// Where a file sits inside its crate, used to turn `crate::`/`super::`/`self::` and
+/// external-crate paths into candidate file paths.
+struct ModuleContext<'a> {
+    caller_file_path: &'a str,
+    workspace: &'a WorkspaceLayout,
+    crate_root: String,
+    root_file_names: Vec<String>,
+    module_segments: Vec<String>,
+    use_aliases: HashMap<String, Vec<String>>,
+}
+
+impl<'a> ModuleContext<'a> {
+    fn new(caller_file_path: &'a str, source: &str, workspace: &'a WorkspaceLayout) -> Self {
+        let (crate_root, root_file_names, module_segments) =
+            match workspace.rust_crate_for_file(caller_file_path) {
+                Some(krate) => (
+                    krate.source_dir.clone(),
+                    crate_root_file_names(&krate.root_files),
+                    module_segments_within(caller_file_path, &krate.source_dir),
+                ),
+                None => (
+                    crate_root_prefix(caller_file_path).to_string(),
+                    default_root_file_names(),
+                    current_module_segments(caller_file_path),
+                ),
+            };
+        Self {
+            caller_file_path,
+            workspace,
+            crate_root,
+            root_file_names,
+            module_segments,
+            use_aliases: use_aliases(source),
+        }
+    }
+}
+
+impl ModuleContext<'_> {
+    /// Prefixes an in-file test path with the file's module path, giving the filter that
+    /// `cargo test` matches against. Files under `src/bin/` and top-level files under `tests/`
+    /// are crate roots of their own.
+    fn crate_test_path(&self, in_file_path: &str) -> String {
+        let skipped = if is_integration_test_root(self.caller_file_path) {
+            self.module_segments.len()
+        } else if self
+            .module_segments
+            .first()
+            .is_some_and(|first| first == "bin")
+        {
+            2
+        } else {
+            0
+        };
+        self.module_segments
+            .iter()
+            .skip(skipped)
+            .map(String::as_str)
+            .chain(in_file_path.split("::"))
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>()
+            .join("::")
+    }
+}
+
+fn is_integration_test_root(file_path: &str) -> bool {
+    let (directory, _) = file_path.rsplit_once('/').unwrap_or(("", file_path));
+    (directory == "tests" || directory.ends_with("/tests"))
+        && !directory.split('/').any(|part| part == "src")
+}
+
+fn default_root_file_names() -> Vec<String> {
+    vec!["lib.rs".to_string(), "main.rs".to_string()]
+}
+
+fn crate_root_file_names(root_files: &[String]) -> Vec<String> {
+    let names: Vec<String> = root_files
+        .iter()
+        .filter_map(|root_file| root_file.rsplit('/').next())
+        .map(str::to_string)
+        .collect();
+    if names.is_empty() {
+        default_root_file_names()
+    } else {
+        names
+    }
+}
+
 fn qualified_target_symbol_keys(
-    caller_file_path: &str,
+    context: &ModuleContext<'_>,
     language: &str,
     source: &str,
     line: u32,
     column: u32,
     symbol: &str,
+    inline_modules: &[String],
 ) -> Vec<SymbolKey> {
     let mut keys = Vec::new();
-    if let Some(module_segments) = qualified_module_segments_for_reference(source, line, column) {
-        for module_path in qualified_module_candidate_paths(caller_file_path, &module_segments) {
+    let mut target_symbol = symbol.to_string();
+    let mut resolved_elsewhere = false;
+    let module_segments = match qualified_module_segments_for_reference(source, line, column) {
+        Some(segments) => {
+            resolved_elsewhere = segments.first().is_some_and(|leading| {
+                context.use_aliases.contains_key(leading)
+                    || context
+                        .workspace
+                        .rust_external_crate(context.caller_file_path, leading)
+                        .is_some()
+            });
+            Some(segments)
+        }
+        None => context.use_aliases.get(symbol).and_then(|path| {
+            let (imported, module) = path.split_last()?;
+            target_symbol = imported.clone();
+            resolved_elsewhere = true;
+            (!module.is_empty()).then(|| module.to_vec())
+        }),
+    };
+    let mut fallback = !resolved_elsewhere;
+    if let Some(module_segments) = module_segments {
+        let candidates =
+            qualified_module_candidate_paths(context, &module_segments, inline_modules);
+        let indexed = candidates
+            .iter()
+            .filter(|path| context.workspace.has_rust_file(path))
+            .cloned()
+            .collect::<Vec<_>>();
+        // A `crate::`, `super::`, or `self::` path into an indexed module file binds there first
+        // and falls back to the caller file's scope only when that file lacks the symbol.
+        // Paths that stay in the caller file keep the plain fallback, as unqualified calls do.
+        let in_caller_file = candidates == [context.caller_file_path];
+        let (module_paths, scope_in_caller) = if indexed.is_empty() || in_caller_file {
+            (candidates, false)
+        } else {
+            fallback = false;
+            (indexed, !resolved_elsewhere)
+        };
+        for module_path in module_paths {
             keys.push(SymbolKey {
-                symbol: symbol.to_string(),
-                qualified_symbol: Some(format!("{language}:{module_path}::{symbol}")),
-                file_path: Some(module_path),
+                symbol: target_symbol.clone(),
+                qualified_symbol: Some(format!("{language}:{module_path}::{target_symbol}")),
+                file_path: Some(if scope_in_caller {
+                    context.caller_file_path.to_string()
+                } else {
+                    module_path
+                }),
                 language: Some(language.to_string()),
             });
         }
     }
-    keys.push(SymbolKey {
-        symbol: symbol.to_string(),
-        qualified_symbol: None,
-        file_path: Some(caller_file_path.to_string()),
-        language: Some(language.to_string()),
-    });
+    // Names reached through `use` or another crate are not defined in the caller file; the
+    // same-file fallback would only bind the call to an unrelated `use` declaration there.
+    if fallback || keys.is_empty() {
+        keys.push(SymbolKey {
+            symbol: symbol.to_string(),
+            qualified_symbol: None,
+            file_path: Some(context.caller_file_path.to_string()),
+            language: Some(language.to_string()),
+        });
+    }
     dedupe_symbol_keys(keys)
 }
 
@@ -144,25 +296,46 @@ fn dedupe_symbol_keys(keys: Vec<SymbolKey>) -> Vec<SymbolKey> {
     deduped
 }
 
-fn qualified_module_candidate_paths(caller_file_path: &str, segments: &[String]) -> Vec<String> {
-    let Some(resolved_segments) = resolve_module_segments_for_reference(caller_file_path, segments)
+/// Candidate files for the module a path names. `inline_modules` are the `mod` blocks around
+/// the reference, so `super::` inside `mod tests` names the file's own module, and any path
+/// that stays within the caller file's modules resolves to the caller file itself.
+fn qualified_module_candidate_paths(
+    context: &ModuleContext<'_>,
+    segments: &[String],
+    inline_modules: &[String],
+) -> Vec<String> {
+    let Some((crate_root, root_file_names, mut resolved_segments)) =
+        resolve_module_segments_for_reference(context, segments, inline_modules)
     else {
         return Vec::new();
     };
+    // `Type::method` and `module::Type::CONST` live in the module that defines the type.
+    if let Some(type_index) = resolved_segments
+        .iter()
+        .position(|segment| segment.starts_with(|ch: char| ch.is_ascii_uppercase()))
+    {
+        resolved_segments.truncate(type_index);
+    }
+    if crate_root == context.crate_root
+        && let Some(inline) = resolved_segments.strip_prefix(context.module_segments.as_slice())
+        && inline_modules.starts_with(inline)
+    {
+        return vec![context.caller_file_path.to_string()];
+    }
 
-    let crate_root = crate_root_prefix(caller_file_path);
     let mut candidates = Vec::new();
     if resolved_segments.is_empty() {
-        candidates.push(join_module_candidate_path(crate_root, "lib.rs"));
-        candidates.push(join_module_candidate_path(crate_root, "main.rs"));
+        for root_file_name in root_file_names {
+            candidates.push(join_module_candidate_path(&crate_root, &root_file_name));
+        }
     } else {
         let module_rel = resolved_segments.join("/");
         candidates.push(join_module_candidate_path(
-            crate_root,
+            &crate_root,
             &format!("{module_rel}.rs"),
         ));
         candidates.push(join_module_candidate_path(
-            crate_root,
+            &crate_root,
             &format!("{module_rel}/mod.rs"),
         ));
     }
@@ -177,15 +350,32 @@ fn qualified_module_candidate_paths(caller_file_path: &str, segments: &[String])
     deduped
 }
 
+/// Resolves a path's module segments to `(crate root dir, crate root file names, module
+/// segments relative to that root)`. Leading `use` aliases are expanded first, so
+/// `store::open` after `use crate::store;` resolves like `crate::store::open`.
 fn resolve_module_segments_for_reference(
-    caller_file_path: &str,
+    context: &ModuleContext<'_>,
     segments: &[String],
-) -> Option<Vec<String>> {
-    if segments.is_empty() {
-        return None;
-    }
+    inline_modules: &[String],
+) -> Option<(String, Vec<String>, Vec<String>)> {
+    let first = segments.first()?;
+    let expanded;
+    let segments = match context.use_aliases.get(first) {
+        Some(alias_path) if !matches!(first.as_str(), "crate" | "self" | "super") => {
+            expanded = alias_path
+                .iter()
+                .chain(&segments[1..])
+                .cloned()
+                .collect::<Vec<_>>();
+            expanded.as_slice()
+        }
+        _ => segments,
+    };
 
-    let mut resolved = current_module_segments(caller_file_path);
+    let mut crate_root = context.crate_root.clone();
+    let mut root_file_names = context.root_file_names.clone();
+    let mut resolved = context.module_segments.clone();
+    resolved.extend(inline_modules.iter().cloned());
     let mut index = 0usize;
     match segments.first().map(String::as_str) {
         Some("crate") => {
@@ -201,14 +391,53 @@ fn resolve_module_segments_for_reference(
                 index += 1;
             }
         }
-        _ => {}
+        Some(leading) => {
+            if let Some(krate) = context
+                .workspace
+                .rust_external_crate(context.caller_file_path, leading)
+            {
+                crate_root = krate.source_dir.clone();
+                root_file_names = crate_root_file_names(&krate.root_files);
+                resolved.clear();
+                index = 1;
+            }
+        }
+        None => {}
     }
 
     for segment in &segments[index..] {
         resolved.push(segment.clone());
     }
 
-    Some(resolved)
+    Some((crate_root, root_file_names, resolved))
+}
+
+/// Module path of `file_path` relative to its crate's source directory
+/// (`src/store/mod.rs` -> `["store"]`, `src/store/query.rs` -> `["store", "query"]`).
+fn module_segments_within(file_path: &str, source_dir: &str) -> Vec<String> {
+    let relative = if source_dir.is_empty() {
+        file_path
+    } else {
+        file_path
+            .strip_prefix(source_dir)
+            .map(|rest| rest.trim_start_matches('/'))
+            .unwrap_or(file_path)
+    };
+    let mut parts = relative
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let Some(file_name) = parts.pop() else {
+        return Vec::new();
+    };
+    if !matches!(file_name.as_str(), "lib.rs" | "main.rs" | "mod.rs")
+        && let Some(stem) = file_name.strip_suffix(".rs")
+        && !stem.is_empty()
+    {
+        parts.push(stem.to_string());
+    }
+    parts
 }
 
 fn current_module_segments(caller_file_path: &str) -> Vec<String> {
@@ -259,6 +488,112 @@ fn join_module_candidate_path(crate_root: &str, relative: &str) -> String {
     }
 }
 
+/// Maps every name brought into scope by a `use` declaration to its full path, e.g.
+/// `use crate::store::{self, query::run as run_query};` yields `store -> [crate, store]` and
+/// `run_query -> [crate, store, query, run]`. Glob imports are skipped.
+fn use_aliases(source: &str) -> HashMap<String, Vec<String>> {
+    let mut aliases = HashMap::new();
+    let mut statement: Option<String> = None;
+    for line in source.lines() {
+        let trimmed = line.trim();
+        let pending = match statement.take() {
+            Some(mut pending) => {
+                pending.push(' ');
+                pending.push_str(trimmed);
+                pending
+            }
+            None => {
+                let Some(rest) = strip_use_keyword(trimmed) else {
+                    continue;
+                };
+                rest.to_string()
+            }
+        };
+        match pending.split_once(';') {
+            Some((tree, _)) => expand_use_tree(&[], tree, &mut aliases),
+            None => statement = Some(pending),
+        }
+    }
+    aliases
+}
+
+fn strip_use_keyword(line: &str) -> Option<&str> {
+    let line = match line.strip_prefix("pub") {
+        Some(rest) if rest.starts_with('(') => rest.split_once(')')?.1.trim_start(),
+        Some(rest) if rest.starts_with(' ') => rest.trim_start(),
+        _ => line,
+    };
+    line.strip_prefix("use ")
+}
+
+fn expand_use_tree(prefix: &[String], tree: &str, aliases: &mut HashMap<String, Vec<String>>) {
+    let tree = tree.trim().trim_start_matches("::");
+    if let Some(open) = tree.find('{') {
+        let Some(close) = tree.rfind('}') else {
+            return;
+        };
+        let mut path = prefix.to_vec();
+        path.extend(use_path_segments(tree[..open].trim_end_matches("::")));
+        for item in split_top_level_commas(&tree[open + 1..close]) {
+            expand_use_tree(&path, item, aliases);
+        }
+        return;
+    }
+
+    let (path_text, alias) = match tree.split_once(" as ") {
+        Some((path_text, alias)) => (path_text, Some(alias.trim())),
+        None => (tree, None),
+    };
+    let mut path = prefix.to_vec();
+    let segments = use_path_segments(path_text);
+    if segments.last().is_some_and(|segment| segment == "*") {
+        return;
+    }
+    if segments.as_slice() == ["self"] {
+        // `use foo::{self}` binds `foo` itself.
+    } else {
+        path.extend(segments);
+    }
+    let Some(imported) = path.last().cloned() else {
+        return;
+    };
+    let local = alias.unwrap_or(&imported);
+    if local != "_" && !local.is_empty() {
+        aliases.insert(local.to_string(), path);
+    }
+}
+
+fn use_path_segments(path_text: &str) -> Vec<String> {
+    path_text
+        .split("::")
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn split_top_level_commas(text: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0usize;
+    for (index, ch) in text.char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                items.push(&text[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&text[start..]);
+    items
+        .into_iter()
+        .filter(|item| !item.trim().is_empty())
+        .collect()
+}
+
 fn relation_hints(file_path: &str, content: &str, language: &str) -> Vec<ExtractedEdge> {
     let mut edges = Vec::new();
 
@@ -426,12 +761,14 @@ callee();
 
     #[test]
     fn module_resolution_helpers_cover_empty_and_unbalanced_paths() {
+        let workspace = WorkspaceLayout::default();
+        let context = ModuleContext::new("src/lib.rs", "", &workspace);
         assert!(
-            qualified_module_candidate_paths("src/lib.rs", &[]).is_empty(),
+            qualified_module_candidate_paths(&context, &[], &[]).is_empty(),
             "empty reference segments should not produce module candidates"
         );
         assert_eq!(
-            resolve_module_segments_for_reference("src/lib.rs", &[]),
+            resolve_module_segments_for_reference(&context, &[], &[]),
             None
         );
         assert!(
@@ -461,6 +798,133 @@ callee();
         assert_eq!(strip_leading_impl_generics("<T>Type"), Some("Type"));
     }
 
+    #[test]
+    fn use_aliases_expand_groups_renames_and_self_imports() {
+        let aliases = use_aliases(
+            r#"
+use crate::store::{self, query::{run as run_query, Plan}};
+pub(crate) use super::helpers::format;
+use acme_util::
+    text::slug;
+use std::io::*;
+use crate::ignored as _;
+"#,
+        );
+        let path = |name: &str| aliases.get(name).map(|segments| segments.join("::"));
+        assert_eq!(path("store"), Some("crate::store".to_string()));
+        assert_eq!(
+            path("run_query"),
+            Some("crate::store::query::run".to_string())
+        );
+        assert_eq!(path("Plan"), Some("crate::store::query::Plan".to_string()));
+        assert_eq!(path("format"), Some("super::helpers::format".to_string()));
+        assert_eq!(path("slug"), Some("acme_util::text::slug".to_string()));
+        assert_eq!(aliases.len(), 5, "glob and underscore imports are skipped");
+        assert_eq!(strip_use_keyword("pub(crate"), None);
+        assert_eq!(module_segments_within("src/a/mod.rs", ""), vec!["src", "a"]);
+    }
+
+    #[test]
+    fn extract_in_workspace_resolves_crate_paths_across_workspace_members() {
+        let file = |relative_path: &str, contents: &str| crate::indexer::files::SourceFile {
+            relative_path: relative_path.to_string(),
+            bytes: contents.as_bytes().to_vec(),
+            content_hash: String::new(),
+            metadata: Default::default(),
+        };
+        let workspace = WorkspaceLayout::from_source_files(&[
+            file("crates/app/Cargo.toml", "[package]\nname = \"app\"\n"),
+            file("crates/app/src/main.rs", ""),
+            file("crates/app/src/cli/run.rs", ""),
+            file(
+                "crates/core-lib/Cargo.toml",
+                "[package]\nname = \"core-lib\"\n",
+            ),
+            file("crates/core-lib/src/lib.rs", ""),
+        ]);
+        let source = r#"
+use core_lib::engine::Engine;
+use crate::config::load;
+
+fn run() {
+    let engine = Engine::new();
+    load();
+    super::shared::helper();
+    core_lib::boot();
+}
+"#;
+        let unit = RustLanguageAdapter
+            .extract_in_workspace("crates/app/src/cli/run.rs", source, &workspace)
+            .expect("rust extraction should succeed");
+        let targets = unit
+            .edges
+            .iter()
+            .filter(|edge| edge.edge_kind == "calls")
+            .filter_map(|edge| edge.to_symbol_key.qualified_symbol.clone())
+            .collect::<Vec<_>>();
+        for expected in [
+            "rust:crates/core-lib/src/engine.rs::new",
+            "rust:crates/core-lib/src/engine/mod.rs::new",
+            "rust:crates/app/src/config.rs::load",
+            "rust:crates/app/src/cli/shared.rs::helper",
+            "rust:crates/core-lib/src/lib.rs::boot",
+        ] {
+            assert!(
+                targets.contains(&expected.to_string()),
+                "missing {expected} in {targets:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn extract_in_workspace_prefixes_test_paths_with_module_path() {
+        let file = |relative_path: &str, contents: &str| crate::indexer::files::SourceFile {
+            relative_path: relative_path.to_string(),
+            bytes: contents.as_bytes().to_vec(),
+            content_hash: String::new(),
+            metadata: Default::default(),
+        };
+        let workspace = WorkspaceLayout::from_source_files(&[
+            file("Cargo.toml", "[package]\nname = \"app\"\n"),
+            file("src/lib.rs", ""),
+            file("src/query/mod.rs", ""),
+            file("src/net/retry.rs", ""),
+            file("src/bin/tool.rs", ""),
+        ]);
+        let source = "fn run() {}\n\n#[cfg(test)]\nmod tests {\n    #[test]\n    fn runs() {}\n\n    mod nested {\n        #[test]\n        fn deep() {}\n    }\n}\n";
+        let test_paths = |file_path: &str| {
+            RustLanguageAdapter
+                .extract_in_workspace(file_path, source, &workspace)
+                .expect("rust extraction should succeed")
+                .symbols
+                .into_iter()
+                .map(|symbol| (symbol.symbol, symbol.test_path))
+                .collect::<HashMap<_, _>>()
+        };
+
+        let query = test_paths("src/query/mod.rs");
+        assert_eq!(query["run"], None);
+        assert_eq!(query["tests"].as_deref(), Some("query::tests"));
+        assert_eq!(query["runs"].as_deref(), Some("query::tests::runs"));
+        let retry = test_paths("src/net/retry.rs");
+        assert_eq!(
+            retry["nested"].as_deref(),
+            Some("net::retry::tests::nested")
+        );
+        assert_eq!(
+            retry["deep"].as_deref(),
+            Some("net::retry::tests::nested::deep")
+        );
+        assert_eq!(
+            test_paths("src/bin/tool.rs")["runs"].as_deref(),
+            Some("tests::runs")
+        );
+        assert_eq!(
+            test_paths("tests/cli.rs")["runs"].as_deref(),
+            Some("tests::runs")
+        );
+    }
+
     #[test]
     fn relation_hints_cover_invalid_and_valid_use_impl_forms() {
         let source = r#"
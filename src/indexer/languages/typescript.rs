@@ -1,13 +1,46 @@
 use anyhow::Context;
-use std::collections::HashMap;
-use tree_sitter::{Node, Parser};
+use std::collections::{HashMap, HashSet};
+use tree_sitter::{Node, Parser, Tree};
 
+use crate::indexer::languages::complexity::{ComplexityRules, function_metrics};
 use crate::indexer::languages::{
     ExtractedEdge, ExtractedReference, ExtractedSymbol, ExtractionUnit, LanguageAdapter, SymbolKey,
 };
+use crate::indexer::workspace::WorkspaceLayout;
 
 pub struct TypeScriptLanguageAdapter;
 
+const COMPLEXITY_RULES: ComplexityRules = ComplexityRules {
+    parameter_kinds: &[
+        "required_parameter",
+        "optional_parameter",
+        "identifier",
+        "assignment_pattern",
+        "rest_pattern",
+        "object_pattern",
+        "array_pattern",
+    ],
+    receiver_names: &[],
+    branch_kinds: &[
+        "if_statement",
+        "for_statement",
+        "for_in_statement",
+        "while_statement",
+        "do_statement",
+        "catch_clause",
+    ],
+    flat_branch_kinds: &["ternary_expression"],
+    switch_kinds: &["switch_statement"],
+    arm_kinds: &["switch_case"],
+    logical_kinds: &["binary_expression"],
+    nested_definition_kinds: &[
+        "function_declaration",
+        "generator_function_declaration",
+        "class_declaration",
+        "method_definition",
+    ],
+};
+
 #[derive(Debug, Clone)]
 struct ImportCallHint {
     import_paths: Vec<String>,
@@ -42,6 +75,15 @@ impl LanguageAdapter for TypeScriptLanguageAdapter {
     }
 
     fn extract(&self, file_path: &str, source: &str) -> anyhow::Result<ExtractionUnit> {
+        self.extract_in_workspace(file_path, source, &WorkspaceLayout::default())
+    }
+
+    fn extract_in_workspace(
+        &self,
+        file_path: &str,
+        source: &str,
+        workspace: &WorkspaceLayout,
+    ) -> anyhow::Result<ExtractionUnit> {
         let mut parser = Parser::new();
         if file_path.ends_with(".tsx") {
             parser
@@ -57,276 +99,332 @@ impl LanguageAdapter for TypeScriptLanguageAdapter {
             .parse(source, None)
             .context("failed to parse typescript source")?;
 
-        let language = self.language_id().to_string();
-        let import_target_hints = import_target_hints(file_path, source);
-        let import_call_hints = import_call_hints(file_path, source);
-        let mut symbols = Vec::new();
-        let mut references = Vec::new();
-        let mut edges = Vec::new();
-        let mut stack = vec![tree.root_node()];
+        extract_ecmascript_tree(&tree, file_path, source, workspace, self.language_id())
+    }
+}
 
-        while let Some(node) = stack.pop() {
-            match node.kind() {
-                "function_declaration" => {
-                    push_named_definition(
-                        node,
-                        source,
-                        "function",
-                        None,
-                        file_path,
-                        &language,
-                        &mut symbols,
-                    );
-                }
-                "class_declaration" => {
-                    let class_name = push_named_definition(
-                        node,
-                        source,
-                        "class",
-                        None,
-                        file_path,
-                        &language,
-                        &mut symbols,
-                    );
+/// Walks a parsed TypeScript or JavaScript tree. Both grammars share node kinds for
+/// declarations, imports and calls, so the JavaScript adapter reuses this extraction.
+pub(crate) fn extract_ecmascript_tree(
+    tree: &Tree,
+    file_path: &str,
+    source: &str,
+    workspace: &WorkspaceLayout,
+    language: &str,
+) -> anyhow::Result<ExtractionUnit> {
+    let language = language.to_string();
+    let import_target_hints = import_target_hints(file_path, source, workspace);
+    let import_call_hints = import_call_hints(file_path, source, workspace);
+    let mut symbols = Vec::new();
+    let mut references = Vec::new();
+    let mut edges = Vec::new();
+    let mut stack = vec![tree.root_node()];
+
+    while let Some(node) = stack.pop() {
+        match node.kind() {
+            "function_declaration" => {
+                push_named_definition(
+                    node,
+                    source,
+                    "function",
+                    None,
+                    file_path,
+                    &language,
+                    &mut symbols,
+                );
+            }
+            "class_declaration" => {
+                let class_name = push_named_definition(
+                    node,
+                    source,
+                    "class",
+                    None,
+                    file_path,
+                    &language,
+                    &mut symbols,
+                );
 
-                    if let Some(class_symbol) = class_name {
-                        for implemented in implemented_types(node, source) {
-                            if let Some(import_paths) = import_target_hints.get(&implemented) {
-                                for import_path in import_paths {
-                                    edges.push(ExtractedEdge {
-                                        from_symbol_key: scoped_symbol_key(
-                                            file_path,
-                                            &language,
-                                            &class_symbol,
-                                        ),
-                                        to_symbol_key: SymbolKey {
-                                            symbol: implemented.clone(),
-                                            qualified_symbol: Some(format!(
-                                                "{language}:{import_path}::{implemented}"
-                                            )),
-                                            file_path: Some(import_path.clone()),
-                                            language: Some(language.clone()),
-                                        },
-                                        edge_kind: "implements".to_string(),
-                                        confidence: 0.95,
-                                        provenance: "ast_reference".to_string(),
-                                    });
-                                }
-                            } else {
+                if let Some(class_symbol) = class_name {
+                    for implemented in implemented_types(node, source) {
+                        if let Some(import_paths) = import_target_hints.get(&implemented) {
+                            for import_path in import_paths {
                                 edges.push(ExtractedEdge {
                                     from_symbol_key: scoped_symbol_key(
                                         file_path,
                                         &language,
                                         &class_symbol,
                                     ),
-                                    to_symbol_key: language_symbol_key(&implemented, &language),
+                                    to_symbol_key: SymbolKey {
+                                        symbol: implemented.clone(),
+                                        qualified_symbol: Some(format!(
+                                            "{language}:{import_path}::{implemented}"
+                                        )),
+                                        file_path: Some(import_path.clone()),
+                                        language: Some(language.clone()),
+                                    },
                                     edge_kind: "implements".to_string(),
                                     confidence: 0.95,
                                     provenance: "ast_reference".to_string(),
                                 });
                             }
+                        } else {
+                            edges.push(ExtractedEdge {
+                                from_symbol_key: scoped_symbol_key(
+                                    file_path,
+                                    &language,
+                                    &class_symbol,
+                                ),
+                                to_symbol_key: language_symbol_key(&implemented, &language),
+                                edge_kind: "implements".to_string(),
+                                confidence: 0.95,
+                                provenance: "ast_reference".to_string(),
+                            });
                         }
                     }
                 }
-                "interface_declaration" => {
+            }
+            "interface_declaration" => {
+                push_named_definition(
+                    node,
+                    source,
+                    "interface",
+                    None,
+                    file_path,
+                    &language,
+                    &mut symbols,
+                );
+            }
+            "enum_declaration" => {
+                push_named_definition(
+                    node,
+                    source,
+                    "enum",
+                    None,
+                    file_path,
+                    &language,
+                    &mut symbols,
+                );
+            }
+            "type_alias_declaration" => {
+                push_named_definition(
+                    node,
+                    source,
+                    "type_alias",
+                    None,
+                    file_path,
+                    &language,
+                    &mut symbols,
+                );
+            }
+            "variable_declarator" => {
+                let value = node.child_by_field_name("value");
+                if value.is_some_and(|value| is_function_value(value.kind())) {
                     push_named_definition(
                         node,
                         source,
-                        "interface",
+                        "variable",
                         None,
                         file_path,
                         &language,
                         &mut symbols,
                     );
+                } else if value.is_some_and(|value| is_require_call(value, source)) {
+                    for binding in require_bindings(node, source) {
+                        push_import_binding(
+                            &binding,
+                            file_path,
+                            &language,
+                            &import_target_hints,
+                            &mut symbols,
+                            &mut edges,
+                        );
+                    }
                 }
-                "enum_declaration" => {
-                    push_named_definition(
-                        node,
+            }
+            "method_definition" => {
+                let container = enclosing_class_name(node, source);
+                if let Some(symbol) = push_named_definition(
+                    node,
+                    source,
+                    "method",
+                    container.clone(),
+                    file_path,
+                    &language,
+                    &mut symbols,
+                ) && let Some(container_symbol) = container
+                {
+                    edges.push(ExtractedEdge {
+                        from_symbol_key: scoped_symbol_key(file_path, &language, &container_symbol),
+                        to_symbol_key: scoped_symbol_key(file_path, &language, &symbol),
+                        edge_kind: "contains".to_string(),
+                        confidence: 1.0,
+                        provenance: "ast_definition".to_string(),
+                    });
+                }
+            }
+            "call_expression" => {
+                let caller = enclosing_callable_name(node, source);
+                if let Some(function_node) = node.child_by_field_name("function") {
+                    collect_call_symbols(
+                        function_node,
                         source,
-                        "enum",
-                        None,
+                        caller.as_deref(),
                         file_path,
                         &language,
-                        &mut symbols,
+                        &import_target_hints,
+                        &import_call_hints,
+                        &mut references,
+                        &mut edges,
                     );
                 }
-                "type_alias_declaration" => {
-                    push_named_definition(
-                        node,
-                        source,
-                        "type_alias",
-                        None,
+            }
+            "import_statement" => {
+                for binding in import_bindings(node, source) {
+                    push_import_binding(
+                        &binding,
                         file_path,
                         &language,
+                        &import_target_hints,
                         &mut symbols,
+                        &mut edges,
                     );
                 }
-                "variable_declarator" => {
-                    let is_callable_value = node
-                        .child_by_field_name("value")
-                        .is_some_and(|value| matches!(value.kind(), "arrow_function" | "function"));
-                    if is_callable_value {
-                        push_named_definition(
-                            node,
-                            source,
-                            "variable",
-                            None,
-                            file_path,
-                            &language,
-                            &mut symbols,
-                        );
-                    }
-                }
-                "method_definition" => {
-                    let container = enclosing_class_name(node, source);
-                    if let Some(symbol) = push_named_definition(
+            }
+            "assignment_expression" | "pair" => {
+                let is_callable_value = node
+                    .child_by_field_name(if node.kind() == "pair" {
+                        "value"
+                    } else {
+                        "right"
+                    })
+                    .is_some_and(|value| is_function_value(value.kind()));
+                if is_callable_value && let Some(name_node) = commonjs_export_name(node, source) {
+                    push_definition(
                         node,
+                        name_node,
                         source,
-                        "method",
-                        container.clone(),
+                        "function",
+                        None,
                         file_path,
                         &language,
                         &mut symbols,
-                    ) && let Some(container_symbol) = container
-                    {
-                        edges.push(ExtractedEdge {
-                            from_symbol_key: scoped_symbol_key(
-                                file_path,
-                                &language,
-                                &container_symbol,
-                            ),
-                            to_symbol_key: scoped_symbol_key(file_path, &language, &symbol),
-                            edge_kind: "contains".to_string(),
-                            confidence: 1.0,
-                            provenance: "ast_definition".to_string(),
-                        });
-                    }
-                }
-                "call_expression" => {
-                    let caller = enclosing_callable_name(node, source);
-                    if let Some(function_node) = node.child_by_field_name("function") {
-                        collect_call_symbols(
-                            function_node,
-                            source,
-                            caller.as_deref(),
-                            file_path,
-                            &language,
-                            &import_target_hints,
-                            &import_call_hints,
-                            &mut references,
-                            &mut edges,
-                        );
-                    }
-                }
-                "import_statement" => {
-                    for binding in import_bindings(node, source) {
-                        symbols.push(ExtractedSymbol {
-                            symbol: binding.local_symbol.clone(),
-                            qualified_symbol: Some(format!(
-                                "{language}:{file_path}::{}",
-                                binding.local_symbol
-                            )),
-                            kind: "import".to_string(),
-                            language: language.clone(),
-                            container: None,
-                            start_line: binding.start_line,
-                            start_column: binding.start_column,
-                            end_line: binding.end_line,
-                            end_column: binding.end_column,
-                            signature: Some(format!("import {}", binding.local_symbol)),
-                        });
-                        if let Some(import_paths) = import_target_hints.get(&binding.local_symbol) {
-                            for import_path in import_paths {
-                                edges.push(ExtractedEdge {
-                                    from_symbol_key: scoped_symbol_key(
-                                        file_path,
-                                        &language,
-                                        &binding.local_symbol,
-                                    ),
-                                    to_symbol_key: SymbolKey {
-                                        symbol: binding.imported_symbol.clone(),
-                                        qualified_symbol: Some(format!(
-                                            "{language}:{import_path}::{}",
-                                            binding.imported_symbol
-                                        )),
-                                        file_path: Some(import_path.clone()),
-                                        language: Some(language.clone()),
-                                    },
-                                    edge_kind: "imports".to_string(),
-                                    confidence: 0.9,
-                                    provenance: "import_resolution".to_string(),
-                                });
-                            }
-                        } else {
-                            edges.push(ExtractedEdge {
-                                from_symbol_key: scoped_symbol_key(
-                                    file_path,
-                                    &language,
-                                    &binding.local_symbol,
-                                ),
-                                to_symbol_key: language_symbol_key(
-                                    &binding.imported_symbol,
-                                    &language,
-                                ),
-                                edge_kind: "imports".to_string(),
-                                confidence: 0.9,
-                                provenance: "import_resolution".to_string(),
-                            });
-                        }
-                    }
+                    );
                 }
-                _ => {}
             }
+            _ => {}
+        }
 
-            let mut cursor = node.walk();
-            for child in node.children(&mut cursor) {
-                stack.push(child);
-            }
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            stack.push(child);
+        }
+    }
+
+    let exported_names = export_clause_names(tree.root_node(), source);
+    for symbol in &mut symbols {
+        if symbol.container.is_none()
+            && symbol.kind != "import"
+            && exported_names.contains(&symbol.symbol)
+        {
+            symbol.visibility = Some("export".to_string());
         }
+    }
 
-        symbols.sort_by(|left, right| {
-            left.start_line
-                .cmp(&right.start_line)
-                .then(left.start_column.cmp(&right.start_column))
-                .then(left.symbol.cmp(&right.symbol))
-                .then(left.kind.cmp(&right.kind))
-        });
-        references.sort_by(|left, right| {
-            left.line
-                .cmp(&right.line)
-                .then(left.column.cmp(&right.column))
-                .then(left.symbol.cmp(&right.symbol))
-        });
-        edges.sort_by(|left, right| {
-            left.from_symbol_key
-                .symbol
-                .cmp(&right.from_symbol_key.symbol)
-                .then(
-                    left.from_symbol_key
-                        .qualified_symbol
-                        .cmp(&right.from_symbol_key.qualified_symbol),
-                )
-                .then(left.to_symbol_key.symbol.cmp(&right.to_symbol_key.symbol))
-                .then(
-                    left.to_symbol_key
-                        .qualified_symbol
-                        .cmp(&right.to_symbol_key.qualified_symbol),
-                )
-                .then(left.edge_kind.cmp(&right.edge_kind))
-        });
-        edges.dedup_by(|left, right| {
-            left.from_symbol_key.symbol == right.from_symbol_key.symbol
-                && left.from_symbol_key.qualified_symbol == right.from_symbol_key.qualified_symbol
-                && left.to_symbol_key.symbol == right.to_symbol_key.symbol
-                && left.to_symbol_key.qualified_symbol == right.to_symbol_key.qualified_symbol
-                && left.edge_kind == right.edge_kind
-        });
+    symbols.sort_by(|left, right| {
+        left.start_line
+            .cmp(&right.start_line)
+            .then(left.start_column.cmp(&right.start_column))
+            .then(left.symbol.cmp(&right.symbol))
+            .then(left.kind.cmp(&right.kind))
+    });
+    references.sort_by(|left, right| {
+        left.line
+            .cmp(&right.line)
+            .then(left.column.cmp(&right.column))
+            .then(left.symbol.cmp(&right.symbol))
+    });
+    edges.sort_by(|left, right| {
+        left.from_symbol_key
+            .symbol
+            .cmp(&right.from_symbol_key.symbol)
+            .then(
+                left.from_symbol_key
+                    .qualified_symbol
+                    .cmp(&right.from_symbol_key.qualified_symbol),
+            )
+            .then(left.to_symbol_key.symbol.cmp(&right.to_symbol_key.symbol))
+            .then(
+                left.to_symbol_key
+                    .qualified_symbol
+                    .cmp(&right.to_symbol_key.qualified_symbol),
+            )
+            .then(left.edge_kind.cmp(&right.edge_kind))
+    });
+    edges.dedup_by(|left, right| {
+        left.from_symbol_key.symbol == right.from_symbol_key.symbol
+            && left.from_symbol_key.qualified_symbol == right.from_symbol_key.qualified_symbol
+            && left.to_symbol_key.symbol == right.to_symbol_key.symbol
+            && left.to_symbol_key.qualified_symbol == right.to_symbol_key.qualified_symbol
+            && left.edge_kind == right.edge_kind
+    });
 
-        Ok(ExtractionUnit {
-            symbols,
-            references,
-            edges,
-        })
+    Ok(ExtractionUnit {
+        symbols,
+        references,
+        edges,
+    })
+}
+
+fn push_import_binding(
+    binding: &ImportBinding,
+    file_path: &str,
+    language: &str,
+    import_target_hints: &HashMap<String, Vec<String>>,
+    symbols: &mut Vec<ExtractedSymbol>,
+    edges: &mut Vec<ExtractedEdge>,
+) {
+    symbols.push(ExtractedSymbol {
+        symbol: binding.local_symbol.clone(),
+        qualified_symbol: Some(format!("{language}:{file_path}::{}", binding.local_symbol)),
+        kind: "import".to_string(),
+        language: language.to_string(),
+        container: None,
+        start_line: binding.start_line,
+        start_column: binding.start_column,
+        end_line: binding.end_line,
+        end_column: binding.end_column,
+        signature: Some(format!("import {}", binding.local_symbol)),
+        test_path: None,
+        metrics: None,
+        visibility: Some("private".to_string()),
+        doc_comment: None,
+    });
+    if let Some(import_paths) = import_target_hints.get(&binding.local_symbol) {
+        for import_path in import_paths {
+            edges.push(ExtractedEdge {
+                from_symbol_key: scoped_symbol_key(file_path, language, &binding.local_symbol),
+                to_symbol_key: SymbolKey {
+                    symbol: binding.imported_symbol.clone(),
+                    qualified_symbol: Some(format!(
+                        "{language}:{import_path}::{}",
+                        binding.imported_symbol
+                    )),
+                    file_path: Some(import_path.clone()),
+                    language: Some(language.to_string()),
+                },
+                edge_kind: "imports".to_string(),
+                confidence: 0.9,
+                provenance: "import_resolution".to_string(),
+            });
+        }
+    } else {
+        edges.push(ExtractedEdge {
+            from_symbol_key: scoped_symbol_key(file_path, language, &binding.local_symbol),
+            to_symbol_key: language_symbol_key(&binding.imported_symbol, language),
+            edge_kind: "imports".to_string(),
+            confidence: 0.9,
+            provenance: "import_resolution".to_string(),
+        });
     }
 }
 
@@ -340,6 +438,22 @@ fn push_named_definition(
     output: &mut Vec<ExtractedSymbol>,
 ) -> Option<String> {
     let name_node = node.child_by_field_name("name")?;
+    push_definition(
+        node, name_node, source, kind, container, file_path, language, output,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn push_definition(
+    node: Node<'_>,
+    name_node: Node<'_>,
+    source: &str,
+    kind: &str,
+    container: Option<String>,
+    file_path: &str,
+    language: &str,
+    output: &mut Vec<ExtractedSymbol>,
+) -> Option<String> {
     if !matches!(
         name_node.kind(),
         "identifier" | "type_identifier" | "property_identifier"
@@ -349,6 +463,7 @@ fn push_named_definition(
     let symbol = node_text(name_node, source)?;
     let (start_line, start_column) = start_position(name_node);
     let (end_line, end_column) = end_position(node);
+    let callable = callable_node(node);
     output.push(ExtractedSymbol {
         symbol: symbol.clone(),
         qualified_symbol: Some(format!("{language}:{file_path}::{symbol}")),
@@ -360,10 +475,141 @@ fn push_named_definition(
         end_line,
         end_column,
         signature: signature_summary(node, source),
+        test_path: None,
+        metrics: (matches!(kind, "function" | "method") || is_function_value(callable.kind()))
+            .then(|| function_metrics(callable, source, &COMPLEXITY_RULES)),
+        visibility: Some(ecmascript_visibility(node).to_string()),
+        doc_comment: None,
     });
     Some(symbol)
 }
 
+/// The function node a definition measures: variable declarators, assignments, and object
+/// pairs hold it in their value.
+fn callable_node(node: Node<'_>) -> Node<'_> {
+    ["value", "right"]
+        .into_iter()
+        .filter_map(|field| node.child_by_field_name(field))
+        .find(|value| is_function_value(value.kind()))
+        .unwrap_or(node)
+}
+
+/// `export` or `export default` for exported declarations and CommonJS exports; methods
+/// follow their class unless marked `private` or `protected`.
+fn ecmascript_visibility(node: Node<'_>) -> &'static str {
+    match node.kind() {
+        "assignment_expression" | "pair" => return "export",
+        "method_definition" => {
+            let mut cursor = node.walk();
+            let hidden = node.children(&mut cursor).any(|child| {
+                child.kind() == "accessibility_modifier"
+                    && child
+                        .child(0)
+                        .is_some_and(|keyword| keyword.kind() != "public")
+            });
+            if hidden {
+                return "private";
+            }
+            let class = std::iter::successors(node.parent(), |parent| parent.parent())
+                .find(|ancestor| matches!(ancestor.kind(), "class_declaration" | "class"));
+            return class.map_or("private", ecmascript_visibility);
+        }
+        _ => {}
+    }
+    let mut current = node;
+    while let Some(parent) = current.parent() {
+        match parent.kind() {
+            "variable_declarator" | "lexical_declaration" | "variable_declaration" => {
+                current = parent;
+            }
+            "export_statement" => {
+                let mut cursor = parent.walk();
+                let is_default = parent
+                    .children(&mut cursor)
+                    .any(|child| child.kind() == "default");
+                return if is_default {
+                    "export default"
+                } else {
+                    "export"
+                };
+            }
+            _ => break,
+        }
+    }
+    "private"
+}
+
+/// Local names re-exported by `export { name }` clauses without a `from` source.
+fn export_clause_names(root: Node<'_>, source: &str) -> HashSet<String> {
+    let mut names = HashSet::new();
+    let mut cursor = root.walk();
+    for statement in root.named_children(&mut cursor) {
+        if statement.kind() != "export_statement"
+            || statement.child_by_field_name("source").is_some()
+        {
+            continue;
+        }
+        let mut statement_cursor = statement.walk();
+        for clause in statement
+            .named_children(&mut statement_cursor)
+            .filter(|child| child.kind() == "export_clause")
+        {
+            let mut clause_cursor = clause.walk();
+            names.extend(
+                clause
+                    .named_children(&mut clause_cursor)
+                    .filter_map(|specifier| specifier.child_by_field_name("name"))
+                    .filter_map(|name| node_text(name, source)),
+            );
+        }
+    }
+    names
+}
+
+fn is_function_value(kind: &str) -> bool {
+    matches!(kind, "arrow_function" | "function" | "function_expression")
+}
+
+fn is_require_call(node: Node<'_>, source: &str) -> bool {
+    node.kind() == "call_expression"
+        && node
+            .child_by_field_name("function")
+            .and_then(|function| node_text(function, source))
+            .is_some_and(|function| function == "require")
+}
+
+fn is_commonjs_exports_object(text: &str) -> bool {
+    matches!(text, "exports" | "module.exports")
+}
+
+/// Returns the exported name for `exports.name = ...`, `module.exports.name = ...` and
+/// `name: ...` pairs inside an object assigned to `module.exports`.
+fn commonjs_export_name<'tree>(node: Node<'tree>, source: &str) -> Option<Node<'tree>> {
+    match node.kind() {
+        "assignment_expression" => {
+            let left = node.child_by_field_name("left")?;
+            if left.kind() != "member_expression" {
+                return None;
+            }
+            let object = left.child_by_field_name("object")?;
+            is_commonjs_exports_object(&node_text(object, source)?)
+                .then(|| left.child_by_field_name("property"))
+                .flatten()
+        }
+        "pair" => {
+            let object = node.parent().filter(|parent| parent.kind() == "object")?;
+            let assignment = object
+                .parent()
+                .filter(|parent| parent.kind() == "assignment_expression")?;
+            let left = assignment.child_by_field_name("left")?;
+            (node_text(left, source)? == "module.exports")
+                .then(|| node.child_by_field_name("key"))
+                .flatten()
+        }
+        _ => None,
+    }
+}
+
 fn implemented_types(node: Node<'_>, source: &str) -> Vec<String> {
     let mut implemented = Vec::new();
     if let Some(text) = node_text(node, source)
@@ -562,12 +808,16 @@ fn enclosing_callable_name(node: Node<'_>, source: &str) -> Option<String> {
         {
             return node_text(name_node, source);
         }
-        if matches!(parent.kind(), "arrow_function" | "function")
-            && let Some(declarator) = parent.parent()
-            && declarator.kind() == "variable_declarator"
-            && let Some(name_node) = declarator.child_by_field_name("name")
+        if is_function_value(parent.kind())
+            && let Some(binding) = parent.parent()
         {
-            return node_text(name_node, source);
+            let name_node = match binding.kind() {
+                "variable_declarator" => binding.child_by_field_name("name"),
+                _ => commonjs_export_name(binding, source),
+            };
+            if let Some(name_node) = name_node {
+                return node_text(name_node, source);
+            }
         }
         current = parent.parent();
     }
@@ -639,11 +889,86 @@ fn import_bindings(node: Node<'_>, source: &str) -> Vec<ImportBinding> {
     bindings
 }
 
-fn import_target_hints(file_path: &str, source: &str) -> HashMap<String, Vec<String>> {
+fn require_bindings(node: Node<'_>, source: &str) -> Vec<ImportBinding> {
+    let Some(pattern) = node
+        .child_by_field_name("name")
+        .filter(|name| name.kind() == "object_pattern")
+        .and_then(|name| node_text(name, source))
+    else {
+        return Vec::new();
+    };
+    let (start_line, start_column) = start_position(node);
+    let (end_line, end_column) = end_position(node);
+    destructured_specifiers(&pattern)
+        .into_iter()
+        .map(|(imported_symbol, local_symbol)| ImportBinding {
+            local_symbol,
+            imported_symbol,
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+        })
+        .collect()
+}
+
+/// Splits a CommonJS `const <pattern> = require("<specifier>")` line.
+fn require_statement(line: &str) -> Option<(&str, String)> {
+    let declaration = ["const ", "let ", "var "]
+        .iter()
+        .find_map(|keyword| line.strip_prefix(keyword))?;
+    let (pattern, value) = declaration.split_once('=')?;
+    let argument = value.trim_start().strip_prefix("require(")?;
+    Some((pattern.trim(), quoted_text(argument)?))
+}
+
+/// Returns `(imported, local)` pairs for a destructuring pattern such as `{ a, b: c }`.
+fn destructured_specifiers(pattern: &str) -> Vec<(String, String)> {
+    let Some(clause) = pattern
+        .trim()
+        .strip_prefix('{')
+        .and_then(|rest| rest.strip_suffix('}'))
+    else {
+        return Vec::new();
+    };
+    clause
+        .split(',')
+        .filter_map(|specifier| {
+            let specifier = specifier.trim();
+            if specifier.is_empty() || specifier.starts_with("...") {
+                return None;
+            }
+            let (imported, local) = specifier.split_once(':').unwrap_or((specifier, specifier));
+            let local = local.split('=').next().unwrap_or(local).trim();
+            Some((imported.trim().to_string(), local.to_string()))
+        })
+        .collect()
+}
+
+fn import_target_hints(
+    file_path: &str,
+    source: &str,
+    workspace: &WorkspaceLayout,
+) -> HashMap<String, Vec<String>> {
     let mut hints = HashMap::new();
 
     for line in source.lines() {
         let trimmed = line.trim();
+        if let Some((pattern, module_specifier)) = require_statement(trimmed) {
+            let import_paths =
+                resolve_typescript_import_paths(file_path, &module_specifier, workspace);
+            if import_paths.is_empty() {
+                continue;
+            }
+            if pattern.starts_with('{') {
+                for (_imported, local_symbol) in destructured_specifiers(pattern) {
+                    extend_import_hint(&mut hints, &local_symbol, &import_paths);
+                }
+            } else {
+                extend_import_hint(&mut hints, pattern, &import_paths);
+            }
+            continue;
+        }
         if !trimmed.starts_with("import ") {
             continue;
         }
@@ -653,7 +978,7 @@ fn import_target_hints(file_path: &str, source: &str) -> HashMap<String, Vec<Str
         let Some(module_specifier) = quoted_text(from_tail) else {
             continue;
         };
-        let import_paths = resolve_typescript_import_paths(file_path, &module_specifier);
+        let import_paths = resolve_typescript_import_paths(file_path, &module_specifier, workspace);
         if import_paths.is_empty() {
             continue;
         }
@@ -730,11 +1055,32 @@ fn extend_import_hint(
     entry.dedup();
 }
 
-fn import_call_hints(file_path: &str, source: &str) -> HashMap<String, ImportCallHint> {
+fn import_call_hints(
+    file_path: &str,
+    source: &str,
+    workspace: &WorkspaceLayout,
+) -> HashMap<String, ImportCallHint> {
     let mut hints = HashMap::new();
 
     for line in source.lines() {
         let trimmed = line.trim();
+        if let Some((pattern, module_specifier)) = require_statement(trimmed) {
+            let import_paths =
+                resolve_typescript_import_paths(file_path, &module_specifier, workspace);
+            if import_paths.is_empty() {
+                continue;
+            }
+            for (imported_symbol, local_symbol) in destructured_specifiers(pattern) {
+                hints.insert(
+                    local_symbol,
+                    ImportCallHint {
+                        import_paths: import_paths.clone(),
+                        imported_symbol,
+                    },
+                );
+            }
+            continue;
+        }
         if !trimmed.starts_with("import ") {
             continue;
         }
@@ -744,7 +1090,7 @@ fn import_call_hints(file_path: &str, source: &str) -> HashMap<String, ImportCal
         let Some(module_specifier) = quoted_text(from_tail) else {
             continue;
         };
-        let import_paths = resolve_typescript_import_paths(file_path, &module_specifier);
+        let import_paths = resolve_typescript_import_paths(file_path, &module_specifier, workspace);
         if import_paths.is_empty() {
             continue;
         }
@@ -791,9 +1137,13 @@ fn quoted_text(segment: &str) -> Option<String> {
     Some(tail[..end].to_string())
 }
 
-fn resolve_typescript_import_paths(from_file_path: &str, module_specifier: &str) -> Vec<String> {
+fn resolve_typescript_import_paths(
+    from_file_path: &str,
+    module_specifier: &str,
+    workspace: &WorkspaceLayout,
+) -> Vec<String> {
     if !module_specifier.starts_with('.') {
-        return Vec::new();
+        return workspace.node_module_files(from_file_path, module_specifier);
     }
 
     let Some(base) = std::path::Path::new(from_file_path).parent() else {
@@ -805,7 +1155,7 @@ fn resolve_typescript_import_paths(from_file_path: &str, module_specifier: &str)
     if candidate.extension().is_some() {
         resolved.push(normalize_relative_path(&candidate));
     } else {
-        for extension in ["ts", "tsx"] {
+        for extension in relative_module_extensions(from_file_path) {
             let mut direct = candidate.clone();
             direct.set_extension(extension);
             resolved.push(normalize_relative_path(&direct));
@@ -820,6 +1170,18 @@ fn resolve_typescript_import_paths(from_file_path: &str, module_specifier: &str)
     resolved
 }
 
+fn relative_module_extensions(from_file_path: &str) -> &'static [&'static str] {
+    let is_javascript = std::path::Path::new(from_file_path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| matches!(extension, "js" | "jsx" | "mjs" | "cjs"));
+    if is_javascript {
+        &["js", "jsx", "mjs", "cjs"]
+    } else {
+        &["ts", "tsx"]
+    }
+}
+
 fn normalize_relative_path(path: &std::path::Path) -> String {
     let mut parts = Vec::new();
     for component in path.components() {
@@ -897,7 +1259,7 @@ import DefaultApi, { Build as MakeBuild, util } from "./pkg/api";
 import * as NS from "./pkg/ns";
 import sideEffect from "./pkg/side";
 "#;
-        let hints = import_target_hints("src/app/main.ts", source);
+        let hints = import_target_hints("src/app/main.ts", source, &WorkspaceLayout::default());
         let expected_paths = vec![
             "src/app/pkg/api.ts".to_string(),
             "src/app/pkg/api.tsx".to_string(),
@@ -926,7 +1288,7 @@ import sideEffect from "./pkg/side";
             ])
         );
 
-        let call_hints = import_call_hints("src/app/main.ts", source);
+        let call_hints = import_call_hints("src/app/main.ts", source, &WorkspaceLayout::default());
         let make_build = call_hints
             .get("MakeBuild")
             .expect("aliased named import should create call hint");
@@ -940,13 +1302,20 @@ import sideEffect from "./pkg/side";
         assert_eq!(quoted_text("from './x'"), Some("./x".to_string()));
         assert_eq!(quoted_text("from ./x"), None);
 
-        assert!(resolve_typescript_import_paths("src/main.ts", "react").is_empty());
+        assert!(
+            resolve_typescript_import_paths("src/main.ts", "react", &WorkspaceLayout::default())
+                .is_empty()
+        );
         assert_eq!(
-            resolve_typescript_import_paths("src/main.ts", "./direct.ts"),
+            resolve_typescript_import_paths(
+                "src/main.ts",
+                "./direct.ts",
+                &WorkspaceLayout::default()
+            ),
             vec!["src/direct.ts".to_string()]
         );
         assert_eq!(
-            resolve_typescript_import_paths("src/main.ts", "./mod"),
+            resolve_typescript_import_paths("src/main.ts", "./mod", &WorkspaceLayout::default()),
             vec![
                 "src/mod.ts".to_string(),
                 "src/mod.tsx".to_string(),
@@ -1156,8 +1525,8 @@ const helper = () => {
             Some("run"),
             "src/app/main.ts",
             "typescript",
-            &import_target_hints("src/app/main.ts", source),
-            &import_call_hints("src/app/main.ts", source),
+            &import_target_hints("src/app/main.ts", source, &WorkspaceLayout::default()),
+            &import_call_hints("src/app/main.ts", source, &WorkspaceLayout::default()),
             &mut references,
             &mut edges,
         );
@@ -1185,7 +1554,8 @@ const helper = () => {
 import { a as alias, b as  } from \"./pkg/mod\";\n\
 import { c } from react;\n\
 import MissingFromClause;\n";
-        let hints = import_target_hints("src/app/main.ts", hints_source);
+        let hints =
+            import_target_hints("src/app/main.ts", hints_source, &WorkspaceLayout::default());
         assert!(
             hints.contains_key("alias"),
             "relative named imports should still be captured in hints"
@@ -1200,7 +1570,11 @@ import { build as makeBuild, c as  } from \"./pkg/mod\";\n\
 import { bad } from react;\n\
 import MissingFromClause;\n\
 import { nope } from react;\n";
-        let call_hints = import_call_hints("src/app/main.ts", call_hints_source);
+        let call_hints = import_call_hints(
+            "src/app/main.ts",
+            call_hints_source,
+            &WorkspaceLayout::default(),
+        );
         assert!(
             call_hints.contains_key("makeBuild"),
             "valid relative named imports should produce call hints"
@@ -1211,7 +1585,8 @@ import { nope } from react;\n";
         );
 
         assert!(
-            resolve_typescript_import_paths("", "./pkg/mod").is_empty(),
+            resolve_typescript_import_paths("", "./pkg/mod", &WorkspaceLayout::default())
+                .is_empty(),
             "missing base directory should return no import candidates"
         );
         assert_eq!(
@@ -1301,8 +1676,11 @@ class Worker implements LocalShape {}
             "valid bindings should remain even when malformed aliases are present"
         );
 
-        let target_hints =
-            import_target_hints("src/app/main.ts", "import { a as , , b } from \"./x\";\n");
+        let target_hints = import_target_hints(
+            "src/app/main.ts",
+            "import { a as , , b } from \"./x\";\n",
+            &WorkspaceLayout::default(),
+        );
         assert!(target_hints.contains_key("b"));
         assert!(!target_hints.contains_key(""));
 
@@ -1312,6 +1690,7 @@ class Worker implements LocalShape {}
 import }{ from \"./x\";\n\
 import { , a, b as  } from \"./x\";\n\
 import { c as makeC } from \"./x\";\n",
+            &WorkspaceLayout::default(),
         );
         assert!(call_hints.contains_key("a"));
         assert!(call_hints.contains_key("makeC"));
@@ -1395,4 +1774,47 @@ import { c as makeC } from \"./x\";\n",
             "mismatched source bytes should short-circuit empty symbol paths without panicking"
         );
     }
+
+    #[test]
+    fn adapter_extract_records_export_visibility() {
+        let source = r#"
+import { helper } from "./helper";
+
+export default function main() {}
+export const run = () => helper();
+const hidden = () => 1;
+function later() {}
+export { later };
+
+export class Service {
+  start() {}
+  private stop() {}
+  protected reset() {}
+}
+
+class Local {
+  go() {}
+}
+"#;
+        let unit = TypeScriptLanguageAdapter
+            .extract("src/app/main.ts", source)
+            .expect("typescript extraction should succeed");
+        let visibility = |symbol: &str| {
+            unit.symbols
+                .iter()
+                .find(|item| item.symbol == symbol)
+                .and_then(|item| item.visibility.clone())
+        };
+        assert_eq!(visibility("helper").as_deref(), Some("private"));
+        assert_eq!(visibility("main").as_deref(), Some("export default"));
+        assert_eq!(visibility("run").as_deref(), Some("export"));
+        assert_eq!(visibility("hidden").as_deref(), Some("private"));
+        assert_eq!(visibility("later").as_deref(), Some("export"));
+        assert_eq!(visibility("Service").as_deref(), Some("export"));
+        assert_eq!(visibility("start").as_deref(), Some("export"));
+        assert_eq!(visibility("stop").as_deref(), Some("private"));
+        assert_eq!(visibility("reset").as_deref(), Some("private"));
+        assert_eq!(visibility("Local").as_deref(), Some("private"));
+        assert_eq!(visibility("go").as_deref(), Some("private"));
+    }
 }
@@ -1,18 +1,30 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::num::NonZeroUsize;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
 
 use rusqlite::{Connection, OptionalExtension, params};
 
 use crate::indexer::languages::LanguageAdapter;
 use crate::indexer::languages::go::GoLanguageAdapter;
+use crate::indexer::languages::javascript::JavaScriptLanguageAdapter;
 use crate::indexer::languages::python::PythonLanguageAdapter;
 use crate::indexer::languages::rust::RustLanguageAdapter;
 use crate::indexer::languages::typescript::TypeScriptLanguageAdapter;
+use crate::indexer::workspace::{LayoutDependents, WorkspaceLayout};
 
+pub mod docs;
 pub mod files;
+pub mod history;
+pub mod incremental;
 pub mod languages;
 pub mod rust_ast;
+pub mod search;
 pub mod text;
+pub mod watch;
+pub mod workspace;
 
 #[derive(Debug)]
 pub struct IndexSummary {
@@ -28,16 +40,28 @@ type DeferredEdge = (
     String,
 );
 
-#[derive(Debug)]
-enum FileIndexOutcome {
-    Indexed,
-    Skipped,
+/// Tuning knobs for an index run.
+#[derive(Debug, Clone, Copy)]
+pub struct IndexOptions {
+    /// Number of worker threads that parse and tokenize changed files. Results are always
+    /// written by a single thread in path order, so the index is identical for any value.
+    pub jobs: NonZeroUsize,
+}
+
+impl Default for IndexOptions {
+    fn default() -> Self {
+        Self {
+            jobs: std::thread::available_parallelism().unwrap_or(NonZeroUsize::MIN),
+        }
+    }
 }
 
 #[derive(Debug)]
 struct PreparedFileData {
+    line_count: i64,
     token_occurrences: Vec<text::TokenOccurrence>,
     extracted_symbols: Vec<languages::ExtractedSymbol>,
+    search_documents: Vec<search::SearchDocument>,
     extracted_references: Vec<languages::ExtractedReference>,
     pending_edges: Vec<DeferredEdge>,
 }
@@ -66,85 +90,362 @@ struct PreparedFileData {
 /// assert!(summary.non_source_files >= 0);
 /// ```
 pub fn index_repository(repo: &Path, db_path: &Path) -> anyhow::Result<IndexSummary> {
+    index_repository_with_options(repo, db_path, &IndexOptions::default())
+}
+
+/// Same as [`index_repository`], with explicit [`IndexOptions`] such as the worker count.
+pub fn index_repository_with_options(
+    repo: &Path,
+    db_path: &Path,
+    options: &IndexOptions,
+) -> anyhow::Result<IndexSummary> {
+    let mut connection = Connection::open(db_path)?;
+    let entries = files::discover_source_entries(repo)?;
+    let workspace = workspace_layout(repo, &entry_paths(&entries));
+    let summary = index_source_entries(&mut connection, entries, &workspace, options)?;
+    history::refresh_history(&mut connection, repo)?;
+    Ok(summary)
+}
+
+/// Indexes `repo` into `db_path` without ingesting git history. Used for throwaway stores such
+/// as the revision snapshots `verify-refactor` compares.
+pub fn index_snapshot(repo: &Path, db_path: &Path) -> anyhow::Result<IndexSummary> {
     let mut connection = Connection::open(db_path)?;
-    let source_files = files::discover_source_files(repo)?;
-    let live_paths: HashSet<String> = source_files
+    crate::store::schema::bootstrap_schema(&connection)?;
+    let entries = files::discover_source_entries(repo)?;
+    let workspace = workspace_layout(repo, &entry_paths(&entries));
+    index_source_entries(
+        &mut connection,
+        entries,
+        &workspace,
+        &IndexOptions::default(),
+    )
+}
+
+fn entry_paths(entries: &[files::SourceEntry]) -> Vec<String> {
+    entries
         .iter()
-        .map(|file| file.relative_path.clone())
+        .map(|entry| entry.relative_path.clone())
+        .collect()
+}
+
+/// Builds the workspace layout for `paths`, reading only the manifests it needs from `repo`.
+fn workspace_layout(repo: &Path, paths: &[String]) -> WorkspaceLayout {
+    WorkspaceLayout::from_paths(paths, |path| fs::read_to_string(repo.join(path)).ok())
+}
+
+/// Prunes rows for files missing from `entries`, then indexes every file whose content hash
+/// differs from the stored one. Files whose size and modification time match the stored row
+/// are not read; files whose metadata changed but whose hash did not only get their row
+/// refreshed.
+fn index_source_entries(
+    connection: &mut Connection,
+    entries: Vec<files::SourceEntry>,
+    workspace: &WorkspaceLayout,
+    options: &IndexOptions,
+) -> anyhow::Result<IndexSummary> {
+    let live_paths: HashSet<String> = entries
+        .iter()
+        .map(|entry| entry.relative_path.clone())
         .collect();
-    prune_stale_file_rows(&mut connection, &live_paths)?;
-    let mut summary = IndexSummary {
-        indexed_files: 0,
-        non_source_files: 0,
-    };
-    let mut deferred_edges = Vec::new();
-    for file in source_files {
-        match index_file(&mut connection, file, &mut deferred_edges)? {
-            FileIndexOutcome::Indexed => summary.indexed_files += 1,
-            FileIndexOutcome::Skipped => summary.non_source_files += 1,
+    prune_stale_file_rows(connection, &live_paths)?;
+    invalidate_layout_dependents(connection, workspace, &live_paths)?;
+    let indexed_states = indexed_file_states(connection)?;
+    let total_files = entries.len();
+    let mut changed_files = Vec::new();
+    let mut touched_files = Vec::new();
+    for entry in entries {
+        let stored = indexed_states.get(&entry.relative_path);
+        if stored.is_some_and(|state| state.matches_metadata(&entry.metadata)) {
+            continue;
+        }
+        let file = files::read_source_file(&entry.path, entry.relative_path)?;
+        if stored.is_some_and(|state| state.content_hash == file.content_hash) {
+            touched_files.push((file.relative_path, file.metadata));
+        } else {
+            changed_files.push(file);
         }
     }
-    replay_deferred_edges(&mut connection, deferred_edges)?;
-    Ok(summary)
+    refresh_file_metadata(connection, &touched_files)?;
+    let mut deferred_edges = Vec::new();
+    let indexed_files = index_changed_files(
+        connection,
+        &changed_files,
+        workspace,
+        options.jobs,
+        &mut deferred_edges,
+    )?;
+    replay_deferred_edges(connection, deferred_edges)?;
+    Ok(IndexSummary {
+        indexed_files,
+        non_source_files: total_files - changed_files.len(),
+    })
 }
 
-fn index_file(
+/// Prepares `files` on `jobs` worker threads and writes each result from the calling thread in
+/// slice order, buffering results that finish early. Symbol ids and deferred edges are therefore
+/// assigned exactly as a sequential run would assign them.
+fn index_changed_files(
     connection: &mut Connection,
-    file: files::SourceFile,
+    files: &[files::SourceFile],
+    workspace: &WorkspaceLayout,
+    jobs: NonZeroUsize,
     deferred_edges: &mut Vec<DeferredEdge>,
-) -> anyhow::Result<FileIndexOutcome> {
-    if file_is_unchanged(connection, &file)? {
-        return Ok(FileIndexOutcome::Skipped);
-    }
-    let prepared = prepare_file_data(&file)?;
+) -> anyhow::Result<usize> {
+    let worker_count = jobs.get().min(files.len());
+    let next_file = AtomicUsize::new(0);
+    std::thread::scope(|scope| {
+        let (sender, receiver) = mpsc::sync_channel(worker_count * 2);
+        for _ in 0..worker_count {
+            let sender = sender.clone();
+            let next_file = &next_file;
+            scope.spawn(move || {
+                loop {
+                    let position = next_file.fetch_add(1, Ordering::Relaxed);
+                    let Some(file) = files.get(position) else {
+                        break;
+                    };
+                    if sender
+                        .send((position, prepare_file_data(file, workspace)))
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        let mut ready = BTreeMap::new();
+        let mut next_position = 0;
+        for (position, prepared) in receiver {
+            ready.insert(position, prepared);
+            while let Some(prepared) = ready.remove(&next_position) {
+                write_prepared_file(connection, &files[next_position], prepared?, deferred_edges)?;
+                next_position += 1;
+            }
+        }
+        Ok(next_position)
+    })
+}
+
+fn write_prepared_file(
+    connection: &mut Connection,
+    file: &files::SourceFile,
+    prepared: PreparedFileData,
+    deferred_edges: &mut Vec<DeferredEdge>,
+) -> anyhow::Result<()> {
     let mut reusable_symbol_ids = existing_symbol_ids(connection, &file.relative_path)?;
     let mut next_symbol_id = next_symbol_id_start(connection)?;
     let tx = connection.transaction()?;
+    let inbound_edges = inbound_edges(&tx, &file.relative_path)?;
     clear_file_rows(&tx, &file.relative_path)?;
     insert_text_occurrences(&tx, &file.relative_path, prepared.token_occurrences)?;
     let insert_symbols_result = insert_symbols(
         &tx,
         &file.relative_path,
         prepared.extracted_symbols,
+        prepared.search_documents,
         &mut reusable_symbol_ids,
         &mut next_symbol_id,
     );
     insert_symbols_result?;
+    restore_inbound_edges(&tx, &file.relative_path, inbound_edges)?;
     insert_references(&tx, &file.relative_path, prepared.extracted_references)?;
     insert_or_defer_edges(&tx, prepared.pending_edges, deferred_edges)?;
-    let file_line_count = std::str::from_utf8(&file.bytes)
-        .map(|s| s.lines().count())
-        .unwrap_or(0) as i64;
-    upsert_indexed_file_row(
-        &tx,
-        &file.relative_path,
-        &file.content_hash,
-        file_line_count,
-    )?;
+    upsert_indexed_file_row(&tx, file, prepared.line_count)?;
     tx.commit()?;
-    Ok(FileIndexOutcome::Indexed)
+    Ok(())
 }
 
-fn file_is_unchanged(connection: &Connection, file: &files::SourceFile) -> anyhow::Result<bool> {
-    let existing_hash: Option<String> = connection
-        .query_row(
-            "SELECT content_hash FROM indexed_files WHERE file_path = ?1",
-            [&file.relative_path],
-            |row| row.get(0),
-        )
-        .optional()?;
-    Ok(existing_hash.as_deref() == Some(file.content_hash.as_str()))
+/// What the `indexed_files` table records about a file from the last run.
+struct IndexedFileState {
+    content_hash: String,
+    metadata: Option<files::FileMetadata>,
+}
+
+impl IndexedFileState {
+    /// Whether `metadata` proves the file is unchanged. An empty hash marks a file that a
+    /// migration queued for re-extraction, and a missing timestamp is never trusted.
+    fn matches_metadata(&self, metadata: &files::FileMetadata) -> bool {
+        !self.content_hash.is_empty()
+            && metadata.modified_ns.is_some()
+            && self.metadata.as_ref() == Some(metadata)
+    }
+}
+
+fn indexed_file_states(
+    connection: &Connection,
+) -> anyhow::Result<HashMap<String, IndexedFileState>> {
+    let mut statement = connection
+        .prepare("SELECT file_path, content_hash, file_size, modified_ns FROM indexed_files")?;
+    let rows = statement.query_map([], |row| {
+        let file_size = row.get::<_, Option<i64>>(2)?;
+        let modified_ns = row.get::<_, Option<i64>>(3)?;
+        let state = IndexedFileState {
+            content_hash: row.get(1)?,
+            metadata: file_size.map(|size| files::FileMetadata { size, modified_ns }),
+        };
+        Ok((row.get::<_, String>(0)?, state))
+    })?;
+    Ok(rows.collect::<Result<HashMap<_, _>, _>>()?)
+}
+
+/// Records new metadata for files whose contents did not change.
+fn refresh_file_metadata(
+    connection: &mut Connection,
+    files: &[(String, files::FileMetadata)],
+) -> anyhow::Result<()> {
+    if files.is_empty() {
+        return Ok(());
+    }
+    let tx = connection.transaction()?;
+    for (file_path, metadata) in files {
+        tx.execute(
+            "UPDATE indexed_files SET file_size = ?2, modified_ns = ?3 WHERE file_path = ?1",
+            params![file_path, metadata.size, metadata.modified_ns],
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
 }
 
-fn prepare_file_data(file: &files::SourceFile) -> anyhow::Result<PreparedFileData> {
+const LAYOUT_FINGERPRINT_PREFIX: &str = "layout:";
+
+/// Compares the layout's fingerprints with those stored by the previous run and clears the
+/// stored hash of every live file that resolved imports against a part that changed, so it is
+/// extracted again even though its own content did not change. Returns those files.
+fn invalidate_layout_dependents<'a>(
+    connection: &mut Connection,
+    workspace: &WorkspaceLayout,
+    live_paths: impl IntoIterator<Item = &'a String>,
+) -> anyhow::Result<BTreeSet<String>> {
+    let fingerprints = workspace.fingerprints();
+    let mut stored = {
+        let mut statement =
+            connection.prepare("SELECT key, value FROM meta WHERE key LIKE 'layout:%'")?;
+        let rows = statement.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        rows.collect::<Result<HashMap<_, _>, _>>()?
+    };
+    let mut changed = Vec::new();
+    for (key, value) in &fingerprints {
+        let stored_key = format!("{LAYOUT_FINGERPRINT_PREFIX}{key}");
+        if stored.remove(&stored_key).as_ref() != Some(value) {
+            changed.push(key.clone());
+        }
+    }
+    changed.extend(stored.into_keys().filter_map(|stored_key| {
+        stored_key
+            .strip_prefix(LAYOUT_FINGERPRINT_PREFIX)
+            .map(str::to_string)
+    }));
+    if changed.is_empty() {
+        return Ok(BTreeSet::new());
+    }
+
+    let live_paths = live_paths.into_iter().collect::<Vec<_>>();
+    let mut dependents = BTreeSet::new();
+    for key in &changed {
+        match workspace.layout_dependents(key) {
+            Some(LayoutDependents::Extensions(extensions)) => {
+                dependents.extend(
+                    live_paths
+                        .iter()
+                        .filter(|path| has_extension(path, extensions))
+                        .map(|path| (*path).clone()),
+                );
+            }
+            Some(LayoutDependents::GoPackage { directory, tokens }) => {
+                dependents.extend(
+                    live_paths
+                        .iter()
+                        .filter(|path| {
+                            has_extension(path, &["go"])
+                                && path.rsplit_once('/').map_or("", |(parent, _)| parent)
+                                    == directory
+                        })
+                        .map(|path| (*path).clone()),
+                );
+                let mentioning = files_mentioning(connection, &tokens)?;
+                dependents.extend(
+                    live_paths
+                        .iter()
+                        .filter(|path| {
+                            has_extension(path, &["go"]) && mentioning.contains(path.as_str())
+                        })
+                        .map(|path| (*path).clone()),
+                );
+            }
+            None => {}
+        }
+    }
+
+    let tx = connection.transaction()?;
+    for path in &dependents {
+        tx.execute(
+            "UPDATE indexed_files SET content_hash = '' WHERE file_path = ?1",
+            [path],
+        )?;
+    }
+    tx.execute("DELETE FROM meta WHERE key LIKE 'layout:%'", [])?;
+    for (key, value) in &fingerprints {
+        tx.execute(
+            "INSERT INTO meta(key, value) VALUES (?1, ?2)",
+            params![format!("{LAYOUT_FINGERPRINT_PREFIX}{key}"), value],
+        )?;
+    }
+    tx.commit()?;
+    Ok(dependents)
+}
+
+fn has_extension(path: &str, extensions: &[&str]) -> bool {
+    extensions
+        .iter()
+        .any(|extension| path.ends_with(&format!(".{extension}")))
+}
+
+fn files_mentioning(connection: &Connection, tokens: &[String]) -> anyhow::Result<HashSet<String>> {
+    let mut statement =
+        connection.prepare("SELECT DISTINCT file_path FROM text_occurrences WHERE symbol = ?1")?;
+    let mut paths = HashSet::new();
+    for token in tokens {
+        let rows = statement.query_map([token], |row| row.get::<_, String>(0))?;
+        for row in rows {
+            paths.insert(row?);
+        }
+    }
+    Ok(paths)
+}
+
+fn indexed_file_hashes(connection: &Connection) -> anyhow::Result<HashMap<String, String>> {
+    let mut statement = connection.prepare("SELECT file_path, content_hash FROM indexed_files")?;
+    let rows = statement.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+    Ok(rows.collect::<Result<HashMap<_, _>, _>>()?)
+}
+
+fn prepare_file_data(
+    file: &files::SourceFile,
+    workspace: &WorkspaceLayout,
+) -> anyhow::Result<PreparedFileData> {
     let text_content = std::str::from_utf8(&file.bytes).ok();
+    let line_count = text_content.map_or(0, |source| source.lines().count()) as i64;
     let token_occurrences = text_content
         .map(text::extract_token_occurrences)
         .unwrap_or_default();
-    let extraction_unit = text_content
-        .map(|source| extract_with_adapter(&file.relative_path, source))
+    let mut extraction_unit = text_content
+        .map(|source| extract_with_adapter(&file.relative_path, source, workspace))
         .transpose()?
         .unwrap_or_default();
+    if let Some(source) = text_content {
+        let lines = source.lines().collect::<Vec<_>>();
+        for symbol in &mut extraction_unit.symbols {
+            symbol.doc_comment = docs::doc_comment(symbol, &lines);
+        }
+    }
     let pending_edges = extraction_unit
         .edges
         .into_iter()
@@ -158,9 +459,18 @@ fn prepare_file_data(file: &files::SourceFile) -> anyhow::Result<PreparedFileDat
             )
         })
         .collect();
+    let search_documents = extraction_unit
+        .symbols
+        .iter()
+        .map(|symbol| {
+            search::search_document(symbol, &file.relative_path, text_content.unwrap_or(""))
+        })
+        .collect();
     Ok(PreparedFileData {
+        line_count,
         token_occurrences,
         extracted_symbols: extraction_unit.symbols,
+        search_documents,
         extracted_references: extraction_unit.references,
         pending_edges,
     })
@@ -185,10 +495,73 @@ fn clear_file_rows(tx: &rusqlite::Transaction<'_>, file_path: &str) -> anyhow::R
             OR to_symbol_id IN (SELECT symbol_id FROM symbols_v2 WHERE file_path = ?1)",
         [file_path],
     )?;
+    tx.execute("DELETE FROM search_terms WHERE file_path = ?1", [file_path])?;
+    tx.execute(
+        "DELETE FROM search_documents WHERE file_path = ?1",
+        [file_path],
+    )?;
     tx.execute("DELETE FROM symbols_v2 WHERE file_path = ?1", [file_path])?;
     Ok(())
 }
 
+/// An edge row as stored: from id, to id, kind, confidence, and provenance.
+type StoredEdge = (i64, i64, String, f64, String);
+
+/// Edges from symbols in other files into `file_path`. Clearing the file drops them, so they are
+/// read first and restored for the symbols that keep their ids after re-extraction; otherwise a
+/// caller that is not re-extracted in the same run would lose its edge.
+fn inbound_edges(
+    tx: &rusqlite::Transaction<'_>,
+    file_path: &str,
+) -> anyhow::Result<Vec<StoredEdge>> {
+    let mut statement = tx.prepare(
+        "SELECT edges.from_symbol_id, edges.to_symbol_id, edges.edge_kind, edges.confidence,
+                edges.provenance
+         FROM symbol_edges_v2 edges
+         JOIN symbols_v2 from_symbols ON from_symbols.symbol_id = edges.from_symbol_id
+         JOIN symbols_v2 to_symbols ON to_symbols.symbol_id = edges.to_symbol_id
+         WHERE to_symbols.file_path = ?1 AND from_symbols.file_path <> ?1",
+    )?;
+    let rows = statement.query_map([file_path], |row| {
+        Ok((
+            row.get(0)?,
+            row.get(1)?,
+            row.get(2)?,
+            row.get(3)?,
+            row.get(4)?,
+        ))
+    })?;
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+fn restore_inbound_edges(
+    tx: &rusqlite::Transaction<'_>,
+    file_path: &str,
+    edges: Vec<StoredEdge>,
+) -> anyhow::Result<()> {
+    for (from_symbol_id, to_symbol_id, edge_kind, confidence, provenance) in edges {
+        tx.execute(
+            "INSERT INTO symbol_edges_v2(
+                from_symbol_id, to_symbol_id, edge_kind, confidence, provenance
+             )
+             SELECT ?1, ?2, ?3, ?4, ?5
+             WHERE EXISTS (
+                SELECT 1 FROM symbols_v2 WHERE symbol_id = ?2 AND file_path = ?6
+             )
+             ON CONFLICT(from_symbol_id, to_symbol_id, edge_kind) DO NOTHING",
+            params![
+                from_symbol_id,
+                to_symbol_id,
+                edge_kind,
+                confidence,
+                provenance,
+                file_path
+            ],
+        )?;
+    }
+    Ok(())
+}
+
 fn insert_text_occurrences(
     tx: &rusqlite::Transaction<'_>,
     file_path: &str,
@@ -213,10 +586,11 @@ fn insert_symbols(
     tx: &rusqlite::Transaction<'_>,
     file_path: &str,
     extracted_symbols: Vec<languages::ExtractedSymbol>,
+    search_documents: Vec<search::SearchDocument>,
     reusable_symbol_ids: &mut HashMap<(String, String), Vec<i64>>,
     next_symbol_id: &mut i64,
 ) -> anyhow::Result<()> {
-    for definition in extracted_symbols {
+    for (definition, document) in extracted_symbols.into_iter().zip(search_documents) {
         let symbol = definition.symbol;
         let kind = definition.kind;
         let language = definition.language;
@@ -242,10 +616,12 @@ fn insert_symbols(
         )?;
         let symbol_line_count =
             i64::from(definition.end_line) - i64::from(definition.start_line) + 1;
+        let metrics = definition.metrics;
         tx.execute(
             "INSERT INTO symbols_v2(
-                symbol_id, file_path, symbol, kind, language, qualified_symbol, container, start_line, start_column, end_line, end_column, signature, line_count
-             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                symbol_id, file_path, symbol, kind, language, qualified_symbol, container, start_line, start_column, end_line, end_column, signature, line_count, test_path,
+                param_count, nesting_depth, branch_count, complexity_score, visibility, doc_comment
+             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20)",
             params![
                 symbol_id,
                 file_path,
@@ -259,9 +635,43 @@ fn insert_symbols(
                 i64::from(definition.end_line),
                 i64::from(definition.end_column),
                 definition.signature.as_deref(),
-                symbol_line_count
+                symbol_line_count,
+                definition.test_path.as_deref(),
+                metrics.map(|metrics| metrics.param_count),
+                metrics.map(|metrics| metrics.nesting_depth),
+                metrics.map(|metrics| metrics.branch_count),
+                metrics.map(|metrics| metrics.complexity_score),
+                definition.visibility.as_deref(),
+                definition.doc_comment.as_deref()
             ],
         )?;
+        insert_search_document(tx, file_path, symbol_id, &document)?;
+    }
+    Ok(())
+}
+
+fn insert_search_document(
+    tx: &rusqlite::Transaction<'_>,
+    file_path: &str,
+    symbol_id: i64,
+    document: &search::SearchDocument,
+) -> anyhow::Result<()> {
+    tx.prepare_cached(
+        "INSERT INTO search_documents(symbol_id, file_path, term_count, token_cost)
+         VALUES (?1, ?2, ?3, ?4)",
+    )?
+    .execute(params![
+        symbol_id,
+        file_path,
+        document.term_count,
+        document.token_cost
+    ])?;
+    let mut insert_term = tx.prepare_cached(
+        "INSERT INTO search_terms(symbol_id, file_path, field, term, frequency)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for ((field, term), frequency) in &document.terms {
+        insert_term.execute(params![symbol_id, file_path, field, term, frequency])?;
     }
     Ok(())
 }
@@ -337,15 +747,22 @@ fn insert_or_defer_edges(
 
 fn upsert_indexed_file_row(
     tx: &rusqlite::Transaction<'_>,
-    file_path: &str,
-    content_hash: &str,
+    file: &files::SourceFile,
     line_count: i64,
 ) -> anyhow::Result<()> {
     tx.execute(
-        "INSERT INTO indexed_files(file_path, content_hash, line_count)
-         VALUES (?1, ?2, ?3)
-         ON CONFLICT(file_path) DO UPDATE SET content_hash = excluded.content_hash, line_count = excluded.line_count",
-        params![file_path, content_hash, line_count],
+        "INSERT INTO indexed_files(file_path, content_hash, line_count, file_size, modified_ns)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(file_path) DO UPDATE SET content_hash = excluded.content_hash,
+             line_count = excluded.line_count, file_size = excluded.file_size,
+             modified_ns = excluded.modified_ns",
+        params![
+            file.relative_path,
+            file.content_hash,
+            line_count,
+            file.metadata.size,
+            file.metadata.modified_ns
+        ],
     )?;
     Ok(())
 }
@@ -462,12 +879,17 @@ fn prune_stale_file_rows(
         stale_paths
     };
 
-    if stale_paths.is_empty() {
+    delete_file_rows(connection, &stale_paths)
+}
+
+/// Removes every row recorded for `paths`.
+fn delete_file_rows(connection: &mut Connection, paths: &[String]) -> anyhow::Result<()> {
+    if paths.is_empty() {
         return Ok(());
     }
 
     let tx = connection.transaction()?;
-    for path in stale_paths {
+    for path in paths {
         tx.execute("DELETE FROM text_occurrences WHERE file_path = ?1", [&path])?;
         tx.execute("DELETE FROM ast_definitions WHERE file_path = ?1", [&path])?;
         tx.execute("DELETE FROM ast_references WHERE file_path = ?1", [&path])?;
@@ -477,6 +899,8 @@ fn prune_stale_file_rows(
                 OR to_symbol_id IN (SELECT symbol_id FROM symbols_v2 WHERE file_path = ?1)",
             [&path],
         )?;
+        tx.execute("DELETE FROM search_terms WHERE file_path = ?1", [&path])?;
+        tx.execute("DELETE FROM search_documents WHERE file_path = ?1", [&path])?;
         tx.execute("DELETE FROM symbols_v2 WHERE file_path = ?1", [&path])?;
         tx.execute("DELETE FROM indexed_files WHERE file_path = ?1", [&path])?;
     }
@@ -746,15 +1170,18 @@ fn take_reusable_symbol_id(
 fn extract_with_adapter(
     file_path: &str,
     source: &str,
+    workspace: &WorkspaceLayout,
 ) -> anyhow::Result<languages::ExtractionUnit> {
     let rust_adapter = RustLanguageAdapter;
     let typescript_adapter = TypeScriptLanguageAdapter;
+    let javascript_adapter = JavaScriptLanguageAdapter;
     let python_adapter = PythonLanguageAdapter;
     let go_adapter = GoLanguageAdapter;
 
     for adapter in [
         &rust_adapter as &dyn LanguageAdapter,
         &typescript_adapter as &dyn LanguageAdapter,
+        &javascript_adapter as &dyn LanguageAdapter,
         &python_adapter as &dyn LanguageAdapter,
         &go_adapter as &dyn LanguageAdapter,
     ] {
@@ -763,7 +1190,7 @@ fn extract_with_adapter(
             .iter()
             .any(|extension| file_path.ends_with(&format!(".{extension}")))
         {
-            return adapter.extract(file_path, source);
+            return adapter.extract_in_workspace(file_path, source, workspace);
         }
     }
 
@@ -824,18 +1251,33 @@ mod tests {
     }
 
     #[test]
-    fn index_file_indexes_changed_source_file() {
+    fn index_changed_files_writes_prepared_source_file() {
         let mut connection = bootstrap_connection();
         let bytes = b"notes about symbols\n".to_vec();
         let source_file = files::SourceFile {
             relative_path: "notes/readme.txt".to_string(),
             content_hash: blake3::hash(&bytes).to_hex().to_string(),
             bytes,
+            metadata: files::FileMetadata::default(),
         };
         let mut deferred_edges = Vec::new();
-        let outcome = index_file(&mut connection, source_file, &mut deferred_edges)
-            .expect("index_file should succeed");
-        assert!(matches!(outcome, FileIndexOutcome::Indexed));
+        let indexed_files = index_changed_files(
+            &mut connection,
+            &[source_file],
+            &WorkspaceLayout::default(),
+            NonZeroUsize::MIN,
+            &mut deferred_edges,
+        )
+        .expect("index_changed_files should succeed");
+        assert_eq!(indexed_files, 1);
+        let line_count: i64 = connection
+            .query_row(
+                "SELECT line_count FROM indexed_files WHERE file_path = 'notes/readme.txt'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("indexed file row should exist");
+        assert_eq!(line_count, 1);
     }
 
     #[test]
@@ -1066,4 +1508,115 @@ mod tests {
             "next-symbol-id helper should fail when symbols_v2 is missing"
         );
     }
+
+    #[test]
+    fn index_source_entries_skips_files_whose_metadata_is_unchanged() {
+        let repo = tempfile::tempdir().expect("temp dir should be created");
+        let source_path = repo.path().join("lib.rs");
+        let an_hour_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        let write_with_mtime = |contents: &str, modified: std::time::SystemTime| {
+            fs::write(&source_path, contents).expect("fixture should write");
+            fs::File::options()
+                .write(true)
+                .open(&source_path)
+                .and_then(|file| file.set_modified(modified))
+                .expect("fixture mtime should be set");
+        };
+        let mut connection = bootstrap_connection();
+        let index = |connection: &mut Connection| {
+            let entries = files::discover_source_entries(repo.path()).expect("discovery works");
+            index_source_entries(
+                connection,
+                entries,
+                &WorkspaceLayout::default(),
+                &IndexOptions::default(),
+            )
+            .expect("indexing should succeed")
+        };
+        let symbols = |connection: &Connection| {
+            connection
+                .query_row("SELECT group_concat(symbol) FROM symbols_v2", [], |row| {
+                    row.get::<_, String>(0)
+                })
+                .expect("symbols should load")
+        };
+
+        write_with_mtime("fn alpha() {}\n", an_hour_ago);
+        assert_eq!(index(&mut connection).indexed_files, 1);
+        assert_eq!(symbols(&connection), "alpha");
+
+        // Same size and mtime: the file is not read, so the edit goes unnoticed.
+        write_with_mtime("fn gamma() {}\n", an_hour_ago);
+        assert_eq!(index(&mut connection).indexed_files, 0);
+        assert_eq!(symbols(&connection), "alpha");
+
+        let later = an_hour_ago + std::time::Duration::from_secs(60);
+        write_with_mtime("fn gamma() {}\n", later);
+        assert_eq!(index(&mut connection).indexed_files, 1);
+        assert_eq!(symbols(&connection), "gamma");
+
+        // A new mtime with the same contents only refreshes the stored metadata.
+        write_with_mtime("fn gamma() {}\n", an_hour_ago);
+        assert_eq!(index(&mut connection).indexed_files, 0);
+        let stored_mtime = connection
+            .query_row("SELECT modified_ns FROM indexed_files", [], |row| {
+                row.get::<_, Option<i64>>(0)
+            })
+            .expect("metadata should load");
+        let expected = an_hour_ago
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("fixture time is after the epoch")
+            .as_nanos() as i64;
+        assert_eq!(stored_mtime, Some(expected));
+    }
+
+    #[test]
+    fn reindexing_a_callee_keeps_edges_from_unchanged_callers() {
+        let repo = tempfile::tempdir().expect("temp dir should be created");
+        fs::write(
+            repo.path().join("caller.py"),
+            "from callee import load\n\n\ndef run():\n    return load()\n",
+        )
+        .expect("fixture should write");
+        fs::write(repo.path().join("callee.py"), "def load():\n    return 1\n")
+            .expect("fixture should write");
+        let mut connection = bootstrap_connection();
+        let index = |connection: &mut Connection| {
+            let entries = files::discover_source_entries(repo.path()).expect("discovery works");
+            let workspace = workspace_layout(repo.path(), &entry_paths(&entries));
+            index_source_entries(connection, entries, &workspace, &IndexOptions::default())
+                .expect("indexing should succeed")
+        };
+        let call_edges = |connection: &Connection| {
+            connection
+                .query_row(
+                    "SELECT COUNT(*)
+                     FROM symbol_edges_v2 edges
+                     JOIN symbols_v2 callee ON callee.symbol_id = edges.to_symbol_id
+                     WHERE edges.edge_kind = 'calls' AND callee.file_path = 'callee.py'",
+                    [],
+                    |row| row.get::<_, i64>(0),
+                )
+                .expect("edges should load")
+        };
+
+        index(&mut connection);
+        assert_eq!(call_edges(&connection), 1);
+
+        fs::write(
+            repo.path().join("callee.py"),
+            "def load():\n    value = 2\n    return value\n",
+        )
+        .expect("fixture should write");
+        assert_eq!(index(&mut connection).indexed_files, 1);
+        assert_eq!(call_edges(&connection), 1);
+
+        fs::write(
+            repo.path().join("callee.py"),
+            "def other():\n    return 3\n",
+        )
+        .expect("fixture should write");
+        index(&mut connection);
+        assert_eq!(call_edges(&connection), 0);
+    }
 }
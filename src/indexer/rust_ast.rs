@@ -1,6 +1,24 @@
 use anyhow::Context;
 use tree_sitter::{Node, Parser};
 
+use crate::indexer::languages::complexity::{ComplexityMetrics, ComplexityRules, function_metrics};
+
+const COMPLEXITY_RULES: ComplexityRules = ComplexityRules {
+    parameter_kinds: &["parameter", "variadic_parameter"],
+    receiver_names: &[],
+    branch_kinds: &[
+        "if_expression",
+        "while_expression",
+        "loop_expression",
+        "for_expression",
+    ],
+    flat_branch_kinds: &[],
+    switch_kinds: &["match_expression"],
+    arm_kinds: &["match_arm"],
+    logical_kinds: &["binary_expression"],
+    nested_definition_kinds: &["function_item", "impl_item", "trait_item", "mod_item"],
+};
+
 #[derive(Debug, Clone)]
 pub struct AstDefinition {
     pub symbol: String,
@@ -11,6 +29,13 @@ pub struct AstDefinition {
     pub end_line: u32,
     pub end_column: u32,
     pub signature: Option<String>,
+    /// Path of the test this item belongs to, relative to its file (`tests::parses_input`):
+    /// the item itself for `#[test]` functions and for modules in or marked `#[cfg(test)]`,
+    /// otherwise the innermost module when the item sits inside a `#[cfg(test)]` module.
+    pub test_path: Option<String>,
+    pub metrics: Option<ComplexityMetrics>,
+    /// The item's visibility modifier (`pub`, `pub(crate)`, `pub(in crate::a)`), or `private`.
+    pub visibility: String,
 }
 
 #[derive(Debug, Clone)]
@@ -19,6 +44,8 @@ pub struct AstReference {
     pub line: u32,
     pub column: u32,
     pub caller: Option<String>,
+    /// Inline `mod` blocks enclosing the reference, outermost first.
+    pub modules: Vec<String>,
 }
 
 /// Extracts top-level Rust item definitions and call references from the given source.
@@ -95,6 +122,9 @@ pub fn extract_rust_items(source: &str) -> anyhow::Result<(Vec<AstDefinition>, V
                         end_line,
                         end_column,
                         signature: None,
+                        test_path: enclosing_test_module_path(node, source),
+                        metrics: None,
+                        visibility: visibility(node, source),
                     });
                 }
             }
@@ -109,6 +139,9 @@ pub fn extract_rust_items(source: &str) -> anyhow::Result<(Vec<AstDefinition>, V
                     );
                 }
             }
+            "token_tree" if is_macro_argument(node) => {
+                collect_macro_call_identifiers(node, source, &mut references);
+            }
             _ => {}
         }
 
@@ -192,6 +225,7 @@ fn collect_call_identifiers(
                     line,
                     column,
                     caller: caller.map(str::to_string),
+                    modules: enclosing_module_names(current, source),
                 });
             }
             continue;
@@ -205,6 +239,45 @@ fn collect_call_identifiers(
     }
 }
 
+/// Token trees are not parsed as expressions, so `assert_eq!(parse(input), 2)` hides the call
+/// from `call_expression` handling. Inside macro invocations, an identifier directly followed
+/// by a parenthesized token tree is treated as a call.
+fn collect_macro_call_identifiers(node: Node<'_>, source: &str, output: &mut Vec<AstReference>) {
+    let caller = enclosing_function_name(node, source);
+    let mut cursor = node.walk();
+    let children = node.children(&mut cursor).collect::<Vec<_>>();
+    for pair in children.windows(2) {
+        let [name, arguments] = pair else {
+            continue;
+        };
+        let is_call = name.kind() == "identifier"
+            && arguments.kind() == "token_tree"
+            && source.as_bytes().get(arguments.start_byte()) == Some(&b'(');
+        if is_call && let Some(symbol) = node_text(*name, source) {
+            let (line, column) = start_position(*name);
+            output.push(AstReference {
+                symbol,
+                line,
+                column,
+                caller: caller.clone(),
+                modules: enclosing_module_names(*name, source),
+            });
+        }
+    }
+}
+
+fn is_macro_argument(node: Node<'_>) -> bool {
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        match parent.kind() {
+            "macro_invocation" => return true,
+            "token_tree" => current = parent.parent(),
+            _ => return false,
+        }
+    }
+    false
+}
+
 /// Create an `AstDefinition` from the node's declared name and append it to `output`.
 ///
 /// If the node has no `name` field, if the name is not an `identifier` or `type_identifier`,
@@ -239,6 +312,7 @@ fn push_named_definition(
 
     let (line, column) = start_position(name_node);
     let (end_line, end_column) = end_position(node);
+    let test_path = test_item_path(node, &symbol, source);
     output.push(AstDefinition {
         symbol,
         kind: kind.to_string(),
@@ -248,9 +322,138 @@ fn push_named_definition(
         end_line,
         end_column,
         signature,
+        test_path,
+        metrics: (kind == "function").then(|| function_metrics(node, source, &COMPLEXITY_RULES)),
+        visibility: visibility(node, source),
     });
 }
 
+fn visibility(node: Node<'_>, source: &str) -> String {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .find(|child| child.kind() == "visibility_modifier")
+        .and_then(|modifier| node_text(modifier, source))
+        .map(|text| {
+            text.split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .replace("( ", "(")
+                .replace(" )", ")")
+        })
+        .unwrap_or_else(|| "private".to_string())
+}
+
+/// Returns the in-file test path of a named item: `#[test]`-style functions and
+/// `#[cfg(test)]` modules name themselves, anything else defers to its enclosing test module.
+fn test_item_path(node: Node<'_>, symbol: &str, source: &str) -> Option<String> {
+    let is_test_item = match node.kind() {
+        "function_item" => has_outer_attribute(node, source, is_test_attribute),
+        "mod_item" => has_outer_attribute(node, source, is_cfg_test_attribute),
+        _ => false,
+    };
+    if !is_test_item {
+        let module_path = enclosing_test_module_path(node, source)?;
+        // A module nested in a test module is a test path of its own.
+        return Some(match node.kind() {
+            "mod_item" if module_path.is_empty() => symbol.to_string(),
+            "mod_item" => format!("{module_path}::{symbol}"),
+            _ => module_path,
+        });
+    }
+    let mut segments = enclosing_module_names(node, source);
+    segments.push(symbol.to_string());
+    Some(segments.join("::"))
+}
+
+/// Returns the path of the innermost inline module around `node` when any enclosing module,
+/// or the file itself via `#![cfg(test)]`, is compiled only for tests.
+fn enclosing_test_module_path(node: Node<'_>, source: &str) -> Option<String> {
+    let mut in_test_module = false;
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        in_test_module |= match parent.kind() {
+            "mod_item" => has_outer_attribute(parent, source, is_cfg_test_attribute),
+            "declaration_list" | "source_file" => {
+                has_inner_attribute(parent, source, is_cfg_test_attribute)
+            }
+            _ => false,
+        };
+        current = parent.parent();
+    }
+    in_test_module.then(|| enclosing_module_names(node, source).join("::"))
+}
+
+fn enclosing_module_names(node: Node<'_>, source: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        if parent.kind() == "mod_item"
+            && let Some(name) = parent
+                .child_by_field_name("name")
+                .and_then(|name_node| node_text(name_node, source))
+        {
+            names.push(name);
+        }
+        current = parent.parent();
+    }
+    names.reverse();
+    names
+}
+
+/// Checks the `#[...]` attributes written directly above `node`.
+fn has_outer_attribute(node: Node<'_>, source: &str, predicate: fn(&str) -> bool) -> bool {
+    let mut sibling = node.prev_sibling();
+    while let Some(current) = sibling {
+        match current.kind() {
+            "attribute_item" => {
+                if node_text(current, source).is_some_and(|text| predicate(attribute_body(&text))) {
+                    return true;
+                }
+            }
+            "line_comment" | "block_comment" => {}
+            _ => break,
+        }
+        sibling = current.prev_sibling();
+    }
+    false
+}
+
+/// Checks the `#![...]` attributes declared inside a module body or at the top of a file.
+fn has_inner_attribute(node: Node<'_>, source: &str, predicate: fn(&str) -> bool) -> bool {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| child.kind() == "inner_attribute_item")
+        .filter_map(|child| node_text(child, source))
+        .any(|text| predicate(attribute_body(&text)))
+}
+
+fn attribute_body(text: &str) -> &str {
+    text.trim_start_matches('#')
+        .trim_start_matches('!')
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .trim()
+}
+
+/// `#[test]`, `#[tokio::test]`, `#[async_std::test(...)]`, and similar test-harness attributes.
+fn is_test_attribute(body: &str) -> bool {
+    let path = body.split('(').next().unwrap_or(body).trim();
+    path.rsplit("::").next() == Some("test")
+}
+
+fn is_cfg_test_attribute(body: &str) -> bool {
+    let Some(predicate) = body.strip_prefix("cfg") else {
+        return false;
+    };
+    let mut words = predicate
+        .split(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_'))
+        .filter(|word| !word.is_empty());
+    predicate.trim_start().starts_with('(')
+        && words.clone().any(|word| word == "test")
+        && !words.any(|word| word == "not")
+}
+
 /// Finds the type name for the `impl` block that encloses `node`, if any.
 ///
 /// The returned `String` is the last identifier text of the `impl`'s `type` child
@@ -599,6 +802,148 @@ mod tests {
         );
     }
 
+    #[test]
+    fn extract_rust_items_marks_test_functions_and_modules() {
+        let source = r#"
+pub fn parse() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() {}
+
+    #[test]
+    fn parses_input() { parse(); }
+
+    #[tokio::test]
+    async fn parses_async() {}
+
+    mod nested {
+        fn nested_fixture() {}
+
+        #[test]
+        fn parses_nested() {}
+    }
+}
+
+#[cfg(not(test))]
+mod runtime {
+    fn start() {}
+}
+"#;
+        let (definitions, _) = extract_rust_items(source).expect("source should parse");
+        let test_path = |symbol: &str| {
+            definitions
+                .iter()
+                .find(|definition| definition.symbol == symbol)
+                .map(|definition| definition.test_path.as_deref())
+                .expect("definition should be extracted")
+        };
+        assert_eq!(test_path("parse"), None);
+        assert_eq!(test_path("tests"), Some("tests"));
+        assert_eq!(test_path("fixture"), Some("tests"));
+        assert_eq!(test_path("parses_input"), Some("tests::parses_input"));
+        assert_eq!(test_path("parses_async"), Some("tests::parses_async"));
+        assert_eq!(test_path("nested"), Some("tests::nested"));
+        assert_eq!(test_path("nested_fixture"), Some("tests::nested"));
+        assert_eq!(
+            test_path("parses_nested"),
+            Some("tests::nested::parses_nested")
+        );
+        assert_eq!(test_path("runtime"), None);
+        assert_eq!(test_path("start"), None);
+
+        let (definitions, _) =
+            extract_rust_items("#![cfg(test)]\nfn helper() {}\n").expect("source should parse");
+        assert_eq!(definitions[0].test_path.as_deref(), Some(""));
+    }
+
+    #[test]
+    fn extract_rust_items_reports_calls_inside_macro_arguments() {
+        let source = "fn check() {\n    assert_eq!(parse(read()), vec![1]);\n}\n";
+        let (_, references) = extract_rust_items(source).expect("source should parse");
+        let calls = references
+            .iter()
+            .map(|reference| (reference.symbol.as_str(), reference.caller.as_deref()))
+            .collect::<Vec<_>>();
+        assert!(calls.contains(&("parse", Some("check"))), "{calls:?}");
+        assert!(calls.contains(&("read", Some("check"))), "{calls:?}");
+        assert!(
+            !calls.iter().any(|(symbol, _)| *symbol == "vec"),
+            "{calls:?}"
+        );
+    }
+
+    #[test]
+    fn extract_rust_items_measures_function_complexity() {
+        let source = r#"
+struct Parser;
+
+impl Parser {
+    fn classify(&self, value: Option<u32>, strict: bool) -> u32 {
+        match value {
+            Some(0) => 0,
+            Some(v) if strict && v > 2 => {
+                if v > 10 {
+                    for _ in 0..v {
+                        if v % 2 == 0 || v % 3 == 0 {
+                            return 1;
+                        }
+                    }
+                } else if v > 5 {
+                    return 2;
+                }
+                v
+            }
+            _ => 3,
+        }
+    }
+}
+
+fn plain(a: u32, b: u32) -> u32 {
+    fn nested(flag: bool) -> u32 {
+        if flag { 1 } else { 0 }
+    }
+    a + b + nested(true)
+}
+"#;
+        let (definitions, _) = extract_rust_items(source).expect("source should parse");
+        let metrics = |name: &str| {
+            definitions
+                .iter()
+                .find(|definition| definition.symbol == name)
+                .and_then(|definition| definition.metrics)
+                .expect("function should carry metrics")
+        };
+
+        assert_eq!(
+            metrics("classify"),
+            ComplexityMetrics {
+                param_count: 2,
+                nesting_depth: 4,
+                branch_count: 5,
+                complexity_score: 9,
+            }
+        );
+        assert_eq!(
+            metrics("plain"),
+            ComplexityMetrics {
+                param_count: 2,
+                nesting_depth: 0,
+                branch_count: 0,
+                complexity_score: 1,
+            }
+        );
+        assert_eq!(metrics("nested").complexity_score, 2);
+        assert!(
+            definitions
+                .iter()
+                .find(|definition| definition.symbol == "Parser")
+                .is_some_and(|definition| definition.metrics.is_none())
+        );
+    }
+
     #[test]
     fn signature_and_enclosing_function_helpers_cover_none_paths() {
         let block_source = "{\n}\n";
@@ -617,4 +962,31 @@ mod tests {
             "root nodes should not report enclosing functions"
         );
     }
+
+    #[test]
+    fn extract_rust_items_records_visibility_modifiers() {
+        let source = r#"
+pub fn open() {}
+pub(crate) fn shared() {}
+pub( super ) struct Parent;
+pub(in crate::store) enum Scoped {}
+fn hidden() {}
+pub use crate::store::Store;
+use std::fmt;
+"#;
+        let (definitions, _) = extract_rust_items(source).expect("extraction should succeed");
+        let visibility = |symbol: &str| {
+            definitions
+                .iter()
+                .find(|definition| definition.symbol == symbol)
+                .map(|definition| definition.visibility.as_str())
+        };
+        assert_eq!(visibility("open"), Some("pub"));
+        assert_eq!(visibility("shared"), Some("pub(crate)"));
+        assert_eq!(visibility("Parent"), Some("pub(super)"));
+        assert_eq!(visibility("Scoped"), Some("pub(in crate::store)"));
+        assert_eq!(visibility("hidden"), Some("private"));
+        assert_eq!(visibility("Store"), Some("pub"));
+        assert_eq!(visibility("fmt"), Some("private"));
+    }
 }
@@ -0,0 +1,260 @@
+use std::collections::BTreeMap;
+
+use crate::indexer::docs::{docstring, leading_comment};
+use crate::indexer::languages::ExtractedSymbol;
+
+/// Words too common in tasks and code to carry ranking signal.
+const STOPWORDS: &[&str] = &[
+    "a", "about", "after", "an", "and", "are", "as", "at", "be", "by", "def", "fn", "for", "from",
+    "func", "function", "if", "in", "into", "is", "it", "let", "mut", "of", "on", "or", "pub",
+    "return", "self", "that", "the", "then", "this", "to", "when", "with",
+];
+
+/// Kinds whose source span is their own body rather than a container of other definitions.
+const STRING_LITERAL_KINDS: &[&str] = &["function", "method", "const", "variable"];
+
+/// Bytes of source per estimated model token.
+const BYTES_PER_TOKEN: usize = 4;
+
+/// One symbol's document in the lexical search index.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchDocument {
+    /// Number of terms across all fields: the BM25 document length.
+    pub term_count: u32,
+    /// Estimated tokens needed to show the symbol's source span.
+    pub token_cost: u32,
+    /// Term frequencies keyed by `(field, term)`; fields are `name`, `signature`, `doc`,
+    /// `string`, and `path`.
+    pub terms: BTreeMap<(&'static str, String), u32>,
+}
+
+impl SearchDocument {
+    fn add(&mut self, field: &'static str, text: &str) {
+        for term in search_terms(text) {
+            *self.terms.entry((field, term)).or_default() += 1;
+            self.term_count += 1;
+        }
+    }
+}
+
+/// Splits text into lowercase search terms: identifiers are split on `_` and camel-case
+/// boundaries, light suffix stemming folds `uploading`/`uploads`/`uploaded` into `upload`,
+/// and stopwords and single characters are dropped.
+pub fn search_terms(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for word in text.split(|ch: char| !ch.is_ascii_alphanumeric()) {
+        for part in identifier_parts(word) {
+            let lowered = part.to_ascii_lowercase();
+            if lowered.len() < 2
+                || lowered.bytes().all(|byte| byte.is_ascii_digit())
+                || STOPWORDS.contains(&lowered.as_str())
+            {
+                continue;
+            }
+            terms.push(stem(&lowered));
+        }
+    }
+    terms
+}
+
+/// Builds the search document for `symbol` from its name, signature, the comment block or
+/// docstring attached to it, string literals in its body, and the segments of its path.
+pub fn search_document(symbol: &ExtractedSymbol, file_path: &str, source: &str) -> SearchDocument {
+    let lines = source.lines().collect::<Vec<_>>();
+    let start = (symbol.start_line as usize)
+        .saturating_sub(1)
+        .min(lines.len());
+    let end = (symbol.end_line as usize).clamp(start, lines.len());
+    let body = &lines[start..end];
+
+    let mut document = SearchDocument::default();
+    document.add("name", &symbol.symbol);
+    if let Some(signature) = &symbol.signature {
+        document.add("signature", signature);
+    }
+    for line in leading_comment(&lines[..start]) {
+        document.add("doc", line);
+    }
+    for line in docstring(body) {
+        document.add("doc", line);
+    }
+    if STRING_LITERAL_KINDS.contains(&symbol.kind.as_str()) {
+        let rust = symbol.language == "rust";
+        for line in body {
+            for literal in string_literals(line, rust) {
+                document.add("string", literal);
+            }
+        }
+    }
+    let stem_path = file_path
+        .rsplit_once('.')
+        .map_or(file_path, |(stem, _)| stem);
+    document.add("path", stem_path);
+
+    let bytes = body.iter().map(|line| line.len() + 1).sum::<usize>();
+    document.token_cost = estimated_tokens(bytes);
+    document
+}
+
+/// Estimates the model tokens needed to show `bytes` of source; never less than one.
+pub fn estimated_tokens(bytes: usize) -> u32 {
+    u32::try_from(bytes.div_ceil(BYTES_PER_TOKEN).max(1)).unwrap_or(u32::MAX)
+}
+
+fn identifier_parts(word: &str) -> Vec<&str> {
+    let bytes = word.as_bytes();
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for index in 1..bytes.len() {
+        let previous = bytes[index - 1];
+        let current = bytes[index];
+        let next_is_lower = bytes.get(index + 1).is_some_and(u8::is_ascii_lowercase);
+        let boundary = current.is_ascii_uppercase()
+            && (previous.is_ascii_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_ascii_uppercase() && next_is_lower));
+        if boundary {
+            parts.push(&word[start..index]);
+            start = index;
+        }
+    }
+    if start < word.len() {
+        parts.push(&word[start..]);
+    }
+    parts
+}
+
+fn stem(term: &str) -> String {
+    if let Some(base) = term.strip_suffix("ies")
+        && base.len() >= 3
+    {
+        return format!("{base}y");
+    }
+    for (suffix, min_base) in [("ing", 3), ("ed", 3)] {
+        if let Some(base) = term.strip_suffix(suffix)
+            && base.len() >= min_base
+        {
+            return base.to_string();
+        }
+    }
+    if let Some(base) = term.strip_suffix('s')
+        && base.len() >= 3
+        && !base.ends_with(['s', 'u', 'i'])
+    {
+        return base.to_string();
+    }
+    term.to_string()
+}
+
+/// Contents of the string literals on one line. Rust uses `'` for characters and lifetimes,
+/// so only double quotes delimit strings there.
+fn string_literals(line: &str, rust: bool) -> Vec<&str> {
+    let mut literals = Vec::new();
+    let mut open: Option<(char, usize)> = None;
+    let mut escaped = false;
+    for (index, ch) in line.char_indices() {
+        match open {
+            Some(_) if escaped => escaped = false,
+            Some(_) if ch == '\\' => escaped = true,
+            Some((quote, start)) if ch == quote => {
+                literals.push(&line[start..index]);
+                open = None;
+            }
+            Some(_) => {}
+            None if ch == '"' || (!rust && (ch == '\'' || ch == '`')) => {
+                open = Some((ch, index + 1));
+            }
+            None => {}
+        }
+    }
+    literals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{search_document, search_terms};
+    use crate::indexer::languages::ExtractedSymbol;
+
+    fn symbol(name: &str, kind: &str, language: &str, lines: (u32, u32)) -> ExtractedSymbol {
+        ExtractedSymbol {
+            symbol: name.to_string(),
+            qualified_symbol: None,
+            kind: kind.to_string(),
+            language: language.to_string(),
+            container: None,
+            start_line: lines.0,
+            start_column: 1,
+            end_line: lines.1,
+            end_column: 1,
+            signature: None,
+            test_path: None,
+            metrics: None,
+            visibility: None,
+            doc_comment: None,
+        }
+    }
+
+    fn field_terms(document: &super::SearchDocument, field: &str) -> Vec<String> {
+        document
+            .terms
+            .keys()
+            .filter(|(name, _)| *name == field)
+            .map(|(_, term)| term.clone())
+            .collect()
+    }
+
+    #[test]
+    fn search_terms_split_identifiers_and_fold_suffixes() {
+        assert_eq!(
+            search_terms("fix retry backoff when uploadingToS3 and HTTPServer retries"),
+            vec![
+                "fix", "retry", "backoff", "upload", "s3", "http", "server", "retry"
+            ]
+        );
+        assert_eq!(
+            search_terms("put_object_bytes uploaded"),
+            vec!["put", "object", "byte", "upload"]
+        );
+    }
+
+    #[test]
+    fn search_document_collects_comments_strings_and_path() {
+        let source = "/// Sends parts with exponential delay.\n#[inline]\nfn send(part: u8) {\n    log(\"bucket upload failed\", 'x');\n}\n";
+        let document = search_document(
+            &symbol("send", "function", "rust", (3, 5)),
+            "src/storage/s3_client.rs",
+            source,
+        );
+        assert_eq!(field_terms(&document, "name"), vec!["send"]);
+        assert_eq!(
+            field_terms(&document, "doc"),
+            vec!["delay", "exponential", "part", "send"]
+        );
+        assert_eq!(
+            field_terms(&document, "string"),
+            vec!["bucket", "fail", "upload"]
+        );
+        assert_eq!(
+            field_terms(&document, "path"),
+            vec!["client", "s3", "src", "storage"]
+        );
+        assert_eq!(document.term_count, 12);
+        assert_eq!(document.token_cost, 15);
+    }
+
+    #[test]
+    fn search_document_reads_python_docstrings() {
+        let source =
+            "def push(data):\n    \"\"\"Upload data\n    with backoff.\"\"\"\n    return data\n";
+        let document = search_document(
+            &symbol("push", "function", "python", (1, 4)),
+            "jobs/sync.py",
+            source,
+        );
+        assert_eq!(
+            field_terms(&document, "doc"),
+            vec!["backoff", "data", "upload"]
+        );
+        assert!(field_terms(&document, "string").is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::BTreeSet;
+use std::path::{Component, Path};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+use anyhow::Context;
+use notify::{EventKind, RecursiveMode, Watcher};
+
+use super::incremental::{IncrementalIndexer, ReindexSummary};
+use super::{IndexOptions, IndexSummary};
+
+#[derive(Debug, Clone, Copy)]
+pub struct WatchOptions {
+    pub index: IndexOptions,
+    /// Quiet period after the last file-system event before a batch is reindexed.
+    pub debounce: Duration,
+}
+
+/// Brings the index up to date, then reindexes touched files as file-system notifications
+/// arrive. Events are debounced into batches; `on_batch` receives each non-empty batch result
+/// (including per-batch errors, which do not stop the watcher). Returns when the notification
+/// channel closes.
+pub fn watch_repository(
+    repo: &Path,
+    db_path: &Path,
+    options: &WatchOptions,
+    on_ready: impl FnOnce(&IndexSummary),
+    mut on_batch: impl FnMut(anyhow::Result<ReindexSummary>),
+) -> anyhow::Result<()> {
+    let repo = repo
+        .canonicalize()
+        .with_context(|| format!("failed to resolve {}", repo.display()))?;
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = sender.send(event);
+    })
+    .context("failed to start file watcher")?;
+    watcher
+        .watch(&repo, RecursiveMode::Recursive)
+        .with_context(|| format!("failed to watch {}", repo.display()))?;
+
+    let (mut indexer, summary) = IncrementalIndexer::open(&repo, db_path, options.index)?;
+    on_ready(&summary);
+
+    while let Ok(first_event) = receiver.recv() {
+        let mut touched_paths = BTreeSet::new();
+        collect_touched_paths(&repo, first_event, &mut touched_paths);
+        loop {
+            match receiver.recv_timeout(options.debounce) {
+                Ok(event) => collect_touched_paths(&repo, event, &mut touched_paths),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        }
+        if touched_paths.is_empty() {
+            continue;
+        }
+        match indexer.reindex_paths(&touched_paths) {
+            Ok(summary) if summary.is_empty() => {}
+            result => on_batch(result),
+        }
+    }
+    Ok(())
+}
+
+fn collect_touched_paths(
+    repo: &Path,
+    event: notify::Result<notify::Event>,
+    touched_paths: &mut BTreeSet<String>,
+) {
+    let Ok(event) = event else {
+        return;
+    };
+    if matches!(event.kind, EventKind::Access(_)) {
+        return;
+    }
+    for path in event.paths {
+        if let Some(relative_path) = watched_relative_path(repo, &path) {
+            touched_paths.insert(relative_path);
+        }
+    }
+}
+
+/// Maps an event path to the repository-relative form used by the index, skipping hidden
+/// entries such as `.git/` and the `.repo-scout/` store itself.
+fn watched_relative_path(repo: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(repo).ok()?;
+    let mut segments = Vec::new();
+    for component in relative.components() {
+        let Component::Normal(segment) = component else {
+            return None;
+        };
+        let segment = segment.to_str()?;
+        if segment.starts_with('.') {
+            return None;
+        }
+        segments.push(segment);
+    }
+    (!segments.is_empty()).then(|| segments.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watched_relative_path_skips_hidden_entries_and_foreign_paths() {
+        let repo = Path::new("/work/repo");
+        assert_eq!(
+            watched_relative_path(repo, Path::new("/work/repo/src/lib.rs")),
+            Some("src/lib.rs".to_string())
+        );
+        assert_eq!(
+            watched_relative_path(repo, Path::new("/work/repo/.repo-scout/index.db")),
+            None
+        );
+        assert_eq!(
+            watched_relative_path(repo, Path::new("/work/repo/src/.cache/tmp.rs")),
+            None
+        );
+        assert_eq!(
+            watched_relative_path(repo, Path::new("/work/other/lib.rs")),
+            None
+        );
+        assert_eq!(watched_relative_path(repo, Path::new("/work/repo")), None);
+    }
+}
@@ -0,0 +1,1555 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Component, Path};
+
+use serde_json::Value as JsonValue;
+
+#[cfg(test)]
+use crate::indexer::files::SourceFile;
+
+/// Repository-level build metadata that language adapters need to resolve imports across
+/// package and module boundaries. Built once per index run from the discovered file list.
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceLayout {
+    go_modules: Vec<GoModuleRoot>,
+    go_packages: BTreeMap<String, Vec<String>>,
+    rust_crates: Vec<RustCrateRoot>,
+    ts_configs: BTreeMap<String, TsConfigResolution>,
+    node_packages: Vec<NodePackage>,
+    module_files: BTreeSet<String>,
+    python_roots: Vec<String>,
+    python_files: BTreeSet<String>,
+    rust_files: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GoModuleRoot {
+    module_path: String,
+    directory: String,
+}
+
+/// A Cargo package's library (or binary) crate as seen from the repository root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RustCrateRoot {
+    /// Crate name as written in paths (`package.name` or `lib.name`, with `-` mapped to `_`).
+    pub crate_name: String,
+    /// Directory holding the crate root file; module paths resolve relative to it.
+    pub source_dir: String,
+    /// Indexed crate root files (`lib.rs`, `main.rs`, or a custom `[lib] path`).
+    pub root_files: Vec<String>,
+    package_name: String,
+    manifest_dir: String,
+    dependency_aliases: BTreeMap<String, String>,
+}
+
+/// Effective `baseUrl`/`paths` of one `tsconfig.json` after following its `extends` chain.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct TsConfigResolution {
+    base_url: Option<String>,
+    paths: Vec<(String, Vec<String>)>,
+}
+
+/// A `package.json` with a `name`, linkable from bare specifiers such as `@acme/shared/util`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct NodePackage {
+    name: String,
+    directory: String,
+    manifest: JsonValue,
+    workspace_member: bool,
+}
+
+const NODE_MODULE_EXTENSIONS: &[&str] = &["ts", "tsx", "d.ts", "js", "jsx", "mjs", "cjs"];
+
+/// Files whose extraction resolved imports against one part of the layout, and so must be
+/// extracted again when that part changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutDependents {
+    /// Every file with one of these extensions.
+    Extensions(&'static [&'static str]),
+    /// The files of a Go package directory, and files that mention one of `tokens` (the last
+    /// word of each import path naming the package).
+    GoPackage {
+        directory: String,
+        tokens: Vec<String>,
+    },
+}
+
+impl WorkspaceLayout {
+    /// Builds the layout from already-loaded files. Used by tests that construct fixtures in
+    /// memory; the indexer calls [`WorkspaceLayout::from_paths`].
+    #[cfg(test)]
+    pub fn from_source_files(files: &[SourceFile]) -> Self {
+        let paths = files
+            .iter()
+            .map(|file| file.relative_path.clone())
+            .collect::<Vec<_>>();
+        Self::from_paths(&paths, |path| {
+            files
+                .iter()
+                .find(|file| file.relative_path == path)
+                .and_then(|file| String::from_utf8(file.bytes.clone()).ok())
+        })
+    }
+
+    /// Builds the layout from the repository's file paths. Only manifests (`go.mod`, `go.work`,
+    /// `Cargo.toml`, Python build configs, and JSON files) are read, through `read_manifest`.
+    pub fn from_paths(paths: &[String], read_manifest: impl Fn(&str) -> Option<String>) -> Self {
+        let mut layout = WorkspaceLayout::default();
+        let manifests = paths
+            .iter()
+            .filter(|path| is_manifest_path(path))
+            .filter_map(|path| Some((path.as_str(), read_manifest(path)?)))
+            .collect::<Vec<_>>();
+        let mut go_work_files = Vec::new();
+        let mut cargo_manifests = Vec::new();
+        let mut json_manifests = Vec::new();
+        let mut python_manifests = Vec::new();
+
+        for (relative_path, text) in &manifests {
+            let (relative_path, text) = (*relative_path, text.as_str());
+            match file_name(relative_path) {
+                "go.mod" => {
+                    let directory = parent_directory(relative_path);
+                    if let Some(module_path) = go_mod_module_path(text) {
+                        layout.push_go_module(module_path, directory.clone());
+                    }
+                    for (module_path, target) in go_local_replacements(text) {
+                        layout.push_go_module(module_path, join_relative(&directory, &target));
+                    }
+                }
+                "go.work" => go_work_files.push((relative_path, text)),
+                "Cargo.toml" => cargo_manifests.push((relative_path, text)),
+                "pyproject.toml" | "setup.cfg" => python_manifests.push((relative_path, text)),
+                _ => json_manifests.push((relative_path, text)),
+            }
+        }
+        for relative_path in paths {
+            layout.insert_source_path(relative_path);
+        }
+
+        for (go_work_path, text) in go_work_files {
+            let directory = parent_directory(go_work_path);
+            for used in go_work_use_directories(text) {
+                let module_directory = join_relative(&directory, &used);
+                let go_mod_path = join_relative(&module_directory, "go.mod");
+                let module_path = manifests
+                    .iter()
+                    .find(|(path, _)| *path == go_mod_path)
+                    .and_then(|(_, text)| go_mod_module_path(text));
+                if let Some(module_path) = module_path {
+                    layout.push_go_module(module_path, module_directory);
+                }
+            }
+            for (module_path, target) in go_local_replacements(text) {
+                layout.push_go_module(module_path, join_relative(&directory, &target));
+            }
+        }
+
+        layout.go_modules.sort_by(|left, right| {
+            right
+                .module_path
+                .len()
+                .cmp(&left.module_path.len())
+                .then(left.module_path.cmp(&right.module_path))
+                .then(left.directory.cmp(&right.directory))
+        });
+        layout.go_modules.dedup();
+        for package_files in layout.go_packages.values_mut() {
+            package_files.sort();
+        }
+        layout.rust_crates = rust_crate_roots(paths, &cargo_manifests);
+        layout.node_packages = node_packages(&json_manifests);
+        layout.ts_configs = ts_config_resolutions(&json_manifests, &layout.node_packages);
+        layout.python_roots = python_package_roots(&python_manifests, &layout.python_files);
+        layout
+    }
+
+    /// Fingerprints the parts of the layout that adapters resolve imports against, keyed by
+    /// part: `go` for module roots, `go:<dir>` for each package's file list, and `node` for
+    /// `tsconfig.json` `paths`/`baseUrl` and `package.json` manifests, `python` for package
+    /// roots and `__init__.py` markers, and `rust` for Cargo crates. Comparing them
+    /// with a previous run's tells which files need their imports resolved again.
+    pub fn fingerprints(&self) -> BTreeMap<String, String> {
+        let mut fingerprints = BTreeMap::new();
+        fingerprints.insert("go".to_string(), fingerprint(&self.go_modules));
+        for (directory, files) in &self.go_packages {
+            fingerprints.insert(format!("go:{directory}"), fingerprint(files));
+        }
+        fingerprints.insert(
+            "node".to_string(),
+            fingerprint(&(&self.ts_configs, &self.node_packages)),
+        );
+        let package_markers = self
+            .python_files
+            .iter()
+            .filter(|file| file_name(file) == "__init__.py")
+            .collect::<Vec<_>>();
+        fingerprints.insert(
+            "python".to_string(),
+            fingerprint(&(&self.python_roots, package_markers)),
+        );
+        fingerprints.insert("rust".to_string(), fingerprint(&self.rust_crates));
+        fingerprints
+    }
+
+    /// Returns which files depend on the layout part named by a [`WorkspaceLayout::fingerprints`]
+    /// key, or `None` for an unknown key.
+    pub fn layout_dependents(&self, key: &str) -> Option<LayoutDependents> {
+        match key {
+            "go" => return Some(LayoutDependents::Extensions(&["go"])),
+            "node" => return Some(LayoutDependents::Extensions(NODE_MODULE_EXTENSIONS)),
+            "python" => return Some(LayoutDependents::Extensions(&["py"])),
+            "rust" => return Some(LayoutDependents::Extensions(&["rs"])),
+            _ => {}
+        }
+        let directory = key.strip_prefix("go:")?;
+        let tokens = self
+            .go_import_paths(directory)
+            .iter()
+            .filter_map(|import_path| last_word(import_path))
+            .chain(last_word(directory))
+            .collect::<BTreeSet<_>>();
+        Some(LayoutDependents::GoPackage {
+            directory: directory.to_string(),
+            tokens: tokens.into_iter().collect(),
+        })
+    }
+
+    /// Import paths that name the Go package in `directory`, one per module containing it.
+    fn go_import_paths(&self, directory: &str) -> Vec<String> {
+        self.go_modules
+            .iter()
+            .filter_map(|module| {
+                if directory == module.directory {
+                    return Some(module.module_path.clone());
+                }
+                let suffix = if module.directory.is_empty() {
+                    directory
+                } else {
+                    directory.strip_prefix(&format!("{}/", module.directory))?
+                };
+                Some(format!("{}/{suffix}", module.module_path))
+            })
+            .collect()
+    }
+
+    /// Resolves a non-relative JavaScript/TypeScript module specifier to indexed files, trying
+    /// the nearest `tsconfig.json` `paths` and `baseUrl` first and then workspace packages
+    /// (`package.json` `exports`, `types`, `module`, and `main`). Unresolvable specifiers, such as
+    /// third-party packages outside the repository, yield no files.
+    pub fn node_module_files(&self, from_file_path: &str, specifier: &str) -> Vec<String> {
+        if let Some(config) = self.nearest_ts_config(from_file_path) {
+            let from_paths = ts_path_targets(config, specifier)
+                .into_iter()
+                .flat_map(|target| self.module_file_candidates(&target))
+                .collect::<Vec<_>>();
+            if !from_paths.is_empty() {
+                return sorted_unique(from_paths);
+            }
+            if let Some(base_url) = &config.base_url {
+                let from_base_url =
+                    self.module_file_candidates(&join_relative(base_url, specifier));
+                if !from_base_url.is_empty() {
+                    return from_base_url;
+                }
+            }
+        }
+        self.node_package_files(specifier)
+    }
+
+    fn nearest_ts_config(&self, from_file_path: &str) -> Option<&TsConfigResolution> {
+        let mut directory = parent_directory(from_file_path);
+        loop {
+            if let Some(config) = self.ts_configs.get(&directory) {
+                return Some(config);
+            }
+            if directory.is_empty() {
+                return None;
+            }
+            directory = parent_directory(&directory);
+        }
+    }
+
+    fn node_package_files(&self, specifier: &str) -> Vec<String> {
+        let package = self
+            .node_packages
+            .iter()
+            .filter(|package| {
+                specifier == package.name || specifier.starts_with(&format!("{}/", package.name))
+            })
+            .max_by_key(|package| (package.name.len(), package.workspace_member));
+        let Some(package) = package else {
+            return Vec::new();
+        };
+        let subpath = specifier[package.name.len()..].trim_start_matches('/');
+        let mut targets = package_export_targets(&package.manifest, subpath);
+        if subpath.is_empty() {
+            for field in ["types", "typings", "module", "main"] {
+                if let Some(entry) = package.manifest.get(field).and_then(JsonValue::as_str) {
+                    targets.push(entry.to_string());
+                }
+            }
+            targets.push("src/index".to_string());
+            targets.push("index".to_string());
+        } else {
+            targets.push(subpath.to_string());
+            targets.push(format!("src/{subpath}"));
+        }
+
+        for target in targets {
+            let target = join_relative(&package.directory, &target);
+            let mut files = self.module_file_candidates(&target);
+            if files.is_empty()
+                && let Some(source_target) = build_output_to_source(&package.directory, &target)
+            {
+                files = self.module_file_candidates(&source_target);
+            }
+            if !files.is_empty() {
+                return files;
+            }
+        }
+        Vec::new()
+    }
+
+    /// Expands a module path the way Node/TypeScript would (`x`, `x.ts`, `x/index.ts`, ...),
+    /// keeping only files that exist in the index.
+    fn module_file_candidates(&self, module_path: &str) -> Vec<String> {
+        if self.module_files.contains(module_path) {
+            return vec![module_path.to_string()];
+        }
+        let mut stems = vec![module_path.to_string()];
+        // ESM-style TypeScript imports name the emitted `.js` file.
+        if let Some(stem) = [".js", ".jsx", ".mjs", ".cjs"]
+            .iter()
+            .find_map(|extension| module_path.strip_suffix(extension))
+        {
+            stems.push(stem.to_string());
+        }
+        let mut files = Vec::new();
+        for stem in stems {
+            for extension in NODE_MODULE_EXTENSIONS {
+                for candidate in [
+                    format!("{stem}.{extension}"),
+                    format!("{stem}/index.{extension}"),
+                ] {
+                    if self.module_files.contains(&candidate) {
+                        files.push(candidate);
+                    }
+                }
+            }
+            if !files.is_empty() {
+                break;
+            }
+        }
+        sorted_unique(files)
+    }
+
+    /// Returns the non-test `.go` files of the package named by `import_path`, or `None` when no
+    /// module declared in this workspace owns the import path (stdlib or third-party packages).
+    pub fn go_package_files(&self, import_path: &str) -> Option<Vec<String>> {
+        let module = self.go_modules.iter().find(|module| {
+            import_path == module.module_path
+                || import_path.starts_with(&format!("{}/", module.module_path))
+        })?;
+        let package_suffix = import_path[module.module_path.len()..].trim_start_matches('/');
+        let package_directory = join_relative(&module.directory, package_suffix);
+        Some(
+            self.go_packages
+                .get(&package_directory)
+                .cloned()
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Returns the crate whose source directory contains `file_path`, preferring the most deeply
+    /// nested crate when manifests are nested.
+    pub fn rust_crate_for_file(&self, file_path: &str) -> Option<&RustCrateRoot> {
+        self.rust_crates
+            .iter()
+            .filter(|krate| is_within_directory(file_path, &krate.source_dir))
+            .max_by_key(|krate| krate.source_dir.len())
+    }
+
+    /// Returns whether `file_path` is an indexed Rust source file.
+    pub fn has_rust_file(&self, file_path: &str) -> bool {
+        self.rust_files.contains(file_path)
+    }
+
+    /// Resolves the leading segment of a Rust path (`foo` in `foo::bar::Baz`) to a workspace
+    /// crate, honoring dependency renames declared by the crate that owns `caller_file_path`.
+    pub fn rust_external_crate(
+        &self,
+        caller_file_path: &str,
+        crate_name: &str,
+    ) -> Option<&RustCrateRoot> {
+        let caller_crate = self
+            .rust_crates
+            .iter()
+            .filter(|krate| is_within_directory(caller_file_path, &krate.manifest_dir))
+            .max_by_key(|krate| krate.manifest_dir.len());
+        if let Some(package_name) =
+            caller_crate.and_then(|krate| krate.dependency_aliases.get(crate_name))
+        {
+            return self
+                .rust_crates
+                .iter()
+                .find(|krate| &krate.package_name == package_name);
+        }
+        self.rust_crates
+            .iter()
+            .find(|krate| krate.crate_name == crate_name)
+    }
+
+    /// Resolves an absolute Python module name (`acme.db.models`) to an indexed `module.py` or
+    /// `module/__init__.py`. Package roots containing `from_file_path` are tried first, deepest
+    /// first, followed by the remaining roots and finally the repository root.
+    pub fn python_module_file(&self, from_file_path: &str, module_name: &str) -> Option<String> {
+        let module_path = module_name.trim().replace('.', "/");
+        if module_path.is_empty() {
+            return None;
+        }
+        let mut roots = self
+            .python_roots
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        roots.sort_by(|left, right| {
+            is_within_directory(from_file_path, right)
+                .cmp(&is_within_directory(from_file_path, left))
+                .then(right.len().cmp(&left.len()))
+                .then(left.cmp(right))
+        });
+        roots
+            .into_iter()
+            .chain(std::iter::once(""))
+            .find_map(|root| self.python_package_file(&join_relative(root, &module_path)))
+    }
+
+    /// Returns the indexed file backing a module path without extension: `path.py` when it
+    /// exists, otherwise the package marker `path/__init__.py`.
+    pub fn python_package_file(&self, module_path: &str) -> Option<String> {
+        [
+            format!("{module_path}.py"),
+            join_relative(module_path, "__init__.py"),
+        ]
+        .into_iter()
+        .find(|candidate| self.python_files.contains(candidate))
+    }
+
+    /// Records a source file in the per-language path sets used to resolve imports.
+    fn insert_source_path(&mut self, relative_path: &str) {
+        if NODE_MODULE_EXTENSIONS
+            .iter()
+            .any(|extension| relative_path.ends_with(&format!(".{extension}")))
+        {
+            self.module_files.insert(relative_path.to_string());
+        }
+        if relative_path.ends_with(".py") {
+            self.python_files.insert(relative_path.to_string());
+        }
+        if relative_path.ends_with(".rs") {
+            self.rust_files.insert(relative_path.to_string());
+        }
+        if relative_path.ends_with(".go") && !relative_path.ends_with("_test.go") {
+            self.go_packages
+                .entry(parent_directory(relative_path))
+                .or_default()
+                .push(relative_path.to_string());
+        }
+    }
+
+    fn push_go_module(&mut self, module_path: String, directory: String) {
+        self.go_modules.push(GoModuleRoot {
+            module_path,
+            directory,
+        });
+    }
+}
+
+/// Whether the layout reads `path`'s contents: module and package manifests and JSON configs.
+pub fn is_manifest_path(path: &str) -> bool {
+    matches!(
+        file_name(path),
+        "go.mod" | "go.work" | "Cargo.toml" | "pyproject.toml" | "setup.cfg"
+    ) || path.ends_with(".json")
+}
+
+fn rust_crate_roots(paths: &[String], manifests: &[(&str, &str)]) -> Vec<RustCrateRoot> {
+    let indexed_paths: HashSet<&str> = paths.iter().map(String::as_str).collect();
+    let parsed: Vec<(String, toml::Table)> = manifests
+        .iter()
+        .filter_map(|(manifest_path, text)| {
+            let table = text.parse::<toml::Table>().ok()?;
+            Some((parent_directory(manifest_path), table))
+        })
+        .collect();
+    let workspace_renames: BTreeMap<String, String> = parsed
+        .iter()
+        .filter_map(|(_, table)| table.get("workspace")?.get("dependencies")?.as_table())
+        .flat_map(renamed_dependencies)
+        .collect();
+
+    let mut crates = Vec::new();
+    for (manifest_dir, table) in &parsed {
+        let Some(package_name) = table
+            .get("package")
+            .and_then(|package| package.get("name"))
+            .and_then(toml::Value::as_str)
+        else {
+            continue;
+        };
+        let lib = table.get("lib");
+        let crate_name = lib
+            .and_then(|lib| lib.get("name"))
+            .and_then(toml::Value::as_str)
+            .unwrap_or(package_name)
+            .replace('-', "_");
+        let lib_path = lib
+            .and_then(|lib| lib.get("path"))
+            .and_then(toml::Value::as_str)
+            .unwrap_or("src/lib.rs");
+        let root_files: Vec<String> = [lib_path, "src/main.rs"]
+            .iter()
+            .map(|relative| join_relative(manifest_dir, relative))
+            .filter(|path| indexed_paths.contains(path.as_str()))
+            .collect();
+        let source_dir = root_files
+            .first()
+            .map(|root_file| parent_directory(root_file))
+            .unwrap_or_else(|| join_relative(manifest_dir, "src"));
+
+        let mut dependency_aliases = BTreeMap::new();
+        for section in ["dependencies", "dev-dependencies", "build-dependencies"] {
+            let Some(dependencies) = table.get(section).and_then(toml::Value::as_table) else {
+                continue;
+            };
+            dependency_aliases.extend(renamed_dependencies(dependencies));
+            for (alias, spec) in dependencies {
+                let alias = alias.replace('-', "_");
+                let inherits_workspace = spec
+                    .get("workspace")
+                    .and_then(toml::Value::as_bool)
+                    .unwrap_or(false);
+                if inherits_workspace && let Some(package) = workspace_renames.get(&alias) {
+                    dependency_aliases.insert(alias, package.clone());
+                }
+            }
+        }
+
+        crates.push(RustCrateRoot {
+            crate_name,
+            source_dir,
+            root_files,
+            package_name: package_name.to_string(),
+            manifest_dir: manifest_dir.clone(),
+            dependency_aliases,
+        });
+    }
+    crates.sort_by(|left, right| left.source_dir.cmp(&right.source_dir));
+    crates
+}
+
+/// Maps `alias = { package = "real-name", .. }` dependency entries from the path-facing alias
+/// to the package they rename.
+fn renamed_dependencies(dependencies: &toml::Table) -> Vec<(String, String)> {
+    dependencies
+        .iter()
+        .filter_map(|(alias, spec)| {
+            let package = spec.get("package")?.as_str()?;
+            Some((alias.replace('-', "_"), package.to_string()))
+        })
+        .collect()
+}
+
+/// Infers directories that hold top-level Python packages. Roots come from `pyproject.toml` and
+/// `setup.cfg` package settings (or the manifest directory and its `src/` when none are set), and
+/// from the parent of every outermost `__init__.py` package.
+fn python_package_roots(
+    manifests: &[(&str, &str)],
+    python_files: &BTreeSet<String>,
+) -> Vec<String> {
+    let mut roots = BTreeSet::new();
+    for (manifest_path, text) in manifests {
+        let directory = parent_directory(manifest_path);
+        let declared = if manifest_path.ends_with("setup.cfg") {
+            setup_cfg_package_dirs(text)
+        } else {
+            pyproject_package_dirs(text)
+        };
+        if declared.is_empty() {
+            let src_directory = join_relative(&directory, "src");
+            if python_files
+                .iter()
+                .any(|file| is_within_directory(file, &src_directory))
+            {
+                roots.insert(src_directory);
+            }
+            roots.insert(directory);
+        } else {
+            for package_dir in declared {
+                roots.insert(join_relative(&directory, &package_dir));
+            }
+        }
+    }
+
+    for init_file in python_files
+        .iter()
+        .filter(|file| file_name(file) == "__init__.py")
+    {
+        let mut package = parent_directory(init_file);
+        if package.is_empty() {
+            continue;
+        }
+        loop {
+            let parent = parent_directory(&package);
+            if parent.is_empty() || !python_files.contains(&join_relative(&parent, "__init__.py")) {
+                break;
+            }
+            package = parent;
+        }
+        roots.insert(parent_directory(&package));
+    }
+
+    roots.into_iter().collect()
+}
+
+/// Reads package directories from setuptools (`packages.find.where`, `package-dir`), Poetry
+/// (`packages[].from`), and Hatch (`packages` entries such as `src/acme`) settings.
+fn pyproject_package_dirs(text: &str) -> Vec<String> {
+    let Ok(manifest) = toml::from_str::<toml::Table>(text) else {
+        return Vec::new();
+    };
+    let Some(tool) = manifest.get("tool").and_then(toml::Value::as_table) else {
+        return Vec::new();
+    };
+    let mut directories = Vec::new();
+
+    if let Some(setuptools) = tool.get("setuptools").and_then(toml::Value::as_table) {
+        let find_where = setuptools
+            .get("packages")
+            .and_then(|packages| packages.get("find"))
+            .and_then(|find| find.get("where"));
+        match find_where {
+            Some(toml::Value::Array(entries)) => {
+                directories.extend(
+                    entries
+                        .iter()
+                        .filter_map(toml::Value::as_str)
+                        .map(str::to_string),
+                );
+            }
+            Some(toml::Value::String(entry)) => directories.push(entry.clone()),
+            _ => {}
+        }
+        if let Some(root) = setuptools
+            .get("package-dir")
+            .and_then(|package_dir| package_dir.get(""))
+            .and_then(toml::Value::as_str)
+        {
+            directories.push(root.to_string());
+        }
+    }
+
+    let poetry_packages = tool
+        .get("poetry")
+        .and_then(|poetry| poetry.get("packages"))
+        .and_then(toml::Value::as_array);
+    for package in poetry_packages.into_iter().flatten() {
+        let from = package.get("from").and_then(toml::Value::as_str);
+        directories.push(from.unwrap_or("").to_string());
+    }
+
+    let hatch_packages = tool
+        .get("hatch")
+        .and_then(|hatch| hatch.get("build"))
+        .and_then(|build| build.get("targets"))
+        .and_then(|targets| targets.get("wheel"))
+        .and_then(|wheel| wheel.get("packages"))
+        .and_then(toml::Value::as_array);
+    for package in hatch_packages
+        .into_iter()
+        .flatten()
+        .filter_map(toml::Value::as_str)
+    {
+        directories.push(parent_directory(package.trim_end_matches('/')));
+    }
+
+    sorted_unique(directories)
+}
+
+/// Reads `[options] package_dir` (`=src` root mapping) and `[options.packages.find] where` from
+/// a `setup.cfg`.
+fn setup_cfg_package_dirs(text: &str) -> Vec<String> {
+    let mut directories = Vec::new();
+    let mut section = String::new();
+    let mut current_key = String::new();
+    for line in text.lines() {
+        let trimmed = line.split(['#', ';']).next().unwrap_or_default().trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if let Some(name) = trimmed
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            section = name.trim().to_string();
+            current_key.clear();
+            continue;
+        }
+        let is_continuation = line.starts_with([' ', '\t']);
+        let value = if is_continuation {
+            trimmed
+        } else if let Some((key, value)) = trimmed.split_once('=') {
+            current_key = key.trim().to_string();
+            value.trim()
+        } else {
+            continue;
+        };
+        if value.is_empty() {
+            continue;
+        }
+        match (section.as_str(), current_key.as_str()) {
+            ("options", "package_dir") => {
+                if let Some(root) = value.strip_prefix('=') {
+                    directories.push(root.trim().to_string());
+                }
+            }
+            ("options.packages.find", "where") => directories.push(value.to_string()),
+            _ => {}
+        }
+    }
+    sorted_unique(directories)
+}
+
+fn is_within_directory(file_path: &str, directory: &str) -> bool {
+    directory.is_empty()
+        || file_path
+            .strip_prefix(directory)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+fn node_packages(json_manifests: &[(&str, &str)]) -> Vec<NodePackage> {
+    let manifests: Vec<(String, JsonValue)> = json_manifests
+        .iter()
+        .filter(|(path, _)| file_name(path) == "package.json")
+        .filter_map(|(path, text)| Some((parent_directory(path), parse_jsonc(text)?)))
+        .collect();
+    let workspace_globs: Vec<String> = manifests
+        .iter()
+        .flat_map(|(directory, manifest)| {
+            let globs = match manifest.get("workspaces") {
+                Some(JsonValue::Array(globs)) => globs.clone(),
+                Some(JsonValue::Object(config)) => config
+                    .get("packages")
+                    .and_then(JsonValue::as_array)
+                    .cloned()
+                    .unwrap_or_default(),
+                _ => Vec::new(),
+            };
+            globs
+                .iter()
+                .filter_map(JsonValue::as_str)
+                .map(|glob| join_relative(directory, glob))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    let mut packages: Vec<NodePackage> = manifests
+        .into_iter()
+        .filter_map(|(directory, manifest)| {
+            let name = manifest.get("name")?.as_str()?.to_string();
+            let workspace_member = workspace_globs
+                .iter()
+                .any(|glob| directory_matches_glob(&directory, glob));
+            Some(NodePackage {
+                name,
+                directory,
+                manifest,
+                workspace_member,
+            })
+        })
+        .collect();
+    packages.sort_by(|left, right| {
+        left.name
+            .cmp(&right.name)
+            .then(right.workspace_member.cmp(&left.workspace_member))
+            .then(left.directory.cmp(&right.directory))
+    });
+    packages
+}
+
+/// Picks the target of `exports` for a package subpath, following condition objects in
+/// `types` > `import` > `default` > `require` order and single-`*` subpath patterns.
+fn package_export_targets(manifest: &JsonValue, subpath: &str) -> Vec<String> {
+    let Some(exports) = manifest.get("exports") else {
+        return Vec::new();
+    };
+    let key = if subpath.is_empty() {
+        ".".to_string()
+    } else {
+        format!("./{subpath}")
+    };
+    let entry = match exports {
+        JsonValue::Object(map) if map.keys().any(|name| name.starts_with('.')) => map
+            .get(&key)
+            .cloned()
+            .map(|entry| (entry, None))
+            .or_else(|| {
+                map.iter().find_map(|(pattern, entry)| {
+                    let (prefix, suffix) = pattern.split_once('*')?;
+                    let matched = key.strip_prefix(prefix)?.strip_suffix(suffix)?;
+                    Some((entry.clone(), Some(matched.to_string())))
+                })
+            }),
+        _ if subpath.is_empty() => Some((exports.clone(), None)),
+        _ => None,
+    };
+    let Some((entry, wildcard)) = entry else {
+        return Vec::new();
+    };
+    let mut targets = Vec::new();
+    let mut stack = vec![entry];
+    while let Some(value) = stack.pop() {
+        match value {
+            JsonValue::String(target) => targets.push(match &wildcard {
+                Some(matched) => target.replace('*', matched),
+                None => target,
+            }),
+            JsonValue::Object(conditions) => {
+                for condition in ["require", "default", "import", "types"] {
+                    if let Some(nested) = conditions.get(condition) {
+                        stack.push(nested.clone());
+                    }
+                }
+            }
+            JsonValue::Array(alternatives) => stack.extend(alternatives.into_iter().rev()),
+            _ => {}
+        }
+    }
+    targets
+}
+
+/// Maps a build-output entry such as `dist/index.js` back to its source (`src/index`) so that
+/// workspace packages whose `main` points at unbuilt output still resolve.
+fn build_output_to_source(package_directory: &str, target: &str) -> Option<String> {
+    let relative = if package_directory.is_empty() {
+        target
+    } else {
+        target
+            .strip_prefix(package_directory)?
+            .trim_start_matches('/')
+    };
+    let (first, rest) = relative.split_once('/')?;
+    if !matches!(first, "dist" | "lib" | "build" | "out") {
+        return None;
+    }
+    let rest = rest
+        .strip_suffix(".d.ts")
+        .or_else(|| rest.rsplit_once('.').map(|(stem, _)| stem))
+        .unwrap_or(rest);
+    Some(join_relative(package_directory, &format!("src/{rest}")))
+}
+
+fn ts_config_resolutions(
+    json_manifests: &[(&str, &str)],
+    node_packages: &[NodePackage],
+) -> BTreeMap<String, TsConfigResolution> {
+    let parsed: HashMap<String, JsonValue> = json_manifests
+        .iter()
+        .filter_map(|(path, text)| Some((path.to_string(), parse_jsonc(text)?)))
+        .collect();
+    let mut resolutions = BTreeMap::new();
+    for (path, _) in json_manifests {
+        if file_name(path) != "tsconfig.json" {
+            continue;
+        }
+        let mut resolution = TsConfigResolution::default();
+        let mut paths_directory = None;
+        for (config_path, config) in ts_config_chain(path, &parsed, node_packages) {
+            let directory = parent_directory(&config_path);
+            let Some(options) = config.get("compilerOptions") else {
+                continue;
+            };
+            if let Some(base_url) = options.get("baseUrl").and_then(JsonValue::as_str) {
+                resolution.base_url = Some(join_relative(&directory, base_url));
+            }
+            if let Some(paths) = options.get("paths").and_then(JsonValue::as_object) {
+                resolution.paths = paths
+                    .iter()
+                    .map(|(pattern, targets)| {
+                        let targets = targets
+                            .as_array()
+                            .map(|targets| {
+                                targets
+                                    .iter()
+                                    .filter_map(JsonValue::as_str)
+                                    .map(str::to_string)
+                                    .collect()
+                            })
+                            .unwrap_or_default();
+                        (pattern.clone(), targets)
+                    })
+                    .collect();
+                paths_directory = Some(directory);
+            }
+        }
+        // `paths` entries are relative to `baseUrl` when set, else to the config declaring them.
+        if let Some(paths_directory) = paths_directory {
+            let base = resolution.base_url.clone().unwrap_or(paths_directory);
+            for (_, targets) in &mut resolution.paths {
+                for target in targets.iter_mut() {
+                    *target = join_relative(&base, target);
+                }
+            }
+        }
+        resolutions.insert(parent_directory(path), resolution);
+    }
+    resolutions
+}
+
+/// Returns `path` and the configs it extends, ordered from the root ancestor to `path` itself.
+fn ts_config_chain(
+    path: &str,
+    parsed: &HashMap<String, JsonValue>,
+    node_packages: &[NodePackage],
+) -> Vec<(String, JsonValue)> {
+    let mut chain = Vec::new();
+    let mut seen = HashSet::new();
+    let mut pending = vec![path.to_string()];
+    while let Some(config_path) = pending.pop() {
+        if !seen.insert(config_path.clone()) || seen.len() > 32 {
+            continue;
+        }
+        let Some(config) = parsed.get(&config_path) else {
+            continue;
+        };
+        let directory = parent_directory(&config_path);
+        let extends = match config.get("extends") {
+            Some(JsonValue::String(extends)) => vec![extends.clone()],
+            Some(JsonValue::Array(extends)) => extends
+                .iter()
+                .filter_map(JsonValue::as_str)
+                .map(str::to_string)
+                .collect(),
+            _ => Vec::new(),
+        };
+        // Later `extends` entries override earlier ones; pushing them in declaration order makes
+        // the reversed chain apply them last.
+        for extended in extends {
+            if let Some(extended_path) = ts_extends_path(&directory, &extended, node_packages) {
+                pending.push(extended_path);
+            }
+        }
+        chain.push((config_path, config.clone()));
+    }
+    chain.reverse();
+    chain
+}
+
+fn ts_extends_path(
+    directory: &str,
+    extends: &str,
+    node_packages: &[NodePackage],
+) -> Option<String> {
+    let with_extension = |path: String| {
+        if path.ends_with(".json") {
+            path
+        } else {
+            format!("{path}.json")
+        }
+    };
+    if extends.starts_with('.') {
+        return Some(with_extension(join_relative(directory, extends)));
+    }
+    let package = node_packages.iter().find(|package| {
+        extends == package.name || extends.starts_with(&format!("{}/", package.name))
+    })?;
+    let subpath = extends[package.name.len()..].trim_start_matches('/');
+    if subpath.is_empty() {
+        return Some(join_relative(&package.directory, "tsconfig.json"));
+    }
+    Some(with_extension(join_relative(&package.directory, subpath)))
+}
+
+/// Applies `compilerOptions.paths` to `specifier`; the longest matching pattern prefix wins.
+fn ts_path_targets(config: &TsConfigResolution, specifier: &str) -> Vec<String> {
+    let mut best: Option<(usize, Vec<String>)> = None;
+    for (pattern, targets) in &config.paths {
+        let (matched_len, wildcard) = match pattern.split_once('*') {
+            Some((prefix, suffix)) => {
+                let Some(matched) = specifier
+                    .strip_prefix(prefix)
+                    .and_then(|rest| rest.strip_suffix(suffix))
+                else {
+                    continue;
+                };
+                (prefix.len(), Some(matched))
+            }
+            None if pattern == specifier => (usize::MAX, None),
+            None => continue,
+        };
+        if best
+            .as_ref()
+            .is_some_and(|(best_len, _)| *best_len >= matched_len)
+        {
+            continue;
+        }
+        let substituted = targets
+            .iter()
+            .map(|target| match wildcard {
+                Some(matched) => target.replacen('*', matched, 1),
+                None => target.clone(),
+            })
+            .collect();
+        best = Some((matched_len, substituted));
+    }
+    best.map(|(_, targets)| targets).unwrap_or_default()
+}
+
+/// Parses JSON that may contain `//` and `/* */` comments and trailing commas, as
+/// `tsconfig.json` files commonly do.
+fn parse_jsonc(text: &str) -> Option<JsonValue> {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+    while let Some(ch) = chars.next() {
+        if in_string {
+            stripped.push(ch);
+            if ch == '\\' {
+                if let Some(escaped) = chars.next() {
+                    stripped.push(escaped);
+                }
+            } else if ch == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match (ch, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                stripped.push(ch);
+            }
+            ('/', Some('/')) => {
+                for skipped in chars.by_ref() {
+                    if skipped == '\n' {
+                        stripped.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = '\0';
+                for skipped in chars.by_ref() {
+                    if previous == '*' && skipped == '/' {
+                        break;
+                    }
+                    previous = skipped;
+                }
+            }
+            (',', _) => {
+                let rest = chars.clone().find(|next| !next.is_whitespace());
+                if !matches!(rest, Some('}') | Some(']')) {
+                    stripped.push(ch);
+                }
+            }
+            _ => stripped.push(ch),
+        }
+    }
+    serde_json::from_str(&stripped).ok()
+}
+
+fn directory_matches_glob(directory: &str, glob: &str) -> bool {
+    let directory_parts: Vec<&str> = directory
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect();
+    let glob_parts: Vec<&str> = glob.split('/').filter(|part| !part.is_empty()).collect();
+    if glob_parts.contains(&"**") {
+        let prefix_len = glob_parts
+            .iter()
+            .position(|part| *part == "**")
+            .unwrap_or(0);
+        return directory_parts.len() >= prefix_len
+            && glob_parts[..prefix_len]
+                .iter()
+                .zip(&directory_parts)
+                .all(|(pattern, part)| segment_matches_glob(part, pattern));
+    }
+    directory_parts.len() == glob_parts.len()
+        && glob_parts
+            .iter()
+            .zip(&directory_parts)
+            .all(|(pattern, part)| segment_matches_glob(part, pattern))
+}
+
+fn segment_matches_glob(segment: &str, pattern: &str) -> bool {
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => {
+            segment.len() >= prefix.len() + suffix.len()
+                && segment.starts_with(prefix)
+                && segment.ends_with(suffix)
+        }
+        None => segment == pattern,
+    }
+}
+
+fn sorted_unique(mut values: Vec<String>) -> Vec<String> {
+    values.sort();
+    values.dedup();
+    values
+}
+
+fn go_mod_module_path(text: &str) -> Option<String> {
+    text.lines().find_map(|line| {
+        let directive = strip_go_comment(line).trim();
+        let module_path = directive.strip_prefix("module")?;
+        if !module_path.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let module_path = module_path.trim().trim_matches('"');
+        (!module_path.is_empty()).then(|| module_path.to_string())
+    })
+}
+
+fn go_work_use_directories(text: &str) -> Vec<String> {
+    go_directive_entries(text, "use")
+        .into_iter()
+        .map(|entry| entry.trim_matches('"').to_string())
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+fn go_local_replacements(text: &str) -> Vec<(String, String)> {
+    go_directive_entries(text, "replace")
+        .into_iter()
+        .filter_map(|entry| {
+            let (left, right) = entry.split_once("=>")?;
+            let module_path = left.split_whitespace().next()?.trim_matches('"');
+            let target = right.split_whitespace().next()?.trim_matches('"');
+            let is_local = target.starts_with("./") || target.starts_with("../") || target == ".";
+            (is_local && !module_path.is_empty())
+                .then(|| (module_path.to_string(), target.to_string()))
+        })
+        .collect()
+}
+
+/// Collects the arguments of a `go.mod`/`go.work` directive in both its single-line
+/// (`use ./svc`) and parenthesized block (`use ( ... )`) forms.
+fn go_directive_entries(text: &str, directive: &str) -> Vec<String> {
+    let mut entries = Vec::new();
+    let mut in_block = false;
+    for line in text.lines() {
+        let line = strip_go_comment(line).trim();
+        if in_block {
+            if line.starts_with(')') {
+                in_block = false;
+            } else if !line.is_empty() {
+                entries.push(line.to_string());
+            }
+            continue;
+        }
+        let Some(rest) = line.strip_prefix(directive) else {
+            continue;
+        };
+        if !rest.starts_with([' ', '\t', '(']) {
+            continue;
+        }
+        let rest = rest.trim();
+        if rest == "(" {
+            in_block = true;
+        } else if !rest.is_empty() {
+            entries.push(rest.to_string());
+        }
+    }
+    entries
+}
+
+fn strip_go_comment(line: &str) -> &str {
+    line.split_once("//").map_or(line, |(head, _)| head)
+}
+
+fn fingerprint(value: &impl std::fmt::Debug) -> String {
+    blake3::hash(format!("{value:?}").as_bytes())
+        .to_hex()
+        .to_string()
+}
+
+/// The last identifier-like word of `text`, as the text index tokenizes it.
+fn last_word(text: &str) -> Option<String> {
+    text.split(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_'))
+        .rfind(|word| !word.is_empty())
+        .map(str::to_string)
+}
+
+fn file_name(relative_path: &str) -> &str {
+    relative_path.rsplit('/').next().unwrap_or(relative_path)
+}
+
+fn parent_directory(relative_path: &str) -> String {
+    relative_path
+        .rsplit_once('/')
+        .map(|(directory, _)| directory.to_string())
+        .unwrap_or_default()
+}
+
+fn join_relative(directory: &str, relative: &str) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for component in Path::new(directory).join(relative).components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+            Component::ParentDir => {
+                parts.pop();
+            }
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+        }
+    }
+    parts.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source_file(relative_path: &str, contents: &str) -> SourceFile {
+        SourceFile {
+            relative_path: relative_path.to_string(),
+            bytes: contents.as_bytes().to_vec(),
+            content_hash: String::new(),
+            metadata: Default::default(),
+        }
+    }
+
+    #[test]
+    fn go_modules_map_import_paths_to_package_files() {
+        let layout = WorkspaceLayout::from_source_files(&[
+            source_file("go.mod", "module github.com/acme/svc // root\n\ngo 1.22\n"),
+            source_file("internal/store/store.go", "package store\n"),
+            source_file("internal/store/query.go", "package store\n"),
+            source_file("internal/store/store_test.go", "package store\n"),
+            source_file("tools/go.mod", "module \"github.com/acme/svc/tools\"\n"),
+            source_file("tools/gen/gen.go", "package gen\n"),
+        ]);
+
+        assert_eq!(
+            layout.go_package_files("github.com/acme/svc/internal/store"),
+            Some(vec![
+                "internal/store/query.go".to_string(),
+                "internal/store/store.go".to_string(),
+            ])
+        );
+        assert_eq!(
+            layout.go_package_files("github.com/acme/svc/tools/gen"),
+            Some(vec!["tools/gen/gen.go".to_string()])
+        );
+        assert_eq!(
+            layout.go_package_files("github.com/acme/svc/missing"),
+            Some(Vec::new())
+        );
+        assert_eq!(layout.go_package_files("github.com/acme/svcx/store"), None);
+        assert_eq!(layout.go_package_files("fmt"), None);
+    }
+
+    #[test]
+    fn go_work_uses_and_local_replacements_register_modules() {
+        let layout = WorkspaceLayout::from_source_files(&[
+            source_file(
+                "go.work",
+                "go 1.22\n\nuse (\n\t./api\n\t./lib // shared\n)\n",
+            ),
+            source_file("api/go.mod", "module example.com/api\n"),
+            source_file("api/main.go", "package main\n"),
+            source_file("lib/go.mod", "module example.com/lib\n"),
+            source_file("lib/util/util.go", "package util\n"),
+            source_file(
+                "svc/go.mod",
+                "module example.com/svc\n\nreplace example.com/vendored => ../third_party/vendored\n",
+            ),
+            source_file("third_party/vendored/v.go", "package vendored\n"),
+        ]);
+
+        assert_eq!(
+            layout.go_package_files("example.com/lib/util"),
+            Some(vec!["lib/util/util.go".to_string()])
+        );
+        assert_eq!(
+            layout.go_package_files("example.com/api"),
+            Some(vec!["api/main.go".to_string()])
+        );
+        assert_eq!(
+            layout.go_package_files("example.com/vendored"),
+            Some(vec!["third_party/vendored/v.go".to_string()])
+        );
+    }
+
+    #[test]
+    fn cargo_manifests_map_crate_names_and_renames_to_source_roots() {
+        let layout = WorkspaceLayout::from_source_files(&[
+            source_file(
+                "Cargo.toml",
+                "[workspace]\nmembers = [\"crates/*\"]\n\n[workspace.dependencies]\nstore = { package = \"acme-storage\", path = \"crates/storage\" }\n",
+            ),
+            source_file(
+                "crates/app/Cargo.toml",
+                "[package]\nname = \"acme-app\"\n\n[dependencies]\nstore = { workspace = true }\nfmt-util = { package = \"text-fmt\", path = \"../fmt\" }\n",
+            ),
+            source_file("crates/app/src/main.rs", "fn main() {}\n"),
+            source_file("crates/app/src/cli/args.rs", ""),
+            source_file(
+                "crates/storage/Cargo.toml",
+                "[package]\nname = \"acme-storage\"\n",
+            ),
+            source_file("crates/storage/src/lib.rs", ""),
+            source_file(
+                "crates/fmt/Cargo.toml",
+                "[package]\nname = \"text-fmt\"\n\n[lib]\nname = \"textfmt\"\npath = \"lib/root.rs\"\n",
+            ),
+            source_file("crates/fmt/lib/root.rs", ""),
+        ]);
+
+        let app = layout
+            .rust_crate_for_file("crates/app/src/cli/args.rs")
+            .expect("app crate should own its source files");
+        assert_eq!(app.crate_name, "acme_app");
+        assert_eq!(app.source_dir, "crates/app/src");
+        assert_eq!(app.root_files, vec!["crates/app/src/main.rs".to_string()]);
+        assert!(
+            layout
+                .rust_crate_for_file("crates/app/tests/it.rs")
+                .is_none()
+        );
+
+        let storage = layout
+            .rust_external_crate("crates/app/src/main.rs", "acme_storage")
+            .expect("crate names should resolve with dashes mapped to underscores");
+        assert_eq!(storage.source_dir, "crates/storage/src");
+        assert_eq!(
+            layout
+                .rust_external_crate("crates/app/src/main.rs", "store")
+                .map(|krate| krate.crate_name.as_str()),
+            Some("acme_storage")
+        );
+        let fmt = layout
+            .rust_external_crate("crates/app/src/main.rs", "fmt_util")
+            .expect("renamed dependencies should resolve to their package");
+        assert_eq!(fmt.crate_name, "textfmt");
+        assert_eq!(fmt.source_dir, "crates/fmt/lib");
+        assert_eq!(fmt.root_files, vec!["crates/fmt/lib/root.rs".to_string()]);
+        assert!(
+            layout
+                .rust_external_crate("crates/storage/src/lib.rs", "store")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn tsconfig_paths_follow_extends_chains_and_base_url() {
+        let layout = WorkspaceLayout::from_source_files(&[
+            source_file(
+                "tsconfig.base.json",
+                r#"{
+  // shared compiler options
+  "compilerOptions": {
+    "baseUrl": ".",
+    "paths": { "@shared/*": ["packages/shared/src/*"], },
+  },
+}"#,
+            ),
+            source_file(
+                "apps/web/tsconfig.json",
+                r#"{ "extends": "../../tsconfig.base", "compilerOptions": { "baseUrl": "src", "paths": { "@/*": ["./*"] } } }"#,
+            ),
+            source_file("apps/web/src/components/Button.tsx", ""),
+            source_file("apps/web/src/lib/api/index.ts", ""),
+            source_file(
+                "apps/legacy/tsconfig.json",
+                r#"{ "extends": "../../tsconfig.base.json" }"#,
+            ),
+            source_file("packages/shared/src/format.ts", ""),
+        ]);
+
+        assert_eq!(
+            layout.node_module_files("apps/web/src/pages/home.tsx", "@/components/Button"),
+            vec!["apps/web/src/components/Button.tsx".to_string()]
+        );
+        assert_eq!(
+            layout.node_module_files("apps/web/src/pages/home.tsx", "lib/api"),
+            vec!["apps/web/src/lib/api/index.ts".to_string()]
+        );
+        assert_eq!(
+            layout.node_module_files("apps/legacy/main.ts", "@shared/format.js"),
+            vec!["packages/shared/src/format.ts".to_string()]
+        );
+        assert!(
+            layout
+                .node_module_files("apps/web/src/pages/home.tsx", "@shared/format")
+                .is_empty(),
+            "a child config that redefines `paths` replaces the inherited mapping"
+        );
+        assert!(
+            layout
+                .node_module_files("apps/web/src/main.ts", "react")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn workspace_packages_resolve_through_exports_main_and_build_output() {
+        let layout = WorkspaceLayout::from_source_files(&[
+            source_file(
+                "package.json",
+                r#"{ "private": true, "workspaces": ["packages/*"] }"#,
+            ),
+            source_file(
+                "packages/shared/package.json",
+                r#"{ "name": "@acme/shared", "exports": { ".": { "types": "./src/index.ts", "default": "./dist/index.js" }, "./utils/*": "./src/utils/*.ts" } }"#,
+            ),
+            source_file("packages/shared/src/index.ts", ""),
+            source_file("packages/shared/src/utils/date.ts", ""),
+            source_file(
+                "packages/ui/package.json",
+                r#"{ "name": "@acme/ui", "main": "dist/index.js" }"#,
+            ),
+            source_file("packages/ui/src/index.tsx", ""),
+            source_file(
+                "examples/ui/package.json",
+                r#"{ "name": "@acme/ui", "main": "main.js" }"#,
+            ),
+            source_file("examples/ui/main.js", ""),
+        ]);
+
+        assert_eq!(
+            layout.node_module_files("apps/web/main.ts", "@acme/shared"),
+            vec!["packages/shared/src/index.ts".to_string()]
+        );
+        assert_eq!(
+            layout.node_module_files("apps/web/main.ts", "@acme/shared/utils/date"),
+            vec!["packages/shared/src/utils/date.ts".to_string()]
+        );
+        assert_eq!(
+            layout.node_module_files("apps/web/main.ts", "@acme/ui"),
+            vec!["packages/ui/src/index.tsx".to_string()],
+            "workspace members win over same-named packages and `dist` maps back to `src`"
+        );
+        assert!(
+            layout
+                .node_module_files("apps/web/main.ts", "@acme/missing")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn python_package_roots_follow_manifests_and_init_markers() {
+        let layout = WorkspaceLayout::from_source_files(&[
+            source_file(
+                "services/billing/pyproject.toml",
+                "[tool.setuptools.packages.find]\nwhere = [\"src\"]\n",
+            ),
+            source_file("services/billing/src/acme/__init__.py", ""),
+            source_file("services/billing/src/acme/db/__init__.py", ""),
+            source_file("services/billing/src/acme/db/models.py", ""),
+            source_file("services/billing/src/acme/plugins/stripe.py", ""),
+            source_file("tools/setup.cfg", "[options]\npackage_dir =\n    =lib\n"),
+            source_file("tools/lib/cli.py", ""),
+            source_file("scripts/helpers/__init__.py", ""),
+            source_file("scripts/helpers/fmt.py", ""),
+        ]);
+
+        assert_eq!(
+            layout.python_roots,
+            vec![
+                "scripts".to_string(),
+                "services/billing/src".to_string(),
+                "tools/lib".to_string(),
+            ]
+        );
+        assert_eq!(
+            layout.python_module_file("services/billing/tests/test_db.py", "acme.db.models"),
+            Some("services/billing/src/acme/db/models.py".to_string())
+        );
+        assert_eq!(
+            layout.python_module_file("services/billing/tests/test_db.py", "acme.db"),
+            Some("services/billing/src/acme/db/__init__.py".to_string())
+        );
+        assert_eq!(
+            layout.python_module_file("services/billing/app.py", "acme.plugins.stripe"),
+            Some("services/billing/src/acme/plugins/stripe.py".to_string()),
+            "namespace packages without `__init__.py` resolve under configured roots"
+        );
+        assert_eq!(
+            layout.python_module_file("tools/run.py", "cli"),
+            Some("tools/lib/cli.py".to_string())
+        );
+        assert_eq!(
+            layout.python_module_file("scripts/run.py", "helpers.fmt"),
+            Some("scripts/helpers/fmt.py".to_string())
+        );
+        assert_eq!(
+            layout.python_module_file("scripts/run.py", "requests"),
+            None
+        );
+    }
+
+    #[test]
+    fn python_manifest_helpers_cover_poetry_hatch_and_setup_cfg_forms() {
+        assert_eq!(
+            pyproject_package_dirs(
+                "[tool.poetry]\npackages = [{ include = \"acme\", from = \"src\" }, { include = \"extra\" }]\n"
+            ),
+            vec![String::new(), "src".to_string()]
+        );
+        assert_eq!(
+            pyproject_package_dirs(
+                "[tool.hatch.build.targets.wheel]\npackages = [\"src/acme\"]\n[tool.setuptools]\npackage-dir = { \"\" = \"lib\" }\n"
+            ),
+            vec!["lib".to_string(), "src".to_string()]
+        );
+        assert!(pyproject_package_dirs("[project]\nname = \"acme\"\n").is_empty());
+        assert!(pyproject_package_dirs("not toml = = =").is_empty());
+        assert_eq!(
+            setup_cfg_package_dirs(
+                "[metadata]\nname = acme # comment\n[options.packages.find]\nwhere = src\n"
+            ),
+            vec!["src".to_string()]
+        );
+        assert_eq!(
+            setup_cfg_package_dirs("[options]\npackage_dir = =source\n"),
+            vec!["source".to_string()]
+        );
+    }
+
+    #[test]
+    fn jsonc_and_glob_helpers_cover_comments_strings_and_wildcards() {
+        assert_eq!(
+            parse_jsonc("{ \"a\": \"http://x/*y*/\", /* c */ \"b\": [1, 2,], }"),
+            Some(serde_json::json!({ "a": "http://x/*y*/", "b": [1, 2] }))
+        );
+        assert_eq!(
+            parse_jsonc("{ \"a\": \"\\\"\" }"),
+            Some(serde_json::json!({ "a": "\"" }))
+        );
+        assert!(directory_matches_glob("packages/ui", "packages/*"));
+        assert!(directory_matches_glob("libs/a/b", "libs/**"));
+        assert!(!directory_matches_glob("packages/ui/x", "packages/*"));
+        assert_eq!(build_output_to_source("pkg", "pkg/src/index.ts"), None);
+        assert_eq!(
+            build_output_to_source("pkg", "pkg/dist/index.d.ts"),
+            Some("pkg/src/index".to_string())
+        );
+    }
+
+    #[test]
+    fn go_directive_helpers_cover_single_line_and_block_forms() {
+        assert_eq!(go_mod_module_path("modulex foo\n"), None);
+        assert_eq!(go_mod_module_path("// module foo\n"), None);
+        assert_eq!(
+            go_work_use_directories("use ./one\nuser x\n"),
+            vec!["./one"]
+        );
+        assert_eq!(
+            go_local_replacements("replace (\n a v1.0.0 => ./a\n b => github.com/x/b v1\n)\n"),
+            vec![("a".to_string(), "./a".to_string())]
+        );
+        assert_eq!(join_relative("", "./lib"), "lib");
+        assert_eq!(join_relative("svc", "../lib/./x"), "lib/x");
+    }
+}
@@ -3,11 +3,12 @@ mod git_utils;
 mod indexer;
 mod output;
 mod query;
+mod serve;
 mod store;
 
 use std::fs;
 use std::path::Path;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use clap::Parser;
 use rusqlite::Connection;
@@ -16,17 +17,20 @@ use serde_json::Value as JsonValue;
 use thiserror::Error;
 
 use crate::cli::{Cli, Command};
-use crate::indexer::index_repository;
+use crate::indexer::watch::{WatchOptions, watch_repository};
+use crate::indexer::{IndexOptions, index_repository, index_repository_with_options};
 use crate::query::{
-    ChangedLineRange, DiffImpactChangedMode, DiffImpactImportMode, DiffImpactOptions,
-    DiffImpactTestMode, ExplainMatch, ImpactMatch, QueryPathMode, QueryScope, QueryTestMode,
-    VerifyPlanOptions, callees_of, callers_of, context_matches, context_matches_scoped,
-    diff_impact_for_changed_files, explain_symbol, file_deps, find_call_path, find_matches_scoped,
-    hotspots, impact_matches, outline_file, refs_matches_scoped, related_symbols,
-    repo_entry_points, snippet_for_symbol, status_summary, suggest_similar_symbols,
-    tests_for_symbol, verify_plan_for_changed_files,
+    CallPathOptions, ChangedLineRange, DiffImpactChangedMode, DiffImpactImportMode,
+    DiffImpactOptions, DiffImpactTestMode, ExplainMatch, ImpactMatch, QueryPathMode, QueryScope,
+    QueryTestMode, VerifyPlanOptions, callees_of, callers_of, context_matches,
+    context_matches_scoped, diff_impact_for_changed_files, doc_matches_scoped, explain_symbol,
+    file_deps, find_call_paths, find_matches_scoped, hotspots, impact_matches, is_code_file_path,
+    is_javascript_file_path, is_test_like_path, language_for_file_path, outline_file,
+    refs_matches_scoped, related_symbols, repo_entry_points, snippet_for_symbol, status_summary,
+    suggest_similar_symbols, tests_for_symbol, tests_for_symbol_via_calls,
+    verify_plan_for_changed_files,
 };
-use crate::store::ensure_store;
+use crate::store::{ensure_store, open_connection};
 
 /// Program entry point that runs the CLI and exits on failure.
 ///
@@ -70,6 +74,8 @@ fn run() -> Result<(), AppError> {
     let cli = Cli::parse();
     match cli.command {
         Command::Index(args) => run_index(args).map_err(AppError::internal),
+        Command::Watch(args) => run_watch(args).map_err(AppError::internal),
+        Command::Serve(args) => run_serve(args).map_err(AppError::internal),
         Command::Status(args) => run_status(args),
         Command::Schema(args) => run_schema(args).map_err(AppError::internal),
         Command::Find(args) => run_find(args),
@@ -90,6 +96,7 @@ fn run() -> Result<(), AppError> {
         Command::Callees(args) => run_callees(args).map_err(AppError::internal),
         Command::Deps(args) => run_deps(args).map_err(AppError::internal),
         Command::Hotspots(args) => run_hotspots(args).map_err(AppError::internal),
+        Command::Owners(args) => run_owners(args).map_err(AppError::internal),
         Command::CallPath(args) => run_call_path(args).map_err(AppError::internal),
         Command::Related(args) => run_related(args).map_err(AppError::internal),
         Command::Health(args) => run_health(args).map_err(AppError::internal),
@@ -105,9 +112,10 @@ fn run() -> Result<(), AppError> {
         Command::ExtractCheck(args) => run_extract_check(args).map_err(AppError::internal),
         Command::MoveCheck(args) => run_move_check(args).map_err(AppError::internal),
         Command::RenameCheck(args) => run_rename_check(args).map_err(AppError::internal),
+        Command::Rename(args) => run_rename(args).map_err(AppError::internal),
         Command::SplitCheck(args) => run_split_check(args).map_err(AppError::internal),
         Command::TestScaffold(args) => run_test_scaffold(args).map_err(AppError::internal),
-        Command::SafeSteps(args) => run_safe_steps(args).map_err(AppError::internal),
+        Command::SafeSteps(args) => run_safe_steps(args),
         Command::VerifyRefactor(args) => run_verify_refactor(args),
     }
 }
@@ -141,6 +149,16 @@ impl AppError {
         }
     }
 
+    fn usage(command: &str, json: bool, message: &str) -> Self {
+        Self {
+            kind: ErrorKind::Usage,
+            message: message.to_string(),
+            command: Some(command.to_string()),
+            json,
+            details: None,
+        }
+    }
+
     fn index(command: &str, json: bool, message: &str, details: Option<JsonValue>) -> Self {
         Self {
             kind: ErrorKind::Index,
@@ -212,18 +230,25 @@ fn print_agent_json(
     index: AgentMetaIndex,
     data: JsonValue,
 ) -> anyhow::Result<()> {
-    let payload = serde_json::json!({
+    let payload = agent_json(schema, command, repo, &index, data);
+    println!("{}", serde_json::to_string_pretty(&payload)?);
+    Ok(())
+}
+
+fn agent_json(
+    schema: &str,
+    command: &str,
+    repo: &Path,
+    index: &AgentMetaIndex,
+    data: JsonValue,
+) -> JsonValue {
+    serde_json::json!({
         "schema": schema,
         "command": command,
         "ok": true,
-        "meta": {
-            "repo": repo.display().to_string(),
-            "index": index,
-        },
+        "meta": agent_meta_json(repo, index),
         "data": data,
-    });
-    println!("{}", serde_json::to_string_pretty(&payload)?);
-    Ok(())
+    })
 }
 
 fn agent_meta_json(repo: &Path, index: &AgentMetaIndex) -> JsonValue {
@@ -233,9 +258,13 @@ fn agent_meta_json(repo: &Path, index: &AgentMetaIndex) -> JsonValue {
     })
 }
 
-fn run_index(args: crate::cli::RepoArgs) -> anyhow::Result<()> {
+fn run_index(args: crate::cli::IndexArgs) -> anyhow::Result<()> {
     let store = ensure_store(&args.repo).map_err(AppError::internal)?;
-    let summary = index_repository(&args.repo, &store.db_path)?;
+    let mut options = IndexOptions::default();
+    if let Some(jobs) = args.jobs {
+        options.jobs = jobs;
+    }
+    let summary = index_repository_with_options(&args.repo, &store.db_path, &options)?;
     write_index_runtime_metadata(&store.db_path, &args.repo)?;
     output::print_index(
         &store.db_path,
@@ -246,6 +275,56 @@ fn run_index(args: crate::cli::RepoArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+fn run_watch(args: crate::cli::WatchArgs) -> anyhow::Result<()> {
+    let store = ensure_store(&args.repo).map_err(AppError::internal)?;
+    let mut index = IndexOptions::default();
+    if let Some(jobs) = args.jobs {
+        index.jobs = jobs;
+    }
+    let options = WatchOptions {
+        index,
+        debounce: Duration::from_millis(args.debounce_ms),
+    };
+    watch_repository(
+        &args.repo,
+        &store.db_path,
+        &options,
+        |summary| {
+            if let Err(error) = write_index_runtime_metadata(&store.db_path, &args.repo) {
+                eprintln!("watch: failed to record index metadata: {error:#}");
+            }
+            output::print_index(
+                &store.db_path,
+                store.schema_version,
+                summary.indexed_files,
+                summary.non_source_files,
+            );
+            output::print_watch_ready(&args.repo);
+        },
+        |batch| match batch {
+            Ok(summary) => {
+                if let Err(error) = write_index_runtime_metadata(&store.db_path, &args.repo) {
+                    eprintln!("watch: failed to record index metadata: {error:#}");
+                }
+                output::print_watch_batch(&summary);
+            }
+            Err(error) => eprintln!("watch: reindex failed: {error:#}"),
+        },
+    )
+}
+
+fn run_serve(args: crate::cli::ServeArgs) -> anyhow::Result<()> {
+    if args.mcp {
+        return serve::serve_stdio(&args.repo, serve::Protocol::Mcp);
+    }
+    match args.socket {
+        Some(socket) => serve::serve_socket(&args.repo, &socket, || {
+            output::print_serve_ready(&socket);
+        }),
+        None => serve::serve_stdio(&args.repo, serve::Protocol::Query),
+    }
+}
+
 fn run_status<T>(args: T) -> Result<(), AppError>
 where
     T: Into<crate::cli::StatusArgs>,
@@ -313,20 +392,8 @@ fn run_find(args: crate::cli::FindArgs) -> Result<(), AppError> {
             })),
         ));
     }
-    let symbol_query = parse_symbol_query(&args.symbol);
-    let scope = query_scope_for_find_refs(args.code_only, args.exclude_tests, args.filters.scope);
-    let mut matches = find_matches_scoped(&store.db_path, &symbol_query.lookup_symbol, &scope)
-        .map_err(AppError::internal)?;
-    filter_query_matches(&mut matches, &args.filters);
-    apply_query_match_ranking_preferences(
-        &mut matches,
-        &symbol_query.preferred_file,
-        &symbol_query.preferred_lang,
-        args.filters.include_fixtures,
-    );
-    if let Some(max_results) = args.max_results {
-        matches.truncate(u32_to_usize(max_results));
-    }
+    let (symbol_query, matches) =
+        find_results(&store.db_path, &args).map_err(AppError::internal)?;
     if args.json {
         let index = AgentMetaIndex {
             schema_version: store.schema_version,
@@ -356,7 +423,7 @@ fn run_find(args: crate::cli::FindArgs) -> Result<(), AppError> {
         output::print_query_compact(&matches);
     } else {
         output::print_query("find", &symbol_query.lookup_symbol, &matches);
-        if matches.is_empty() {
+        if matches.is_empty() && args.doc.is_none() {
             let suggestions = suggest_similar_symbols(&store.db_path, &symbol_query.lookup_symbol)
                 .map_err(AppError::internal)?;
             if !suggestions.is_empty() {
@@ -367,6 +434,37 @@ fn run_find(args: crate::cli::FindArgs) -> Result<(), AppError> {
     Ok(())
 }
 
+/// Runs `find` for the symbol argument, or over indexed doc comments with `--doc`.
+fn find_results(
+    db_path: &Path,
+    args: &crate::cli::FindArgs,
+) -> anyhow::Result<(ParsedSymbolQuery, Vec<crate::query::QueryMatch>)> {
+    let scope = query_scope_for_find_refs(args.code_only, args.exclude_tests, args.filters.scope);
+    let (lookup, symbol_query) = match &args.doc {
+        Some(text) => (
+            doc_matches_scoped as SymbolLookup,
+            ParsedSymbolQuery {
+                lookup_symbol: text.clone(),
+                preferred_file: None,
+                preferred_lang: None,
+            },
+        ),
+        None => (
+            find_matches_scoped as SymbolLookup,
+            parse_symbol_query(args.symbol.as_deref().unwrap_or_default()),
+        ),
+    };
+    let matches = ranked_symbol_matches(
+        db_path,
+        lookup,
+        &symbol_query,
+        &scope,
+        &args.filters,
+        args.max_results,
+    )?;
+    Ok((symbol_query, matches))
+}
+
 /// Run the "refs" query for a symbol and print the results.
 ///
 /// Ensures the repository store exists, obtains references for `args.symbol` from the store,
@@ -414,18 +512,15 @@ fn run_refs(args: crate::cli::RefsArgs) -> Result<(), AppError> {
     }
     let symbol_query = parse_symbol_query(&args.symbol);
     let scope = query_scope_for_find_refs(args.code_only, args.exclude_tests, args.filters.scope);
-    let mut matches = refs_matches_scoped(&store.db_path, &symbol_query.lookup_symbol, &scope)
-        .map_err(AppError::internal)?;
-    filter_query_matches(&mut matches, &args.filters);
-    apply_query_match_ranking_preferences(
-        &mut matches,
-        &symbol_query.preferred_file,
-        &symbol_query.preferred_lang,
-        args.filters.include_fixtures,
-    );
-    if let Some(max_results) = args.max_results {
-        matches.truncate(u32_to_usize(max_results));
-    }
+    let matches = ranked_symbol_matches(
+        &store.db_path,
+        refs_matches_scoped,
+        &symbol_query,
+        &scope,
+        &args.filters,
+        args.max_results,
+    )
+    .map_err(AppError::internal)?;
     if args.json {
         let index = AgentMetaIndex {
             schema_version: store.schema_version,
@@ -535,39 +630,8 @@ fn run_resolve(args: crate::cli::ResolveArgs) -> Result<(), AppError> {
         ));
     }
 
-    let connection = Connection::open(&store.db_path).map_err(AppError::internal)?;
-    let mut statement = connection
-        .prepare(
-            "SELECT symbol_id, symbol, qualified_symbol, kind, language, file_path, start_line,
-                     signature
-             FROM symbols_v2
-             WHERE symbol LIKE ?1
-             ORDER BY CASE WHEN symbol = ?2 THEN 0 ELSE 1 END,
-                      symbol ASC, file_path ASC, start_line ASC
-             LIMIT 50",
-        )
-        .map_err(AppError::internal)?;
-    let rows = statement
-        .query_map([format!("%{}%", args.symbol), args.symbol.clone()], |row| {
-            Ok(ResolveCandidate {
-                symbol_id: row.get(0)?,
-                symbol: row.get(1)?,
-                qualified_symbol: row.get(2)?,
-                kind: row.get(3)?,
-                language: row.get(4)?,
-                file_path: row.get(5)?,
-                line: row.get(6)?,
-                signature: row.get(7)?,
-            })
-        })
+    let candidates = resolve_candidates(&store.db_path, &args.symbol, &args.filters)
         .map_err(AppError::internal)?;
-    let mut candidates = Vec::new();
-    for row in rows {
-        let entry = row.map_err(AppError::internal)?;
-        if path_passes_filters(&entry.file_path, &args.filters) {
-            candidates.push(entry);
-        }
-    }
 
     if args.json {
         let recommended = candidates.first().map(|entry| entry.symbol_id);
@@ -605,6 +669,43 @@ fn run_resolve(args: crate::cli::ResolveArgs) -> Result<(), AppError> {
     Ok(())
 }
 
+fn resolve_candidates(
+    db_path: &Path,
+    symbol: &str,
+    filters: &crate::cli::SymbolFilterArgs,
+) -> anyhow::Result<Vec<ResolveCandidate>> {
+    let connection = open_connection(db_path)?;
+    let mut statement = connection.prepare_cached(
+        "SELECT symbol_id, symbol, qualified_symbol, kind, language, file_path, start_line,
+                 signature
+         FROM symbols_v2
+         WHERE symbol LIKE ?1
+         ORDER BY CASE WHEN symbol = ?2 THEN 0 ELSE 1 END,
+                  symbol ASC, file_path ASC, start_line ASC
+         LIMIT 50",
+    )?;
+    let rows = statement.query_map([format!("%{symbol}%"), symbol.to_string()], |row| {
+        Ok(ResolveCandidate {
+            symbol_id: row.get(0)?,
+            symbol: row.get(1)?,
+            qualified_symbol: row.get(2)?,
+            kind: row.get(3)?,
+            language: row.get(4)?,
+            file_path: row.get(5)?,
+            line: row.get(6)?,
+            signature: row.get(7)?,
+        })
+    })?;
+    let mut candidates = Vec::new();
+    for row in rows {
+        let entry = row?;
+        if path_passes_filters(&entry.file_path, filters) {
+            candidates.push(entry);
+        }
+    }
+    Ok(candidates)
+}
+
 fn run_query_batch(args: crate::cli::QueryBatchArgs) -> Result<(), AppError> {
     let raw = fs::read_to_string(&args.input).map_err(AppError::internal)?;
     let mut requests = Vec::new();
@@ -627,71 +728,8 @@ fn run_query_batch(args: crate::cli::QueryBatchArgs) -> Result<(), AppError> {
         }
     }
 
-    let mut responses = Vec::new();
-    for request in requests {
-        let id = request
-            .get("id")
-            .and_then(JsonValue::as_str)
-            .unwrap_or("request")
-            .to_string();
-        let command = request
-            .get("command")
-            .and_then(JsonValue::as_str)
-            .unwrap_or("")
-            .to_string();
-        let symbol = request
-            .get("symbol")
-            .and_then(JsonValue::as_str)
-            .unwrap_or("")
-            .to_string();
-
-        let result = match command.as_str() {
-            "find" => {
-                let matches = find_matches_scoped(
-                    &ensure_store(&args.repo)
-                        .map_err(AppError::internal)?
-                        .db_path,
-                    &symbol,
-                    &QueryScope::default(),
-                )
-                .map_err(AppError::internal)?;
-                serde_json::json!({ "query": symbol, "results": matches })
-            }
-            "refs" => {
-                let matches = refs_matches_scoped(
-                    &ensure_store(&args.repo)
-                        .map_err(AppError::internal)?
-                        .db_path,
-                    &symbol,
-                    &QueryScope::default(),
-                )
-                .map_err(AppError::internal)?;
-                serde_json::json!({ "query": symbol, "results": matches })
-            }
-            _ => {
-                let error = serde_json::json!({
-                    "id": id,
-                    "ok": false,
-                    "error": {
-                        "code": "UNSUPPORTED_BATCH_COMMAND",
-                        "message": format!("unsupported command '{command}'")
-                    }
-                });
-                responses.push(error);
-                if args.fail_fast {
-                    break;
-                }
-                continue;
-            }
-        };
-
-        responses.push(serde_json::json!({
-            "id": id,
-            "ok": true,
-            "command": command,
-            "data": result
-        }));
-    }
+    let responses =
+        serve::run_batch(&args.repo, &requests, args.fail_fast).map_err(AppError::internal)?;
 
     if matches!(args.format, crate::cli::QueryBatchFormat::Jsonl) {
         for response in responses {
@@ -821,18 +859,26 @@ fn run_refactor_plan(args: crate::cli::RefactorPlanArgs) -> Result<(), AppError>
 /// ```
 fn run_impact(args: crate::cli::QueryArgs) -> anyhow::Result<()> {
     let store = ensure_store(&args.repo)?;
-    let symbol_query = parse_symbol_query(&args.symbol);
-    let mut matches = impact_matches(&store.db_path, &symbol_query.lookup_symbol)?;
-    filter_impact_matches(&mut matches, &args.filters);
-    apply_impact_ranking_preferences(&mut matches, args.filters.include_fixtures);
+    let (symbol, matches) = impact_results(&store.db_path, &args)?;
     if args.json {
-        output::print_impact_json(&symbol_query.lookup_symbol, &matches)?;
+        output::print_impact_json(&symbol, &matches)?;
     } else {
-        output::print_impact(&symbol_query.lookup_symbol, &matches);
+        output::print_impact(&symbol, &matches);
     }
     Ok(())
 }
 
+fn impact_results(
+    db_path: &Path,
+    args: &crate::cli::QueryArgs,
+) -> anyhow::Result<(String, Vec<ImpactMatch>)> {
+    let symbol_query = parse_symbol_query(&args.symbol);
+    let mut matches = impact_matches(db_path, &symbol_query.lookup_symbol)?;
+    filter_impact_matches(&mut matches, &args.filters);
+    apply_impact_ranking_preferences(&mut matches, args.filters.include_fixtures);
+    Ok((symbol_query.lookup_symbol, matches))
+}
+
 /// Query matches relevant to a task within a repository and print the results.
 ///
 /// The function ensures the repository store is available, retrieves context matches for the
@@ -862,17 +908,22 @@ fn run_impact(args: crate::cli::QueryArgs) -> anyhow::Result<()> {
 ///     json: false,
 ///     code_only: false,
 ///     exclude_tests: false,
+///     pack: false,
 /// };
 /// let _ = crate::run_context(args);
 /// ```
 fn run_context(args: crate::cli::ContextArgs) -> anyhow::Result<()> {
     let store = ensure_store(&args.repo)?;
-    let scope = QueryScope::from_flags(args.code_only, args.exclude_tests);
-    let matches = if scope == QueryScope::default() {
-        context_matches(&store.db_path, &args.task, args.budget)?
-    } else {
-        context_matches_scoped(&store.db_path, &args.task, args.budget, &scope)?
-    };
+    if args.pack {
+        let pack = context_pack_results(&store.db_path, &args)?;
+        if args.json {
+            output::print_context_pack_json(&pack)?;
+        } else {
+            output::print_context_pack(&pack);
+        }
+        return Ok(());
+    }
+    let matches = context_results(&store.db_path, &args)?;
     if args.json {
         output::print_context_json(&args.task, args.budget, &matches)?;
     } else {
@@ -881,6 +932,26 @@ fn run_context(args: crate::cli::ContextArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+fn context_results(
+    db_path: &Path,
+    args: &crate::cli::ContextArgs,
+) -> anyhow::Result<Vec<crate::query::ContextMatch>> {
+    let scope = QueryScope::from_flags(args.code_only, args.exclude_tests);
+    if scope == QueryScope::default() {
+        context_matches(db_path, &args.task, args.budget)
+    } else {
+        context_matches_scoped(db_path, &args.task, args.budget, &scope)
+    }
+}
+
+fn context_pack_results(
+    db_path: &Path,
+    args: &crate::cli::ContextArgs,
+) -> anyhow::Result<crate::query::pack::ContextPack> {
+    let scope = QueryScope::from_flags(args.code_only, args.exclude_tests);
+    crate::query::pack::context_pack(db_path, &args.task, args.budget, &scope)
+}
+
 /// Query test targets that reference a symbol and print the results.
 ///
 /// Ensures the repository store exists, retrieves test targets for `args.symbol`, and prints them
@@ -902,6 +973,15 @@ fn run_context(args: crate::cli::ContextArgs) -> anyhow::Result<()> {
 /// ```
 fn run_tests_for(args: crate::cli::TestsForArgs) -> anyhow::Result<()> {
     let store = ensure_store(&args.repo)?;
+    if args.via_calls {
+        let targets = tests_for_symbol_via_calls(&store.db_path, &args.symbol, args.max_depth)?;
+        if args.json {
+            output::print_tests_for_calls_json(&args.symbol, args.max_depth, &targets)?;
+        } else {
+            output::print_tests_for_calls(&args.symbol, &targets);
+        }
+        return Ok(());
+    }
     let targets = tests_for_symbol(&store.db_path, &args.symbol, args.include_support)?;
     if args.json {
         output::print_tests_for_json(&args.symbol, &targets)?;
@@ -939,50 +1019,7 @@ fn run_tests_for(args: crate::cli::TestsForArgs) -> anyhow::Result<()> {
 /// cannot be computed or printed.
 fn run_verify_plan(args: crate::cli::VerifyPlanArgs) -> anyhow::Result<()> {
     let store = ensure_store(&args.repo)?;
-    let mut changed_files = args
-        .changed_files
-        .iter()
-        .map(|path| normalize_changed_file(&args.repo, path))
-        .collect::<Vec<_>>();
-    if let Some(ref since) = args.since {
-        let git_files = git_utils::changed_files_since(&args.repo, since)?;
-        changed_files.extend(git_files);
-    }
-    if args.unstaged {
-        let unstaged = git_utils::unstaged_files(&args.repo)?;
-        changed_files.extend(unstaged);
-    }
-    if changed_files.is_empty() {
-        anyhow::bail!("no changed files: provide --changed-file, --since, or --unstaged");
-    }
-    changed_files.sort();
-    changed_files.dedup();
-
-    let mut changed_lines = args
-        .changed_lines
-        .iter()
-        .map(|spec| parse_changed_line_spec(&args.repo, spec))
-        .collect::<anyhow::Result<Vec<_>>>()?;
-    changed_lines.sort_by(|left, right| {
-        left.file_path
-            .cmp(&right.file_path)
-            .then(left.start_line.cmp(&right.start_line))
-            .then(left.end_line.cmp(&right.end_line))
-    });
-    changed_lines.dedup_by(|left, right| {
-        left.file_path == right.file_path
-            && left.start_line == right.start_line
-            && left.end_line == right.end_line
-    });
-    let mut changed_symbols = args.changed_symbols.clone();
-    changed_symbols.sort();
-    changed_symbols.dedup();
-
-    let options = VerifyPlanOptions {
-        max_targeted: args.max_targeted,
-        changed_lines,
-        changed_symbols,
-    };
+    let (changed_files, options) = verify_plan_request(&args)?;
     let steps = verify_plan_for_changed_files(&store.db_path, &changed_files, &options)?;
     if args.json {
         output::print_verify_plan_json(&changed_files, &steps)?;
@@ -992,46 +1029,68 @@ fn run_verify_plan(args: crate::cli::VerifyPlanArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+fn verify_plan_request(
+    args: &crate::cli::VerifyPlanArgs,
+) -> anyhow::Result<(Vec<String>, VerifyPlanOptions)> {
+    let ChangeSet {
+        changed_files,
+        changed_lines,
+        changed_symbols,
+    } = ChangeSet::collect(
+        &args.repo,
+        &args.changed_files,
+        &args.changed_lines,
+        &args.changed_symbols,
+        args.since.as_deref(),
+        args.unstaged,
+    )?;
+    let options = VerifyPlanOptions {
+        max_targeted: args.max_targeted,
+        changed_lines,
+        changed_symbols,
+    };
+    Ok((changed_files, options))
+}
+
 fn run_diff_impact(args: crate::cli::DiffImpactArgs) -> anyhow::Result<()> {
     let store = ensure_store(&args.repo)?;
-    let mut changed_files = args
-        .changed_files
-        .iter()
-        .map(|path| normalize_changed_file(&args.repo, path))
-        .collect::<Vec<_>>();
-    if let Some(ref since) = args.since {
-        let git_files = git_utils::changed_files_since(&args.repo, since)?;
-        changed_files.extend(git_files);
-    }
-    if args.unstaged {
-        let unstaged = git_utils::unstaged_files(&args.repo)?;
-        changed_files.extend(unstaged);
-    }
-    if changed_files.is_empty() {
-        anyhow::bail!("no changed files: provide --changed-file, --since, or --unstaged");
+    let (changed_files, options) = diff_impact_request(&args)?;
+    let matches = diff_impact_for_changed_files(&store.db_path, &changed_files, &options)?;
+    let include_tests = matches!(options.test_mode, DiffImpactTestMode::IncludeTests);
+    if args.json {
+        let print_result = output::print_diff_impact_json(
+            &changed_files,
+            options.max_distance,
+            include_tests,
+            &matches,
+        );
+        print_result?;
+    } else {
+        output::print_diff_impact(
+            &changed_files,
+            options.max_distance,
+            include_tests,
+            &matches,
+        );
     }
-    changed_files.sort();
-    changed_files.dedup();
+    Ok(())
+}
 
-    let mut changed_lines = args
-        .changed_lines
-        .iter()
-        .map(|spec| parse_changed_line_spec(&args.repo, spec))
-        .collect::<anyhow::Result<Vec<_>>>()?;
-    changed_lines.sort_by(|left, right| {
-        left.file_path
-            .cmp(&right.file_path)
-            .then(left.start_line.cmp(&right.start_line))
-            .then(left.end_line.cmp(&right.end_line))
-    });
-    changed_lines.dedup_by(|left, right| {
-        left.file_path == right.file_path
-            && left.start_line == right.start_line
-            && left.end_line == right.end_line
-    });
-    let mut changed_symbols = args.changed_symbols.clone();
-    changed_symbols.sort();
-    changed_symbols.dedup();
+fn diff_impact_request(
+    args: &crate::cli::DiffImpactArgs,
+) -> anyhow::Result<(Vec<String>, DiffImpactOptions)> {
+    let ChangeSet {
+        changed_files,
+        changed_lines,
+        changed_symbols,
+    } = ChangeSet::collect(
+        &args.repo,
+        &args.changed_files,
+        &args.changed_lines,
+        &args.changed_symbols,
+        args.since.as_deref(),
+        args.unstaged,
+    )?;
     let test_mode = if args.exclude_tests {
         DiffImpactTestMode::ExcludeTests
     } else {
@@ -1059,25 +1118,67 @@ fn run_diff_impact(args: crate::cli::DiffImpactArgs) -> anyhow::Result<()> {
             Some(args.max_results)
         },
     };
-    let matches = diff_impact_for_changed_files(&store.db_path, &changed_files, &options)?;
-    let include_tests = matches!(options.test_mode, DiffImpactTestMode::IncludeTests);
-    if args.json {
-        let print_result = output::print_diff_impact_json(
-            &changed_files,
-            options.max_distance,
-            include_tests,
-            &matches,
-        );
-        print_result?;
-    } else {
-        output::print_diff_impact(
-            &changed_files,
-            options.max_distance,
-            include_tests,
-            &matches,
-        );
+    Ok((changed_files, options))
+}
+
+/// Changed files, line ranges, and symbols named by `--changed-*`, `--since`, and `--unstaged`,
+/// normalized to repository-relative paths, sorted, and deduplicated.
+struct ChangeSet {
+    changed_files: Vec<String>,
+    changed_lines: Vec<ChangedLineRange>,
+    changed_symbols: Vec<String>,
+}
+
+impl ChangeSet {
+    fn collect(
+        repo: &Path,
+        changed_files: &[String],
+        changed_lines: &[String],
+        changed_symbols: &[String],
+        since: Option<&str>,
+        unstaged: bool,
+    ) -> anyhow::Result<Self> {
+        let mut files = changed_files
+            .iter()
+            .map(|path| normalize_changed_file(repo, path))
+            .collect::<Vec<_>>();
+        if let Some(since) = since {
+            files.extend(git_utils::changed_files_since(repo, since)?);
+        }
+        if unstaged {
+            files.extend(git_utils::unstaged_files(repo)?);
+        }
+        if files.is_empty() {
+            anyhow::bail!("no changed files: provide --changed-file, --since, or --unstaged");
+        }
+        files.sort();
+        files.dedup();
+
+        let mut lines = changed_lines
+            .iter()
+            .map(|spec| parse_changed_line_spec(repo, spec))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        lines.sort_by(|left, right| {
+            left.file_path
+                .cmp(&right.file_path)
+                .then(left.start_line.cmp(&right.start_line))
+                .then(left.end_line.cmp(&right.end_line))
+        });
+        lines.dedup_by(|left, right| {
+            left.file_path == right.file_path
+                && left.start_line == right.start_line
+                && left.end_line == right.end_line
+        });
+        let mut symbols = changed_symbols.to_vec();
+        symbols.sort();
+        symbols.dedup();
+
+        Ok(Self {
+            changed_files: files,
+            changed_lines: lines,
+            changed_symbols: symbols,
+        })
     }
-    Ok(())
 }
 
 #[must_use]
@@ -1150,23 +1251,27 @@ fn parse_changed_line_spec(
 
 fn run_explain(args: crate::cli::ExplainArgs) -> anyhow::Result<()> {
     let store = ensure_store(&args.repo)?;
-    let symbol_query = parse_symbol_query(&args.symbol);
-    let mut matches = explain_symbol(
-        &store.db_path,
-        &symbol_query.lookup_symbol,
-        args.include_snippets,
-    )?;
-    filter_explain_matches(&mut matches, &args.filters);
+    let (symbol, matches) = explain_results(&store.db_path, &args)?;
     if args.json {
-        output::print_explain_json(&symbol_query.lookup_symbol, args.include_snippets, &matches)?;
+        output::print_explain_json(&symbol, args.include_snippets, &matches)?;
     } else if args.compact {
         output::print_explain_compact(&matches);
     } else {
-        output::print_explain(&symbol_query.lookup_symbol, &matches);
+        output::print_explain(&symbol, &matches);
     }
     Ok(())
 }
 
+fn explain_results(
+    db_path: &Path,
+    args: &crate::cli::ExplainArgs,
+) -> anyhow::Result<(String, Vec<ExplainMatch>)> {
+    let symbol_query = parse_symbol_query(&args.symbol);
+    let mut matches = explain_symbol(db_path, &symbol_query.lookup_symbol, args.include_snippets)?;
+    filter_explain_matches(&mut matches, &args.filters);
+    Ok((symbol_query.lookup_symbol, matches))
+}
+
 fn run_outline(args: crate::cli::OutlineArgs) -> anyhow::Result<()> {
     let store = ensure_store(&args.repo)?;
     let entries = outline_file(&store.db_path, &args.file)?;
@@ -1189,8 +1294,28 @@ fn run_deps(args: crate::cli::DepsArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+fn run_owners(args: crate::cli::OwnersArgs) -> anyhow::Result<()> {
+    let store = ensure_store(&args.repo)?;
+    let reports = crate::query::history::owners(&store.db_path, &args.target, args.limit)?;
+    if args.json {
+        output::print_owners_json(&args.target, &reports)?;
+    } else {
+        output::print_owners(&args.target, &reports);
+    }
+    Ok(())
+}
+
 fn run_hotspots(args: crate::cli::HotspotsArgs) -> anyhow::Result<()> {
     let store = ensure_store(&args.repo)?;
+    if args.churn {
+        let entries = crate::query::history::churn_hotspots(&store.db_path, args.limit)?;
+        if args.json {
+            output::print_churn_hotspots_json(&entries)?;
+        } else {
+            output::print_churn_hotspots(&entries);
+        }
+        return Ok(());
+    }
     let entries = hotspots(&store.db_path, args.limit)?;
     if args.json {
         output::print_hotspots_json(&entries)?;
@@ -1202,53 +1327,76 @@ fn run_hotspots(args: crate::cli::HotspotsArgs) -> anyhow::Result<()> {
 
 fn run_call_path(args: crate::cli::CallPathArgs) -> anyhow::Result<()> {
     let store = ensure_store(&args.repo)?;
-    let path = find_call_path(&store.db_path, &args.from, &args.to, args.max_depth)?;
+    let report = find_call_paths(
+        &store.db_path,
+        &args.from,
+        &args.to,
+        CallPathOptions {
+            max_depth: args.max_depth,
+            max_paths: args.max_paths,
+            include_structural: args.include_structural,
+        },
+    )?;
     if args.json {
-        output::print_call_path_json(&args.from, &args.to, &path)?;
+        output::print_call_path_json(&args.from, &args.to, &report)?;
     } else {
-        output::print_call_path(&args.from, &args.to, &path);
+        output::print_call_path(&args.from, &args.to, &report);
     }
     Ok(())
 }
 
 fn run_related(args: crate::cli::QueryArgs) -> anyhow::Result<()> {
     let store = ensure_store(&args.repo)?;
-    let symbol_query = parse_symbol_query(&args.symbol);
-    let mut results = related_symbols(&store.db_path, &symbol_query.lookup_symbol)?;
-    filter_related_symbols(&mut results, &args.filters);
-    sort_related_symbols_by_path_preferences(&mut results, args.filters.include_fixtures);
+    let (symbol, results) = related_results(&store.db_path, &args)?;
     if args.json {
-        output::print_related_json(&symbol_query.lookup_symbol, &results)?;
+        output::print_related_json(&symbol, &results)?;
     } else {
-        output::print_related(&symbol_query.lookup_symbol, &results);
+        output::print_related(&symbol, &results);
     }
     Ok(())
 }
 
+fn related_results(
+    db_path: &Path,
+    args: &crate::cli::QueryArgs,
+) -> anyhow::Result<(String, Vec<crate::query::RelatedSymbol>)> {
+    let symbol_query = parse_symbol_query(&args.symbol);
+    let mut results = related_symbols(db_path, &symbol_query.lookup_symbol)?;
+    filter_related_symbols(&mut results, &args.filters);
+    sort_related_symbols_by_path_preferences(&mut results, args.filters.include_fixtures);
+    Ok((symbol_query.lookup_symbol, results))
+}
+
 fn run_callers(args: crate::cli::QueryArgs) -> anyhow::Result<()> {
     let store = ensure_store(&args.repo)?;
-    let symbol_query = parse_symbol_query(&args.symbol);
-    let mut matches = callers_of(&store.db_path, &symbol_query.lookup_symbol)?;
-    filter_edge_matches(&mut matches, &args.filters);
-    sort_edge_matches_by_path_preferences(&mut matches, args.filters.include_fixtures);
+    let (symbol, matches) = edge_results(&store.db_path, callers_of, &args)?;
     if args.json {
-        output::print_edges_json("callers", &symbol_query.lookup_symbol, &matches)?;
+        output::print_edges_json("callers", &symbol, &matches)?;
     } else {
-        output::print_edges("callers", &symbol_query.lookup_symbol, &matches);
+        output::print_edges("callers", &symbol, &matches);
     }
     Ok(())
 }
 
-fn run_callees(args: crate::cli::QueryArgs) -> anyhow::Result<()> {
-    let store = ensure_store(&args.repo)?;
+fn edge_results(
+    db_path: &Path,
+    lookup: fn(&Path, &str) -> anyhow::Result<Vec<crate::query::EdgeMatch>>,
+    args: &crate::cli::QueryArgs,
+) -> anyhow::Result<(String, Vec<crate::query::EdgeMatch>)> {
     let symbol_query = parse_symbol_query(&args.symbol);
-    let mut matches = callees_of(&store.db_path, &symbol_query.lookup_symbol)?;
+    let mut matches = lookup(db_path, &symbol_query.lookup_symbol)?;
     filter_edge_matches(&mut matches, &args.filters);
     sort_edge_matches_by_path_preferences(&mut matches, args.filters.include_fixtures);
+    Ok((symbol_query.lookup_symbol, matches))
+}
+
+fn run_callees(args: crate::cli::QueryArgs) -> anyhow::Result<()> {
+    let store = ensure_store(&args.repo)?;
+    let (symbol, matches) = edge_results(&store.db_path, callees_of, &args)?;
     if args.json {
-        output::print_edges_json("callees", &symbol_query.lookup_symbol, &matches)?;
+        output::print_edges_json("callees", &symbol, &matches)?;
     } else {
-        output::print_edges("callees", &symbol_query.lookup_symbol, &matches);
+        output::print_edges("callees", &symbol, &matches);
     }
     Ok(())
 }
@@ -1294,6 +1442,32 @@ fn parse_symbol_query(raw_symbol: &str) -> ParsedSymbolQuery {
     parsed
 }
 
+/// Runs a `find`/`refs` style lookup and applies path filters, ranking preferences, and the
+/// result cap the same way for every caller.
+type SymbolLookup = fn(&Path, &str, &QueryScope) -> anyhow::Result<Vec<crate::query::QueryMatch>>;
+
+fn ranked_symbol_matches(
+    db_path: &Path,
+    lookup: SymbolLookup,
+    symbol_query: &ParsedSymbolQuery,
+    scope: &QueryScope,
+    filters: &crate::cli::SymbolFilterArgs,
+    max_results: Option<u32>,
+) -> anyhow::Result<Vec<crate::query::QueryMatch>> {
+    let mut matches = lookup(db_path, &symbol_query.lookup_symbol, scope)?;
+    filter_query_matches(&mut matches, filters);
+    apply_query_match_ranking_preferences(
+        &mut matches,
+        &symbol_query.preferred_file,
+        &symbol_query.preferred_lang,
+        filters.include_fixtures,
+    );
+    if let Some(max_results) = max_results {
+        matches.truncate(u32_to_usize(max_results));
+    }
+    Ok(matches)
+}
+
 fn query_scope_for_find_refs(
     code_only: bool,
     exclude_tests: bool,
@@ -1356,7 +1530,7 @@ fn filter_explain_matches(matches: &mut Vec<ExplainMatch>, filters: &crate::cli:
         filters
             .lang
             .as_deref()
-            .map_or(true, |lang| item.language.eq_ignore_ascii_case(lang))
+            .is_none_or(|lang| item.language.eq_ignore_ascii_case(lang))
     });
 }
 
@@ -1372,15 +1546,15 @@ fn path_passes_filters(path: &str, filters: &crate::cli::SymbolFilterArgs) -> bo
     {
         return false;
     }
-    if let Some(file_filter) = filters.file.as_deref() {
-        if normalized_path != normalize_path(file_filter) {
-            return false;
-        }
+    if let Some(file_filter) = filters.file.as_deref()
+        && normalized_path != normalize_path(file_filter)
+    {
+        return false;
     }
-    if let Some(lang_filter) = filters.lang.as_deref() {
-        if !file_language(&normalized_path).eq_ignore_ascii_case(lang_filter) {
-            return false;
-        }
+    if let Some(lang_filter) = filters.lang.as_deref()
+        && !language_for_file_path(&normalized_path).eq_ignore_ascii_case(lang_filter)
+    {
+        return false;
     }
     true
 }
@@ -1406,10 +1580,9 @@ fn apply_query_match_ranking_preferences(
         {
             adjusted_score += 0.30;
         }
-        if preferred_lang
-            .as_deref()
-            .is_some_and(|preferred| file_language(&item.file_path).eq_ignore_ascii_case(preferred))
-        {
+        if preferred_lang.as_deref().is_some_and(|preferred| {
+            language_for_file_path(&item.file_path).eq_ignore_ascii_case(preferred)
+        }) {
             adjusted_score += 0.15;
         }
         item.score = adjusted_score.max(0.0);
@@ -1519,52 +1692,6 @@ fn sort_related_symbols_by_path_preferences(
     });
 }
 
-fn file_language(file_path: &str) -> &'static str {
-    let normalized = normalize_path(file_path);
-    if normalized.ends_with(".rs") {
-        "rust"
-    } else if normalized.ends_with(".ts") || normalized.ends_with(".tsx") {
-        "typescript"
-    } else if normalized.ends_with(".py") {
-        "python"
-    } else if normalized.ends_with(".go") {
-        "go"
-    } else {
-        "unknown"
-    }
-}
-
-fn is_code_file_path(file_path: &str) -> bool {
-    let normalized = normalize_path(file_path);
-    normalized.ends_with(".rs")
-        || normalized.ends_with(".ts")
-        || normalized.ends_with(".tsx")
-        || normalized.ends_with(".py")
-        || normalized.ends_with(".go")
-}
-
-fn is_test_like_path(file_path: &str) -> bool {
-    let normalized = normalize_path(file_path);
-    normalized.starts_with("tests/")
-        || normalized.contains("/tests/")
-        || std::path::Path::new(&normalized)
-            .file_name()
-            .and_then(|name| name.to_str())
-            .is_some_and(is_test_like_file_name)
-}
-
-fn is_test_like_file_name(file_name: &str) -> bool {
-    file_name.ends_with("_test.rs")
-        || file_name.ends_with("_test.go")
-        || file_name.ends_with("_test.py")
-        || file_name.ends_with("_tests.py")
-        || (file_name.starts_with("test_") && file_name.ends_with(".py"))
-        || file_name.ends_with(".test.ts")
-        || file_name.ends_with(".test.tsx")
-        || file_name.ends_with(".spec.ts")
-        || file_name.ends_with(".spec.tsx")
-}
-
 fn is_fixture_path(file_path: &str) -> bool {
     let normalized = normalize_path(file_path);
     normalized.starts_with("tests/fixtures/") || normalized.contains("/tests/fixtures/")
@@ -1617,7 +1744,7 @@ fn normalize_path(input: &str) -> String {
 fn known_language_prefix(prefix: &str) -> Option<(&str, &str)> {
     let (lang, rest) = prefix.split_once(':')?;
     match lang {
-        "rust" | "python" | "typescript" | "go" => Some((lang, rest)),
+        "rust" | "python" | "typescript" | "javascript" | "go" => Some((lang, rest)),
         _ => None,
     }
 }
@@ -1629,6 +1756,7 @@ fn looks_like_file_path(value: &str) -> bool {
         || normalized.ends_with(".py")
         || normalized.ends_with(".ts")
         || normalized.ends_with(".tsx")
+        || is_javascript_file_path(&normalized)
         || normalized.ends_with(".go")
 }
 
@@ -1653,8 +1781,12 @@ fn run_snippet(args: crate::cli::SnippetArgs) -> anyhow::Result<()> {
 
 fn run_health(args: crate::cli::HealthArgs) -> anyhow::Result<()> {
     let store = ensure_store(&args.repo)?;
-    let report =
-        crate::query::diagnostics::health_report(&store.db_path, args.top, args.threshold)?;
+    let report = crate::query::diagnostics::health_report(
+        &store.db_path,
+        args.top,
+        args.threshold,
+        &function_ranking(&args.ranking),
+    )?;
 
     let baseline_path = args.repo.join(".repo-scout").join("health-baseline.json");
     if args.save_baseline {
@@ -1737,11 +1869,22 @@ fn run_circular(args: crate::cli::CircularArgs) -> anyhow::Result<()> {
 fn run_anatomy(_args: crate::cli::AnatomyArgs) -> anyhow::Result<()> {
     let args = _args;
     let store = ensure_store(&args.repo)?;
-    let report = crate::query::diagnostics::file_anatomy(&store.db_path, &args.file)?;
+    let mut report = crate::query::diagnostics::file_anatomy(&store.db_path, &args.file)?;
+    let sections = output::AnatomySections {
+        clusters: args.clusters,
+        cohesion: args.cohesion,
+        suggest_split: args.suggest_split,
+    };
+    if args.clusters || args.cohesion || args.suggest_split {
+        report.clusters = Some(crate::query::cohesion::file_clusters(
+            &store.db_path,
+            &args.file,
+        )?);
+    }
     if args.json {
         output::print_anatomy_json(&report)?;
     } else {
-        output::print_anatomy(&report);
+        output::print_anatomy(&report, sections);
     }
     Ok(())
 }
@@ -1750,14 +1893,23 @@ fn run_coupling(_args: crate::cli::CouplingArgs) -> anyhow::Result<()> {
     let args = _args;
     let store = ensure_store(&args.repo)?;
     let include_tests = args.include_tests || args.include_fixtures;
-    let entries = crate::query::diagnostics::coupling_report(
-        &store.db_path,
-        args.limit,
-        crate::query::diagnostics::CouplingScope {
-            include_tests,
-            include_fixtures: args.include_fixtures,
-        },
-    )?;
+    let scope = crate::query::diagnostics::CouplingScope {
+        include_tests,
+        include_fixtures: args.include_fixtures,
+    };
+    let entries = if args.temporal {
+        crate::query::diagnostics::temporal_coupling_report(
+            &store.db_path,
+            args.limit,
+            scope,
+            crate::query::diagnostics::TemporalCouplingOptions {
+                min_shared_commits: args.min_shared,
+                max_changeset_files: args.max_changeset,
+            },
+        )?
+    } else {
+        crate::query::diagnostics::coupling_report(&store.db_path, args.limit, scope)?
+    };
     if args.json {
         output::print_coupling_json(&entries)?;
     } else {
@@ -1812,6 +1964,7 @@ fn run_suggest(_args: crate::cli::SuggestArgs) -> anyhow::Result<()> {
         args.top,
         args.safe_only,
         args.min_score,
+        &function_ranking(&args.ranking),
     )?;
     if args.json {
         output::print_suggest_json(&suggestions)?;
@@ -1853,6 +2006,13 @@ fn run_extract_check(_args: crate::cli::ExtractCheckArgs) -> anyhow::Result<()>
             "extract_end_line": report.extract_end_line,
             "estimated_line_count": report.estimated_line_count,
             "signature": report.signature,
+            "parameters": report.parameters,
+            "return_values": report.return_values,
+            "locals": report.locals,
+            "early_exits": report.early_exits,
+            "calls": report.calls,
+            "statement_aligned": report.statement_aligned,
+            "mechanically_safe": report.mechanically_safe,
             "warnings": report.warnings,
         });
         println!("{}", serde_json::to_string_pretty(&payload)?);
@@ -1870,6 +2030,44 @@ fn run_extract_check(_args: crate::cli::ExtractCheckArgs) -> anyhow::Result<()>
             "  Estimated extracted size: {} lines",
             report.estimated_line_count
         );
+        let parameters = report
+            .parameters
+            .iter()
+            .map(|parameter| {
+                let mutated = if parameter.mutated { " (mutated)" } else { "" };
+                format!("{}{mutated}", parameter.name)
+            })
+            .collect::<Vec<_>>();
+        let names = |variables: &[crate::query::flow::FlowVariable]| {
+            variables
+                .iter()
+                .map(|variable| variable.name.clone())
+                .collect::<Vec<_>>()
+        };
+        let calls = report
+            .calls
+            .iter()
+            .map(|call| call.name.clone())
+            .collect::<std::collections::BTreeSet<_>>();
+        for (label, values) in [
+            ("Parameters", parameters),
+            ("Return values", names(&report.return_values)),
+            ("Locals", names(&report.locals)),
+            ("Calls", calls.into_iter().collect()),
+        ] {
+            let listed = if values.is_empty() {
+                "none".to_string()
+            } else {
+                values.join(", ")
+            };
+            println!("  {label}: {listed}");
+        }
+        let verdict = if report.mechanically_safe {
+            "yes"
+        } else {
+            "no"
+        };
+        println!("  Mechanically safe: {verdict}");
         if !report.warnings.is_empty() {
             println!("  Warnings:");
             for warning in report.warnings {
@@ -1969,6 +2167,28 @@ fn run_rename_check(_args: crate::cli::RenameCheckArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+fn run_rename(_args: crate::cli::RenameArgs) -> anyhow::Result<()> {
+    let args = _args;
+    let store = ensure_store(&args.repo)?;
+    let patch = crate::query::rename::rename_patch(
+        &store.db_path,
+        &args.symbol,
+        &args.to,
+        args.include_text,
+    )?;
+    if args.apply {
+        crate::query::rename::apply_rename(&patch)?;
+        index_repository(&args.repo, &store.db_path)?;
+        write_index_runtime_metadata(&store.db_path, &args.repo)?;
+    }
+    if args.json {
+        output::print_rename_json(&patch, args.apply)?;
+    } else {
+        output::print_rename(&patch, args.apply, args.patch);
+    }
+    Ok(())
+}
+
 fn include_path_for_rename_check(path: &str, include_tests: bool, include_fixtures: bool) -> bool {
     if !include_fixtures && is_fixture_path(path) {
         return false;
@@ -1983,15 +2203,22 @@ fn run_split_check(_args: crate::cli::SplitCheckArgs) -> anyhow::Result<()> {
     let args = _args;
     let store = ensure_store(&args.repo)?;
     let anatomy = crate::query::diagnostics::file_anatomy(&store.db_path, &args.file)?;
-    let mode = if args.auto {
-        "auto"
-    } else if args.groups.is_some() {
-        "manual"
+    let (mode, grouping) = if args.auto {
+        ("auto", Some(crate::query::planning::SplitGrouping::Auto))
+    } else if let Some(groups) = &args.groups {
+        let groups = crate::query::planning::parse_split_groups(groups)?;
+        (
+            "manual",
+            Some(crate::query::planning::SplitGrouping::Manual(groups)),
+        )
     } else {
-        "none"
+        ("none", None)
     };
+    let report = grouping
+        .map(|grouping| crate::query::planning::split_check(&store.db_path, &args.file, &grouping))
+        .transpose()?;
     if args.json {
-        let payload = serde_json::json!({
+        let mut payload = serde_json::json!({
             "schema_version": output::JSON_SCHEMA_VERSION_V2,
             "command": "split-check",
             "file": args.file,
@@ -1999,11 +2226,30 @@ fn run_split_check(_args: crate::cli::SplitCheckArgs) -> anyhow::Result<()> {
             "symbol_count": anatomy.total_symbols,
             "function_count": anatomy.function_count,
         });
+        if let (Some(report), Some(fields)) = (&report, payload.as_object_mut()) {
+            fields.insert("groups".to_string(), serde_json::to_value(&report.groups)?);
+            fields.insert(
+                "cross_group_edges".to_string(),
+                serde_json::to_value(&report.cross_group_edges)?,
+            );
+            fields.insert(
+                "visibility_changes".to_string(),
+                serde_json::to_value(&report.visibility_changes)?,
+            );
+            fields.insert("modularity".to_string(), report.modularity.into());
+            fields.insert(
+                "warnings".to_string(),
+                serde_json::to_value(&report.warnings)?,
+            );
+        }
         println!("{}", serde_json::to_string_pretty(&payload)?);
     } else {
         println!("Split check for {} ({mode})", args.file);
         println!("  symbols: {}", anatomy.total_symbols);
         println!("  functions: {}", anatomy.function_count);
+        if let Some(report) = &report {
+            output::print_split_check(report);
+        }
     }
     Ok(())
 }
@@ -2034,33 +2280,56 @@ fn run_test_scaffold(_args: crate::cli::TestScaffoldArgs) -> anyhow::Result<()>
     Ok(())
 }
 
-fn run_safe_steps(_args: crate::cli::SafeStepsArgs) -> anyhow::Result<()> {
+fn run_safe_steps(_args: crate::cli::SafeStepsArgs) -> Result<(), AppError> {
     let args = _args;
-    let action = match args.action {
-        crate::cli::SafeStepsAction::Extract => "extract",
-        crate::cli::SafeStepsAction::Move => "move",
-        crate::cli::SafeStepsAction::Rename => "rename",
-        crate::cli::SafeStepsAction::Split => "split",
+    let store = ensure_store(&args.repo).map_err(AppError::internal)?;
+    let plan = match args.action {
+        crate::cli::SafeStepsAction::Extract => {
+            let lines = args.lines.as_deref().ok_or_else(|| {
+                AppError::usage(
+                    "safe-steps",
+                    args.json,
+                    "--lines <start>-<end> is required for extract",
+                )
+            })?;
+            crate::query::planning::parse_line_range(lines)
+                .and_then(|range| {
+                    crate::query::safe_steps::extract_steps(
+                        &store.db_path,
+                        &args.symbol,
+                        range,
+                        args.to.as_deref(),
+                    )
+                })
+                .map_err(AppError::internal)?
+        }
+        crate::cli::SafeStepsAction::Move => {
+            let destination = args.to.as_deref().ok_or_else(|| {
+                AppError::usage("safe-steps", args.json, "--to <file> is required for move")
+            })?;
+            crate::query::safe_steps::move_steps(&store.db_path, &args.symbol, destination)
+                .map_err(AppError::internal)?
+        }
+        crate::cli::SafeStepsAction::Rename => {
+            let new_name = args.to.as_deref().ok_or_else(|| {
+                AppError::usage(
+                    "safe-steps",
+                    args.json,
+                    "--to <new name> is required for rename",
+                )
+            })?;
+            crate::query::safe_steps::rename_steps(&store.db_path, &args.symbol, new_name)
+                .map_err(AppError::internal)?
+        }
+        crate::cli::SafeStepsAction::Split => {
+            crate::query::safe_steps::split_steps(&store.db_path, &args.symbol)
+                .map_err(AppError::internal)?
+        }
     };
-    let steps = vec![
-        format!("Step 1: Stage the {action} change for {}", args.symbol),
-        "Step 2: Run targeted checks".to_string(),
-        "Step 3: Run full test suite".to_string(),
-    ];
     if args.json {
-        let payload = serde_json::json!({
-            "schema_version": output::JSON_SCHEMA_VERSION_V2,
-            "command": "safe-steps",
-            "symbol": args.symbol,
-            "action": action,
-            "steps": steps,
-        });
-        println!("{}", serde_json::to_string_pretty(&payload)?);
+        output::print_safe_steps_json(&plan).map_err(AppError::internal)?;
     } else {
-        println!("Safe refactoring steps for {} ({action}):", args.symbol);
-        for step in steps {
-            println!("  {step}");
-        }
+        output::print_safe_steps(&plan);
     }
     Ok(())
 }
@@ -2073,7 +2342,7 @@ fn run_verify_refactor(_args: crate::cli::VerifyRefactorArgs) -> Result<(), AppE
         args.after.as_deref(),
     )
     .map_err(AppError::internal)?;
-    if args.strict && (!report.warnings.is_empty() || !report.changed_files.is_empty()) {
+    if args.strict && report.is_broken() {
         return Err(AppError::partial(
             "verify-refactor",
             args.json,
@@ -2082,51 +2351,46 @@ fn run_verify_refactor(_args: crate::cli::VerifyRefactorArgs) -> Result<(), AppE
                 if !report.warnings.is_empty() {
                     report.warnings.join("; ")
                 } else {
-                    format!("{} changed file(s) detected", report.changed_files.len())
+                    format!(
+                        "{} stale reference(s) to removed or renamed symbols",
+                        report.stale_references.len()
+                    )
                 }
             ),
             Some(serde_json::json!({
                 "changed_files": report.changed_files,
+                "stale_references": report.stale_references,
+                "lost_edges": report.lost_edges,
                 "warnings": report.warnings,
             })),
         ));
     }
     if args.json {
-        let payload = serde_json::json!({
-            "schema_version": output::JSON_SCHEMA_VERSION_V2,
-            "command": "verify-refactor",
-            "before": report.before,
-            "after": report.after,
-            "changed_files": report.changed_files,
-            "warnings": report.warnings,
-        });
-        println!(
-            "{}",
-            serde_json::to_string_pretty(&payload).map_err(AppError::internal)?
-        );
+        output::print_verify_refactor_json(&report).map_err(AppError::internal)?;
     } else {
-        println!(
-            "Refactoring verification ({} -> {}):",
-            report.before, report.after
-        );
-        println!("  Changed files: {}", report.changed_files.len());
-        if !report.changed_files.is_empty() {
-            for file in &report.changed_files {
-                println!("    - {file}");
-            }
-        }
-        if !report.warnings.is_empty() {
-            println!("  Warnings:");
-            for warning in report.warnings {
-                println!("    - {warning}");
-            }
-        } else if report.changed_files.is_empty() {
-            println!("  No discrepancies detected.");
-        }
+        output::print_verify_refactor(&report);
     }
     Ok(())
 }
 
+fn function_ranking(
+    args: &crate::cli::FunctionRankingArgs,
+) -> crate::query::diagnostics::FunctionRanking {
+    use crate::cli::ComplexitySort;
+    use crate::query::diagnostics::FunctionMetric;
+
+    crate::query::diagnostics::FunctionRanking {
+        sort: args.sort.map(|sort| match sort {
+            ComplexitySort::Complexity => FunctionMetric::Complexity,
+            ComplexitySort::Nesting => FunctionMetric::Nesting,
+            ComplexitySort::Params => FunctionMetric::Params,
+        }),
+        min_complexity: args.min_complexity,
+        min_nesting: args.min_nesting,
+        min_params: args.min_params,
+    }
+}
+
 fn print_health_diff(
     baseline: &crate::query::diagnostics::HealthReport,
     current: &crate::query::diagnostics::HealthReport,
@@ -2163,7 +2427,7 @@ fn print_health_diff(
     );
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct IndexFreshness {
     indexed_at: Option<String>,
     head_sha: Option<String>,
@@ -2229,7 +2493,7 @@ fn has_newer_source_file(root: &Path, than: SystemTime) -> bool {
     fn is_source(path: &Path) -> bool {
         matches!(
             path.extension().and_then(|ext| ext.to_str()),
-            Some("rs" | "py" | "go" | "ts" | "tsx" | "js" | "jsx")
+            Some("rs" | "py" | "go" | "ts" | "tsx" | "js" | "jsx" | "mjs" | "cjs")
         )
     }
 
@@ -2357,8 +2621,8 @@ mod tests {
     };
     use crate::cli::{
         CallPathArgs, ContextArgs, DepsArgs, DiffImpactArgs, ExplainArgs, FindArgs, HotspotsArgs,
-        OutlineArgs, QueryArgs, RefsArgs, RepoArgs, SnippetArgs, SymbolFilterArgs, TestsForArgs,
-        VerifyPlanArgs,
+        IndexArgs, OutlineArgs, QueryArgs, RefsArgs, RepoArgs, SnippetArgs, SymbolFilterArgs,
+        TestsForArgs, VerifyPlanArgs,
     };
     use std::path::Path;
     use tempfile::TempDir;
@@ -2400,8 +2664,9 @@ fn integration_check() {
         let repo = fixture_repo();
         let repo_path = repo.path().to_path_buf();
 
-        run_index(RepoArgs {
+        run_index(IndexArgs {
             repo: repo_path.clone(),
+            jobs: None,
         })
         .expect("index should succeed");
         run_status(RepoArgs {
@@ -2410,7 +2675,8 @@ fn integration_check() {
         .expect("status should succeed");
 
         run_find(FindArgs {
-            symbol: "run_find".to_string(),
+            symbol: Some("run_find".to_string()),
+            doc: None,
             repo: repo_path.clone(),
             json: true,
             code_only: true,
@@ -2423,7 +2689,8 @@ fn integration_check() {
         })
         .expect("find json should succeed");
         run_find(FindArgs {
-            symbol: "run_find".to_string(),
+            symbol: Some("run_find".to_string()),
+            doc: None,
             repo: repo_path.clone(),
             json: false,
             code_only: false,
@@ -2485,6 +2752,7 @@ fn integration_check() {
             budget: 16,
             code_only: false,
             exclude_tests: false,
+            pack: false,
         })
         .expect("context json with default scope should succeed");
         run_context(ContextArgs {
@@ -2494,6 +2762,7 @@ fn integration_check() {
             budget: 16,
             code_only: true,
             exclude_tests: true,
+            pack: false,
         })
         .expect("context text with scoped query should succeed");
 
@@ -2502,6 +2771,8 @@ fn integration_check() {
             repo: repo_path.clone(),
             json: true,
             include_support: true,
+            via_calls: false,
+            max_depth: 3,
         })
         .expect("tests-for json should succeed");
         run_tests_for(TestsForArgs {
@@ -2509,6 +2780,8 @@ fn integration_check() {
             repo: repo_path.clone(),
             json: false,
             include_support: false,
+            via_calls: false,
+            max_depth: 3,
         })
         .expect("tests-for text should succeed");
 
@@ -2658,12 +2931,14 @@ fn integration_check() {
             repo: repo_path.clone(),
             json: true,
             limit: 5,
+            churn: false,
         })
         .expect("hotspots json");
         run_hotspots(HotspotsArgs {
             repo: repo_path.clone(),
             json: false,
             limit: 5,
+            churn: false,
         })
         .expect("hotspots text");
 
@@ -2706,6 +2981,8 @@ fn integration_check() {
             repo: repo_path.clone(),
             json: true,
             max_depth: 5,
+            max_paths: 3,
+            include_structural: false,
         })
         .expect("call-path json");
         run_call_path(CallPathArgs {
@@ -2714,6 +2991,8 @@ fn integration_check() {
             repo: repo_path.clone(),
             json: false,
             max_depth: 5,
+            max_paths: 3,
+            include_structural: false,
         })
         .expect("call-path text");
 
@@ -2746,7 +3025,8 @@ fn integration_check() {
 
         // Find compact
         run_find(FindArgs {
-            symbol: "run_find".into(),
+            symbol: Some("run_find".into()),
+            doc: None,
             repo: repo_path.clone(),
             json: false,
             code_only: false,
@@ -1,15 +1,21 @@
 use std::path::Path;
 
+use crate::indexer::incremental::ReindexSummary;
 use crate::query::{
-    ContextMatch, DiffImpactMatch, EdgeMatch, ExplainMatch, FileDeps, HotspotEntry, ImpactMatch,
-    OutlineEntry, QueryMatch, RelatedSymbol, SnippetMatch, StatusSummary, TestTarget,
-    VerificationStep,
+    CallPathEndpoint, CallPathHop, CallPathReport, ContextMatch, DiffImpactMatch, EdgeMatch,
+    ExplainMatch, FileDeps, HotspotEntry, ImpactMatch, OutlineEntry, QueryMatch, RelatedSymbol,
+    SnippetMatch, StatusSummary, TestFunctionTarget, TestTarget, VerificationStep,
     diagnostics::{
         AnatomyReport, CircularReport, CouplingEntry, DeadSymbol, HealthReport, Suggestion,
         TestGapReport,
     },
+    history::{ChurnHotspot, OwnersReport},
     orientation::{OrientReport, TreeNode, TreeNodeKind, TreeReport},
-    planning::BoundaryReport,
+    pack::{ContextPack, PackedSignature, outline_entry_text},
+    planning::{BoundaryReport, BoundarySymbol, SplitCheckReport},
+    rename::RenamePatch,
+    safe_steps::SafeStepsPlan,
+    verification::VerifyRefactorReport,
 };
 use serde::Serialize;
 
@@ -17,14 +23,6 @@ pub const JSON_SCHEMA_VERSION: u32 = 1;
 pub const JSON_SCHEMA_VERSION_V2: u32 = 2;
 pub const JSON_SCHEMA_VERSION_V3: u32 = 3;
 
-#[derive(Debug, Serialize)]
-struct JsonQueryOutput<'a> {
-    schema_version: u32,
-    command: &'a str,
-    query: &'a str,
-    results: &'a [QueryMatch],
-}
-
 #[derive(Debug, Serialize)]
 struct JsonImpactOutput<'a> {
     schema_version: u32,
@@ -42,6 +40,15 @@ struct JsonContextOutput<'a> {
     results: &'a [ContextMatch],
 }
 
+#[derive(Debug, Serialize)]
+struct JsonContextPackOutput<'a> {
+    schema_version: u32,
+    command: &'a str,
+    mode: &'a str,
+    #[serde(flatten)]
+    pack: &'a ContextPack,
+}
+
 #[derive(Debug, Serialize)]
 struct JsonTestsForOutput<'a> {
     schema_version: u32,
@@ -50,6 +57,16 @@ struct JsonTestsForOutput<'a> {
     results: &'a [TestTarget],
 }
 
+#[derive(Debug, Serialize)]
+struct JsonTestsForCallsOutput<'a> {
+    schema_version: u32,
+    command: &'a str,
+    query: &'a str,
+    mode: &'a str,
+    max_depth: u32,
+    results: &'a [TestFunctionTarget],
+}
+
 #[derive(Debug, Serialize)]
 struct JsonVerifyPlanOutput<'a> {
     schema_version: u32,
@@ -100,6 +117,26 @@ pub fn print_index(
     println!("non_source_files: {non_source_files}");
 }
 
+pub fn print_watch_ready(repo: &Path) {
+    println!("watching: {}", repo.display());
+}
+
+pub fn print_watch_batch(summary: &ReindexSummary) {
+    for file_path in &summary.reindexed_files {
+        println!("reindexed: {file_path}");
+    }
+    for file_path in &summary.dependent_files {
+        println!("dependent: {file_path}");
+    }
+    for file_path in &summary.removed_files {
+        println!("removed: {file_path}");
+    }
+}
+
+pub fn print_serve_ready(socket: &Path) {
+    println!("serving: {}", socket.display());
+}
+
 pub fn print_status(index_path: &Path, schema_version: i64, summary: &StatusSummary) {
     println!("index_path: {}", index_path.display());
     println!("schema_version: {schema_version}");
@@ -146,38 +183,6 @@ pub fn print_query_compact(matches: &[QueryMatch]) {
     }
 }
 
-/// Emit the query results as pretty-printed JSON to stdout.
-///
-/// The JSON payload uses the current JSON_SCHEMA_VERSION and includes the
-/// original command, the queried symbol, and the provided results slice.
-///
-/// # Errors
-///
-/// Returns an error if serialization of the JSON payload fails.
-///
-/// # Examples
-///
-/// ```no_run
-/// // `matches` is a slice of `QueryMatch`; an empty slice can be passed when
-/// // there are no results.
-/// let _ = crate::output::print_query_json(
-///     "query",
-///     "my_symbol",
-///     &[] as &[crate::types::QueryMatch],
-/// );
-/// ```
-pub fn print_query_json(command: &str, symbol: &str, matches: &[QueryMatch]) -> anyhow::Result<()> {
-    let payload = JsonQueryOutput {
-        schema_version: JSON_SCHEMA_VERSION,
-        command,
-        query: symbol,
-        results: matches,
-    };
-    let serialized = serde_json::to_string_pretty(&payload)?;
-    println!("{serialized}");
-    Ok(())
-}
-
 /// Prints a human-readable summary of impact results for a symbol.
 ///
 /// The output includes the command ("impact"), the queried symbol, the number of results,
@@ -292,6 +297,76 @@ pub fn print_context_json(task: &str, budget: u32, matches: &[ContextMatch]) ->
     Ok(())
 }
 
+/// Prints a context pack as Markdown: one section per packed symbol with its source in a
+/// fenced block and its caller/callee signatures, followed by the packed file outlines.
+pub fn print_context_pack(pack: &ContextPack) {
+    println!("# Context pack: {}", pack.task);
+    println!();
+    println!(
+        "Budget: {} tokens, used ~{}, omitted {}.",
+        pack.budget, pack.token_estimate, pack.omitted
+    );
+    for symbol in &pack.symbols {
+        println!();
+        println!(
+            "## `{}` ({}) {}:{}-{}",
+            symbol.symbol, symbol.kind, symbol.file_path, symbol.start_line, symbol.end_line
+        );
+        println!();
+        println!("Why: {}", symbol.why_included);
+        println!();
+        println!("```{}", markdown_fence_language(&symbol.language));
+        println!("{}", symbol.source);
+        if symbol.source_truncated {
+            println!("// ... truncated to fit the budget");
+        }
+        println!("```");
+        print_pack_signatures("Callers", &symbol.callers);
+        print_pack_signatures("Callees", &symbol.callees);
+    }
+    for outline in &pack.outlines {
+        println!();
+        println!("## Outline: {}", outline.file_path);
+        println!();
+        for entry in &outline.entries {
+            println!("- L{} `{}`", entry.line, outline_entry_text(entry));
+        }
+    }
+}
+
+fn print_pack_signatures(label: &str, signatures: &[PackedSignature]) {
+    if signatures.is_empty() {
+        return;
+    }
+    println!();
+    println!("{label}:");
+    for neighbor in signatures {
+        println!(
+            "- `{}` ({}:{})",
+            neighbor.signature, neighbor.file_path, neighbor.line
+        );
+    }
+}
+
+fn markdown_fence_language(language: &str) -> &str {
+    match language {
+        "unknown" => "",
+        other => other,
+    }
+}
+
+pub fn print_context_pack_json(pack: &ContextPack) -> anyhow::Result<()> {
+    let payload = JsonContextPackOutput {
+        schema_version: JSON_SCHEMA_VERSION_V2,
+        command: "context",
+        mode: "pack",
+        pack,
+    };
+    let serialized = serde_json::to_string_pretty(&payload)?;
+    println!("{serialized}");
+    Ok(())
+}
+
 /// Prints a human-readable summary of test targets for a given symbol to stdout.
 ///
 /// The output includes the command ("tests-for"), the queried symbol, the number of results,
@@ -345,6 +420,47 @@ pub fn print_tests_for_json(symbol: &str, targets: &[TestTarget]) -> anyhow::Res
     Ok(())
 }
 
+/// Prints test functions that reach `symbol` through call edges, nearest first.
+pub fn print_tests_for_calls(symbol: &str, targets: &[TestFunctionTarget]) {
+    println!("command: tests-for");
+    println!("query: {symbol}");
+    println!("mode: calls");
+    println!("results: {}", targets.len());
+    for target in targets {
+        println!(
+            "{}:{} {} distance={} via: {} [{} {:.2}]",
+            target.file_path,
+            target.line,
+            target.symbol,
+            target.distance,
+            target.call_chain.join(" -> "),
+            target.confidence,
+            target.score
+        );
+        if let Some(command) = &target.command {
+            println!("  run: {command}");
+        }
+    }
+}
+
+pub fn print_tests_for_calls_json(
+    symbol: &str,
+    max_depth: u32,
+    targets: &[TestFunctionTarget],
+) -> anyhow::Result<()> {
+    let payload = JsonTestsForCallsOutput {
+        schema_version: JSON_SCHEMA_VERSION_V2,
+        command: "tests-for",
+        query: symbol,
+        mode: "calls",
+        max_depth,
+        results: targets,
+    };
+    let serialized = serde_json::to_string_pretty(&payload)?;
+    println!("{serialized}");
+    Ok(())
+}
+
 /// Prints a human-readable verification plan summary and its verification steps.
 ///
 /// The output includes the command name ("verify-plan"), the number of changed files,
@@ -518,6 +634,12 @@ pub fn print_explain(symbol: &str, matches: &[ExplainMatch]) {
         if let Some(signature) = &result.signature {
             println!("signature: {signature}");
         }
+        if let Some(doc_comment) = &result.doc_comment {
+            println!("doc:");
+            for line in doc_comment.lines() {
+                println!("{}", format!("  {line}").trim_end());
+            }
+        }
         println!(
             "inbound: called_by={} imported_by={} implemented_by={} contained_by={}",
             result.inbound.called_by,
@@ -615,13 +737,22 @@ pub fn print_outline(file: &str, entries: &[OutlineEntry]) {
     println!("file: {file}");
     println!("results: {}", entries.len());
     for entry in entries {
-        let vis = if entry.visibility.is_empty() {
+        let sig = entry.signature.as_deref().unwrap_or(&entry.symbol);
+        let vis = if matches!(entry.visibility.as_str(), "" | "private")
+            || sig.starts_with(&format!("{} ", entry.visibility))
+        {
             String::new()
         } else {
             format!("{} ", entry.visibility)
         };
-        let sig = entry.signature.as_deref().unwrap_or(&entry.symbol);
         println!("  L{} {}{} ({})", entry.line, vis, sig, entry.kind);
+        if let Some(summary) = entry
+            .doc_comment
+            .as_deref()
+            .and_then(|doc| doc.lines().next())
+        {
+            println!("      {summary}");
+        }
     }
 }
 
@@ -772,6 +903,109 @@ pub fn print_hotspots_json(entries: &[HotspotEntry]) -> anyhow::Result<()> {
     Ok(())
 }
 
+pub fn print_churn_hotspots(entries: &[ChurnHotspot]) {
+    if entries.is_empty() {
+        println!("No churn hotspots found (history is recorded when indexing a git repo).");
+        return;
+    }
+    println!("churn hotspots:");
+    for (i, e) in entries.iter().enumerate() {
+        println!(
+            "  #{}: {} ({}) in {}:{} — commits: {}, complexity: {}, score: {}, authors: {}, \
+             last: {}",
+            i + 1,
+            e.symbol,
+            e.kind,
+            e.file_path,
+            e.start_line,
+            e.commits,
+            e.complexity,
+            e.churn_score,
+            e.authors,
+            e.last_touched
+        );
+    }
+}
+
+#[derive(Serialize)]
+struct JsonChurnHotspotOutput<'a> {
+    schema_version: u32,
+    command: &'a str,
+    mode: &'a str,
+    results: &'a [ChurnHotspot],
+}
+
+pub fn print_churn_hotspots_json(entries: &[ChurnHotspot]) -> anyhow::Result<()> {
+    let payload = JsonChurnHotspotOutput {
+        schema_version: JSON_SCHEMA_VERSION_V2,
+        command: "hotspots",
+        mode: "churn",
+        results: entries,
+    };
+    let serialized = serde_json::to_string_pretty(&payload)?;
+    println!("{serialized}");
+    Ok(())
+}
+
+pub fn print_owners(target: &str, reports: &[OwnersReport]) {
+    if reports.is_empty() {
+        println!("No file or symbol named {target} is indexed.");
+        return;
+    }
+    for report in reports {
+        match (&report.symbol, report.start_line, report.end_line) {
+            (Some(symbol), Some(start_line), Some(end_line)) => println!(
+                "owners of {symbol} ({}:{start_line}-{end_line}):",
+                report.file_path
+            ),
+            _ => println!("owners of {}:", report.file_path),
+        }
+        let Some(last_touched) = &report.last_touched else {
+            println!("  (no recorded commits)");
+            continue;
+        };
+        println!(
+            "  commits: {}, last touched: {last_touched}",
+            report.commits
+        );
+        for (i, owner) in report.owners.iter().enumerate() {
+            let lines = owner
+                .lines_changed
+                .map(|lines| format!(", lines: {lines}"))
+                .unwrap_or_default();
+            println!(
+                "  #{}: {} <{}> — commits: {} ({:.0}%){lines}, last: {}",
+                i + 1,
+                owner.author,
+                owner.email,
+                owner.commits,
+                owner.share * 100.0,
+                owner.last_touched
+            );
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonOwnersOutput<'a> {
+    schema_version: u32,
+    command: &'a str,
+    target: &'a str,
+    results: &'a [OwnersReport],
+}
+
+pub fn print_owners_json(target: &str, reports: &[OwnersReport]) -> anyhow::Result<()> {
+    let payload = JsonOwnersOutput {
+        schema_version: JSON_SCHEMA_VERSION_V2,
+        command: "owners",
+        target,
+        results: reports,
+    };
+    let serialized = serde_json::to_string_pretty(&payload)?;
+    println!("{serialized}");
+    Ok(())
+}
+
 pub fn print_refs_grouped(symbol: &str, matches: &[QueryMatch]) {
     println!("command: refs");
     println!("query: {symbol}");
@@ -822,17 +1056,42 @@ pub fn print_refs_grouped(symbol: &str, matches: &[QueryMatch]) {
     }
 }
 
-pub fn print_call_path(from: &str, to: &str, path: &Option<Vec<String>>) {
+pub fn print_call_path(from: &str, to: &str, report: &CallPathReport) {
     println!("command: call-path");
     println!("from: {from}");
     println!("to: {to}");
-    match path {
-        Some(steps) => {
-            println!("path_length: {}", steps.len());
-            println!("path: {}", steps.join(" -> "));
+    print_call_path_candidates("from", &report.from_candidates);
+    print_call_path_candidates("to", &report.to_candidates);
+    let Some(names) = report.first_path_names() else {
+        println!("path: none (no call path found)");
+        return;
+    };
+    println!("path_length: {}", names.len());
+    println!("path: {}", names.join(" -> "));
+    println!("paths: {}", report.paths.len());
+    for (index, path) in report.paths.iter().enumerate() {
+        println!("path {}:", index + 1);
+        for hop in path {
+            let edge = hop
+                .edge_kind
+                .as_deref()
+                .map_or(String::new(), |kind| format!(" [{kind}]"));
+            println!(
+                "  {}:{} {}{edge}",
+                hop.endpoint.file_path, hop.endpoint.line, hop.endpoint.symbol
+            );
         }
-        None => {
-            println!("path: none (no call path found)");
+    }
+}
+
+fn print_call_path_candidates(end: &str, candidates: &[CallPathEndpoint]) {
+    println!("{end}_candidates: {}", candidates.len());
+    if candidates.len() > 1 {
+        for candidate in candidates {
+            println!(
+                "  {} {}:{}",
+                candidate.qualified_symbol, candidate.file_path, candidate.line
+            );
         }
     }
 }
@@ -843,20 +1102,22 @@ struct JsonCallPathOutput<'a> {
     command: &'a str,
     from: &'a str,
     to: &'a str,
-    path: &'a Option<Vec<String>>,
+    path: Option<Vec<String>>,
+    paths: &'a [Vec<CallPathHop>],
+    from_candidates: &'a [CallPathEndpoint],
+    to_candidates: &'a [CallPathEndpoint],
 }
 
-pub fn print_call_path_json(
-    from: &str,
-    to: &str,
-    path: &Option<Vec<String>>,
-) -> anyhow::Result<()> {
+pub fn print_call_path_json(from: &str, to: &str, report: &CallPathReport) -> anyhow::Result<()> {
     let payload = JsonCallPathOutput {
         schema_version: JSON_SCHEMA_VERSION_V2,
         command: "call-path",
         from,
         to,
-        path,
+        path: report.first_path_names(),
+        paths: &report.paths,
+        from_candidates: &report.from_candidates,
+        to_candidates: &report.to_candidates,
     };
     let serialized = serde_json::to_string_pretty(&payload)?;
     println!("{serialized}");
@@ -923,12 +1184,15 @@ pub fn print_health(report: &HealthReport, show_files: bool, show_functions: boo
         }
         for (i, func) in report.largest_functions.iter().enumerate() {
             println!(
-                "    #{:<3} {:<40} {} lines    {}:{}",
+                "    #{:<3} {:<40} {} lines    {}:{}    complexity={} nesting={} params={}",
                 i + 1,
                 func.symbol,
                 func.line_count,
                 func.file_path,
-                func.start_line
+                func.start_line,
+                metric_text(func.complexity_score),
+                metric_text(func.nesting_depth),
+                metric_text(func.param_count)
             );
         }
     }
@@ -1010,7 +1274,15 @@ struct JsonAnatomyOutput<'a> {
     report: &'a AnatomyReport,
 }
 
-pub fn print_anatomy(report: &AnatomyReport) {
+/// Cluster sections `print_anatomy` shows when the report carries a cluster analysis.
+#[derive(Debug, Clone, Copy)]
+pub struct AnatomySections {
+    pub clusters: bool,
+    pub cohesion: bool,
+    pub suggest_split: bool,
+}
+
+pub fn print_anatomy(report: &AnatomyReport, sections: AnatomySections) {
     println!("Anatomy of {}:", report.file_path);
     println!();
     println!(
@@ -1027,6 +1299,356 @@ pub fn print_anatomy(report: &AnatomyReport) {
             symbol.symbol, symbol.kind, symbol.start_line, line_count
         );
     }
+    let Some(analysis) = &report.clusters else {
+        return;
+    };
+    if sections.clusters {
+        println!();
+        println!("  clusters:");
+        if analysis.clusters.is_empty() {
+            println!("    (no cohesive clusters found)");
+        }
+        for (index, cluster) in analysis.clusters.iter().enumerate() {
+            println!(
+                "    #{} {} ({} symbols, {} lines, cohesion {:.2})",
+                index + 1,
+                cluster.name,
+                cluster.symbols.len(),
+                cluster.line_count,
+                cluster.cohesion
+            );
+            println!("       {}", cluster.symbols.join(", "));
+        }
+        if !analysis.unclustered.is_empty() {
+            println!("    unclustered: {}", analysis.unclustered.join(", "));
+        }
+    }
+    if sections.cohesion {
+        println!();
+        println!("  cohesion:");
+        for cluster in &analysis.clusters {
+            println!(
+                "    {}: {:.2} ({} internal / {} external edges)",
+                cluster.name, cluster.cohesion, cluster.internal_edges, cluster.external_edges
+            );
+        }
+        println!("    file modularity: {:.3}", analysis.modularity);
+    }
+    if sections.suggest_split {
+        println!();
+        println!("  suggested splits:");
+        if analysis.clusters.len() < 2 && analysis.unclustered.is_empty() {
+            println!("    (file is a single concern)");
+            return;
+        }
+        for (index, cluster) in analysis.clusters.iter().enumerate() {
+            println!(
+                "    {}. {} -> {} ({} lines, {} cross-cluster edges)",
+                index + 1,
+                cluster.name,
+                cluster.proposed_file,
+                cluster.line_count,
+                cluster.external_edges
+            );
+        }
+    }
+}
+
+pub fn print_split_check(report: &SplitCheckReport) {
+    println!("  modularity: {:.3}", report.modularity);
+    for group in &report.groups {
+        let placement = if group.stays { " (stays)" } else { "" };
+        println!();
+        println!(
+            "  {} -> {}{placement}: {} lines, cohesion {:.2}",
+            group.name, group.target_file, group.line_count, group.cohesion
+        );
+        println!("    symbols: {}", group.symbols.join(", "));
+        for import in &group.imports {
+            println!("    imports {} from {}", import.symbol, import.from_file);
+        }
+    }
+    if !report.cross_group_edges.is_empty() {
+        println!();
+        println!("  cross-group references:");
+        for edge in &report.cross_group_edges {
+            println!(
+                "    {} ({}) -> {} ({}) [{}]",
+                edge.from_symbol, edge.from_file, edge.to_symbol, edge.to_file, edge.edge_kind
+            );
+        }
+    }
+    if !report.visibility_changes.is_empty() {
+        println!();
+        println!("  visibility changes:");
+        for change in &report.visibility_changes {
+            println!(
+                "    {} in {}: {} -> {} (used from {})",
+                change.symbol,
+                change.file,
+                change.current.as_deref().unwrap_or("unknown"),
+                change.required,
+                change.used_from.join(", ")
+            );
+        }
+    }
+    if !report.warnings.is_empty() {
+        println!();
+        println!("  warnings:");
+        for warning in &report.warnings {
+            println!("    - {warning}");
+        }
+    }
+}
+
+pub fn print_safe_steps(plan: &SafeStepsPlan) {
+    let target = plan
+        .target
+        .as_deref()
+        .map(|target| format!(" -> {target}"))
+        .unwrap_or_default();
+    println!(
+        "Safe refactoring steps for {} ({}{target}):",
+        plan.symbol, plan.action
+    );
+    for step in &plan.steps {
+        println!();
+        println!("  Step {}: {}", step.step, step.title);
+        for edit in &step.edits {
+            let lines = line_runs(&edit.lines)
+                .iter()
+                .map(|&(start, end)| {
+                    if start == end {
+                        start.to_string()
+                    } else {
+                        format!("{start}-{end}")
+                    }
+                })
+                .collect::<Vec<_>>();
+            let lines = if lines.is_empty() {
+                String::new()
+            } else {
+                format!(":{}", lines.join(","))
+            };
+            println!("    - {}{lines}: {}", edit.file_path, edit.change);
+        }
+        for alias in &step.temporary_aliases {
+            println!(
+                "    temporary alias in {} (removed in step {}): {}",
+                alias.file_path,
+                alias.removed_in_step,
+                alias
+                    .code
+                    .as_deref()
+                    .unwrap_or("forward the old name by hand")
+            );
+        }
+        let commands = step
+            .verify
+            .iter()
+            .map(|verification| verification.step.as_str())
+            .collect::<Vec<_>>();
+        if !commands.is_empty() {
+            println!("    verify: {}", commands.join("; "));
+        }
+    }
+    if !plan.warnings.is_empty() {
+        println!();
+        println!("  warnings:");
+        for warning in &plan.warnings {
+            println!("    - {warning}");
+        }
+    }
+}
+
+/// Collapses sorted line numbers into inclusive runs of consecutive lines.
+fn line_runs(lines: &[u32]) -> Vec<(u32, u32)> {
+    let mut runs: Vec<(u32, u32)> = Vec::new();
+    for &line in lines {
+        match runs.last_mut() {
+            Some((_, end)) if *end + 1 == line => *end = line,
+            _ => runs.push((line, line)),
+        }
+    }
+    runs
+}
+
+#[derive(Serialize)]
+struct JsonSafeStepsOutput<'a> {
+    schema_version: u32,
+    command: &'a str,
+    #[serde(flatten)]
+    plan: &'a SafeStepsPlan,
+}
+
+pub fn print_safe_steps_json(plan: &SafeStepsPlan) -> anyhow::Result<()> {
+    let payload = JsonSafeStepsOutput {
+        schema_version: JSON_SCHEMA_VERSION_V2,
+        command: "safe-steps",
+        plan,
+    };
+    println!("{}", serde_json::to_string_pretty(&payload)?);
+    Ok(())
+}
+
+/// Prints the rename. Without `--apply`, stdout carries only the unified diff so it can be piped
+/// to `git apply`; the summary goes to stderr.
+/// Prints the rename summary, or with `show_patch` the unified diff on stdout and the summary on
+/// stderr so the diff can be piped to `git apply`.
+pub fn print_rename(patch: &RenamePatch, applied: bool, show_patch: bool) {
+    let mut summary = vec![format!(
+        "Rename {} -> {} ({} at {}:{}): {} edits in {} files",
+        patch.symbol,
+        patch.new_name,
+        patch.definition.kind,
+        patch.definition.file_path,
+        patch.definition.line,
+        patch.edits.len(),
+        patch.files_changed
+    )];
+    for skipped in &patch.skipped {
+        summary.push(format!(
+            "  skipped {}:{}:{} ({})",
+            skipped.file_path, skipped.line, skipped.column, skipped.reason
+        ));
+    }
+    for warning in &patch.warnings {
+        summary.push(format!("  warning: {warning}"));
+    }
+    if applied {
+        summary.push("  applied and reindexed".to_string());
+    }
+    if show_patch {
+        print!("{}", patch.patch);
+        for line in summary {
+            eprintln!("{line}");
+        }
+    } else {
+        for line in summary {
+            println!("{line}");
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonRenameOutput<'a> {
+    schema_version: u32,
+    command: &'a str,
+    applied: bool,
+    #[serde(flatten)]
+    patch: &'a RenamePatch,
+}
+
+pub fn print_rename_json(patch: &RenamePatch, applied: bool) -> anyhow::Result<()> {
+    let payload = JsonRenameOutput {
+        schema_version: JSON_SCHEMA_VERSION_V2,
+        command: "rename",
+        applied,
+        patch,
+    };
+    println!("{}", serde_json::to_string_pretty(&payload)?);
+    Ok(())
+}
+
+pub fn print_verify_refactor(report: &VerifyRefactorReport) {
+    println!(
+        "Refactoring verification ({} -> {}):",
+        report.before, report.after
+    );
+    println!("  Changed files: {}", report.changed_files.len());
+    for file in &report.changed_files {
+        println!("    - {file}");
+    }
+    println!(
+        "  Symbols: {} added, {} removed, {} renamed, {} moved",
+        report.added.len(),
+        report.removed.len(),
+        report.renamed.len(),
+        report.moved.len()
+    );
+    for symbol in &report.renamed {
+        println!(
+            "    renamed {} -> {} ({}) in {}:{}",
+            symbol.old_symbol, symbol.new_symbol, symbol.kind, symbol.new_file, symbol.new_line
+        );
+    }
+    for symbol in &report.moved {
+        println!(
+            "    moved {} ({}) {}:{} -> {}:{}",
+            symbol.new_symbol,
+            symbol.kind,
+            symbol.old_file,
+            symbol.old_line,
+            symbol.new_file,
+            symbol.new_line
+        );
+    }
+    for symbol in &report.removed {
+        println!(
+            "    removed {} ({}) from {}:{}",
+            symbol.symbol, symbol.kind, symbol.file_path, symbol.line
+        );
+    }
+    for symbol in &report.added {
+        println!(
+            "    added {} ({}) in {}:{}",
+            symbol.symbol, symbol.kind, symbol.file_path, symbol.line
+        );
+    }
+    if !report.stale_references.is_empty() {
+        println!("  Stale references:");
+        for reference in &report.stale_references {
+            let replacement = reference
+                .replacement
+                .as_deref()
+                .map(|name| format!(" (renamed to {name})"))
+                .unwrap_or_default();
+            println!(
+                "    - {}:{} still names {}{replacement}",
+                reference.file_path, reference.line, reference.symbol
+            );
+        }
+    }
+    if !report.lost_edges.is_empty() {
+        println!("  Lost edges:");
+        for edge in &report.lost_edges {
+            let removed = if edge.target_removed {
+                " [target removed]"
+            } else {
+                ""
+            };
+            println!(
+                "    - {} ({}) -{}-> {} ({}){removed}",
+                edge.from_symbol, edge.from_file, edge.edge_kind, edge.to_symbol, edge.to_file
+            );
+        }
+    }
+    if !report.warnings.is_empty() {
+        println!("  Warnings:");
+        for warning in &report.warnings {
+            println!("    - {warning}");
+        }
+    } else if report.stale_references.is_empty() && report.lost_edges.is_empty() {
+        println!("  No discrepancies detected.");
+    }
+}
+
+#[derive(Serialize)]
+struct JsonVerifyRefactorOutput<'a> {
+    schema_version: u32,
+    command: &'a str,
+    #[serde(flatten)]
+    report: &'a VerifyRefactorReport,
+}
+
+pub fn print_verify_refactor_json(report: &VerifyRefactorReport) -> anyhow::Result<()> {
+    let payload = JsonVerifyRefactorOutput {
+        schema_version: JSON_SCHEMA_VERSION_V2,
+        command: "verify-refactor",
+        report,
+    };
+    println!("{}", serde_json::to_string_pretty(&payload)?);
+    Ok(())
 }
 
 pub fn print_anatomy_json(report: &AnatomyReport) -> anyhow::Result<()> {
@@ -1063,6 +1685,20 @@ pub fn print_coupling(entries: &[CouplingEntry]) {
             entry.b_to_a_edges,
             entry.total_edges
         );
+        if let Some(temporal) = &entry.temporal {
+            println!(
+                "       co-changed in {} commits (support {:.3}, confidence {:.2} / {:.2}){}",
+                temporal.shared_commits,
+                temporal.support,
+                temporal.confidence_a_to_b,
+                temporal.confidence_b_to_a,
+                if temporal.hidden_dependency {
+                    " — hidden dependency"
+                } else {
+                    ""
+                }
+            );
+        }
     }
 }
 
@@ -1168,18 +1804,27 @@ pub fn print_suggest(entries: &[Suggestion]) {
     }
     for (index, entry) in entries.iter().enumerate() {
         println!(
-            "  #{:<3} {}:{} score={:.1} lines={} fan_in={} tested={}",
+            "  #{:<3} {}:{} score={:.1} lines={} fan_in={} tested={} complexity={} nesting={} \
+             params={}",
             index + 1,
             entry.file_path,
             entry.symbol,
             entry.refactoring_value,
             entry.line_count,
             entry.fan_in,
-            if entry.has_tests { "yes" } else { "no" }
+            if entry.has_tests { "yes" } else { "no" },
+            metric_text(entry.complexity_score),
+            metric_text(entry.nesting_depth),
+            metric_text(entry.param_count)
         );
     }
 }
 
+/// Renders an optional complexity metric; `-` marks symbols indexed before metrics existed.
+fn metric_text(value: Option<u32>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| value.to_string())
+}
+
 pub fn print_suggest_json(entries: &[Suggestion]) -> anyhow::Result<()> {
     let payload = JsonSuggestOutput {
         schema_version: JSON_SCHEMA_VERSION_V2,
@@ -1206,10 +1851,7 @@ pub fn print_boundary(report: &BoundaryReport, public_only: bool) {
         println!("    (none)");
     }
     for symbol in &report.public_symbols {
-        println!(
-            "    {} ({}) external_refs={}",
-            symbol.symbol, symbol.kind, symbol.external_references
-        );
+        print_boundary_symbol(symbol);
     }
     if public_only {
         return;
@@ -1220,13 +1862,22 @@ pub fn print_boundary(report: &BoundaryReport, public_only: bool) {
         println!("    (none)");
     }
     for symbol in &report.internal_symbols {
-        println!(
-            "    {} ({}) external_refs={}",
-            symbol.symbol, symbol.kind, symbol.external_references
-        );
+        print_boundary_symbol(symbol);
     }
 }
 
+fn print_boundary_symbol(symbol: &BoundarySymbol) {
+    let visibility = symbol
+        .visibility
+        .as_deref()
+        .map(|visibility| format!(" visibility={visibility}"))
+        .unwrap_or_default();
+    println!(
+        "    {} ({}){visibility} external_refs={}",
+        symbol.symbol, symbol.kind, symbol.external_references
+    );
+}
+
 pub fn print_boundary_json(report: &BoundaryReport) -> anyhow::Result<()> {
     let payload = JsonBoundaryOutput {
         schema_version: JSON_SCHEMA_VERSION_V2,
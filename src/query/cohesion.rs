@@ -0,0 +1,584 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+use rusqlite::{Connection, params};
+use serde::Serialize;
+
+use crate::query::language_for_file_path;
+use crate::store::open_connection;
+
+/// Definitions referenced by name in source text; call edges cover functions already.
+const REFERENCED_KINDS: &[&str] = &[
+    "struct",
+    "enum",
+    "trait",
+    "interface",
+    "class",
+    "type_alias",
+    "type",
+    "const",
+    "static",
+];
+
+/// A top-level definition of a file, with the members its type owns folded in.
+#[derive(Debug, Clone)]
+pub struct GraphUnit {
+    pub symbol: String,
+    pub kind: String,
+    pub start_line: u32,
+    /// Lines covered by the definition and its members, counting overlaps once.
+    pub line_count: u32,
+    pub visibility: Option<String>,
+    pub members: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphEdge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: String,
+}
+
+/// The intra-file reference graph between a file's top-level definitions.
+///
+/// Edges come from `symbol_edges_v2` rows whose endpoints are both in the file, plus
+/// `references` edges from definitions whose source names one of the file's types or
+/// constants.
+/// Methods count as part of the type that contains them; imports, modules, and test
+/// symbols are left out.
+#[derive(Debug, Clone)]
+pub struct FileGraph {
+    pub file_path: String,
+    pub language: &'static str,
+    pub units: Vec<GraphUnit>,
+    pub edges: Vec<GraphEdge>,
+}
+
+/// A group of definitions that reference each other more than the rest of the file.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileCluster {
+    pub name: String,
+    pub proposed_file: String,
+    pub symbols: Vec<String>,
+    pub line_count: u32,
+    pub internal_edges: u32,
+    pub external_edges: u32,
+    /// Share of possible symbol pairs in the cluster that are linked.
+    pub cohesion: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusterAnalysis {
+    pub clusters: Vec<FileCluster>,
+    /// Definitions linked to nothing else in the file.
+    pub unclustered: Vec<String>,
+    /// Modularity of the clustering: near 0 for one concern, higher for separable ones.
+    pub modularity: f64,
+}
+
+/// Loads the graph of `file_path`, reading its source under `repo_root` for type references.
+pub fn load_file_graph(
+    connection: &Connection,
+    repo_root: &Path,
+    file_path: &str,
+) -> anyhow::Result<FileGraph> {
+    let mut statement = connection.prepare_cached(
+        "SELECT symbol_id, symbol, kind, start_line, end_line, visibility, signature
+         FROM symbols_v2
+         WHERE file_path = ?1
+           AND kind NOT IN ('import', 'module')
+           AND test_path IS NULL
+         ORDER BY start_line ASC, start_column ASC, symbol ASC",
+    )?;
+    let symbols = statement
+        .query_map(params![file_path], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, u32>(3)?,
+                row.get::<_, u32>(4)?,
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, Option<String>>(6)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut statement = connection.prepare_cached(
+        "SELECT e.from_symbol_id, e.to_symbol_id, e.edge_kind
+         FROM symbol_edges_v2 e
+         JOIN symbols_v2 source ON source.symbol_id = e.from_symbol_id
+         JOIN symbols_v2 target ON target.symbol_id = e.to_symbol_id
+         WHERE source.file_path = ?1 AND target.file_path = ?1
+         ORDER BY e.from_symbol_id ASC, e.to_symbol_id ASC, e.edge_kind ASC",
+    )?;
+    let raw_edges = statement
+        .query_map(params![file_path], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Members follow the type that contains them.
+    let ids = symbols.iter().map(|row| row.0).collect::<BTreeSet<_>>();
+    let mut owner_of = HashMap::<i64, i64>::new();
+    for (from, to, kind) in &raw_edges {
+        if kind == "contains" && ids.contains(from) && ids.contains(to) && from != to {
+            owner_of.entry(*to).or_insert(*from);
+        }
+    }
+    let root = |mut id: i64| {
+        for _ in 0..8 {
+            match owner_of.get(&id) {
+                Some(owner) => id = *owner,
+                None => break,
+            }
+        }
+        id
+    };
+
+    let mut unit_of = HashMap::<i64, usize>::new();
+    let mut units = Vec::new();
+    let mut spans = Vec::<Vec<(u32, u32)>>::new();
+    for (id, symbol, kind, start_line, end_line, visibility, _) in &symbols {
+        if root(*id) != *id {
+            continue;
+        }
+        unit_of.insert(*id, units.len());
+        units.push(GraphUnit {
+            symbol: symbol.clone(),
+            kind: kind.clone(),
+            start_line: *start_line,
+            line_count: 0,
+            visibility: visibility.clone(),
+            members: Vec::new(),
+        });
+        spans.push(vec![(*start_line, *end_line)]);
+    }
+    for (id, symbol, _, start_line, end_line, _, _) in &symbols {
+        let owner = root(*id);
+        if owner == *id {
+            continue;
+        }
+        if let Some(&unit) = unit_of.get(&owner) {
+            units[unit].members.push(symbol.clone());
+            spans[unit].push((*start_line, *end_line));
+            unit_of.insert(*id, unit);
+        }
+    }
+    for (unit, unit_spans) in units.iter_mut().zip(spans) {
+        unit.line_count = covered_lines(unit_spans);
+    }
+
+    let mut seen = BTreeSet::new();
+    for (from, to, kind) in raw_edges {
+        if kind == "contains" {
+            continue;
+        }
+        if let (Some(&from), Some(&to)) = (unit_of.get(&from), unit_of.get(&to))
+            && from != to
+        {
+            seen.insert((from, to, kind));
+        }
+    }
+    let referenced = units
+        .iter()
+        .enumerate()
+        .filter(|(_, unit)| REFERENCED_KINDS.contains(&unit.kind.as_str()))
+        .map(|(index, unit)| (index, unit.symbol.as_str()))
+        .collect::<Vec<_>>();
+    let source = std::fs::read_to_string(repo_root.join(file_path)).ok();
+    let lines = source
+        .as_deref()
+        .map(|source| source.lines().collect::<Vec<_>>());
+    for (id, _, _, start_line, end_line, _, signature) in &symbols {
+        let Some(&from) = unit_of.get(id) else {
+            continue;
+        };
+        // Fall back to the stored signature when the file cannot be read.
+        let text = match &lines {
+            Some(lines) => {
+                let start = (*start_line as usize).saturating_sub(1).min(lines.len());
+                let end = (*end_line as usize).clamp(start, lines.len());
+                lines[start..end].join("\n")
+            }
+            None => signature.clone().unwrap_or_default(),
+        };
+        for &(to, name) in &referenced {
+            if to != from && mentions(&text, name) {
+                seen.insert((from, to, "references".to_string()));
+            }
+        }
+    }
+
+    Ok(FileGraph {
+        file_path: file_path.to_string(),
+        language: language_for_file_path(file_path),
+        units,
+        edges: seen
+            .into_iter()
+            .map(|(from, to, kind)| GraphEdge { from, to, kind })
+            .collect(),
+    })
+}
+
+/// Clusters a file's definitions by modularity over its intra-file reference graph.
+pub fn file_clusters(db_path: &Path, file_path: &str) -> anyhow::Result<ClusterAnalysis> {
+    let connection = open_connection(db_path)?;
+    let repo_root = db_path.parent().and_then(Path::parent).unwrap_or(db_path);
+    let graph = load_file_graph(&connection, repo_root, file_path)?;
+    let assignment = graph.communities();
+    let mut members = BTreeMap::<usize, Vec<usize>>::new();
+    for (unit, community) in assignment.iter().enumerate() {
+        members.entry(*community).or_default().push(unit);
+    }
+
+    let mut clusters = Vec::new();
+    let mut unclustered = Vec::new();
+    let mut taken = BTreeSet::new();
+    for units in members.into_values() {
+        if units.len() < 2 {
+            unclustered.extend(units.iter().map(|&unit| graph.units[unit].symbol.clone()));
+            continue;
+        }
+        let name = graph.units[graph.entry_unit(&units)].symbol.clone();
+        let proposed_file = proposed_file(file_path, &name, &mut taken);
+        clusters.push(graph.cluster(name, proposed_file, &units));
+    }
+    clusters.sort_by(|left, right| {
+        right
+            .line_count
+            .cmp(&left.line_count)
+            .then(left.name.cmp(&right.name))
+    });
+    Ok(ClusterAnalysis {
+        clusters,
+        unclustered,
+        modularity: graph.modularity(&assignment),
+    })
+}
+
+impl FileGraph {
+    /// Undirected adjacency with one unit of weight per directed edge.
+    fn adjacency(&self) -> Vec<BTreeMap<usize, f64>> {
+        let mut adjacency = vec![BTreeMap::new(); self.units.len()];
+        for edge in &self.edges {
+            *adjacency[edge.from].entry(edge.to).or_insert(0.0) += 1.0;
+            *adjacency[edge.to].entry(edge.from).or_insert(0.0) += 1.0;
+        }
+        adjacency
+    }
+
+    /// Assigns each unit a community with the Louvain method, numbered by first appearance.
+    pub fn communities(&self) -> Vec<usize> {
+        let mut assignment = (0..self.units.len()).collect::<Vec<_>>();
+        let mut adjacency = self.adjacency();
+        loop {
+            let level = local_moves(&adjacency);
+            let count = level.iter().max().map_or(0, |max| max + 1);
+            if count == adjacency.len() {
+                break;
+            }
+            for community in &mut assignment {
+                *community = level[*community];
+            }
+            let mut aggregated = vec![BTreeMap::new(); count];
+            for (node, row) in adjacency.iter().enumerate() {
+                for (&neighbor, &weight) in row {
+                    *aggregated[level[node]]
+                        .entry(level[neighbor])
+                        .or_insert(0.0) += weight;
+                }
+            }
+            adjacency = aggregated;
+        }
+        assignment
+    }
+
+    /// Newman modularity of `assignment`, rounded to three places.
+    pub fn modularity(&self, assignment: &[usize]) -> f64 {
+        let adjacency = self.adjacency();
+        let total = adjacency.iter().flat_map(BTreeMap::values).sum::<f64>();
+        if total == 0.0 {
+            return 0.0;
+        }
+        let mut inside = BTreeMap::<usize, f64>::new();
+        let mut degree = BTreeMap::<usize, f64>::new();
+        for (node, row) in adjacency.iter().enumerate() {
+            for (&neighbor, &weight) in row {
+                *degree.entry(assignment[node]).or_insert(0.0) += weight;
+                if assignment[node] == assignment[neighbor] {
+                    *inside.entry(assignment[node]).or_insert(0.0) += weight;
+                }
+            }
+        }
+        let modularity = degree
+            .iter()
+            .map(|(community, degree)| {
+                inside.get(community).copied().unwrap_or(0.0) / total - (degree / total).powi(2)
+            })
+            .sum::<f64>();
+        round3(modularity)
+    }
+
+    /// The unit a group is named after: public first, then most referenced from outside the
+    /// group, then most connected, then earliest.
+    pub fn entry_unit(&self, units: &[usize]) -> usize {
+        let group = units.iter().copied().collect::<BTreeSet<_>>();
+        let score = |unit: usize| {
+            let external = self
+                .edges
+                .iter()
+                .filter(|edge| edge.to == unit && !group.contains(&edge.from))
+                .count();
+            let degree = self
+                .edges
+                .iter()
+                .filter(|edge| edge.to == unit || edge.from == unit)
+                .count();
+            (
+                is_public(self.units[unit].visibility.as_deref()),
+                external,
+                degree,
+            )
+        };
+        units
+            .iter()
+            .copied()
+            .max_by(|&left, &right| {
+                score(left).cmp(&score(right)).then(
+                    self.units[right]
+                        .start_line
+                        .cmp(&self.units[left].start_line),
+                )
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn cluster(&self, name: String, proposed_file: String, units: &[usize]) -> FileCluster {
+        let group = units.iter().copied().collect::<BTreeSet<_>>();
+        let mut internal_edges = 0;
+        let mut external_edges = 0;
+        let mut linked_pairs = BTreeSet::new();
+        for edge in &self.edges {
+            match (group.contains(&edge.from), group.contains(&edge.to)) {
+                (true, true) => {
+                    internal_edges += 1;
+                    linked_pairs.insert((edge.from.min(edge.to), edge.from.max(edge.to)));
+                }
+                (true, false) | (false, true) => external_edges += 1,
+                (false, false) => {}
+            }
+        }
+        let possible_pairs = units.len() * units.len().saturating_sub(1) / 2;
+        let cohesion = if possible_pairs == 0 {
+            1.0
+        } else {
+            round3(linked_pairs.len() as f64 / possible_pairs as f64)
+        };
+        FileCluster {
+            name,
+            proposed_file,
+            symbols: units
+                .iter()
+                .map(|&unit| self.units[unit].symbol.clone())
+                .collect(),
+            line_count: units.iter().map(|&unit| self.units[unit].line_count).sum(),
+            internal_edges,
+            external_edges,
+            cohesion,
+        }
+    }
+}
+
+/// One Louvain pass: moves nodes to the neighboring community with the best modularity gain
+/// until no move helps. Diagonal entries hold twice the weight of a node's internal edges.
+fn local_moves(adjacency: &[BTreeMap<usize, f64>]) -> Vec<usize> {
+    let degree = adjacency
+        .iter()
+        .map(|row| row.values().sum::<f64>())
+        .collect::<Vec<_>>();
+    let total = degree.iter().sum::<f64>();
+    let mut community = (0..adjacency.len()).collect::<Vec<_>>();
+    if total == 0.0 {
+        return community;
+    }
+    let mut community_degree = degree.clone();
+    for _ in 0..100 {
+        let mut moved = false;
+        for node in 0..adjacency.len() {
+            let current = community[node];
+            let mut links = BTreeMap::<usize, f64>::new();
+            for (&neighbor, &weight) in &adjacency[node] {
+                if neighbor != node {
+                    *links.entry(community[neighbor]).or_insert(0.0) += weight;
+                }
+            }
+            community_degree[current] -= degree[node];
+            let gain = |candidate: usize| {
+                links.get(&candidate).copied().unwrap_or(0.0)
+                    - community_degree[candidate] * degree[node] / total
+            };
+            let mut best = current;
+            let mut best_gain = gain(current);
+            for &candidate in links.keys() {
+                let candidate_gain = gain(candidate);
+                if candidate_gain > best_gain + 1e-9 {
+                    best = candidate;
+                    best_gain = candidate_gain;
+                }
+            }
+            community_degree[best] += degree[node];
+            community[node] = best;
+            moved |= best != current;
+        }
+        if !moved {
+            break;
+        }
+    }
+
+    let mut renumbered = BTreeMap::new();
+    community
+        .iter()
+        .map(|label| {
+            let next = renumbered.len();
+            *renumbered.entry(*label).or_insert(next)
+        })
+        .collect()
+}
+
+/// Proposes a file for a group named after `name`, next to `file_path` (inside its module
+/// directory for Rust). `taken` keeps proposals for one file distinct.
+pub fn proposed_file(file_path: &str, name: &str, taken: &mut BTreeSet<String>) -> String {
+    let (directory, file_name) = match file_path.rsplit_once('/') {
+        Some((directory, file_name)) => (format!("{directory}/"), file_name),
+        None => (String::new(), file_path),
+    };
+    let (stem, extension) = file_name.rsplit_once('.').unwrap_or((file_name, ""));
+    let directory = match (language_for_file_path(file_path), stem) {
+        ("rust", "mod" | "lib" | "main") => directory,
+        ("rust", _) => format!("{directory}{stem}/"),
+        _ => directory,
+    };
+    let separator = match language_for_file_path(file_path) {
+        "typescript" | "javascript" => '-',
+        _ => '_',
+    };
+    let base = split_words(name).join(&separator.to_string());
+    let mut candidate = format!("{directory}{base}.{extension}");
+    let mut suffix = 2;
+    while candidate == file_path || !taken.insert(candidate.clone()) {
+        candidate = format!("{directory}{base}{separator}{suffix}.{extension}");
+        suffix += 1;
+    }
+    candidate
+}
+
+/// Lowercase words of an identifier split on `_`, `-`, and case changes.
+fn split_words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for character in name.chars() {
+        if character == '_' || character == '-' {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            previous_lower = false;
+            continue;
+        }
+        if character.is_uppercase() && previous_lower && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        previous_lower = character.is_lowercase() || character.is_ascii_digit();
+        current.extend(character.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    if words.is_empty() {
+        words.push("group".to_string());
+    }
+    words
+}
+
+pub fn is_public(visibility: Option<&str>) -> bool {
+    matches!(visibility, Some(value) if value != "private" && value != "pub(self)")
+}
+
+/// Whether `text` contains `name` as a whole identifier.
+fn mentions(text: &str, name: &str) -> bool {
+    let is_identifier = |character: char| character.is_alphanumeric() || character == '_';
+    text.match_indices(name).any(|(index, _)| {
+        let before = text[..index].chars().next_back();
+        let after = text[index + name.len()..].chars().next();
+        !before.is_some_and(is_identifier) && !after.is_some_and(is_identifier)
+    })
+}
+
+fn covered_lines(mut spans: Vec<(u32, u32)>) -> u32 {
+    spans.sort_unstable();
+    let mut covered = 0;
+    let mut reached = 0;
+    for (start, end) in spans {
+        let start = start.max(reached + 1);
+        if end >= start {
+            covered += end - start + 1;
+            reached = end;
+        }
+    }
+    covered
+}
+
+pub fn round3(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use super::{covered_lines, local_moves, mentions, proposed_file, split_words};
+
+    #[test]
+    fn local_moves_separates_two_triangles_joined_by_one_edge() {
+        let mut adjacency = vec![BTreeMap::new(); 6];
+        for (left, right) in [(0, 1), (1, 2), (0, 2), (3, 4), (4, 5), (3, 5), (2, 3)] {
+            adjacency[left].insert(right, 1.0);
+            adjacency[right].insert(left, 1.0);
+        }
+        assert_eq!(local_moves(&adjacency), vec![0, 0, 0, 1, 1, 1]);
+    }
+
+    #[test]
+    fn proposed_files_follow_language_layout() {
+        let mut taken = BTreeSet::new();
+        assert_eq!(
+            proposed_file("src/query/flow.rs", "FlowWalker", &mut taken),
+            "src/query/flow/flow_walker.rs"
+        );
+        assert_eq!(
+            proposed_file("src/lib.rs", "parse_config", &mut taken),
+            "src/parse_config.rs"
+        );
+        assert_eq!(
+            proposed_file("src/lib.rs", "parse_config", &mut taken),
+            "src/parse_config_2.rs"
+        );
+        assert_eq!(
+            proposed_file("web/list.ts", "renderList", &mut taken),
+            "web/render-list.ts"
+        );
+        assert_eq!(split_words("HTTPServer_v2"), ["httpserver", "v2"]);
+    }
+
+    #[test]
+    fn mentions_and_covered_lines_handle_edges() {
+        assert!(mentions("fn load(config: &Config) -> Config", "Config"));
+        assert!(!mentions("fn load(config: &ConfigSet)", "Config"));
+        assert_eq!(covered_lines(vec![(1, 10), (3, 4), (12, 12)]), 11);
+    }
+}
@@ -1,9 +1,13 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 
-use rusqlite::{Connection, params};
+use rusqlite::{named_params, params};
 use serde::{Deserialize, Serialize};
 
+use crate::query::cohesion::ClusterAnalysis;
+use crate::query::is_test_like_path;
+use crate::store::open_connection;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileHealth {
     pub file_path: String,
@@ -17,19 +21,87 @@ pub struct FunctionHealth {
     pub symbol: String,
     pub line_count: u32,
     pub start_line: u32,
+    pub param_count: Option<u32>,
+    pub nesting_depth: Option<u32>,
+    pub branch_count: Option<u32>,
+    pub complexity_score: Option<u32>,
+}
+
+/// Complexity metric `health` and `suggest` can rank functions by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FunctionMetric {
+    Complexity,
+    Nesting,
+    Params,
+}
+
+/// Ordering and minimum metrics for function rankings. `sort: None` keeps each command's
+/// default order (line count for `health`, refactoring value for `suggest`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FunctionRanking {
+    pub sort: Option<FunctionMetric>,
+    pub min_complexity: Option<u32>,
+    pub min_nesting: Option<u32>,
+    pub min_params: Option<u32>,
+}
+
+impl FunctionRanking {
+    fn order_by(&self) -> &'static str {
+        match self.sort {
+            None => "line_count DESC",
+            Some(FunctionMetric::Complexity) => {
+                "complexity_score DESC, nesting_depth DESC, line_count DESC"
+            }
+            Some(FunctionMetric::Nesting) => {
+                "nesting_depth DESC, complexity_score DESC, line_count DESC"
+            }
+            Some(FunctionMetric::Params) => {
+                "param_count DESC, complexity_score DESC, line_count DESC"
+            }
+        }
+    }
+
+    /// Whether the ranking uses only the command's default size-based order, with no metric
+    /// sort or threshold.
+    fn is_size_only(&self) -> bool {
+        self.sort.is_none()
+            && self.min_complexity.is_none()
+            && self.min_nesting.is_none()
+            && self.min_params.is_none()
+    }
+
+    fn metric(&self, metrics: [Option<u32>; 3]) -> Option<u32> {
+        let [complexity, nesting, params] = metrics;
+        match self.sort? {
+            FunctionMetric::Complexity => complexity,
+            FunctionMetric::Nesting => nesting,
+            FunctionMetric::Params => params,
+        }
+    }
 }
 
+/// SQL predicate applying the [`FunctionRanking`] thresholds, bound as named parameters.
+const FUNCTION_THRESHOLD_FILTER: &str =
+    "(:min_complexity IS NULL OR complexity_score >= :min_complexity)
+     AND (:min_nesting IS NULL OR nesting_depth >= :min_nesting)
+     AND (:min_params IS NULL OR param_count >= :min_params)";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthReport {
     pub largest_files: Vec<FileHealth>,
     pub largest_functions: Vec<FunctionHealth>,
 }
 
-pub fn health_report(db_path: &Path, top_n: u32, threshold: u32) -> anyhow::Result<HealthReport> {
-    let connection = Connection::open(db_path)?;
+pub fn health_report(
+    db_path: &Path,
+    top_n: u32,
+    threshold: u32,
+    ranking: &FunctionRanking,
+) -> anyhow::Result<HealthReport> {
+    let connection = open_connection(db_path)?;
 
     let largest_files = {
-        let mut stmt = connection.prepare(
+        let mut stmt = connection.prepare_cached(
             "SELECT f.file_path, f.line_count, COUNT(s.symbol_id) as sym_count
              FROM indexed_files f
              LEFT JOIN symbols_v2 s ON f.file_path = s.file_path
@@ -49,21 +121,38 @@ pub fn health_report(db_path: &Path, top_n: u32, threshold: u32) -> anyhow::Resu
     };
 
     let largest_functions = {
-        let mut stmt = connection.prepare(
-            "SELECT file_path, symbol, line_count, start_line
+        let mut stmt = connection.prepare_cached(&format!(
+            "SELECT file_path, symbol, line_count, start_line,
+                    param_count, nesting_depth, branch_count, complexity_score
              FROM symbols_v2
-             WHERE kind = 'function' AND line_count IS NOT NULL AND line_count >= ?1
-             ORDER BY line_count DESC, file_path ASC, symbol ASC
-             LIMIT ?2",
+             WHERE (kind IN ('function', 'method') OR complexity_score IS NOT NULL)
+               AND line_count IS NOT NULL AND line_count >= :threshold
+               AND {FUNCTION_THRESHOLD_FILTER}
+             ORDER BY {}, file_path ASC, symbol ASC
+             LIMIT :top",
+            ranking.order_by()
+        ))?;
+        let rows = stmt.query_map(
+            named_params! {
+                ":threshold": threshold,
+                ":top": top_n,
+                ":min_complexity": ranking.min_complexity,
+                ":min_nesting": ranking.min_nesting,
+                ":min_params": ranking.min_params,
+            },
+            |row| {
+                Ok(FunctionHealth {
+                    file_path: row.get(0)?,
+                    symbol: row.get(1)?,
+                    line_count: row.get(2)?,
+                    start_line: row.get(3)?,
+                    param_count: row.get(4)?,
+                    nesting_depth: row.get(5)?,
+                    branch_count: row.get(6)?,
+                    complexity_score: row.get(7)?,
+                })
+            },
         )?;
-        let rows = stmt.query_map(params![threshold, top_n], |row| {
-            Ok(FunctionHealth {
-                file_path: row.get(0)?,
-                symbol: row.get(1)?,
-                line_count: row.get(2)?,
-                start_line: row.get(3)?,
-            })
-        })?;
         rows.collect::<Result<Vec<_>, _>>()?
     };
 
@@ -93,11 +182,15 @@ pub struct AnatomyReport {
     pub total_symbols: u32,
     pub function_count: u32,
     pub symbols: Vec<AnatomySymbol>,
+    /// Cohesive symbol clusters; only reported by `anatomy --clusters`, `--cohesion`, or
+    /// `--suggest-split`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clusters: Option<ClusterAnalysis>,
 }
 
 pub fn file_anatomy(db_path: &Path, file_path: &str) -> anyhow::Result<AnatomyReport> {
-    let connection = Connection::open(db_path)?;
-    let mut stmt = connection.prepare(
+    let connection = open_connection(db_path)?;
+    let mut stmt = connection.prepare_cached(
         "SELECT symbol, kind, start_line, line_count
          FROM symbols_v2
          WHERE file_path = ?1
@@ -121,6 +214,7 @@ pub fn file_anatomy(db_path: &Path, file_path: &str) -> anyhow::Result<AnatomyRe
         total_symbols,
         function_count,
         symbols,
+        clusters: None,
     })
 }
 
@@ -131,6 +225,31 @@ pub struct CouplingEntry {
     pub a_to_b_edges: u32,
     pub b_to_a_edges: u32,
     pub total_edges: u32,
+    /// Co-change statistics; only reported by `coupling --temporal`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temporal: Option<TemporalCoupling>,
+}
+
+/// How often two files changed in the same commits of the recorded git history.
+#[derive(Debug, Clone, Serialize)]
+pub struct TemporalCoupling {
+    pub shared_commits: u32,
+    pub commits_a: u32,
+    pub commits_b: u32,
+    /// `shared_commits` over all analyzed commits.
+    pub support: f64,
+    /// Fraction of `file_a`'s commits that also changed `file_b`.
+    pub confidence_a_to_b: f64,
+    /// Fraction of `file_b`'s commits that also changed `file_a`.
+    pub confidence_b_to_a: f64,
+    /// Changed together without any static edge between the files.
+    pub hidden_dependency: bool,
+}
+
+impl TemporalCoupling {
+    fn confidence(&self) -> f64 {
+        self.confidence_a_to_b.max(self.confidence_b_to_a)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -139,13 +258,132 @@ pub struct CouplingScope {
     pub include_fixtures: bool,
 }
 
+/// Thresholds for `coupling --temporal`.
+#[derive(Debug, Clone, Copy)]
+pub struct TemporalCouplingOptions {
+    /// Pairs changed together in fewer commits are not reported as temporally coupled.
+    pub min_shared_commits: u32,
+    /// Commits changing more files than this (bulk renames, formatting sweeps) are skipped.
+    pub max_changeset_files: u32,
+}
+
 pub fn coupling_report(
     db_path: &Path,
     limit: u32,
     scope: CouplingScope,
 ) -> anyhow::Result<Vec<CouplingEntry>> {
-    let connection = Connection::open(db_path)?;
-    let mut stmt = connection.prepare(
+    let connection = open_connection(db_path)?;
+    let mut entries = static_coupling_entries(&connection, i64::from(limit))?;
+    entries.retain(|entry| {
+        include_path_for_scope(&entry.file_a, scope) && include_path_for_scope(&entry.file_b, scope)
+    });
+    Ok(entries)
+}
+
+/// Merges static coupling with change coupling mined from the git history recorded by `index`.
+///
+/// Every pair of files changed together in at least `min_shared_commits` commits is reported
+/// with its support and confidence, next to its static edge counts; pairs with no static edge
+/// are flagged as hidden dependencies. Statically coupled pairs below the threshold follow.
+/// Pairs rank by the higher of their two confidences, then by shared commits and edges.
+pub fn temporal_coupling_report(
+    db_path: &Path,
+    limit: u32,
+    scope: CouplingScope,
+    options: TemporalCouplingOptions,
+) -> anyhow::Result<Vec<CouplingEntry>> {
+    let connection = open_connection(db_path)?;
+    let mut entries = static_coupling_entries(&connection, -1)?
+        .into_iter()
+        .map(|entry| ((entry.file_a.clone(), entry.file_b.clone()), entry))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut statement = connection.prepare_cached(
+        "SELECT commit_id, file_path FROM history_file_changes
+         ORDER BY commit_id ASC, file_path ASC",
+    )?;
+    let rows = statement.query_map([], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+    })?;
+    let mut changesets = BTreeMap::<i64, Vec<String>>::new();
+    for row in rows {
+        let (commit_id, file_path) = row?;
+        if include_path_for_scope(&file_path, scope) {
+            changesets.entry(commit_id).or_default().push(file_path);
+        }
+    }
+    let mut analyzed_commits = 0_u32;
+    let mut file_commits = HashMap::<String, u32>::new();
+    let mut shared = BTreeMap::<(String, String), u32>::new();
+    for files in changesets.values() {
+        if files.len() > options.max_changeset_files as usize {
+            continue;
+        }
+        analyzed_commits += 1;
+        for (index, file_a) in files.iter().enumerate() {
+            *file_commits.entry(file_a.clone()).or_default() += 1;
+            for file_b in &files[index + 1..] {
+                *shared.entry((file_a.clone(), file_b.clone())).or_default() += 1;
+            }
+        }
+    }
+
+    for ((file_a, file_b), shared_commits) in shared {
+        if shared_commits < options.min_shared_commits.max(1) {
+            continue;
+        }
+        let commits_a = file_commits.get(&file_a).copied().unwrap_or(shared_commits);
+        let commits_b = file_commits.get(&file_b).copied().unwrap_or(shared_commits);
+        let entry = entries
+            .entry((file_a.clone(), file_b.clone()))
+            .or_insert_with(|| CouplingEntry {
+                file_a,
+                file_b,
+                a_to_b_edges: 0,
+                b_to_a_edges: 0,
+                total_edges: 0,
+                temporal: None,
+            });
+        entry.temporal = Some(TemporalCoupling {
+            shared_commits,
+            commits_a,
+            commits_b,
+            support: ratio(shared_commits, analyzed_commits),
+            confidence_a_to_b: ratio(shared_commits, commits_a),
+            confidence_b_to_a: ratio(shared_commits, commits_b),
+            hidden_dependency: entry.total_edges == 0,
+        });
+    }
+
+    let mut entries = entries
+        .into_values()
+        .filter(|entry| {
+            include_path_for_scope(&entry.file_a, scope)
+                && include_path_for_scope(&entry.file_b, scope)
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by(|left, right| {
+        let confidence = |entry: &CouplingEntry| entry.temporal.as_ref().map(|t| t.confidence());
+        let shared = |entry: &CouplingEntry| entry.temporal.as_ref().map(|t| t.shared_commits);
+        confidence(right)
+            .partial_cmp(&confidence(left))
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(shared(right).cmp(&shared(left)))
+            .then(right.total_edges.cmp(&left.total_edges))
+            .then(left.file_a.cmp(&right.file_a))
+            .then(left.file_b.cmp(&right.file_b))
+    });
+    entries.truncate(limit as usize);
+    Ok(entries)
+}
+
+/// Cross-file edge counts for file pairs, ordered by total edges; a negative `limit` returns
+/// every pair.
+fn static_coupling_entries(
+    connection: &rusqlite::Connection,
+    limit: i64,
+) -> anyhow::Result<Vec<CouplingEntry>> {
+    let mut stmt = connection.prepare_cached(
         "WITH file_edges AS (
             SELECT src.file_path AS from_file, tgt.file_path AS to_file, COUNT(*) AS edge_count
             FROM symbol_edges_v2 e
@@ -174,13 +412,17 @@ pub fn coupling_report(
             a_to_b_edges,
             b_to_a_edges,
             total_edges: a_to_b_edges.saturating_add(b_to_a_edges),
+            temporal: None,
         })
     })?;
-    let mut entries = rows.collect::<Result<Vec<_>, _>>()?;
-    entries.retain(|entry| {
-        include_path_for_scope(&entry.file_a, scope) && include_path_for_scope(&entry.file_b, scope)
-    });
-    Ok(entries)
+    rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+}
+
+fn ratio(part: u32, total: u32) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    (f64::from(part) / f64::from(total) * 1000.0).round() / 1000.0
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -194,15 +436,16 @@ pub struct DeadSymbol {
 }
 
 pub fn dead_symbols(db_path: &Path, aggressive: bool) -> anyhow::Result<Vec<DeadSymbol>> {
-    let connection = Connection::open(db_path)?;
-    let mut stmt = connection.prepare(
+    let connection = open_connection(db_path)?;
+    let mut stmt = connection.prepare_cached(
         "SELECT s.file_path,
                 s.symbol,
                 s.kind,
                 s.start_line,
                 CASE
-                    WHEN s.visibility IN ('public', 'pub', 'export') THEN 1
-                    WHEN s.signature LIKE 'pub %' OR s.signature LIKE 'pub(%' THEN 1
+                    WHEN s.visibility IS NULL
+                        THEN COALESCE(s.signature LIKE 'pub %' OR s.signature LIKE 'pub(%', 0)
+                    WHEN s.visibility IN ('public', 'pub', 'export', 'export default') THEN 1
                     ELSE 0
                 END AS is_public,
                 (SELECT COUNT(*) FROM symbol_edges_v2 e_in WHERE e_in.to_symbol_id = s.symbol_id) AS inbound_refs,
@@ -283,7 +526,7 @@ pub struct TestGapReport {
 }
 
 pub fn test_gap_analysis(db_path: &Path, target: &str) -> anyhow::Result<TestGapReport> {
-    let connection = Connection::open(db_path)?;
+    let connection = open_connection(db_path)?;
     let is_file_target = target.contains('/');
     let file_filter = if is_file_target { Some(target) } else { None };
     let symbol_filter = if is_file_target { None } else { Some(target) };
@@ -295,13 +538,23 @@ pub fn test_gap_analysis(db_path: &Path, target: &str) -> anyhow::Result<TestGap
                FROM symbols_v2 s
                LEFT JOIN text_occurrences t
                       ON t.symbol = s.symbol
-                     AND (t.file_path LIKE 'tests/%' OR t.file_path LIKE '%_test.%')
+                     AND (t.file_path LIKE 'tests/%'
+                          OR t.file_path LIKE '%_test.%'
+                          OR EXISTS (
+                              SELECT 1
+                              FROM symbols_v2 unit
+                              WHERE unit.file_path = t.file_path
+                                AND unit.kind = 'function'
+                                AND unit.test_path IS NOT NULL
+                                AND t.line BETWEEN unit.start_line AND unit.end_line
+                          ))
                WHERE s.kind = 'function'
+                 AND s.test_path IS NULL
                  AND (?1 IS NULL OR s.file_path = ?1)
                  AND (?2 IS NULL OR s.symbol = ?2)
                GROUP BY s.symbol, line_count
                ORDER BY s.symbol ASC";
-    let mut stmt = connection.prepare(sql)?;
+    let mut stmt = connection.prepare_cached(sql)?;
     let rows = stmt.query_map(params![file_filter, symbol_filter], |row| {
         let line_count: u32 = row.get(1)?;
         let risk = if line_count > 80 {
@@ -357,26 +610,12 @@ fn include_path_for_scope(path: &str, scope: CouplingScope) -> bool {
     if !scope.include_fixtures && is_fixture_path(path) {
         return false;
     }
-    if !scope.include_tests && is_test_path(path) {
+    if !scope.include_tests && is_test_like_path(path) {
         return false;
     }
     true
 }
 
-fn is_test_path(file_path: &str) -> bool {
-    let normalized = file_path.replace('\\', "/");
-    let file_name = normalized.rsplit('/').next().unwrap_or(normalized.as_str());
-
-    normalized.starts_with("tests/")
-        || normalized.contains("/tests/")
-        || normalized.ends_with("_test.rs")
-        || normalized.ends_with(".test.ts")
-        || normalized.ends_with(".spec.ts")
-        || normalized.ends_with("_test.py")
-        || normalized.ends_with("_tests.py")
-        || file_name.starts_with("test_")
-}
-
 fn is_fixture_path(file_path: &str) -> bool {
     let normalized = file_path.replace('\\', "/");
     normalized.starts_with("tests/fixtures/") || normalized.contains("/tests/fixtures/")
@@ -390,16 +629,23 @@ pub struct Suggestion {
     pub fan_in: u32,
     pub has_tests: bool,
     pub refactoring_value: f64,
+    pub param_count: Option<u32>,
+    pub nesting_depth: Option<u32>,
+    pub complexity_score: Option<u32>,
 }
 
+/// Functions shorter than this are left out of the default `suggest` ranking.
+const SUGGEST_MIN_LINES: u32 = 10;
+
 pub fn suggest_refactorings(
     db_path: &Path,
     top: u32,
     safe_only: bool,
     min_score: Option<f64>,
+    ranking: &FunctionRanking,
 ) -> anyhow::Result<Vec<Suggestion>> {
-    let connection = Connection::open(db_path)?;
-    let mut stmt = connection.prepare(
+    let connection = open_connection(db_path)?;
+    let mut stmt = connection.prepare_cached(&format!(
         "SELECT s.file_path,
                 s.symbol,
                 COALESCE(s.line_count, s.end_line - s.start_line + 1) AS line_count,
@@ -407,32 +653,50 @@ pub fn suggest_refactorings(
                 CASE
                     WHEN COUNT(DISTINCT tests.file_path) > 0 THEN 1
                     ELSE 0
-                END AS has_tests
+                END AS has_tests,
+                s.param_count,
+                s.nesting_depth,
+                s.complexity_score
          FROM symbols_v2 s
          LEFT JOIN symbol_edges_v2 incoming ON incoming.to_symbol_id = s.symbol_id
          LEFT JOIN text_occurrences tests
                 ON tests.symbol = s.symbol
                AND (tests.file_path LIKE 'tests/%' OR tests.file_path LIKE '%_test.%')
-         WHERE s.kind = 'function'
+         WHERE (s.kind IN ('function', 'method') OR s.complexity_score IS NOT NULL)
+           AND {FUNCTION_THRESHOLD_FILTER}
          GROUP BY s.symbol_id
-         HAVING line_count >= 10
-         ORDER BY line_count DESC, fan_in DESC, s.file_path ASC, s.symbol ASC",
+         HAVING :min_lines IS NULL OR line_count >= :min_lines
+         ORDER BY line_count DESC, fan_in DESC, s.file_path ASC, s.symbol ASC"
+    ))?;
+    // Short functions only drop out of the size-based default; a metric sort or threshold
+    // should still surface a short but deeply nested or branchy function.
+    let min_lines = ranking.is_size_only().then_some(SUGGEST_MIN_LINES);
+    let rows = stmt.query_map(
+        named_params! {
+            ":min_lines": min_lines,
+            ":min_complexity": ranking.min_complexity,
+            ":min_nesting": ranking.min_nesting,
+            ":min_params": ranking.min_params,
+        },
+        |row| {
+            let line_count: u32 = row.get(2)?;
+            let fan_in: u32 = row.get(3)?;
+            let has_tests = row.get::<_, u32>(4)? > 0;
+            let test_penalty = if has_tests { 0.0 } else { 20.0 };
+            let refactoring_value = f64::from(line_count) + f64::from(fan_in) * 5.0 + test_penalty;
+            Ok(Suggestion {
+                file_path: row.get(0)?,
+                symbol: row.get(1)?,
+                line_count,
+                fan_in,
+                has_tests,
+                refactoring_value,
+                param_count: row.get(5)?,
+                nesting_depth: row.get(6)?,
+                complexity_score: row.get(7)?,
+            })
+        },
     )?;
-    let rows = stmt.query_map([], |row| {
-        let line_count: u32 = row.get(2)?;
-        let fan_in: u32 = row.get(3)?;
-        let has_tests = row.get::<_, u32>(4)? > 0;
-        let test_penalty = if has_tests { 0.0 } else { 20.0 };
-        let refactoring_value = f64::from(line_count) + f64::from(fan_in) * 5.0 + test_penalty;
-        Ok(Suggestion {
-            file_path: row.get(0)?,
-            symbol: row.get(1)?,
-            line_count,
-            fan_in,
-            has_tests,
-            refactoring_value,
-        })
-    })?;
 
     let mut suggestions = Vec::new();
     for row in rows {
@@ -448,9 +712,14 @@ pub fn suggest_refactorings(
         suggestions.push(suggestion);
     }
     suggestions.sort_by(|a, b| {
-        b.refactoring_value
-            .partial_cmp(&a.refactoring_value)
-            .unwrap_or(std::cmp::Ordering::Equal)
+        ranking
+            .metric([b.complexity_score, b.nesting_depth, b.param_count])
+            .cmp(&ranking.metric([a.complexity_score, a.nesting_depth, a.param_count]))
+            .then(
+                b.refactoring_value
+                    .partial_cmp(&a.refactoring_value)
+                    .unwrap_or(std::cmp::Ordering::Equal),
+            )
             .then(a.file_path.cmp(&b.file_path))
             .then(a.symbol.cmp(&b.symbol))
     });
@@ -474,12 +743,12 @@ pub struct CycleEdge {
 }
 
 pub fn detect_circular_deps(db_path: &Path, max_length: u32) -> anyhow::Result<CircularReport> {
-    let connection = Connection::open(db_path)?;
+    let connection = open_connection(db_path)?;
 
     // Step 1: Build directed file-level adjacency list
     let mut adj: HashMap<String, Vec<String>> = HashMap::new();
     {
-        let mut stmt = connection.prepare(
+        let mut stmt = connection.prepare_cached(
             "SELECT DISTINCT src_sym.file_path, tgt_sym.file_path
              FROM symbol_edges_v2 e
              JOIN symbols_v2 src_sym ON e.from_symbol_id = src_sym.symbol_id
@@ -519,7 +788,7 @@ pub fn detect_circular_deps(db_path: &Path, max_length: u32) -> anyhow::Result<C
                AND src_sym.file_path != tgt_sym.file_path
              ORDER BY src_sym.file_path ASC, src_sym.symbol ASC, tgt_sym.file_path ASC, tgt_sym.symbol ASC"
         );
-        let mut stmt = connection.prepare(&sql)?;
+        let mut stmt = connection.prepare_cached(&sql)?;
         // Bind the SCC files twice (for both IN clauses)
         let mut edge_params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
         for file in &scc {
@@ -557,13 +826,19 @@ pub fn detect_circular_deps(db_path: &Path, max_length: u32) -> anyhow::Result<C
 }
 
 /// Tarjan's Strongly Connected Components algorithm.
+/// Mutable bookkeeping shared by the recursive steps of Tarjan's algorithm.
+#[derive(Default)]
+struct TarjanState {
+    index_counter: u32,
+    stack: Vec<String>,
+    on_stack: HashMap<String, bool>,
+    index: HashMap<String, u32>,
+    lowlink: HashMap<String, u32>,
+    result: Vec<Vec<String>>,
+}
+
 fn tarjan_scc(adj: &HashMap<String, Vec<String>>) -> anyhow::Result<Vec<Vec<String>>> {
-    let mut index_counter: u32 = 0;
-    let mut stack: Vec<String> = Vec::new();
-    let mut on_stack: HashMap<String, bool> = HashMap::new();
-    let mut index: HashMap<String, u32> = HashMap::new();
-    let mut lowlink: HashMap<String, u32> = HashMap::new();
-    let mut result: Vec<Vec<String>> = Vec::new();
+    let mut state = TarjanState::default();
 
     // Collect all nodes (both sources and targets)
     let mut all_nodes: Vec<String> = adj.keys().cloned().collect();
@@ -580,51 +855,37 @@ fn tarjan_scc(adj: &HashMap<String, Vec<String>>) -> anyhow::Result<Vec<Vec<Stri
     fn strongconnect(
         v: &str,
         adj: &HashMap<String, Vec<String>>,
-        index_counter: &mut u32,
-        stack: &mut Vec<String>,
-        on_stack: &mut HashMap<String, bool>,
-        index: &mut HashMap<String, u32>,
-        lowlink: &mut HashMap<String, u32>,
-        result: &mut Vec<Vec<String>>,
+        state: &mut TarjanState,
     ) -> anyhow::Result<()> {
-        index.insert(v.to_string(), *index_counter);
-        lowlink.insert(v.to_string(), *index_counter);
-        *index_counter += 1;
-        stack.push(v.to_string());
-        on_stack.insert(v.to_string(), true);
+        state.index.insert(v.to_string(), state.index_counter);
+        state.lowlink.insert(v.to_string(), state.index_counter);
+        state.index_counter += 1;
+        state.stack.push(v.to_string());
+        state.on_stack.insert(v.to_string(), true);
 
         if let Some(neighbors) = adj.get(v) {
             for w in neighbors {
-                if !index.contains_key(w.as_str()) {
-                    strongconnect(
-                        w,
-                        adj,
-                        index_counter,
-                        stack,
-                        on_stack,
-                        index,
-                        lowlink,
-                        result,
-                    )?;
-                    let w_low = lowlink[w.as_str()];
-                    let v_low = lowlink[v];
+                if !state.index.contains_key(w.as_str()) {
+                    strongconnect(w, adj, state)?;
+                    let w_low = state.lowlink[w.as_str()];
+                    let v_low = state.lowlink[v];
                     if w_low < v_low {
-                        lowlink.insert(v.to_string(), w_low);
+                        state.lowlink.insert(v.to_string(), w_low);
                     }
-                } else if on_stack.get(w.as_str()).copied().unwrap_or(false) {
-                    let w_idx = index[w.as_str()];
-                    let v_low = lowlink[v];
+                } else if state.on_stack.get(w.as_str()).copied().unwrap_or(false) {
+                    let w_idx = state.index[w.as_str()];
+                    let v_low = state.lowlink[v];
                     if w_idx < v_low {
-                        lowlink.insert(v.to_string(), w_idx);
+                        state.lowlink.insert(v.to_string(), w_idx);
                     }
                 }
             }
         }
 
-        if lowlink[v] == index[v] {
+        if state.lowlink[v] == state.index[v] {
             let mut component = Vec::new();
             loop {
-                let w = match stack.pop() {
+                let w = match state.stack.pop() {
                     Some(w) => w,
                     None => {
                         return Err(anyhow::anyhow!(
@@ -632,31 +893,22 @@ fn tarjan_scc(adj: &HashMap<String, Vec<String>>) -> anyhow::Result<Vec<Vec<Stri
                         ));
                     }
                 };
-                on_stack.insert(w.clone(), false);
+                state.on_stack.insert(w.clone(), false);
                 component.push(w.clone());
                 if w == v {
                     break;
                 }
             }
-            result.push(component);
+            state.result.push(component);
         }
         Ok(())
     }
 
     for node in &all_nodes {
-        if !index.contains_key(node.as_str()) {
-            strongconnect(
-                node,
-                adj,
-                &mut index_counter,
-                &mut stack,
-                &mut on_stack,
-                &mut index,
-                &mut lowlink,
-                &mut result,
-            )?;
+        if !state.index.contains_key(node.as_str()) {
+            strongconnect(node, adj, &mut state)?;
         }
     }
 
-    Ok(result)
+    Ok(state.result)
 }
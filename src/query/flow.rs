@@ -0,0 +1,865 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use serde::Serialize;
+use tree_sitter::{Language, Node, Parser};
+
+/// A variable declared before the extracted range and used inside it.
+#[derive(Debug, Clone, Serialize)]
+pub struct FlowParameter {
+    pub name: String,
+    pub declared_line: u32,
+    /// Assigned or mutated inside the range, so it must be passed mutably.
+    pub mutated: bool,
+}
+
+/// A variable defined inside the extracted range.
+#[derive(Debug, Clone, Serialize)]
+pub struct FlowVariable {
+    pub name: String,
+    /// Last assignment inside the range for return values, first one for locals.
+    pub line: u32,
+}
+
+/// A `return`, `?`, `break`, or `continue` inside the range whose target lies outside it.
+#[derive(Debug, Clone, Serialize)]
+pub struct EarlyExit {
+    pub kind: String,
+    pub line: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RangeCall {
+    pub name: String,
+    pub line: u32,
+}
+
+/// Data flow across the boundary of a line range inside one function.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FlowAnalysis {
+    pub parameters: Vec<FlowParameter>,
+    pub return_values: Vec<FlowVariable>,
+    pub locals: Vec<FlowVariable>,
+    pub early_exits: Vec<EarlyExit>,
+    pub calls: Vec<RangeCall>,
+    /// Whether the range covers whole statements of a single block.
+    pub statement_aligned: bool,
+    /// First statement the range cuts through, as `(start_line, end_line)`.
+    #[serde(skip)]
+    pub split_statement: Option<(u32, u32)>,
+}
+
+/// Returns the tree-sitter grammar for a file path, or `None` for unsupported extensions.
+pub fn language_for_path(file_path: &str) -> Option<Language> {
+    let extension = file_path.rsplit_once('.')?.1;
+    let language = match extension {
+        "rs" => tree_sitter_rust::LANGUAGE.into(),
+        "go" => tree_sitter_go::LANGUAGE.into(),
+        "py" => tree_sitter_python::LANGUAGE.into(),
+        "ts" => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+        "tsx" => tree_sitter_typescript::LANGUAGE_TSX.into(),
+        "js" | "jsx" | "mjs" | "cjs" => tree_sitter_javascript::LANGUAGE.into(),
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// Analyzes variable flow for lines `start..=end` of the function spanning
+/// `function_start..=function_end` in `source`.
+///
+/// Variables are tracked by name across the whole function, in source order: shadowing within
+/// the range is honored, but control flow (branches, loop back-edges) is not.
+pub fn analyze_range(
+    file_path: &str,
+    source: &str,
+    function_lines: (u32, u32),
+    range: (u32, u32),
+) -> anyhow::Result<Option<FlowAnalysis>> {
+    let Some(language) = language_for_path(file_path) else {
+        return Ok(None);
+    };
+    let mut parser = Parser::new();
+    parser
+        .set_language(&language)
+        .context("failed to load tree-sitter language for flow analysis")?;
+    let tree = parser
+        .parse(source, None)
+        .with_context(|| format!("failed to parse {file_path}"))?;
+    let Some(function) = enclosing_function(tree.root_node(), function_lines, range) else {
+        return Ok(None);
+    };
+
+    let mut walker = FlowWalker {
+        source,
+        range,
+        rust: file_path.ends_with(".rs"),
+        events: Vec::new(),
+        early_exits: Vec::new(),
+        calls: Vec::new(),
+    };
+    for field in ["receiver", "parameters", "parameter"] {
+        if let Some(parameters) = function.child_by_field_name(field) {
+            walker.bind(parameters, Role::Decl, None);
+        }
+    }
+    if let Some(body) = function.child_by_field_name("body") {
+        walker.walk(body, false);
+    }
+
+    let mut analysis = walker.finish();
+    analysis.split_statement = split_statement(function, range);
+    analysis.statement_aligned = analysis.split_statement.is_none();
+    Ok(Some(analysis))
+}
+
+const FUNCTION_KINDS: &[&str] = &[
+    "function_item",
+    "function_declaration",
+    "method_declaration",
+    "function_definition",
+    "method_definition",
+    "function_expression",
+    "function",
+    "arrow_function",
+    "generator_function_declaration",
+];
+
+/// Closures and nested functions: exits inside them never leave the analyzed function.
+const NESTED_FUNCTION_KINDS: &[&str] = &[
+    "closure_expression",
+    "func_literal",
+    "lambda",
+    "function_definition",
+    "function_declaration",
+    "function_expression",
+    "function",
+    "arrow_function",
+    "method_definition",
+    "generator_function_declaration",
+];
+
+/// Nested items that cannot capture the function's variables.
+const SKIPPED_KINDS: &[&str] = &[
+    "function_item",
+    "impl_item",
+    "struct_item",
+    "enum_item",
+    "trait_item",
+    "mod_item",
+    "use_declaration",
+    "const_item",
+    "static_item",
+    "class_definition",
+    "class_declaration",
+    "label",
+    "scoped_identifier",
+    "scoped_type_identifier",
+    "type_annotation",
+    "type_arguments",
+    "type_parameters",
+    "line_comment",
+    "block_comment",
+    "comment",
+];
+
+const SKIPPED_FIELDS: &[&str] = &["type", "return_type", "result", "type_parameters", "label"];
+
+const LOOP_KINDS: &[&str] = &[
+    "loop_expression",
+    "while_expression",
+    "for_expression",
+    "for_statement",
+    "for_in_statement",
+    "while_statement",
+    "do_statement",
+];
+
+/// Constructs an unlabeled `break` also leaves in Go and TypeScript.
+const SWITCH_KINDS: &[&str] = &[
+    "expression_switch_statement",
+    "type_switch_statement",
+    "select_statement",
+    "switch_statement",
+];
+
+const BLOCK_KINDS: &[&str] = &["block", "statement_list", "statement_block"];
+
+fn enclosing_function(
+    root: Node<'_>,
+    function_lines: (u32, u32),
+    range: (u32, u32),
+) -> Option<Node<'_>> {
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        let (start, end) = node_lines(node);
+        if end < range.1 || start > range.0 {
+            continue;
+        }
+        if FUNCTION_KINDS.contains(&node.kind())
+            && start >= function_lines.0
+            && end <= function_lines.1
+        {
+            return Some(node);
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+    None
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    /// Introduces a new binding (`let`, `:=`, parameters, loop variables).
+    Decl,
+    /// Replaces the value of a binding (`x = ...`).
+    Assign,
+    /// Reads and updates a binding (`x += 1`, `x.field = ...`, `x++`).
+    Mutate,
+    Read,
+}
+
+impl Role {
+    fn defines(self) -> bool {
+        matches!(self, Self::Decl | Self::Assign)
+    }
+
+    fn uses(self) -> bool {
+        matches!(self, Self::Read | Self::Mutate)
+    }
+}
+
+struct Event {
+    name: String,
+    role: Role,
+    /// Byte offset the event takes effect at; bindings take effect after their initializer.
+    position: usize,
+    line: u32,
+}
+
+struct FlowWalker<'a> {
+    source: &'a str,
+    range: (u32, u32),
+    /// Rust patterns treat bare uppercase identifiers as enum variants and constants.
+    rust: bool,
+    events: Vec<Event>,
+    early_exits: Vec<EarlyExit>,
+    calls: Vec<RangeCall>,
+}
+
+impl FlowWalker<'_> {
+    fn text(&self, node: Node<'_>) -> &str {
+        &self.source[node.byte_range()]
+    }
+
+    fn in_range(&self, line: u32) -> bool {
+        line >= self.range.0 && line <= self.range.1
+    }
+
+    fn record(&mut self, node: Node<'_>, role: Role, effective: Option<Node<'_>>) {
+        self.events.push(Event {
+            name: self.text(node).to_string(),
+            role,
+            position: effective.map_or(node.start_byte(), |effective| effective.end_byte()),
+            line: node_lines(node).0,
+        });
+    }
+
+    /// Records every identifier bound by a pattern, reading any default values it contains.
+    fn bind(&mut self, node: Node<'_>, role: Role, effective: Option<Node<'_>>) {
+        let kind = node.kind();
+        if SKIPPED_KINDS.contains(&kind) {
+            return;
+        }
+        if is_identifier(kind) || kind == "shorthand_field_identifier" {
+            let constant = self.rust && self.text(node).starts_with(char::is_uppercase);
+            if !constant {
+                self.record(node, role, effective);
+            }
+            return;
+        }
+        let mut cursor = node.walk();
+        for (index, child) in node.children(&mut cursor).enumerate() {
+            if !child.is_named() {
+                continue;
+            }
+            match node.field_name_for_child(index as u32) {
+                Some(field) if SKIPPED_FIELDS.contains(&field) => {}
+                Some("value" | "right" | "default" | "index") => self.walk(child, false),
+                _ => self.bind(child, role, effective),
+            }
+        }
+    }
+
+    /// Records the root variable of an assignment target, and reads everything else in it.
+    fn assign_target(&mut self, target: Node<'_>, role: Role, effective: Node<'_>) {
+        let kind = target.kind();
+        if is_identifier(kind) {
+            self.record(target, role, Some(effective));
+            return;
+        }
+        if matches!(
+            kind,
+            "pattern_list"
+                | "tuple_pattern"
+                | "list_pattern"
+                | "expression_list"
+                | "tuple_expression"
+                | "array_pattern"
+                | "object_pattern"
+                | "parenthesized_expression"
+        ) {
+            let mut cursor = target.walk();
+            for child in target.named_children(&mut cursor) {
+                self.assign_target(child, role, effective);
+            }
+            return;
+        }
+        // `x.field = ...`, `x[i] = ...`, `*x = ...`: the value behind `x` changes in place.
+        let mut root = target;
+        while !is_identifier(root.kind()) {
+            let next = ["object", "value", "operand", "argument"]
+                .iter()
+                .find_map(|field| root.child_by_field_name(field))
+                .or_else(|| root.named_child(0));
+            match next {
+                Some(next) => root = next,
+                None => break,
+            }
+        }
+        if is_identifier(root.kind()) {
+            self.record(root, Role::Mutate, None);
+        }
+        self.walk_except(target, root);
+    }
+
+    fn walk_except(&mut self, node: Node<'_>, skipped: Node<'_>) {
+        if node.id() == skipped.id() {
+            return;
+        }
+        if node.child_count() == 0 || is_identifier(node.kind()) {
+            self.walk(node, false);
+            return;
+        }
+        let mut cursor = node.walk();
+        for (index, child) in node.children(&mut cursor).enumerate() {
+            if !child.is_named() || is_skipped_child(node, index) {
+                continue;
+            }
+            self.walk_except(child, skipped);
+        }
+    }
+
+    fn walk(&mut self, node: Node<'_>, nested: bool) {
+        let kind = node.kind();
+        if SKIPPED_KINDS.contains(&kind) {
+            return;
+        }
+        if is_identifier(kind) {
+            self.record(node, Role::Read, None);
+            return;
+        }
+        let line = node_lines(node).0;
+        if !nested && self.in_range(line) {
+            self.note_exit(node);
+        }
+        if self.in_range(line)
+            && let Some(name) = call_name(node, self.source)
+        {
+            self.calls.push(RangeCall { name, line });
+        }
+        let nested = nested || NESTED_FUNCTION_KINDS.contains(&kind);
+
+        match kind {
+            "let_declaration"
+            | "short_var_declaration"
+            | "var_spec"
+            | "const_spec"
+            | "variable_declarator" => {
+                for field in ["value", "right", "alternative"] {
+                    if let Some(value) = node.child_by_field_name(field) {
+                        self.walk(value, nested);
+                    }
+                }
+                for field in ["pattern", "left", "name"] {
+                    let mut cursor = node.walk();
+                    for target in node.children_by_field_name(field, &mut cursor) {
+                        self.bind(target, Role::Decl, Some(node));
+                    }
+                }
+                return;
+            }
+            "let_condition" => {
+                if let Some(value) = node.child_by_field_name("value") {
+                    self.walk(value, nested);
+                }
+                if let Some(pattern) = node.child_by_field_name("pattern") {
+                    self.bind(pattern, Role::Decl, Some(node));
+                }
+                return;
+            }
+            "match_arm" => {
+                if let Some(pattern) = node.child_by_field_name("pattern") {
+                    self.bind(pattern, Role::Decl, Some(pattern));
+                }
+                if let Some(value) = node.child_by_field_name("value") {
+                    self.walk(value, nested);
+                }
+                return;
+            }
+            "for_expression" | "for_statement" | "for_in_statement" | "for_in_clause"
+            | "range_clause" => {
+                if let Some(value) = node
+                    .child_by_field_name("value")
+                    .or_else(|| node.child_by_field_name("right"))
+                {
+                    self.walk(value, nested);
+                    let role = if is_declaring_loop(node) {
+                        Role::Decl
+                    } else {
+                        Role::Assign
+                    };
+                    if let Some(pattern) = node
+                        .child_by_field_name("pattern")
+                        .or_else(|| node.child_by_field_name("left"))
+                    {
+                        self.bind(pattern, role, Some(value));
+                    }
+                    let mut cursor = node.walk();
+                    for (index, child) in node.children(&mut cursor).enumerate() {
+                        let field = node.field_name_for_child(index as u32);
+                        if child.is_named()
+                            && !matches!(field, Some("value" | "right" | "pattern" | "left"))
+                        {
+                            self.walk(child, nested);
+                        }
+                    }
+                    return;
+                }
+            }
+            "assignment_expression" | "assignment" | "assignment_statement" => {
+                if let Some(value) = node.child_by_field_name("right") {
+                    self.walk(value, nested);
+                }
+                let compound = node
+                    .child_by_field_name("operator")
+                    .is_some_and(|operator| self.text(operator) != "=");
+                let role = if compound { Role::Mutate } else { Role::Assign };
+                if let Some(target) = node.child_by_field_name("left") {
+                    self.assign_target(target, role, node);
+                }
+                return;
+            }
+            "compound_assignment_expr"
+            | "augmented_assignment"
+            | "augmented_assignment_expression" => {
+                if let Some(value) = node.child_by_field_name("right") {
+                    self.walk(value, nested);
+                }
+                if let Some(target) = node.child_by_field_name("left") {
+                    self.assign_target(target, Role::Mutate, node);
+                }
+                return;
+            }
+            "update_expression" | "inc_statement" | "dec_statement" => {
+                if let Some(target) = node
+                    .child_by_field_name("argument")
+                    .or_else(|| node.named_child(0))
+                {
+                    self.assign_target(target, Role::Mutate, node);
+                }
+                return;
+            }
+            "named_expression" => {
+                if let Some(value) = node.child_by_field_name("value") {
+                    self.walk(value, nested);
+                }
+                if let Some(name) = node.child_by_field_name("name") {
+                    self.bind(name, Role::Assign, Some(node));
+                }
+                return;
+            }
+            "as_pattern_target" => {
+                self.bind(node, Role::Assign, Some(node));
+                return;
+            }
+            "closure_expression"
+            | "func_literal"
+            | "lambda"
+            | "arrow_function"
+            | "function_expression"
+            | "function"
+            | "function_definition"
+            | "function_declaration"
+            | "method_definition"
+            | "generator_function_declaration"
+            | "catch_clause" => {
+                for field in ["parameters", "parameter"] {
+                    if let Some(parameters) = node.child_by_field_name(field) {
+                        self.bind(parameters, Role::Decl, None);
+                    }
+                }
+                if let Some(body) = node.child_by_field_name("body") {
+                    self.walk(body, nested);
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        let mut cursor = node.walk();
+        for (index, child) in node.children(&mut cursor).enumerate() {
+            if child.is_named() && !is_skipped_child(node, index) {
+                self.walk(child, nested);
+            }
+        }
+    }
+
+    fn note_exit(&mut self, node: Node<'_>) {
+        let kind = match node.kind() {
+            "return_expression" | "return_statement" => "return",
+            "try_expression" => "?",
+            "break_expression" | "break_statement" => "break",
+            "continue_expression" | "continue_statement" => "continue",
+            _ => return,
+        };
+        let crosses = match kind {
+            "return" | "?" => true,
+            _ => match loop_target(node, kind == "break", self.source) {
+                Some(target) => {
+                    let (start, end) = node_lines(target);
+                    start < self.range.0 || end > self.range.1
+                }
+                None => true,
+            },
+        };
+        if crosses {
+            self.early_exits.push(EarlyExit {
+                kind: kind.to_string(),
+                line: node_lines(node).0,
+            });
+        }
+    }
+
+    fn finish(self) -> FlowAnalysis {
+        let (start, end) = self.range;
+        let mut by_name = BTreeMap::<&str, Vec<&Event>>::new();
+        for event in &self.events {
+            by_name.entry(event.name.as_str()).or_default().push(event);
+        }
+
+        let mut analysis = FlowAnalysis::default();
+        for (name, mut events) in by_name {
+            // Only names the function binds are variables; the rest are functions and globals.
+            if !events.iter().any(|event| event.role.defines()) {
+                continue;
+            }
+            events.sort_by_key(|event| event.position);
+            let before = events.iter().filter(|event| event.line < start);
+            let inside = events
+                .iter()
+                .filter(|event| event.line >= start && event.line <= end)
+                .collect::<Vec<_>>();
+            let first_after = events.iter().find(|event| event.line > end);
+            if inside.is_empty() {
+                continue;
+            }
+
+            let declared_before = before
+                .filter(|event| event.role != Role::Read)
+                .map(|event| event.line)
+                .next();
+            let writes_inside = inside
+                .iter()
+                .filter(|event| event.role != Role::Read)
+                .collect::<Vec<_>>();
+            let is_parameter = declared_before.is_some() && inside[0].role.uses();
+            let is_return =
+                !writes_inside.is_empty() && first_after.is_some_and(|event| event.role.uses());
+
+            if let (true, Some(declared_line)) = (is_parameter, declared_before) {
+                analysis.parameters.push(FlowParameter {
+                    name: name.to_string(),
+                    declared_line,
+                    mutated: !writes_inside.is_empty(),
+                });
+            }
+            if is_return {
+                analysis.return_values.push(FlowVariable {
+                    name: name.to_string(),
+                    line: writes_inside.last().map_or(start, |event| event.line),
+                });
+            } else if !is_parameter
+                && let Some(first_definition) = inside.iter().find(|event| event.role.defines())
+            {
+                analysis.locals.push(FlowVariable {
+                    name: name.to_string(),
+                    line: first_definition.line,
+                });
+            }
+        }
+        analysis.parameters.sort_by(|left, right| {
+            (left.declared_line, &left.name).cmp(&(right.declared_line, &right.name))
+        });
+        for variables in [&mut analysis.return_values, &mut analysis.locals] {
+            variables
+                .sort_by(|left, right| (left.line, &left.name).cmp(&(right.line, &right.name)));
+        }
+        analysis.early_exits = self.early_exits;
+        analysis.calls = self.calls;
+        analysis
+    }
+}
+
+fn is_identifier(kind: &str) -> bool {
+    matches!(
+        kind,
+        "identifier"
+            | "self"
+            | "shorthand_property_identifier"
+            | "shorthand_property_identifier_pattern"
+    )
+}
+
+/// Children that name members rather than variables: `obj.attr` and `f(name=...)` in Python.
+fn is_skipped_child(node: Node<'_>, index: usize) -> bool {
+    match node.field_name_for_child(index as u32) {
+        Some(field) if SKIPPED_FIELDS.contains(&field) => true,
+        Some("attribute") => node.kind() == "attribute",
+        Some("name") => node.kind() == "keyword_argument",
+        _ => false,
+    }
+}
+
+/// Whether a loop introduces its variables (`for x in`, `for (const x of`, `for k, v :=`).
+fn is_declaring_loop(node: Node<'_>) -> bool {
+    match node.kind() {
+        "for_in_statement" => node.child_by_field_name("kind").is_some(),
+        "range_clause" => {
+            let mut cursor = node.walk();
+            node.children(&mut cursor).any(|child| child.kind() == ":=")
+        }
+        "for_statement" => false,
+        _ => true,
+    }
+}
+
+/// The loop (or switch, for an unlabeled `break`) a `break`/`continue` jumps out of.
+fn loop_target<'tree>(node: Node<'tree>, is_break: bool, source: &str) -> Option<Node<'tree>> {
+    let label = node
+        .child_by_field_name("label")
+        .or_else(|| {
+            let mut cursor = node.walk();
+            node.named_children(&mut cursor).find(|child| {
+                matches!(
+                    child.kind(),
+                    "label" | "label_name" | "statement_identifier"
+                )
+            })
+        })
+        .map(|label| {
+            source[label.byte_range()]
+                .trim_start_matches('\'')
+                .to_string()
+        });
+    let mut current = node.parent();
+    while let Some(ancestor) = current {
+        let kind = ancestor.kind();
+        if NESTED_FUNCTION_KINDS.contains(&kind) || FUNCTION_KINDS.contains(&kind) {
+            return None;
+        }
+        match &label {
+            Some(label) => {
+                let ancestor_label = ancestor
+                    .child_by_field_name("label")
+                    .or_else(|| {
+                        let mut cursor = ancestor.walk();
+                        ancestor
+                            .named_children(&mut cursor)
+                            .find(|child| child.kind() == "label")
+                    })
+                    .map(|found| source[found.byte_range()].trim_start_matches('\''));
+                if ancestor_label == Some(label.as_str()) {
+                    return Some(ancestor);
+                }
+            }
+            None => {
+                if LOOP_KINDS.contains(&kind) || (is_break && SWITCH_KINDS.contains(&kind)) {
+                    return Some(ancestor);
+                }
+            }
+        }
+        current = ancestor.parent();
+    }
+    None
+}
+
+fn call_name(node: Node<'_>, source: &str) -> Option<String> {
+    let callee = match node.kind() {
+        "call_expression" | "call" => node.child_by_field_name("function")?,
+        "new_expression" => node.child_by_field_name("constructor")?,
+        "macro_invocation" => {
+            let name = node.child_by_field_name("macro")?;
+            return Some(format!("{}!", &source[name.byte_range()]));
+        }
+        _ => return None,
+    };
+    callee_name(callee, source)
+}
+
+/// Names a callee by its identifier path (`check`, `seen.max`, `fmt.Sprint`). Calls on any other
+/// receiver, such as `"too big".into()` or `items.iter().map(..)`, are named by the method alone.
+fn callee_name(callee: Node<'_>, source: &str) -> Option<String> {
+    let text = &source[callee.byte_range()];
+    if is_identifier_path(text) {
+        return Some(text.to_string());
+    }
+    let member = match callee.kind() {
+        "generic_function" => return callee_name(callee.child_by_field_name("function")?, source),
+        "field_expression" | "selector_expression" => callee.child_by_field_name("field")?,
+        "member_expression" => callee.child_by_field_name("property")?,
+        "attribute" => callee.child_by_field_name("attribute")?,
+        "scoped_identifier" => callee.child_by_field_name("name")?,
+        _ => return None,
+    };
+    let name = &source[member.byte_range()];
+    is_identifier_path(name).then(|| name.to_string())
+}
+
+fn is_identifier_path(text: &str) -> bool {
+    text.split("::")
+        .flat_map(|part| part.split('.'))
+        .all(|segment| {
+            segment
+                .chars()
+                .next()
+                .is_some_and(|first| first.is_alphabetic() || first == '_' || first == '$')
+                && segment
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+        })
+}
+
+/// Finds the first statement the range cuts through, if any.
+///
+/// The range must fall within the statements of one block and cover each of them whole.
+fn split_statement(function: Node<'_>, range: (u32, u32)) -> Option<(u32, u32)> {
+    let mut innermost: Option<Node<'_>> = None;
+    let mut stack = vec![function];
+    while let Some(node) = stack.pop() {
+        let (start, end) = node_lines(node);
+        if end < range.0 || start > range.1 {
+            continue;
+        }
+        if BLOCK_KINDS.contains(&node.kind()) {
+            let statements = block_statements(node);
+            let covers = statements
+                .first()
+                .is_some_and(|first| node_lines(*first).0 <= range.0)
+                && statements
+                    .last()
+                    .is_some_and(|last| node_lines(*last).1 >= range.1);
+            if covers
+                && innermost
+                    .is_none_or(|current| node.byte_range().len() < current.byte_range().len())
+            {
+                innermost = Some(node);
+            }
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+
+    let Some(block) = innermost else {
+        return Some(node_lines(function));
+    };
+    block_statements(block)
+        .into_iter()
+        .map(node_lines)
+        .find(|&(start, end)| {
+            start <= range.1 && end >= range.0 && (start < range.0 || end > range.1)
+        })
+}
+
+fn block_statements(block: Node<'_>) -> Vec<Node<'_>> {
+    let mut cursor = block.walk();
+    block
+        .named_children(&mut cursor)
+        .filter(|child| !child.kind().contains("comment"))
+        .collect()
+}
+
+fn node_lines(node: Node<'_>) -> (u32, u32) {
+    let start = node.start_position().row as u32 + 1;
+    let mut end = node.end_position().row as u32 + 1;
+    // A node ending at column 0 stops before that line (Python blocks include the newline).
+    if node.end_position().column == 0 && end > start {
+        end -= 1;
+    }
+    (start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FlowAnalysis, analyze_range};
+
+    fn analyze(file_path: &str, source: &str, range: (u32, u32)) -> FlowAnalysis {
+        let lines = source.lines().count() as u32;
+        analyze_range(file_path, source, (1, lines), range)
+            .expect("analysis should run")
+            .expect("function should be found")
+    }
+
+    fn names<T>(items: &[T], name: impl Fn(&T) -> &str) -> Vec<&str> {
+        items.iter().map(name).collect()
+    }
+
+    #[test]
+    fn receivers_are_parameters_and_shadowing_starts_a_local() {
+        let source = "fn step(&mut self, x: u32) -> u32 {\n    let x = x + self.base;\n    \
+                      self.count += x;\n    x\n}\n";
+        let flow = analyze("src/lib.rs", source, (2, 3));
+        assert_eq!(names(&flow.parameters, |p| &p.name), ["self", "x"]);
+        assert!(flow.parameters[0].mutated);
+        // The shadowing `let` is read after the range, so the new `x` flows out.
+        assert_eq!(names(&flow.return_values, |v| &v.name), ["x"]);
+        assert!(flow.statement_aligned);
+    }
+
+    #[test]
+    fn exits_inside_closures_and_inner_loops_stay_in_range() {
+        let source = "fn run(items: Vec<u32>) -> Option<u32> {\n    'outer: for item in items {\n        \
+                      let parsed = items.iter().map(|i| { return i + 1; });\n        \
+                      for _ in 0..item { break; }\n        if item > 3 { continue 'outer; }\n    \
+                      }\n    None\n}\n";
+        let flow = analyze("src/lib.rs", source, (3, 5));
+        assert_eq!(flow.early_exits.len(), 1, "{:?}", flow.early_exits);
+        assert_eq!(flow.early_exits[0].kind, "continue");
+        assert_eq!(flow.early_exits[0].line, 5);
+    }
+
+    #[test]
+    fn calls_on_expressions_are_named_by_their_method() {
+        let source = "fn check(items: Vec<u32>) -> Result<u32, String> {\n    \
+                      let total = items.iter().map(|i| i + 1).sum::<u32>();\n    \
+                      if total > 9 { return Err(\"too big\".into()); }\n    \
+                      Ok(total.min(self::LIMIT))\n}\n";
+        let flow = analyze("src/lib.rs", source, (2, 4));
+        assert_eq!(
+            names(&flow.calls, |c| &c.name),
+            ["sum", "map", "items.iter", "Err", "into", "Ok", "total.min"]
+        );
+
+        let source = "def report(rows):\n    text = \", \".join(rows)\n    return text.strip()\n";
+        let flow = analyze("report.py", source, (2, 3));
+        assert_eq!(names(&flow.calls, |c| &c.name), ["join", "text.strip"]);
+    }
+
+    #[test]
+    fn unsupported_files_have_no_flow() {
+        assert!(
+            analyze_range("notes.txt", "text", (1, 1), (1, 1))
+                .expect("analysis should run")
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,281 @@
+use std::path::Path;
+
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+
+use crate::store::open_connection;
+
+/// A function or method ranked by how often it changed times how complex it is.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChurnHotspot {
+    pub symbol: String,
+    pub kind: String,
+    pub file_path: String,
+    pub start_line: u32,
+    pub end_line: u32,
+    /// Commits whose changes touched the symbol's lines.
+    pub commits: u32,
+    pub authors: u32,
+    pub complexity: u32,
+    /// `commits * complexity`.
+    pub churn_score: u32,
+    pub last_touched: String,
+}
+
+/// Who changed a file or a symbol's line range, and how often.
+#[derive(Debug, Clone, Serialize)]
+pub struct OwnersReport {
+    pub target: String,
+    pub file_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<u32>,
+    pub commits: u32,
+    pub last_touched: Option<String>,
+    pub owners: Vec<Owner>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Owner {
+    pub author: String,
+    pub email: String,
+    pub commits: u32,
+    /// Fraction of the target's commits made by this author.
+    pub share: f64,
+    /// Lines added plus deleted; reported for files only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines_changed: Option<u32>,
+    pub last_touched: String,
+}
+
+/// Ranks functions and methods by commits touching their lines times their complexity score.
+///
+/// Commit counts come from the git history recorded by `index`; symbols never changed in the
+/// recorded history, or without complexity metrics, are left out.
+pub fn churn_hotspots(db_path: &Path, limit: u32) -> anyhow::Result<Vec<ChurnHotspot>> {
+    let connection = open_connection(db_path)?;
+    let mut statement = connection.prepare_cached(
+        "SELECT s.symbol, s.kind, s.file_path, s.start_line, s.end_line, s.complexity_score,
+                COUNT(DISTINCT h.commit_id), COUNT(DISTINCT c.email), MAX(c.authored_at)
+         FROM symbols_v2 s
+         JOIN history_line_changes h
+           ON h.file_path = s.file_path
+          AND h.start_line <= s.end_line
+          AND h.end_line >= s.start_line
+         JOIN history_commits c ON c.commit_id = h.commit_id
+         WHERE s.complexity_score IS NOT NULL
+         GROUP BY s.symbol_id",
+    )?;
+    let rows = statement.query_map([], |row| {
+        let commits = row.get::<_, u32>(6)?;
+        let complexity = row.get::<_, u32>(5)?;
+        Ok(ChurnHotspot {
+            symbol: row.get(0)?,
+            kind: row.get(1)?,
+            file_path: row.get(2)?,
+            start_line: row.get(3)?,
+            end_line: row.get(4)?,
+            commits,
+            authors: row.get(7)?,
+            complexity,
+            churn_score: commits.saturating_mul(complexity),
+            last_touched: iso_date(row.get(8)?),
+        })
+    })?;
+    let mut hotspots = rows.collect::<Result<Vec<_>, _>>()?;
+    hotspots.sort_by(|left, right| {
+        right
+            .churn_score
+            .cmp(&left.churn_score)
+            .then(right.commits.cmp(&left.commits))
+            .then(left.file_path.cmp(&right.file_path))
+            .then(left.start_line.cmp(&right.start_line))
+            .then(left.symbol.cmp(&right.symbol))
+    });
+    hotspots.truncate(limit as usize);
+    Ok(hotspots)
+}
+
+/// Lists the top `limit` authors of `target`, an indexed file path or a symbol name.
+///
+/// A file counts every recorded commit that changed it, following renames. A symbol counts the
+/// commits whose changes touched its current line range, with one report per definition.
+/// Authors are ranked by commits, then by most recent change.
+pub fn owners(db_path: &Path, target: &str, limit: u32) -> anyhow::Result<Vec<OwnersReport>> {
+    let connection = open_connection(db_path)?;
+    let file_path = target.trim_start_matches("./");
+    let is_file = connection
+        .prepare_cached("SELECT 1 FROM indexed_files WHERE file_path = ?1")?
+        .query_row(params![file_path], |_| Ok(()))
+        .optional()?
+        .is_some();
+    if is_file {
+        let owners = ranked_owners(
+            &connection,
+            "SELECT c.author, c.email, COUNT(*), SUM(f.lines_added + f.lines_deleted),
+                    MAX(c.authored_at)
+             FROM history_file_changes f
+             JOIN history_commits c ON c.commit_id = f.commit_id
+             WHERE f.file_path = ?1
+             GROUP BY c.email",
+            params![file_path],
+        )?;
+        let report = owners_report(target, file_path, None, owners, limit);
+        return Ok(vec![report]);
+    }
+
+    let mut statement = connection.prepare_cached(
+        "SELECT file_path, symbol, start_line, end_line
+         FROM symbols_v2
+         WHERE symbol = ?1 OR qualified_symbol = ?1
+         ORDER BY file_path ASC, start_line ASC, start_column ASC",
+    )?;
+    let definitions = statement
+        .query_map(params![target], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, u32>(2)?,
+                row.get::<_, u32>(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut reports = Vec::new();
+    for (file_path, symbol, start_line, end_line) in definitions {
+        let owners = ranked_owners(
+            &connection,
+            "SELECT c.author, c.email, COUNT(DISTINCT c.commit_id), NULL, MAX(c.authored_at)
+             FROM history_line_changes h
+             JOIN history_commits c ON c.commit_id = h.commit_id
+             WHERE h.file_path = ?1 AND h.start_line <= ?3 AND h.end_line >= ?2
+             GROUP BY c.email",
+            params![file_path, start_line, end_line],
+        )?;
+        reports.push(owners_report(
+            target,
+            &file_path,
+            Some((symbol, start_line, end_line)),
+            owners,
+            limit,
+        ));
+    }
+    Ok(reports)
+}
+
+/// Runs an owners query returning `(author, email, commits, lines, last authored_at)` rows and
+/// ranks them. The `commits` and `last_touched` totals are filled in by [`owners_report`].
+fn ranked_owners(
+    connection: &Connection,
+    sql: &str,
+    params: impl rusqlite::Params,
+) -> anyhow::Result<Vec<(Owner, i64)>> {
+    let mut statement = connection.prepare_cached(sql)?;
+    let rows = statement.query_map(params, |row| {
+        let authored_at = row.get::<_, i64>(4)?;
+        Ok((
+            Owner {
+                author: row.get(0)?,
+                email: row.get(1)?,
+                commits: row.get(2)?,
+                share: 0.0,
+                lines_changed: row.get(3)?,
+                last_touched: iso_date(authored_at),
+            },
+            authored_at,
+        ))
+    })?;
+    let mut owners = rows.collect::<Result<Vec<_>, _>>()?;
+    owners.sort_by(|(left, left_at), (right, right_at)| {
+        right
+            .commits
+            .cmp(&left.commits)
+            .then(right_at.cmp(left_at))
+            .then(left.author.cmp(&right.author))
+            .then(left.email.cmp(&right.email))
+    });
+    Ok(owners)
+}
+
+fn owners_report(
+    target: &str,
+    file_path: &str,
+    symbol: Option<(String, u32, u32)>,
+    owners: Vec<(Owner, i64)>,
+    limit: u32,
+) -> OwnersReport {
+    // Authors are grouped per commit, so their commit counts add up to the target's total.
+    let commits = owners.iter().map(|(owner, _)| owner.commits).sum::<u32>();
+    let last_touched = owners.iter().map(|(_, at)| *at).max().map(iso_date);
+    let (symbol, start_line, end_line) = match symbol {
+        Some((symbol, start_line, end_line)) => (Some(symbol), Some(start_line), Some(end_line)),
+        None => (None, None, None),
+    };
+    OwnersReport {
+        target: target.to_string(),
+        file_path: file_path.to_string(),
+        symbol,
+        start_line,
+        end_line,
+        commits,
+        last_touched,
+        owners: owners
+            .into_iter()
+            .take(limit as usize)
+            .map(|(mut owner, _)| {
+                owner.share = share(owner.commits, commits);
+                owner
+            })
+            .collect(),
+    }
+}
+
+fn share(part: u32, total: u32) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    (f64::from(part) / f64::from(total) * 1000.0).round() / 1000.0
+}
+
+/// Formats seconds since the Unix epoch as a UTC `YYYY-MM-DD` date.
+pub fn iso_date(unix_seconds: i64) -> String {
+    // Howard Hinnant's days-to-civil conversion.
+    let days = unix_seconds.div_euclid(86_400);
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{iso_date, share};
+
+    #[test]
+    fn iso_date_formats_utc_days() {
+        assert_eq!(iso_date(0), "1970-01-01");
+        assert_eq!(iso_date(951_782_400), "2000-02-29");
+        assert_eq!(iso_date(1_704_067_199), "2023-12-31");
+        assert_eq!(iso_date(-86_400), "1969-12-31");
+    }
+
+    #[test]
+    fn share_rounds_to_three_places() {
+        assert_eq!(share(1, 3), 0.333);
+        assert_eq!(share(2, 2), 1.0);
+        assert_eq!(share(0, 0), 0.0);
+    }
+}
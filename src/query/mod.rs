@@ -1,16 +1,25 @@
+pub mod cohesion;
 pub mod diagnostics;
+pub mod flow;
+pub mod history;
 pub mod orientation;
+pub mod pack;
 pub mod planning;
+pub mod rename;
+pub mod safe_steps;
 pub mod verification;
 
 use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use rusqlite::{Connection, params};
+use rusqlite::{Connection, OptionalExtension, params};
 use serde::Serialize;
 use serde_json::Value as JsonValue;
 
+use crate::indexer::search;
+use crate::store::open_connection;
+
 #[derive(Debug, Clone, Serialize)]
 pub struct QueryMatch {
     pub file_path: String,
@@ -140,6 +149,8 @@ pub struct ContextMatch {
     pub why_included: String,
     pub confidence: String,
     pub score: f64,
+    /// Estimated tokens needed to show the symbol's source span.
+    pub token_cost: u32,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -151,6 +162,20 @@ pub struct TestTarget {
     pub score: f64,
 }
 
+/// A test function that reaches a symbol through `calls` edges.
+#[derive(Debug, Clone, Serialize)]
+pub struct TestFunctionTarget {
+    pub symbol: String,
+    pub file_path: String,
+    pub line: u32,
+    pub distance: u32,
+    /// Function names from the test down to the queried symbol, inclusive.
+    pub call_chain: Vec<String>,
+    pub command: Option<String>,
+    pub confidence: String,
+    pub score: f64,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct VerificationStep {
     pub step: String,
@@ -189,6 +214,8 @@ pub struct ExplainMatch {
     pub end_column: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc_comment: Option<String>,
     pub inbound: ExplainInboundSummary,
     pub outbound: ExplainOutboundSummary,
     pub why_included: String,
@@ -273,7 +300,7 @@ pub fn diff_impact_for_changed_files(
     changed_files: &[String],
     options: &DiffImpactOptions,
 ) -> anyhow::Result<Vec<DiffImpactMatch>> {
-    let connection = Connection::open(db_path)?;
+    let connection = open_connection(db_path)?;
     let changed_lines_by_file = changed_lines_by_file(&options.changed_lines);
     let changed_symbol_filter = options
         .changed_symbols
@@ -396,7 +423,7 @@ fn changed_symbol_seeds(
     changed_file: &str,
     include_imports: bool,
 ) -> anyhow::Result<Vec<ChangedSymbolSeed>> {
-    let mut statement = connection.prepare(
+    let mut statement = connection.prepare_cached(
         "SELECT symbol_id, symbol, kind, file_path, start_line, start_column, end_line,
                 language, qualified_symbol
          FROM symbols_v2
@@ -499,7 +526,7 @@ fn incoming_neighbors(
     connection: &Connection,
     to_symbol_id: i64,
 ) -> anyhow::Result<Vec<IncomingNeighbor>> {
-    let mut statement = connection.prepare(
+    let mut statement = connection.prepare_cached(
         "SELECT fs.symbol_id, fs.symbol, fs.kind, fs.file_path, fs.start_line, fs.start_column,
                 fs.language, fs.qualified_symbol, e.edge_kind, e.confidence, e.provenance
          FROM symbol_edges_v2 e
@@ -687,10 +714,10 @@ pub fn explain_symbol(
     symbol: &str,
     include_snippets: bool,
 ) -> anyhow::Result<Vec<ExplainMatch>> {
-    let connection = Connection::open(db_path)?;
-    let mut statement = connection.prepare(
+    let connection = open_connection(db_path)?;
+    let mut statement = connection.prepare_cached(
         "SELECT symbol_id, symbol, kind, file_path, start_line, start_column, end_line,
-                end_column, signature, language, qualified_symbol
+                end_column, signature, language, qualified_symbol, doc_comment
          FROM symbols_v2
          WHERE symbol = ?1
          ORDER BY file_path ASC, start_line ASC, start_column ASC, kind ASC",
@@ -708,6 +735,7 @@ pub fn explain_symbol(
             row.get::<_, Option<String>>(8)?,
             row.get::<_, String>(9)?,
             row.get::<_, Option<String>>(10)?,
+            row.get::<_, Option<String>>(11)?,
         ))
     })?;
 
@@ -725,6 +753,7 @@ pub fn explain_symbol(
             signature,
             language,
             qualified_symbol,
+            doc_comment,
         ) = row?;
         let language = normalized_language(&language, &file_path).to_string();
         let qualified_symbol =
@@ -744,6 +773,7 @@ pub fn explain_symbol(
             end_line,
             end_column,
             signature,
+            doc_comment,
             inbound,
             outbound,
             why_included: "exact symbol definition match".to_string(),
@@ -788,7 +818,7 @@ fn relationship_summaries_for_symbol_id(
          FROM symbol_edges_v2 \
          WHERE to_symbol_id = ?1 \
          GROUP BY edge_kind";
-    let mut inbound_statement = connection.prepare(inbound_sql)?;
+    let mut inbound_statement = connection.prepare_cached(inbound_sql)?;
     let inbound_rows = inbound_statement.query_map(params![symbol_id], |row| {
         Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u32))
     })?;
@@ -807,7 +837,7 @@ fn relationship_summaries_for_symbol_id(
          FROM symbol_edges_v2 \
          WHERE from_symbol_id = ?1 \
          GROUP BY edge_kind";
-    let mut outbound_statement = connection.prepare(outbound_sql)?;
+    let mut outbound_statement = connection.prepare_cached(outbound_sql)?;
     let outbound_rows = outbound_statement.query_map(params![symbol_id], |row| {
         Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u32))
     })?;
@@ -946,11 +976,13 @@ fn calibrated_test_target_rank(hit_count: i64) -> (&'static str, f64) {
     }
 }
 
-fn language_for_file_path(file_path: &str) -> &'static str {
+pub(crate) fn language_for_file_path(file_path: &str) -> &'static str {
     if file_path.ends_with(".rs") {
         "rust"
     } else if file_path.ends_with(".ts") || file_path.ends_with(".tsx") {
         "typescript"
+    } else if is_javascript_file_path(file_path) {
+        "javascript"
     } else if file_path.ends_with(".py") {
         "python"
     } else if file_path.ends_with(".go") {
@@ -964,6 +996,7 @@ fn normalized_language(language: &str, file_path: &str) -> &'static str {
     match language {
         "rust" => "rust",
         "typescript" => "typescript",
+        "javascript" => "javascript",
         "python" => "python",
         "go" => "go",
         "unknown" => "unknown",
@@ -1034,7 +1067,7 @@ pub fn find_matches_scoped(
     symbol: &str,
     scope: &QueryScope,
 ) -> anyhow::Result<Vec<QueryMatch>> {
-    let connection = Connection::open(db_path)?;
+    let connection = open_connection(db_path)?;
     let ast_definitions = ast_definition_matches(&connection, symbol)?;
     if !ast_definitions.is_empty() {
         return Ok(ast_definitions);
@@ -1043,10 +1076,76 @@ pub fn find_matches_scoped(
     ranked_text_matches(&connection, symbol, scope)
 }
 
+/// Finds definitions whose doc comments contain `text`, case-insensitively.
+///
+/// A doc comment containing `text` as a phrase scores 0.95; one containing every word of
+/// `text` in any order scores 0.85. Results follow score, then file path and position.
+pub fn doc_matches_scoped(
+    db_path: &Path,
+    text: &str,
+    scope: &QueryScope,
+) -> anyhow::Result<Vec<QueryMatch>> {
+    let connection = open_connection(db_path)?;
+    let phrase = text.trim().to_lowercase();
+    let words = phrase.split_whitespace().collect::<Vec<_>>();
+    if words.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut statement = connection.prepare_cached(
+        "SELECT file_path, start_line, start_column, symbol, doc_comment
+         FROM symbols_v2
+         WHERE doc_comment IS NOT NULL
+         ORDER BY file_path ASC, start_line ASC, start_column ASC, symbol ASC",
+    )?;
+    let rows = statement.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, i64>(1)? as u32,
+            row.get::<_, i64>(2)? as u32,
+            row.get::<_, String>(3)?,
+            row.get::<_, String>(4)?,
+        ))
+    })?;
+    let mut matches = Vec::new();
+    for row in rows {
+        let (file_path, line, column, symbol, doc_comment) = row?;
+        if !scope.includes_path(&file_path) {
+            continue;
+        }
+        let doc_comment = doc_comment.to_lowercase();
+        let (why_matched, score) = if doc_comment.contains(&phrase) {
+            ("doc_comment_phrase", 0.95)
+        } else if words.iter().all(|word| doc_comment.contains(word)) {
+            ("doc_comment_terms", 0.85)
+        } else {
+            continue;
+        };
+        matches.push(QueryMatch {
+            file_path,
+            line,
+            column,
+            symbol,
+            why_matched: why_matched.to_string(),
+            confidence: "doc_match".to_string(),
+            score,
+        });
+    }
+    matches.sort_by(|left, right| {
+        right
+            .score
+            .partial_cmp(&left.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(left.file_path.cmp(&right.file_path))
+            .then(left.line.cmp(&right.line))
+            .then(left.column.cmp(&right.column))
+    });
+    Ok(matches)
+}
+
 pub fn suggest_similar_symbols(db_path: &Path, symbol: &str) -> anyhow::Result<Vec<String>> {
-    let connection = Connection::open(db_path)?;
+    let connection = open_connection(db_path)?;
     let pattern = format!("%{symbol}%");
-    let mut stmt = connection.prepare(
+    let mut stmt = connection.prepare_cached(
         "SELECT DISTINCT symbol FROM symbols_v2
          WHERE symbol LIKE ?1
          ORDER BY LENGTH(symbol) ASC
@@ -1085,7 +1184,7 @@ pub fn refs_matches_scoped(
     symbol: &str,
     scope: &QueryScope,
 ) -> anyhow::Result<Vec<QueryMatch>> {
-    let connection = Connection::open(db_path)?;
+    let connection = open_connection(db_path)?;
     let ast_references = ast_reference_matches(&connection, symbol)?;
     if !ast_references.is_empty() {
         return Ok(ast_references);
@@ -1117,8 +1216,8 @@ pub fn refs_matches_scoped(
 /// // `matches` contains ImpactMatch entries referring to symbols that impact `my_crate::MyType`.
 /// ```
 pub fn impact_matches(db_path: &Path, symbol: &str) -> anyhow::Result<Vec<ImpactMatch>> {
-    let connection = Connection::open(db_path)?;
-    let mut target_ids_statement = connection.prepare(
+    let connection = open_connection(db_path)?;
+    let mut target_ids_statement = connection.prepare_cached(
         "SELECT symbol_id
          FROM symbols_v2
          WHERE symbol = ?1
@@ -1136,7 +1235,7 @@ pub fn impact_matches(db_path: &Path, symbol: &str) -> anyhow::Result<Vec<Impact
     let mut seen = HashSet::new();
 
     for target_id in target_ids {
-        let mut incoming_statement = connection.prepare(
+        let mut incoming_statement = connection.prepare_cached(
             "SELECT fs.file_path, fs.start_line, fs.start_column, fs.symbol, fs.kind, \
                     e.edge_kind, e.confidence, e.provenance \
              FROM symbol_edges_v2 e \
@@ -1194,12 +1293,14 @@ pub fn impact_matches(db_path: &Path, symbol: &str) -> anyhow::Result<Vec<Impact
     Ok(results)
 }
 
-/// Finds symbols relevant to a natural-language task by extracting keywords from the task,
-/// matching exact symbol definitions, and including their graph neighbors.
+/// Finds symbols relevant to a natural-language task by ranking the lexical search index
+/// built during `index` and including the graph neighbors of each ranked definition.
 ///
-/// The function returns a vector of ContextMatch ordered by score (highest first) and
-/// then by file path, start line, and symbol. Results are truncated to at most
-/// max(1, budget / 200) entries.
+/// Task terms are scored with BM25 against each symbol's name, signature, doc comment,
+/// string literals, and path segments. Definitions called by other ranked definitions gain
+/// part of their caller's score. Results are ordered by score (highest first) and then by
+/// file path, start line, and symbol, and are packed greedily until their estimated token
+/// cost would exceed `budget`; the top result is always kept.
 ///
 /// # Examples
 ///
@@ -1229,24 +1330,21 @@ pub fn context_matches_scoped(
     budget: u32,
     scope: &QueryScope,
 ) -> anyhow::Result<Vec<ContextMatch>> {
-    let connection = Connection::open(db_path)?;
-    let keywords = extract_keywords(task);
-    if keywords.is_empty() {
+    let connection = open_connection(db_path)?;
+    let query_terms = context_query_terms(task);
+    if query_terms.is_empty() {
         return Ok(Vec::new());
     }
     let mut matches = Vec::new();
     let mut seen = HashSet::new();
-    for seed in context_seed_symbols(&connection)? {
-        let Some(metadata) = context_match_metadata(&keywords, &seed.symbol) else {
-            continue;
-        };
-        push_direct_context_match(&mut matches, &mut seen, &seed, &metadata);
+    for seed in ranked_context_seeds(&connection, task, &query_terms)? {
+        push_direct_context_match(&mut matches, &mut seen, &seed);
         let neighbor_result = push_neighbor_context_matches(
             &connection,
             seed.symbol_id,
             &seed.symbol,
-            &metadata.matched_keywords,
-            metadata.direct_score,
+            &seed.matched_terms,
+            seed.score,
             &mut seen,
             &mut matches,
         );
@@ -1254,10 +1352,28 @@ pub fn context_matches_scoped(
     }
     filter_context_matches_by_scope(&mut matches, scope);
     sort_context_matches(&mut matches);
-    truncate_context_matches_by_budget(&mut matches, budget);
+    dedup_context_snippets(&mut matches);
+    pack_context_matches_by_budget(&mut matches, budget);
     Ok(matches)
 }
 
+/// BM25 term-frequency saturation.
+const CONTEXT_BM25_K1: f64 = 1.2;
+/// BM25 document-length normalization.
+const CONTEXT_BM25_B: f64 = 0.75;
+/// Share of a ranked caller's score credited to each ranked definition it calls.
+const CONTEXT_CALLER_SUPPORT: f64 = 0.25;
+
+/// Per-field weights applied to term frequencies before BM25 saturation.
+fn context_field_weight(field: &str) -> f64 {
+    match field {
+        "name" => 3.0,
+        "signature" | "doc" => 1.5,
+        "string" => 1.0,
+        _ => 0.75,
+    }
+}
+
 #[derive(Debug)]
 struct ContextSeedSymbol {
     symbol_id: i64,
@@ -1266,59 +1382,250 @@ struct ContextSeedSymbol {
     kind: String,
     start_line: u32,
     end_line: u32,
+    token_cost: u32,
+    matched_terms: Vec<String>,
+    name_term_count: usize,
+    exact_symbol_match: bool,
+    score: f64,
 }
 
-#[derive(Debug)]
-struct ContextMatchMetadata {
-    matched_keywords: Vec<String>,
-    overlap_count: usize,
-    exact_symbol_match: bool,
-    direct_score: f64,
+#[derive(Debug, Default)]
+struct ContextTermHits {
+    weighted_frequency: f64,
+    fields: Vec<String>,
+    /// Stored document length; `None` for fallback documents, which are scored as average.
+    length: Option<f64>,
 }
 
-fn context_seed_symbols(connection: &Connection) -> anyhow::Result<Vec<ContextSeedSymbol>> {
-    let mut statement = connection.prepare(
-        "SELECT symbol_id, file_path, symbol, kind, start_line, end_line
-         FROM symbols_v2
-         ORDER BY file_path ASC, start_line ASC, start_column ASC, symbol ASC",
+/// Deduplicated search terms of the task, in first-occurrence order.
+fn context_query_terms(task: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    search::search_terms(task)
+        .into_iter()
+        .filter(|term| seen.insert(term.clone()))
+        .collect()
+}
+
+fn ranked_context_seeds(
+    connection: &Connection,
+    task: &str,
+    query_terms: &[String],
+) -> anyhow::Result<Vec<ContextSeedSymbol>> {
+    let (stored_count, stored_length) = connection.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(term_count), 0) FROM search_documents",
+        [],
+        |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+    )?;
+    let fallback_documents = fallback_context_documents(connection)?;
+    let document_count = stored_count as f64 + fallback_documents.len() as f64;
+    if document_count == 0.0 {
+        return Ok(Vec::new());
+    }
+    let average_length = if stored_count > 0 {
+        stored_length as f64 / stored_count as f64
+    } else {
+        0.0
+    };
+
+    let mut scores = BTreeMap::<i64, f64>::new();
+    let mut matched_terms = BTreeMap::<i64, Vec<String>>::new();
+    for term in query_terms {
+        let hits = context_term_hits(connection, term, &fallback_documents)?;
+        let document_frequency = hits.len() as f64;
+        let idf =
+            (1.0 + (document_count - document_frequency + 0.5) / (document_frequency + 0.5)).ln();
+        for (symbol_id, hit) in hits {
+            let tf = hit.weighted_frequency;
+            let length_ratio = match hit.length {
+                Some(length) if average_length > 0.0 => length / average_length,
+                _ => 1.0,
+            };
+            let saturation = tf * (CONTEXT_BM25_K1 + 1.0)
+                / (tf + CONTEXT_BM25_K1 * (1.0 - CONTEXT_BM25_B + CONTEXT_BM25_B * length_ratio));
+            *scores.entry(symbol_id).or_default() += idf * saturation;
+            matched_terms
+                .entry(symbol_id)
+                .or_default()
+                .push(format!("{term} in {}", hit.fields.join("+")));
+        }
+    }
+    add_context_caller_support(connection, &mut scores)?;
+    context_seeds_for_scores(connection, task, scores, matched_terms)
+}
+
+/// Symbols stored without a search document, such as rows written by older tools, with the
+/// terms of their names. They stay searchable through the `name` field alone.
+fn fallback_context_documents(connection: &Connection) -> anyhow::Result<Vec<(i64, Vec<String>)>> {
+    let mut statement = connection.prepare_cached(
+        "SELECT s.symbol_id, s.symbol
+         FROM symbols_v2 s
+         LEFT JOIN search_documents d ON d.symbol_id = s.symbol_id
+         WHERE d.symbol_id IS NULL
+         ORDER BY s.symbol_id ASC",
     )?;
     let rows = statement.query_map([], |row| {
-        Ok(ContextSeedSymbol {
-            symbol_id: row.get::<_, i64>(0)?,
-            file_path: row.get::<_, String>(1)?,
-            symbol: row.get::<_, String>(2)?,
-            kind: row.get::<_, String>(3)?,
-            start_line: row.get::<_, i64>(4)? as u32,
-            end_line: row.get::<_, i64>(5)? as u32,
-        })
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
     })?;
-    rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    let mut documents = Vec::new();
+    for row in rows {
+        let (symbol_id, symbol) = row?;
+        documents.push((symbol_id, search::search_terms(&symbol)));
+    }
+    Ok(documents)
 }
 
-fn context_match_metadata(keywords: &[String], symbol: &str) -> Option<ContextMatchMetadata> {
-    let symbol_tokens = symbol_keywords(symbol);
-    let matched_keywords = matched_task_keywords(keywords, &symbol_tokens);
-    if matched_keywords.is_empty() {
-        return None;
+fn context_term_hits(
+    connection: &Connection,
+    term: &str,
+    fallback_documents: &[(i64, Vec<String>)],
+) -> anyhow::Result<BTreeMap<i64, ContextTermHits>> {
+    let mut statement = connection.prepare_cached(
+        "SELECT t.symbol_id, t.field, t.frequency, d.term_count
+         FROM search_terms t
+         JOIN search_documents d ON d.symbol_id = t.symbol_id
+         WHERE t.term = ?1
+         ORDER BY t.symbol_id ASC, t.field ASC",
+    )?;
+    let rows = statement.query_map(params![term], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, i64>(2)?,
+            row.get::<_, i64>(3)?,
+        ))
+    })?;
+    let mut hits = BTreeMap::<i64, ContextTermHits>::new();
+    for row in rows {
+        let (symbol_id, field, frequency, term_count) = row?;
+        let hit = hits.entry(symbol_id).or_insert_with(|| ContextTermHits {
+            length: Some(term_count as f64),
+            ..ContextTermHits::default()
+        });
+        hit.weighted_frequency += context_field_weight(&field) * frequency as f64;
+        hit.fields.push(field);
     }
-    let overlap_count = matched_keywords.len();
-    let exact_symbol_match = keywords
-        .iter()
-        .any(|keyword| keyword == &symbol.to_ascii_lowercase());
-    let direct_score = context_direct_score(overlap_count, exact_symbol_match, symbol_tokens.len());
-    Some(ContextMatchMetadata {
-        matched_keywords,
-        overlap_count,
-        exact_symbol_match,
-        direct_score,
-    })
+    for (symbol_id, terms) in fallback_documents {
+        let frequency = terms.iter().filter(|candidate| *candidate == term).count();
+        if frequency > 0 {
+            hits.insert(
+                *symbol_id,
+                ContextTermHits {
+                    weighted_frequency: context_field_weight("name") * frequency as f64,
+                    fields: vec!["name".to_string()],
+                    length: None,
+                },
+            );
+        }
+    }
+    Ok(hits)
+}
+
+/// Credits each ranked definition with part of the score of every ranked caller.
+fn add_context_caller_support(
+    connection: &Connection,
+    scores: &mut BTreeMap<i64, f64>,
+) -> anyhow::Result<()> {
+    let mut statement = connection.prepare_cached(
+        "SELECT DISTINCT to_symbol_id FROM symbol_edges_v2
+         WHERE from_symbol_id = ?1 AND edge_kind = 'calls' AND to_symbol_id != from_symbol_id",
+    )?;
+    let mut support = BTreeMap::<i64, f64>::new();
+    for (symbol_id, score) in scores.iter() {
+        let callees = statement.query_map(params![symbol_id], |row| row.get::<_, i64>(0))?;
+        for callee in callees {
+            let callee = callee?;
+            if scores.contains_key(&callee) {
+                *support.entry(callee).or_default() += score * CONTEXT_CALLER_SUPPORT;
+            }
+        }
+    }
+    for (symbol_id, bonus) in support {
+        if let Some(score) = scores.get_mut(&symbol_id) {
+            *score += bonus;
+        }
+    }
+    Ok(())
+}
+
+fn context_seeds_for_scores(
+    connection: &Connection,
+    task: &str,
+    scores: BTreeMap<i64, f64>,
+    mut matched_terms: BTreeMap<i64, Vec<String>>,
+) -> anyhow::Result<Vec<ContextSeedSymbol>> {
+    let top_score = scores.values().copied().fold(0.0, f64::max);
+    let task_words = task
+        .split(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_'))
+        .map(str::to_ascii_lowercase)
+        .collect::<HashSet<_>>();
+    let mut statement = connection.prepare_cached(
+        "SELECT s.file_path, s.symbol, s.kind, s.start_line, s.end_line, d.token_cost
+         FROM symbols_v2 s
+         LEFT JOIN search_documents d ON d.symbol_id = s.symbol_id
+         WHERE s.symbol_id = ?1",
+    )?;
+    let mut seeds = Vec::new();
+    for (symbol_id, raw_score) in scores {
+        let Some((file_path, symbol, kind, start_line, end_line, token_cost)) = statement
+            .query_row(params![symbol_id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)? as u32,
+                    row.get::<_, i64>(4)? as u32,
+                    row.get::<_, Option<i64>>(5)?,
+                ))
+            })
+            .optional()?
+        else {
+            continue;
+        };
+        let matched_terms = matched_terms.remove(&symbol_id).unwrap_or_default();
+        seeds.push(ContextSeedSymbol {
+            symbol_id,
+            exact_symbol_match: task_words.contains(&symbol.to_ascii_lowercase()),
+            name_term_count: matched_terms
+                .iter()
+                .filter(|term| term.contains(" in name"))
+                .count(),
+            token_cost: token_cost.map_or_else(
+                || estimated_span_token_cost(start_line, end_line),
+                |cost| cost as u32,
+            ),
+            file_path,
+            symbol,
+            kind,
+            start_line,
+            end_line,
+            matched_terms,
+            score: context_direct_score(raw_score, top_score),
+        });
+    }
+    seeds.sort_by(|left, right| {
+        right
+            .score
+            .partial_cmp(&left.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(left.file_path.cmp(&right.file_path))
+            .then(left.start_line.cmp(&right.start_line))
+            .then(left.symbol.cmp(&right.symbol))
+    });
+    Ok(seeds)
+}
+
+/// Maps a BM25 score onto the shared 0.55..0.98 relevance scale, relative to the best match.
+fn context_direct_score(raw_score: f64, top_score: f64) -> f64 {
+    if top_score <= 0.0 {
+        return 0.55;
+    }
+    (0.55 + 0.43 * (raw_score / top_score)).clamp(0.55, 0.98)
 }
 
 fn push_direct_context_match(
     matches: &mut Vec<ContextMatch>,
     seen: &mut HashSet<String>,
     seed: &ContextSeedSymbol,
-    metadata: &ContextMatchMetadata,
 ) {
     let key = format!(
         "{}:{}:{}:{}:direct",
@@ -1335,14 +1642,15 @@ fn push_direct_context_match(
         kind: seed.kind.clone(),
         why_included: format!(
             "direct definition token-overlap relevance for [{}]",
-            metadata.matched_keywords.join(", ")
+            seed.matched_terms.join(", ")
         ),
-        confidence: if metadata.overlap_count >= 2 || metadata.exact_symbol_match {
+        confidence: if seed.name_term_count >= 2 || seed.exact_symbol_match {
             "context_high".to_string()
         } else {
             "context_medium".to_string()
         },
-        score: metadata.direct_score,
+        score: seed.score,
+        token_cost: seed.token_cost,
     });
 }
 
@@ -1355,10 +1663,11 @@ fn push_neighbor_context_matches(
     seen: &mut HashSet<String>,
     matches: &mut Vec<ContextMatch>,
 ) -> anyhow::Result<()> {
-    let mut statement = connection.prepare(
-        "SELECT n.file_path, n.symbol, n.kind, n.start_line, n.end_line \
+    let mut statement = connection.prepare_cached(
+        "SELECT n.file_path, n.symbol, n.kind, n.start_line, n.end_line, d.token_cost \
          FROM symbol_edges_v2 e \
          JOIN symbols_v2 n ON n.symbol_id = e.to_symbol_id \
+         LEFT JOIN search_documents d ON d.symbol_id = n.symbol_id \
          WHERE e.from_symbol_id = ?1 \
          ORDER BY n.file_path ASC, n.start_line ASC, n.start_column ASC, n.symbol ASC",
     )?;
@@ -1369,10 +1678,11 @@ fn push_neighbor_context_matches(
             row.get::<_, String>(2)?,
             row.get::<_, i64>(3)? as u32,
             row.get::<_, i64>(4)? as u32,
+            row.get::<_, Option<i64>>(5)?,
         ))
     })?;
     for row in rows {
-        let (file_path, neighbor_symbol, kind, start_line, end_line) = row?;
+        let (file_path, neighbor_symbol, kind, start_line, end_line, token_cost) = row?;
         let key = format!("{file_path}:{start_line}:{neighbor_symbol}:{kind}:neighbor");
         if !seen.insert(key) {
             continue;
@@ -1389,11 +1699,20 @@ fn push_neighbor_context_matches(
             ),
             confidence: "context_medium".to_string(),
             score: (direct_score - 0.2).max(0.55),
+            token_cost: token_cost.map_or_else(
+                || estimated_span_token_cost(start_line, end_line),
+                |cost| cost as u32,
+            ),
         });
     }
     Ok(())
 }
 
+/// Fallback token estimate for symbols without a search document: one line is ~10 tokens.
+fn estimated_span_token_cost(start_line: u32, end_line: u32) -> u32 {
+    (end_line.saturating_sub(start_line) + 1).saturating_mul(10)
+}
+
 fn filter_context_matches_by_scope(matches: &mut Vec<ContextMatch>, scope: &QueryScope) {
     matches.retain(|item| scope.includes_path(&item.file_path));
 }
@@ -1413,13 +1732,40 @@ fn sort_context_matches(matches: &mut [ContextMatch]) {
     });
 }
 
-fn truncate_context_matches_by_budget(matches: &mut Vec<ContextMatch>, budget: u32) {
-    let max_results = std::cmp::max(1, budget / 200);
+/// Drops lower-ranked repeats of a snippet, such as a definition that is both a direct match
+/// and a graph neighbor, so its tokens are only spent once.
+fn dedup_context_snippets(matches: &mut Vec<ContextMatch>) {
+    let mut seen = HashSet::new();
+    matches.retain(|item| {
+        seen.insert((
+            item.file_path.clone(),
+            item.start_line,
+            item.symbol.clone(),
+            item.kind.clone(),
+        ))
+    });
+}
+
+/// Keeps matches in rank order while their summed token cost fits in `budget`, skipping any
+/// snippet too large for the remaining space. The top match is kept even when it alone
+/// exceeds the budget.
+fn pack_context_matches_by_budget(matches: &mut Vec<ContextMatch>, budget: u32) {
+    let mut remaining = budget;
+    let mut keep = Vec::with_capacity(matches.len());
+    for (rank, item) in matches.iter().enumerate() {
+        let fits = rank == 0 || item.token_cost <= remaining;
+        if fits {
+            remaining = remaining.saturating_sub(item.token_cost);
+        }
+        keep.push(fits);
+    }
+    let max_results = keep.iter().filter(|fits| **fits).count();
     debug_assert!(
-        max_results >= 1,
+        matches.is_empty() || max_results >= 1,
         "context budget must map to at least one result"
     );
-    matches.truncate(bounded_usize(max_results));
+    let mut keep = keep.into_iter();
+    matches.retain(|_| keep.next().unwrap_or(false));
 }
 
 /// Finds test files that reference `symbol` and returns them as prioritized test targets.
@@ -1445,7 +1791,7 @@ pub fn tests_for_symbol(
     symbol: &str,
     include_support: bool,
 ) -> anyhow::Result<Vec<TestTarget>> {
-    let connection = Connection::open(db_path)?;
+    let connection = open_connection(db_path)?;
     let runners = RecommendationRunners::for_db_path(db_path);
     let mut ranked_targets = test_targets_for_symbol_with_sub_tokens(&connection, symbol)?
         .into_iter()
@@ -1476,18 +1822,17 @@ pub fn tests_for_symbol(
         } else {
             ("context_medium", 0.58)
         };
+        let (target_kind, location) = if unit_test_target_parts(&target).is_some() {
+            ("unit_test", "unit test")
+        } else if is_runnable {
+            ("integration_test_file", "test file")
+        } else {
+            ("support_test_file", "support path")
+        };
         targets.push(TestTarget {
             target: target.clone(),
-            target_kind: if is_runnable {
-                "integration_test_file".to_string()
-            } else {
-                "support_test_file".to_string()
-            },
-            why_included: if is_runnable {
-                format!("direct symbol match for '{symbol}' in test file")
-            } else {
-                format!("direct symbol match for '{symbol}' in support path")
-            },
+            target_kind: target_kind.to_string(),
+            why_included: format!("direct symbol match for '{symbol}' in {location}"),
             confidence: confidence.to_string(),
             score,
         });
@@ -1496,6 +1841,179 @@ pub fn tests_for_symbol(
     Ok(targets)
 }
 
+/// Finds test functions that call `symbol` directly or through up to `max_depth - 1`
+/// intermediate functions, walking `calls` edges backward from every definition of `symbol`.
+///
+/// A function counts as a test when the indexer marked it as one (Rust `#[test]` functions) or
+/// when it lives in a test file and nothing calls it, i.e. a harness entry point. Each test is
+/// reported once at its shortest distance, with the call chain that reaches the symbol.
+pub fn tests_for_symbol_via_calls(
+    db_path: &Path,
+    symbol: &str,
+    max_depth: u32,
+) -> anyhow::Result<Vec<TestFunctionTarget>> {
+    let connection = open_connection(db_path)?;
+    let runners = RecommendationRunners::for_db_path(db_path);
+    let mut start_stmt = connection
+        .prepare_cached("SELECT symbol_id FROM symbols_v2 WHERE symbol = ?1 ORDER BY symbol_id")?;
+    let start_ids = start_stmt
+        .query_map(params![symbol], |row| row.get::<_, i64>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Each visited caller maps to its distance and the next hop toward `symbol`.
+    let mut visited: HashMap<i64, (u32, Option<i64>, CallerNode)> = HashMap::new();
+    let mut queue = VecDeque::new();
+    for start_id in start_ids {
+        let node = CallerNode {
+            symbol: symbol.to_string(),
+            ..CallerNode::default()
+        };
+        visited.insert(start_id, (0, None, node));
+        queue.push_back((start_id, 0));
+    }
+    let mut targets = Vec::new();
+    while let Some((current_id, distance)) = queue.pop_front() {
+        if distance >= max_depth {
+            continue;
+        }
+        for (caller_id, caller) in direct_callers(&connection, current_id)? {
+            if visited.contains_key(&caller_id) {
+                continue;
+            }
+            if is_test_function(&connection, caller_id, &caller)? {
+                let call_chain = call_chain_from(&visited, &caller, current_id);
+                targets.push(test_function_target(
+                    caller.clone(),
+                    distance + 1,
+                    call_chain,
+                    &runners,
+                ));
+            }
+            visited.insert(caller_id, (distance + 1, Some(current_id), caller));
+            queue.push_back((caller_id, distance + 1));
+        }
+    }
+
+    targets.sort_by(|left, right| {
+        left.distance
+            .cmp(&right.distance)
+            .then(left.file_path.cmp(&right.file_path))
+            .then(left.line.cmp(&right.line))
+            .then(left.symbol.cmp(&right.symbol))
+    });
+    Ok(targets)
+}
+
+#[derive(Debug, Clone, Default)]
+struct CallerNode {
+    symbol: String,
+    file_path: String,
+    line: u32,
+    kind: String,
+    test_path: Option<String>,
+}
+
+fn direct_callers(
+    connection: &Connection,
+    symbol_id: i64,
+) -> anyhow::Result<Vec<(i64, CallerNode)>> {
+    let mut statement = connection.prepare_cached(
+        "SELECT s.symbol_id, s.symbol, s.file_path, s.start_line, s.kind, s.test_path
+         FROM symbol_edges_v2 e
+         JOIN symbols_v2 s ON s.symbol_id = e.from_symbol_id
+         WHERE e.to_symbol_id = ?1
+           AND e.edge_kind = 'calls'
+         ORDER BY s.file_path, s.start_line, s.symbol_id",
+    )?;
+    let rows = statement.query_map(params![symbol_id], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            CallerNode {
+                symbol: row.get(1)?,
+                file_path: row.get(2)?,
+                line: row.get(3)?,
+                kind: row.get(4)?,
+                test_path: row.get(5)?,
+            },
+        ))
+    })?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+}
+
+fn is_test_function(
+    connection: &Connection,
+    symbol_id: i64,
+    caller: &CallerNode,
+) -> anyhow::Result<bool> {
+    if caller.kind != "function" {
+        return Ok(false);
+    }
+    if let Some(test_path) = &caller.test_path {
+        return Ok(test_path.rsplit("::").next() == Some(caller.symbol.as_str()));
+    }
+    if !is_test_like_path(&caller.file_path) {
+        return Ok(false);
+    }
+    let mut statement = connection.prepare_cached(
+        "SELECT EXISTS(
+             SELECT 1 FROM symbol_edges_v2 WHERE to_symbol_id = ?1 AND edge_kind = 'calls'
+         )",
+    )?;
+    let has_callers: bool = statement.query_row(params![symbol_id], |row| row.get(0))?;
+    Ok(!has_callers)
+}
+
+fn call_chain_from(
+    visited: &HashMap<i64, (u32, Option<i64>, CallerNode)>,
+    caller: &CallerNode,
+    callee_id: i64,
+) -> Vec<String> {
+    let mut chain = vec![caller.symbol.clone()];
+    let mut next = Some(callee_id);
+    while let Some(symbol_id) = next {
+        let Some((_, next_hop, node)) = visited.get(&symbol_id) else {
+            break;
+        };
+        chain.push(node.symbol.clone());
+        next = *next_hop;
+    }
+    chain
+}
+
+fn test_function_target(
+    caller: CallerNode,
+    distance: u32,
+    call_chain: Vec<String>,
+    runners: &RecommendationRunners,
+) -> TestFunctionTarget {
+    let command = match (&caller.test_path, is_test_like_path(&caller.file_path)) {
+        (Some(test_path), false) => {
+            test_command_for_target(&format!("{}::{test_path}", caller.file_path), runners)
+        }
+        (Some(test_path), true) => cargo_test_command_for_target(&caller.file_path)
+            .map(|command| format!("{command} {test_path}")),
+        (None, _) => test_command_for_target(&caller.file_path, runners),
+    };
+    let (confidence, score) = match distance {
+        1 => ("graph_exact", 0.95),
+        2 => ("graph_likely", 0.85),
+        _ => (
+            "context_medium",
+            (0.85 - 0.1 * f64::from(distance - 2)).max(0.5),
+        ),
+    };
+    TestFunctionTarget {
+        symbol: caller.symbol,
+        file_path: caller.file_path,
+        line: caller.line,
+        distance,
+        call_chain,
+        command,
+        confidence: confidence.to_string(),
+        score,
+    }
+}
+
 /// Builds a prioritized verification plan (test commands) for the given changed files.
 ///
 /// The function inspects the symbol and test information stored in the SQLite database at
@@ -1526,7 +2044,7 @@ pub fn verify_plan_for_changed_files(
     changed_files: &[String],
     options: &VerifyPlanOptions,
 ) -> anyhow::Result<Vec<VerificationStep>> {
-    let connection = Connection::open(db_path)?;
+    let connection = open_connection(db_path)?;
     let runners = RecommendationRunners::for_db_path(db_path);
     let changed_lines_by_file = changed_lines_by_file(&options.changed_lines);
     let changed_symbol_filter = options
@@ -1555,10 +2073,11 @@ pub fn verify_plan_for_changed_files(
     Ok(steps)
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Default)]
 struct RecommendationRunners {
     pytest: bool,
     node: NodeTestRunner,
+    repo_root: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
@@ -1596,6 +2115,7 @@ impl RecommendationRunners {
         Self {
             pytest: is_pytest_explicitly_configured(repo_root),
             node: detect_node_test_runner(repo_root),
+            repo_root: Some(repo_root.to_path_buf()),
         }
     }
 }
@@ -1754,7 +2274,7 @@ fn changed_file_symbols(
     connection: &Connection,
     changed_file: &str,
 ) -> anyhow::Result<Vec<ChangedFileSymbol>> {
-    let mut statement = connection.prepare(
+    let mut statement = connection.prepare_cached(
         "SELECT DISTINCT symbol, start_line, end_line
          FROM symbols_v2
          WHERE file_path = ?1
@@ -1869,9 +2389,7 @@ fn select_full_suite_command(
             || targeted_steps
                 .iter()
                 .any(|step| step.step.starts_with("npx vitest run "))
-            || changed_files
-                .iter()
-                .any(|file| is_typescript_source_file(file)))
+            || changed_files.iter().any(|file| is_node_source_file(file)))
     {
         return command.to_string();
     }
@@ -1914,7 +2432,7 @@ fn ast_definition_matches(
     connection: &Connection,
     symbol: &str,
 ) -> anyhow::Result<Vec<QueryMatch>> {
-    let mut statement = connection.prepare(
+    let mut statement = connection.prepare_cached(
         "SELECT file_path, line, column, symbol
          FROM ast_definitions
          WHERE symbol = ?1
@@ -1936,7 +2454,7 @@ fn ast_definition_matches(
 }
 
 fn ast_reference_matches(connection: &Connection, symbol: &str) -> anyhow::Result<Vec<QueryMatch>> {
-    let mut statement = connection.prepare(
+    let mut statement = connection.prepare_cached(
         "SELECT file_path, line, column, symbol
          FROM ast_references
          WHERE symbol = ?1
@@ -1994,7 +2512,7 @@ fn text_exact_matches(
     symbol: &str,
     scope: &QueryScope,
 ) -> anyhow::Result<Vec<QueryMatch>> {
-    let mut statement = connection.prepare(
+    let mut statement = connection.prepare_cached(
         "SELECT file_path, line, column, symbol
          FROM text_occurrences
          WHERE symbol = ?1
@@ -2021,7 +2539,7 @@ fn text_substring_matches(
     scope: &QueryScope,
 ) -> anyhow::Result<Vec<QueryMatch>> {
     let pattern = format!("%{symbol}%");
-    let mut statement = connection.prepare(
+    let mut statement = connection.prepare_cached(
         "SELECT file_path, line, column, symbol
          FROM text_occurrences
          WHERE symbol LIKE ?1 AND symbol <> ?2
@@ -2049,15 +2567,26 @@ fn apply_scope_filters(matches: Vec<QueryMatch>, scope: &QueryScope) -> Vec<Quer
         .collect()
 }
 
-fn is_code_file_path(file_path: &str) -> bool {
+pub(crate) fn is_code_file_path(file_path: &str) -> bool {
     file_path.ends_with(".rs")
         || file_path.ends_with(".ts")
         || file_path.ends_with(".tsx")
+        || is_javascript_file_path(file_path)
         || file_path.ends_with(".py")
         || file_path.ends_with(".go")
 }
 
-fn is_test_like_path(file_path: &str) -> bool {
+pub(crate) fn is_javascript_file_path(file_path: &str) -> bool {
+    [".js", ".jsx", ".mjs", ".cjs"]
+        .iter()
+        .any(|extension| file_path.ends_with(extension))
+}
+
+/// Whether `file_path` is a test file. The CLI filters, diagnostics, and queries all share this
+/// check so they agree on which files are tests.
+pub(crate) fn is_test_like_path(file_path: &str) -> bool {
+    let normalized = file_path.replace('\\', "/");
+    let file_path = normalized.trim_start_matches("./");
     file_path.starts_with("tests/")
         || file_path.contains("/tests/")
         || Path::new(file_path)
@@ -2071,11 +2600,16 @@ fn is_test_like_file_name(file_name: &str) -> bool {
         || file_name.ends_with("_test.go")
         || file_name.ends_with("_test.py")
         || file_name.ends_with("_tests.py")
-        || (file_name.starts_with("test_") && file_name.ends_with(".py"))
-        || file_name.ends_with(".test.ts")
-        || file_name.ends_with(".test.tsx")
-        || file_name.ends_with(".spec.ts")
-        || file_name.ends_with(".spec.tsx")
+        || file_name.starts_with("test_")
+        || is_node_test_file_name(file_name)
+}
+
+fn is_node_test_file_name(file_name: &str) -> bool {
+    let Some((stem, extension)) = file_name.rsplit_once('.') else {
+        return false;
+    };
+    matches!(extension, "ts" | "tsx" | "js" | "jsx" | "mjs" | "cjs")
+        && (stem.ends_with(".test") || stem.ends_with(".spec"))
 }
 
 fn fallback_path_class_rank(file_path: &str) -> u8 {
@@ -2107,7 +2641,7 @@ fn fallback_path_class_rank(file_path: &str) -> u8 {
 /// conn.execute("CREATE TABLE t(x TEXT, line INTEGER, col INTEGER)", [], ).unwrap();
 /// conn.execute("INSERT INTO t VALUES('sym',1,2)", [], ).unwrap();
 ///
-/// let mut stmt = conn.prepare("SELECT x, line, col FROM t").unwrap();
+/// let mut stmt = conn.prepare_cached("SELECT x, line, col FROM t").unwrap();
 /// let mapped = stmt.query_map([], |row| {
 ///     Ok(QueryMatch{
 ///         file_path: "file.rs".into(),
@@ -2134,118 +2668,6 @@ where
     Ok(matches)
 }
 
-/// Extracts meaningful lowercase keywords from a task string.
-///
-/// Splits the input on characters that are not ASCII alphanumeric or underscore,
-/// lowercases each token, ignores tokens shorter than 3 characters, and returns
-/// deduplicated tokens in their first-occurrence order.
-///
-/// # Examples
-///
-/// ```
-/// let kws = extract_keywords("Fix crash in HTTPServer::handle_req v2");
-/// assert_eq!(kws, vec!["fix", "crash", "httpserver", "handle_req"]);
-/// ```
-fn extract_keywords(task: &str) -> Vec<String> {
-    let mut keywords = Vec::new();
-    let mut seen = HashSet::new();
-    let stopwords = [
-        "about", "after", "and", "for", "from", "into", "that", "the", "then", "this", "when",
-        "with",
-    ];
-
-    for token in task
-        .split(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_'))
-        .filter(|token| !token.is_empty())
-    {
-        let lowered = token.to_ascii_lowercase();
-        if lowered.len() < 3 {
-            continue;
-        }
-        if stopwords.contains(&lowered.as_str()) {
-            continue;
-        }
-        if seen.insert(lowered.clone()) {
-            keywords.push(lowered);
-        }
-    }
-
-    keywords
-}
-
-fn symbol_keywords(symbol: &str) -> Vec<String> {
-    let mut tokens = Vec::new();
-    let mut current = String::new();
-    let mut previous_was_lower = false;
-
-    for ch in symbol.chars() {
-        if !(ch.is_ascii_alphanumeric() || ch == '_') {
-            if !current.is_empty() {
-                tokens.push(current.to_ascii_lowercase());
-                current.clear();
-            }
-            previous_was_lower = false;
-            continue;
-        }
-
-        if ch == '_' {
-            if !current.is_empty() {
-                tokens.push(current.to_ascii_lowercase());
-                current.clear();
-            }
-            previous_was_lower = false;
-            continue;
-        }
-
-        if ch.is_ascii_uppercase() && previous_was_lower && !current.is_empty() {
-            tokens.push(current.to_ascii_lowercase());
-            current.clear();
-        }
-
-        previous_was_lower = ch.is_ascii_lowercase();
-        current.push(ch);
-    }
-
-    if !current.is_empty() {
-        tokens.push(current.to_ascii_lowercase());
-    }
-
-    let mut filtered = tokens
-        .into_iter()
-        .filter(|token| token.len() >= 3)
-        .collect::<Vec<_>>();
-    filtered.sort();
-    filtered.dedup();
-    filtered
-}
-
-fn matched_task_keywords(task_keywords: &[String], symbol_keywords: &[String]) -> Vec<String> {
-    let mut matched = Vec::new();
-    for task_keyword in task_keywords {
-        if symbol_keywords.iter().any(|symbol_keyword| {
-            symbol_keyword == task_keyword
-                || symbol_keyword.starts_with(task_keyword)
-                || task_keyword.starts_with(symbol_keyword)
-        }) {
-            matched.push(task_keyword.clone());
-        }
-    }
-    matched
-}
-
-fn context_direct_score(
-    overlap_count: usize,
-    exact_symbol_match: bool,
-    symbol_token_count: usize,
-) -> f64 {
-    let specificity_bonus = std::cmp::min(symbol_token_count, 4) as f64 * 0.04;
-    let mut score = 0.62 + (overlap_count as f64 * 0.09) + specificity_bonus;
-    if exact_symbol_match {
-        score += 0.04;
-    }
-    score.min(0.98)
-}
-
 /// Finds test files that reference a symbol and how often the symbol appears in each.
 ///
 /// Returns a vector of `(file_path, hit_count)` for files that look like test targets
@@ -2281,7 +2703,7 @@ fn test_targets_for_symbol_inner(
     symbol: &str,
     use_sub_tokens: bool,
 ) -> anyhow::Result<Vec<(String, i64)>> {
-    let mut statement = connection.prepare(
+    let mut statement = connection.prepare_cached(
         "SELECT file_path, COUNT(*) AS hit_count
          FROM text_occurrences
          WHERE symbol = ?1
@@ -2302,6 +2724,7 @@ fn test_targets_for_symbol_inner(
             targets.push((file_path, hit_count));
         }
     }
+    targets.extend(unit_test_targets_for_symbol(connection, symbol)?);
 
     // Secondary heuristic: split symbol into sub-tokens
     // (e.g. "index_repository" → ["index", "repository"]) and search
@@ -2313,7 +2736,7 @@ fn test_targets_for_symbol_inner(
     let sub_tokens: Vec<&str> = symbol.split('_').filter(|t| t.len() >= 3).collect();
     if !sub_tokens.is_empty() {
         for token in &sub_tokens {
-            let mut sub_stmt = connection.prepare(
+            let mut sub_stmt = connection.prepare_cached(
                 "SELECT file_path, COUNT(*) AS hit_count
                  FROM text_occurrences
                  WHERE symbol = ?1
@@ -2337,6 +2760,50 @@ fn test_targets_for_symbol_inner(
     Ok(targets)
 }
 
+/// Finds in-file unit tests (Rust `#[test]` functions and `#[cfg(test)]` module helpers)
+/// whose bodies mention `symbol`. Targets are `<file>::<test path>`, e.g.
+/// `src/query/mod.rs::query::tests::ranks_exact_matches`. Files that are test targets by
+/// path are left to the file-level matches.
+fn unit_test_targets_for_symbol(
+    connection: &Connection,
+    symbol: &str,
+) -> anyhow::Result<Vec<(String, i64)>> {
+    let mut statement = connection.prepare_cached(
+        "SELECT t.file_path, s.test_path, COUNT(*) AS hit_count
+         FROM text_occurrences t
+         JOIN symbols_v2 s
+           ON s.file_path = t.file_path
+          AND s.kind = 'function'
+          AND s.test_path IS NOT NULL
+          AND t.line BETWEEN s.start_line AND s.end_line
+         WHERE t.symbol = ?1
+         GROUP BY t.file_path, s.test_path
+         ORDER BY hit_count DESC, t.file_path ASC, s.test_path ASC",
+    )?;
+    let rows = statement.query_map(params![symbol], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, i64>(2)?,
+        ))
+    })?;
+
+    let mut targets = Vec::new();
+    for row in rows {
+        let (file_path, test_path, hit_count) = row?;
+        if !is_test_like_path(&file_path) {
+            targets.push((format!("{file_path}::{test_path}"), hit_count));
+        }
+    }
+    Ok(targets)
+}
+
+/// Splits a unit test target into its Rust file and `cargo test` filter.
+fn unit_test_target_parts(target: &str) -> Option<(&str, &str)> {
+    let (file_stem, test_path) = target.split_once(".rs::")?;
+    Some((&target[..file_stem.len() + 3], test_path))
+}
+
 /// Derives a `cargo test` invocation for a standalone test file directly under `tests/`.
 ///
 /// Returns `Some` with command `cargo test --test {stem}` when `target` is a path
@@ -2359,6 +2826,7 @@ fn test_targets_for_symbol_inner(
 /// ```
 fn test_command_for_target(target: &str, runners: &RecommendationRunners) -> Option<String> {
     cargo_test_command_for_target(target)
+        .or_else(|| cargo_unit_test_command_for_target(target, runners))
         .or_else(|| pytest_test_command_for_target(target, runners))
         .or_else(|| go_test_command_for_target(target))
         .or_else(|| node_test_command_for_target(target, runners))
@@ -2382,6 +2850,65 @@ fn cargo_test_command_for_target(target: &str) -> Option<String> {
     Some(format!("cargo test --test {stem}"))
 }
 
+/// Derives `cargo test -p <package> --lib <filter>` (or `--bin <name>`) for a unit test
+/// target, reading the nearest `Cargo.toml` above the file. Without a manifest the package
+/// selection is left to cargo.
+fn cargo_unit_test_command_for_target(
+    target: &str,
+    runners: &RecommendationRunners,
+) -> Option<String> {
+    let (file_path, test_path) = unit_test_target_parts(target)?;
+    let selection = runners
+        .repo_root
+        .as_deref()
+        .and_then(|repo_root| cargo_target_selection(repo_root, file_path));
+    Some(match selection {
+        Some(selection) => format!("cargo test {selection} {test_path}"),
+        None => format!("cargo test {test_path}"),
+    })
+}
+
+fn cargo_target_selection(repo_root: &Path, file_path: &str) -> Option<String> {
+    let mut manifest_dir = Path::new(file_path).parent();
+    while let Some(directory) = manifest_dir {
+        let manifest = fs::read_to_string(repo_root.join(directory).join("Cargo.toml"))
+            .ok()
+            .and_then(|text| text.parse::<toml::Table>().ok());
+        if let Some(package) = manifest
+            .as_ref()
+            .and_then(|manifest| manifest.get("package")?.get("name")?.as_str())
+        {
+            let manifest = manifest.as_ref()?;
+            let relative = Path::new(file_path).strip_prefix(directory).ok()?;
+            let lib_path = manifest
+                .get("lib")
+                .and_then(|lib| lib.get("path"))
+                .and_then(toml::Value::as_str)
+                .unwrap_or("src/lib.rs");
+            let bin_name = if relative == Path::new("src/main.rs") {
+                Some(package.to_string())
+            } else if let Ok(bin_relative) = relative.strip_prefix("src/bin") {
+                bin_relative
+                    .components()
+                    .next()
+                    .and_then(|component| Path::new(component.as_os_str()).file_stem())
+                    .and_then(|stem| stem.to_str())
+                    .map(str::to_string)
+            } else if repo_root.join(directory).join(lib_path).is_file() {
+                None
+            } else {
+                Some(package.to_string())
+            };
+            return Some(match bin_name {
+                Some(bin_name) => format!("-p {package} --bin {bin_name}"),
+                None => format!("-p {package} --lib"),
+            });
+        }
+        manifest_dir = directory.parent();
+    }
+    None
+}
+
 fn pytest_test_command_for_target(target: &str, runners: &RecommendationRunners) -> Option<String> {
     if !runners.pytest || !is_pytest_test_file(target) {
         return None;
@@ -2390,7 +2917,7 @@ fn pytest_test_command_for_target(target: &str, runners: &RecommendationRunners)
 }
 
 fn node_test_command_for_target(target: &str, runners: &RecommendationRunners) -> Option<String> {
-    if !is_typescript_test_file(target) {
+    if !is_node_test_file(target) {
         return None;
     }
     runners.node.targeted_command_for(target)
@@ -2436,15 +2963,15 @@ fn is_pytest_test_file(target: &str) -> bool {
         })
 }
 
-fn is_typescript_test_file(target: &str) -> bool {
-    target.ends_with(".test.ts")
-        || target.ends_with(".test.tsx")
-        || target.ends_with(".spec.ts")
-        || target.ends_with(".spec.tsx")
+fn is_node_test_file(target: &str) -> bool {
+    Path::new(target)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(is_node_test_file_name)
 }
 
-fn is_typescript_source_file(target: &str) -> bool {
-    target.ends_with(".ts") || target.ends_with(".tsx")
+fn is_node_source_file(target: &str) -> bool {
+    target.ends_with(".ts") || target.ends_with(".tsx") || is_javascript_file_path(target)
 }
 
 fn is_go_source_file(target: &str) -> bool {
@@ -2593,7 +3120,7 @@ pub struct StatusSummary {
 }
 
 pub fn status_summary(db_path: &Path) -> anyhow::Result<StatusSummary> {
-    let connection = Connection::open(db_path)?;
+    let connection = open_connection(db_path)?;
     let source_files: i64 =
         connection.query_row("SELECT COUNT(*) FROM indexed_files", [], |row| row.get(0))?;
     let definitions: i64 =
@@ -2606,7 +3133,7 @@ pub fn status_summary(db_path: &Path) -> anyhow::Result<StatusSummary> {
         })?;
     let edges: i64 =
         connection.query_row("SELECT COUNT(*) FROM symbol_edges_v2", [], |row| row.get(0))?;
-    let mut lang_stmt = connection.prepare(
+    let mut lang_stmt = connection.prepare_cached(
         "SELECT language, COUNT(DISTINCT file_path) FROM symbols_v2 \
          GROUP BY language ORDER BY language",
     )?;
@@ -2643,8 +3170,8 @@ pub fn snippet_for_symbol(
     symbol: &str,
     context_lines: u32,
 ) -> anyhow::Result<Vec<SnippetMatch>> {
-    let connection = Connection::open(db_path)?;
-    let mut stmt = connection.prepare(
+    let connection = open_connection(db_path)?;
+    let mut stmt = connection.prepare_cached(
         "SELECT file_path, symbol, kind, start_line, end_line, signature
          FROM symbols_v2
          WHERE symbol = ?1
@@ -2688,46 +3215,37 @@ pub struct OutlineEntry {
     pub line: u32,
     pub signature: Option<String>,
     pub visibility: String,
+    pub doc_comment: Option<String>,
 }
 
 pub fn outline_file(db_path: &Path, file_path: &str) -> anyhow::Result<Vec<OutlineEntry>> {
-    let connection = Connection::open(db_path)?;
-    let mut stmt = connection.prepare(
-        "SELECT symbol, kind, start_line, signature
+    let connection = open_connection(db_path)?;
+    let mut stmt = connection.prepare_cached(
+        "SELECT symbol, kind, start_line, signature, COALESCE(visibility, ''), doc_comment
          FROM symbols_v2
          WHERE file_path = ?1
          ORDER BY start_line",
     )?;
     let rows = stmt.query_map(params![file_path], |row| {
-        Ok((
-            row.get::<_, String>(0)?,
-            row.get::<_, String>(1)?,
-            row.get::<_, u32>(2)?,
-            row.get::<_, Option<String>>(3)?,
-        ))
+        Ok(OutlineEntry {
+            symbol: row.get(0)?,
+            kind: row.get(1)?,
+            line: row.get(2)?,
+            signature: row.get(3)?,
+            visibility: row.get(4)?,
+            doc_comment: row.get(5)?,
+        })
     })?;
     let mut entries = Vec::new();
     for row in rows {
-        let (symbol, kind, line, signature) = row?;
-        let visibility = signature
-            .as_deref()
-            .map(|s| if s.starts_with("pub") { "pub" } else { "" })
-            .unwrap_or("")
-            .to_string();
-        entries.push(OutlineEntry {
-            symbol,
-            kind,
-            line,
-            signature,
-            visibility,
-        });
+        entries.push(row?);
     }
     Ok(entries)
 }
 
 pub fn repo_entry_points(db_path: &Path) -> anyhow::Result<Vec<String>> {
-    let connection = Connection::open(db_path)?;
-    let mut stmt = connection.prepare(
+    let connection = open_connection(db_path)?;
+    let mut stmt = connection.prepare_cached(
         "SELECT file_path FROM symbols_v2
          WHERE symbol = 'main' AND kind = 'function'
          ORDER BY file_path",
@@ -2751,8 +3269,8 @@ pub struct EdgeMatch {
 }
 
 pub fn callers_of(db_path: &Path, symbol: &str) -> anyhow::Result<Vec<EdgeMatch>> {
-    let connection = Connection::open(db_path)?;
-    let mut stmt = connection.prepare(
+    let connection = open_connection(db_path)?;
+    let mut stmt = connection.prepare_cached(
         "SELECT s_from.file_path, s_from.symbol, s_from.kind,
                 s_from.start_line, s_from.start_column, e.confidence
          FROM symbol_edges_v2 e
@@ -2775,8 +3293,8 @@ pub fn callers_of(db_path: &Path, symbol: &str) -> anyhow::Result<Vec<EdgeMatch>
 }
 
 pub fn callees_of(db_path: &Path, symbol: &str) -> anyhow::Result<Vec<EdgeMatch>> {
-    let connection = Connection::open(db_path)?;
-    let mut stmt = connection.prepare(
+    let connection = open_connection(db_path)?;
+    let mut stmt = connection.prepare_cached(
         "SELECT s_to.file_path, s_to.symbol, s_to.kind,
                 s_to.start_line, s_to.start_column, e.confidence
          FROM symbol_edges_v2 e
@@ -2811,10 +3329,10 @@ pub struct FileDeps {
 }
 
 pub fn file_deps(db_path: &Path, file_path: &str) -> anyhow::Result<FileDeps> {
-    let connection = Connection::open(db_path)?;
+    let connection = open_connection(db_path)?;
 
     // Files this file depends on (outgoing edges)
-    let mut depends_stmt = connection.prepare(
+    let mut depends_stmt = connection.prepare_cached(
         "SELECT s_to.file_path, COUNT(*) as cnt
          FROM symbol_edges_v2 e
          JOIN symbols_v2 s_from ON e.from_symbol_id = s_from.symbol_id
@@ -2834,7 +3352,7 @@ pub fn file_deps(db_path: &Path, file_path: &str) -> anyhow::Result<FileDeps> {
         .collect();
 
     // Files that depend on this file (incoming edges)
-    let mut depended_stmt = connection.prepare(
+    let mut depended_stmt = connection.prepare_cached(
         "SELECT s_from.file_path, COUNT(*) as cnt
          FROM symbol_edges_v2 e
          JOIN symbols_v2 s_from ON e.from_symbol_id = s_from.symbol_id
@@ -2868,12 +3386,12 @@ pub struct RelatedSymbol {
 }
 
 pub fn related_symbols(db_path: &Path, symbol: &str) -> anyhow::Result<Vec<RelatedSymbol>> {
-    let connection = Connection::open(db_path)?;
+    let connection = open_connection(db_path)?;
     let mut results: Vec<RelatedSymbol> = Vec::new();
     let mut seen = HashSet::new();
 
     // Find the target symbol's file_path and symbol_id
-    let mut sym_stmt = connection.prepare(
+    let mut sym_stmt = connection.prepare_cached(
         "SELECT symbol_id, file_path FROM symbols_v2
          WHERE symbol = ?1
          ORDER BY file_path, symbol_id LIMIT 1",
@@ -2890,7 +3408,7 @@ pub fn related_symbols(db_path: &Path, symbol: &str) -> anyhow::Result<Vec<Relat
     seen.insert(symbol.to_string());
 
     // 1. Siblings: same file, different symbol
-    let mut sib_stmt = connection.prepare(
+    let mut sib_stmt = connection.prepare_cached(
         "SELECT symbol, file_path, kind FROM symbols_v2
          WHERE file_path = ?1 AND symbol != ?2
          ORDER BY symbol",
@@ -2916,7 +3434,7 @@ pub fn related_symbols(db_path: &Path, symbol: &str) -> anyhow::Result<Vec<Relat
     }
 
     // 2. Shared callers: symbols called by the same caller
-    let mut shared_stmt = connection.prepare(
+    let mut shared_stmt = connection.prepare_cached(
         "SELECT DISTINCT s2.symbol, s2.file_path, s2.kind
          FROM symbol_edges_v2 e1
          JOIN symbol_edges_v2 e2
@@ -2952,87 +3470,240 @@ pub fn related_symbols(db_path: &Path, symbol: &str) -> anyhow::Result<Vec<Relat
     Ok(results)
 }
 
-pub fn find_call_path(
-    db_path: &Path,
-    from: &str,
-    to: &str,
-    max_depth: u32,
-) -> anyhow::Result<Option<Vec<String>>> {
-    let connection = Connection::open(db_path)?;
+/// A symbol that a `call-path` endpoint selector resolved to.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CallPathEndpoint {
+    pub symbol: String,
+    pub qualified_symbol: String,
+    pub file_path: String,
+    pub line: u32,
+}
 
-    let mut from_stmt = connection.prepare(
-        "SELECT symbol_id, symbol FROM symbols_v2
-         WHERE symbol = ?1
-         ORDER BY file_path, symbol_id LIMIT 1",
-    )?;
-    let from_id: Option<(i64, String)> = from_stmt
-        .query_map(params![from], |row| Ok((row.get(0)?, row.get(1)?)))?
-        .filter_map(|r| r.ok())
-        .next();
+/// One symbol on a call path, with the kind of the edge that led to it.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CallPathHop {
+    #[serde(flatten)]
+    pub endpoint: CallPathEndpoint,
+    /// `None` on the first hop of a path.
+    pub edge_kind: Option<String>,
+}
 
-    let (start_id, start_name) = match from_id {
-        Some(v) => v,
-        None => return Ok(None),
-    };
+#[derive(Debug, Clone, Copy)]
+pub struct CallPathOptions {
+    pub max_depth: u32,
+    pub max_paths: usize,
+    /// Also follow `imports` and `implements` edges, not just `calls`.
+    pub include_structural: bool,
+}
 
-    let mut visited: HashMap<i64, (i64, String)> = HashMap::new();
-    visited.insert(start_id, (-1, start_name));
-    let mut queue: VecDeque<(i64, u32)> = VecDeque::new();
-    queue.push_back((start_id, 0));
+#[derive(Debug, Clone, Serialize)]
+pub struct CallPathReport {
+    pub from_candidates: Vec<CallPathEndpoint>,
+    pub to_candidates: Vec<CallPathEndpoint>,
+    /// Distinct shortest paths, all of the same length.
+    pub paths: Vec<Vec<CallPathHop>>,
+}
+
+impl CallPathReport {
+    /// Symbol names along the first path, the shape `call-path` reported before hops carried
+    /// locations.
+    pub fn first_path_names(&self) -> Option<Vec<String>> {
+        self.paths
+            .first()
+            .map(|path| path.iter().map(|hop| hop.endpoint.symbol.clone()).collect())
+    }
+}
 
-    let mut found_id: Option<i64> = None;
+/// Finds up to `max_paths` distinct shortest paths from any definition matching `from` to any
+/// definition matching `to`. Selectors are bare names, qualified symbols
+/// (`rust:src/a.rs::foo`), or `file:line`.
+pub fn find_call_paths(
+    db_path: &Path,
+    from: &str,
+    to: &str,
+    options: CallPathOptions,
+) -> anyhow::Result<CallPathReport> {
+    let connection = open_connection(db_path)?;
+    let from_candidates = call_path_endpoints(&connection, from)?;
+    let to_candidates = call_path_endpoints(&connection, to)?;
+    let targets = to_candidates
+        .iter()
+        .map(|(id, _)| *id)
+        .collect::<HashSet<_>>();
 
-    while let Some((current_id, depth)) = queue.pop_front() {
-        if depth >= max_depth {
-            continue;
-        }
-        let mut edge_stmt = connection.prepare(
-            "SELECT e.to_symbol_id, s.symbol
-             FROM symbol_edges_v2 e
-             JOIN symbols_v2 s
-               ON s.symbol_id = e.to_symbol_id
-             WHERE e.from_symbol_id = ?1
-               AND e.edge_kind = 'calls'
-             ORDER BY s.symbol, s.file_path",
-        )?;
-        let neighbors: Vec<(i64, String)> = edge_stmt
-            .query_map(params![current_id], |row| Ok((row.get(0)?, row.get(1)?)))?
-            .filter_map(|r| r.ok())
-            .collect();
+    let mut nodes = from_candidates
+        .iter()
+        .chain(&to_candidates)
+        .cloned()
+        .collect::<HashMap<_, _>>();
+    let mut distance = from_candidates
+        .iter()
+        .map(|(id, _)| (*id, 0_u32))
+        .collect::<HashMap<_, _>>();
+    let mut parents: HashMap<i64, Vec<(i64, String)>> = HashMap::new();
+    let mut frontier = from_candidates
+        .iter()
+        .map(|(id, _)| *id)
+        .collect::<Vec<_>>();
+    let mut reached = Vec::new();
 
-        for (neighbor_id, neighbor_name) in neighbors {
-            if visited.contains_key(&neighbor_id) {
-                continue;
-            }
-            visited.insert(neighbor_id, (current_id, neighbor_name.clone()));
-            if neighbor_name == to {
-                found_id = Some(neighbor_id);
-                break;
+    for depth in 1..=options.max_depth {
+        let mut next = Vec::new();
+        for &current in &frontier {
+            for (neighbor, edge_kind, endpoint) in
+                call_path_edges(&connection, current, options.include_structural)?
+            {
+                match distance.get(&neighbor) {
+                    Some(&seen) if seen < depth => continue,
+                    Some(_) => {}
+                    None => {
+                        distance.insert(neighbor, depth);
+                        nodes.entry(neighbor).or_insert(endpoint);
+                        next.push(neighbor);
+                    }
+                }
+                let incoming = parents.entry(neighbor).or_default();
+                if !incoming.iter().any(|(parent, _)| *parent == current) {
+                    incoming.push((current, edge_kind));
+                }
             }
-            queue.push_back((neighbor_id, depth + 1));
         }
-        if found_id.is_some() {
+        reached = next
+            .iter()
+            .copied()
+            .filter(|id| targets.contains(id))
+            .collect();
+        if !reached.is_empty() || next.is_empty() {
             break;
         }
+        frontier = next;
     }
 
-    match found_id {
-        None => Ok(None),
-        Some(end_id) => {
-            let mut path = Vec::new();
-            let mut cur = end_id;
-            while cur != -1 {
-                if let Some((prev, name)) = visited.get(&cur) {
-                    path.push(name.clone());
-                    cur = *prev;
-                } else {
-                    break;
+    let mut paths = Vec::new();
+    for target in reached {
+        let mut suffix = Vec::new();
+        unwind_call_paths(
+            target,
+            &nodes,
+            &parents,
+            &mut suffix,
+            &mut paths,
+            options.max_paths,
+        );
+    }
+    Ok(CallPathReport {
+        from_candidates: from_candidates.into_iter().map(|(_, e)| e).collect(),
+        to_candidates: to_candidates.into_iter().map(|(_, e)| e).collect(),
+        paths,
+    })
+}
+
+/// Walks predecessor lists back from `id`, emitting each complete path once it reaches a start
+/// symbol. `suffix` holds the hops after `id`, nearest first.
+fn unwind_call_paths(
+    id: i64,
+    nodes: &HashMap<i64, CallPathEndpoint>,
+    parents: &HashMap<i64, Vec<(i64, String)>>,
+    suffix: &mut Vec<CallPathHop>,
+    paths: &mut Vec<Vec<CallPathHop>>,
+    max_paths: usize,
+) {
+    if paths.len() >= max_paths {
+        return;
+    }
+    let Some(endpoint) = nodes.get(&id) else {
+        return;
+    };
+    suffix.push(CallPathHop {
+        endpoint: endpoint.clone(),
+        edge_kind: None,
+    });
+    match parents.get(&id) {
+        None => paths.push(suffix.iter().rev().cloned().collect()),
+        Some(incoming) => {
+            for (parent, edge_kind) in incoming {
+                if let Some(hop) = suffix.last_mut() {
+                    hop.edge_kind = Some(edge_kind.clone());
                 }
+                unwind_call_paths(*parent, nodes, parents, suffix, paths, max_paths);
             }
-            path.reverse();
-            Ok(Some(path))
         }
     }
+    suffix.pop();
+}
+
+fn call_path_endpoints(
+    connection: &Connection,
+    selector: &str,
+) -> anyhow::Result<Vec<(i64, CallPathEndpoint)>> {
+    if let Some((file_path, line)) = parse_file_line_selector(selector) {
+        let mut statement = connection.prepare_cached(
+            "SELECT symbol_id, symbol,
+                    COALESCE(qualified_symbol, language || ':' || file_path || '::' || symbol),
+                    file_path, start_line
+             FROM symbols_v2
+             WHERE file_path = ?1 AND start_line <= ?2 AND end_line >= ?2
+             ORDER BY end_line - start_line, start_line DESC, symbol_id
+             LIMIT 1",
+        )?;
+        let rows = statement.query_map(params![file_path, line], call_path_endpoint_row)?;
+        return rows.collect::<Result<Vec<_>, _>>().map_err(Into::into);
+    }
+    let mut statement = connection.prepare_cached(
+        "SELECT symbol_id, symbol,
+                COALESCE(qualified_symbol, language || ':' || file_path || '::' || symbol),
+                file_path, start_line
+         FROM symbols_v2
+         WHERE symbol = ?1
+            OR COALESCE(qualified_symbol, language || ':' || file_path || '::' || symbol) = ?1
+         ORDER BY file_path, start_line, symbol_id",
+    )?;
+    let rows = statement.query_map(params![selector], call_path_endpoint_row)?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+}
+
+fn call_path_edges(
+    connection: &Connection,
+    symbol_id: i64,
+    include_structural: bool,
+) -> anyhow::Result<Vec<(i64, String, CallPathEndpoint)>> {
+    let mut statement = connection.prepare_cached(
+        "SELECT s.symbol_id, s.symbol,
+                COALESCE(s.qualified_symbol, s.language || ':' || s.file_path || '::' || s.symbol),
+                s.file_path, s.start_line, e.edge_kind
+         FROM symbol_edges_v2 e
+         JOIN symbols_v2 s ON s.symbol_id = e.to_symbol_id
+         WHERE e.from_symbol_id = ?1
+           AND (e.edge_kind = 'calls'
+                OR (?2 AND e.edge_kind IN ('imports', 'implements')))
+         ORDER BY CASE e.edge_kind WHEN 'calls' THEN 0 ELSE 1 END,
+                  s.file_path, s.start_line, s.symbol_id",
+    )?;
+    let rows = statement.query_map(params![symbol_id, include_structural], |row| {
+        let (id, endpoint) = call_path_endpoint_row(row)?;
+        Ok((id, row.get::<_, String>(5)?, endpoint))
+    })?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+}
+
+fn call_path_endpoint_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<(i64, CallPathEndpoint)> {
+    Ok((
+        row.get(0)?,
+        CallPathEndpoint {
+            symbol: row.get(1)?,
+            qualified_symbol: row.get(2)?,
+            file_path: row.get(3)?,
+            line: row.get(4)?,
+        },
+    ))
+}
+
+/// Splits a `path/to/file.rs:42` selector. Qualified symbols never end in `:<digits>`.
+pub(crate) fn parse_file_line_selector(selector: &str) -> Option<(&str, u32)> {
+    let (file_path, line) = selector.rsplit_once(':')?;
+    let line = line.parse::<u32>().ok()?;
+    let file_path = file_path.trim_start_matches("./");
+    (!file_path.is_empty() && !file_path.ends_with(':')).then_some((file_path, line))
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -3046,8 +3717,8 @@ pub struct HotspotEntry {
 }
 
 pub fn hotspots(db_path: &Path, limit: u32) -> anyhow::Result<Vec<HotspotEntry>> {
-    let connection = Connection::open(db_path)?;
-    let mut stmt = connection.prepare(
+    let connection = open_connection(db_path)?;
+    let mut stmt = connection.prepare_cached(
         "SELECT s.symbol, s.file_path, s.kind,
                 COUNT(DISTINCT e_in.from_symbol_id) as fan_in,
                 COUNT(DISTINCT e_out.to_symbol_id) as fan_out,
@@ -3103,18 +3774,30 @@ mod tests {
         (repo, db_path)
     }
 
-    fn insert_symbol_row(
-        connection: &Connection,
+    struct SymbolRow<'a> {
         symbol_id: i64,
-        file_path: &str,
-        symbol: &str,
-        kind: &str,
+        file_path: &'a str,
+        symbol: &'a str,
+        kind: &'a str,
         line: u32,
         column: u32,
         end_line: u32,
-        language: &str,
-        qualified_symbol: Option<&str>,
-    ) {
+        language: &'a str,
+        qualified_symbol: Option<&'a str>,
+    }
+
+    fn insert_symbol_row(connection: &Connection, row: SymbolRow<'_>) {
+        let SymbolRow {
+            symbol_id,
+            file_path,
+            symbol,
+            kind,
+            line,
+            column,
+            end_line,
+            language,
+            qualified_symbol,
+        } = row;
         connection
             .execute(
                 "INSERT INTO symbols_v2(
@@ -3225,55 +3908,63 @@ mod tests {
         let connection = Connection::open(&db_path).expect("db should open");
         insert_symbol_row(
             &connection,
-            1,
-            "src/lib.rs",
-            "target",
-            "function",
-            1,
-            4,
-            1,
-            "mystery",
-            None,
+            SymbolRow {
+                symbol_id: 1,
+                file_path: "src/lib.rs",
+                symbol: "target",
+                kind: "function",
+                line: 1,
+                column: 4,
+                end_line: 1,
+                language: "mystery",
+                qualified_symbol: None,
+            },
         );
         insert_symbol_row(
             &connection,
-            2,
-            "src/z.rs",
-            "target",
-            "function",
-            1,
-            4,
-            1,
-            "rust",
-            None,
+            SymbolRow {
+                symbol_id: 2,
+                file_path: "src/z.rs",
+                symbol: "target",
+                kind: "function",
+                line: 1,
+                column: 4,
+                end_line: 1,
+                language: "rust",
+                qualified_symbol: None,
+            },
         );
 
         for id in 10..=14 {
             insert_symbol_row(
                 &connection,
-                id,
-                "src/caller.rs",
-                &format!("from_{id}"),
-                "function",
-                1,
-                1,
-                1,
-                "rust",
-                Some(&format!("rust:src/caller.rs::from_{id}")),
+                SymbolRow {
+                    symbol_id: id,
+                    file_path: "src/caller.rs",
+                    symbol: &format!("from_{id}"),
+                    kind: "function",
+                    line: 1,
+                    column: 1,
+                    end_line: 1,
+                    language: "rust",
+                    qualified_symbol: Some(&format!("rust:src/caller.rs::from_{id}")),
+                },
             );
         }
         for id in 20..=24 {
             insert_symbol_row(
                 &connection,
-                id,
-                "src/callee.rs",
-                &format!("to_{id}"),
-                "function",
-                1,
-                1,
-                1,
-                "rust",
-                Some(&format!("rust:src/callee.rs::to_{id}")),
+                SymbolRow {
+                    symbol_id: id,
+                    file_path: "src/callee.rs",
+                    symbol: &format!("to_{id}"),
+                    kind: "function",
+                    line: 1,
+                    column: 1,
+                    end_line: 1,
+                    language: "rust",
+                    qualified_symbol: Some(&format!("rust:src/callee.rs::to_{id}")),
+                },
             );
         }
 
@@ -3408,6 +4099,7 @@ mod tests {
         assert_eq!(language_for_file_path("src/lib.rs"), "rust");
         assert_eq!(language_for_file_path("src/a.ts"), "typescript");
         assert_eq!(language_for_file_path("src/a.tsx"), "typescript");
+        assert_eq!(language_for_file_path("src/a.mjs"), "javascript");
         assert_eq!(language_for_file_path("src/a.py"), "python");
         assert_eq!(language_for_file_path("src/a.go"), "go");
         assert_eq!(language_for_file_path("README.md"), "unknown");
@@ -3415,25 +4107,52 @@ mod tests {
     }
 
     #[test]
-    fn keyword_helpers_cover_filters_and_tokenization_edges() {
-        let keywords = extract_keywords("Fix the HTTPServer and io in parser with parser");
-        assert_eq!(keywords, vec!["fix", "httpserver", "parser"]);
+    fn context_helpers_cover_term_dedup_scoring_and_packing() {
         assert_eq!(
-            symbol_keywords("HTTPServer::do_work + parseJSON"),
-            vec!["httpserver", "json", "parse", "work"]
+            context_query_terms("Fix the HTTPServer and io in parser with parser"),
+            vec!["fix", "http", "server", "io", "parser"]
         );
+        assert_eq!(context_direct_score(4.0, 4.0), 0.98);
+        assert_eq!(context_direct_score(0.0, 0.0), 0.55);
+
+        let context_match = |symbol: &str, token_cost: u32| ContextMatch {
+            file_path: "src/lib.rs".to_string(),
+            start_line: 1,
+            end_line: 1,
+            symbol: symbol.to_string(),
+            kind: "function".to_string(),
+            why_included: String::new(),
+            confidence: "context_high".to_string(),
+            score: 0.9,
+            token_cost,
+        };
+        let mut matches = vec![
+            context_match("huge", 500),
+            context_match("medium", 60),
+            context_match("large", 80),
+            context_match("small", 30),
+        ];
+        pack_context_matches_by_budget(&mut matches, 100);
         assert_eq!(
-            matched_task_keywords(
-                &[
-                    "parse".to_string(),
-                    "http".to_string(),
-                    "unused".to_string()
-                ],
-                &["parser".to_string(), "httpserver".to_string()]
-            ),
-            vec!["parse".to_string(), "http".to_string()]
+            matches
+                .iter()
+                .map(|item| item.symbol.as_str())
+                .collect::<Vec<_>>(),
+            vec!["huge"]
+        );
+        let mut matches = vec![
+            context_match("medium", 60),
+            context_match("large", 80),
+            context_match("small", 30),
+        ];
+        pack_context_matches_by_budget(&mut matches, 100);
+        assert_eq!(
+            matches
+                .iter()
+                .map(|item| item.symbol.as_str())
+                .collect::<Vec<_>>(),
+            vec!["medium", "small"]
         );
-        assert_eq!(context_direct_score(20, true, 20), 0.98);
     }
 
     #[test]
@@ -3447,8 +4166,22 @@ mod tests {
             Some("./pkg".to_string())
         );
         assert!(!is_pytest_test_file("tests/helper.txt"));
-        assert!(is_typescript_source_file("src/file.ts"));
-        assert!(is_typescript_source_file("src/file.tsx"));
+        assert!(is_node_source_file("src/file.ts"));
+        assert!(is_node_source_file("src/file.tsx"));
+        assert!(is_node_source_file("lib/file.cjs"));
+        assert!(is_node_test_file("src/file.spec.mjs"));
+        for test_file in [
+            "web/app.test.jsx",
+            "web/app.spec.mjs",
+            "web\\app.test.cjs",
+            "./tests/app.rs",
+            "pkg/store_test.go",
+            "src/test_utils.rs",
+            "web/test_helpers.ts",
+        ] {
+            assert!(is_test_like_path(test_file), "{test_file}");
+        }
+        assert!(!is_test_like_path("web/app.jsx"));
         assert_eq!(verification_scope_rank("unknown"), 2);
         assert_eq!(confidence_rank("unknown"), 0);
     }
@@ -3543,6 +4276,7 @@ mod tests {
             &RecommendationRunners {
                 pytest: true,
                 node: NodeTestRunner::None,
+                ..RecommendationRunners::default()
             },
         );
         assert_eq!(pytest_command, "pytest");
@@ -3559,6 +4293,7 @@ mod tests {
             &RecommendationRunners {
                 pytest: false,
                 node: NodeTestRunner::Jest,
+                ..RecommendationRunners::default()
             },
         );
         assert_eq!(jest_command, "npx jest");
@@ -3577,6 +4312,56 @@ mod tests {
         assert_eq!(go_command, "go test ./...");
     }
 
+    #[test]
+    fn parse_file_line_selector_ignores_qualified_symbols() {
+        assert_eq!(
+            parse_file_line_selector("./src/a.rs:42"),
+            Some(("src/a.rs", 42))
+        );
+        assert_eq!(parse_file_line_selector("rust:src/a.rs::foo"), None);
+        assert_eq!(parse_file_line_selector("rust:src/a.rs::42"), None);
+        assert_eq!(parse_file_line_selector(":42"), None);
+        assert_eq!(parse_file_line_selector("foo"), None);
+    }
+
+    #[test]
+    fn cargo_unit_test_commands_select_package_and_target() {
+        let repo = tempdir().expect("temp dir should be created");
+        fs::create_dir_all(repo.path().join("src/bin")).expect("src dir should be created");
+        fs::write(
+            repo.path().join("Cargo.toml"),
+            "[package]\nname = \"tool\"\n",
+        )
+        .expect("manifest should be written");
+        let runners = RecommendationRunners {
+            repo_root: Some(repo.path().to_path_buf()),
+            ..RecommendationRunners::default()
+        };
+        let command = |target: &str| cargo_unit_test_command_for_target(target, &runners);
+
+        assert_eq!(
+            command("src/query.rs::query::tests::ranks"),
+            Some("cargo test -p tool --bin tool query::tests::ranks".to_string())
+        );
+        assert_eq!(
+            command("src/bin/extra.rs::tests::runs"),
+            Some("cargo test -p tool --bin extra tests::runs".to_string())
+        );
+        fs::write(repo.path().join("src/lib.rs"), "").expect("lib root should be written");
+        assert_eq!(
+            command("src/query.rs::query::tests::ranks"),
+            Some("cargo test -p tool --lib query::tests::ranks".to_string())
+        );
+        assert_eq!(
+            cargo_unit_test_command_for_target(
+                "src/query.rs::query::tests",
+                &RecommendationRunners::default()
+            ),
+            Some("cargo test query::tests".to_string())
+        );
+        assert_eq!(command("tests/cli.rs"), None);
+    }
+
     #[test]
     fn runner_detection_and_repo_root_resolution_cover_fallbacks() {
         assert_eq!(repo_root_from_db_path(Path::new("index.db")), None);
@@ -3711,39 +4496,45 @@ mod tests {
         let connection = Connection::open(&db_path).expect("db should open");
         insert_symbol_row(
             &connection,
-            1,
-            "src/lib.rs",
-            "target",
-            "function",
-            10,
-            1,
-            10,
-            "rust",
-            Some("rust:src/lib.rs::target"),
+            SymbolRow {
+                symbol_id: 1,
+                file_path: "src/lib.rs",
+                symbol: "target",
+                kind: "function",
+                line: 10,
+                column: 1,
+                end_line: 10,
+                language: "rust",
+                qualified_symbol: Some("rust:src/lib.rs::target"),
+            },
         );
         insert_symbol_row(
             &connection,
-            2,
-            "src/caller.rs",
-            "impl_user",
-            "function",
-            1,
-            1,
-            1,
-            "rust",
-            Some("rust:src/caller.rs::impl_user"),
+            SymbolRow {
+                symbol_id: 2,
+                file_path: "src/caller.rs",
+                symbol: "impl_user",
+                kind: "function",
+                line: 1,
+                column: 1,
+                end_line: 1,
+                language: "rust",
+                qualified_symbol: Some("rust:src/caller.rs::impl_user"),
+            },
         );
         insert_symbol_row(
             &connection,
-            3,
-            "src/caller.rs",
-            "unknown_user",
-            "function",
-            2,
-            1,
-            2,
-            "rust",
-            Some("rust:src/caller.rs::unknown_user"),
+            SymbolRow {
+                symbol_id: 3,
+                file_path: "src/caller.rs",
+                symbol: "unknown_user",
+                kind: "function",
+                line: 2,
+                column: 1,
+                end_line: 2,
+                language: "rust",
+                qualified_symbol: Some("rust:src/caller.rs::unknown_user"),
+            },
         );
         connection
             .execute(
@@ -3776,39 +4567,45 @@ mod tests {
 
         insert_symbol_row(
             &connection,
-            10,
-            "src/lib.rs",
-            "target",
-            "function",
-            10,
-            2,
-            10,
-            "rust",
-            Some("rust:src/lib.rs::target:alt"),
+            SymbolRow {
+                symbol_id: 10,
+                file_path: "src/lib.rs",
+                symbol: "target",
+                kind: "function",
+                line: 10,
+                column: 2,
+                end_line: 10,
+                language: "rust",
+                qualified_symbol: Some("rust:src/lib.rs::target:alt"),
+            },
         );
         insert_symbol_row(
             &connection,
-            11,
-            "src/dep.rs",
-            "neighbor",
-            "function",
-            20,
-            1,
-            20,
-            "rust",
-            Some("rust:src/dep.rs::neighbor"),
+            SymbolRow {
+                symbol_id: 11,
+                file_path: "src/dep.rs",
+                symbol: "neighbor",
+                kind: "function",
+                line: 20,
+                column: 1,
+                end_line: 20,
+                language: "rust",
+                qualified_symbol: Some("rust:src/dep.rs::neighbor"),
+            },
         );
         insert_symbol_row(
             &connection,
-            12,
-            "src/dep.rs",
-            "neighbor",
-            "function",
-            20,
-            2,
-            20,
-            "rust",
-            Some("rust:src/dep.rs::neighbor:alt"),
+            SymbolRow {
+                symbol_id: 12,
+                file_path: "src/dep.rs",
+                symbol: "neighbor",
+                kind: "function",
+                line: 20,
+                column: 2,
+                end_line: 20,
+                language: "rust",
+                qualified_symbol: Some("rust:src/dep.rs::neighbor:alt"),
+            },
         );
         connection
             .execute(
@@ -3863,6 +4660,7 @@ mod tests {
             &RecommendationRunners {
                 pytest: false,
                 node: NodeTestRunner::Jest,
+                ..RecommendationRunners::default()
             },
         );
         assert_eq!(
@@ -3971,51 +4769,59 @@ mod tests {
 
         insert_symbol_row(
             &connection,
-            1,
-            "src/a.rs",
-            "changed_a",
-            "function",
-            1,
-            1,
-            1,
-            "rust",
-            Some("rust:src/a.rs::changed_a"),
+            SymbolRow {
+                symbol_id: 1,
+                file_path: "src/a.rs",
+                symbol: "changed_a",
+                kind: "function",
+                line: 1,
+                column: 1,
+                end_line: 1,
+                language: "rust",
+                qualified_symbol: Some("rust:src/a.rs::changed_a"),
+            },
         );
         insert_symbol_row(
             &connection,
-            2,
-            "src/a.rs",
-            "changed_a",
-            "variable",
-            1,
-            1,
-            1,
-            "rust",
-            Some("rust:src/a.rs::changed_a"),
+            SymbolRow {
+                symbol_id: 2,
+                file_path: "src/a.rs",
+                symbol: "changed_a",
+                kind: "variable",
+                line: 1,
+                column: 1,
+                end_line: 1,
+                language: "rust",
+                qualified_symbol: Some("rust:src/a.rs::changed_a"),
+            },
         );
         insert_symbol_row(
             &connection,
-            3,
-            "src/a.rs",
-            "changed_b",
-            "function",
-            2,
-            1,
-            2,
-            "rust",
-            Some("rust:src/a.rs::changed_b"),
+            SymbolRow {
+                symbol_id: 3,
+                file_path: "src/a.rs",
+                symbol: "changed_b",
+                kind: "function",
+                line: 2,
+                column: 1,
+                end_line: 2,
+                language: "rust",
+                qualified_symbol: Some("rust:src/a.rs::changed_b"),
+            },
         );
         insert_symbol_row(
             &connection,
-            4,
-            "src/caller.rs",
-            "caller",
-            "function",
-            1,
-            1,
-            1,
-            "rust",
-            Some("rust:src/caller.rs::caller"),
+            SymbolRow {
+                symbol_id: 4,
+                file_path: "src/caller.rs",
+                symbol: "caller",
+                kind: "function",
+                line: 1,
+                column: 1,
+                end_line: 1,
+                language: "rust",
+                qualified_symbol: Some("rust:src/caller.rs::caller"),
+            },
         );
 
         connection
@@ -1,9 +1,10 @@
 use std::collections::BTreeMap;
 use std::path::Path;
 
-use rusqlite::Connection;
 use serde::Serialize;
 
+use crate::store::open_connection;
+
 #[derive(Debug, Clone, Serialize)]
 pub struct TreeReport {
     pub root: TreeNode,
@@ -44,12 +45,12 @@ pub struct TreeReportArgs {
 }
 
 pub fn tree_report(db_path: &Path, args: &TreeReportArgs) -> anyhow::Result<TreeReport> {
-    let connection = Connection::open(db_path)?;
+    let connection = open_connection(db_path)?;
 
     // 1. Query all indexed files with line counts and symbol counts
     let mut file_stats: BTreeMap<String, (Option<u32>, u32)> = BTreeMap::new();
     {
-        let mut stmt = connection.prepare(
+        let mut stmt = connection.prepare_cached(
             "SELECT f.file_path, f.line_count, COUNT(s.symbol_id) as sym_count
              FROM indexed_files f
              LEFT JOIN symbols_v2 s ON f.file_path = s.file_path
@@ -72,7 +73,7 @@ pub fn tree_report(db_path: &Path, args: &TreeReportArgs) -> anyhow::Result<Tree
     let mut imports_map: BTreeMap<String, Vec<String>> = BTreeMap::new();
     let mut used_by_map: BTreeMap<String, Vec<String>> = BTreeMap::new();
     if !args.no_deps {
-        let mut stmt = connection.prepare(
+        let mut stmt = connection.prepare_cached(
             "SELECT DISTINCT src_sym.file_path, tgt_sym.file_path
              FROM symbol_edges_v2 e
              JOIN symbols_v2 src_sym ON e.from_symbol_id = src_sym.symbol_id
@@ -96,7 +97,7 @@ pub fn tree_report(db_path: &Path, args: &TreeReportArgs) -> anyhow::Result<Tree
     // 3. Query symbols per file (if --symbols)
     let mut symbols_map: BTreeMap<String, Vec<TreeSymbol>> = BTreeMap::new();
     if args.show_symbols {
-        let mut stmt = connection.prepare(
+        let mut stmt = connection.prepare_cached(
             "SELECT file_path, symbol, kind, start_line
              FROM symbols_v2
              ORDER BY file_path ASC, start_line ASC, symbol ASC",
@@ -282,7 +283,9 @@ fn truncate_depth(node: &mut TreeNode, current_depth: u32, max_depth: u32) {
 
 // --- Orient report ---
 
-use super::diagnostics::{CircularReport, HealthReport, detect_circular_deps, health_report};
+use super::diagnostics::{
+    CircularReport, FunctionRanking, HealthReport, detect_circular_deps, health_report,
+};
 use super::{HotspotEntry, hotspots};
 
 #[derive(Debug, Clone, Serialize)]
@@ -323,7 +326,7 @@ pub fn orient_report(db_path: &Path, args: &OrientReportArgs) -> anyhow::Result<
             show_symbols: false,
         },
     )?;
-    let health = health_report(db_path, args.top, 0)?;
+    let health = health_report(db_path, args.top, 0, &FunctionRanking::default())?;
     let hotspot_entries = hotspots(db_path, 10)?;
     let circular = detect_circular_deps(db_path, 10)?;
 
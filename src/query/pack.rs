@@ -0,0 +1,291 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+
+use super::{
+    ContextMatch, OutlineEntry, QueryScope, context_matches_scoped, extract_symbol_snippet,
+    language_for_file_path, outline_file,
+};
+use crate::indexer::search::estimated_tokens;
+use crate::store::open_connection;
+
+/// A token-budgeted bundle of source, neighbor signatures, and file outlines for a task.
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextPack {
+    pub task: String,
+    pub budget: u32,
+    /// Estimated tokens used by the packed symbols and outlines.
+    pub token_estimate: u32,
+    pub symbols: Vec<PackedSymbol>,
+    pub outlines: Vec<PackedOutline>,
+    /// Ranked symbols and outlines left out because they did not fit the budget.
+    pub omitted: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PackedSymbol {
+    pub file_path: String,
+    pub language: String,
+    pub symbol: String,
+    pub kind: String,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub why_included: String,
+    pub score: f64,
+    pub source: String,
+    /// Whether trailing source lines were cut to fit the budget.
+    pub source_truncated: bool,
+    pub callers: Vec<PackedSignature>,
+    pub callees: Vec<PackedSignature>,
+    pub token_cost: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PackedSignature {
+    pub file_path: String,
+    pub line: u32,
+    pub symbol: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PackedOutline {
+    pub file_path: String,
+    pub entries: Vec<OutlineEntry>,
+    pub token_cost: u32,
+}
+
+/// Builds a context pack for `task`: the source of the ranked `context` symbols with the
+/// signatures of their direct callers and callees, then outlines of the files they live in.
+///
+/// Symbols whose line ranges overlap a higher-ranked symbol in the same file are dropped, so
+/// a method is not repeated inside its packed class. Symbols are packed in rank order and
+/// skipped when they do not fit the remaining budget; outlines fill what is left. When not
+/// even the top symbol fits, its source is cut to the lines that do.
+#[must_use = "context packs should be consumed by callers"]
+pub fn context_pack(
+    db_path: &Path,
+    task: &str,
+    budget: u32,
+    scope: &QueryScope,
+) -> anyhow::Result<ContextPack> {
+    let ranked = context_matches_scoped(db_path, task, u32::MAX, scope)?;
+    let connection = open_connection(db_path)?;
+
+    let mut pack = ContextPack {
+        task: task.to_string(),
+        budget,
+        token_estimate: 0,
+        symbols: Vec::new(),
+        outlines: Vec::new(),
+        omitted: 0,
+    };
+    let mut packed_ranges = BTreeMap::<String, Vec<(u32, u32)>>::new();
+    for item in &ranked {
+        let ranges = packed_ranges.entry(item.file_path.clone()).or_default();
+        if ranges
+            .iter()
+            .any(|(start, end)| item.start_line <= *end && *start <= item.end_line)
+        {
+            continue;
+        }
+        let Some(mut symbol) = packed_symbol(&connection, db_path, item)? else {
+            continue;
+        };
+        let remaining = budget.saturating_sub(pack.token_estimate);
+        if symbol.token_cost > remaining
+            && (!pack.symbols.is_empty() || !trim_packed_source(&mut symbol, remaining))
+        {
+            pack.omitted += 1;
+            continue;
+        }
+        ranges.push((item.start_line, item.end_line));
+        pack.token_estimate += symbol.token_cost;
+        pack.symbols.push(symbol);
+    }
+
+    let mut outlined = HashSet::new();
+    let files = pack
+        .symbols
+        .iter()
+        .map(|symbol| symbol.file_path.clone())
+        .collect::<Vec<_>>();
+    for file_path in files {
+        if !outlined.insert(file_path.clone()) {
+            continue;
+        }
+        let entries = outline_file(db_path, &file_path)?;
+        let token_cost = estimated_tokens(
+            entries
+                .iter()
+                .map(|entry| outline_entry_text(entry).len() + 1)
+                .sum(),
+        );
+        if token_cost > budget.saturating_sub(pack.token_estimate) {
+            pack.omitted += 1;
+            continue;
+        }
+        pack.token_estimate += token_cost;
+        pack.outlines.push(PackedOutline {
+            file_path,
+            entries,
+            token_cost,
+        });
+    }
+    Ok(pack)
+}
+
+/// The line an outline entry contributes to a pack: its signature, or its kind and name.
+pub fn outline_entry_text(entry: &OutlineEntry) -> String {
+    entry
+        .signature
+        .clone()
+        .unwrap_or_else(|| format!("{} {}", entry.kind, entry.symbol))
+}
+
+fn packed_symbol(
+    connection: &Connection,
+    db_path: &Path,
+    item: &ContextMatch,
+) -> anyhow::Result<Option<PackedSymbol>> {
+    let Some(source) =
+        extract_symbol_snippet(db_path, &item.file_path, item.start_line, item.end_line)
+    else {
+        return Ok(None);
+    };
+    let symbol_id = connection
+        .prepare_cached(
+            "SELECT symbol_id FROM symbols_v2
+             WHERE file_path = ?1 AND start_line = ?2 AND symbol = ?3 AND kind = ?4
+             ORDER BY start_column ASC
+             LIMIT 1",
+        )?
+        .query_row(
+            params![item.file_path, item.start_line, item.symbol, item.kind],
+            |row| row.get::<_, i64>(0),
+        )
+        .optional()?;
+    let (callers, callees) = match symbol_id {
+        Some(symbol_id) => (
+            neighbor_signatures(connection, symbol_id, true)?,
+            neighbor_signatures(connection, symbol_id, false)?,
+        ),
+        None => (Vec::new(), Vec::new()),
+    };
+    let mut symbol = PackedSymbol {
+        file_path: item.file_path.clone(),
+        language: language_for_file_path(&item.file_path).to_string(),
+        symbol: item.symbol.clone(),
+        kind: item.kind.clone(),
+        start_line: item.start_line,
+        end_line: item.end_line,
+        why_included: item.why_included.clone(),
+        score: item.score,
+        source,
+        source_truncated: false,
+        callers,
+        callees,
+        token_cost: 0,
+    };
+    symbol.token_cost = packed_symbol_cost(&symbol);
+    Ok(Some(symbol))
+}
+
+fn packed_symbol_cost(symbol: &PackedSymbol) -> u32 {
+    let signature_bytes = symbol
+        .callers
+        .iter()
+        .chain(&symbol.callees)
+        .map(|neighbor| neighbor.signature.len() + neighbor.file_path.len() + 8)
+        .sum::<usize>();
+    let header_bytes = symbol.file_path.len() + symbol.symbol.len() + symbol.why_included.len();
+    estimated_tokens(symbol.source.len() + signature_bytes + header_bytes)
+}
+
+/// Cuts the source to the leading lines that fit in `budget`, dropping neighbor signatures
+/// first. Returns false when not even the first line fits.
+fn trim_packed_source(symbol: &mut PackedSymbol, budget: u32) -> bool {
+    symbol.callers.clear();
+    symbol.callees.clear();
+    let lines = symbol
+        .source
+        .lines()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    for keep in (1..=lines.len()).rev() {
+        symbol.source = lines[..keep].join("\n");
+        symbol.token_cost = packed_symbol_cost(symbol);
+        if symbol.token_cost <= budget {
+            symbol.source_truncated = keep < lines.len();
+            return true;
+        }
+    }
+    false
+}
+
+/// Signatures of the symbols calling (`incoming`) or called by the given symbol.
+fn neighbor_signatures(
+    connection: &Connection,
+    symbol_id: i64,
+    incoming: bool,
+) -> anyhow::Result<Vec<PackedSignature>> {
+    let sql = if incoming {
+        "SELECT DISTINCT n.file_path, n.start_line, n.symbol, n.kind, n.signature
+         FROM symbol_edges_v2 e
+         JOIN symbols_v2 n ON n.symbol_id = e.from_symbol_id
+         WHERE e.to_symbol_id = ?1 AND e.edge_kind = 'calls' AND e.from_symbol_id != ?1
+         ORDER BY n.file_path ASC, n.start_line ASC, n.symbol ASC"
+    } else {
+        "SELECT DISTINCT n.file_path, n.start_line, n.symbol, n.kind, n.signature
+         FROM symbol_edges_v2 e
+         JOIN symbols_v2 n ON n.symbol_id = e.to_symbol_id
+         WHERE e.from_symbol_id = ?1 AND e.edge_kind = 'calls' AND e.to_symbol_id != ?1
+         ORDER BY n.file_path ASC, n.start_line ASC, n.symbol ASC"
+    };
+    let mut statement = connection.prepare_cached(sql)?;
+    let rows = statement.query_map(params![symbol_id], |row| {
+        let symbol = row.get::<_, String>(2)?;
+        let kind = row.get::<_, String>(3)?;
+        Ok(PackedSignature {
+            file_path: row.get(0)?,
+            line: row.get::<_, i64>(1)? as u32,
+            signature: row
+                .get::<_, Option<String>>(4)?
+                .unwrap_or_else(|| format!("{kind} {symbol}")),
+            symbol,
+        })
+    })?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PackedSymbol, trim_packed_source};
+
+    #[test]
+    fn trim_packed_source_keeps_leading_lines_that_fit() {
+        let mut symbol = PackedSymbol {
+            file_path: "a.rs".to_string(),
+            language: "rust".to_string(),
+            symbol: "f".to_string(),
+            kind: "function".to_string(),
+            start_line: 1,
+            end_line: 3,
+            why_included: String::new(),
+            score: 0.9,
+            source: format!("fn f() {{\n{}\n}}", "    x();".repeat(20)),
+            source_truncated: false,
+            callers: Vec::new(),
+            callees: Vec::new(),
+            token_cost: 0,
+        };
+        assert!(trim_packed_source(&mut symbol, 10));
+        assert_eq!(symbol.source, "fn f() {");
+        assert!(symbol.source_truncated);
+        assert!(symbol.token_cost <= 10);
+
+        assert!(!trim_packed_source(&mut symbol, 1));
+    }
+}
@@ -1,13 +1,20 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
 
-use rusqlite::{Connection, params};
+use rusqlite::params;
 use serde::Serialize;
 
+use crate::query::cohesion::{FileGraph, is_public, load_file_graph, proposed_file};
+use crate::query::flow::{EarlyExit, FlowParameter, FlowVariable, RangeCall, analyze_range};
+use crate::query::parse_file_line_selector;
+use crate::store::open_connection;
+
 #[derive(Debug, Clone, Serialize)]
 pub struct BoundarySymbol {
     pub symbol: String,
     pub kind: String,
     pub is_public: bool,
+    pub visibility: Option<String>,
     pub external_references: u32,
 }
 
@@ -19,15 +26,18 @@ pub struct BoundaryReport {
 }
 
 pub fn boundary_analysis(db_path: &Path, file_path: &str) -> anyhow::Result<BoundaryReport> {
-    let connection = Connection::open(db_path)?;
-    let mut stmt = connection.prepare(
+    let connection = open_connection(db_path)?;
+    let mut stmt = connection.prepare_cached(
         "SELECT s.symbol,
                 s.kind,
                 CASE
-                    WHEN s.visibility IN ('public', 'pub', 'export') THEN 1
-                    WHEN s.signature LIKE 'pub %' OR s.signature LIKE 'pub(%' THEN 1
+                    WHEN s.visibility IS NULL
+                        THEN COALESCE(s.signature LIKE 'pub %' OR s.signature LIKE 'pub(%', 0)
+                    WHEN s.visibility IN ('public', 'pub', 'export', 'export default') THEN 1
+                    WHEN s.visibility LIKE 'pub(%' AND s.visibility != 'pub(self)' THEN 1
                     ELSE 0
                 END AS is_public,
+                s.visibility,
                 SUM(
                     CASE
                         WHEN caller.file_path IS NOT NULL AND caller.file_path != s.file_path THEN 1
@@ -46,7 +56,8 @@ pub fn boundary_analysis(db_path: &Path, file_path: &str) -> anyhow::Result<Boun
             symbol: row.get(0)?,
             kind: row.get(1)?,
             is_public: row.get::<_, u32>(2)? > 0,
-            external_references: row.get(3)?,
+            visibility: row.get(3)?,
+            external_references: row.get(4)?,
         })
     })?;
 
@@ -78,6 +89,17 @@ pub struct ExtractCheckReport {
     pub extract_end_line: u32,
     pub estimated_line_count: u32,
     pub signature: Option<String>,
+    /// Variables declared before the range and used inside it.
+    pub parameters: Vec<FlowParameter>,
+    /// Variables assigned inside the range and read after it.
+    pub return_values: Vec<FlowVariable>,
+    /// Variables that live only inside the range.
+    pub locals: Vec<FlowVariable>,
+    pub early_exits: Vec<EarlyExit>,
+    pub calls: Vec<RangeCall>,
+    pub statement_aligned: bool,
+    /// The range covers whole statements and no control flow leaves it.
+    pub mechanically_safe: bool,
     pub warnings: Vec<String>,
 }
 
@@ -110,31 +132,75 @@ pub fn parse_line_range(input: &str) -> anyhow::Result<LineRange> {
     Ok(LineRange { start, end })
 }
 
+type FunctionDefinition = (String, String, u32, u32, Option<String>);
+
+/// Resolves an `extract-check` selector to one function or method: `<file>:<line>` picks the
+/// innermost function spanning the line, while a name may be bare, qualified, or
+/// `Container::name` and must match exactly one definition.
+fn select_function(
+    connection: &rusqlite::Connection,
+    selector: &str,
+) -> anyhow::Result<FunctionDefinition> {
+    let row = |row: &rusqlite::Row<'_>| -> rusqlite::Result<FunctionDefinition> {
+        Ok((
+            row.get(0)?,
+            row.get(1)?,
+            row.get(2)?,
+            row.get(3)?,
+            row.get(4)?,
+        ))
+    };
+    let candidates = if let Some((file_path, line)) = parse_file_line_selector(selector) {
+        let mut stmt = connection.prepare_cached(
+            "SELECT symbol, file_path, start_line, end_line, signature
+             FROM symbols_v2
+             WHERE file_path = ?1 AND start_line <= ?2 AND end_line >= ?2
+               AND kind IN ('function', 'method')
+             ORDER BY end_line - start_line, start_line DESC, symbol_id
+             LIMIT 1",
+        )?;
+        stmt.query_map(params![file_path, line], row)?
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        let mut stmt = connection.prepare_cached(
+            "SELECT symbol, file_path, start_line, end_line, signature
+             FROM symbols_v2
+             WHERE kind IN ('function', 'method')
+               AND (symbol = ?1 OR qualified_symbol = ?1 OR container || '::' || symbol = ?1)
+             ORDER BY file_path, start_line, symbol_id",
+        )?;
+        stmt.query_map(params![selector], row)?
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    match candidates.as_slice() {
+        [] => anyhow::bail!("symbol '{selector}' not found as a function"),
+        [definition] => Ok(definition.clone()),
+        _ => {
+            let locations = candidates
+                .iter()
+                .map(|(_, file_path, start_line, _, _)| format!("{file_path}:{start_line}"))
+                .collect::<Vec<_>>();
+            anyhow::bail!(
+                "'{selector}' matches {} functions ({}); pass <file>:<line> to pick one",
+                candidates.len(),
+                locations.join(", ")
+            )
+        }
+    }
+}
+
+/// Checks whether lines of `symbol` can be extracted into a new function.
+///
+/// Among the functions and methods named `symbol`, the first whose bounds contain the range is
+/// analyzed. Variable flow across the range boundary comes from the file's syntax tree.
 pub fn extract_check(
     db_path: &Path,
     symbol: &str,
     range: LineRange,
 ) -> anyhow::Result<ExtractCheckReport> {
-    let connection = Connection::open(db_path)?;
-    let mut stmt = connection.prepare(
-        "SELECT file_path, start_line, end_line, signature
-         FROM symbols_v2
-         WHERE symbol = ?1 AND kind = 'function'
-         ORDER BY file_path ASC, start_line ASC
-         LIMIT 1",
-    )?;
-    let row = stmt
-        .query_row(params![symbol], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, u32>(1)?,
-                row.get::<_, u32>(2)?,
-                row.get::<_, Option<String>>(3)?,
-            ))
-        })
-        .map_err(|_| anyhow::anyhow!("symbol '{symbol}' not found as a function"))?;
-
-    let (file_path, function_start_line, function_end_line, signature) = row;
+    let connection = open_connection(db_path)?;
+    let (symbol, file_path, function_start_line, function_end_line, signature) =
+        select_function(&connection, symbol)?;
     if range.start < function_start_line || range.end > function_end_line {
         anyhow::bail!(
             "extract range {}-{} is outside function bounds {}-{}",
@@ -154,8 +220,68 @@ pub fn extract_check(
         warnings.push("range is very small; extraction may not be worthwhile".to_string());
     }
 
+    let source = db_path
+        .parent()
+        .and_then(Path::parent)
+        .and_then(|repo_root| std::fs::read_to_string(repo_root.join(&file_path)).ok());
+    let flow = match source {
+        Some(source) => analyze_range(
+            &file_path,
+            &source,
+            (function_start_line, function_end_line),
+            (range.start, range.end),
+        )?,
+        None => None,
+    };
+    let Some(flow) = flow else {
+        warnings.push(format!(
+            "variable flow is unavailable for {file_path}; review the range by hand"
+        ));
+        return Ok(ExtractCheckReport {
+            symbol,
+            file_path,
+            function_start_line,
+            function_end_line,
+            extract_start_line: range.start,
+            extract_end_line: range.end,
+            estimated_line_count,
+            signature,
+            parameters: Vec::new(),
+            return_values: Vec::new(),
+            locals: Vec::new(),
+            early_exits: Vec::new(),
+            calls: Vec::new(),
+            statement_aligned: false,
+            mechanically_safe: false,
+            warnings,
+        });
+    };
+
+    if let Some((start, end)) = flow.split_statement {
+        warnings.push(format!(
+            "range cuts through the statement at lines {start}-{end}"
+        ));
+    }
+    for exit in &flow.early_exits {
+        let target = match exit.kind.as_str() {
+            "break" | "continue" => "a loop outside the range",
+            _ => "the enclosing function",
+        };
+        warnings.push(format!(
+            "`{}` at line {} jumps to {target}",
+            exit.kind, exit.line
+        ));
+    }
+    if flow.return_values.len() > 1 {
+        warnings.push(format!(
+            "{} variables are read after the range and must be returned together",
+            flow.return_values.len()
+        ));
+    }
+    let mechanically_safe = flow.statement_aligned && flow.early_exits.is_empty();
+
     Ok(ExtractCheckReport {
-        symbol: symbol.to_string(),
+        symbol,
         file_path,
         function_start_line,
         function_end_line,
@@ -163,6 +289,311 @@ pub fn extract_check(
         extract_end_line: range.end,
         estimated_line_count,
         signature,
+        parameters: flow.parameters,
+        return_values: flow.return_values,
+        locals: flow.locals,
+        early_exits: flow.early_exits,
+        calls: flow.calls,
+        statement_aligned: flow.statement_aligned,
+        mechanically_safe,
         warnings,
     })
 }
+
+/// How `split_check` groups a file's definitions.
+#[derive(Debug, Clone)]
+pub enum SplitGrouping {
+    /// Clusters proposed from the intra-file reference graph.
+    Auto,
+    /// Symbol names per group; unlisted definitions stay in the original file.
+    Manual(Vec<Vec<String>>),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SplitImport {
+    pub symbol: String,
+    pub from_file: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SplitGroup {
+    pub name: String,
+    pub target_file: String,
+    /// The group keeps the original file.
+    pub stays: bool,
+    pub symbols: Vec<String>,
+    pub line_count: u32,
+    pub internal_edges: u32,
+    pub external_edges: u32,
+    pub cohesion: f64,
+    /// Symbols of other groups this group references, which become new imports.
+    pub imports: Vec<SplitImport>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrossGroupEdge {
+    pub from_symbol: String,
+    pub from_file: String,
+    pub to_symbol: String,
+    pub to_file: String,
+    pub edge_kind: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VisibilityChange {
+    pub symbol: String,
+    pub file: String,
+    pub current: Option<String>,
+    pub required: String,
+    pub used_from: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SplitCheckReport {
+    pub file_path: String,
+    pub groups: Vec<SplitGroup>,
+    pub cross_group_edges: Vec<CrossGroupEdge>,
+    pub visibility_changes: Vec<VisibilityChange>,
+    /// Modularity of the proposed split; higher means fewer cross-file references.
+    pub modularity: f64,
+    pub warnings: Vec<String>,
+}
+
+/// Parses `--groups`: groups separated by `:`, symbols within a group by `,`.
+pub fn parse_split_groups(input: &str) -> anyhow::Result<Vec<Vec<String>>> {
+    let groups = input
+        .split(':')
+        .map(|group| {
+            group
+                .split(',')
+                .map(str::trim)
+                .filter(|symbol| !symbol.is_empty())
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    if groups.iter().any(Vec::is_empty) {
+        anyhow::bail!("invalid --groups format: expected <symbol>,<symbol>:<symbol>,...");
+    }
+    Ok(groups)
+}
+
+/// Checks splitting `file_path` into groups of definitions.
+///
+/// Every group is scored by cohesion, and each reference between groups is reported along
+/// with the import it needs and any visibility the referenced symbol must gain. Definitions
+/// outside every group stay in the original file; when there are none, the largest group
+/// keeps it.
+pub fn split_check(
+    db_path: &Path,
+    file_path: &str,
+    grouping: &SplitGrouping,
+) -> anyhow::Result<SplitCheckReport> {
+    let connection = open_connection(db_path)?;
+    let repo_root = db_path.parent().and_then(Path::parent).unwrap_or(db_path);
+    let graph = load_file_graph(&connection, repo_root, file_path)?;
+    if graph.units.is_empty() {
+        anyhow::bail!("no indexed definitions found in '{file_path}'");
+    }
+
+    let mut group_units = match grouping {
+        SplitGrouping::Auto => {
+            let mut communities = BTreeMap::<usize, Vec<usize>>::new();
+            for (unit, community) in graph.communities().into_iter().enumerate() {
+                communities.entry(community).or_default().push(unit);
+            }
+            communities
+                .into_values()
+                .filter(|units| units.len() > 1)
+                .collect::<Vec<_>>()
+        }
+        SplitGrouping::Manual(groups) => manual_group_units(&graph, groups)?,
+    };
+    let grouped = group_units
+        .iter()
+        .flatten()
+        .copied()
+        .collect::<BTreeSet<_>>();
+    let remainder = (0..graph.units.len())
+        .filter(|unit| !grouped.contains(unit))
+        .collect::<Vec<_>>();
+    let staying = if remainder.is_empty() {
+        group_units
+            .iter()
+            .enumerate()
+            .max_by_key(|(index, units)| {
+                let lines = units
+                    .iter()
+                    .map(|&unit| graph.units[unit].line_count)
+                    .sum::<u32>();
+                (lines, std::cmp::Reverse(*index))
+            })
+            .map_or(0, |(index, _)| index)
+    } else {
+        group_units.push(remainder);
+        group_units.len() - 1
+    };
+
+    let mut taken = BTreeSet::new();
+    let mut group_of = vec![0; graph.units.len()];
+    let mut groups = Vec::new();
+    for (index, units) in group_units.iter().enumerate() {
+        for &unit in units {
+            group_of[unit] = index;
+        }
+        let stays = index == staying;
+        let name = graph.units[graph.entry_unit(units)].symbol.clone();
+        let target_file = if stays {
+            file_path.to_string()
+        } else {
+            proposed_file(file_path, &name, &mut taken)
+        };
+        let cluster = graph.cluster(name, target_file, units);
+        groups.push(SplitGroup {
+            name: cluster.name,
+            target_file: cluster.proposed_file,
+            stays,
+            symbols: cluster.symbols,
+            line_count: cluster.line_count,
+            internal_edges: cluster.internal_edges,
+            external_edges: cluster.external_edges,
+            cohesion: cluster.cohesion,
+            imports: Vec::new(),
+        });
+    }
+
+    // Go files in one directory share a package, so moved code needs no imports.
+    let needs_imports = graph.language != "go";
+    let mut cross_group_edges = Vec::new();
+    let mut imports = BTreeMap::<usize, BTreeSet<(String, String)>>::new();
+    let mut used_from = BTreeMap::<usize, BTreeSet<String>>::new();
+    let mut dependencies = BTreeSet::new();
+    for edge in &graph.edges {
+        let (from_group, to_group) = (group_of[edge.from], group_of[edge.to]);
+        if from_group == to_group {
+            continue;
+        }
+        let to_symbol = graph.units[edge.to].symbol.clone();
+        let to_file = groups[to_group].target_file.clone();
+        cross_group_edges.push(CrossGroupEdge {
+            from_symbol: graph.units[edge.from].symbol.clone(),
+            from_file: groups[from_group].target_file.clone(),
+            to_symbol: to_symbol.clone(),
+            to_file: to_file.clone(),
+            edge_kind: edge.kind.clone(),
+        });
+        if needs_imports {
+            imports
+                .entry(from_group)
+                .or_default()
+                .insert((to_symbol, to_file));
+        }
+        used_from
+            .entry(edge.to)
+            .or_default()
+            .insert(groups[from_group].target_file.clone());
+        dependencies.insert((from_group, to_group));
+    }
+    for (group, group_imports) in imports {
+        groups[group].imports = group_imports
+            .into_iter()
+            .map(|(symbol, from_file)| SplitImport { symbol, from_file })
+            .collect();
+    }
+
+    let mut visibility_changes = Vec::new();
+    for (unit, used_from) in used_from {
+        let definition = &graph.units[unit];
+        let current = definition.visibility.as_deref();
+        let required = match graph.language {
+            // Child modules already see the private items of the file they split from.
+            "rust" if !groups[group_of[unit]].stays && !is_public(current) => "pub(super)",
+            "typescript" | "javascript"
+                if !matches!(current, Some("export" | "export default")) =>
+            {
+                "export"
+            }
+            "python" if !is_public(current) => "public",
+            _ => continue,
+        };
+        visibility_changes.push(VisibilityChange {
+            symbol: definition.symbol.clone(),
+            file: groups[group_of[unit]].target_file.clone(),
+            current: definition.visibility.clone(),
+            required: required.to_string(),
+            used_from: used_from.into_iter().collect(),
+        });
+    }
+
+    let mut warnings = Vec::new();
+    if graph.edges.is_empty() {
+        warnings.push("no references between definitions in this file".to_string());
+    }
+    for &(from, to) in &dependencies {
+        if from < to && dependencies.contains(&(to, from)) {
+            warnings.push(format!(
+                "{} and {} would reference each other",
+                groups[from].target_file, groups[to].target_file
+            ));
+        }
+    }
+    if groups.len() < 2 {
+        warnings.push("no cohesive groups to split out".to_string());
+    }
+
+    Ok(SplitCheckReport {
+        file_path: file_path.to_string(),
+        modularity: graph.modularity(&group_of),
+        groups,
+        cross_group_edges,
+        visibility_changes,
+        warnings,
+    })
+}
+
+/// Maps `--groups` symbol names to graph units; members select the type that owns them.
+fn manual_group_units(
+    graph: &FileGraph,
+    groups: &[Vec<String>],
+) -> anyhow::Result<Vec<Vec<usize>>> {
+    let mut unknown = Vec::new();
+    let mut duplicated = BTreeSet::new();
+    let mut assigned = BTreeMap::<usize, usize>::new();
+    let mut group_units = Vec::new();
+    for (index, symbols) in groups.iter().enumerate() {
+        let mut units = BTreeSet::new();
+        for symbol in symbols {
+            let matches = graph
+                .units
+                .iter()
+                .enumerate()
+                .filter(|(_, unit)| &unit.symbol == symbol || unit.members.contains(symbol))
+                .map(|(unit, _)| unit)
+                .collect::<Vec<_>>();
+            if matches.is_empty() {
+                unknown.push(symbol.clone());
+            }
+            for unit in matches {
+                if *assigned.entry(unit).or_insert(index) != index {
+                    duplicated.insert(graph.units[unit].symbol.clone());
+                }
+                units.insert(unit);
+            }
+        }
+        group_units.push(units.into_iter().collect::<Vec<_>>());
+    }
+    if !unknown.is_empty() {
+        anyhow::bail!(
+            "unknown symbols in --groups for '{}': {}",
+            graph.file_path,
+            unknown.join(", ")
+        );
+    }
+    if !duplicated.is_empty() {
+        anyhow::bail!(
+            "symbols listed in more than one group: {}",
+            duplicated.into_iter().collect::<Vec<_>>().join(", ")
+        );
+    }
+    Ok(group_units)
+}
@@ -0,0 +1,958 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use rusqlite::{Connection, params};
+use serde::Serialize;
+use tree_sitter::{Parser, Point};
+
+use crate::query::flow::language_for_path;
+use crate::query::{language_for_file_path, parse_file_line_selector};
+use crate::store::open_connection;
+
+/// A rename computed from the index, with the unified diff it would apply.
+#[derive(Debug, Clone, Serialize)]
+pub struct RenamePatch {
+    pub symbol: String,
+    pub new_name: String,
+    pub definition: RenameTarget,
+    pub edits: Vec<RenameEdit>,
+    /// Occurrences of the name that were left alone, with the reason.
+    pub skipped: Vec<SkippedOccurrence>,
+    pub warnings: Vec<String>,
+    pub files_changed: usize,
+    pub patch: String,
+    #[serde(skip)]
+    rewritten: BTreeMap<String, String>,
+    #[serde(skip)]
+    repo_root: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RenameTarget {
+    #[serde(skip)]
+    pub(crate) symbol_id: i64,
+    pub file_path: String,
+    pub line: u32,
+    pub kind: String,
+    pub container: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RenameEdit {
+    pub file_path: String,
+    pub line: u32,
+    pub column: u32,
+    /// `definition`, `import`, `reference`, or `text` (a string or comment).
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedOccurrence {
+    pub file_path: String,
+    pub line: u32,
+    pub column: u32,
+    /// `other_definition`, `ambiguous`, `text` (a string or comment), or `unresolved` (code the
+    /// index has no reference for, such as a method call).
+    pub reason: String,
+}
+
+#[derive(Debug, Clone)]
+struct NamedSymbol {
+    symbol_id: i64,
+    file_path: String,
+    kind: String,
+    container: Option<String>,
+    start_line: u32,
+    start_column: u32,
+    end_line: u32,
+}
+
+impl NamedSymbol {
+    fn is_import(&self) -> bool {
+        self.kind == "import"
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Binding {
+    Target,
+    Other,
+    Ambiguous,
+}
+
+impl Binding {
+    fn skip_reason(self) -> &'static str {
+        match self {
+            Binding::Other => "other_definition",
+            _ => "ambiguous",
+        }
+    }
+}
+
+/// Resolves which occurrences of a name bind to one definition.
+struct Resolver<'a> {
+    connection: &'a Connection,
+    target: NamedSymbol,
+    /// Every definition and import with the target's name.
+    named: Vec<NamedSymbol>,
+    /// `from_symbol_id -> [(to_symbol_id, edge_kind)]` for edges into `named`.
+    edges: HashMap<i64, Vec<(i64, String)>>,
+    /// `file_path -> [(symbol_id, start_line, end_line)]` of non-import symbols.
+    scopes: HashMap<String, Vec<(i64, u32, u32)>>,
+    sources: HashMap<String, Option<String>>,
+    repo_root: PathBuf,
+}
+
+/// Computes the rename of the definition selected by `selector` (a name, qualified symbol,
+/// `Container::name`, or `file:line`) to `new_name`, without writing anything.
+///
+/// The definition and every reference and import that resolves to it are rewritten. A
+/// reference binds to the target when the symbol enclosing it has an edge to the target (or to an
+/// import of it); otherwise it follows the indexer's scope order: a same-named symbol in the same
+/// file, then the unique definition of that name. Occurrences that resolve elsewhere or cannot be
+/// resolved are reported in `skipped`. Strings and comments are only rewritten with
+/// `include_text`, and only in files the rename already touches.
+pub fn rename_patch(
+    db_path: &Path,
+    selector: &str,
+    new_name: &str,
+    include_text: bool,
+) -> anyhow::Result<RenamePatch> {
+    if !is_identifier(new_name) {
+        anyhow::bail!("'{new_name}' is not a valid identifier");
+    }
+    let connection = open_connection(db_path)?;
+    let target = select_target(&connection, selector)?;
+    let name = target_name(&connection, target.symbol_id)?;
+    if name == new_name {
+        anyhow::bail!("'{selector}' is already named '{new_name}'");
+    }
+    let repo_root = db_path
+        .parent()
+        .and_then(Path::parent)
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let mut resolver = Resolver {
+        connection: &connection,
+        named: named_symbols(&connection, &name)?,
+        edges: edges_into(&connection, &name)?,
+        target,
+        scopes: HashMap::new(),
+        sources: HashMap::new(),
+        repo_root: repo_root.clone(),
+    };
+
+    let mut edits = BTreeSet::new();
+    let mut skipped = Vec::new();
+    edits.insert(RenameEdit {
+        file_path: resolver.target.file_path.clone(),
+        line: resolver.target.start_line,
+        column: resolver.target.start_column,
+        kind: "definition".to_string(),
+    });
+    let imports = resolver
+        .named
+        .iter()
+        .filter(|symbol| symbol.is_import())
+        .cloned()
+        .collect::<Vec<_>>();
+    for import in &imports {
+        let binding = resolver.import_binding(import);
+        for (line, column) in resolver.import_name_positions(import, &name) {
+            if binding == Binding::Target {
+                edits.insert(edit(&import.file_path, line, column, "import"));
+            } else {
+                skipped.push(skip(&import.file_path, line, column, binding.skip_reason()));
+            }
+        }
+    }
+    for (file_path, line, column) in occurrences(&connection, "ast_references", &name)? {
+        // Some adapters also record the import statement itself as a reference.
+        let in_import = imports.iter().any(|import| {
+            import.file_path == file_path && import.start_line <= line && line <= import.end_line
+        });
+        if in_import {
+            continue;
+        }
+        match resolver.reference_binding(&file_path, line, column)? {
+            Binding::Target => {
+                edits.insert(edit(&file_path, line, column, "reference"));
+            }
+            binding => skipped.push(skip(&file_path, line, column, binding.skip_reason())),
+        }
+    }
+
+    let touched = edits
+        .iter()
+        .map(|edit| edit.file_path.clone())
+        .collect::<BTreeSet<_>>();
+    let claimed = edits
+        .iter()
+        .map(|edit| (edit.file_path.clone(), edit.line, edit.column))
+        .chain(
+            skipped
+                .iter()
+                .map(|skip| (skip.file_path.clone(), skip.line, skip.column)),
+        )
+        .chain(
+            resolver
+                .named
+                .iter()
+                .filter(|symbol| !symbol.is_import())
+                .map(|symbol| {
+                    (
+                        symbol.file_path.clone(),
+                        symbol.start_line,
+                        symbol.start_column,
+                    )
+                }),
+        )
+        .collect::<BTreeSet<_>>();
+    // Callers the index links to the target are reviewed too, since their occurrences may be
+    // calls the adapter did not record as references.
+    let reviewed = touched
+        .iter()
+        .cloned()
+        .chain(caller_files(&connection, resolver.target.symbol_id)?)
+        .collect::<BTreeSet<_>>();
+    let mut text = occurrences(&connection, "text_occurrences", &name)?;
+    text.retain(|occurrence| reviewed.contains(&occurrence.0) && !claimed.contains(occurrence));
+    for file_path in &reviewed {
+        let positions = text
+            .iter()
+            .filter(|occurrence| &occurrence.0 == file_path)
+            .map(|occurrence| (occurrence.1, occurrence.2))
+            .collect::<Vec<_>>();
+        let Some(source) = resolver.source(file_path) else {
+            continue;
+        };
+        let strings = text_positions(file_path, &source, &positions);
+        for (line, column) in positions {
+            if !strings.contains(&(line, column)) {
+                skipped.push(skip(file_path, line, column, "unresolved"));
+            } else if include_text && touched.contains(file_path) {
+                edits.insert(edit(file_path, line, column, "text"));
+            } else {
+                skipped.push(skip(file_path, line, column, "text"));
+            }
+        }
+    }
+    skipped.sort_by(|left, right| {
+        (&left.file_path, left.line, left.column).cmp(&(&right.file_path, right.line, right.column))
+    });
+
+    let warnings = conflict_warnings(&connection, &touched, new_name)?;
+    let edits = edits.into_iter().collect::<Vec<_>>();
+    let mut rewritten = BTreeMap::new();
+    let mut patch = String::new();
+    for file_path in &touched {
+        let Some(source) = resolver.source(file_path) else {
+            anyhow::bail!("unable to read {file_path}");
+        };
+        let file_edits = edits
+            .iter()
+            .filter(|edit| &edit.file_path == file_path)
+            .collect::<Vec<_>>();
+        let updated = rewrite(file_path, &source, &file_edits, &name, new_name)?;
+        patch.push_str(&unified_diff(file_path, &source, &updated));
+        rewritten.insert(file_path.clone(), updated);
+    }
+    let target = &resolver.target;
+    Ok(RenamePatch {
+        symbol: name,
+        new_name: new_name.to_string(),
+        definition: RenameTarget {
+            symbol_id: target.symbol_id,
+            file_path: target.file_path.clone(),
+            line: target.start_line,
+            kind: target.kind.clone(),
+            container: target.container.clone(),
+        },
+        edits,
+        skipped,
+        warnings,
+        files_changed: rewritten.len(),
+        patch,
+        rewritten,
+        repo_root,
+    })
+}
+
+/// Writes the rewritten files of `patch` into the repository. Nothing is written while an
+/// occurrence is ambiguous, and every file is staged next to its original before any is renamed
+/// into place, so a failed write leaves the tree untouched.
+pub fn apply_rename(patch: &RenamePatch) -> anyhow::Result<()> {
+    let ambiguous = patch
+        .skipped
+        .iter()
+        .filter(|skipped| skipped.reason == "ambiguous")
+        .map(|skipped| format!("{}:{}:{}", skipped.file_path, skipped.line, skipped.column))
+        .collect::<Vec<_>>();
+    if !ambiguous.is_empty() {
+        anyhow::bail!(
+            "refusing to apply: {} occurrences of '{}' are ambiguous ({}); review the patch and \
+             rename them by hand",
+            ambiguous.len(),
+            patch.symbol,
+            ambiguous.join(", ")
+        );
+    }
+    let mut staged = Vec::new();
+    for (file_path, contents) in &patch.rewritten {
+        let path = patch.repo_root.join(file_path);
+        let staging = staging_path(&path);
+        staged.push((staging.clone(), path.clone()));
+        let written = std::fs::write(&staging, contents).and_then(|()| {
+            let permissions = std::fs::metadata(&path)?.permissions();
+            std::fs::set_permissions(&staging, permissions)
+        });
+        if let Err(error) = written {
+            for (staging, _) in &staged {
+                let _ = std::fs::remove_file(staging);
+            }
+            return Err(anyhow::anyhow!("unable to write {file_path}: {error}"));
+        }
+    }
+    for (staging, path) in &staged {
+        std::fs::rename(staging, path)?;
+    }
+    Ok(())
+}
+
+fn staging_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".repo-scout-rename");
+    path.with_file_name(name)
+}
+
+fn edit(file_path: &str, line: u32, column: u32, kind: &str) -> RenameEdit {
+    RenameEdit {
+        file_path: file_path.to_string(),
+        line,
+        column,
+        kind: kind.to_string(),
+    }
+}
+
+fn skip(file_path: &str, line: u32, column: u32, reason: &str) -> SkippedOccurrence {
+    SkippedOccurrence {
+        file_path: file_path.to_string(),
+        line,
+        column,
+        reason: reason.to_string(),
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+const SYMBOL_COLUMNS: &str =
+    "symbol_id, file_path, kind, container, start_line, start_column, end_line";
+
+fn symbol_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<NamedSymbol> {
+    Ok(NamedSymbol {
+        symbol_id: row.get(0)?,
+        file_path: row.get(1)?,
+        kind: row.get(2)?,
+        container: row.get(3)?,
+        start_line: row.get(4)?,
+        start_column: row.get(5)?,
+        end_line: row.get(6)?,
+    })
+}
+
+fn select_target(connection: &Connection, selector: &str) -> anyhow::Result<NamedSymbol> {
+    let candidates = if let Some((file_path, line)) = parse_file_line_selector(selector) {
+        let mut statement = connection.prepare(&format!(
+            "SELECT {SYMBOL_COLUMNS}
+             FROM symbols_v2
+             WHERE file_path = ?1 AND start_line <= ?2 AND end_line >= ?2 AND kind != 'import'
+             ORDER BY end_line - start_line, start_line DESC, symbol_id
+             LIMIT 1"
+        ))?;
+        let rows = statement.query_map(params![file_path, line], symbol_row)?;
+        rows.collect::<Result<Vec<_>, _>>()?
+    } else {
+        let mut statement = connection.prepare(&format!(
+            "SELECT {SYMBOL_COLUMNS}
+             FROM symbols_v2
+             WHERE kind != 'import'
+               AND (symbol = ?1 OR qualified_symbol = ?1 OR container || '::' || symbol = ?1)
+             ORDER BY file_path, start_line, symbol_id"
+        ))?;
+        let rows = statement.query_map([selector], symbol_row)?;
+        rows.collect::<Result<Vec<_>, _>>()?
+    };
+    match candidates.as_slice() {
+        [] => anyhow::bail!("symbol '{selector}' not found"),
+        [target] => Ok(target.clone()),
+        _ => {
+            let locations = candidates
+                .iter()
+                .map(|candidate| format!("{}:{}", candidate.file_path, candidate.start_line))
+                .collect::<Vec<_>>();
+            anyhow::bail!(
+                "'{selector}' matches {} definitions ({}); pass <file>:<line> to pick one",
+                candidates.len(),
+                locations.join(", ")
+            )
+        }
+    }
+}
+
+fn target_name(connection: &Connection, symbol_id: i64) -> anyhow::Result<String> {
+    connection
+        .query_row(
+            "SELECT symbol FROM symbols_v2 WHERE symbol_id = ?1",
+            [symbol_id],
+            |row| row.get(0),
+        )
+        .map_err(Into::into)
+}
+
+fn named_symbols(connection: &Connection, name: &str) -> anyhow::Result<Vec<NamedSymbol>> {
+    let mut statement = connection.prepare(&format!(
+        "SELECT {SYMBOL_COLUMNS}
+         FROM symbols_v2
+         WHERE symbol = ?1
+         ORDER BY file_path, CASE WHEN kind = 'import' THEN 1 ELSE 0 END, start_line, start_column"
+    ))?;
+    let rows = statement.query_map([name], symbol_row)?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+}
+
+fn edges_into(
+    connection: &Connection,
+    name: &str,
+) -> anyhow::Result<HashMap<i64, Vec<(i64, String)>>> {
+    let mut statement = connection.prepare(
+        "SELECT e.from_symbol_id, e.to_symbol_id, e.edge_kind
+         FROM symbol_edges_v2 e
+         JOIN symbols_v2 s ON s.symbol_id = e.to_symbol_id
+         WHERE s.symbol = ?1",
+    )?;
+    let rows = statement.query_map([name], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+    let mut edges = HashMap::<i64, Vec<(i64, String)>>::new();
+    for row in rows {
+        let (from, to, kind) = row?;
+        edges.entry(from).or_default().push((to, kind));
+    }
+    Ok(edges)
+}
+
+/// `(file_path, line, column)` rows of `table` for `name`.
+fn occurrences(
+    connection: &Connection,
+    table: &str,
+    name: &str,
+) -> anyhow::Result<Vec<(String, u32, u32)>> {
+    let mut statement = connection.prepare(&format!(
+        "SELECT file_path, line, column FROM {table} WHERE symbol = ?1
+         ORDER BY file_path, line, column"
+    ))?;
+    let rows = statement.query_map([name], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+}
+
+impl Resolver<'_> {
+    fn source(&mut self, file_path: &str) -> Option<String> {
+        self.sources
+            .entry(file_path.to_string())
+            .or_insert_with(|| std::fs::read_to_string(self.repo_root.join(file_path)).ok())
+            .clone()
+    }
+
+    fn symbol(&self, symbol_id: i64) -> Option<&NamedSymbol> {
+        self.named
+            .iter()
+            .find(|symbol| symbol.symbol_id == symbol_id)
+    }
+
+    fn definition_binding(&self, symbol_id: i64) -> Binding {
+        if symbol_id == self.target.symbol_id {
+            Binding::Target
+        } else {
+            Binding::Other
+        }
+    }
+
+    /// Where an import points: its `imports` edge, else the only top-level definition of the
+    /// name in its language, else the one whose module the import statement names.
+    fn import_binding(&mut self, import: &NamedSymbol) -> Binding {
+        let imported = self
+            .edges
+            .get(&import.symbol_id)
+            .into_iter()
+            .flatten()
+            .filter(|(_, kind)| kind == "imports")
+            .map(|(to, _)| *to)
+            .collect::<Vec<_>>();
+        if !imported.is_empty() {
+            return if imported.contains(&self.target.symbol_id) {
+                Binding::Target
+            } else {
+                Binding::Other
+            };
+        }
+        let language = language_for_file_path(&import.file_path);
+        let candidates = self
+            .named
+            .iter()
+            .filter(|symbol| {
+                !symbol.is_import()
+                    && symbol.container.is_none()
+                    && language_for_file_path(&symbol.file_path) == language
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        if let [only] = candidates.as_slice() {
+            return self.definition_binding(only.symbol_id);
+        }
+        let statement = self
+            .source(&import.file_path)
+            .map(|source| {
+                source
+                    .lines()
+                    .skip(import.start_line.saturating_sub(1) as usize)
+                    .take((import.end_line + 1).saturating_sub(import.start_line) as usize)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .unwrap_or_default();
+        let named = candidates
+            .iter()
+            .filter(|candidate| {
+                module_hint(&candidate.file_path).is_some_and(|hint| names_module(&statement, hint))
+            })
+            .collect::<Vec<_>>();
+        match named.as_slice() {
+            [only] => self.definition_binding(only.symbol_id),
+            _ if candidates
+                .iter()
+                .all(|candidate| candidate.symbol_id != self.target.symbol_id) =>
+            {
+                Binding::Other
+            }
+            _ => Binding::Ambiguous,
+        }
+    }
+
+    /// Positions of the imported name inside an import statement, skipping module path segments
+    /// and string specifiers.
+    fn import_name_positions(&mut self, import: &NamedSymbol, name: &str) -> Vec<(u32, u32)> {
+        let Some(source) = self.source(&import.file_path) else {
+            return Vec::new();
+        };
+        let lines = source.lines().collect::<Vec<_>>();
+        let mut positions = Vec::new();
+        for line in import.start_line..=import.end_line {
+            let Some(text) = lines.get(line.saturating_sub(1) as usize) else {
+                continue;
+            };
+            let import_keyword = text.find(" import ");
+            for start in word_positions(text, name) {
+                let rest = &text[start + name.len()..];
+                let before = &text[..start];
+                let path_segment = rest.starts_with("::")
+                    || rest.starts_with('.')
+                    || rest.starts_with('/')
+                    || before.ends_with('/')
+                    || import_keyword.is_some_and(|keyword| start < keyword);
+                if !path_segment {
+                    positions.push((line, start as u32 + 1));
+                }
+            }
+        }
+        let strings = text_positions(&import.file_path, &source, &positions);
+        positions.retain(|position| !strings.contains(position));
+        positions
+    }
+
+    fn reference_binding(
+        &mut self,
+        file_path: &str,
+        line: u32,
+        column: u32,
+    ) -> anyhow::Result<Binding> {
+        let qualifier = self.path_qualifier(file_path, line, column);
+        if let Some(scope) = self.enclosing_symbol(file_path, line)? {
+            let edge_targets = self
+                .edges
+                .get(&scope)
+                .cloned()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(_, kind)| kind != "imports")
+                .map(|(to, _)| to)
+                .collect::<Vec<_>>();
+            let targets = edge_targets
+                .iter()
+                .map(|to| match self.symbol(*to).cloned() {
+                    Some(symbol) if symbol.is_import() => self.import_binding(&symbol),
+                    _ => self.definition_binding(*to),
+                })
+                .collect::<BTreeSet<_>>();
+            if targets.len() == 1 {
+                return Ok(*targets.first().unwrap_or(&Binding::Ambiguous));
+            }
+            if !targets.is_empty() {
+                return Ok(self
+                    .qualified_binding(qualifier.as_deref(), &edge_targets)
+                    .unwrap_or(Binding::Ambiguous));
+            }
+        }
+        // Same order as the indexer's scope resolution: this file's definitions before its
+        // imports, then a unique definition anywhere in the language.
+        let local = self
+            .named
+            .iter()
+            .find(|symbol| symbol.file_path == file_path)
+            .cloned();
+        if let Some(local) = local {
+            return Ok(if local.is_import() {
+                self.import_binding(&local)
+            } else {
+                self.definition_binding(local.symbol_id)
+            });
+        }
+        let language = language_for_file_path(file_path);
+        let definitions = self
+            .named
+            .iter()
+            .filter(|symbol| {
+                !symbol.is_import() && language_for_file_path(&symbol.file_path) == language
+            })
+            .map(|symbol| symbol.symbol_id)
+            .collect::<Vec<_>>();
+        Ok(match definitions.as_slice() {
+            [only] => self.definition_binding(*only),
+            ids if ids.contains(&self.target.symbol_id) => self
+                .qualified_binding(qualifier.as_deref(), ids)
+                .unwrap_or(Binding::Ambiguous),
+            _ => Binding::Other,
+        })
+    }
+
+    /// The path segment written right before the occurrence at `line:column`, such as `a` in
+    /// `a::compute(3)` or `models` in `models.load()`.
+    fn path_qualifier(&mut self, file_path: &str, line: u32, column: u32) -> Option<String> {
+        let source = self.source(file_path)?;
+        let text = source.lines().nth(line.checked_sub(1)? as usize)?;
+        let before = text.get(..column.checked_sub(1)? as usize)?;
+        let before = before
+            .strip_suffix("::")
+            .or_else(|| before.strip_suffix('.'))?;
+        let start = before
+            .rfind(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_'))
+            .map_or(0, |index| index + 1);
+        Some(before[start..].to_string()).filter(|segment| !segment.is_empty())
+    }
+
+    /// Binds an occurrence written as `qualifier::name` (or `qualifier.name`) to the one candidate
+    /// definition whose module or container the qualifier names, if exactly one does.
+    fn qualified_binding(&self, qualifier: Option<&str>, candidates: &[i64]) -> Option<Binding> {
+        let qualifier = qualifier?;
+        let named = candidates
+            .iter()
+            .filter_map(|symbol_id| self.symbol(*symbol_id))
+            .filter(|symbol| {
+                !symbol.is_import()
+                    && (symbol.container.as_deref() == Some(qualifier)
+                        || module_hint(&symbol.file_path) == Some(qualifier))
+            })
+            .map(|symbol| symbol.symbol_id)
+            .collect::<BTreeSet<_>>();
+        match named.iter().collect::<Vec<_>>().as_slice() {
+            [only] => Some(self.definition_binding(**only)),
+            _ => None,
+        }
+    }
+
+    fn enclosing_symbol(&mut self, file_path: &str, line: u32) -> anyhow::Result<Option<i64>> {
+        if !self.scopes.contains_key(file_path) {
+            let mut statement = self.connection.prepare_cached(
+                "SELECT symbol_id, start_line, end_line
+                 FROM symbols_v2
+                 WHERE file_path = ?1 AND kind != 'import'",
+            )?;
+            let rows = statement.query_map([file_path], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?;
+            let scopes = rows.collect::<Result<Vec<_>, _>>()?;
+            self.scopes.insert(file_path.to_string(), scopes);
+        }
+        Ok(self.scopes[file_path]
+            .iter()
+            .filter(|(_, start, end)| *start <= line && line <= *end)
+            .min_by_key(|(_, start, end)| (end - start, std::cmp::Reverse(*start)))
+            .map(|(symbol_id, _, _)| *symbol_id))
+    }
+}
+
+/// The file or package name an import statement uses for a module.
+fn module_hint(file_path: &str) -> Option<&str> {
+    let path = Path::new(file_path);
+    let stem = path.file_stem()?.to_str()?;
+    if matches!(stem, "mod" | "index" | "__init__" | "lib" | "main") {
+        path.parent()?.file_name()?.to_str()
+    } else {
+        Some(stem)
+    }
+}
+
+/// Whether `statement` uses `module` as a path segment (`a::`, `a.`, `a/`, or the last word of
+/// a string specifier), rather than merely containing its letters.
+fn names_module(statement: &str, module: &str) -> bool {
+    word_positions(statement, module).into_iter().any(|start| {
+        let rest = &statement[start + module.len()..];
+        rest.starts_with("::")
+            || rest.starts_with('.')
+            || rest.starts_with('/')
+            || rest.starts_with('"')
+            || rest.starts_with('\'')
+            || rest.trim_start().starts_with("import")
+    })
+}
+
+/// Byte offsets where `name` appears as a whole word in `text`.
+fn word_positions(text: &str, name: &str) -> Vec<usize> {
+    let is_word = |ch: char| ch.is_ascii_alphanumeric() || ch == '_';
+    text.match_indices(name)
+        .map(|(start, _)| start)
+        .filter(|start| {
+            !text[..*start].chars().next_back().is_some_and(is_word)
+                && !text[start + name.len()..]
+                    .chars()
+                    .next()
+                    .is_some_and(is_word)
+        })
+        .collect()
+}
+
+/// The subset of `positions` (1-based line and byte column) that fall inside a string literal or
+/// comment of `source`.
+fn text_positions(file_path: &str, source: &str, positions: &[(u32, u32)]) -> Vec<(u32, u32)> {
+    let Some(language) = language_for_path(file_path) else {
+        return Vec::new();
+    };
+    let mut parser = Parser::new();
+    if parser.set_language(&language).is_err() {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(source, None) else {
+        return Vec::new();
+    };
+    positions
+        .iter()
+        .copied()
+        .filter(|(line, column)| {
+            let point = Point::new(
+                line.saturating_sub(1) as usize,
+                column.saturating_sub(1) as usize,
+            );
+            let mut node = tree.root_node().descendant_for_point_range(point, point);
+            while let Some(current) = node {
+                let kind = current.kind();
+                if kind.contains("comment") || kind.contains("string") {
+                    return true;
+                }
+                node = current.parent();
+            }
+            false
+        })
+        .collect()
+}
+
+fn caller_files(connection: &Connection, symbol_id: i64) -> anyhow::Result<Vec<String>> {
+    let mut statement = connection.prepare(
+        "SELECT DISTINCT s.file_path
+         FROM symbol_edges_v2 e
+         JOIN symbols_v2 s ON s.symbol_id = e.from_symbol_id
+         WHERE e.to_symbol_id = ?1",
+    )?;
+    let rows = statement.query_map([symbol_id], |row| row.get(0))?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+}
+
+fn conflict_warnings(
+    connection: &Connection,
+    files: &BTreeSet<String>,
+    new_name: &str,
+) -> anyhow::Result<Vec<String>> {
+    let mut statement = connection.prepare(
+        "SELECT DISTINCT file_path FROM symbols_v2 WHERE symbol = ?1 ORDER BY file_path",
+    )?;
+    let rows = statement.query_map([new_name], |row| row.get::<_, String>(0))?;
+    let mut warnings = Vec::new();
+    for file_path in rows {
+        let file_path = file_path?;
+        if files.contains(&file_path) {
+            warnings.push(format!("{file_path} already uses the name '{new_name}'"));
+        }
+    }
+    Ok(warnings)
+}
+
+fn rewrite(
+    file_path: &str,
+    source: &str,
+    edits: &[&RenameEdit],
+    name: &str,
+    new_name: &str,
+) -> anyhow::Result<String> {
+    let mut lines = source
+        .split_inclusive('\n')
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let mut by_line = BTreeMap::<u32, Vec<usize>>::new();
+    for edit in edits {
+        by_line
+            .entry(edit.line)
+            .or_default()
+            .push(edit.column.saturating_sub(1) as usize);
+    }
+    for (line, mut columns) in by_line {
+        columns.sort_unstable();
+        columns.dedup();
+        for column in columns.into_iter().rev() {
+            let text = line
+                .checked_sub(1)
+                .and_then(|index| lines.get_mut(index as usize))
+                .filter(|text| {
+                    text.get(column..column + name.len()) == Some(name)
+                        && word_positions(text, name).contains(&column)
+                });
+            let Some(text) = text else {
+                anyhow::bail!(
+                    "{file_path}:{line}:{} no longer reads '{name}'; run `repo-scout index` \
+                     before renaming",
+                    column + 1
+                );
+            };
+            text.replace_range(column..column + name.len(), new_name);
+        }
+    }
+    Ok(lines.concat())
+}
+
+const DIFF_CONTEXT: usize = 3;
+
+/// A unified diff between two versions of a file that have the same number of lines.
+fn unified_diff(file_path: &str, before: &str, after: &str) -> String {
+    let old = before.split_inclusive('\n').collect::<Vec<_>>();
+    let new = after.split_inclusive('\n').collect::<Vec<_>>();
+    let changed = (0..old.len())
+        .filter(|index| old.get(*index) != new.get(*index))
+        .collect::<Vec<_>>();
+    if changed.is_empty() {
+        return String::new();
+    }
+    let mut hunks = Vec::<(usize, usize)>::new();
+    for index in changed {
+        let start = index.saturating_sub(DIFF_CONTEXT);
+        let end = (index + DIFF_CONTEXT + 1).min(old.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+    let mut diff = format!("--- a/{file_path}\n+++ b/{file_path}\n");
+    for (start, end) in hunks {
+        let count = end - start;
+        diff.push_str(&format!(
+            "@@ -{},{count} +{},{count} @@\n",
+            start + 1,
+            start + 1
+        ));
+        let mut index = start;
+        while index < end {
+            if old[index] == new[index] {
+                push_diff_line(&mut diff, ' ', old[index]);
+                index += 1;
+                continue;
+            }
+            let run_end = (index..end)
+                .find(|line| old[*line] == new[*line])
+                .unwrap_or(end);
+            for line in &old[index..run_end] {
+                push_diff_line(&mut diff, '-', line);
+            }
+            for line in &new[index..run_end] {
+                push_diff_line(&mut diff, '+', line);
+            }
+            index = run_end;
+        }
+    }
+    diff
+}
+
+fn push_diff_line(diff: &mut String, marker: char, line: &str) {
+    diff.push(marker);
+    diff.push_str(line);
+    if !line.ends_with('\n') {
+        diff.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrite_replaces_whole_words_at_the_recorded_columns() {
+        let source = "fn query() {}\nlet x = query_all(query());\n";
+        let edits = [
+            edit("a.rs", 1, 4, "definition"),
+            edit("a.rs", 2, 19, "reference"),
+        ];
+        let edits = edits.iter().collect::<Vec<_>>();
+        let updated = rewrite("a.rs", source, &edits, "query", "fetch").expect("rewrite");
+        assert_eq!(updated, "fn fetch() {}\nlet x = query_all(fetch());\n");
+
+        let stale = [edit("a.rs", 2, 9, "reference")];
+        let stale = stale.iter().collect::<Vec<_>>();
+        assert!(rewrite("a.rs", source, &stale, "query", "fetch").is_err());
+    }
+
+    #[test]
+    fn unified_diff_merges_nearby_changes_into_one_hunk() {
+        let before = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm";
+        let after = "a\nB\nc\nD\ne\nf\ng\nh\ni\nj\nk\nl\nM";
+        assert_eq!(
+            unified_diff("x.txt", before, after),
+            "--- a/x.txt\n+++ b/x.txt\n@@ -1,7 +1,7 @@\n a\n-b\n+B\n c\n-d\n+D\n e\n f\n g\n\
+             @@ -10,4 +10,4 @@\n j\n k\n l\n-m\n\\ No newline at end of file\n+M\n\
+             \\ No newline at end of file\n"
+        );
+    }
+
+    #[test]
+    fn names_module_requires_a_path_segment() {
+        assert!(names_module("pub use a::compute;", "a"));
+        assert!(!names_module("pub use a::compute;", "b"));
+        assert!(names_module("import { compute } from \"./b\";", "b"));
+        assert!(names_module("from models import load", "models"));
+        assert!(!names_module("from acme import models", "models"));
+    }
+
+    #[test]
+    fn text_positions_only_match_strings_and_comments() {
+        let source = "// query here\nfn run() {\n    let query = \"query\";\n}\n";
+        let positions = [(1, 4), (3, 9), (3, 18)];
+        assert_eq!(
+            text_positions("a.rs", source, &positions),
+            vec![(1, 4), (3, 18)]
+        );
+    }
+}
@@ -0,0 +1,981 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use rusqlite::{Connection, params};
+use serde::Serialize;
+
+use crate::query::cohesion::is_public;
+use crate::query::planning::{LineRange, SplitGrouping, extract_check, split_check};
+use crate::query::rename::rename_patch;
+use crate::query::{
+    VerificationStep, VerifyPlanOptions, is_test_like_path, language_for_file_path,
+    verify_plan_for_changed_files,
+};
+use crate::store::open_connection;
+
+/// An ordered refactoring plan where every step leaves the build green.
+#[derive(Debug, Clone, Serialize)]
+pub struct SafeStepsPlan {
+    pub symbol: String,
+    pub action: String,
+    /// The new name, destination file, or extracted function name.
+    pub target: Option<String>,
+    pub steps: Vec<SafeStep>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SafeStep {
+    pub step: u32,
+    /// `definition`, `re_export`, `callers`, `extract`, `delegate`, `split_group`, `cleanup`,
+    /// or `verify`.
+    pub kind: String,
+    pub title: String,
+    pub edits: Vec<StepEdit>,
+    /// Shims that keep the old name or location working until a later cleanup step.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub temporary_aliases: Vec<TemporaryAlias>,
+    /// `verify-plan` commands for the files this step edits; the final step runs everything.
+    pub verify: Vec<VerificationStep>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StepEdit {
+    pub file_path: String,
+    /// Lines the edit touches, or the line to insert after.
+    pub lines: Vec<u32>,
+    pub change: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TemporaryAlias {
+    pub file_path: String,
+    /// Code to add, when it can be written without knowing the full signature.
+    pub code: Option<String>,
+    pub removed_in_step: u32,
+}
+
+struct Definition {
+    file_path: String,
+    kind: String,
+    start_line: u32,
+    end_line: u32,
+    visibility: Option<String>,
+}
+
+struct ImportSite {
+    file_path: String,
+    line: u32,
+    end_line: u32,
+    re_export: bool,
+}
+
+/// Plans renaming the definition selected by `selector` to `new_name`: the definition and its
+/// uses in the same file first, then re-exports, then callers one file at a time, with an alias
+/// for the old name until every caller is updated. Sites are resolved like `rename` does.
+pub fn rename_steps(
+    db_path: &Path,
+    selector: &str,
+    new_name: &str,
+) -> anyhow::Result<SafeStepsPlan> {
+    let patch = rename_patch(db_path, selector, new_name, false)?;
+    let connection = open_connection(db_path)?;
+    let symbol = patch.symbol.as_str();
+    let definition = definition_by_id(&connection, patch.definition.symbol_id)?;
+    let imports = import_sites(&connection, symbol)?;
+    let mut warnings = Vec::new();
+    if language_for_file_path(&definition.file_path) == "go"
+        && starts_uppercase(symbol) != starts_uppercase(new_name)
+    {
+        warnings.push(format!(
+            "renaming {symbol} to {new_name} changes whether it is exported"
+        ));
+    }
+    let unresolved = patch
+        .skipped
+        .iter()
+        .filter(|skipped| matches!(skipped.reason.as_str(), "ambiguous" | "unresolved"))
+        .map(|skipped| format!("{}:{}", skipped.file_path, skipped.line))
+        .collect::<Vec<_>>();
+    if !unresolved.is_empty() {
+        warnings.push(format!(
+            "{} uses of {symbol} could not be resolved to this definition and are not planned ({})",
+            unresolved.len(),
+            unresolved.join(", ")
+        ));
+    }
+
+    let mut sites = BTreeMap::<String, BTreeSet<u32>>::new();
+    let mut re_export_lines = BTreeMap::<String, BTreeSet<u32>>::new();
+    for edit in &patch.edits {
+        let re_export = edit.kind == "import"
+            && imports.iter().any(|site| {
+                site.re_export
+                    && site.file_path == edit.file_path
+                    && (site.line..=site.end_line).contains(&edit.line)
+            });
+        let lines = if re_export && edit.file_path != definition.file_path {
+            re_export_lines.entry(edit.file_path.clone()).or_default()
+        } else {
+            sites.entry(edit.file_path.clone()).or_default()
+        };
+        lines.insert(edit.line);
+    }
+    let own_lines = sites.remove(&definition.file_path).unwrap_or_default();
+    let outside_uses = !sites.is_empty() || !re_export_lines.is_empty();
+
+    let mut plan = PlanBuilder::new(db_path);
+    let aliases = if outside_uses {
+        vec![TemporaryAlias {
+            file_path: definition.file_path.clone(),
+            code: rename_alias(&definition, symbol, new_name),
+            removed_in_step: 0,
+        }]
+    } else {
+        Vec::new()
+    };
+    plan.step(
+        "definition",
+        format!("Rename {symbol} to {new_name} in {}", definition.file_path),
+        vec![StepEdit {
+            file_path: definition.file_path.clone(),
+            lines: own_lines.into_iter().collect(),
+            change: format!("rename {symbol} to {new_name} at its definition and uses here"),
+        }],
+        aliases,
+    )?;
+
+    let re_exports = re_export_lines
+        .into_iter()
+        .map(|(file_path, lines)| StepEdit {
+            file_path,
+            lines: lines.into_iter().collect(),
+            change: format!("re-export {new_name} next to {symbol}"),
+        })
+        .collect::<Vec<_>>();
+    plan.step(
+        "re_export",
+        format!("Re-export {new_name} next to {symbol}"),
+        re_exports.clone(),
+        Vec::new(),
+    )?;
+
+    let skip = BTreeSet::from([definition.file_path.clone()]);
+    for (file_path, lines) in callers_in_order(sites, &skip) {
+        plan.step(
+            "callers",
+            format!("Update {symbol} to {new_name} in {file_path}"),
+            vec![StepEdit {
+                file_path,
+                change: format!("replace {symbol} with {new_name} ({} sites)", lines.len()),
+                lines,
+            }],
+            Vec::new(),
+        )?;
+    }
+
+    if outside_uses {
+        let mut edits = vec![StepEdit {
+            file_path: definition.file_path.clone(),
+            lines: vec![definition.start_line],
+            change: format!("remove the temporary {symbol} alias"),
+        }];
+        edits.extend(re_exports.into_iter().map(|edit| StepEdit {
+            change: format!("drop the {symbol} re-export"),
+            ..edit
+        }));
+        plan.cleanup(format!("Remove the temporary {symbol} alias"), edits)?;
+    }
+    plan.finish(symbol, "rename", Some(new_name), warnings)
+}
+
+/// Plans moving `symbol` to `destination`: the definition moves first and the old file
+/// re-exports it until re-exports and callers import it from the new file.
+pub fn move_steps(
+    db_path: &Path,
+    symbol: &str,
+    destination: &str,
+) -> anyhow::Result<SafeStepsPlan> {
+    let connection = open_connection(db_path)?;
+    let definitions = definitions(&connection, symbol)?;
+    let Some(definition) = definitions.first() else {
+        anyhow::bail!("symbol '{symbol}' not found");
+    };
+    let source = definition.file_path.as_str();
+    if source == destination {
+        anyhow::bail!("{symbol} is already defined in {destination}");
+    }
+    let language = language_for_file_path(source);
+    let mut warnings = Vec::new();
+    if definitions.len() > 1 {
+        warnings.push(format!(
+            "{} definitions are named {symbol}; the plan moves the one in {source}",
+            definitions.len()
+        ));
+    }
+    let references = references_by_file(&connection, &[symbol])?;
+    let imports = import_sites(&connection, symbol)?;
+    let remaining_uses = references
+        .get(source)
+        .into_iter()
+        .flatten()
+        .copied()
+        .filter(|line| *line < definition.start_line || *line > definition.end_line)
+        .collect::<Vec<_>>();
+    let same_package =
+        language == "go" && parent_directory(source) == parent_directory(destination);
+    if language == "go" && !same_package && !starts_uppercase(symbol) {
+        warnings.push(format!(
+            "{symbol} is unexported and must be renamed to be used from another package"
+        ));
+    }
+
+    let mut plan = PlanBuilder::new(db_path);
+    let mut destination_change = format!(
+        "add {symbol} (lines {}-{} of {source})",
+        definition.start_line, definition.end_line
+    );
+    let needs_wider_visibility = !remaining_uses.is_empty()
+        || references.keys().any(|file_path| file_path != source)
+        || !imports.is_empty();
+    match language {
+        "rust" if needs_wider_visibility && !is_public(definition.visibility.as_deref()) => {
+            destination_change.push_str(" and make it pub(crate)");
+        }
+        "typescript" | "javascript"
+            if needs_wider_visibility
+                && !is_exported(language, definition.visibility.as_deref()) =>
+        {
+            destination_change.push_str(" and export it");
+        }
+        _ => {}
+    }
+    let mut edits = vec![StepEdit {
+        file_path: destination.to_string(),
+        lines: Vec::new(),
+        change: destination_change,
+    }];
+    if language == "rust" && !is_indexed(&connection, destination)? {
+        edits.push(StepEdit {
+            file_path: destination.to_string(),
+            lines: Vec::new(),
+            change: "declare the new module in its parent module".to_string(),
+        });
+    }
+    edits.push(StepEdit {
+        file_path: source.to_string(),
+        lines: (definition.start_line..=definition.end_line).collect(),
+        change: format!("remove {symbol}"),
+    });
+    let alias = (!same_package).then(|| TemporaryAlias {
+        file_path: source.to_string(),
+        code: move_alias(definition, symbol, destination),
+        removed_in_step: 0,
+    });
+    plan.step(
+        "definition",
+        format!("Move {symbol} from {source} to {destination}"),
+        edits,
+        alias.into_iter().collect(),
+    )?;
+    if same_package {
+        warnings.push(format!(
+            "{source} and {destination} share a Go package; callers need no changes"
+        ));
+        return plan.finish(symbol, "move", Some(destination), warnings);
+    }
+
+    let skip = BTreeSet::from([source.to_string(), destination.to_string()]);
+    let re_exports = imports
+        .iter()
+        .filter(|site| site.re_export && !skip.contains(&site.file_path))
+        .map(|site| StepEdit {
+            file_path: site.file_path.clone(),
+            lines: vec![site.line],
+            change: format!("re-export {symbol} from {destination}"),
+        })
+        .collect::<Vec<_>>();
+    plan.step(
+        "re_export",
+        format!("Point re-exports of {symbol} at {destination}"),
+        re_exports,
+        Vec::new(),
+    )?;
+
+    let non_re_exports = imports
+        .iter()
+        .filter(|site| !site.re_export)
+        .collect::<Vec<_>>();
+    let caller_lines = relocation_sites(references, &non_re_exports);
+    for (file_path, lines) in callers_in_order(caller_lines, &skip) {
+        plan.step(
+            "callers",
+            format!("Import {symbol} from {destination} in {file_path}"),
+            vec![StepEdit {
+                file_path,
+                lines,
+                change: format!("import {symbol} from {destination}"),
+            }],
+            Vec::new(),
+        )?;
+    }
+
+    let mut edits = vec![StepEdit {
+        file_path: source.to_string(),
+        lines: Vec::new(),
+        change: "remove the temporary re-export".to_string(),
+    }];
+    if !remaining_uses.is_empty() {
+        edits.push(StepEdit {
+            file_path: source.to_string(),
+            lines: remaining_uses,
+            change: format!("import {symbol} from {destination} for the remaining uses"),
+        });
+    }
+    plan.cleanup(
+        format!("Remove the temporary re-export from {source}"),
+        edits,
+    )?;
+    plan.finish(symbol, "move", Some(destination), warnings)
+}
+
+/// Plans extracting `range` of `symbol` into `new_name`, using `extract-check`'s variable flow
+/// for the new function's parameters and return values.
+pub fn extract_steps(
+    db_path: &Path,
+    symbol: &str,
+    range: LineRange,
+    new_name: Option<&str>,
+) -> anyhow::Result<SafeStepsPlan> {
+    let report = extract_check(db_path, symbol, range)?;
+    let symbol = report.symbol.as_str();
+    let language = language_for_file_path(&report.file_path);
+    let new_name = new_name.map_or_else(|| extracted_name(language, symbol), str::to_string);
+    let parameters = report
+        .parameters
+        .iter()
+        .map(|parameter| parameter.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let returns = report
+        .return_values
+        .iter()
+        .map(|value| value.name.as_str())
+        .collect::<Vec<_>>();
+    let mut warnings = report.warnings.clone();
+    if !report.mechanically_safe {
+        warnings.push(format!(
+            "lines {}-{} are not mechanically safe to extract; resolve the warnings first",
+            range.start, range.end
+        ));
+    }
+
+    let mut plan = PlanBuilder::new(db_path);
+    let returns_text = match returns.as_slice() {
+        [] => String::new(),
+        values => format!(" returning {}", values.join(", ")),
+    };
+    plan.step(
+        "extract",
+        format!("Add {new_name} below {symbol}"),
+        vec![StepEdit {
+            file_path: report.file_path.clone(),
+            lines: vec![report.function_end_line],
+            change: format!(
+                "add {new_name}({parameters}){returns_text} with a copy of lines {}-{}",
+                range.start, range.end
+            ),
+        }],
+        Vec::new(),
+    )?;
+    let call = format!(
+        "{}{new_name}({parameters})",
+        result_binding(language, &returns)
+    );
+    plan.step(
+        "delegate",
+        format!("Call {new_name} from {symbol}"),
+        vec![StepEdit {
+            file_path: report.file_path.clone(),
+            lines: (range.start..=range.end).collect(),
+            change: format!("replace the lines with {call}"),
+        }],
+        Vec::new(),
+    )?;
+    plan.finish(symbol, "extract", Some(&new_name), warnings)
+}
+
+/// Plans splitting `file_path` along `split-check`'s automatic groups: each group moves to its
+/// file while the original re-exports what it moved, then callers import from the new files.
+pub fn split_steps(db_path: &Path, file_path: &str) -> anyhow::Result<SafeStepsPlan> {
+    let report = split_check(db_path, file_path, &SplitGrouping::Auto)?;
+    let connection = open_connection(db_path)?;
+    let language = language_for_file_path(file_path);
+    let mut plan = PlanBuilder::new(db_path);
+    let staying_imports = report
+        .groups
+        .iter()
+        .filter(|group| group.stays)
+        .flat_map(|group| &group.imports)
+        .collect::<Vec<_>>();
+    let mut moved = BTreeSet::new();
+    let mut re_exported = false;
+    for group in report.groups.iter().filter(|group| !group.stays) {
+        let mut edits = vec![StepEdit {
+            file_path: group.target_file.clone(),
+            lines: Vec::new(),
+            change: format!("create it with {}", group.symbols.join(", ")),
+        }];
+        if !group.imports.is_empty() {
+            let imports = group
+                .imports
+                .iter()
+                .map(|import| format!("{} from {}", import.symbol, import.from_file))
+                .collect::<Vec<_>>();
+            edits.push(StepEdit {
+                file_path: group.target_file.clone(),
+                lines: Vec::new(),
+                change: format!("import {}", imports.join(", ")),
+            });
+        }
+        for change in report
+            .visibility_changes
+            .iter()
+            .filter(|change| change.file == group.target_file)
+        {
+            edits.push(StepEdit {
+                file_path: group.target_file.clone(),
+                lines: Vec::new(),
+                change: format!("make {} {}", change.symbol, change.required),
+            });
+        }
+        let mut lines = BTreeSet::new();
+        let mut exported = Vec::new();
+        for symbol in &group.symbols {
+            for definition in definitions(&connection, symbol)?
+                .into_iter()
+                .filter(|definition| definition.file_path == file_path)
+            {
+                lines.extend(definition.start_line..=definition.end_line);
+                if is_exported(language, definition.visibility.as_deref())
+                    && !exported.contains(symbol)
+                {
+                    exported.push(symbol.clone());
+                }
+            }
+            moved.insert(symbol.clone());
+        }
+        edits.push(StepEdit {
+            file_path: file_path.to_string(),
+            lines: lines.into_iter().collect(),
+            change: format!("remove {}", group.symbols.join(", ")),
+        });
+        if language == "rust" {
+            edits.push(StepEdit {
+                file_path: file_path.to_string(),
+                lines: Vec::new(),
+                change: format!("declare mod {}", file_stem(&group.target_file)),
+            });
+        }
+        let used_here = staying_imports
+            .iter()
+            .filter(|import| import.from_file == group.target_file)
+            .map(|import| import.symbol.as_str())
+            .collect::<Vec<_>>();
+        if !used_here.is_empty() {
+            edits.push(StepEdit {
+                file_path: file_path.to_string(),
+                lines: Vec::new(),
+                change: format!("import {} from {}", used_here.join(", "), group.target_file),
+            });
+        }
+        let alias = (language != "go" && !exported.is_empty()).then(|| {
+            re_exported = true;
+            TemporaryAlias {
+                file_path: file_path.to_string(),
+                code: split_alias(language, file_path, &group.target_file, &exported),
+                removed_in_step: 0,
+            }
+        });
+        plan.step(
+            "split_group",
+            format!("Move the {} group to {}", group.name, group.target_file),
+            edits,
+            alias.into_iter().collect(),
+        )?;
+    }
+
+    let mut skip = report
+        .groups
+        .iter()
+        .map(|group| group.target_file.clone())
+        .collect::<BTreeSet<_>>();
+    skip.insert(file_path.to_string());
+    if language != "go" {
+        let symbols = moved.iter().map(String::as_str).collect::<Vec<_>>();
+        let references = references_by_file(&connection, &symbols)?;
+        let mut imports = Vec::new();
+        for symbol in &symbols {
+            imports.extend(import_sites(&connection, symbol)?);
+        }
+        let caller_lines = relocation_sites(references, &imports.iter().collect::<Vec<_>>());
+        for (caller, lines) in callers_in_order(caller_lines, &skip) {
+            plan.step(
+                "callers",
+                format!("Import moved symbols from their new files in {caller}"),
+                vec![StepEdit {
+                    file_path: caller,
+                    lines,
+                    change: "import moved symbols from their new files".to_string(),
+                }],
+                Vec::new(),
+            )?;
+        }
+    }
+    if re_exported {
+        plan.cleanup(
+            format!("Remove the temporary re-exports from {file_path}"),
+            vec![StepEdit {
+                file_path: file_path.to_string(),
+                lines: Vec::new(),
+                change: "remove the temporary re-exports".to_string(),
+            }],
+        )?;
+    }
+    plan.finish(file_path, "split", None, report.warnings)
+}
+
+/// Accumulates steps and attaches `verify-plan` commands to each.
+struct PlanBuilder<'a> {
+    db_path: &'a Path,
+    steps: Vec<SafeStep>,
+    touched: BTreeSet<String>,
+}
+
+impl<'a> PlanBuilder<'a> {
+    fn new(db_path: &'a Path) -> Self {
+        Self {
+            db_path,
+            steps: Vec::new(),
+            touched: BTreeSet::new(),
+        }
+    }
+
+    /// Appends a step unless it has nothing to edit. Intermediate steps run the targeted tests
+    /// for their files, or the full suite when no targeted test is known.
+    fn step(
+        &mut self,
+        kind: &str,
+        title: String,
+        edits: Vec<StepEdit>,
+        temporary_aliases: Vec<TemporaryAlias>,
+    ) -> anyhow::Result<u32> {
+        if edits.is_empty() {
+            return Ok(0);
+        }
+        let files = edits
+            .iter()
+            .map(|edit| edit.file_path.clone())
+            .collect::<BTreeSet<_>>();
+        let mut verify = self.verification(&files)?;
+        if verify.iter().any(|step| step.scope != "full_suite") {
+            verify.retain(|step| step.scope != "full_suite");
+        }
+        self.touched.extend(files);
+        let number = self.steps.len() as u32 + 1;
+        self.steps.push(SafeStep {
+            step: number,
+            kind: kind.to_string(),
+            title,
+            edits,
+            temporary_aliases,
+            verify,
+        });
+        Ok(number)
+    }
+
+    /// Appends the step that removes every temporary alias added so far.
+    fn cleanup(&mut self, title: String, edits: Vec<StepEdit>) -> anyhow::Result<()> {
+        let number = self.step("cleanup", title, edits, Vec::new())?;
+        for step in &mut self.steps {
+            for alias in &mut step.temporary_aliases {
+                alias.removed_in_step = number;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(
+        mut self,
+        symbol: &str,
+        action: &str,
+        target: Option<&str>,
+        warnings: Vec<String>,
+    ) -> anyhow::Result<SafeStepsPlan> {
+        let touched = std::mem::take(&mut self.touched);
+        let verify = self.verification(&touched)?;
+        self.steps.push(SafeStep {
+            step: self.steps.len() as u32 + 1,
+            kind: "verify".to_string(),
+            title: "Run the full verification plan".to_string(),
+            edits: Vec::new(),
+            temporary_aliases: Vec::new(),
+            verify,
+        });
+        Ok(SafeStepsPlan {
+            symbol: symbol.to_string(),
+            action: action.to_string(),
+            target: target.map(str::to_string),
+            steps: self.steps,
+            warnings,
+        })
+    }
+
+    fn verification(&self, files: &BTreeSet<String>) -> anyhow::Result<Vec<VerificationStep>> {
+        let files = files.iter().cloned().collect::<Vec<_>>();
+        verify_plan_for_changed_files(self.db_path, &files, &VerifyPlanOptions::default())
+    }
+}
+
+fn definitions(connection: &Connection, symbol: &str) -> anyhow::Result<Vec<Definition>> {
+    let mut statement = connection.prepare_cached(
+        "SELECT file_path, kind, start_line, end_line, visibility
+         FROM symbols_v2
+         WHERE symbol = ?1 AND kind NOT IN ('import', 'module')
+         ORDER BY file_path ASC, start_line ASC",
+    )?;
+    let rows = statement.query_map(params![symbol], |row| {
+        Ok(Definition {
+            file_path: row.get(0)?,
+            kind: row.get(1)?,
+            start_line: row.get(2)?,
+            end_line: row.get(3)?,
+            visibility: row.get(4)?,
+        })
+    })?;
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+fn definition_by_id(connection: &Connection, symbol_id: i64) -> anyhow::Result<Definition> {
+    let mut statement = connection.prepare_cached(
+        "SELECT file_path, kind, start_line, end_line, visibility
+         FROM symbols_v2
+         WHERE symbol_id = ?1",
+    )?;
+    let definition = statement.query_row(params![symbol_id], |row| {
+        Ok(Definition {
+            file_path: row.get(0)?,
+            kind: row.get(1)?,
+            start_line: row.get(2)?,
+            end_line: row.get(3)?,
+            visibility: row.get(4)?,
+        })
+    })?;
+    Ok(definition)
+}
+
+/// Import statements naming `symbol`. Public imports, and any import in a Python package's
+/// `__init__.py`, re-export it.
+fn import_sites(connection: &Connection, symbol: &str) -> anyhow::Result<Vec<ImportSite>> {
+    let mut statement = connection.prepare_cached(
+        "SELECT file_path, start_line, end_line, visibility
+         FROM symbols_v2
+         WHERE symbol = ?1 AND kind = 'import'
+         ORDER BY file_path ASC, start_line ASC",
+    )?;
+    let rows = statement.query_map(params![symbol], |row| {
+        let file_path = row.get::<_, String>(0)?;
+        let visibility = row.get::<_, Option<String>>(3)?;
+        let re_export = match language_for_file_path(&file_path) {
+            "python" => file_path == "__init__.py" || file_path.ends_with("/__init__.py"),
+            _ => is_public(visibility.as_deref()),
+        };
+        Ok(ImportSite {
+            line: row.get(1)?,
+            end_line: row.get(2)?,
+            file_path,
+            re_export,
+        })
+    })?;
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+fn references_by_file(
+    connection: &Connection,
+    symbols: &[&str],
+) -> anyhow::Result<BTreeMap<String, BTreeSet<u32>>> {
+    let mut statement = connection
+        .prepare_cached("SELECT file_path, line FROM ast_references WHERE symbol = ?1")?;
+    let mut references = BTreeMap::<String, BTreeSet<u32>>::new();
+    for symbol in symbols {
+        let rows = statement.query_map(params![symbol], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?))
+        })?;
+        for row in rows {
+            let (file_path, line) = row?;
+            references.entry(file_path).or_default().insert(line);
+        }
+    }
+    Ok(references)
+}
+
+fn is_indexed(connection: &Connection, file_path: &str) -> anyhow::Result<bool> {
+    let mut statement =
+        connection.prepare_cached("SELECT COUNT(*) FROM indexed_files WHERE file_path = ?1")?;
+    Ok(statement.query_row(params![file_path], |row| row.get::<_, u32>(0))? > 0)
+}
+
+/// Lines to edit per file when definitions change location: the imports where a file imports
+/// them, and otherwise each use that names the old path.
+fn relocation_sites(
+    references: BTreeMap<String, BTreeSet<u32>>,
+    imports: &[&ImportSite],
+) -> BTreeMap<String, BTreeSet<u32>> {
+    let mut import_lines = BTreeMap::<String, BTreeSet<u32>>::new();
+    for site in imports {
+        import_lines
+            .entry(site.file_path.clone())
+            .or_default()
+            .insert(site.line);
+    }
+    let mut sites = references;
+    sites.extend(import_lines);
+    sites
+}
+
+/// Caller files outside `skip`, production code before tests, each in path order.
+fn callers_in_order(
+    caller_lines: BTreeMap<String, BTreeSet<u32>>,
+    skip: &BTreeSet<String>,
+) -> Vec<(String, Vec<u32>)> {
+    let mut callers = caller_lines
+        .into_iter()
+        .filter(|(file_path, _)| !skip.contains(file_path))
+        .map(|(file_path, lines)| (file_path, lines.into_iter().collect::<Vec<_>>()))
+        .collect::<Vec<_>>();
+    callers.sort_by_key(|(file_path, _)| (is_test_like_path(file_path), file_path.clone()));
+    callers
+}
+
+/// Code that keeps `old` resolving to `new` next to the renamed definition.
+fn rename_alias(definition: &Definition, old: &str, new: &str) -> Option<String> {
+    let visibility = definition.visibility.as_deref();
+    let kind = definition.kind.as_str();
+    match language_for_file_path(&definition.file_path) {
+        "rust" if kind != "method" => Some(format!(
+            "{}use self::{new} as {old};",
+            rust_visibility_prefix(visibility)
+        )),
+        "go" => match kind {
+            "method" => None,
+            "const" => Some(format!("const {old} = {new}")),
+            "function" | "var" => Some(format!("var {old} = {new}")),
+            _ => Some(format!("type {old} = {new}")),
+        },
+        "python" => Some(format!("{old} = {new}")),
+        "typescript" | "javascript" if kind != "method" => {
+            if matches!(visibility, Some("export" | "export default")) {
+                Some(format!("export {{ {new} as {old} }};"))
+            } else if matches!(kind, "type" | "interface" | "type_alias") {
+                Some(format!("type {old} = {new};"))
+            } else {
+                Some(format!("const {old} = {new};"))
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Code that re-exports a moved definition from its old file.
+fn move_alias(definition: &Definition, symbol: &str, destination: &str) -> Option<String> {
+    let source = definition.file_path.as_str();
+    match language_for_file_path(source) {
+        "rust" => rust_module_path(destination).map(|module| {
+            let prefix = rust_visibility_prefix(definition.visibility.as_deref());
+            format!("{prefix}use {module}::{symbol};")
+        }),
+        "typescript" | "javascript" => Some(format!(
+            "export {{ {symbol} }} from '{}';",
+            relative_import(source, destination)
+        )),
+        "python" => Some(format!(
+            "from {} import {symbol}  # noqa: F401",
+            python_module(destination)
+        )),
+        _ => None,
+    }
+}
+
+/// Code that re-exports a split-out group's symbols from the original file.
+fn split_alias(
+    language: &str,
+    file_path: &str,
+    target_file: &str,
+    symbols: &[String],
+) -> Option<String> {
+    let list = symbols.join(", ");
+    match language {
+        "rust" => Some(format!(
+            "pub use self::{}::{{{list}}};",
+            file_stem(target_file)
+        )),
+        "typescript" | "javascript" => Some(format!(
+            "export {{ {list} }} from '{}';",
+            relative_import(file_path, target_file)
+        )),
+        "python" => Some(format!(
+            "from {} import {list}  # noqa: F401",
+            python_module(target_file)
+        )),
+        _ => None,
+    }
+}
+
+/// Whether code outside the file can already import a definition with this visibility.
+fn is_exported(language: &str, visibility: Option<&str>) -> bool {
+    match language {
+        "typescript" | "javascript" => matches!(visibility, Some("export" | "export default")),
+        _ => is_public(visibility),
+    }
+}
+
+fn rust_visibility_prefix(visibility: Option<&str>) -> String {
+    match visibility {
+        Some(value) if value.starts_with("pub") => format!("{value} "),
+        _ => String::new(),
+    }
+}
+
+/// The `crate::` path of a Rust file under a `src/` directory.
+fn rust_module_path(file_path: &str) -> Option<String> {
+    let relative = match file_path.rsplit_once("src/") {
+        Some((prefix, relative)) if prefix.is_empty() || prefix.ends_with('/') => relative,
+        _ => return None,
+    };
+    let mut segments = relative.strip_suffix(".rs")?.split('/').collect::<Vec<_>>();
+    if matches!(segments.last(), Some(&("mod" | "lib" | "main"))) {
+        segments.pop();
+    }
+    segments.insert(0, "crate");
+    Some(segments.join("::"))
+}
+
+/// The dotted module name of a Python file, ignoring a leading `src/` layout directory.
+fn python_module(file_path: &str) -> String {
+    let relative = file_path.strip_prefix("src/").unwrap_or(file_path);
+    let relative = relative.strip_suffix(".py").unwrap_or(relative);
+    let relative = relative.strip_suffix("/__init__").unwrap_or(relative);
+    relative.replace('/', ".")
+}
+
+/// The import specifier for `target` as seen from `from_file`, without an extension.
+fn relative_import(from_file: &str, target: &str) -> String {
+    let from_directory = parent_directory(from_file)
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>();
+    let target = target.rsplit_once('.').map_or(target, |(stem, _)| stem);
+    let target_segments = target.split('/').collect::<Vec<_>>();
+    let shared = from_directory
+        .iter()
+        .zip(&target_segments)
+        .take_while(|(left, right)| left == right)
+        .count();
+    let mut parts = vec![".."; from_directory.len() - shared];
+    if parts.is_empty() {
+        parts.push(".");
+    }
+    parts.extend(&target_segments[shared..]);
+    parts.join("/")
+}
+
+fn parent_directory(file_path: &str) -> &str {
+    file_path
+        .rsplit_once('/')
+        .map_or("", |(directory, _)| directory)
+}
+
+fn file_stem(file_path: &str) -> &str {
+    let file_name = file_path
+        .rsplit_once('/')
+        .map_or(file_path, |(_, name)| name);
+    file_name
+        .split_once('.')
+        .map_or(file_name, |(stem, _)| stem)
+}
+
+fn starts_uppercase(name: &str) -> bool {
+    name.chars().next().is_some_and(char::is_uppercase)
+}
+
+/// The statement prefix that binds an extracted function's results in the caller.
+fn result_binding(language: &str, values: &[&str]) -> String {
+    let list = values.join(", ");
+    match (language, values.len()) {
+        (_, 0) => String::new(),
+        ("rust", 1) => format!("let {list} = "),
+        ("rust", _) => format!("let ({list}) = "),
+        ("go", _) => format!("{list} := "),
+        ("typescript" | "javascript", 1) => format!("const {list} = "),
+        ("typescript" | "javascript", _) => format!("const [{list}] = "),
+        _ => format!("{list} = "),
+    }
+}
+
+/// Default name for an extracted function, following the language's naming convention.
+fn extracted_name(language: &str, symbol: &str) -> String {
+    match language {
+        "go" | "typescript" | "javascript" => {
+            let mut characters = symbol.chars();
+            let first = characters.next().map(|c| c.to_ascii_uppercase());
+            let prefix = if starts_uppercase(symbol) {
+                "Extracted"
+            } else {
+                "extracted"
+            };
+            format!(
+                "{prefix}{}{}",
+                first.into_iter().collect::<String>(),
+                characters.as_str()
+            )
+        }
+        _ => format!("{symbol}_extracted"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extracted_name, python_module, relative_import, rust_module_path};
+
+    #[test]
+    fn rust_module_path_follows_module_files() {
+        assert_eq!(rust_module_path("src/lib.rs").as_deref(), Some("crate"));
+        assert_eq!(
+            rust_module_path("src/query/mod.rs").as_deref(),
+            Some("crate::query")
+        );
+        assert_eq!(
+            rust_module_path("crates/core/src/store/schema.rs").as_deref(),
+            Some("crate::store::schema")
+        );
+        assert_eq!(rust_module_path("tests/common/mod.rs"), None);
+    }
+
+    #[test]
+    fn import_paths_are_relative_to_the_importing_file() {
+        assert_eq!(
+            relative_import("src/app.ts", "src/util/math.ts"),
+            "./util/math"
+        );
+        assert_eq!(relative_import("src/a/b.ts", "src/c.ts"), "../c");
+        assert_eq!(python_module("src/pkg/__init__.py"), "pkg");
+        assert_eq!(python_module("pkg/tools.py"), "pkg.tools");
+    }
+
+    #[test]
+    fn extracted_names_follow_language_case() {
+        assert_eq!(extracted_name("rust", "load"), "load_extracted");
+        assert_eq!(extracted_name("typescript", "load"), "extractedLoad");
+        assert_eq!(extracted_name("go", "Load"), "ExtractedLoad");
+    }
+}
@@ -1,59 +1,511 @@
-use std::path::Path;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use rusqlite::Connection;
 use serde::Serialize;
 
+use crate::git_utils;
+use crate::indexer::index_snapshot;
+
 #[derive(Debug, Clone, Serialize)]
 pub struct VerifyRefactorReport {
     pub before: String,
     pub after: String,
     pub changed_files: Vec<String>,
+    /// Definitions present only after the change.
+    pub added: Vec<SnapshotSymbolRef>,
+    /// Definitions present only before the change.
+    pub removed: Vec<SnapshotSymbolRef>,
+    /// Definitions whose body stayed the same under a new name.
+    pub renamed: Vec<RelocatedSymbol>,
+    /// Definitions that now live in another file.
+    pub moved: Vec<RelocatedSymbol>,
+    /// References and imports in the after snapshot naming a definition that no longer exists.
+    pub stale_references: Vec<StaleReference>,
+    /// Edges from surviving definitions that no longer exist after the change.
+    pub lost_edges: Vec<LostEdge>,
+    /// Problems that kept the comparison from running.
     pub warnings: Vec<String>,
 }
 
+impl VerifyRefactorReport {
+    /// Whether the change left the code structurally broken, or could not be checked.
+    pub fn is_broken(&self) -> bool {
+        !self.warnings.is_empty() || !self.stale_references.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotSymbolRef {
+    pub symbol: String,
+    pub kind: String,
+    pub file_path: String,
+    pub line: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RelocatedSymbol {
+    pub kind: String,
+    pub old_symbol: String,
+    pub new_symbol: String,
+    pub old_file: String,
+    pub new_file: String,
+    pub old_line: u32,
+    pub new_line: u32,
+    /// The body is unchanged apart from the name; otherwise the definition was matched by name.
+    pub same_body: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleReference {
+    pub symbol: String,
+    pub file_path: String,
+    pub line: u32,
+    /// The new name when the definition was renamed.
+    pub replacement: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LostEdge {
+    pub from_symbol: String,
+    pub from_file: String,
+    pub to_symbol: String,
+    pub to_file: String,
+    pub edge_kind: String,
+    /// The edge's target no longer exists at all.
+    pub target_removed: bool,
+}
+
+/// Compares the indexed symbols and edges of `before` with those of `after`, or of the working
+/// tree when `after` is omitted.
+///
+/// Each revision is checked out into a temporary git worktree and indexed into a throwaway
+/// store. Definitions whose body hash matches under another name or in another file are
+/// reported as renamed or moved instead of as a removal plus an addition.
 pub fn verify_refactor_report(
     repo_root: &Path,
     before: &str,
     after: Option<&str>,
 ) -> anyhow::Result<VerifyRefactorReport> {
     let after_value = after.unwrap_or("working-tree").to_string();
-    let mut warnings = Vec::new();
-    let mut changed_files = Vec::new();
-
-    if let Some(after_ref) = after {
-        let output = Command::new("git")
-            .args(["diff", "--name-only", before, after_ref])
-            .current_dir(repo_root)
-            .output();
-        match output {
-            Ok(output) if output.status.success() => {
-                let stdout = String::from_utf8(output.stdout)?;
-                changed_files = stdout
-                    .lines()
-                    .filter(|line| !line.is_empty())
-                    .map(ToOwned::to_owned)
-                    .collect();
-            }
-            Ok(output) => {
-                warnings.push(format!(
-                    "unable to compute git diff between '{before}' and '{after_ref}': {}",
-                    String::from_utf8_lossy(&output.stderr)
-                ));
-            }
-            Err(error) => {
-                warnings.push(format!(
-                    "unable to invoke git diff between '{before}' and '{after_ref}': {error}"
-                ));
-            }
-        }
-    } else {
-        warnings.push("after reference not provided; comparing against working tree".to_string());
-    }
-
-    Ok(VerifyRefactorReport {
+    let mut report = VerifyRefactorReport {
         before: before.to_string(),
         after: after_value,
-        changed_files,
-        warnings,
-    })
+        changed_files: Vec::new(),
+        added: Vec::new(),
+        removed: Vec::new(),
+        renamed: Vec::new(),
+        moved: Vec::new(),
+        stale_references: Vec::new(),
+        lost_edges: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    let mut diff_args = vec!["diff", "--name-only", before];
+    diff_args.extend(after);
+    let target = after.unwrap_or("the working tree");
+    let output = Command::new("git")
+        .args(&diff_args)
+        .current_dir(repo_root)
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            let stdout = String::from_utf8(output.stdout)?;
+            report.changed_files = stdout
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(ToOwned::to_owned)
+                .collect();
+        }
+        Ok(output) => {
+            report.warnings.push(format!(
+                "unable to compute git diff between '{before}' and '{target}': {}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+            return Ok(report);
+        }
+        Err(error) => {
+            report.warnings.push(format!(
+                "unable to invoke git diff between '{before}' and '{target}': {error}"
+            ));
+            return Ok(report);
+        }
+    }
+
+    let snapshots = revision_snapshot(repo_root, before).and_then(|old| {
+        let new = match after {
+            Some(after_ref) => revision_snapshot(repo_root, after_ref)?,
+            None => directory_snapshot(repo_root, "working-tree")?,
+        };
+        Ok((old, new))
+    });
+    match snapshots {
+        Ok((old, new)) => compare_snapshots(&old, &new, &mut report),
+        Err(error) => report.warnings.push(format!(
+            "unable to index '{before}' and '{target}' for comparison: {error:#}"
+        )),
+    }
+    Ok(report)
+}
+
+/// A definition from a snapshot store.
+#[derive(Debug, Clone)]
+struct SnapshotSymbol {
+    symbol: String,
+    kind: String,
+    file_path: String,
+    container: Option<String>,
+    start_line: u32,
+    /// Hash of the trimmed source lines with the definition's own name blanked out.
+    body_hash: String,
+}
+
+impl SnapshotSymbol {
+    fn key(&self) -> (&str, Option<&str>, &str, &str) {
+        (
+            &self.file_path,
+            self.container.as_deref(),
+            &self.symbol,
+            &self.kind,
+        )
+    }
+
+    fn reference(&self) -> SnapshotSymbolRef {
+        SnapshotSymbolRef {
+            symbol: self.symbol.clone(),
+            kind: self.kind.clone(),
+            file_path: self.file_path.clone(),
+            line: self.start_line,
+        }
+    }
+}
+
+/// Definitions, edges, and name references of one indexed revision.
+#[derive(Debug, Default)]
+struct Snapshot {
+    symbols: Vec<SnapshotSymbol>,
+    edges: BTreeSet<(usize, usize, String)>,
+    /// `(file_path, line, symbol)` of every reference and import.
+    references: Vec<(String, u32, String)>,
+}
+
+fn revision_snapshot(repo_root: &Path, revision: &str) -> anyhow::Result<Snapshot> {
+    let commit = git_utils::resolve_commit(repo_root, revision)?;
+    let prefix = git_utils::path_prefix(repo_root)?;
+    let worktree = scratch_path(&commit);
+    git_utils::add_worktree(repo_root, &worktree, &commit)?;
+    let snapshot = directory_snapshot(&worktree.join(&prefix), &commit);
+    let removed = git_utils::remove_worktree(repo_root, &worktree);
+    let snapshot = snapshot?;
+    removed?;
+    Ok(snapshot)
+}
+
+/// Indexes `directory` into a temporary store named after `label` and loads it.
+fn directory_snapshot(directory: &Path, label: &str) -> anyhow::Result<Snapshot> {
+    let db_path = scratch_path(label).with_extension("db");
+    let _ = std::fs::remove_file(&db_path);
+    let loaded = index_snapshot(directory, &db_path)
+        .and_then(|_| load_snapshot(&Connection::open(&db_path)?, directory));
+    let _ = std::fs::remove_file(&db_path);
+    loaded
+}
+
+fn scratch_path(label: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "repo-scout-snapshot-{}-{label}",
+        std::process::id()
+    ))
+}
+
+fn load_snapshot(connection: &Connection, root: &Path) -> anyhow::Result<Snapshot> {
+    let mut snapshot = Snapshot::default();
+    let mut statement = connection.prepare(
+        "SELECT symbol_id, symbol, kind, file_path, container, start_line, end_line
+         FROM symbols_v2
+         ORDER BY file_path ASC, start_line ASC, start_column ASC",
+    )?;
+    let rows = statement.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, Option<String>>(4)?,
+            row.get::<_, u32>(5)?,
+            row.get::<_, u32>(6)?,
+        ))
+    })?;
+    let mut sources = HashMap::<String, Vec<String>>::new();
+    let mut index_of = HashMap::new();
+    for row in rows {
+        let (symbol_id, symbol, kind, file_path, container, start_line, end_line) = row?;
+        if kind == "import" {
+            snapshot.references.push((file_path, start_line, symbol));
+            continue;
+        }
+        let lines = sources.entry(file_path.clone()).or_insert_with(|| {
+            std::fs::read_to_string(root.join(&file_path))
+                .map(|source| source.lines().map(str::to_string).collect())
+                .unwrap_or_default()
+        });
+        let body_hash = body_hash(lines, &symbol, start_line, end_line);
+        index_of.insert(symbol_id, snapshot.symbols.len());
+        snapshot.symbols.push(SnapshotSymbol {
+            symbol,
+            kind,
+            file_path,
+            container,
+            start_line,
+            body_hash,
+        });
+    }
+
+    let mut statement = connection
+        .prepare("SELECT from_symbol_id, to_symbol_id, edge_kind FROM symbol_edges_v2")?;
+    let rows = statement.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+    for row in rows {
+        let (from, to, kind) = row?;
+        if let (Some(&from), Some(&to)) = (index_of.get(&from), index_of.get(&to)) {
+            snapshot.edges.insert((from, to, kind));
+        }
+    }
+
+    let mut statement = connection.prepare("SELECT file_path, line, symbol FROM ast_references")?;
+    let rows = statement.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, u32>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+    for row in rows {
+        snapshot.references.push(row?);
+    }
+    Ok(snapshot)
+}
+
+fn body_hash(lines: &[String], name: &str, start_line: u32, end_line: u32) -> String {
+    let start = (start_line as usize).saturating_sub(1);
+    let end = (end_line as usize).min(lines.len());
+    let body = lines
+        .get(start..end)
+        .unwrap_or_default()
+        .iter()
+        .map(|line| blank_identifier(line.trim(), name))
+        .collect::<Vec<_>>()
+        .join("\n");
+    blake3::hash(body.as_bytes()).to_hex().to_string()
+}
+
+/// Replaces whole-identifier occurrences of `name` in `text` with `$`.
+fn blank_identifier(text: &str, name: &str) -> String {
+    let is_identifier = |character: char| character.is_alphanumeric() || character == '_';
+    let mut blanked = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find(name) {
+        let before = rest[..index].chars().next_back();
+        let after = rest[index + name.len()..].chars().next();
+        blanked.push_str(&rest[..index]);
+        if before.is_some_and(is_identifier) || after.is_some_and(is_identifier) {
+            blanked.push_str(name);
+        } else {
+            blanked.push('$');
+        }
+        rest = &rest[index + name.len()..];
+    }
+    blanked.push_str(rest);
+    blanked
+}
+
+fn compare_snapshots(old: &Snapshot, new: &Snapshot, report: &mut VerifyRefactorReport) {
+    let new_keys = new
+        .symbols
+        .iter()
+        .enumerate()
+        .map(|(index, symbol)| (symbol.key(), index))
+        .collect::<HashMap<_, _>>();
+    // Maps each old definition to its counterpart after the change.
+    let mut counterpart = vec![None; old.symbols.len()];
+    let mut matched_new = vec![false; new.symbols.len()];
+    for (index, symbol) in old.symbols.iter().enumerate() {
+        if let Some(&new_index) = new_keys.get(&symbol.key()) {
+            counterpart[index] = Some(new_index);
+            matched_new[new_index] = true;
+        }
+    }
+
+    // Unmatched definitions pair up by body, preferring the same name, then the same file.
+    let mut by_body = BTreeMap::<(&str, &str), Vec<usize>>::new();
+    for (index, symbol) in new.symbols.iter().enumerate() {
+        if !matched_new[index] {
+            by_body
+                .entry((&symbol.body_hash, &symbol.kind))
+                .or_default()
+                .push(index);
+        }
+    }
+    let mut same_body = vec![false; old.symbols.len()];
+    for (index, symbol) in old.symbols.iter().enumerate() {
+        if counterpart[index].is_some() {
+            continue;
+        }
+        let Some(candidates) = by_body.get_mut(&(symbol.body_hash.as_str(), symbol.kind.as_str()))
+        else {
+            continue;
+        };
+        let best = candidates
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, candidate)| {
+                let candidate = &new.symbols[**candidate];
+                (
+                    candidate.symbol != symbol.symbol,
+                    candidate.file_path != symbol.file_path,
+                )
+            })
+            .map(|(position, _)| position);
+        if let Some(position) = best {
+            let new_index = candidates.remove(position);
+            counterpart[index] = Some(new_index);
+            matched_new[new_index] = true;
+            same_body[index] = true;
+        }
+    }
+    // A definition whose body changed still moved when its name and kind are unique on both sides.
+    let mut by_name = BTreeMap::<(&str, &str), (Vec<usize>, Vec<usize>)>::new();
+    for (index, symbol) in old.symbols.iter().enumerate() {
+        if counterpart[index].is_none() {
+            by_name
+                .entry((&symbol.symbol, &symbol.kind))
+                .or_default()
+                .0
+                .push(index);
+        }
+    }
+    for (index, symbol) in new.symbols.iter().enumerate() {
+        if !matched_new[index]
+            && let Some((_, new_indexes)) = by_name.get_mut(&(&symbol.symbol, &symbol.kind))
+        {
+            new_indexes.push(index);
+        }
+    }
+    for (old_indexes, new_indexes) in by_name.values() {
+        if let ([old_index], [new_index]) = (old_indexes.as_slice(), new_indexes.as_slice()) {
+            counterpart[*old_index] = Some(*new_index);
+            matched_new[*new_index] = true;
+        }
+    }
+
+    for (index, symbol) in old.symbols.iter().enumerate() {
+        let Some(new_index) = counterpart[index] else {
+            report.removed.push(symbol.reference());
+            continue;
+        };
+        let current = &new.symbols[new_index];
+        let relocated = RelocatedSymbol {
+            kind: symbol.kind.clone(),
+            old_symbol: symbol.symbol.clone(),
+            new_symbol: current.symbol.clone(),
+            old_file: symbol.file_path.clone(),
+            new_file: current.file_path.clone(),
+            old_line: symbol.start_line,
+            new_line: current.start_line,
+            same_body: same_body[index],
+        };
+        if current.symbol != symbol.symbol {
+            report.renamed.push(relocated);
+        } else if current.file_path != symbol.file_path {
+            report.moved.push(relocated);
+        }
+    }
+    report.added = new
+        .symbols
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !matched_new[*index])
+        .map(|(_, symbol)| symbol.reference())
+        .collect();
+
+    // Names defined before but nowhere after are stale wherever they are still referenced.
+    let defined_after = new
+        .symbols
+        .iter()
+        .map(|symbol| symbol.symbol.as_str())
+        .collect::<BTreeSet<_>>();
+    let mut vanished = BTreeMap::<&str, Option<&str>>::new();
+    for (index, symbol) in old.symbols.iter().enumerate() {
+        if defined_after.contains(symbol.symbol.as_str()) {
+            continue;
+        }
+        let replacement =
+            counterpart[index].map(|new_index| new.symbols[new_index].symbol.as_str());
+        vanished
+            .entry(&symbol.symbol)
+            .and_modify(|existing| *existing = existing.or(replacement))
+            .or_insert(replacement);
+    }
+    let mut stale = new
+        .references
+        .iter()
+        .filter_map(|(file_path, line, symbol)| {
+            vanished
+                .get(symbol.as_str())
+                .map(|replacement| StaleReference {
+                    symbol: symbol.clone(),
+                    file_path: file_path.clone(),
+                    line: *line,
+                    replacement: replacement.map(str::to_string),
+                })
+        })
+        .collect::<Vec<_>>();
+    stale.sort_by(|left, right| {
+        (&left.file_path, left.line, &left.symbol).cmp(&(
+            &right.file_path,
+            right.line,
+            &right.symbol,
+        ))
+    });
+    stale.dedup_by(|left, right| {
+        left.file_path == right.file_path && left.line == right.line && left.symbol == right.symbol
+    });
+    report.stale_references = stale;
+
+    for (from, to, kind) in &old.edges {
+        let Some(new_from) = counterpart[*from] else {
+            continue;
+        };
+        let new_to = counterpart[*to];
+        if new_to.is_some_and(|new_to| new.edges.contains(&(new_from, new_to, kind.clone()))) {
+            continue;
+        }
+        report.lost_edges.push(LostEdge {
+            from_symbol: new.symbols[new_from].symbol.clone(),
+            from_file: new.symbols[new_from].file_path.clone(),
+            to_symbol: old.symbols[*to].symbol.clone(),
+            to_file: old.symbols[*to].file_path.clone(),
+            edge_kind: kind.clone(),
+            target_removed: new_to.is_none(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::blank_identifier;
+
+    #[test]
+    fn blank_identifier_only_replaces_whole_names() {
+        assert_eq!(
+            blank_identifier("fn load(loader: Load) { load_all(); load() }", "load"),
+            "fn $(loader: Load) { load_all(); $() }"
+        );
+    }
 }
@@ -0,0 +1,137 @@
+use std::path::Path;
+
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use super::{INVALID_PARAMS, RpcError, SERVED_METHODS, command_data, command_from_params};
+use crate::store::{ensure_store, open_connection, reuse_connections_on_this_thread};
+
+/// Runs `query` batch requests against one read transaction, so every response describes the
+/// same index state even if `index` or `watch` commits while the batch is running.
+///
+/// Each request names a served command in `command`; its remaining keys (other than `id`) are
+/// the command's arguments, as for JSON-RPC `params`.
+pub fn run_batch(
+    repo: &Path,
+    requests: &[JsonValue],
+    fail_fast: bool,
+) -> anyhow::Result<Vec<JsonValue>> {
+    let store = ensure_store(repo)?;
+    reuse_connections_on_this_thread();
+    let connection = open_connection(&store.db_path)?;
+    connection.execute_batch("BEGIN DEFERRED")?;
+    // A deferred transaction takes its snapshot at the first read, not at BEGIN.
+    connection.query_row("SELECT COUNT(*) FROM sqlite_schema", [], |row| {
+        row.get::<_, i64>(0)
+    })?;
+
+    let mut responses = Vec::with_capacity(requests.len());
+    for request in requests {
+        let id = request
+            .get("id")
+            .cloned()
+            .unwrap_or_else(|| JsonValue::from("request"));
+        let command = request
+            .get("command")
+            .and_then(JsonValue::as_str)
+            .unwrap_or("");
+        match run_request(repo, &store.db_path, command, request) {
+            Ok(data) => responses.push(serde_json::json!({
+                "id": id,
+                "ok": true,
+                "command": command,
+                "data": data,
+            })),
+            Err((code, message)) => {
+                responses.push(serde_json::json!({
+                    "id": id,
+                    "ok": false,
+                    "error": { "code": code, "message": message },
+                }));
+                if fail_fast {
+                    break;
+                }
+            }
+        }
+    }
+
+    connection.execute_batch("COMMIT")?;
+    Ok(responses)
+}
+
+fn run_request(
+    repo: &Path,
+    db_path: &Path,
+    command: &str,
+    request: &JsonValue,
+) -> Result<JsonValue, (&'static str, String)> {
+    if !SERVED_METHODS.contains(&command) {
+        return Err((
+            "UNSUPPORTED_BATCH_COMMAND",
+            format!("unsupported command '{command}'"),
+        ));
+    }
+    let params = match request {
+        JsonValue::Object(request) => request
+            .iter()
+            .filter(|(key, _)| !matches!(key.as_str(), "id" | "command"))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect::<JsonMap<_, _>>(),
+        _ => JsonMap::new(),
+    };
+    let parsed = command_from_params(command, repo, Some(&JsonValue::Object(params)))
+        .map_err(batch_error)?;
+    command_data(db_path, parsed).map_err(|error| ("INTERNAL_ERROR", format!("{error:#}")))
+}
+
+fn batch_error(error: RpcError) -> (&'static str, String) {
+    let code = if error.code == INVALID_PARAMS {
+        "USAGE_ERROR"
+    } else {
+        "INTERNAL_ERROR"
+    };
+    (code, error.message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_batch_accepts_served_commands_with_filters() {
+        let repo = tempfile::tempdir().expect("temp repo should be created");
+        std::fs::write(
+            repo.path().join("lib.rs"),
+            "pub fn launch() {}\npub fn start() { launch(); }\n",
+        )
+        .expect("fixture should be written");
+        let store = ensure_store(repo.path()).expect("store should bootstrap");
+        crate::indexer::index_repository(repo.path(), &store.db_path)
+            .expect("fixture should index");
+
+        let responses = run_batch(
+            repo.path(),
+            &[
+                serde_json::json!({ "id": "a", "command": "callers", "symbol": "launch" }),
+                serde_json::json!({
+                    "id": "b",
+                    "command": "find",
+                    "symbol": "launch",
+                    "scope": "tests",
+                }),
+                serde_json::json!({ "id": "c", "command": "find", "lines": 3 }),
+                serde_json::json!({ "id": "d", "command": "index" }),
+                serde_json::json!({ "id": "e", "command": "outline", "file": "lib.rs" }),
+            ],
+            false,
+        )
+        .expect("batch should run");
+
+        assert_eq!(responses.len(), 5);
+        assert_eq!(responses[0]["data"]["results"][0]["symbol"], "start");
+        assert_eq!(responses[1]["ok"], true);
+        assert_eq!(responses[1]["data"]["results"], serde_json::json!([]));
+        assert_eq!(responses[2]["error"]["code"], "USAGE_ERROR");
+        assert_eq!(responses[3]["error"]["code"], "UNSUPPORTED_BATCH_COMMAND");
+        assert_eq!(responses[4]["data"]["file"], "lib.rs");
+    }
+}
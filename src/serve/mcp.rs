@@ -0,0 +1,235 @@
+use std::any::TypeId;
+use std::num::NonZeroUsize;
+
+use clap::{Arg, ArgAction, CommandFactory};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use super::{
+    COMMAND_FAILED, INVALID_PARAMS, METHOD_NOT_FOUND, RpcError, SERVER_FIXED_ARGS, ServeSession,
+};
+use crate::cli::Cli;
+
+/// Commands registered as MCP tools. Tool names match the CLI subcommands.
+pub const MCP_TOOLS: &[&str] = &[
+    "find",
+    "refs",
+    "explain",
+    "snippet",
+    "context",
+    "diff-impact",
+    "verify-plan",
+    "outline",
+    "tests-for",
+];
+
+/// Protocol revisions this server can speak, newest first.
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+pub(super) fn call(
+    session: &mut ServeSession,
+    method: &str,
+    params: Option<&JsonValue>,
+) -> Result<JsonValue, RpcError> {
+    match method {
+        "initialize" => Ok(initialize(params)),
+        "ping" => Ok(serde_json::json!({})),
+        "tools/list" => Ok(serde_json::json!({ "tools": tool_definitions() })),
+        "tools/call" => call_tool(session, params),
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("unsupported method '{method}'"),
+        )),
+    }
+}
+
+fn initialize(params: Option<&JsonValue>) -> JsonValue {
+    let requested = params
+        .and_then(|params| params.get("protocolVersion"))
+        .and_then(JsonValue::as_str);
+    let protocol_version = requested
+        .filter(|version| PROTOCOL_VERSIONS.contains(version))
+        .unwrap_or(PROTOCOL_VERSIONS[0]);
+    serde_json::json!({
+        "protocolVersion": protocol_version,
+        "capabilities": { "tools": { "listChanged": false } },
+        "serverInfo": { "name": "repo-scout", "version": env!("CARGO_PKG_VERSION") },
+    })
+}
+
+fn call_tool(
+    session: &mut ServeSession,
+    params: Option<&JsonValue>,
+) -> Result<JsonValue, RpcError> {
+    let Some(name) = params
+        .and_then(|params| params.get("name"))
+        .and_then(JsonValue::as_str)
+    else {
+        return Err(RpcError::new(
+            INVALID_PARAMS,
+            "tools/call requires a tool name",
+        ));
+    };
+    if !MCP_TOOLS.contains(&name) {
+        return Err(RpcError::new(
+            INVALID_PARAMS,
+            format!("unknown tool '{name}'"),
+        ));
+    }
+    let arguments = params.and_then(|params| params.get("arguments"));
+    match session.query(name, arguments) {
+        Ok(envelope) => Ok(serde_json::json!({
+            "content": [{ "type": "text", "text": envelope.to_string() }],
+            "structuredContent": envelope,
+            "isError": false,
+        })),
+        Err(error) if error.code == COMMAND_FAILED => Ok(serde_json::json!({
+            "content": [{ "type": "text", "text": error.message }],
+            "isError": true,
+        })),
+        Err(error) => Err(error),
+    }
+}
+
+fn tool_definitions() -> Vec<JsonValue> {
+    let cli = Cli::command();
+    MCP_TOOLS
+        .iter()
+        .filter_map(|name| cli.find_subcommand(name))
+        .map(|subcommand| {
+            serde_json::json!({
+                "name": subcommand.get_name(),
+                "description": subcommand.get_about().map(ToString::to_string),
+                "inputSchema": tool_input_schema(subcommand),
+            })
+        })
+        .collect()
+}
+
+/// Derives a JSON Schema for a tool's `arguments` from the subcommand's clap definition, so
+/// the schema tracks `src/cli.rs` without a second hand-written description.
+fn tool_input_schema(subcommand: &clap::Command) -> JsonValue {
+    let mut properties = JsonMap::new();
+    let mut required = Vec::new();
+    for arg in subcommand.get_arguments() {
+        let id = arg.get_id().as_str();
+        if SERVER_FIXED_ARGS.contains(&id) {
+            continue;
+        }
+        if arg.is_required_set() {
+            required.push(id.to_string());
+        }
+        properties.insert(id.to_string(), argument_schema(arg));
+    }
+    serde_json::json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+fn argument_schema(arg: &Arg) -> JsonValue {
+    if matches!(arg.get_action(), ArgAction::SetTrue) {
+        return serde_json::json!({ "type": "boolean", "default": false });
+    }
+    let mut schema = value_schema(arg);
+    if matches!(arg.get_action(), ArgAction::Append) {
+        schema = serde_json::json!({ "type": "array", "items": schema });
+    } else if let [default] = arg.get_default_values() {
+        let default = default.to_string_lossy();
+        let default = match schema.get("type").and_then(JsonValue::as_str) {
+            Some("integer") => default
+                .parse::<u64>()
+                .map_or_else(|_| JsonValue::from(default.as_ref()), JsonValue::from),
+            _ => JsonValue::from(default.as_ref()),
+        };
+        schema["default"] = default;
+    }
+    if let Some(long) = arg.get_long() {
+        schema["description"] = JsonValue::from(format!("CLI flag --{long}"));
+    }
+    schema
+}
+
+fn value_schema(arg: &Arg) -> JsonValue {
+    let possible_values = arg
+        .get_possible_values()
+        .iter()
+        .map(|value| value.get_name().to_string())
+        .collect::<Vec<_>>();
+    if !possible_values.is_empty() {
+        return serde_json::json!({ "type": "string", "enum": possible_values });
+    }
+    let value_type = arg.get_value_parser().type_id();
+    if value_type == TypeId::of::<NonZeroUsize>() {
+        serde_json::json!({ "type": "integer", "minimum": 1 })
+    } else if [
+        TypeId::of::<u32>(),
+        TypeId::of::<u64>(),
+        TypeId::of::<usize>(),
+    ]
+    .iter()
+    .any(|type_id| value_type == *type_id)
+    {
+        serde_json::json!({ "type": "integer", "minimum": 0 })
+    } else {
+        serde_json::json!({ "type": "string" })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_schema(name: &str) -> JsonValue {
+        tool_definitions()
+            .into_iter()
+            .find(|tool| tool["name"] == name)
+            .map(|tool| tool["inputSchema"].clone())
+            .expect("tool should be registered")
+    }
+
+    #[test]
+    fn tool_input_schemas_follow_clap_arguments() {
+        assert_eq!(tool_definitions().len(), MCP_TOOLS.len());
+
+        let find = tool_schema("find");
+        assert_eq!(find["required"], serde_json::json!([]));
+        assert_eq!(find["properties"]["symbol"]["type"], "string");
+        assert_eq!(find["properties"]["doc"]["type"], "string");
+        assert_eq!(find["properties"]["code_only"]["type"], "boolean");
+        assert_eq!(find["properties"]["max_results"]["type"], "integer");
+        assert_eq!(
+            find["properties"]["scope"]["enum"],
+            serde_json::json!(["all", "production", "tests"])
+        );
+        assert_eq!(find["properties"]["exclude_globs"]["type"], "array");
+        for fixed in SERVER_FIXED_ARGS {
+            assert!(find["properties"].get(*fixed).is_none(), "{fixed} leaked");
+        }
+
+        let diff_impact = tool_schema("diff-impact");
+        assert_eq!(diff_impact["properties"]["max_distance"]["default"], 2);
+        assert_eq!(
+            diff_impact["properties"]["changed_files"]["items"]["type"],
+            "string"
+        );
+
+        let context = tool_schema("context");
+        assert_eq!(context["required"], serde_json::json!(["task"]));
+        assert_eq!(context["properties"]["budget"]["default"], 1200);
+    }
+
+    #[test]
+    fn initialize_negotiates_protocol_version() {
+        let known = initialize(Some(
+            &serde_json::json!({ "protocolVersion": "2024-11-05" }),
+        ));
+        assert_eq!(known["protocolVersion"], "2024-11-05");
+        let unknown = initialize(Some(
+            &serde_json::json!({ "protocolVersion": "1999-01-01" }),
+        ));
+        assert_eq!(unknown["protocolVersion"], PROTOCOL_VERSIONS[0]);
+        assert_eq!(unknown["serverInfo"]["name"], "repo-scout");
+    }
+}
@@ -0,0 +1,636 @@
+use std::collections::BTreeSet;
+use std::ffi::OsString;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use clap::{ArgAction, CommandFactory, Parser};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use crate::cli::{Cli, Command};
+use crate::query::history::{churn_hotspots, owners};
+use crate::query::{
+    CallPathOptions, DiffImpactTestMode, callees_of, callers_of, diff_impact_for_changed_files,
+    file_deps, find_call_paths, hotspots, outline_file, refs_matches_scoped, snippet_for_symbol,
+    status_summary, tests_for_symbol, tests_for_symbol_via_calls, verify_plan_for_changed_files,
+};
+use crate::store::{StoreMetadata, ensure_store, reuse_connections_on_this_thread};
+use crate::{
+    AgentMetaIndex, IndexFreshness, agent_json, context_pack_results, context_results,
+    diff_impact_request, edge_results, explain_results, find_results, impact_results,
+    parse_symbol_query, query_scope_for_find_refs, ranked_symbol_matches, read_index_freshness,
+    related_results, resolve_candidates, verify_plan_request,
+};
+
+mod batch;
+mod mcp;
+
+pub use batch::run_batch;
+
+/// Commands callable as JSON-RPC methods; each answers with the command's agent JSON envelope.
+pub const SERVED_METHODS: &[&str] = &[
+    "status",
+    "find",
+    "refs",
+    "resolve",
+    "impact",
+    "context",
+    "tests-for",
+    "verify-plan",
+    "diff-impact",
+    "explain",
+    "snippet",
+    "outline",
+    "callers",
+    "callees",
+    "related",
+    "deps",
+    "hotspots",
+    "owners",
+    "call-path",
+];
+
+/// Command arguments the server fixes or that only shape terminal output; they are neither
+/// accepted as parameters nor advertised in tool schemas.
+const SERVER_FIXED_ARGS: &[&str] = &[
+    "repo",
+    "json",
+    "compact",
+    "auto_index",
+    "require_index_fresh",
+];
+
+/// How long a computed index freshness is reused before the working tree is walked again.
+const FRESHNESS_TTL: Duration = Duration::from_secs(2);
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const COMMAND_FAILED: i64 = -32000;
+
+/// Which method set a server speaks over its JSON-RPC 2.0 framing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    /// Query commands called directly as methods (see [`SERVED_METHODS`]).
+    Query,
+    /// Model Context Protocol: query commands exposed as tools.
+    Mcp,
+}
+
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Per-client server state: the store is opened once and SQLite connections stay open on the
+/// serving thread, so consecutive requests skip process start-up and statement preparation.
+struct ServeSession {
+    protocol: Protocol,
+    repo: PathBuf,
+    store: StoreMetadata,
+    freshness: Option<(Instant, IndexFreshness)>,
+}
+
+impl ServeSession {
+    fn open(repo: &Path, protocol: Protocol) -> anyhow::Result<Self> {
+        reuse_connections_on_this_thread();
+        Ok(Self {
+            protocol,
+            repo: repo.to_path_buf(),
+            store: ensure_store(repo)?,
+            freshness: None,
+        })
+    }
+
+    /// Answers one line of JSON-RPC 2.0 input (a request or a batch). Returns `None` when there
+    /// is nothing to send back, i.e. the input held only notifications.
+    fn handle_message(&mut self, message: &str) -> Option<JsonValue> {
+        let parsed = match serde_json::from_str::<JsonValue>(message) {
+            Ok(parsed) => parsed,
+            Err(error) => {
+                return Some(error_response(
+                    JsonValue::Null,
+                    RpcError::new(PARSE_ERROR, format!("parse error: {error}")),
+                ));
+            }
+        };
+        let JsonValue::Array(batch) = parsed else {
+            return self.handle_request(parsed);
+        };
+        if batch.is_empty() {
+            return Some(error_response(
+                JsonValue::Null,
+                RpcError::new(INVALID_REQUEST, "empty batch"),
+            ));
+        }
+        let responses = batch
+            .into_iter()
+            .filter_map(|request| self.handle_request(request))
+            .collect::<Vec<_>>();
+        (!responses.is_empty()).then_some(JsonValue::Array(responses))
+    }
+
+    fn handle_request(&mut self, request: JsonValue) -> Option<JsonValue> {
+        let JsonValue::Object(mut request) = request else {
+            return Some(error_response(
+                JsonValue::Null,
+                RpcError::new(INVALID_REQUEST, "request must be an object"),
+            ));
+        };
+        let id = request.remove("id");
+        let outcome = self.call(&request);
+        let id = id?;
+        Some(match outcome {
+            Ok(result) => serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => error_response(id, error),
+        })
+    }
+
+    fn call(&mut self, request: &JsonMap<String, JsonValue>) -> Result<JsonValue, RpcError> {
+        if request.get("jsonrpc").and_then(JsonValue::as_str) != Some("2.0") {
+            return Err(RpcError::new(INVALID_REQUEST, "jsonrpc must be \"2.0\""));
+        }
+        let Some(method) = request.get("method").and_then(JsonValue::as_str) else {
+            return Err(RpcError::new(INVALID_REQUEST, "method must be a string"));
+        };
+        match self.protocol {
+            Protocol::Query => self.query(method, request.get("params")),
+            Protocol::Mcp => mcp::call(self, method, request.get("params")),
+        }
+    }
+
+    /// Runs a served command and wraps its result in the command's agent JSON envelope.
+    fn query(&mut self, method: &str, params: Option<&JsonValue>) -> Result<JsonValue, RpcError> {
+        if !SERVED_METHODS.contains(&method) {
+            return Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unsupported method '{method}'"),
+            ));
+        }
+        let command = command_from_params(method, &self.repo, params)?;
+        let data = command_data(&self.store.db_path, command)
+            .map_err(|error| RpcError::new(COMMAND_FAILED, format!("{error:#}")))?;
+        let index = self
+            .index_meta()
+            .map_err(|error| RpcError::new(COMMAND_FAILED, format!("{error:#}")))?;
+        Ok(agent_json(
+            &format!("repo-scout/{method}@v1"),
+            method,
+            &self.repo,
+            &index,
+            data,
+        ))
+    }
+
+    fn index_meta(&mut self) -> anyhow::Result<AgentMetaIndex> {
+        let cached = self
+            .freshness
+            .as_ref()
+            .filter(|(computed_at, _)| computed_at.elapsed() < FRESHNESS_TTL);
+        let freshness = match cached {
+            Some((_, freshness)) => freshness.clone(),
+            None => {
+                let freshness = read_index_freshness(&self.repo, &self.store.db_path)?;
+                self.freshness = Some((Instant::now(), freshness.clone()));
+                freshness
+            }
+        };
+        Ok(AgentMetaIndex {
+            schema_version: self.store.schema_version,
+            indexed_at: freshness.indexed_at,
+            head_sha: freshness.head_sha,
+            stale: freshness.stale,
+        })
+    }
+}
+
+fn error_response(id: JsonValue, error: RpcError) -> JsonValue {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+/// Parses `params` with the same clap definitions as the command line, so method parameters
+/// take the CLI argument names (`symbol`, `code_only`, `changed_files`, ...) and defaults.
+/// Arguments in [`SERVER_FIXED_ARGS`] are rejected and `--json` is implied.
+fn command_from_params(
+    method: &str,
+    repo: &Path,
+    params: Option<&JsonValue>,
+) -> Result<Command, RpcError> {
+    let empty = JsonMap::new();
+    let params = match params {
+        None | Some(JsonValue::Null) => &empty,
+        Some(JsonValue::Object(params)) => params,
+        Some(_) => return Err(RpcError::new(INVALID_PARAMS, "params must be an object")),
+    };
+    let cli = Cli::command();
+    let subcommand = cli
+        .find_subcommand(method)
+        .ok_or_else(|| RpcError::new(METHOD_NOT_FOUND, format!("unknown method '{method}'")))?;
+
+    let mut argv = vec![
+        OsString::from("repo-scout"),
+        OsString::from(method),
+        OsString::from("--repo"),
+        repo.as_os_str().to_os_string(),
+    ];
+    let mut positionals = Vec::new();
+    let mut consumed = BTreeSet::new();
+    for arg in subcommand.get_arguments() {
+        let id = arg.get_id().as_str();
+        if SERVER_FIXED_ARGS.contains(&id) {
+            if id == "json" {
+                argv.push(OsString::from("--json"));
+            }
+            continue;
+        }
+        let long_key = arg.get_long().map(|long| long.replace('-', "_"));
+        let Some((key, value)) = params.get_key_value(id).or_else(|| {
+            long_key
+                .as_deref()
+                .and_then(|key| params.get_key_value(key))
+        }) else {
+            continue;
+        };
+        consumed.insert(key.as_str());
+        let values = match value {
+            JsonValue::Array(values) => values.iter().collect::<Vec<_>>(),
+            value => vec![value],
+        };
+        for value in values {
+            let text = match value {
+                JsonValue::Null => continue,
+                JsonValue::String(text) => text.clone(),
+                JsonValue::Number(number) => number.to_string(),
+                JsonValue::Bool(flag) if matches!(arg.get_action(), ArgAction::SetTrue) => {
+                    if *flag && let Some(long) = arg.get_long() {
+                        argv.push(OsString::from(format!("--{long}")));
+                    }
+                    continue;
+                }
+                _ => {
+                    return Err(RpcError::new(
+                        INVALID_PARAMS,
+                        format!("unsupported value for parameter '{key}'"),
+                    ));
+                }
+            };
+            match arg.get_long() {
+                Some(long) if !arg.is_positional() => {
+                    argv.push(OsString::from(format!("--{long}")));
+                    argv.push(OsString::from(text));
+                }
+                _ => positionals.push(OsString::from(text)),
+            }
+        }
+    }
+    if let Some(unknown) = params.keys().find(|key| !consumed.contains(key.as_str())) {
+        return Err(RpcError::new(
+            INVALID_PARAMS,
+            format!("unknown parameter '{unknown}' for method '{method}'"),
+        ));
+    }
+    if !positionals.is_empty() {
+        argv.push(OsString::from("--"));
+        argv.extend(positionals);
+    }
+
+    Cli::try_parse_from(argv)
+        .map(|cli| cli.command)
+        .map_err(|error| {
+            let rendered = error.to_string();
+            let message = rendered
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .trim_start_matches("error: ");
+            RpcError::new(INVALID_PARAMS, message)
+        })
+}
+
+/// Computes the `data` member of a command's agent JSON envelope.
+fn command_data(db_path: &Path, command: Command) -> anyhow::Result<JsonValue> {
+    let data = match command {
+        Command::Status(_) => serde_json::json!({ "summary": status_summary(db_path)? }),
+        Command::Find(args) => {
+            let (symbol_query, matches) = find_results(db_path, &args)?;
+            serde_json::json!({ "query": symbol_query.lookup_symbol, "results": matches })
+        }
+        Command::Refs(args) => {
+            let symbol_query = parse_symbol_query(&args.symbol);
+            let scope =
+                query_scope_for_find_refs(args.code_only, args.exclude_tests, args.filters.scope);
+            let matches = ranked_symbol_matches(
+                db_path,
+                refs_matches_scoped,
+                &symbol_query,
+                &scope,
+                &args.filters,
+                args.max_results,
+            )?;
+            serde_json::json!({ "query": symbol_query.lookup_symbol, "results": matches })
+        }
+        Command::Resolve(args) => {
+            let candidates = resolve_candidates(db_path, &args.symbol, &args.filters)?;
+            serde_json::json!({
+                "query": args.symbol,
+                "ambiguous": candidates.len() > 1,
+                "recommended_symbol_id": candidates.first().map(|entry| entry.symbol_id),
+                "candidates": candidates,
+            })
+        }
+        Command::Impact(args) => {
+            let (symbol, matches) = impact_results(db_path, &args)?;
+            serde_json::json!({ "query": symbol, "results": matches })
+        }
+        Command::Context(args) if args.pack => {
+            let pack = context_pack_results(db_path, &args)?;
+            let mut data = serde_json::to_value(pack)?;
+            data["mode"] = "pack".into();
+            data
+        }
+        Command::Context(args) => {
+            let matches = context_results(db_path, &args)?;
+            serde_json::json!({ "task": args.task, "budget": args.budget, "results": matches })
+        }
+        Command::TestsFor(args) if args.via_calls => {
+            let targets = tests_for_symbol_via_calls(db_path, &args.symbol, args.max_depth)?;
+            serde_json::json!({
+                "query": args.symbol,
+                "mode": "calls",
+                "max_depth": args.max_depth,
+                "results": targets,
+            })
+        }
+        Command::TestsFor(args) => {
+            let targets = tests_for_symbol(db_path, &args.symbol, args.include_support)?;
+            serde_json::json!({ "query": args.symbol, "results": targets })
+        }
+        Command::VerifyPlan(args) => {
+            let (changed_files, options) = verify_plan_request(&args)?;
+            let steps = verify_plan_for_changed_files(db_path, &changed_files, &options)?;
+            serde_json::json!({ "changed_files": changed_files, "results": steps })
+        }
+        Command::DiffImpact(args) => {
+            let (changed_files, options) = diff_impact_request(&args)?;
+            let matches = diff_impact_for_changed_files(db_path, &changed_files, &options)?;
+            serde_json::json!({
+                "changed_files": changed_files,
+                "max_distance": options.max_distance,
+                "include_tests": matches!(options.test_mode, DiffImpactTestMode::IncludeTests),
+                "results": matches,
+            })
+        }
+        Command::Explain(args) => {
+            let (symbol, matches) = explain_results(db_path, &args)?;
+            serde_json::json!({
+                "query": symbol,
+                "include_snippets": args.include_snippets,
+                "results": matches,
+            })
+        }
+        Command::Snippet(args) => {
+            let matches = snippet_for_symbol(db_path, &args.symbol, args.context)?;
+            serde_json::json!({ "query": args.symbol, "results": matches })
+        }
+        Command::Outline(args) => {
+            let entries = outline_file(db_path, &args.file)?;
+            serde_json::json!({ "file": args.file, "results": entries })
+        }
+        Command::Callers(args) => {
+            let (symbol, matches) = edge_results(db_path, callers_of, &args)?;
+            serde_json::json!({ "query": symbol, "results": matches })
+        }
+        Command::Callees(args) => {
+            let (symbol, matches) = edge_results(db_path, callees_of, &args)?;
+            serde_json::json!({ "query": symbol, "results": matches })
+        }
+        Command::Related(args) => {
+            let (symbol, results) = related_results(db_path, &args)?;
+            serde_json::json!({ "query": symbol, "results": results })
+        }
+        Command::Deps(args) => {
+            let deps = file_deps(db_path, &args.file)?;
+            serde_json::json!({
+                "file": args.file,
+                "depends_on": deps.depends_on,
+                "depended_on_by": deps.depended_on_by,
+            })
+        }
+        Command::Hotspots(args) if args.churn => serde_json::json!({
+            "mode": "churn",
+            "results": churn_hotspots(db_path, args.limit)?,
+        }),
+        Command::Hotspots(args) => {
+            serde_json::json!({ "results": hotspots(db_path, args.limit)? })
+        }
+        Command::Owners(args) => serde_json::json!({
+            "target": args.target,
+            "results": owners(db_path, &args.target, args.limit)?,
+        }),
+        Command::CallPath(args) => {
+            let report = find_call_paths(
+                db_path,
+                &args.from,
+                &args.to,
+                CallPathOptions {
+                    max_depth: args.max_depth,
+                    max_paths: args.max_paths,
+                    include_structural: args.include_structural,
+                },
+            )?;
+            serde_json::json!({
+                "from": args.from,
+                "to": args.to,
+                "path": report.first_path_names(),
+                "paths": report.paths,
+                "from_candidates": report.from_candidates,
+                "to_candidates": report.to_candidates,
+            })
+        }
+        _ => anyhow::bail!("command is not available over JSON-RPC"),
+    };
+    Ok(data)
+}
+
+fn serve_lines(
+    session: &mut ServeSession,
+    reader: impl BufRead,
+    writer: &mut impl Write,
+) -> anyhow::Result<()> {
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = session.handle_message(&line) {
+            writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Serves newline-delimited JSON-RPC 2.0 requests from stdin until it closes.
+pub fn serve_stdio(repo: &Path, protocol: Protocol) -> anyhow::Result<()> {
+    let mut session = ServeSession::open(repo, protocol)?;
+    let stdin = std::io::stdin().lock();
+    let mut stdout = std::io::stdout().lock();
+    serve_lines(&mut session, stdin, &mut stdout)
+}
+
+/// Serves newline-delimited JSON-RPC 2.0 requests on a Unix domain socket, one thread per
+/// client connection. `on_ready` runs once the socket is accepting connections.
+#[cfg(unix)]
+pub fn serve_socket(repo: &Path, socket: &Path, on_ready: impl FnOnce()) -> anyhow::Result<()> {
+    use std::io::BufReader;
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::net::UnixListener;
+
+    use anyhow::Context;
+
+    if let Ok(metadata) = std::fs::symlink_metadata(socket) {
+        if !metadata.file_type().is_socket() {
+            anyhow::bail!("refusing to replace {}: not a socket", socket.display());
+        }
+        std::fs::remove_file(socket)
+            .with_context(|| format!("failed to remove stale socket {}", socket.display()))?;
+    }
+    let listener = UnixListener::bind(socket)
+        .with_context(|| format!("failed to bind {}", socket.display()))?;
+    on_ready();
+
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let repo = repo.to_path_buf();
+        std::thread::spawn(move || {
+            let served = ServeSession::open(&repo, Protocol::Query).and_then(|mut session| {
+                let reader = BufReader::new(stream.try_clone()?);
+                let mut writer = stream;
+                serve_lines(&mut session, reader, &mut writer)
+            });
+            if let Err(error) = served {
+                eprintln!("serve: connection failed: {error:#}");
+            }
+        });
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn serve_socket(_repo: &Path, socket: &Path, _on_ready: impl FnOnce()) -> anyhow::Result<()> {
+    anyhow::bail!(
+        "cannot serve on {}: Unix domain sockets are not supported on this platform",
+        socket.display()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_session() -> (tempfile::TempDir, ServeSession) {
+        let repo = tempfile::tempdir().expect("temp repo should be created");
+        std::fs::write(
+            repo.path().join("lib.rs"),
+            "pub fn launch() {}\npub fn start() { launch(); }\n",
+        )
+        .expect("fixture should be written");
+        let store = ensure_store(repo.path()).expect("store should bootstrap");
+        crate::indexer::index_repository(repo.path(), &store.db_path)
+            .expect("fixture should index");
+        let session =
+            ServeSession::open(repo.path(), Protocol::Query).expect("session should open");
+        (repo, session)
+    }
+
+    fn call(session: &mut ServeSession, request: JsonValue) -> JsonValue {
+        session
+            .handle_message(&request.to_string())
+            .expect("request with an id should be answered")
+    }
+
+    #[test]
+    fn handle_message_wraps_results_in_agent_envelopes() {
+        let (_repo, mut session) = fixture_session();
+        let response = call(
+            &mut session,
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 7,
+                "method": "find",
+                "params": { "symbol": "launch", "max_results": 1 },
+            }),
+        );
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["result"]["schema"], "repo-scout/find@v1");
+        assert_eq!(response["result"]["ok"], true);
+        assert_eq!(response["result"]["data"]["query"], "launch");
+        assert_eq!(
+            response["result"]["data"]["results"][0]["file_path"],
+            "lib.rs"
+        );
+
+        let response = call(
+            &mut session,
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "callers",
+                "method": "callers",
+                "params": { "symbol": "launch" },
+            }),
+        );
+        assert_eq!(response["result"]["data"]["results"][0]["symbol"], "start");
+    }
+
+    #[test]
+    fn handle_message_reports_json_rpc_errors() {
+        let (_repo, mut session) = fixture_session();
+        let unknown_method = call(
+            &mut session,
+            serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "index" }),
+        );
+        assert_eq!(unknown_method["error"]["code"], METHOD_NOT_FOUND);
+
+        let unknown_param = call(
+            &mut session,
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "find",
+                "params": { "symbol": "launch", "repo": "/elsewhere" },
+            }),
+        );
+        assert_eq!(unknown_param["error"]["code"], INVALID_PARAMS);
+
+        let missing_param = call(
+            &mut session,
+            serde_json::json!({ "jsonrpc": "2.0", "id": 3, "method": "refs" }),
+        );
+        assert_eq!(missing_param["error"]["code"], INVALID_PARAMS);
+
+        let parse_error = session
+            .handle_message("{not json")
+            .expect("parse errors should be answered");
+        assert_eq!(parse_error["error"]["code"], PARSE_ERROR);
+
+        let notification = session.handle_message(
+            &serde_json::json!({ "jsonrpc": "2.0", "method": "status" }).to_string(),
+        );
+        assert!(notification.is_none());
+    }
+}
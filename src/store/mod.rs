@@ -1,11 +1,21 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 use anyhow::{Context, anyhow};
 use rusqlite::{Connection, ffi::ErrorCode};
 
 pub mod schema;
 
+const REUSED_STATEMENT_CACHE_CAPACITY: usize = 128;
+
+thread_local! {
+    static REUSED_CONNECTIONS: RefCell<Option<HashMap<PathBuf, Rc<Connection>>>> =
+        const { RefCell::new(None) };
+}
+
 #[derive(Debug)]
 pub struct StoreMetadata {
     pub db_path: PathBuf,
@@ -36,6 +46,33 @@ pub fn ensure_store(repo: &Path) -> anyhow::Result<StoreMetadata> {
     })
 }
 
+/// Opens `db_path` for querying. After [`reuse_connections_on_this_thread`], the calling thread
+/// gets back the same connection (and its prepared-statement cache) on every call.
+pub fn open_connection(db_path: &Path) -> anyhow::Result<Rc<Connection>> {
+    REUSED_CONNECTIONS.with(|cell| {
+        let mut reused = cell.borrow_mut();
+        let Some(connections) = reused.as_mut() else {
+            return Ok(Rc::new(Connection::open(db_path)?));
+        };
+        if let Some(connection) = connections.get(db_path) {
+            return Ok(Rc::clone(connection));
+        }
+        let connection = Connection::open(db_path)?;
+        connection.set_prepared_statement_cache_capacity(REUSED_STATEMENT_CACHE_CAPACITY);
+        let connection = Rc::new(connection);
+        connections.insert(db_path.to_path_buf(), Rc::clone(&connection));
+        Ok(connection)
+    })
+}
+
+/// Keeps connections handed out by [`open_connection`] open for the lifetime of the calling
+/// thread. Long-running processes such as `serve` use this; one-shot commands do not.
+pub fn reuse_connections_on_this_thread() {
+    REUSED_CONNECTIONS.with(|cell| {
+        cell.borrow_mut().get_or_insert_with(HashMap::new);
+    });
+}
+
 fn index_db_path(repo: &Path) -> PathBuf {
     repo.join(".repo-scout").join("index.db")
 }
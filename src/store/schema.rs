@@ -1,13 +1,13 @@
 use anyhow::Context;
 use rusqlite::{Connection, OptionalExtension};
 
-pub const SCHEMA_VERSION: i64 = 4;
+pub const SCHEMA_VERSION: i64 = 11;
 
 /// Creates the database schema and records the current schema version.
 ///
 /// This creates the necessary tables (meta, symbols, refs, indexed_files,
 /// text_occurrences, ast_definitions, ast_references, symbols_v2,
-/// symbol_edges_v2) and their associated indices if they do not already
+/// symbol_edges_v2, search and git history tables) and their associated indices if they do not already
 /// exist, then writes `SCHEMA_VERSION` into the `meta` table under the key
 /// `schema_version`.
 ///
@@ -95,6 +95,40 @@ pub fn bootstrap_schema(connection: &Connection) -> anyhow::Result<()> {
             provenance TEXT NOT NULL DEFAULT 'ast_definition',
             UNIQUE(from_symbol_id, to_symbol_id, edge_kind)
         );
+        CREATE TABLE IF NOT EXISTS search_documents (
+            symbol_id INTEGER PRIMARY KEY,
+            file_path TEXT NOT NULL,
+            term_count INTEGER NOT NULL,
+            token_cost INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS search_terms (
+            symbol_id INTEGER NOT NULL,
+            file_path TEXT NOT NULL,
+            field TEXT NOT NULL,
+            term TEXT NOT NULL,
+            frequency INTEGER NOT NULL,
+            PRIMARY KEY(symbol_id, field, term)
+        );
+        CREATE TABLE IF NOT EXISTS history_commits (
+            commit_id INTEGER PRIMARY KEY,
+            sha TEXT NOT NULL,
+            author TEXT NOT NULL,
+            email TEXT NOT NULL,
+            authored_at INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS history_file_changes (
+            commit_id INTEGER NOT NULL,
+            file_path TEXT NOT NULL,
+            lines_added INTEGER NOT NULL,
+            lines_deleted INTEGER NOT NULL,
+            PRIMARY KEY(commit_id, file_path)
+        );
+        CREATE TABLE IF NOT EXISTS history_line_changes (
+            commit_id INTEGER NOT NULL,
+            file_path TEXT NOT NULL,
+            start_line INTEGER NOT NULL,
+            end_line INTEGER NOT NULL
+        );
         CREATE INDEX IF NOT EXISTS idx_text_occurrences_symbol
             ON text_occurrences(symbol);
         CREATE INDEX IF NOT EXISTS idx_text_occurrences_file
@@ -111,10 +145,24 @@ pub fn bootstrap_schema(connection: &Connection) -> anyhow::Result<()> {
             ON symbol_edges_v2(from_symbol_id, edge_kind);
         CREATE INDEX IF NOT EXISTS idx_edges_v2_to_kind
             ON symbol_edges_v2(to_symbol_id, edge_kind);
+        CREATE INDEX IF NOT EXISTS idx_search_terms_term
+            ON search_terms(term);
+        CREATE INDEX IF NOT EXISTS idx_search_terms_file
+            ON search_terms(file_path);
+        CREATE INDEX IF NOT EXISTS idx_search_documents_file
+            ON search_documents(file_path);
+        CREATE INDEX IF NOT EXISTS idx_history_file_changes_file
+            ON history_file_changes(file_path);
+        CREATE INDEX IF NOT EXISTS idx_history_line_changes_file
+            ON history_line_changes(file_path, start_line);
         "#,
     )?;
     migrate_schema_v3(connection)?;
     migrate_schema_v4(connection)?;
+    migrate_schema_v5(connection)?;
+    migrate_schema_v9(connection)?;
+    migrate_schema_v11(connection)?;
+    clear_stale_extractions(connection)?;
 
     let upsert_schema_result = connection.execute(
         "INSERT OR REPLACE INTO meta(key, value) VALUES('schema_version', ?1)",
@@ -218,7 +266,6 @@ fn migrate_schema_v4(connection: &Connection) -> anyhow::Result<()> {
         "ALTER TABLE symbols_v2 ADD COLUMN line_count INTEGER",
     )?;
 
-    // Reserved for future phases (nullable)
     ensure_column_exists(
         connection,
         "symbols_v2",
@@ -253,6 +300,66 @@ fn migrate_schema_v4(connection: &Connection) -> anyhow::Result<()> {
     Ok(())
 }
 
+fn migrate_schema_v5(connection: &Connection) -> anyhow::Result<()> {
+    if table_has_column(connection, "symbols_v2", "test_path")? {
+        return Ok(());
+    }
+    connection.execute("ALTER TABLE symbols_v2 ADD COLUMN test_path TEXT", [])?;
+    // Test markers come from the Rust adapter; clear stored hashes so the next index run
+    // re-extracts files that were indexed before the column existed.
+    connection.execute(
+        "UPDATE indexed_files SET content_hash = '' WHERE file_path LIKE '%.rs'",
+        [],
+    )?;
+    Ok(())
+}
+
+fn migrate_schema_v9(connection: &Connection) -> anyhow::Result<()> {
+    ensure_column_exists(
+        connection,
+        "symbols_v2",
+        "doc_comment",
+        "ALTER TABLE symbols_v2 ADD COLUMN doc_comment TEXT",
+    )
+}
+
+fn migrate_schema_v11(connection: &Connection) -> anyhow::Result<()> {
+    if !table_has_column(connection, "indexed_files", "file_size")? {
+        // Rows indexed before these columns existed have no metadata, so the next run re-hashes
+        // them. History commit ids now increase with recency, which incremental history refreshes
+        // rely on, so forget the recorded HEAD and let the next run rebuild the history window.
+        connection.execute("ALTER TABLE indexed_files ADD COLUMN file_size INTEGER", [])?;
+        connection.execute("DELETE FROM meta WHERE key = 'history_head'", [])?;
+    }
+    ensure_column_exists(
+        connection,
+        "indexed_files",
+        "modified_ns",
+        "ALTER TABLE indexed_files ADD COLUMN modified_ns INTEGER",
+    )
+}
+
+/// The last schema version that changed what adapters extract for every language.
+const EXTRACTION_SCHEMA_VERSION: i64 = 9;
+
+fn clear_stale_extractions(connection: &Connection) -> anyhow::Result<()> {
+    let stored_version = connection
+        .query_row(
+            "SELECT value FROM meta WHERE key = 'schema_version'",
+            [],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()?
+        .and_then(|value| value.parse::<i64>().ok());
+    if stored_version.is_some_and(|version| version < EXTRACTION_SCHEMA_VERSION) {
+        // Complexity metrics (v6), visibility (v7), search documents (v8), and doc comments
+        // (v9) come from every adapter; clear stored hashes so the next index run re-extracts
+        // files indexed before they were recorded.
+        connection.execute("UPDATE indexed_files SET content_hash = ''", [])?;
+    }
+    Ok(())
+}
+
 fn ensure_column_exists(
     connection: &Connection,
     table: &str,
@@ -330,6 +437,37 @@ mod tests {
         assert!(error.to_string().contains("invalid schema_version value"));
     }
 
+    #[test]
+    fn bootstrap_schema_clears_file_hashes_when_upgrading_extracted_columns() {
+        let connection = Connection::open_in_memory().expect("sqlite in-memory db should open");
+        bootstrap_schema(&connection).expect("bootstrap should succeed");
+        connection
+            .execute_batch(
+                "INSERT INTO indexed_files(file_path, content_hash) VALUES ('src/lib.rs', 'abc');
+                 UPDATE meta SET value = '8' WHERE key = 'schema_version';",
+            )
+            .expect("v8 state should be written");
+
+        bootstrap_schema(&connection).expect("bootstrap should upgrade");
+        let hash: String = connection
+            .query_row("SELECT content_hash FROM indexed_files", [], |row| {
+                row.get(0)
+            })
+            .expect("hash should read");
+        assert_eq!(hash, "");
+
+        connection
+            .execute("UPDATE indexed_files SET content_hash = 'def'", [])
+            .expect("hash should update");
+        bootstrap_schema(&connection).expect("bootstrap should be idempotent");
+        let hash: String = connection
+            .query_row("SELECT content_hash FROM indexed_files", [], |row| {
+                row.get(0)
+            })
+            .expect("hash should read");
+        assert_eq!(hash, "def");
+    }
+
     #[test]
     fn bootstrap_schema_migrates_legacy_tables_and_sets_version() {
         let connection = Connection::open_in_memory().expect("sqlite in-memory db should open");
@@ -414,6 +552,10 @@ mod tests {
                 .any(|column| column == "qualified_symbol"),
             "qualified_symbol column should be added by migration"
         );
+        assert!(
+            symbols_columns.iter().any(|column| column == "test_path"),
+            "test_path column should be added by migration"
+        );
 
         let edges_columns = table_columns(&connection, "symbol_edges_v2");
         assert!(
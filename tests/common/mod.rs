@@ -5,6 +5,10 @@ use assert_cmd::Command;
 use tempfile::TempDir;
 
 pub fn repo_scout_cmd() -> Command {
+    Command::new(repo_scout_bin())
+}
+
+pub fn repo_scout_bin() -> PathBuf {
     let env_candidates = [
         "CARGO_BIN_EXE_repo_scout",
         "CARGO_BIN_EXE_repo-scout",
@@ -16,7 +20,7 @@ pub fn repo_scout_cmd() -> Command {
         if let Some(path) = std::env::var_os(name).map(PathBuf::from)
             && path.is_file()
         {
-            return Command::new(path);
+            return path;
         }
     }
 
@@ -30,7 +34,7 @@ pub fn repo_scout_cmd() -> Command {
     for bin_name in bin_candidates {
         let path = target_dir.join(format!("{bin_name}{}", std::env::consts::EXE_SUFFIX));
         if path.is_file() {
-            return Command::new(path);
+            return path;
         }
     }
 
@@ -85,3 +89,66 @@ pub fn write_file(root: &Path, relative_path: &str, contents: &str) -> PathBuf {
     fs::write(&full_path, contents).expect("fixture file should be written");
     full_path
 }
+
+/// A Rust module whose config functions (`load_config`, `parse_config`, `read_raw`) and cache
+/// functions (`warm_cache`, `collect_entries`) share only `normalize`. The `split-check` and
+/// `safe-steps` fixtures index it as `src/store.rs`.
+#[allow(dead_code)]
+pub const STORE_SOURCE: &str = r#"pub struct Config {
+    pub path: String,
+}
+
+pub fn load_config(path: &str) -> Config {
+    let raw = read_raw(path);
+    parse_config(&raw)
+}
+
+fn parse_config(raw: &str) -> Config {
+    Config { path: normalize(raw) }
+}
+
+fn read_raw(path: &str) -> String {
+    path.to_string()
+}
+
+pub struct Cache {
+    pub entries: Vec<String>,
+}
+
+pub fn warm_cache(config: &Config) -> Cache {
+    let entries = collect_entries(&config.path);
+    Cache { entries }
+}
+
+fn collect_entries(path: &str) -> Vec<String> {
+    vec![normalize(path)]
+}
+
+fn normalize(value: &str) -> String {
+    value.trim().to_string()
+}
+"#;
+
+#[allow(dead_code)]
+pub fn repo_arg(repo: &Path) -> &str {
+    repo.to_str().expect("repo path should be utf-8")
+}
+
+/// Runs `args` against `repo` with `--json` and parses stdout.
+#[allow(dead_code)]
+pub fn run_json(repo: &Path, args: &[&str]) -> serde_json::Value {
+    let mut command = args.to_vec();
+    command.extend(["--repo", repo_arg(repo), "--json"]);
+    serde_json::from_str(&run_stdout(&command)).expect("json output should parse")
+}
+
+/// Writes `files` (relative path and contents) into a new temporary repository and indexes it.
+#[allow(dead_code)]
+pub fn indexed_repo(files: &[(&str, &str)]) -> TempDir {
+    let repo = temp_repo();
+    for (relative_path, contents) in files {
+        write_file(repo.path(), relative_path, contents);
+    }
+    run_stdout(&["index", "--repo", repo_arg(repo.path())]);
+    repo
+}
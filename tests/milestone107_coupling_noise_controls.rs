@@ -74,3 +74,65 @@ fn milestone107_coupling_filters_tests_by_default_and_has_opt_in_flags() {
          default={default_out}\ninclude-tests={include_tests_out}"
     );
 }
+
+#[test]
+fn milestone107_coupling_treats_test_prefixed_files_in_any_language_as_tests() {
+    let repo = common::temp_repo();
+    common::write_file(
+        repo.path(),
+        "src/a.rs",
+        "pub fn a() -> i32 { crate::test_utils::fixture() }\n",
+    );
+    common::write_file(
+        repo.path(),
+        "src/test_utils.rs",
+        "pub fn fixture() -> i32 { crate::a::a() }\n",
+    );
+    common::write_file(
+        repo.path(),
+        "web/app.ts",
+        "import { helper } from \"./test_helpers\";\nexport function app() { return helper(); }\n",
+    );
+    common::write_file(
+        repo.path(),
+        "web/test_helpers.ts",
+        "import { app } from \"./app\";\nexport function helper() { return app(); }\n",
+    );
+    let repo_arg = repo.path().to_str().expect("repo path utf-8");
+    common::run_stdout(&["index", "--repo", repo_arg]);
+
+    let rows = |extra: &[&str]| -> Vec<(String, String)> {
+        let mut args = vec!["coupling", "--repo", repo_arg, "--json"];
+        args.extend_from_slice(extra);
+        let out = common::run_stdout(&args);
+        let payload: Value = serde_json::from_str(&out).expect("coupling json");
+        payload["results"]
+            .as_array()
+            .expect("rows")
+            .iter()
+            .map(|row| {
+                (
+                    row["file_a"].as_str().unwrap_or_default().to_string(),
+                    row["file_b"].as_str().unwrap_or_default().to_string(),
+                )
+            })
+            .collect()
+    };
+
+    let default_rows = rows(&[]);
+    assert!(
+        default_rows
+            .iter()
+            .all(|(file_a, file_b)| { !file_a.contains("/test_") && !file_b.contains("/test_") }),
+        "default should suppress test_* files: {default_rows:?}"
+    );
+    let include_rows = rows(&["--include-tests"]);
+    for test_file in ["src/test_utils.rs", "web/test_helpers.ts"] {
+        assert!(
+            include_rows
+                .iter()
+                .any(|(file_a, file_b)| file_a == test_file || file_b == test_file),
+            "--include-tests should report {test_file}: {include_rows:?}"
+        );
+    }
+}
@@ -0,0 +1,182 @@
+mod common;
+
+use common::run_stdout;
+use serde_json::Value;
+use std::path::Path;
+
+fn write_go_module_fixture(repo: &Path) {
+    common::write_file(repo, "go.work", "go 1.22\n\nuse (\n\t./svc\n\t./lib\n)\n");
+    common::write_file(
+        repo,
+        "svc/go.mod",
+        "module github.com/acme/svc\n\ngo 1.22\n",
+    );
+    common::write_file(
+        repo,
+        "svc/cmd/api/main.go",
+        r#"package main
+
+import (
+	"fmt"
+
+	"github.com/acme/lib/textutil"
+	db "github.com/acme/svc/internal/store"
+)
+
+func main() {
+	conn := db.Open("primary")
+	fmt.Println(textutil.Slug(conn.Name))
+}
+"#,
+    );
+    common::write_file(
+        repo,
+        "svc/internal/store/store.go",
+        r#"package store
+
+type Conn struct {
+	Name string
+}
+
+func Open(name string) *Conn {
+	return &Conn{Name: normalize(name)}
+}
+"#,
+    );
+    common::write_file(
+        repo,
+        "svc/internal/store/normalize.go",
+        "package store\n\nfunc normalize(name string) string {\n\treturn name\n}\n",
+    );
+    common::write_file(
+        repo,
+        "svc/internal/store/store_test.go",
+        "package store\n\nfunc Open(name string) *Conn {\n\treturn nil\n}\n",
+    );
+    common::write_file(repo, "lib/go.mod", "module github.com/acme/lib\n");
+    common::write_file(
+        repo,
+        "lib/textutil/slug.go",
+        "package textutil\n\nfunc Slug(value string) string {\n\treturn value\n}\n",
+    );
+}
+
+fn run_json(repo: &Path, command: &[&str]) -> Value {
+    let repo_arg = repo.to_str().expect("repo path should be utf-8");
+    let mut args = command.to_vec();
+    args.push("--repo");
+    args.push(repo_arg);
+    args.push("--json");
+    let output = run_stdout(&args);
+    serde_json::from_str(&output).expect("json output should parse")
+}
+
+fn result_files(payload: &Value, relationship: &str, symbol: &str) -> Vec<String> {
+    payload["results"]
+        .as_array()
+        .expect("results should be an array")
+        .iter()
+        .filter(|item| item["relationship"] == relationship && item["symbol"] == symbol)
+        .map(|item| item["file_path"].as_str().unwrap_or_default().to_string())
+        .collect()
+}
+
+#[test]
+fn milestone121_go_module_imports_resolve_to_package_files() {
+    let repo = common::temp_repo();
+    write_go_module_fixture(repo.path());
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+
+    let deps = run_stdout(&[
+        "deps",
+        "svc/cmd/api/main.go",
+        "--repo",
+        repo.path().to_str().unwrap(),
+    ]);
+    assert!(deps.contains("svc/internal/store/store.go"), "{deps}");
+    assert!(deps.contains("lib/textutil/slug.go"), "{deps}");
+    assert!(!deps.contains("store_test.go"), "{deps}");
+
+    let impact = run_json(repo.path(), &["impact", "Open"]);
+    assert_eq!(
+        result_files(&impact, "called_by", "main"),
+        vec!["svc/cmd/api/main.go".to_string()]
+    );
+
+    let impact = run_json(repo.path(), &["impact", "Slug"]);
+    assert_eq!(
+        result_files(&impact, "called_by", "main"),
+        vec!["svc/cmd/api/main.go".to_string()]
+    );
+}
+
+#[test]
+fn milestone121_go_module_imports_link_types_and_functions_through_imports_edges() {
+    let repo = common::temp_repo();
+    write_go_module_fixture(repo.path());
+    common::write_file(
+        repo.path(),
+        "svc/internal/api/handler.go",
+        r#"package api
+
+import "github.com/acme/svc/internal/store"
+
+func Describe(conn *store.Conn) string {
+	return conn.Name
+}
+"#,
+    );
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+
+    let impact = run_json(repo.path(), &["impact", "Conn"]);
+    assert_eq!(
+        result_files(&impact, "imported_by", "store"),
+        vec!["svc/internal/api/handler.go".to_string()],
+        "{impact}"
+    );
+
+    let impact = run_json(repo.path(), &["impact", "Open"]);
+    assert_eq!(
+        result_files(&impact, "imported_by", "db"),
+        vec!["svc/cmd/api/main.go".to_string()],
+        "{impact}"
+    );
+}
+
+#[test]
+fn milestone121_reindex_resolves_unchanged_importers_against_new_package_files() {
+    let repo = common::temp_repo();
+    write_go_module_fixture(repo.path());
+    common::write_file(
+        repo.path(),
+        "svc/cmd/worker/main.go",
+        r#"package main
+
+import "github.com/acme/svc/internal/store"
+
+func main() {
+	store.Close(store.Open("worker"))
+}
+"#,
+    );
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+    let impact = run_json(repo.path(), &["impact", "Close"]);
+    assert!(
+        result_files(&impact, "called_by", "main").is_empty(),
+        "{impact}"
+    );
+
+    common::write_file(
+        repo.path(),
+        "svc/internal/store/close.go",
+        "package store\n\nfunc Close(conn *Conn) {\n}\n",
+    );
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+
+    let impact = run_json(repo.path(), &["impact", "Close"]);
+    assert_eq!(
+        result_files(&impact, "called_by", "main"),
+        vec!["svc/cmd/worker/main.go".to_string()],
+        "{impact}"
+    );
+}
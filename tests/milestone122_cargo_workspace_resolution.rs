@@ -0,0 +1,241 @@
+mod common;
+
+use common::run_stdout;
+use serde_json::Value;
+use std::path::Path;
+
+fn write_cargo_workspace_fixture(repo: &Path) {
+    common::write_file(
+        repo,
+        "Cargo.toml",
+        "[workspace]\nmembers = [\"crates/*\"]\n\n[workspace.dependencies]\nstore = { package = \"acme-storage\", path = \"crates/storage\" }\n",
+    );
+    common::write_file(
+        repo,
+        "crates/app/Cargo.toml",
+        "[package]\nname = \"acme-app\"\n\n[dependencies]\nstore = { workspace = true }\nacme-legacy = { path = \"../legacy\" }\n",
+    );
+    common::write_file(
+        repo,
+        "crates/app/src/main.rs",
+        "mod cli;\n\nfn main() {\n    cli::run();\n}\n",
+    );
+    common::write_file(
+        repo,
+        "crates/app/src/cli.rs",
+        "use store::open;\n\npub fn run() {\n    open();\n    acme_legacy::open();\n}\n",
+    );
+    common::write_file(
+        repo,
+        "crates/storage/Cargo.toml",
+        "[package]\nname = \"acme-storage\"\n",
+    );
+    common::write_file(
+        repo,
+        "crates/storage/src/lib.rs",
+        "mod engine;\n\npub fn open() {\n    engine::start();\n}\n",
+    );
+    common::write_file(repo, "crates/storage/src/engine.rs", "pub fn start() {}\n");
+    common::write_file(
+        repo,
+        "crates/legacy/Cargo.toml",
+        "[package]\nname = \"acme-legacy\"\n",
+    );
+    common::write_file(repo, "crates/legacy/src/lib.rs", "pub fn open() {}\n");
+}
+
+fn run_json(repo: &Path, command: &[&str]) -> Value {
+    let repo_arg = repo.to_str().expect("repo path should be utf-8");
+    let mut args = command.to_vec();
+    args.push("--repo");
+    args.push(repo_arg);
+    args.push("--json");
+    let output = run_stdout(&args);
+    serde_json::from_str(&output).expect("json output should parse")
+}
+
+#[test]
+fn milestone122_call_path_crosses_workspace_crates() {
+    let repo = common::temp_repo();
+    write_cargo_workspace_fixture(repo.path());
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+
+    let payload = run_json(repo.path(), &["call-path", "main", "start"]);
+    assert_eq!(
+        payload["path"],
+        serde_json::json!(["main", "run", "open", "start"]),
+        "{payload}"
+    );
+}
+
+#[test]
+fn milestone122_renamed_and_dashed_crates_resolve_to_their_sources() {
+    let repo = common::temp_repo();
+    write_cargo_workspace_fixture(repo.path());
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+
+    let payload = run_json(repo.path(), &["callees", "run"]);
+    let mut callees = payload["results"]
+        .as_array()
+        .expect("results should be an array")
+        .iter()
+        .map(|item| {
+            format!(
+                "{}::{}",
+                item["file_path"].as_str().unwrap_or_default(),
+                item["symbol"].as_str().unwrap_or_default()
+            )
+        })
+        .collect::<Vec<_>>();
+    callees.sort();
+    assert_eq!(
+        callees,
+        vec![
+            "crates/legacy/src/lib.rs::open".to_string(),
+            "crates/storage/src/lib.rs::open".to_string(),
+        ],
+        "{payload}"
+    );
+}
+
+#[test]
+fn milestone122_crate_paths_do_not_fall_back_to_same_named_locals() {
+    let repo = common::temp_repo();
+    common::write_file(
+        repo.path(),
+        "Cargo.toml",
+        "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n",
+    );
+    common::write_file(
+        repo.path(),
+        "src/lib.rs",
+        "pub mod store;\n\npub fn compute(values: &[u32]) -> u32 {\n    values.iter().sum()\n}\n",
+    );
+    common::write_file(
+        repo.path(),
+        "src/store.rs",
+        "pub fn compute(values: &[u32]) -> u32 {\n    values.len() as u32\n}\n\npub fn total() -> u32 {\n    crate::compute(&[5])\n}\n",
+    );
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+
+    let payload = run_json(repo.path(), &["callees", "total"]);
+    let mut callees = payload["results"]
+        .as_array()
+        .expect("results should be an array")
+        .iter()
+        .map(|item| {
+            format!(
+                "{}:{}",
+                item["file_path"].as_str().unwrap_or_default(),
+                item["line"].as_u64().unwrap_or_default()
+            )
+        })
+        .collect::<Vec<_>>();
+    callees.sort();
+    assert_eq!(callees, vec!["src/lib.rs:3"], "{payload}");
+}
+
+fn callee_locations(repo: &Path, symbol: &str) -> Vec<String> {
+    let payload = run_json(repo, &["callees", symbol]);
+    let mut callees = payload["results"]
+        .as_array()
+        .expect("results should be an array")
+        .iter()
+        .map(|item| {
+            format!(
+                "{}:{}",
+                item["file_path"].as_str().unwrap_or_default(),
+                item["line"].as_u64().unwrap_or_default()
+            )
+        })
+        .collect::<Vec<_>>();
+    callees.sort();
+    callees
+}
+
+#[test]
+fn milestone122_super_paths_inside_inline_modules_stay_in_the_file() {
+    let repo = common::temp_repo();
+    common::write_file(
+        repo.path(),
+        "Cargo.toml",
+        "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n",
+    );
+    common::write_file(repo.path(), "src/lib.rs", "pub mod net;\n");
+    common::write_file(
+        repo.path(),
+        "src/net/mod.rs",
+        "pub mod retry;\n\npub fn f() -> u32 {\n    0\n}\n",
+    );
+    common::write_file(
+        repo.path(),
+        "src/net/retry.rs",
+        "pub fn backoff() -> u32 {\n    1\n}\n\npub fn f() -> u32 {\n    2\n}\n\nmod inner {\n    pub fn k() -> u32 {\n        super::f()\n    }\n\n    mod deeper {\n        fn deep() -> u32 {\n            super::super::backoff()\n        }\n    }\n}\n\n#[cfg(test)]\nmod tests {\n    use super::*;\n\n    #[test]\n    fn t() {\n        super::f();\n    }\n}\n",
+    );
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+
+    assert_eq!(
+        callee_locations(repo.path(), "k"),
+        vec!["src/net/retry.rs:5"]
+    );
+    assert_eq!(
+        callee_locations(repo.path(), "deep"),
+        vec!["src/net/retry.rs:1"]
+    );
+    assert_eq!(
+        callee_locations(repo.path(), "t"),
+        vec!["src/net/retry.rs:5"]
+    );
+}
+
+#[test]
+fn milestone122_crate_paths_fall_back_to_the_caller_file_when_the_module_lacks_the_symbol() {
+    let repo = common::temp_repo();
+    common::write_file(
+        repo.path(),
+        "Cargo.toml",
+        "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n",
+    );
+    common::write_file(
+        repo.path(),
+        "src/lib.rs",
+        "pub mod store;\npub use store::*;\n",
+    );
+    common::write_file(
+        repo.path(),
+        "src/store.rs",
+        "pub fn helper() -> u32 {\n    1\n}\n\npub fn total() -> u32 {\n    crate::helper()\n}\n",
+    );
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+
+    assert_eq!(
+        callee_locations(repo.path(), "total"),
+        vec!["src/store.rs:1"]
+    );
+}
+
+#[test]
+fn milestone122_reindex_follows_edited_manifests_for_unchanged_importers() {
+    let repo = common::temp_repo();
+    write_cargo_workspace_fixture(repo.path());
+    let deps_args = [
+        "deps",
+        "crates/app/src/cli.rs",
+        "--repo",
+        repo.path().to_str().unwrap(),
+    ];
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+    let deps = run_stdout(&deps_args);
+    assert!(deps.contains("crates/storage/src/lib.rs"), "{deps}");
+
+    common::write_file(
+        repo.path(),
+        "crates/app/Cargo.toml",
+        "[package]\nname = \"acme-app\"\n\n[dependencies]\nstore = { package = \"acme-legacy\", path = \"../legacy\" }\nacme-legacy = { path = \"../legacy\" }\n",
+    );
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+
+    let deps = run_stdout(&deps_args);
+    assert!(!deps.contains("crates/storage/src/lib.rs"), "{deps}");
+    assert!(deps.contains("crates/legacy/src/lib.rs"), "{deps}");
+}
@@ -0,0 +1,150 @@
+mod common;
+
+use common::run_stdout;
+use serde_json::Value;
+use std::path::Path;
+
+fn write_frontend_monorepo_fixture(repo: &Path) {
+    common::write_file(
+        repo,
+        "package.json",
+        r#"{ "private": true, "workspaces": ["apps/*", "packages/*"] }"#,
+    );
+    common::write_file(
+        repo,
+        "tsconfig.base.json",
+        r#"{
+  // shared settings for every app
+  "compilerOptions": { "strict": true, },
+}"#,
+    );
+    common::write_file(
+        repo,
+        "apps/web/tsconfig.json",
+        r#"{
+  "extends": "../../tsconfig.base.json",
+  "compilerOptions": { "baseUrl": ".", "paths": { "@/*": ["src/*"] } }
+}"#,
+    );
+    common::write_file(
+        repo,
+        "apps/web/src/pages/home.ts",
+        r#"import { Button } from "@/components/Button";
+import { formatPrice } from "@acme/shared";
+
+export function renderHome() {
+  return Button(formatPrice(10));
+}
+"#,
+    );
+    common::write_file(
+        repo,
+        "apps/web/src/components/Button.ts",
+        "export function Button(label: string) {\n  return label;\n}\n",
+    );
+    common::write_file(
+        repo,
+        "packages/shared/package.json",
+        r#"{ "name": "@acme/shared", "main": "dist/index.js", "types": "dist/index.d.ts" }"#,
+    );
+    common::write_file(
+        repo,
+        "packages/shared/src/index.ts",
+        "export function formatPrice(value: number) {\n  return `$${value}`;\n}\n",
+    );
+}
+
+fn run_json(repo: &Path, command: &[&str]) -> Value {
+    let repo_arg = repo.to_str().expect("repo path should be utf-8");
+    let mut args = command.to_vec();
+    args.push("--repo");
+    args.push(repo_arg);
+    args.push("--json");
+    let output = run_stdout(&args);
+    serde_json::from_str(&output).expect("json output should parse")
+}
+
+fn called_by_files(payload: &Value) -> Vec<String> {
+    payload["results"]
+        .as_array()
+        .expect("results should be an array")
+        .iter()
+        .filter(|item| item["relationship"] == "called_by")
+        .map(|item| item["file_path"].as_str().unwrap_or_default().to_string())
+        .collect()
+}
+
+#[test]
+fn milestone123_tsconfig_path_aliases_and_workspace_packages_resolve() {
+    let repo = common::temp_repo();
+    write_frontend_monorepo_fixture(repo.path());
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+
+    let deps = run_stdout(&[
+        "deps",
+        "apps/web/src/pages/home.ts",
+        "--repo",
+        repo.path().to_str().unwrap(),
+    ]);
+    assert!(deps.contains("apps/web/src/components/Button.ts"), "{deps}");
+    assert!(deps.contains("packages/shared/src/index.ts"), "{deps}");
+
+    let impact = run_json(repo.path(), &["impact", "formatPrice"]);
+    assert_eq!(
+        called_by_files(&impact),
+        vec!["apps/web/src/pages/home.ts".to_string()],
+        "{impact}"
+    );
+    let impact = run_json(repo.path(), &["impact", "Button"]);
+    assert_eq!(
+        called_by_files(&impact),
+        vec!["apps/web/src/pages/home.ts".to_string()],
+        "{impact}"
+    );
+}
+
+#[test]
+fn milestone123_reindex_follows_edited_tsconfig_paths_for_unchanged_importers() {
+    let repo = common::temp_repo();
+    write_frontend_monorepo_fixture(repo.path());
+    common::write_file(
+        repo.path(),
+        "apps/web/legacy/components/Button.ts",
+        "export function Button(label: string) {\n  return `[${label}]`;\n}\n",
+    );
+    common::write_file(
+        repo.path(),
+        "apps/web/tsconfig.json",
+        r#"{ "compilerOptions": { "baseUrl": ".", "paths": { "@/*": ["legacy/*"] } } }"#,
+    );
+    let deps_args = [
+        "deps",
+        "apps/web/src/pages/home.ts",
+        "--repo",
+        repo.path().to_str().unwrap(),
+    ];
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+    let deps = run_stdout(&deps_args);
+    assert!(
+        deps.contains("apps/web/legacy/components/Button.ts"),
+        "{deps}"
+    );
+    assert!(
+        !deps.contains("apps/web/src/components/Button.ts"),
+        "{deps}"
+    );
+
+    common::write_file(
+        repo.path(),
+        "apps/web/tsconfig.json",
+        r#"{ "compilerOptions": { "baseUrl": ".", "paths": { "@/*": ["src/*"] } } }"#,
+    );
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+
+    let deps = run_stdout(&deps_args);
+    assert!(deps.contains("apps/web/src/components/Button.ts"), "{deps}");
+    assert!(
+        !deps.contains("apps/web/legacy/components/Button.ts"),
+        "{deps}"
+    );
+}
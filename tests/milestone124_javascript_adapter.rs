@@ -0,0 +1,121 @@
+mod common;
+
+use common::run_stdout;
+use serde_json::Value;
+use std::path::Path;
+
+fn write_legacy_service_fixture(repo: &Path) {
+    common::write_file(
+        repo,
+        "services/billing/index.js",
+        r#"const { chargeCard } = require("./payments");
+const audit = require("./audit");
+
+exports.handleInvoice = function (invoice) {
+  audit.record(invoice);
+  return chargeCard(invoice.total);
+};
+"#,
+    );
+    common::write_file(
+        repo,
+        "services/billing/payments.js",
+        r#"function chargeCard(amount) {
+  return amount;
+}
+
+function refundCard(amount) {
+  return -amount;
+}
+
+module.exports = { chargeCard, refundCard };
+"#,
+    );
+    common::write_file(
+        repo,
+        "services/billing/audit.js",
+        "exports.record = (entry) => entry;\n",
+    );
+    common::write_file(
+        repo,
+        "web/src/app.mjs",
+        r#"import { renderPanel } from "./panel.jsx";
+
+export class App {
+  mount() {
+    return renderPanel();
+  }
+}
+"#,
+    );
+    common::write_file(
+        repo,
+        "web/src/panel.jsx",
+        "export const renderPanel = () => <section />;\n",
+    );
+}
+
+fn run_json(repo: &Path, command: &[&str]) -> Value {
+    let repo_arg = repo.to_str().expect("repo path should be utf-8");
+    let mut args = command.to_vec();
+    args.push("--repo");
+    args.push(repo_arg);
+    args.push("--json");
+    let output = run_stdout(&args);
+    serde_json::from_str(&output).expect("json output should parse")
+}
+
+fn result_symbols(payload: &Value) -> Vec<String> {
+    payload["results"]
+        .as_array()
+        .expect("results should be an array")
+        .iter()
+        .map(|item| item["symbol"].as_str().unwrap_or_default().to_string())
+        .collect()
+}
+
+#[test]
+fn milestone124_javascript_definitions_are_found() {
+    let repo = common::temp_repo();
+    write_legacy_service_fixture(repo.path());
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+
+    let find = run_json(repo.path(), &["find", "handleInvoice"]);
+    let result = &find["results"][0];
+    assert_eq!(result["file_path"], "services/billing/index.js", "{find}");
+    assert_eq!(result["why_matched"], "ast_definition", "{find}");
+
+    let find = run_json(repo.path(), &["find", "mount"]);
+    assert_eq!(find["results"][0]["file_path"], "web/src/app.mjs", "{find}");
+}
+
+#[test]
+fn milestone124_commonjs_and_esm_calls_resolve_across_files() {
+    let repo = common::temp_repo();
+    write_legacy_service_fixture(repo.path());
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+
+    let callers = run_stdout(&[
+        "callers",
+        "chargeCard",
+        "--repo",
+        repo.path().to_str().unwrap(),
+    ]);
+    assert!(callers.contains("handleInvoice"), "{callers}");
+
+    let callers = run_stdout(&["callers", "record", "--repo", repo.path().to_str().unwrap()]);
+    assert!(callers.contains("handleInvoice"), "{callers}");
+
+    let callers = run_stdout(&[
+        "callers",
+        "renderPanel",
+        "--repo",
+        repo.path().to_str().unwrap(),
+    ]);
+    assert!(callers.contains("mount"), "{callers}");
+
+    let dead = run_json(repo.path(), &["dead", "--scope", "production"]);
+    let dead_symbols = result_symbols(&dead);
+    assert!(dead_symbols.contains(&"refundCard".to_string()), "{dead}");
+    assert!(!dead_symbols.contains(&"chargeCard".to_string()), "{dead}");
+}
@@ -0,0 +1,229 @@
+mod common;
+
+use common::run_stdout;
+use serde_json::Value;
+use std::path::Path;
+
+fn write_src_layout_fixture(repo: &Path) {
+    common::write_file(
+        repo,
+        "services/billing/pyproject.toml",
+        r#"[project]
+name = "acme"
+
+[tool.setuptools.packages.find]
+where = ["src"]
+"#,
+    );
+    common::write_file(repo, "services/billing/src/acme/__init__.py", "");
+    common::write_file(
+        repo,
+        "services/billing/src/acme/db/__init__.py",
+        "from .models import load_user\n",
+    );
+    common::write_file(
+        repo,
+        "services/billing/src/acme/db/models.py",
+        "def load_user(user_id):\n    return {\"id\": user_id}\n",
+    );
+    common::write_file(
+        repo,
+        "services/billing/src/acme/api/handlers.py",
+        r#"from acme.db.models import load_user
+import acme.db
+
+
+def show_user(user_id):
+    return load_user(user_id)
+"#,
+    );
+    common::write_file(
+        repo,
+        "services/billing/tests/test_models.py",
+        r#"from acme.db.models import load_user
+
+
+def test_load_user():
+    assert load_user(1)["id"] == 1
+"#,
+    );
+    common::write_file(
+        repo,
+        "services/legacy/models.py",
+        "def load_user(user_id):\n    return None\n",
+    );
+}
+
+fn run_json(repo: &Path, command: &[&str]) -> Value {
+    let repo_arg = repo.to_str().expect("repo path should be utf-8");
+    let mut args = command.to_vec();
+    args.push("--repo");
+    args.push(repo_arg);
+    args.push("--json");
+    let output = run_stdout(&args);
+    serde_json::from_str(&output).expect("json output should parse")
+}
+
+fn called_by_files(payload: &Value) -> Vec<String> {
+    let mut files = payload["results"]
+        .as_array()
+        .expect("results should be an array")
+        .iter()
+        .filter(|item| item["relationship"] == "called_by")
+        .map(|item| item["file_path"].as_str().unwrap_or_default().to_string())
+        .collect::<Vec<_>>();
+    files.sort();
+    files.dedup();
+    files
+}
+
+#[test]
+fn milestone125_src_layout_imports_resolve_to_package_modules() {
+    let repo = common::temp_repo();
+    write_src_layout_fixture(repo.path());
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+
+    let deps = run_stdout(&[
+        "deps",
+        "services/billing/src/acme/api/handlers.py",
+        "--repo",
+        repo.path().to_str().unwrap(),
+    ]);
+    assert!(
+        deps.contains("services/billing/src/acme/db/models.py"),
+        "{deps}"
+    );
+
+    let impact = run_json(repo.path(), &["impact", "load_user"]);
+    assert_eq!(
+        called_by_files(&impact),
+        vec![
+            "services/billing/src/acme/api/handlers.py".to_string(),
+            "services/billing/tests/test_models.py".to_string(),
+        ],
+        "{impact}"
+    );
+}
+
+#[test]
+fn milestone125_tests_for_finds_tests_of_src_layout_modules() {
+    let repo = common::temp_repo();
+    write_src_layout_fixture(repo.path());
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+
+    let tests_for = run_json(
+        repo.path(),
+        &["tests-for", "load_user", "--include-support"],
+    );
+    let targets = tests_for["results"]
+        .as_array()
+        .expect("results should be an array")
+        .iter()
+        .map(|item| item["target"].as_str().unwrap_or_default().to_string())
+        .collect::<Vec<_>>();
+    assert!(
+        targets.contains(&"services/billing/tests/test_models.py".to_string()),
+        "{tests_for}"
+    );
+}
+
+#[test]
+fn milestone125_reindex_follows_new_package_markers_for_unchanged_importers() {
+    let repo = common::temp_repo();
+    common::write_file(
+        repo.path(),
+        "main.py",
+        "from acme.helpers import slugify\n\n\ndef run():\n    return slugify(\"a b\")\n",
+    );
+    common::write_file(
+        repo.path(),
+        "tools/acme/helpers.py",
+        "def slugify(text):\n    return text.replace(\" \", \"-\")\n",
+    );
+    common::write_file(
+        repo.path(),
+        "vendor/acme/helpers.py",
+        "def slugify(text):\n    return text\n",
+    );
+    let deps_args = ["deps", "main.py", "--repo", repo.path().to_str().unwrap()];
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+    let deps = run_stdout(&deps_args);
+    assert!(!deps.contains("tools/acme/helpers.py"), "{deps}");
+
+    common::write_file(repo.path(), "tools/acme/__init__.py", "");
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+
+    let deps = run_stdout(&deps_args);
+    assert!(deps.contains("tools/acme/helpers.py"), "{deps}");
+    assert!(!deps.contains("vendor/acme/helpers.py"), "{deps}");
+}
+
+#[test]
+fn milestone125_calls_through_module_aliases_resolve_to_the_module() {
+    let repo = common::temp_repo();
+    write_src_layout_fixture(repo.path());
+    common::write_file(
+        repo.path(),
+        "services/billing/src/acme/api/reports.py",
+        r#"import acme.db.models as m
+
+
+def report(user_id):
+    return m.load_user(user_id)
+"#,
+    );
+    common::write_file(
+        repo.path(),
+        "services/billing/src/acme/audit.py",
+        r#"from .db import models
+
+
+def audit(user_id):
+    return models.load_user(user_id)
+"#,
+    );
+    common::write_file(
+        repo.path(),
+        "services/billing/src/acme/export.py",
+        r#"import acme.db.models
+from .db import (
+    models as tables,
+)
+
+
+def export(user_id):
+    return acme.db.models.load_user(user_id)
+
+
+def archive(user_id):
+    return tables.load_user(user_id)
+"#,
+    );
+    run_stdout(&["index", "--repo", repo.path().to_str().unwrap()]);
+
+    let impact = run_json(repo.path(), &["impact", "load_user"]);
+    assert_eq!(
+        called_by_files(&impact),
+        vec![
+            "services/billing/src/acme/api/handlers.py".to_string(),
+            "services/billing/src/acme/api/reports.py".to_string(),
+            "services/billing/src/acme/audit.py".to_string(),
+            "services/billing/src/acme/export.py".to_string(),
+            "services/billing/tests/test_models.py".to_string(),
+        ],
+        "{impact}"
+    );
+    let callers = run_json(repo.path(), &["callers", "load_user"]);
+    let callers = callers["results"]
+        .as_array()
+        .expect("results should be an array")
+        .iter()
+        .map(|item| item["symbol"].as_str().unwrap_or_default().to_string())
+        .collect::<std::collections::BTreeSet<_>>();
+    for caller in ["report", "audit", "export", "archive"] {
+        assert!(
+            callers.contains(caller),
+            "{caller} missing from {callers:?}"
+        );
+    }
+}
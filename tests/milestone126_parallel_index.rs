@@ -0,0 +1,154 @@
+mod common;
+
+use rusqlite::Connection;
+use std::path::Path;
+
+const INDEXED_TABLES: &[&str] = &[
+    "indexed_files",
+    "text_occurrences",
+    "ast_definitions",
+    "ast_references",
+    "symbols_v2",
+    "symbol_edges_v2",
+];
+
+fn write_mixed_language_fixture(repo: &Path) {
+    for module in 0..12 {
+        common::write_file(
+            repo,
+            &format!("src/module_{module}.rs"),
+            &format!(
+                "pub fn entry_{module}() -> usize {{\n    helper_{module}() + crate::shared::shared_value()\n}}\n\nfn helper_{module}() -> usize {{\n    {module}\n}}\n"
+            ),
+        );
+    }
+    common::write_file(
+        repo,
+        "src/shared.rs",
+        "pub fn shared_value() -> usize {\n    1\n}\n",
+    );
+    common::write_file(
+        repo,
+        "web/app.ts",
+        "import { render } from \"./view\";\n\nexport function start() {\n  return render();\n}\n",
+    );
+    common::write_file(
+        repo,
+        "web/view.ts",
+        "export function render() {\n  return 1;\n}\n",
+    );
+    common::write_file(
+        repo,
+        "tools/report.py",
+        "from tools.format import pretty\n\n\ndef report():\n    return pretty(1)\n",
+    );
+    common::write_file(
+        repo,
+        "tools/format.py",
+        "def pretty(value):\n    return str(value)\n",
+    );
+    common::write_file(repo, "README.md", "# fixture\n");
+}
+
+fn index_with_jobs(repo: &Path, jobs: &str) {
+    common::run_stdout(&[
+        "index",
+        "--repo",
+        repo.to_str().expect("repo path should be utf-8"),
+        "--jobs",
+        jobs,
+    ]);
+}
+
+fn dump_index(repo: &Path) -> Vec<String> {
+    let connection = Connection::open(repo.join(".repo-scout").join("index.db"))
+        .expect("index database should open");
+    let mut rows = Vec::new();
+    for table in INDEXED_TABLES {
+        let mut statement = connection
+            .prepare(&format!("SELECT * FROM {table} ORDER BY rowid"))
+            .expect("table dump should prepare");
+        let column_count = statement.column_count();
+        let table_rows = statement
+            .query_map([], |row| {
+                let values = (0..column_count)
+                    .map(|index| {
+                        row.get::<_, rusqlite::types::Value>(index)
+                            .map(|value| format!("{value:?}"))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(format!("{table}: {}", values.join(" | ")))
+            })
+            .expect("table rows should be queryable");
+        for row in table_rows {
+            rows.push(row.expect("table row should decode"));
+        }
+    }
+    rows
+}
+
+#[test]
+fn milestone126_parallel_index_matches_sequential_index() {
+    let sequential = common::temp_repo();
+    let parallel = common::temp_repo();
+    write_mixed_language_fixture(sequential.path());
+    write_mixed_language_fixture(parallel.path());
+
+    index_with_jobs(sequential.path(), "1");
+    index_with_jobs(parallel.path(), "4");
+
+    let sequential_rows = dump_index(sequential.path());
+    assert!(
+        sequential_rows
+            .iter()
+            .any(|row| row.starts_with("symbol_edges_v2: ")),
+        "fixture should produce edges"
+    );
+    assert_eq!(sequential_rows, dump_index(parallel.path()));
+}
+
+#[test]
+fn milestone126_parallel_reindex_matches_sequential_reindex() {
+    let sequential = common::temp_repo();
+    let parallel = common::temp_repo();
+    write_mixed_language_fixture(sequential.path());
+    write_mixed_language_fixture(parallel.path());
+    index_with_jobs(sequential.path(), "1");
+    index_with_jobs(parallel.path(), "3");
+
+    for repo in [sequential.path(), parallel.path()] {
+        common::write_file(
+            repo,
+            "src/shared.rs",
+            "pub fn shared_value() -> usize {\n    other_value()\n}\n\nfn other_value() -> usize {\n    2\n}\n",
+        );
+        std::fs::remove_file(repo.join("web/view.ts")).expect("fixture file should be removed");
+    }
+    index_with_jobs(sequential.path(), "1");
+    let output = common::run_stdout(&[
+        "index",
+        "--repo",
+        parallel.path().to_str().expect("repo path should be utf-8"),
+        "--jobs",
+        "3",
+    ]);
+    assert!(output.contains("indexed_files: 1"), "{output}");
+
+    assert_eq!(dump_index(sequential.path()), dump_index(parallel.path()));
+}
+
+#[test]
+fn milestone126_index_rejects_zero_jobs() {
+    let repo = common::temp_repo();
+    write_mixed_language_fixture(repo.path());
+    common::repo_scout_cmd()
+        .args([
+            "index",
+            "--repo",
+            repo.path().to_str().expect("repo path should be utf-8"),
+            "--jobs",
+            "0",
+        ])
+        .assert()
+        .failure();
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+struct WatchProcess {
+    child: Child,
+    lines: Receiver<String>,
+}
+
+impl WatchProcess {
+    fn spawn(repo: &Path) -> Self {
+        let mut child = Command::new(common::repo_scout_bin())
+            .args([
+                "watch",
+                "--repo",
+                repo.to_str().expect("repo path should be utf-8"),
+                "--debounce-ms",
+                "100",
+            ])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("watch should start");
+        let stdout = child.stdout.take().expect("stdout should be piped");
+        let (sender, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { child, lines }
+    }
+
+    /// Collects output lines until one starts with `prefix`, failing after a generous timeout.
+    fn wait_for(&self, prefix: &str) -> Vec<String> {
+        let deadline = Instant::now() + Duration::from_secs(30);
+        let mut seen = Vec::new();
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            let Ok(line) = self.lines.recv_timeout(remaining) else {
+                break;
+            };
+            let done = line.starts_with(prefix);
+            seen.push(line);
+            if done {
+                return seen;
+            }
+        }
+        panic!("timed out waiting for `{prefix}`; output so far: {seen:?}");
+    }
+}
+
+impl Drop for WatchProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn write_fixture(repo: &Path) {
+    common::write_file(
+        repo,
+        "src/lib.rs",
+        "mod util;\n\npub fn run() -> i32 {\n    util::helper()\n}\n",
+    );
+    common::write_file(repo, "src/util.rs", "pub fn helper() -> i32 {\n    1\n}\n");
+}
+
+fn callers(repo: &Path, symbol: &str) -> String {
+    common::run_stdout(&[
+        "callers",
+        symbol,
+        "--repo",
+        repo.to_str().expect("repo path should be utf-8"),
+    ])
+}
+
+#[test]
+fn milestone127_watch_reindexes_touched_files_and_replays_dependent_edges() {
+    let repo = common::temp_repo();
+    write_fixture(repo.path());
+    let watch = WatchProcess::spawn(repo.path());
+    let startup = watch.wait_for("watching:");
+    assert!(
+        startup.iter().any(|line| line == "indexed_files: 2"),
+        "{startup:?}"
+    );
+    assert!(callers(repo.path(), "helper").contains("run"));
+
+    common::write_file(
+        repo.path(),
+        "src/util.rs",
+        "pub fn helper() -> i32 {\n    extra()\n}\n\npub fn extra() -> i32 {\n    2\n}\n",
+    );
+    let batch = watch.wait_for("dependent:");
+    assert!(
+        batch.iter().any(|line| line == "reindexed: src/util.rs"),
+        "{batch:?}"
+    );
+    assert!(
+        batch.iter().any(|line| line == "dependent: src/lib.rs"),
+        "{batch:?}"
+    );
+    assert!(callers(repo.path(), "helper").contains("run"));
+    assert!(callers(repo.path(), "extra").contains("helper"));
+}
+
+#[test]
+fn milestone127_watch_prunes_deleted_files() {
+    let repo = common::temp_repo();
+    write_fixture(repo.path());
+    common::write_file(repo.path(), "src/legacy.rs", "pub fn legacy_only() {}\n");
+    let watch = WatchProcess::spawn(repo.path());
+    watch.wait_for("watching:");
+
+    std::fs::remove_file(repo.path().join("src/legacy.rs")).expect("fixture should be removed");
+    let batch = watch.wait_for("removed:");
+    assert!(
+        batch.iter().any(|line| line == "removed: src/legacy.rs"),
+        "{batch:?}"
+    );
+
+    let find = common::run_stdout(&[
+        "find",
+        "legacy_only",
+        "--repo",
+        repo.path().to_str().expect("repo path should be utf-8"),
+    ]);
+    assert!(!find.contains("src/legacy.rs"), "{find}");
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use serde_json::{Value, json};
+
+fn write_fixture(repo: &Path) {
+    common::write_file(
+        repo,
+        "src/lib.rs",
+        "mod util;\n\npub fn run() -> i32 {\n    util::helper()\n}\n",
+    );
+    common::write_file(repo, "src/util.rs", "pub fn helper() -> i32 {\n    1\n}\n");
+}
+
+fn indexed_repo() -> tempfile::TempDir {
+    let repo = common::temp_repo();
+    write_fixture(repo.path());
+    common::run_stdout(&[
+        "index",
+        "--repo",
+        repo.path().to_str().expect("repo path should be utf-8"),
+    ]);
+    repo
+}
+
+/// Sends newline-delimited requests to `serve` over stdio and returns one parsed value per
+/// response line.
+fn serve_stdio(repo: &Path, requests: &[Value]) -> Vec<Value> {
+    let mut child = Command::new(common::repo_scout_bin())
+        .args([
+            "serve",
+            "--repo",
+            repo.to_str().expect("repo path should be utf-8"),
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("serve should start");
+    let mut stdin = child.stdin.take().expect("stdin should be piped");
+    for request in requests {
+        writeln!(stdin, "{request}").expect("request should be written");
+    }
+    drop(stdin);
+    let output = child.wait_with_output().expect("serve should exit");
+    assert!(output.status.success());
+    String::from_utf8(output.stdout)
+        .expect("serve output should be utf-8")
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("response should be json"))
+        .collect()
+}
+
+#[test]
+fn milestone128_serve_stdio_matches_cli_agent_envelopes() {
+    let repo = indexed_repo();
+    let repo_arg = repo.path().to_str().expect("repo path should be utf-8");
+    let responses = serve_stdio(
+        repo.path(),
+        &[
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "find", "params": { "symbol": "helper" } }),
+            json!({ "jsonrpc": "2.0", "method": "status" }),
+            json!([
+                { "jsonrpc": "2.0", "id": 2, "method": "refs", "params": { "symbol": "helper" } },
+                {
+                    "jsonrpc": "2.0",
+                    "id": 3,
+                    "method": "diff-impact",
+                    "params": { "changed_files": ["src/util.rs"] },
+                },
+            ]),
+            json!({ "jsonrpc": "2.0", "id": 4, "method": "watch" }),
+        ],
+    );
+    assert_eq!(responses.len(), 3, "{responses:?}");
+
+    let find = &responses[0];
+    assert_eq!(find["jsonrpc"], "2.0");
+    assert_eq!(find["id"], 1);
+    let cli_find: Value = serde_json::from_str(&common::run_stdout(&[
+        "find", "helper", "--repo", repo_arg, "--json",
+    ]))
+    .expect("cli output should be json");
+    for key in ["schema", "command", "ok", "meta", "data"] {
+        assert_eq!(find["result"][key], cli_find[key], "mismatched `{key}`");
+    }
+
+    let batch = responses[1]
+        .as_array()
+        .expect("batch should answer with an array");
+    assert_eq!(batch[0]["result"]["schema"], "repo-scout/refs@v1");
+    assert_eq!(batch[1]["result"]["command"], "diff-impact");
+    assert_eq!(
+        batch[1]["result"]["data"]["changed_files"],
+        json!(["src/util.rs"])
+    );
+    let impacted = batch[1]["result"]["data"]["results"]
+        .as_array()
+        .expect("diff-impact results should be an array");
+    assert!(
+        impacted.iter().any(|entry| entry["symbol"] == "run"),
+        "{impacted:?}"
+    );
+
+    assert_eq!(responses[2]["id"], 4);
+    assert_eq!(responses[2]["error"]["code"], -32601);
+}
+
+#[cfg(unix)]
+#[test]
+fn milestone128_serve_socket_keeps_answering_connections() {
+    use std::os::unix::net::UnixStream;
+
+    let repo = indexed_repo();
+    let socket = repo.path().join("scout.sock");
+    let mut child = Command::new(common::repo_scout_bin())
+        .args([
+            "serve",
+            "--repo",
+            repo.path().to_str().expect("repo path should be utf-8"),
+            "--socket",
+            socket.to_str().expect("socket path should be utf-8"),
+        ])
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("serve should start");
+    let mut ready = String::new();
+    BufReader::new(child.stdout.take().expect("stdout should be piped"))
+        .read_line(&mut ready)
+        .expect("ready line should be readable");
+    assert!(ready.starts_with("serving: "), "{ready}");
+
+    for id in 1..=2 {
+        let stream = UnixStream::connect(&socket).expect("socket should accept connections");
+        let mut writer = stream.try_clone().expect("stream should clone");
+        writeln!(
+            writer,
+            "{}",
+            json!({ "jsonrpc": "2.0", "id": id, "method": "callers", "params": { "symbol": "helper" } })
+        )
+        .expect("request should be written");
+        let mut line = String::new();
+        BufReader::new(stream)
+            .read_line(&mut line)
+            .expect("response should be readable");
+        let response: Value = serde_json::from_str(&line).expect("response should be json");
+        assert_eq!(response["id"], id);
+        assert_eq!(response["result"]["data"]["results"][0]["symbol"], "run");
+    }
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
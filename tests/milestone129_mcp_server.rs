@@ -0,0 +1,152 @@
+mod common;
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use serde_json::{Value, json};
+
+fn indexed_repo() -> tempfile::TempDir {
+    let repo = common::temp_repo();
+    common::write_file(
+        repo.path(),
+        "src/lib.rs",
+        "mod util;\n\npub fn run() -> i32 {\n    util::helper()\n}\n",
+    );
+    common::write_file(
+        repo.path(),
+        "src/util.rs",
+        "pub fn helper() -> i32 {\n    1\n}\n",
+    );
+    common::run_stdout(&[
+        "index",
+        "--repo",
+        repo.path().to_str().expect("repo path should be utf-8"),
+    ]);
+    repo
+}
+
+fn mcp_session(repo: &Path, messages: &[Value]) -> Vec<Value> {
+    let mut child = Command::new(common::repo_scout_bin())
+        .args([
+            "serve",
+            "--mcp",
+            "--repo",
+            repo.to_str().expect("repo path should be utf-8"),
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("mcp server should start");
+    let mut stdin = child.stdin.take().expect("stdin should be piped");
+    for message in messages {
+        writeln!(stdin, "{message}").expect("message should be written");
+    }
+    drop(stdin);
+    let output = child.wait_with_output().expect("mcp server should exit");
+    assert!(output.status.success());
+    String::from_utf8(output.stdout)
+        .expect("mcp output should be utf-8")
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("response should be json"))
+        .collect()
+}
+
+#[test]
+fn milestone129_mcp_server_lists_and_calls_tools() {
+    let repo = indexed_repo();
+    let responses = mcp_session(
+        repo.path(),
+        &[
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2025-06-18",
+                    "capabilities": {},
+                    "clientInfo": { "name": "test", "version": "0" },
+                },
+            }),
+            json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 3,
+                "method": "tools/call",
+                "params": { "name": "refs", "arguments": { "symbol": "helper" } },
+            }),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 4,
+                "method": "tools/call",
+                "params": { "name": "verify-plan", "arguments": {} },
+            }),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 5,
+                "method": "tools/call",
+                "params": { "name": "hotspots", "arguments": {} },
+            }),
+        ],
+    );
+    assert_eq!(responses.len(), 5, "{responses:?}");
+
+    assert_eq!(responses[0]["result"]["protocolVersion"], "2025-06-18");
+    assert!(responses[0]["result"]["capabilities"]["tools"].is_object());
+
+    let tools = responses[1]["result"]["tools"]
+        .as_array()
+        .expect("tools/list should return an array");
+    let names = tools
+        .iter()
+        .map(|tool| tool["name"].as_str().expect("tool name should be a string"))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            "find",
+            "refs",
+            "explain",
+            "snippet",
+            "context",
+            "diff-impact",
+            "verify-plan",
+            "outline",
+            "tests-for",
+        ]
+    );
+    let verify_plan = &tools[6]["inputSchema"];
+    assert_eq!(verify_plan["type"], "object");
+    assert_eq!(verify_plan["properties"]["changed_files"]["type"], "array");
+    assert_eq!(verify_plan["properties"]["unstaged"]["type"], "boolean");
+
+    let refs = &responses[2]["result"];
+    assert_eq!(refs["isError"], false);
+    assert_eq!(refs["structuredContent"]["schema"], "repo-scout/refs@v1");
+    let text: Value = serde_json::from_str(
+        refs["content"][0]["text"]
+            .as_str()
+            .expect("tool text should be a string"),
+    )
+    .expect("tool text should hold the json envelope");
+    assert_eq!(text, refs["structuredContent"]);
+    assert!(
+        text["data"]["results"]
+            .as_array()
+            .expect("refs results should be an array")
+            .iter()
+            .any(|entry| entry["file_path"] == "src/lib.rs")
+    );
+
+    let failed = &responses[3]["result"];
+    assert_eq!(failed["isError"], true);
+    assert!(
+        failed["content"][0]["text"]
+            .as_str()
+            .is_some_and(|text| text.contains("no changed files")),
+        "{failed}"
+    );
+
+    assert_eq!(responses[4]["error"]["code"], -32602);
+}
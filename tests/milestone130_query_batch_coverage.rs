@@ -0,0 +1,91 @@
+mod common;
+
+use serde_json::Value;
+
+#[test]
+fn milestone130_query_batch_runs_read_only_commands_with_filters() {
+    let repo = common::temp_repo();
+    common::write_file(
+        repo.path(),
+        "src/lib.rs",
+        "mod util;\n\npub fn run() -> i32 {\n    util::helper()\n}\n",
+    );
+    common::write_file(
+        repo.path(),
+        "src/util.rs",
+        "pub fn helper() -> i32 {\n    1\n}\n",
+    );
+    let requests = [
+        r#"{"id":"callers","command":"callers","symbol":"helper"}"#,
+        r#"{"id":"explain","command":"explain","symbol":"helper","include_snippets":true}"#,
+        r#"{"id":"path","command":"call-path","from":"run","to":"helper"}"#,
+        r#"{"id":"deps","command":"deps","file":"src/lib.rs"}"#,
+        r#"{"id":"scoped","command":"find","symbol":"helper","file":"src/lib.rs"}"#,
+        r#"{"id":"impact","command":"diff-impact","changed_files":["src/util.rs"]}"#,
+        r#"{"id":"bad","command":"outline","unknown":1}"#,
+        r#"{"id":"index","command":"index"}"#,
+        r#"{"id":"late","command":"find","symbol":"run"}"#,
+    ];
+    let input = common::write_file(repo.path(), "batch.jsonl", &requests.join("\n"));
+    let repo_arg = repo.path().to_str().expect("repo path utf-8");
+    common::run_stdout(&["index", "--repo", repo_arg]);
+
+    let out = common::run_stdout(&[
+        "query",
+        "--repo",
+        repo_arg,
+        "--format",
+        "jsonl",
+        "--input",
+        input.to_str().expect("input path utf-8"),
+    ]);
+    let responses: Vec<Value> = out
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("response should be json"))
+        .collect();
+    assert_eq!(responses.len(), requests.len());
+
+    assert_eq!(responses[0]["data"]["results"][0]["symbol"], "run");
+    assert_eq!(responses[1]["command"], "explain");
+    assert_eq!(responses[1]["data"]["include_snippets"], true);
+    assert_eq!(responses[2]["data"]["path"][0], "run");
+    assert_eq!(responses[3]["data"]["file"], "src/lib.rs");
+    assert_eq!(responses[4]["ok"], true);
+    assert_eq!(responses[4]["data"]["results"], serde_json::json!([]));
+    assert!(
+        responses[5]["data"]["results"]
+            .as_array()
+            .expect("diff-impact results should be an array")
+            .iter()
+            .any(|entry| entry["symbol"] == "run")
+    );
+    assert_eq!(responses[6]["error"]["code"], "USAGE_ERROR");
+    assert_eq!(responses[7]["error"]["code"], "UNSUPPORTED_BATCH_COMMAND");
+    assert_eq!(responses[8]["data"]["results"][0]["symbol"], "run");
+
+    let fail_fast = common::run_stdout(&[
+        "query",
+        "--repo",
+        repo_arg,
+        "--format",
+        "json",
+        "--fail-fast",
+        "--input",
+        common::write_file(
+            repo.path(),
+            "batch.json",
+            &format!("[{},{}]", requests[7], requests[8]),
+        )
+        .to_str()
+        .expect("input path utf-8"),
+    ]);
+    let envelope: Value = serde_json::from_str(&fail_fast).expect("envelope should be json");
+    assert_eq!(envelope["schema"], "repo-scout/query@v1");
+    assert_eq!(
+        envelope["data"]["results"]
+            .as_array()
+            .expect("results should be an array")
+            .len(),
+        1
+    );
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use serde_json::Value;
+
+fn indexed_repo() -> tempfile::TempDir {
+    let repo = common::temp_repo();
+    common::write_file(
+        repo.path(),
+        "Cargo.toml",
+        "[package]\nname = \"demo-app\"\nversion = \"0.1.0\"\nedition = \"2024\"\n",
+    );
+    common::write_file(repo.path(), "src/lib.rs", "pub mod parser;\n");
+    common::write_file(
+        repo.path(),
+        "src/parser.rs",
+        r#"pub fn parse_header(input: &str) -> usize {
+    input.len()
+}
+
+pub fn untested_helper() -> usize {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_header() {
+        assert_eq!(parse_header("ab"), 2);
+    }
+}
+"#,
+    );
+    common::run_stdout(&[
+        "index",
+        "--repo",
+        repo.path().to_str().expect("repo path utf-8"),
+    ]);
+    repo
+}
+
+fn run_json(args: &[&str]) -> Value {
+    serde_json::from_str(&common::run_stdout(args)).expect("output should be json")
+}
+
+#[test]
+fn milestone131_tests_for_and_verify_plan_target_in_file_unit_tests() {
+    let repo = indexed_repo();
+    let repo_arg = repo.path().to_str().expect("repo path utf-8");
+
+    let tests_for = run_json(&["tests-for", "parse_header", "--repo", repo_arg, "--json"]);
+    let targets = tests_for["results"]
+        .as_array()
+        .expect("tests-for results should be an array");
+    let unit = targets
+        .iter()
+        .find(|target| target["target_kind"] == "unit_test")
+        .unwrap_or_else(|| panic!("missing unit test target in {targets:?}"));
+    assert_eq!(
+        unit["target"],
+        "src/parser.rs::parser::tests::parses_plain_header"
+    );
+
+    let plan = run_json(&[
+        "verify-plan",
+        "--changed-file",
+        "src/parser.rs",
+        "--repo",
+        repo_arg,
+        "--json",
+    ]);
+    let steps = plan["results"]
+        .as_array()
+        .expect("verify-plan results should be an array")
+        .iter()
+        .filter_map(|step| step["step"].as_str())
+        .collect::<Vec<_>>();
+    assert!(
+        steps.contains(&"cargo test -p demo-app --lib parser::tests::parses_plain_header"),
+        "{steps:?}"
+    );
+}
+
+#[test]
+fn milestone131_test_gaps_counts_in_file_unit_tests() {
+    let repo = indexed_repo();
+    let gaps = run_json(&[
+        "test-gaps",
+        "src/parser.rs",
+        "--repo",
+        repo.path().to_str().expect("repo path utf-8"),
+        "--json",
+    ]);
+    let symbols = |bucket: &str| {
+        gaps["report"][bucket]
+            .as_array()
+            .expect("test-gaps bucket should be an array")
+            .iter()
+            .filter_map(|entry| entry["symbol"].as_str().map(str::to_string))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(symbols("covered"), vec!["parse_header"]);
+    assert_eq!(symbols("uncovered"), vec!["untested_helper"]);
+}
@@ -0,0 +1,172 @@
+mod common;
+
+use serde_json::Value;
+
+fn indexed_repo() -> tempfile::TempDir {
+    let repo = common::temp_repo();
+    common::write_file(
+        repo.path(),
+        "Cargo.toml",
+        "[package]\nname = \"demo-app\"\nversion = \"0.1.0\"\nedition = \"2024\"\n",
+    );
+    common::write_file(repo.path(), "src/lib.rs", "pub mod parser;\n");
+    common::write_file(
+        repo.path(),
+        "src/parser.rs",
+        r#"pub fn parse_header(input: &str) -> usize {
+    input.len()
+}
+
+pub fn parse_document(input: &str) -> usize {
+    parse_header(input) + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> usize {
+        parse_document("ab")
+    }
+
+    #[test]
+    fn parses_header_directly() {
+        assert_eq!(parse_header("ab"), 2);
+    }
+
+    #[test]
+    fn parses_through_fixture() {
+        let length = fixture();
+        assert_eq!(length, 3);
+    }
+
+    #[test]
+    fn mentions_parse_header_only_in_text() {
+        let name = "parse_header";
+        assert!(!name.is_empty());
+    }
+}
+"#,
+    );
+    common::run_stdout(&[
+        "index",
+        "--repo",
+        repo.path().to_str().expect("repo path utf-8"),
+    ]);
+    repo
+}
+
+fn tests_for_via_calls(repo: &tempfile::TempDir, extra: &[&str]) -> Value {
+    let mut args = vec![
+        "tests-for",
+        "parse_header",
+        "--via-calls",
+        "--repo",
+        repo.path().to_str().expect("repo path utf-8"),
+        "--json",
+    ];
+    args.extend_from_slice(extra);
+    serde_json::from_str(&common::run_stdout(&args)).expect("tests-for output should be json")
+}
+
+#[test]
+fn milestone132_tests_for_via_calls_reports_test_functions_and_chains() {
+    let repo = indexed_repo();
+    let out = tests_for_via_calls(&repo, &[]);
+    assert_eq!(out["mode"], "calls");
+    assert_eq!(out["max_depth"], 3);
+    let results = out["results"]
+        .as_array()
+        .expect("results should be an array");
+    assert_eq!(results.len(), 2, "{results:?}");
+
+    let direct = &results[0];
+    assert_eq!(direct["symbol"], "parses_header_directly");
+    assert_eq!(direct["file_path"], "src/parser.rs");
+    assert_eq!(direct["distance"], 1);
+    assert_eq!(direct["confidence"], "graph_exact");
+    assert_eq!(
+        direct["call_chain"],
+        serde_json::json!(["parses_header_directly", "parse_header"])
+    );
+    assert_eq!(
+        direct["command"],
+        "cargo test -p demo-app --lib parser::tests::parses_header_directly"
+    );
+
+    let indirect = &results[1];
+    assert_eq!(indirect["symbol"], "parses_through_fixture");
+    assert_eq!(indirect["distance"], 3);
+    assert_eq!(
+        indirect["call_chain"],
+        serde_json::json!([
+            "parses_through_fixture",
+            "fixture",
+            "parse_document",
+            "parse_header"
+        ])
+    );
+    assert!(indirect["score"].as_f64() < direct["score"].as_f64());
+}
+
+#[test]
+fn milestone132_tests_for_via_calls_respects_max_depth() {
+    let repo = indexed_repo();
+    let out = tests_for_via_calls(&repo, &["--max-depth", "2"]);
+    let symbols = out["results"]
+        .as_array()
+        .expect("results should be an array")
+        .iter()
+        .map(|entry| entry["symbol"].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(symbols, vec![Value::from("parses_header_directly")]);
+}
+
+#[test]
+fn milestone132_tests_for_via_calls_follows_super_paths_from_test_modules() {
+    let repo = common::temp_repo();
+    common::write_file(
+        repo.path(),
+        "Cargo.toml",
+        "[package]\nname = \"demo-app\"\nversion = \"0.1.0\"\nedition = \"2024\"\n",
+    );
+    common::write_file(repo.path(), "src/lib.rs", "pub mod net;\n");
+    common::write_file(repo.path(), "src/net/mod.rs", "pub mod retry;\n");
+    common::write_file(
+        repo.path(),
+        "src/net/retry.rs",
+        r#"pub fn backoff(attempt: u32) -> u32 {
+    attempt * 2
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn doubles_the_attempt() {
+        assert_eq!(super::backoff(2), 4);
+    }
+}
+"#,
+    );
+    let repo_arg = repo.path().to_str().expect("repo path utf-8");
+    common::run_stdout(&["index", "--repo", repo_arg]);
+
+    let out: Value = serde_json::from_str(&common::run_stdout(&[
+        "tests-for",
+        "backoff",
+        "--via-calls",
+        "--repo",
+        repo_arg,
+        "--json",
+    ]))
+    .expect("tests-for output should be json");
+    let results = out["results"]
+        .as_array()
+        .expect("results should be an array");
+    assert_eq!(results.len(), 1, "{out}");
+    assert_eq!(results[0]["symbol"], "doubles_the_attempt");
+    assert_eq!(
+        results[0]["command"],
+        "cargo test -p demo-app --lib net::retry::tests::doubles_the_attempt"
+    );
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use std::path::Path;
+
+use serde_json::{Value, json};
+
+fn indexed_repo() -> tempfile::TempDir {
+    let repo = common::temp_repo();
+    common::write_file(
+        repo.path(),
+        "src/lib.rs",
+        "mod flow;\nmod other;\n\npub fn start() {\n    flow::left();\n    flow::right();\n}\n",
+    );
+    common::write_file(
+        repo.path(),
+        "src/flow.rs",
+        "pub fn left() {\n    target();\n}\n\npub fn right() {\n    target();\n}\n\npub fn target() {}\n",
+    );
+    common::write_file(
+        repo.path(),
+        "src/other.rs",
+        "pub fn start() {}\n\npub trait Runner {\n    fn run(&self);\n}\n\npub struct Job;\n\nimpl Runner for Job {\n    fn run(&self) {}\n}\n\npub fn target() {}\n",
+    );
+    common::run_stdout(&[
+        "index",
+        "--repo",
+        repo.path().to_str().expect("repo path should be utf-8"),
+    ]);
+    repo
+}
+
+fn call_path_json(repo: &Path, args: &[&str]) -> Value {
+    let mut command = vec!["call-path"];
+    command.extend_from_slice(args);
+    command.extend([
+        "--repo",
+        repo.to_str().expect("repo path should be utf-8"),
+        "--json",
+    ]);
+    serde_json::from_str(&common::run_stdout(&command)).expect("json output should parse")
+}
+
+fn hop_names(path: &Value) -> Vec<&str> {
+    path.as_array()
+        .expect("path should be an array")
+        .iter()
+        .map(|hop| {
+            hop["symbol"]
+                .as_str()
+                .expect("hop symbol should be a string")
+        })
+        .collect()
+}
+
+#[test]
+fn milestone133_call_path_returns_every_shortest_path_with_locations() {
+    let repo = indexed_repo();
+    let payload = call_path_json(repo.path(), &["start", "target"]);
+
+    assert_eq!(payload["from_candidates"].as_array().map(Vec::len), Some(2));
+    assert_eq!(payload["to_candidates"].as_array().map(Vec::len), Some(2));
+    assert_eq!(payload["path"], json!(["start", "left", "target"]));
+
+    let paths = payload["paths"]
+        .as_array()
+        .expect("paths should be an array");
+    assert_eq!(paths.len(), 2, "{payload}");
+    assert_eq!(hop_names(&paths[0]), vec!["start", "left", "target"]);
+    assert_eq!(hop_names(&paths[1]), vec!["start", "right", "target"]);
+    assert_eq!(
+        paths[1][0],
+        json!({
+            "symbol": "start",
+            "qualified_symbol": "rust:src/lib.rs::start",
+            "file_path": "src/lib.rs",
+            "line": 4,
+            "edge_kind": null,
+        })
+    );
+    assert_eq!(paths[1][2]["file_path"], "src/flow.rs");
+    assert_eq!(paths[1][2]["line"], 9);
+    assert_eq!(paths[1][2]["edge_kind"], "calls");
+
+    let limited = call_path_json(repo.path(), &["start", "target", "--max-paths", "1"]);
+    assert_eq!(limited["paths"].as_array().map(Vec::len), Some(1));
+}
+
+#[test]
+fn milestone133_call_path_accepts_qualified_and_file_line_selectors() {
+    let repo = indexed_repo();
+
+    let qualified = call_path_json(repo.path(), &["rust:src/other.rs::start", "target"]);
+    assert_eq!(
+        qualified["from_candidates"][0]["file_path"], "src/other.rs",
+        "{qualified}"
+    );
+    assert_eq!(qualified["path"], Value::Null);
+
+    let by_line = call_path_json(repo.path(), &["src/lib.rs:5", "src/flow.rs:9"]);
+    assert_eq!(by_line["from_candidates"][0]["symbol"], "start");
+    assert_eq!(by_line["to_candidates"].as_array().map(Vec::len), Some(1));
+    assert_eq!(by_line["paths"].as_array().map(Vec::len), Some(2));
+}
+
+#[test]
+fn milestone133_call_path_follows_structural_edges_on_request() {
+    let repo = indexed_repo();
+
+    let calls_only = call_path_json(repo.path(), &["Job", "Runner"]);
+    assert_eq!(calls_only["paths"], json!([]));
+
+    let structural = call_path_json(repo.path(), &["Job", "Runner", "--include-structural"]);
+    assert_eq!(structural["path"], json!(["Job", "Runner"]), "{structural}");
+    assert_eq!(structural["paths"][0][1]["edge_kind"], "implements");
+}
@@ -0,0 +1,237 @@
+mod common;
+
+use common::repo_arg;
+use serde_json::Value;
+
+const RUST_SOURCE: &str = r#"pub struct Scanner;
+
+impl Scanner {
+    pub fn classify(&self, value: Option<u32>, strict: bool) -> u32 {
+        match value {
+            Some(0) => 0,
+            Some(v) if strict && v > 2 => {
+                if v > 10 {
+                    for _ in 0..v {
+                        if v % 2 == 0 || v % 3 == 0 {
+                            return 1;
+                        }
+                    }
+                } else if v > 5 {
+                    return 2;
+                }
+                v
+            }
+            _ => 3,
+        }
+    }
+}
+
+pub fn plain(a: u32, b: u32) -> u32 {
+    let total = a + b;
+    let doubled = total * 2;
+    let halved = doubled / 2;
+    let shifted = halved << 1;
+    let masked = shifted & 0xff;
+    let added = masked + 1;
+    let removed = added - 1;
+    let squared = removed * removed;
+    let root = squared / removed.max(1);
+    root
+}
+"#;
+
+const GO_SOURCE: &str = r#"package calc
+
+func Sum(a, b int, rest ...int) int {
+	if a > 0 && b > 0 {
+		for _, r := range rest {
+			a += r
+		}
+	} else if a < 0 {
+		return 0
+	}
+	switch b {
+	case 1:
+		return 1
+	case 2:
+		return 2
+	default:
+		return 3
+	}
+}
+"#;
+
+const PYTHON_SOURCE: &str = r#"class Walker:
+    def walk(self, a, b=1, *args, **kwargs):
+        if a and b:
+            for x in args:
+                while x:
+                    x -= 1
+        elif a:
+            return 1 if b else 2
+        try:
+            pass
+        except ValueError:
+            pass
+        return [y for y in args if y]
+"#;
+
+const TYPESCRIPT_SOURCE: &str = r#"export function route(a: number, b?: string): number {
+  if (a > 0 || b) {
+    switch (a) {
+      case 1:
+        return 1;
+      default:
+        return a ?? 2;
+    }
+  }
+  return a > 1 ? 1 : 0;
+}
+
+export const visit = (x: number) => {
+  for (const y of [x]) {
+    if (y) {
+      return y;
+    }
+  }
+  return 0;
+};
+"#;
+
+fn indexed_repo() -> tempfile::TempDir {
+    common::indexed_repo(&[
+        ("src/scanner.rs", RUST_SOURCE),
+        ("calc/sum.go", GO_SOURCE),
+        ("py/walker.py", PYTHON_SOURCE),
+        ("web/route.ts", TYPESCRIPT_SOURCE),
+    ])
+}
+
+fn function<'a>(functions: &'a Value, symbol: &str) -> &'a Value {
+    functions
+        .as_array()
+        .expect("functions should be an array")
+        .iter()
+        .find(|entry| entry["symbol"] == symbol)
+        .unwrap_or_else(|| panic!("{symbol} should be reported in {functions}"))
+}
+
+fn metrics(entry: &Value) -> [u64; 4] {
+    [
+        "param_count",
+        "nesting_depth",
+        "branch_count",
+        "complexity_score",
+    ]
+    .map(|field| {
+        entry[field]
+            .as_u64()
+            .unwrap_or_else(|| panic!("{field} should be populated in {entry}"))
+    })
+}
+
+#[test]
+fn milestone134_every_adapter_populates_complexity_metrics() {
+    let repo = indexed_repo();
+    let report = common::run_json(repo.path(), &["health", "--large-functions"]);
+    let functions = &report["largest_functions"];
+
+    assert_eq!(metrics(function(functions, "classify")), [2, 4, 5, 9]);
+    assert_eq!(metrics(function(functions, "plain")), [2, 0, 0, 1]);
+    assert_eq!(metrics(function(functions, "Sum")), [3, 2, 4, 7]);
+    assert_eq!(metrics(function(functions, "walk")), [4, 3, 7, 9]);
+    assert_eq!(metrics(function(functions, "route")), [2, 2, 3, 6]);
+    assert_eq!(metrics(function(functions, "visit")), [1, 2, 2, 3]);
+}
+
+#[test]
+fn milestone134_health_sorts_and_filters_by_complexity_metrics() {
+    let repo = indexed_repo();
+
+    let by_complexity = common::run_json(
+        repo.path(),
+        &["health", "--sort", "complexity", "--top", "3"],
+    );
+    let symbols = by_complexity["largest_functions"]
+        .as_array()
+        .expect("functions should be an array")
+        .iter()
+        .map(|entry| entry["symbol"].as_str().expect("symbol should be a string"))
+        .collect::<Vec<_>>();
+    assert_eq!(symbols, vec!["classify", "walk", "Sum"]);
+
+    let by_params = common::run_json(repo.path(), &["health", "--sort", "params", "--top", "1"]);
+    assert_eq!(by_params["largest_functions"][0]["symbol"], "walk");
+
+    let nested = common::run_json(
+        repo.path(),
+        &["health", "--min-nesting", "3", "--min-params", "3"],
+    );
+    assert_eq!(
+        nested["largest_functions"]
+            .as_array()
+            .map(|functions| functions.len()),
+        Some(1)
+    );
+    assert_eq!(nested["largest_functions"][0]["symbol"], "walk");
+
+    let text = common::run_stdout(&[
+        "health",
+        "--repo",
+        repo_arg(repo.path()),
+        "--large-functions",
+        "--min-complexity",
+        "9",
+    ]);
+    assert!(
+        text.contains("complexity=9 nesting=4 params=2"),
+        "text health should show metrics:\n{text}"
+    );
+    assert!(!text.contains("route"), "{text}");
+}
+
+#[test]
+fn milestone134_suggest_sorts_and_filters_by_complexity_metrics() {
+    let repo = indexed_repo();
+
+    let default_order = common::run_json(repo.path(), &["suggest"]);
+    assert_eq!(default_order["results"][0]["symbol"], "classify");
+
+    let by_params = common::run_json(repo.path(), &["suggest", "--sort", "params"]);
+    assert_eq!(by_params["results"][0]["symbol"], "walk");
+    assert_eq!(by_params["results"][0]["param_count"], 4);
+
+    let by_nesting = common::run_json(repo.path(), &["suggest", "--sort", "nesting"]);
+    assert_eq!(by_nesting["results"][0]["symbol"], "classify");
+    assert_eq!(by_nesting["results"][1]["symbol"], "walk");
+
+    let filtered = common::run_json(repo.path(), &["suggest", "--min-complexity", "8"]);
+    let symbols = filtered["results"]
+        .as_array()
+        .expect("results should be an array")
+        .iter()
+        .map(|entry| entry["symbol"].as_str().expect("symbol should be a string"))
+        .collect::<Vec<_>>();
+    assert_eq!(symbols, vec!["classify", "walk"]);
+}
+
+#[test]
+fn milestone134_suggest_metric_sorts_include_short_nested_functions() {
+    let repo = common::temp_repo();
+    common::write_file(
+        repo.path(),
+        "src/lib.rs",
+        "pub fn knot(v: u32) -> u32 {\n    if v > 1 { for _ in 0..v { while v > 2 { if v > 3 { return 1; } } } }\n    0\n}\n",
+    );
+    common::run_stdout(&["index", "--repo", repo_arg(repo.path())]);
+
+    let default_order = common::run_json(repo.path(), &["suggest"]);
+    assert_eq!(default_order["results"], Value::Array(Vec::new()));
+
+    let by_nesting = common::run_json(repo.path(), &["suggest", "--sort", "nesting"]);
+    assert_eq!(by_nesting["results"][0]["symbol"], "knot");
+    assert_eq!(by_nesting["results"][0]["line_count"], 4);
+
+    let filtered = common::run_json(repo.path(), &["suggest", "--min-nesting", "3"]);
+    assert_eq!(filtered["results"][0]["symbol"], "knot");
+}
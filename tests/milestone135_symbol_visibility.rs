@@ -0,0 +1,164 @@
+mod common;
+
+use std::path::Path;
+
+use common::repo_arg;
+
+const RUST_SOURCE: &str = r#"pub fn open() {}
+
+pub(crate) fn unused_shared() {}
+
+fn unused_private() {}
+
+pub fn unused_public() {}
+"#;
+
+const GO_SOURCE: &str = r#"package calc
+
+func Exported() {}
+
+func unexported() {}
+"#;
+
+const PYTHON_SOURCE: &str = r#"__all__ = ["listed"]
+
+
+def listed():
+    pass
+
+
+def unlisted():
+    pass
+
+
+def _hidden():
+    pass
+"#;
+
+const TYPESCRIPT_SOURCE: &str = r#"export function shown() {}
+
+export default function main() {}
+
+function local() {}
+"#;
+
+fn indexed_repo() -> tempfile::TempDir {
+    common::indexed_repo(&[
+        ("src/lib.rs", RUST_SOURCE),
+        ("calc/calc.go", GO_SOURCE),
+        ("py/mod.py", PYTHON_SOURCE),
+        ("web/view.ts", TYPESCRIPT_SOURCE),
+    ])
+}
+
+fn boundary_split(repo: &Path, file: &str) -> (Vec<String>, Vec<String>) {
+    let report = common::run_json(repo, &["boundary", file]);
+    let symbols = |field: &str| {
+        report["report"][field]
+            .as_array()
+            .unwrap_or_else(|| panic!("{field} should be an array in {report}"))
+            .iter()
+            .map(|entry| entry["symbol"].as_str().unwrap_or_default().to_string())
+            .collect::<Vec<_>>()
+    };
+    let (mut public, mut internal) = (symbols("public_symbols"), symbols("internal_symbols"));
+    public.sort();
+    internal.sort();
+    (public, internal)
+}
+
+#[test]
+fn milestone135_boundary_splits_symbols_by_recorded_visibility() {
+    let repo = indexed_repo();
+
+    assert_eq!(
+        boundary_split(repo.path(), "src/lib.rs"),
+        (
+            vec![
+                "open".to_string(),
+                "unused_public".to_string(),
+                "unused_shared".to_string()
+            ],
+            vec!["unused_private".to_string()]
+        )
+    );
+    assert_eq!(
+        boundary_split(repo.path(), "calc/calc.go"),
+        (vec!["Exported".to_string()], vec!["unexported".to_string()])
+    );
+    assert_eq!(
+        boundary_split(repo.path(), "py/mod.py"),
+        (
+            vec!["listed".to_string()],
+            vec!["_hidden".to_string(), "unlisted".to_string()]
+        )
+    );
+    assert_eq!(
+        boundary_split(repo.path(), "web/view.ts"),
+        (
+            vec!["main".to_string(), "shown".to_string()],
+            vec!["local".to_string()]
+        )
+    );
+
+    let text = common::run_stdout(&["boundary", "src/lib.rs", "--repo", repo_arg(repo.path())]);
+    assert!(
+        text.contains("unused_shared (function) visibility=pub(crate)"),
+        "{text}"
+    );
+}
+
+#[test]
+fn milestone135_conservative_dead_skips_exported_symbols() {
+    let repo = indexed_repo();
+    let report = common::run_json(repo.path(), &["dead", "--mode", "conservative"]);
+    let mut symbols = report["results"]
+        .as_array()
+        .expect("results should be an array")
+        .iter()
+        .map(|entry| entry["symbol"].as_str().unwrap_or_default())
+        .collect::<Vec<_>>();
+    symbols.sort_unstable();
+
+    assert_eq!(
+        symbols,
+        vec![
+            "_hidden",
+            "local",
+            "unexported",
+            "unlisted",
+            "unused_private",
+            "unused_shared"
+        ]
+    );
+}
+
+#[test]
+fn milestone135_outline_reports_recorded_visibility() {
+    let repo = indexed_repo();
+    let outline = common::run_json(repo.path(), &["outline", "web/view.ts"]);
+    let visibilities = outline["results"]
+        .as_array()
+        .expect("results should be an array")
+        .iter()
+        .map(|entry| {
+            (
+                entry["symbol"].as_str().unwrap_or_default(),
+                entry["visibility"].as_str().unwrap_or_default(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        visibilities,
+        vec![
+            ("shown", "export"),
+            ("main", "export default"),
+            ("local", "private")
+        ]
+    );
+
+    let text = common::run_stdout(&["outline", "src/lib.rs", "--repo", repo_arg(repo.path())]);
+    assert!(text.contains("L1 pub fn open()"), "{text}");
+    assert!(!text.contains("pub pub"), "{text}");
+    assert!(text.contains("L5 fn unused_private()"), "{text}");
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use std::path::Path;
+
+use common::repo_arg;
+use serde_json::Value;
+
+const STORAGE_SOURCE: &str = r#"/// Sends one part of an object to the bucket, waiting longer after each failure.
+pub fn send_part(part: &[u8]) -> bool {
+    let delay = next_delay(2);
+    log("S3 put failed, backing off", delay);
+    !part.is_empty()
+}
+
+/// Exponential backoff between retries.
+pub fn next_delay(attempt: u32) -> u32 {
+    attempt * 2
+}
+
+fn log(message: &str, delay: u32) {
+    let _ = (message, delay);
+}
+"#;
+
+const UNRELATED_SOURCE: &str = r#"pub fn render_page() {}
+
+pub fn parse_header() {}
+"#;
+
+fn indexed_repo() -> tempfile::TempDir {
+    common::indexed_repo(&[
+        ("src/storage/s3_upload.rs", STORAGE_SOURCE),
+        ("src/web.rs", UNRELATED_SOURCE),
+    ])
+}
+
+fn context_results(repo: &Path, task: &str, budget: &str) -> Vec<Value> {
+    let payload = common::run_json(repo, &["context", "--task", task, "--budget", budget]);
+    payload["results"]
+        .as_array()
+        .expect("results should be an array")
+        .clone()
+}
+
+fn symbols(results: &[Value]) -> Vec<&str> {
+    results
+        .iter()
+        .map(|row| row["symbol"].as_str().unwrap_or_default())
+        .collect()
+}
+
+#[test]
+fn milestone136_context_ranks_by_docs_strings_and_paths() {
+    let repo = indexed_repo();
+    let results = context_results(
+        repo.path(),
+        "fix retry backoff when uploading to S3",
+        "1200",
+    );
+    let found = symbols(&results);
+
+    assert_eq!(found.first(), Some(&"next_delay"), "{results:?}");
+    assert!(found.contains(&"send_part"), "{results:?}");
+    assert!(!found.contains(&"render_page"), "{results:?}");
+    assert!(!found.contains(&"parse_header"), "{results:?}");
+
+    let why = results[0]["why_included"].as_str().unwrap_or_default();
+    assert!(why.contains("backoff in doc"), "{why}");
+    assert!(why.contains("s3 in path"), "{why}");
+}
+
+#[test]
+fn milestone136_context_packs_results_by_token_cost() {
+    let repo = indexed_repo();
+    let full = context_results(repo.path(), "retry backoff upload S3", "1200");
+    assert!(full.len() > 1, "{full:?}");
+    assert!(
+        full.iter()
+            .all(|row| row["token_cost"].as_u64().is_some_and(|cost| cost > 0)),
+        "{full:?}"
+    );
+    let total = full
+        .iter()
+        .map(|row| row["token_cost"].as_u64().unwrap_or_default())
+        .sum::<u64>();
+    assert!(total <= 1200, "{full:?}");
+
+    let top_cost = full[0]["token_cost"].as_u64().unwrap_or_default();
+    let tight = context_results(
+        repo.path(),
+        "retry backoff upload S3",
+        &top_cost.to_string(),
+    );
+    assert_eq!(symbols(&tight)[0], symbols(&full)[0]);
+    assert!(tight.len() < full.len(), "{tight:?}");
+    let tight_total = tight
+        .iter()
+        .map(|row| row["token_cost"].as_u64().unwrap_or_default())
+        .sum::<u64>();
+    assert!(tight_total <= top_cost.max(1), "{tight:?}");
+
+    let minimal = context_results(repo.path(), "retry backoff upload S3", "1");
+    assert_eq!(symbols(&minimal), vec![symbols(&full)[0]]);
+}
+
+#[test]
+fn milestone136_context_tight_budget_keeps_only_the_top_result() {
+    let repo = common::temp_repo();
+    common::write_file(
+        repo.path(),
+        "src/lib.rs",
+        include_str!("fixtures/phase2/rust_symbols/src/lib.rs"),
+    );
+    common::run_stdout(&["index", "--repo", repo_arg(repo.path())]);
+
+    let out = common::run_stdout(&[
+        "context",
+        "--task",
+        "modify start_engine and update callers",
+        "--repo",
+        repo_arg(repo.path()),
+        "--budget",
+        "20",
+    ]);
+    assert!(out.contains("budget: 20"), "{out}");
+    assert!(out.contains("results: 1"), "{out}");
+    assert!(out.contains("start_engine (function)"), "{out}");
+}
@@ -0,0 +1,164 @@
+mod common;
+
+use std::path::Path;
+
+use common::repo_arg;
+use serde_json::Value;
+
+const STORAGE_SOURCE: &str = r#"/// Sends one part of an object to the bucket, waiting longer after each failure.
+pub fn send_part(part: &[u8]) -> bool {
+    let delay = next_delay(2);
+    log("S3 put failed, backing off", delay);
+    !part.is_empty()
+}
+
+/// Exponential backoff between retries.
+pub fn next_delay(attempt: u32) -> u32 {
+    attempt * 2
+}
+
+fn log(message: &str, delay: u32) {
+    let _ = (message, delay);
+}
+"#;
+
+const TASK: &str = "fix retry backoff when uploading to S3";
+
+fn indexed_repo() -> tempfile::TempDir {
+    common::indexed_repo(&[("src/storage/s3_upload.rs", STORAGE_SOURCE)])
+}
+
+fn pack_json(repo: &Path, budget: &str) -> Value {
+    common::run_json(
+        repo,
+        &["context", "--task", TASK, "--budget", budget, "--pack"],
+    )
+}
+
+fn packed_symbol<'a>(pack: &'a Value, symbol: &str) -> &'a Value {
+    pack["symbols"]
+        .as_array()
+        .expect("symbols should be an array")
+        .iter()
+        .find(|entry| entry["symbol"] == symbol)
+        .unwrap_or_else(|| panic!("{symbol} should be packed in {pack}"))
+}
+
+#[test]
+fn milestone137_context_pack_includes_source_neighbors_and_outlines() {
+    let repo = indexed_repo();
+    let pack = pack_json(repo.path(), "1200");
+
+    assert_eq!(pack["command"], "context");
+    assert_eq!(pack["mode"], "pack");
+    assert_eq!(pack["omitted"], 0);
+
+    let next_delay = packed_symbol(&pack, "next_delay");
+    assert_eq!(
+        next_delay["source"],
+        "pub fn next_delay(attempt: u32) -> u32 {\n    attempt * 2\n}"
+    );
+    assert_eq!(
+        next_delay["callers"][0]["signature"],
+        "pub fn send_part(part: &[u8]) -> bool"
+    );
+    let send_part = packed_symbol(&pack, "send_part");
+    let callees = send_part["callees"]
+        .as_array()
+        .expect("callees should be an array")
+        .iter()
+        .map(|entry| entry["symbol"].as_str().unwrap_or_default())
+        .collect::<Vec<_>>();
+    assert_eq!(callees, vec!["next_delay", "log"]);
+
+    let outlines = pack["outlines"]
+        .as_array()
+        .expect("outlines should be an array");
+    assert_eq!(outlines.len(), 1);
+    assert_eq!(outlines[0]["file_path"], "src/storage/s3_upload.rs");
+
+    let used = pack["token_estimate"].as_u64().unwrap_or_default();
+    assert!(used > 0 && used <= 1200, "{pack}");
+}
+
+#[test]
+fn milestone137_context_pack_trims_to_budget() {
+    let repo = indexed_repo();
+    let full = pack_json(repo.path(), "1200");
+    let full_used = full["token_estimate"].as_u64().unwrap_or_default();
+
+    let budget = full_used / 2;
+    let tight = pack_json(repo.path(), &budget.to_string());
+    assert!(
+        tight["token_estimate"].as_u64().unwrap_or_default() <= budget,
+        "{tight}"
+    );
+    assert!(tight["omitted"].as_u64().unwrap_or_default() > 0, "{tight}");
+    assert!(
+        !tight["symbols"]
+            .as_array()
+            .expect("symbols should be an array")
+            .is_empty(),
+        "{tight}"
+    );
+}
+
+#[test]
+fn milestone137_context_pack_renders_markdown() {
+    let repo = indexed_repo();
+    let out = common::run_stdout(&[
+        "context",
+        "--task",
+        TASK,
+        "--repo",
+        repo_arg(repo.path()),
+        "--pack",
+    ]);
+
+    assert!(
+        out.starts_with(&format!("# Context pack: {TASK}\n")),
+        "{out}"
+    );
+    assert!(
+        out.contains("## `next_delay` (function) src/storage/s3_upload.rs:9-11"),
+        "{out}"
+    );
+    assert!(
+        out.contains("```rust\npub fn next_delay(attempt: u32) -> u32 {\n    attempt * 2\n}\n```"),
+        "{out}"
+    );
+    assert!(
+        out.contains("Callees:\n- `pub fn next_delay(attempt: u32) -> u32`"),
+        "{out}"
+    );
+    assert!(
+        out.contains("## Outline: src/storage/s3_upload.rs"),
+        "{out}"
+    );
+    assert_eq!(out.matches("## `send_part`").count(), 1, "{out}");
+}
+
+#[test]
+fn milestone137_query_batch_serves_context_packs() {
+    let repo = indexed_repo();
+    let input = common::write_file(
+        repo.path(),
+        "batch.jsonl",
+        &format!(r#"{{"id":"pack","command":"context","task":"{TASK}","pack":true}}"#),
+    );
+    let out = common::run_stdout(&[
+        "query",
+        "--repo",
+        repo_arg(repo.path()),
+        "--format",
+        "jsonl",
+        "--input",
+        input.to_str().expect("input path utf-8"),
+    ]);
+    let response: Value = serde_json::from_str(out.trim()).expect("response should be json");
+
+    assert_eq!(response["ok"], true, "{response}");
+    assert_eq!(response["data"]["mode"], "pack");
+    assert_eq!(response["data"]["budget"], 1200);
+    packed_symbol(&response["data"], "next_delay");
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use common::repo_arg;
+
+const RUST_SOURCE: &str = r#"/// Retries the upload with exponential backoff.
+///
+/// Gives up after five attempts.
+#[inline]
+pub fn upload_with_retry() {}
+
+// Not documentation.
+pub fn plain() {}
+"#;
+
+const GO_SOURCE: &str = r#"package store
+
+// FlushCache writes pending entries to disk.
+func FlushCache() {}
+"#;
+
+const PYTHON_SOURCE: &str = r#"def parse_config(path):
+    """Load the YAML config from disk."""
+    return path
+"#;
+
+const TS_SOURCE: &str = r#"/**
+ * Renders the dashboard header.
+ */
+export function renderHeader() {}
+"#;
+
+fn indexed_repo() -> tempfile::TempDir {
+    common::indexed_repo(&[
+        ("src/upload.rs", RUST_SOURCE),
+        ("store/cache.go", GO_SOURCE),
+        ("app/config.py", PYTHON_SOURCE),
+        ("web/header.ts", TS_SOURCE),
+    ])
+}
+
+#[test]
+fn milestone138_explain_reports_doc_comments_for_each_language() {
+    let repo = indexed_repo();
+    let cases = [
+        (
+            "upload_with_retry",
+            "Retries the upload with exponential backoff.\n\nGives up after five attempts.",
+        ),
+        ("FlushCache", "FlushCache writes pending entries to disk."),
+        ("parse_config", "Load the YAML config from disk."),
+        ("renderHeader", "Renders the dashboard header."),
+    ];
+    for (symbol, expected) in cases {
+        let payload = common::run_json(repo.path(), &["explain", symbol]);
+        assert_eq!(
+            payload["results"][0]["doc_comment"], expected,
+            "{symbol}: {payload}"
+        );
+    }
+
+    let plain = common::run_json(repo.path(), &["explain", "plain"]);
+    assert!(plain["results"][0].get("doc_comment").is_none(), "{plain}");
+
+    let text = common::run_stdout(&[
+        "explain",
+        "upload_with_retry",
+        "--repo",
+        repo_arg(repo.path()),
+    ]);
+    assert!(
+        text.contains("doc:\n  Retries the upload with exponential backoff.\n\n  Gives up"),
+        "{text}"
+    );
+}
+
+#[test]
+fn milestone138_outline_shows_doc_comments() {
+    let repo = indexed_repo();
+    let payload = common::run_json(repo.path(), &["outline", "src/upload.rs"]);
+    let entries = payload["results"]
+        .as_array()
+        .expect("outline results should be an array");
+    let documented = entries
+        .iter()
+        .find(|entry| entry["symbol"] == "upload_with_retry")
+        .expect("documented function should be outlined");
+    assert_eq!(
+        documented["doc_comment"],
+        "Retries the upload with exponential backoff.\n\nGives up after five attempts."
+    );
+
+    let text = common::run_stdout(&["outline", "src/upload.rs", "--repo", repo_arg(repo.path())]);
+    assert!(
+        text.contains("      Retries the upload with exponential backoff."),
+        "{text}"
+    );
+    assert!(!text.contains("Gives up after five attempts."), "{text}");
+}
+
+#[test]
+fn milestone138_find_doc_searches_doc_comments() {
+    let repo = indexed_repo();
+    let phrase = common::run_json(repo.path(), &["find", "--doc", "exponential backoff"]);
+    let results = phrase["results"].as_array().expect("results array");
+    assert_eq!(results.len(), 1, "{phrase}");
+    assert_eq!(results[0]["symbol"], "upload_with_retry");
+    assert_eq!(results[0]["why_matched"], "doc_comment_phrase");
+
+    let terms = common::run_json(repo.path(), &["find", "--doc", "disk config"]);
+    let results = terms["results"].as_array().expect("results array");
+    assert_eq!(results.len(), 1, "{terms}");
+    assert_eq!(results[0]["symbol"], "parse_config");
+    assert_eq!(results[0]["why_matched"], "doc_comment_terms");
+
+    let none = common::run_json(repo.path(), &["find", "--doc", "documentation"]);
+    assert_eq!(none["results"], serde_json::json!([]), "{none}");
+}
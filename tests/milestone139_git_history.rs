@@ -0,0 +1,228 @@
+mod common;
+
+use std::path::Path;
+use std::process::Command;
+
+use common::repo_arg;
+
+const BASE: &str = r#"pub fn stable(value: u32) -> u32 {
+    if value > 1 { value } else { 0 }
+}
+
+pub fn churned(value: u32) -> u32 {
+    value
+}
+"#;
+
+const SECOND: &str = r#"pub fn stable(value: u32) -> u32 {
+    if value > 1 { value } else { 0 }
+}
+
+pub fn churned(value: u32) -> u32 {
+    if value > 2 { value + 1 } else { value }
+}
+"#;
+
+const THIRD: &str = r#"pub fn stable(value: u32) -> u32 {
+    if value > 1 { value } else { 0 }
+}
+
+pub fn churned(value: u32) -> u32 {
+    if value > 2 && value < 9 { value + 2 } else { value }
+}
+"#;
+
+fn commit(repo: &Path, author: &str, date: &str, message: &str) {
+    for args in [vec!["add", "-A"], vec!["commit", "-q", "-m", message]] {
+        let output = Command::new("git")
+            .args(&args)
+            .current_dir(repo)
+            .env("GIT_AUTHOR_NAME", author)
+            .env(
+                "GIT_AUTHOR_EMAIL",
+                format!("{}@example.com", author.to_lowercase()),
+            )
+            .env("GIT_AUTHOR_DATE", date)
+            .env("GIT_COMMITTER_NAME", author)
+            .env("GIT_COMMITTER_EMAIL", "ci@example.com")
+            .env("GIT_COMMITTER_DATE", date)
+            .output()
+            .expect("git should run");
+        assert!(
+            output.status.success(),
+            "git {args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
+
+fn history_repo() -> tempfile::TempDir {
+    let repo = common::temp_repo();
+    let output = Command::new("git")
+        .args(["init", "-q"])
+        .current_dir(repo.path())
+        .output()
+        .expect("git init should run");
+    assert!(output.status.success());
+    common::write_file(repo.path(), ".gitignore", ".repo-scout/\n");
+    common::write_file(repo.path(), "src/calc.rs", BASE);
+    commit(repo.path(), "Ada", "2024-01-02T10:00:00Z", "add calc");
+    common::write_file(repo.path(), "src/calc.rs", SECOND);
+    commit(repo.path(), "Grace", "2024-02-03T10:00:00Z", "tune churned");
+    // Renamed and edited in one commit; history before the rename must carry over.
+    std::fs::remove_file(repo.path().join("src/calc.rs")).expect("old file should be removed");
+    common::write_file(repo.path(), "src/math.rs", THIRD);
+    commit(
+        repo.path(),
+        "Grace",
+        "2024-03-04T10:00:00Z",
+        "rename calc to math",
+    );
+    common::run_stdout(&["index", "--repo", repo_arg(repo.path())]);
+    repo
+}
+
+#[test]
+fn milestone139_hotspots_churn_ranks_by_commits_times_complexity() {
+    let repo = history_repo();
+    let payload = common::run_json(repo.path(), &["hotspots", "--churn"]);
+    assert_eq!(payload["mode"], "churn");
+    let results = payload["results"].as_array().expect("results array");
+    assert_eq!(results[0]["symbol"], "churned", "{payload}");
+    assert_eq!(results[0]["file_path"], "src/math.rs");
+    assert_eq!(results[0]["commits"], 3, "{payload}");
+    assert_eq!(results[0]["authors"], 2, "{payload}");
+    assert_eq!(results[0]["last_touched"], "2024-03-04");
+    let complexity = results[0]["complexity"].as_u64().expect("complexity");
+    assert_eq!(results[0]["churn_score"].as_u64(), Some(3 * complexity));
+
+    let stable = results
+        .iter()
+        .find(|row| row["symbol"] == "stable")
+        .expect("stable should be ranked");
+    assert_eq!(stable["commits"], 1, "{payload}");
+    assert_eq!(stable["last_touched"], "2024-01-02");
+
+    let text = common::run_stdout(&["hotspots", "--churn", "--repo", repo_arg(repo.path())]);
+    assert!(
+        text.contains("churned (function) in src/math.rs:5"),
+        "{text}"
+    );
+}
+
+#[test]
+fn milestone139_owners_lists_top_authors_of_files_and_symbols() {
+    let repo = history_repo();
+    let file = common::run_json(repo.path(), &["owners", "src/math.rs"]);
+    let report = &file["results"][0];
+    assert_eq!(report["commits"], 3, "{file}");
+    assert_eq!(report["last_touched"], "2024-03-04");
+    assert_eq!(report["owners"][0]["author"], "Grace");
+    assert_eq!(report["owners"][0]["commits"], 2);
+    assert_eq!(report["owners"][0]["share"], 0.667);
+    assert_eq!(report["owners"][1]["author"], "Ada");
+    assert_eq!(report["owners"][1]["email"], "ada@example.com");
+
+    let symbol = common::run_json(repo.path(), &["owners", "stable"]);
+    let report = &symbol["results"][0];
+    assert_eq!(report["symbol"], "stable", "{symbol}");
+    assert_eq!(report["commits"], 1, "{symbol}");
+    assert_eq!(report["owners"][0]["author"], "Ada");
+    assert_eq!(report["owners"].as_array().map(Vec::len), Some(1));
+
+    let text = common::run_stdout(&["owners", "churned", "--repo", repo_arg(repo.path())]);
+    assert!(
+        text.contains("owners of churned (src/math.rs:5-7):"),
+        "{text}"
+    );
+    assert!(
+        text.contains("#1: Grace <grace@example.com> — commits: 2 (67%)"),
+        "{text}"
+    );
+}
+
+#[test]
+fn milestone139_history_is_skipped_outside_git() {
+    let repo = common::temp_repo();
+    common::write_file(repo.path(), "src/lib.rs", BASE);
+    common::run_stdout(&["index", "--repo", repo_arg(repo.path())]);
+    let payload = common::run_json(repo.path(), &["hotspots", "--churn"]);
+    assert_eq!(payload["results"], serde_json::json!([]));
+    let owners = common::run_json(repo.path(), &["owners", "src/lib.rs"]);
+    assert_eq!(owners["results"][0]["commits"], 0, "{owners}");
+}
+
+/// Every history row keyed by commit SHA instead of commit id, sorted.
+fn history_rows(repo: &Path) -> (Vec<String>, Vec<String>) {
+    let connection = rusqlite::Connection::open(repo.join(".repo-scout/index.db"))
+        .expect("index database should open");
+    let rows = |sql: &str| {
+        let mut statement = connection
+            .prepare(sql)
+            .expect("history query should prepare");
+        let mut rows = statement
+            .query_map([], |row| row.get::<_, String>(0))
+            .expect("history query should run")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("history rows should load");
+        rows.sort();
+        rows
+    };
+    let file_changes = rows(
+        "SELECT c.sha || ' ' || c.author || ' ' || f.file_path || ' +' || f.lines_added
+                || ' -' || f.lines_deleted
+         FROM history_file_changes f JOIN history_commits c ON c.commit_id = f.commit_id",
+    );
+    let line_changes = rows(
+        "SELECT c.sha || ' ' || h.file_path || ':' || h.start_line || '-' || h.end_line
+         FROM history_line_changes h JOIN history_commits c ON c.commit_id = h.commit_id",
+    );
+    (file_changes, line_changes)
+}
+
+#[test]
+fn milestone139_incremental_history_matches_a_full_rebuild() {
+    let repo = common::temp_repo();
+    let output = Command::new("git")
+        .args(["init", "-q"])
+        .current_dir(repo.path())
+        .output()
+        .expect("git init should run");
+    assert!(output.status.success());
+    common::write_file(repo.path(), ".gitignore", ".repo-scout/\n");
+    common::write_file(repo.path(), "src/calc.rs", BASE);
+    common::write_file(repo.path(), "NOTES.md", "calc notes\n");
+    commit(repo.path(), "Ada", "2024-01-02T10:00:00Z", "add calc");
+    common::write_file(repo.path(), "src/calc.rs", SECOND);
+    commit(repo.path(), "Grace", "2024-02-03T10:00:00Z", "tune churned");
+    common::run_stdout(&["index", "--repo", repo_arg(repo.path())]);
+
+    // Shift every line, then rename and edit, so carried rows must move to new paths and lines.
+    common::write_file(
+        repo.path(),
+        "src/calc.rs",
+        &format!("// header\n\n{SECOND}"),
+    );
+    common::write_file(repo.path(), "NOTES.md", "calc notes\nmore\n");
+    commit(repo.path(), "Ada", "2024-03-04T10:00:00Z", "add header");
+    std::fs::remove_file(repo.path().join("src/calc.rs")).expect("old file should be removed");
+    common::write_file(repo.path(), "src/math.rs", &format!("// header\n\n{THIRD}"));
+    commit(repo.path(), "Linus", "2024-04-05T10:00:00Z", "rename calc");
+    common::run_stdout(&["index", "--repo", repo_arg(repo.path())]);
+    let incremental = history_rows(repo.path());
+
+    std::fs::remove_dir_all(repo.path().join(".repo-scout")).expect("index should be removed");
+    common::run_stdout(&["index", "--repo", repo_arg(repo.path())]);
+    let rebuilt = history_rows(repo.path());
+
+    assert_eq!(incremental, rebuilt);
+    assert_eq!(incremental.0.len(), 6, "{:?}", incremental.0);
+    assert!(
+        incremental
+            .1
+            .iter()
+            .all(|row| row.contains(" src/math.rs:")),
+        "only files with symbols record line history: {:?}",
+        incremental.1
+    );
+}
@@ -0,0 +1,140 @@
+mod common;
+
+use std::path::Path;
+use std::process::Command;
+
+use common::repo_arg;
+use serde_json::Value;
+
+fn git(repo: &Path, args: &[&str]) {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo)
+        .env("GIT_AUTHOR_NAME", "Ada")
+        .env("GIT_AUTHOR_EMAIL", "ada@example.com")
+        .env("GIT_COMMITTER_NAME", "Ada")
+        .env("GIT_COMMITTER_EMAIL", "ada@example.com")
+        .output()
+        .expect("git should run");
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+fn commit(repo: &Path, files: &[(&str, String)]) {
+    for (path, content) in files {
+        common::write_file(repo, path, content);
+    }
+    git(repo, &["add", "-A"]);
+    git(repo, &["commit", "-q", "-m", "change"]);
+}
+
+/// `config/app.toml` and `src/settings.rs` always change together without any code edge between
+/// them; `src/api.rs` calls `src/db.rs`, and they changed together once.
+fn coupled_repo() -> tempfile::TempDir {
+    let repo = common::temp_repo();
+    git(repo.path(), &["init", "-q"]);
+    commit(
+        repo.path(),
+        &[
+            (".gitignore", ".repo-scout/\n".to_string()),
+            ("src/db.rs", "pub fn query() {}\n".to_string()),
+            (
+                "src/api.rs",
+                "use crate::db::query;\npub fn handle() { query(); }\n".to_string(),
+            ),
+            ("src/lib.rs", "pub mod api;\npub mod db;\n".to_string()),
+        ],
+    );
+    for round in 0..3 {
+        commit(
+            repo.path(),
+            &[
+                ("config/app.toml", format!("timeout = {round}\n")),
+                (
+                    "src/settings.rs",
+                    format!("pub const TIMEOUT: u32 = {round};\n"),
+                ),
+            ],
+        );
+    }
+    commit(
+        repo.path(),
+        &[(
+            "src/settings.rs",
+            "pub const TIMEOUT: u32 = 9;\n".to_string(),
+        )],
+    );
+    common::run_stdout(&["index", "--repo", repo_arg(repo.path())]);
+    repo
+}
+
+fn coupling(repo: &Path, extra: &[&str]) -> Vec<Value> {
+    let mut args = vec!["coupling"];
+    args.extend_from_slice(extra);
+    common::run_json(repo, &args)["results"]
+        .as_array()
+        .expect("results should be an array")
+        .clone()
+}
+
+fn pair<'a>(entries: &'a [Value], file_a: &str, file_b: &str) -> Option<&'a Value> {
+    entries
+        .iter()
+        .find(|entry| entry["file_a"] == file_a && entry["file_b"] == file_b)
+}
+
+#[test]
+fn milestone140_temporal_coupling_flags_hidden_co_change_dependencies() {
+    let repo = coupled_repo();
+    let entries = coupling(repo.path(), &["--temporal"]);
+
+    let hidden = &entries[0];
+    assert_eq!(hidden["file_a"], "config/app.toml", "{entries:?}");
+    assert_eq!(hidden["file_b"], "src/settings.rs");
+    assert_eq!(hidden["total_edges"], 0);
+    let temporal = &hidden["temporal"];
+    assert_eq!(temporal["shared_commits"], 3);
+    assert_eq!(temporal["commits_a"], 3);
+    assert_eq!(temporal["commits_b"], 4);
+    assert_eq!(temporal["support"], 0.6);
+    assert_eq!(temporal["confidence_a_to_b"], 1.0);
+    assert_eq!(temporal["confidence_b_to_a"], 0.75);
+    assert_eq!(temporal["hidden_dependency"], true);
+
+    // The static pair changed together only once, below the default threshold.
+    let api_db = pair(&entries, "src/api.rs", "src/db.rs").expect("static pair should merge in");
+    assert!(api_db["total_edges"].as_u64().unwrap_or_default() > 0);
+    assert!(api_db.get("temporal").is_none(), "{api_db}");
+
+    let loose = coupling(repo.path(), &["--temporal", "--min-shared", "1"]);
+    let api_db = pair(&loose, "src/api.rs", "src/db.rs").expect("static pair should be listed");
+    assert_eq!(api_db["temporal"]["shared_commits"], 1);
+    assert_eq!(api_db["temporal"]["hidden_dependency"], false);
+
+    let text = common::run_stdout(&["coupling", "--temporal", "--repo", repo_arg(repo.path())]);
+    assert!(
+        text.contains(
+            "co-changed in 3 commits (support 0.600, confidence 1.00 / 0.75) — hidden dependency"
+        ),
+        "{text}"
+    );
+}
+
+#[test]
+fn milestone140_temporal_coupling_skips_large_changesets() {
+    let repo = coupled_repo();
+    let entries = coupling(repo.path(), &["--temporal", "--max-changeset", "2"]);
+    // The seed commit touched four files and is skipped, so only co-changes remain.
+    let hidden = pair(&entries, "config/app.toml", "src/settings.rs").expect("pair");
+    assert_eq!(hidden["temporal"]["support"], 0.75, "{entries:?}");
+
+    let plain = coupling(repo.path(), &[]);
+    assert!(
+        plain.iter().all(|entry| entry.get("temporal").is_none()),
+        "{plain:?}"
+    );
+    assert!(pair(&plain, "config/app.toml", "src/settings.rs").is_none());
+}
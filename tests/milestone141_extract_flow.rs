@@ -0,0 +1,199 @@
+mod common;
+
+use std::path::Path;
+
+use common::repo_arg;
+use serde_json::Value;
+
+const RUST_SOURCE: &str = r#"pub fn summarize(items: &[u32], limit: u32) -> Result<u32, String> {
+    let mut total = 0;
+    let mut seen = 0;
+    for item in items {
+        if *item > limit {
+            break;
+        }
+        let doubled = scale(*item);
+        total += doubled;
+        seen += 1;
+    }
+    let average = check(total / seen.max(1))?;
+    Ok(average + seen)
+}
+"#;
+
+const GO_SOURCE: &str = r#"package calc
+
+func Tally(values []int) (int, error) {
+	count := 0
+	for _, v := range values {
+		if v < 0 {
+			return 0, errors.New("negative")
+		}
+		count += v
+	}
+	label := fmt.Sprint(count)
+	return len(label), nil
+}
+"#;
+
+const PYTHON_SOURCE: &str = r#"def build_report(rows, title):
+    lines = [title]
+    for row in rows:
+        if not row:
+            continue
+        text = format_row(row)
+        lines.append(text)
+    header = lines[0]
+    return "\n".join(lines) + header
+"#;
+
+const TS_SOURCE: &str = r#"export function renderList(items: string[], prefix: string): string {
+  let output = "";
+  for (const item of items) {
+    const line = `${prefix}${item}`;
+    output += line;
+  }
+  return output;
+}
+"#;
+
+fn indexed_repo() -> tempfile::TempDir {
+    let repo = common::temp_repo();
+    // A same-named function makes the bare name ambiguous, so tests select `src/lib.rs:1`.
+    common::write_file(repo.path(), "src/a.rs", "pub fn summarize() {}\n");
+    common::write_file(repo.path(), "src/lib.rs", RUST_SOURCE);
+    common::write_file(repo.path(), "calc/tally.go", GO_SOURCE);
+    common::write_file(repo.path(), "app/report.py", PYTHON_SOURCE);
+    common::write_file(repo.path(), "web/list.ts", TS_SOURCE);
+    common::run_stdout(&["index", "--repo", repo_arg(repo.path())]);
+    repo
+}
+
+fn extract(repo: &Path, symbol: &str, lines: &str) -> Value {
+    common::run_json(repo, &["extract-check", symbol, "--lines", lines])
+}
+
+fn names(payload: &Value, field: &str) -> Vec<String> {
+    payload[field]
+        .as_array()
+        .unwrap_or_else(|| panic!("{field} should be an array: {payload}"))
+        .iter()
+        .map(|entry| entry["name"].as_str().expect("name").to_string())
+        .collect()
+}
+
+#[test]
+fn milestone141_extract_check_reports_rust_variable_flow() {
+    let repo = indexed_repo();
+    let body = extract(repo.path(), "src/lib.rs:1", "8-10");
+    assert_eq!(body["file_path"], "src/lib.rs", "{body}");
+    assert_eq!(names(&body, "parameters"), ["total", "seen", "item"]);
+    assert_eq!(body["parameters"][0]["declared_line"], 2);
+    assert_eq!(body["parameters"][0]["mutated"], true);
+    assert_eq!(body["parameters"][2]["mutated"], false);
+    assert_eq!(names(&body, "return_values"), ["total", "seen"]);
+    assert_eq!(names(&body, "locals"), ["doubled"]);
+    assert_eq!(names(&body, "calls"), ["scale"]);
+    assert_eq!(body["early_exits"], serde_json::json!([]));
+    assert_eq!(body["statement_aligned"], true);
+    assert_eq!(body["mechanically_safe"], true, "{body}");
+
+    let guard = extract(repo.path(), "src/lib.rs:1", "5-7");
+    assert_eq!(names(&guard, "parameters"), ["limit", "item"]);
+    assert_eq!(
+        guard["early_exits"],
+        serde_json::json!([{ "kind": "break", "line": 6 }])
+    );
+    assert_eq!(guard["mechanically_safe"], false);
+
+    let tail = extract(repo.path(), "src/lib.rs:1", "12-12");
+    assert_eq!(tail["early_exits"][0]["kind"], "?", "{tail}");
+    assert_eq!(names(&tail, "return_values"), ["average"]);
+    assert_eq!(names(&tail, "calls"), ["check", "seen.max"]);
+
+    let split = extract(repo.path(), "src/lib.rs:1", "4-5");
+    assert_eq!(split["statement_aligned"], false, "{split}");
+    assert_eq!(split["mechanically_safe"], false);
+    let warnings = split["warnings"].to_string();
+    assert!(
+        warnings.contains("range cuts through the statement at lines 4-11"),
+        "{warnings}"
+    );
+
+    let text = common::run_stdout(&[
+        "extract-check",
+        "src/lib.rs:4",
+        "--lines",
+        "8-10",
+        "--repo",
+        repo_arg(repo.path()),
+    ]);
+    assert!(
+        text.contains("  Parameters: total (mutated), seen (mutated), item"),
+        "{text}"
+    );
+    assert!(text.contains("  Return values: total, seen"), "{text}");
+    assert!(text.contains("  Mechanically safe: yes"), "{text}");
+}
+
+#[test]
+fn milestone141_extract_check_reports_go_python_and_typescript_flow() {
+    let repo = indexed_repo();
+
+    let go_guard = extract(repo.path(), "Tally", "6-8");
+    assert_eq!(names(&go_guard, "parameters"), ["v"]);
+    assert_eq!(
+        go_guard["early_exits"],
+        serde_json::json!([{ "kind": "return", "line": 7 }])
+    );
+    let go_tail = extract(repo.path(), "Tally", "11-11");
+    assert_eq!(names(&go_tail, "parameters"), ["count"]);
+    assert_eq!(names(&go_tail, "return_values"), ["label"]);
+    assert_eq!(names(&go_tail, "calls"), ["fmt.Sprint"]);
+    assert_eq!(go_tail["mechanically_safe"], true);
+
+    let py_body = extract(repo.path(), "build_report", "6-7");
+    assert_eq!(names(&py_body, "parameters"), ["lines", "row"]);
+    assert_eq!(py_body["parameters"][0]["mutated"], false);
+    assert_eq!(names(&py_body, "return_values"), Vec::<String>::new());
+    assert_eq!(names(&py_body, "locals"), ["text"]);
+    assert_eq!(names(&py_body, "calls"), ["format_row", "lines.append"]);
+    assert_eq!(py_body["mechanically_safe"], true, "{py_body}");
+    let py_guard = extract(repo.path(), "build_report", "4-5");
+    assert_eq!(py_guard["early_exits"][0]["kind"], "continue");
+
+    let ts_body = extract(repo.path(), "renderList", "4-5");
+    assert_eq!(names(&ts_body, "parameters"), ["prefix", "output", "item"]);
+    assert_eq!(ts_body["parameters"][1]["mutated"], true);
+    assert_eq!(names(&ts_body, "return_values"), ["output"]);
+    assert_eq!(names(&ts_body, "locals"), ["line"]);
+    assert_eq!(ts_body["mechanically_safe"], true, "{ts_body}");
+    let ts_loop = extract(repo.path(), "renderList", "3-6");
+    assert_eq!(names(&ts_loop, "parameters"), ["items", "prefix", "output"]);
+    assert_eq!(names(&ts_loop, "locals"), ["item", "line"]);
+}
+
+#[test]
+fn milestone141_extract_check_rejects_ambiguous_names() {
+    let repo = indexed_repo();
+    let mut cmd = common::repo_scout_cmd();
+    cmd.args([
+        "extract-check",
+        "summarize",
+        "--lines",
+        "8-10",
+        "--repo",
+        repo_arg(repo.path()),
+    ]);
+    let output = cmd.assert().failure().get_output().stderr.clone();
+    let stderr = String::from_utf8(output).expect("stderr should be utf-8");
+    assert!(
+        stderr.contains("matches 2 functions (src/a.rs:1, src/lib.rs:1)"),
+        "{stderr}"
+    );
+
+    let body = extract(repo.path(), "src/lib.rs:4", "8-10");
+    assert_eq!(body["symbol"], "summarize", "{body}");
+    assert_eq!(body["file_path"], "src/lib.rs");
+    assert_eq!(body["function_start_line"], 1);
+}
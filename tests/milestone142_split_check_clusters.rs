@@ -0,0 +1,209 @@
+mod common;
+
+use std::path::Path;
+
+use common::repo_arg;
+use serde_json::Value;
+
+const TS_SOURCE: &str = r#"function formatName(name: string): string {
+  return name.trim();
+}
+
+export function greet(name: string): string {
+  return `hi ${formatName(name)}`;
+}
+
+export function farewell(name: string): string {
+  return `bye ${formatName(name)}`;
+}
+"#;
+
+fn indexed_repo() -> tempfile::TempDir {
+    common::indexed_repo(&[
+        ("src/store.rs", common::STORE_SOURCE),
+        ("web/names.ts", TS_SOURCE),
+    ])
+}
+
+fn split_check(repo: &Path, file: &str, extra: &[&str]) -> Value {
+    let mut args = vec!["split-check", file];
+    args.extend_from_slice(extra);
+    common::run_json(repo, &args)
+}
+
+fn group<'a>(payload: &'a Value, symbol: &str) -> &'a Value {
+    payload["groups"]
+        .as_array()
+        .expect("groups should be an array")
+        .iter()
+        .find(|group| {
+            group["symbols"]
+                .as_array()
+                .is_some_and(|symbols| symbols.iter().any(|entry| entry == symbol))
+        })
+        .unwrap_or_else(|| panic!("{symbol} should be grouped: {payload}"))
+}
+
+#[test]
+fn milestone142_split_check_auto_clusters_the_reference_graph() {
+    let repo = indexed_repo();
+    let payload = split_check(repo.path(), "src/store.rs", &["--auto"]);
+    assert_eq!(payload["mode"], "auto");
+    assert_eq!(
+        payload["groups"].as_array().map(Vec::len),
+        Some(2),
+        "{payload}"
+    );
+
+    let config = group(&payload, "load_config");
+    assert_eq!(
+        config["symbols"],
+        serde_json::json!(["Config", "load_config", "parse_config", "read_raw"])
+    );
+    assert_eq!(config["target_file"], "src/store.rs");
+    assert_eq!(config["stays"], true);
+
+    let cache = group(&payload, "warm_cache");
+    assert_eq!(cache["name"], "warm_cache");
+    assert_eq!(cache["target_file"], "src/store/warm_cache.rs");
+    assert_eq!(
+        cache["symbols"],
+        serde_json::json!(["Cache", "warm_cache", "collect_entries", "normalize"])
+    );
+    assert_eq!(
+        cache["imports"],
+        serde_json::json!([{ "symbol": "Config", "from_file": "src/store.rs" }])
+    );
+
+    let edges = payload["cross_group_edges"].as_array().expect("edges");
+    assert!(
+        edges
+            .iter()
+            .any(|edge| edge["from_symbol"] == "parse_config"
+                && edge["to_symbol"] == "normalize"
+                && edge["edge_kind"] == "calls"),
+        "{payload}"
+    );
+    assert_eq!(
+        payload["visibility_changes"],
+        serde_json::json!([{
+            "symbol": "normalize",
+            "file": "src/store/warm_cache.rs",
+            "current": "private",
+            "required": "pub(super)",
+            "used_from": ["src/store.rs"],
+        }])
+    );
+    assert!(payload["modularity"].as_f64().unwrap_or_default() > 0.0);
+
+    let text = common::run_stdout(&[
+        "split-check",
+        "src/store.rs",
+        "--auto",
+        "--repo",
+        repo_arg(repo.path()),
+    ]);
+    assert!(
+        text.contains("warm_cache -> src/store/warm_cache.rs: 13 lines"),
+        "{text}"
+    );
+    assert!(
+        text.contains("normalize in src/store/warm_cache.rs: private -> pub(super)"),
+        "{text}"
+    );
+}
+
+#[test]
+fn milestone142_split_check_validates_manual_groups() {
+    let repo = indexed_repo();
+    let payload = split_check(repo.path(), "web/names.ts", &["--groups", "farewell"]);
+    assert_eq!(payload["mode"], "manual");
+    let moved = group(&payload, "farewell");
+    assert_eq!(moved["target_file"], "web/farewell.ts");
+    assert_eq!(moved["stays"], false);
+    assert_eq!(
+        moved["imports"],
+        serde_json::json!([{ "symbol": "formatName", "from_file": "web/names.ts" }])
+    );
+    assert_eq!(group(&payload, "greet")["stays"], true);
+    assert_eq!(payload["visibility_changes"][0]["symbol"], "formatName");
+    assert_eq!(payload["visibility_changes"][0]["required"], "export");
+
+    // Private helpers left in the parent Rust module stay visible to the new child module.
+    let rust = split_check(
+        repo.path(),
+        "src/store.rs",
+        &["--groups", "warm_cache,Cache"],
+    );
+    assert_eq!(rust["visibility_changes"], serde_json::json!([]), "{rust}");
+    assert_eq!(
+        group(&rust, "warm_cache")["imports"],
+        serde_json::json!([
+            { "symbol": "Config", "from_file": "src/store.rs" },
+            { "symbol": "collect_entries", "from_file": "src/store.rs" },
+        ])
+    );
+
+    for (groups, message) in [
+        ("missing,load_config", "unknown symbols in --groups"),
+        ("load_config:load_config", "listed in more than one group"),
+        ("load_config::warm_cache", "invalid --groups format"),
+    ] {
+        let mut cmd = common::repo_scout_cmd();
+        cmd.args([
+            "split-check",
+            "src/store.rs",
+            "--groups",
+            groups,
+            "--repo",
+            repo_arg(repo.path()),
+        ]);
+        cmd.assert()
+            .failure()
+            .stderr(predicates::str::contains(message));
+    }
+}
+
+#[test]
+fn milestone142_anatomy_reports_clusters_cohesion_and_splits() {
+    let repo = indexed_repo();
+    let payload: Value = serde_json::from_str(&common::run_stdout(&[
+        "anatomy",
+        "src/store.rs",
+        "--cohesion",
+        "--repo",
+        repo_arg(repo.path()),
+        "--json",
+    ]))
+    .expect("anatomy json should parse");
+    let clusters = &payload["report"]["clusters"];
+    assert_eq!(clusters["clusters"].as_array().map(Vec::len), Some(2));
+    assert_eq!(clusters["unclustered"], serde_json::json!([]));
+    assert!(clusters["modularity"].as_f64().unwrap_or_default() > 0.0);
+
+    let plain = common::run_stdout(&[
+        "anatomy",
+        "src/store.rs",
+        "--repo",
+        repo_arg(repo.path()),
+        "--json",
+    ]);
+    assert!(!plain.contains("\"clusters\""), "{plain}");
+
+    let text = common::run_stdout(&[
+        "anatomy",
+        "src/store.rs",
+        "--clusters",
+        "--suggest-split",
+        "--repo",
+        repo_arg(repo.path()),
+    ]);
+    assert!(
+        text.contains("Cache, warm_cache, collect_entries, normalize"),
+        "{text}"
+    );
+    assert!(
+        text.contains("warm_cache -> src/store/warm_cache.rs (13 lines"),
+        "{text}"
+    );
+}
@@ -0,0 +1,308 @@
+mod common;
+
+use std::path::Path;
+
+use common::repo_arg;
+use serde_json::Value;
+
+fn indexed_repo() -> tempfile::TempDir {
+    common::indexed_repo(&[
+        (
+            "Cargo.toml",
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        ),
+        (
+            "src/lib.rs",
+            "pub mod api;\npub mod db;\npub mod store;\npub use db::query;\n",
+        ),
+        (
+            "src/db.rs",
+            "pub fn query(id: u32) -> u32 {\n    id + 1\n}\n\npub fn helper() -> u32 {\n    query(1)\n}\n",
+        ),
+        (
+            "src/api.rs",
+            "use crate::db::query;\n\npub fn handle() -> u32 {\n    query(2)\n}\n\npub fn boot() {\n    let config = crate::store::load_config(\"x\");\n    crate::store::warm_cache(&config);\n}\n",
+        ),
+        ("src/store.rs", common::STORE_SOURCE),
+        (
+            "tests/db_test.rs",
+            "use demo::query;\n\n#[test]\nfn query_works() {\n    assert_eq!(query(1), 2);\n}\n",
+        ),
+        (
+            "web/names.ts",
+            "export function greet(name: string): string {\n  return name.trim();\n}\n",
+        ),
+        (
+            "web/app.ts",
+            "import { greet } from \"./names\";\n\nexport function main(): string {\n  return greet(\"a\");\n}\n",
+        ),
+    ])
+}
+
+fn safe_steps(repo: &Path, target: &str, extra: &[&str]) -> Value {
+    let mut args = vec!["safe-steps", target];
+    args.extend_from_slice(extra);
+    common::run_json(repo, &args)
+}
+
+fn kinds(plan: &Value) -> Vec<&str> {
+    plan["steps"]
+        .as_array()
+        .expect("steps should be an array")
+        .iter()
+        .map(|step| step["kind"].as_str().expect("kind"))
+        .collect()
+}
+
+fn commands(step: &Value) -> Vec<&str> {
+    step["verify"]
+        .as_array()
+        .expect("verify should be an array")
+        .iter()
+        .map(|verification| verification["step"].as_str().expect("command"))
+        .collect()
+}
+
+#[test]
+fn milestone143_rename_plan_orders_definition_re_exports_and_callers() {
+    let repo = indexed_repo();
+    let plan = safe_steps(
+        repo.path(),
+        "query",
+        &["--action", "rename", "--to", "fetch"],
+    );
+    assert_eq!(plan["command"], "safe-steps");
+    assert_eq!(plan["target"], "fetch");
+    assert_eq!(
+        kinds(&plan),
+        [
+            "definition",
+            "re_export",
+            "callers",
+            "callers",
+            "cleanup",
+            "verify"
+        ],
+        "{plan}"
+    );
+    let steps = &plan["steps"];
+    assert_eq!(steps[0]["edits"][0]["file_path"], "src/db.rs");
+    assert_eq!(steps[0]["edits"][0]["lines"], serde_json::json!([1, 6]));
+    let alias = &steps[0]["temporary_aliases"][0];
+    assert_eq!(alias["code"], "pub use self::fetch as query;");
+    assert_eq!(alias["removed_in_step"], 5);
+    assert_eq!(steps[1]["edits"][0]["file_path"], "src/lib.rs");
+    // Production callers come before tests, one file per step.
+    assert_eq!(steps[2]["edits"][0]["file_path"], "src/api.rs");
+    assert_eq!(steps[2]["edits"][0]["lines"], serde_json::json!([1, 4]));
+    assert_eq!(steps[3]["edits"][0]["file_path"], "tests/db_test.rs");
+    assert_eq!(commands(&steps[3]), ["cargo test --test db_test"]);
+    assert!(commands(&steps[5]).contains(&"cargo test"), "{plan}");
+
+    let text = common::run_stdout(&[
+        "safe-steps",
+        "query",
+        "--action",
+        "rename",
+        "--to",
+        "fetch",
+        "--repo",
+        repo_arg(repo.path()),
+    ]);
+    assert!(
+        text.contains("  Step 1: Rename query to fetch in src/db.rs"),
+        "{text}"
+    );
+    assert!(
+        text.contains("temporary alias in src/db.rs (removed in step 5)"),
+        "{text}"
+    );
+    assert!(text.contains("verify: cargo test --test db_test"), "{text}");
+}
+
+#[test]
+fn milestone143_move_plan_re_exports_from_the_old_file_until_callers_move() {
+    let repo = indexed_repo();
+    let plan = safe_steps(
+        repo.path(),
+        "query",
+        &["--action", "move", "--to", "src/storage.rs"],
+    );
+    let steps = &plan["steps"];
+    assert_eq!(
+        steps[0]["title"],
+        "Move query from src/db.rs to src/storage.rs"
+    );
+    assert_eq!(
+        steps[0]["temporary_aliases"][0]["code"],
+        "pub use crate::storage::query;"
+    );
+    // Files importing the symbol only change the import.
+    assert_eq!(steps[2]["edits"][0]["file_path"], "src/api.rs");
+    assert_eq!(steps[2]["edits"][0]["lines"], serde_json::json!([1]));
+    let cleanup = steps
+        .as_array()
+        .expect("steps")
+        .iter()
+        .find(|step| step["kind"] == "cleanup")
+        .expect("cleanup step");
+    assert_eq!(
+        cleanup["edits"][1]["lines"],
+        serde_json::json!([6]),
+        "{plan}"
+    );
+
+    let ts = safe_steps(
+        repo.path(),
+        "greet",
+        &["--action", "move", "--to", "web/util/greeting.ts"],
+    );
+    assert_eq!(
+        ts["steps"][0]["temporary_aliases"][0]["code"],
+        "export { greet } from './util/greeting';"
+    );
+    let renamed = safe_steps(
+        repo.path(),
+        "greet",
+        &["--action", "rename", "--to", "welcome"],
+    );
+    assert_eq!(
+        renamed["steps"][0]["temporary_aliases"][0]["code"],
+        "export { welcome as greet };"
+    );
+}
+
+#[test]
+fn milestone143_extract_and_split_plans_use_check_results() {
+    let repo = indexed_repo();
+    let extract = safe_steps(
+        repo.path(),
+        "warm_cache",
+        &["--action", "extract", "--lines", "23-23"],
+    );
+    assert_eq!(kinds(&extract), ["extract", "delegate", "verify"]);
+    assert_eq!(
+        extract["steps"][0]["edits"][0]["change"],
+        "add warm_cache_extracted(config) returning entries with a copy of lines 23-23"
+    );
+    assert_eq!(
+        extract["steps"][1]["edits"][0]["change"],
+        "replace the lines with let entries = warm_cache_extracted(config)"
+    );
+
+    let split = safe_steps(repo.path(), "src/store.rs", &["--action", "split"]);
+    assert_eq!(split["action"], "split");
+    let group = &split["steps"][0];
+    assert_eq!(group["kind"], "split_group");
+    assert_eq!(
+        group["temporary_aliases"][0]["code"],
+        "pub use self::warm_cache::{Cache, warm_cache};"
+    );
+    let changes = group["edits"]
+        .as_array()
+        .expect("edits")
+        .iter()
+        .map(|edit| edit["change"].as_str().expect("change"))
+        .collect::<Vec<_>>();
+    assert!(
+        changes.contains(&"import normalize from src/store/warm_cache.rs"),
+        "{split}"
+    );
+    assert_eq!(split["steps"][1]["edits"][0]["file_path"], "src/api.rs");
+}
+
+#[test]
+fn milestone143_rename_and_move_require_a_target() {
+    let repo = indexed_repo();
+    let mut cmd = common::repo_scout_cmd();
+    cmd.args([
+        "safe-steps",
+        "query",
+        "--action",
+        "rename",
+        "--repo",
+        repo_arg(repo.path()),
+    ]);
+    let output = cmd.assert().code(2).get_output().stderr.clone();
+    let stderr = String::from_utf8(output).expect("stderr should be utf-8");
+    assert!(
+        stderr.contains("--to <new name> is required for rename"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn milestone143_rename_plan_resolves_one_definition_and_aliases_cross_file_callers() {
+    let repo = common::temp_repo();
+    common::write_file(
+        repo.path(),
+        "Cargo.toml",
+        "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    );
+    common::write_file(
+        repo.path(),
+        "src/lib.rs",
+        "pub mod api;\npub mod store;\n\npub fn compute() -> u32 {\n    2\n}\n",
+    );
+    common::write_file(
+        repo.path(),
+        "src/store.rs",
+        "pub fn compute() -> u32 {\n    3\n}\n\npub fn total() -> u32 {\n    compute() + 1\n}\n",
+    );
+    common::write_file(
+        repo.path(),
+        "src/api.rs",
+        "pub fn handle() -> u32 {\n    crate::compute()\n}\n",
+    );
+    common::run_stdout(&["index", "--repo", repo_arg(repo.path())]);
+
+    let mut cmd = common::repo_scout_cmd();
+    cmd.args([
+        "safe-steps",
+        "compute",
+        "--action",
+        "rename",
+        "--to",
+        "calculate",
+        "--repo",
+        repo_arg(repo.path()),
+    ]);
+    let output = cmd.assert().failure().get_output().stderr.clone();
+    let stderr = String::from_utf8(output).expect("stderr should be utf-8");
+    assert!(
+        stderr.contains("matches 2 definitions (src/lib.rs:4, src/store.rs:1)"),
+        "{stderr}"
+    );
+
+    let plan = safe_steps(
+        repo.path(),
+        "src/lib.rs:4",
+        &["--action", "rename", "--to", "calculate"],
+    );
+    assert_eq!(
+        kinds(&plan),
+        ["definition", "callers", "cleanup", "verify"],
+        "{plan}"
+    );
+    let steps = &plan["steps"];
+    assert_eq!(steps[0]["edits"][0]["file_path"], "src/lib.rs");
+    assert_eq!(steps[0]["edits"][0]["lines"], serde_json::json!([4]));
+    assert_eq!(
+        steps[0]["temporary_aliases"][0]["code"],
+        "pub use self::calculate as compute;"
+    );
+    // The same-named function in src/store.rs and its caller are left alone.
+    assert_eq!(steps[1]["edits"][0]["file_path"], "src/api.rs");
+    assert_eq!(steps[1]["edits"][0]["lines"], serde_json::json!([2]));
+
+    let store = safe_steps(
+        repo.path(),
+        "src/store.rs:1",
+        &["--action", "rename", "--to", "calculate"],
+    );
+    assert_eq!(kinds(&store), ["definition", "verify"], "{store}");
+    assert_eq!(
+        store["steps"][0]["edits"][0]["lines"],
+        serde_json::json!([1, 6])
+    );
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use std::path::Path;
+use std::process::Command;
+
+use common::repo_arg;
+use serde_json::Value;
+
+fn git(repo: &Path, args: &[&str]) {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo)
+        .env("GIT_AUTHOR_NAME", "Ada")
+        .env("GIT_AUTHOR_EMAIL", "ada@example.com")
+        .env("GIT_COMMITTER_NAME", "Ada")
+        .env("GIT_COMMITTER_EMAIL", "ada@example.com")
+        .output()
+        .expect("git should run");
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+fn commit(repo: &Path, files: &[(&str, &str)]) {
+    for (path, content) in files {
+        common::write_file(repo, path, content);
+    }
+    git(repo, &["add", "-A"]);
+    git(repo, &["commit", "-q", "-m", "change"]);
+}
+
+/// The first commit defines `query`, `helper` and `legacy` in `src/db.rs`; the second renames
+/// `query` to `fetch` and moves `helper` into `src/util.rs`, updating every caller.
+fn refactored_repo() -> tempfile::TempDir {
+    let repo = common::temp_repo();
+    git(repo.path(), &["init", "-q"]);
+    commit(
+        repo.path(),
+        &[
+            (".gitignore", ".repo-scout/\n"),
+            ("src/lib.rs", "pub mod api;\npub mod db;\npub mod util;\n"),
+            (
+                "src/db.rs",
+                "pub fn query(id: u32) -> u32 {\n    id + 1\n}\n\npub fn helper() -> u32 {\n    7\n}\n\npub fn legacy() -> u32 {\n    0\n}\n",
+            ),
+            ("src/util.rs", "pub fn noop() {}\n"),
+            (
+                "src/api.rs",
+                "use crate::db::query;\n\npub fn handle() -> u32 {\n    query(2) + crate::db::legacy()\n}\n",
+            ),
+        ],
+    );
+    git(repo.path(), &["tag", "before"]);
+    commit(
+        repo.path(),
+        &[
+            (
+                "src/db.rs",
+                "pub fn fetch(id: u32) -> u32 {\n    id + 1\n}\n\npub fn legacy() -> u32 {\n    0\n}\n",
+            ),
+            (
+                "src/util.rs",
+                "pub fn noop() {}\n\npub fn helper() -> u32 {\n    7\n}\n",
+            ),
+            (
+                "src/api.rs",
+                "use crate::db::fetch;\n\npub fn handle() -> u32 {\n    fetch(2) + crate::db::legacy()\n}\n",
+            ),
+        ],
+    );
+    repo
+}
+
+fn verify_json(repo: &Path, extra: &[&str]) -> Value {
+    let mut args = vec!["verify-refactor"];
+    args.extend_from_slice(extra);
+    common::run_json(repo, &args)
+}
+
+#[test]
+fn milestone144_reports_renamed_and_moved_symbols_between_revisions() {
+    let repo = refactored_repo();
+    let report = verify_json(
+        repo.path(),
+        &["--before", "before", "--after", "HEAD", "--strict"],
+    );
+    assert_eq!(report["command"], "verify-refactor");
+    assert_eq!(report["renamed"][0]["old_symbol"], "query", "{report}");
+    assert_eq!(report["renamed"][0]["new_symbol"], "fetch");
+    assert_eq!(report["renamed"][0]["same_body"], true);
+    assert_eq!(report["moved"][0]["new_symbol"], "helper", "{report}");
+    assert_eq!(report["moved"][0]["old_file"], "src/db.rs");
+    assert_eq!(report["moved"][0]["new_file"], "src/util.rs");
+    assert_eq!(report["removed"], serde_json::json!([]), "{report}");
+    assert_eq!(report["stale_references"], serde_json::json!([]));
+    assert_eq!(report["lost_edges"], serde_json::json!([]), "{report}");
+
+    let text = common::run_stdout(&[
+        "verify-refactor",
+        "--repo",
+        repo_arg(repo.path()),
+        "--before",
+        "before",
+        "--after",
+        "HEAD",
+    ]);
+    assert!(
+        text.contains("renamed query -> fetch (function) in src/db.rs:1"),
+        "{text}"
+    );
+    assert!(text.contains("No discrepancies detected."), "{text}");
+}
+
+#[test]
+fn milestone144_working_tree_stale_callers_fail_strict_mode() {
+    let repo = refactored_repo();
+    // Drop `legacy` without updating its caller.
+    common::write_file(
+        repo.path(),
+        "src/db.rs",
+        "pub fn fetch(id: u32) -> u32 {\n    id + 1\n}\n",
+    );
+    let report = verify_json(repo.path(), &["--before", "HEAD"]);
+    assert_eq!(report["after"], "working-tree");
+    assert_eq!(report["removed"][0]["symbol"], "legacy", "{report}");
+    let stale = report["stale_references"]
+        .as_array()
+        .expect("stale references should be an array");
+    assert!(
+        stale
+            .iter()
+            .any(|reference| reference["symbol"] == "legacy"
+                && reference["file_path"] == "src/api.rs"),
+        "{report}"
+    );
+    assert!(
+        report["lost_edges"]
+            .as_array()
+            .expect("lost edges should be an array")
+            .iter()
+            .any(|edge| edge["from_symbol"] == "handle"
+                && edge["to_symbol"] == "legacy"
+                && edge["target_removed"] == true),
+        "{report}"
+    );
+
+    let mut cmd = common::repo_scout_cmd();
+    cmd.args([
+        "verify-refactor",
+        "--repo",
+        repo_arg(repo.path()),
+        "--before",
+        "HEAD",
+        "--strict",
+    ]);
+    let output = cmd.assert().code(5).get_output().stderr.clone();
+    let stderr = String::from_utf8(output).expect("stderr should be utf-8");
+    assert!(stderr.contains("stale reference"), "{stderr}");
+}
@@ -0,0 +1,292 @@
+mod common;
+
+use std::path::Path;
+use std::process::Command;
+
+use common::repo_arg;
+use serde_json::Value;
+
+const DB_SOURCE: &str = "/// Looks up one row; see query docs.\npub fn query(id: u32) -> u32 {\n    id + 1\n}\n\npub struct Foo;\n\nimpl Foo {\n    pub fn query(&self) -> u32 {\n        query(3)\n    }\n}\n";
+
+const API_SOURCE: &str = "use crate::db::query;\n\npub fn handle() -> u32 {\n    let label = \"query\";\n    query(label.len() as u32) + crate::db::Foo.query()\n}\n";
+
+fn indexed_repo() -> tempfile::TempDir {
+    common::indexed_repo(&[
+        (
+            "Cargo.toml",
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        ),
+        (
+            "src/lib.rs",
+            "pub mod api;\npub mod db;\npub use db::query;\n",
+        ),
+        ("src/db.rs", DB_SOURCE),
+        ("src/api.rs", API_SOURCE),
+        (
+            "tests/db_test.rs",
+            "use demo::query;\n\n#[test]\nfn query_works() {\n    assert_eq!(query(1), 2);\n}\n",
+        ),
+    ])
+}
+
+fn read(repo: &Path, file_path: &str) -> String {
+    std::fs::read_to_string(repo.join(file_path)).expect("file should be readable")
+}
+
+fn rename_json(repo: &Path, selector: &str, extra: &[&str]) -> Value {
+    let mut args = vec!["rename", selector, "--to", "fetch"];
+    args.extend_from_slice(extra);
+    common::run_json(repo, &args)
+}
+
+#[test]
+fn milestone145_patch_mode_emits_a_diff_that_git_applies_without_writing() {
+    let repo = indexed_repo();
+    let patch = common::run_stdout(&[
+        "rename",
+        "src/db.rs:2",
+        "--to",
+        "fetch",
+        "--repo",
+        repo_arg(repo.path()),
+        "--patch",
+    ]);
+    assert!(
+        patch.starts_with("--- a/src/api.rs\n+++ b/src/api.rs\n"),
+        "{patch}"
+    );
+    assert!(patch.contains("-use crate::db::query;\n+use crate::db::fetch;\n"));
+    assert_eq!(read(repo.path(), "src/db.rs"), DB_SOURCE);
+
+    common::write_file(repo.path(), "rename.patch", &patch);
+    let output = Command::new("git")
+        .args(["apply", "rename.patch"])
+        .current_dir(repo.path())
+        .output()
+        .expect("git should run");
+    assert!(
+        output.status.success(),
+        "git apply failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        read(repo.path(), "src/api.rs"),
+        API_SOURCE
+            .replace("use crate::db::query", "use crate::db::fetch")
+            .replace("    query(label", "    fetch(label")
+    );
+    assert_eq!(
+        read(repo.path(), "src/lib.rs"),
+        "pub mod api;\npub mod db;\npub use db::fetch;\n"
+    );
+}
+
+#[test]
+fn milestone145_apply_rewrites_bound_references_and_skips_other_scopes() {
+    let repo = indexed_repo();
+    let report = rename_json(repo.path(), "src/db.rs:2", &["--apply"]);
+    assert_eq!(report["command"], "rename");
+    assert_eq!(report["applied"], true);
+    assert_eq!(report["files_changed"], 4, "{report}");
+    let db = read(repo.path(), "src/db.rs");
+    // The method keeps its name; its call to the free function follows the rename.
+    assert!(db.contains("pub fn fetch(id: u32)"), "{db}");
+    assert!(
+        db.contains("    pub fn query(&self) -> u32 {\n        fetch(3)"),
+        "{db}"
+    );
+    assert!(db.contains("see query docs"), "{db}");
+    let api = read(repo.path(), "src/api.rs");
+    assert!(api.contains("let label = \"query\";"), "{api}");
+    assert!(api.contains("crate::db::Foo.query()"), "{api}");
+    let test = read(repo.path(), "tests/db_test.rs");
+    assert!(test.contains("use demo::fetch;"), "{test}");
+    assert!(test.contains("assert_eq!(fetch(1), 2);"), "{test}");
+    assert!(test.contains("fn query_works()"), "{test}");
+    let skipped = report["skipped"]
+        .as_array()
+        .expect("skipped should be an array");
+    assert!(
+        skipped
+            .iter()
+            .any(|entry| entry["file_path"] == "src/api.rs" && entry["reason"] == "text"),
+        "{report}"
+    );
+}
+
+#[test]
+fn milestone145_include_text_also_rewrites_strings_and_comments() {
+    let repo = indexed_repo();
+    rename_json(repo.path(), "src/db.rs:2", &["--apply", "--include-text"]);
+    assert!(read(repo.path(), "src/db.rs").contains("see fetch docs"));
+    assert!(read(repo.path(), "src/api.rs").contains("let label = \"fetch\";"));
+}
+
+#[test]
+fn milestone145_ambiguous_names_need_a_selector() {
+    let repo = indexed_repo();
+    let mut cmd = common::repo_scout_cmd();
+    cmd.args([
+        "rename",
+        "query",
+        "--to",
+        "fetch",
+        "--repo",
+        repo_arg(repo.path()),
+    ]);
+    let output = cmd.assert().failure().get_output().stderr.clone();
+    let stderr = String::from_utf8(output).expect("stderr should be utf-8");
+    assert!(
+        stderr.contains("matches 2 definitions (src/db.rs:2, src/db.rs:9)"),
+        "{stderr}"
+    );
+
+    let method = rename_json(repo.path(), "Foo::query", &[]);
+    assert_eq!(method["definition"]["container"], "Foo");
+    let files = method["edits"]
+        .as_array()
+        .expect("edits should be an array")
+        .iter()
+        .map(|edit| edit["file_path"].as_str().expect("file path"))
+        .collect::<Vec<_>>();
+    assert!(!files.contains(&"tests/db_test.rs"), "{method}");
+    assert_eq!(read(repo.path(), "src/db.rs"), DB_SOURCE);
+}
+
+#[test]
+fn milestone145_default_output_is_a_summary_and_apply_reindexes() {
+    let repo = indexed_repo();
+    let summary = common::run_stdout(&[
+        "rename",
+        "src/db.rs:2",
+        "--to",
+        "fetch",
+        "--repo",
+        repo_arg(repo.path()),
+    ]);
+    assert!(
+        summary.starts_with("Rename query -> fetch (function at src/db.rs:2)"),
+        "{summary}"
+    );
+    assert!(!summary.contains("--- a/"), "{summary}");
+
+    let applied = common::run_stdout(&[
+        "rename",
+        "src/db.rs:2",
+        "--to",
+        "fetch",
+        "--repo",
+        repo_arg(repo.path()),
+        "--apply",
+    ]);
+    assert!(applied.contains("applied and reindexed"), "{applied}");
+    // The refreshed index already knows the new name.
+    let again = common::run_stdout(&[
+        "rename",
+        "fetch",
+        "--to",
+        "lookup",
+        "--repo",
+        repo_arg(repo.path()),
+    ]);
+    assert!(
+        again.starts_with("Rename fetch -> lookup (function at src/db.rs:2)"),
+        "{again}"
+    );
+}
+
+#[test]
+fn milestone145_apply_refuses_ambiguous_occurrences() {
+    let repo = common::temp_repo();
+    common::write_file(
+        repo.path(),
+        "src/lib.rs",
+        "pub mod store;\n\npub fn compute() -> u32 {\n    2\n}\n",
+    );
+    let store = "pub fn compute() -> u32 {\n    3\n}\n\npub fn total() -> u32 {\n    crate::compute() + compute()\n}\n";
+    common::write_file(repo.path(), "src/store.rs", store);
+    common::run_stdout(&["index", "--repo", repo_arg(repo.path())]);
+
+    let mut cmd = common::repo_scout_cmd();
+    cmd.args([
+        "rename",
+        "src/lib.rs:3",
+        "--to",
+        "calculate",
+        "--repo",
+        repo_arg(repo.path()),
+        "--apply",
+    ]);
+    let output = cmd.assert().failure().get_output().stderr.clone();
+    let stderr = String::from_utf8(output).expect("stderr should be utf-8");
+    assert!(stderr.contains("refusing to apply"), "{stderr}");
+    assert!(stderr.contains("src/store.rs:6:12"), "{stderr}");
+    assert!(read(repo.path(), "src/lib.rs").contains("pub fn compute()"));
+    assert_eq!(read(repo.path(), "src/store.rs"), store);
+}
+
+#[test]
+fn milestone145_path_qualified_sites_bind_to_the_module_they_name() {
+    let repo = common::temp_repo();
+    common::write_file(
+        repo.path(),
+        "src/lib.rs",
+        "mod a;\nmod b;\n\npub use a::compute;\n\npub fn run() -> i32 {\n    a::compute(3) + b::compute(2)\n}\n",
+    );
+    common::write_file(
+        repo.path(),
+        "src/a.rs",
+        "pub fn compute(x: i32) -> i32 {\n    x * 2\n}\n",
+    );
+    common::write_file(
+        repo.path(),
+        "src/b.rs",
+        "pub fn compute(x: i32) -> i32 {\n    x + 1\n}\n",
+    );
+    common::run_stdout(&["index", "--repo", repo_arg(repo.path())]);
+
+    let positions = |payload: &Value, key: &str| {
+        payload[key]
+            .as_array()
+            .expect("list should be an array")
+            .iter()
+            .map(|item| {
+                format!(
+                    "{}:{}:{}",
+                    item["file_path"].as_str().unwrap_or_default(),
+                    item["line"],
+                    item["column"]
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    let payload = rename_json(repo.path(), "src/a.rs:1", &[]);
+    assert_eq!(
+        positions(&payload, "edits"),
+        vec!["src/a.rs:1:8", "src/lib.rs:4:12", "src/lib.rs:7:8"],
+        "{payload}"
+    );
+    assert_eq!(positions(&payload, "skipped"), vec!["src/lib.rs:7:24"]);
+    assert_eq!(payload["skipped"][0]["reason"], "other_definition");
+
+    let payload = rename_json(repo.path(), "src/b.rs:1", &[]);
+    assert_eq!(
+        positions(&payload, "edits"),
+        vec!["src/b.rs:1:8", "src/lib.rs:7:24"],
+        "{payload}"
+    );
+
+    common::run_stdout(&[
+        "rename",
+        "src/a.rs:1",
+        "--to",
+        "double",
+        "--repo",
+        repo_arg(repo.path()),
+        "--apply",
+    ]);
+    assert_eq!(
+        read(repo.path(), "src/lib.rs"),
+        "mod a;\nmod b;\n\npub use a::double;\n\npub fn run() -> i32 {\n    a::double(3) + b::compute(2)\n}\n"
+    );
+}
@@ -130,7 +130,7 @@ fn milestone14_rust_behavior_unchanged_through_adapter() {
     let indexer_source =
         fs::read_to_string(root.join("src/indexer/mod.rs")).expect("indexer module should load");
     assert!(indexer_source.contains("RustLanguageAdapter"));
-    assert!(indexer_source.contains("extract("));
+    assert!(indexer_source.contains("extract_in_workspace("));
 
     let repo = common::temp_repo();
     common::write_file(
@@ -171,7 +171,7 @@ fn milestone14_schema_language_metadata_migration() {
     let db_path = build_v2_index(repo.path());
 
     let status_out = run_stdout(&["status", "--repo", repo.path().to_str().unwrap()]);
-    assert!(status_out.contains("schema_version: 4"));
+    assert!(status_out.contains("schema_version: 11"));
 
     let connection = Connection::open(db_path).expect("db should remain readable");
     assert!(table_has_column(&connection, "symbols_v2", "language"));
@@ -52,5 +52,5 @@ fn outline_json_output() {
     ]);
     let payload: Value = serde_json::from_str(&json_out).expect("valid json");
     assert_eq!(payload["command"], "outline");
-    assert!(payload["results"].as_array().unwrap().len() > 0);
+    assert!(!payload["results"].as_array().unwrap().is_empty());
 }
@@ -92,8 +92,8 @@ fn milestone90_v3_upgrades_to_v4_with_new_columns() {
     // Running status should trigger migration
     let status = run_stdout(&["status", "--repo", repo.path().to_str().unwrap()]);
     assert!(
-        status.contains("schema_version: 4"),
-        "store bootstrap should migrate v3 dbs to schema v4, got: {status}"
+        status.contains("schema_version: 11"),
+        "store bootstrap should migrate v3 dbs to the current schema, got: {status}"
     );
 
     // Verify new columns exist
@@ -146,8 +146,8 @@ fn milestone90_v4_migration_is_idempotent() {
 
     let first = run_stdout(&["status", "--repo", repo.path().to_str().unwrap()]);
     let second = run_stdout(&["status", "--repo", repo.path().to_str().unwrap()]);
-    assert!(first.contains("schema_version: 4"));
-    assert!(second.contains("schema_version: 4"));
+    assert!(first.contains("schema_version: 11"));
+    assert!(second.contains("schema_version: 11"));
 
     let connection = Connection::open(db_path).expect("db should remain readable");
     let version: String = connection
@@ -157,7 +157,7 @@ fn milestone90_v4_migration_is_idempotent() {
             |row| row.get(0),
         )
         .expect("meta schema_version should exist");
-    assert_eq!(version, "4");
+    assert_eq!(version, "11");
 }
 
 #[test]
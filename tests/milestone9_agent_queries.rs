@@ -122,7 +122,8 @@ fn milestone9_context_budgeted_terminal() {
 
     assert!(out.contains("command: context"));
     assert!(out.contains("budget: 200"));
-    assert!(out.contains("results: 1"));
+    // 200 tokens fit both `start_engine` and its caller `run`.
+    assert!(out.contains("results: 2"));
     assert!(out.contains("why:"));
 }
 